@@ -0,0 +1,226 @@
+use futures::StreamExt;
+use indexlake::{
+    ILError, ILResult,
+    catalog::{Catalog, CatalogDatabase, IsolationLevel, RowStream, Transaction},
+    catalog::{CatalogDataType, CatalogSchemaRef, Row, Scalar},
+};
+use log::debug;
+use std::sync::Arc;
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+/// Catalog backed by a DuckDB database file.
+///
+/// A DuckDB file can only be opened by one database instance at a time, so the catalog keeps a
+/// single instance and hands out a new connection to it for every query and transaction.
+/// Transactions are serialized within the process the same way as the SQLite catalog, as ids
+/// are allocated with `MAX(id) + 1` and concurrent writers would conflict.
+///
+/// Links the system libduckdb, enable the `bundled` feature to compile DuckDB from source
+/// instead.
+#[derive(Debug)]
+pub struct DuckDbCatalog {
+    conn: std::sync::Mutex<duckdb::Connection>,
+    transaction_lock: Arc<Mutex<()>>,
+}
+
+impl DuckDbCatalog {
+    /// Opens the database file, creating it if it does not exist. Run [`Catalog::migrate`] to
+    /// create the catalog tables in a new file.
+    pub fn try_new(path: impl Into<String>) -> ILResult<Self> {
+        let conn = duckdb::Connection::open(path.into()).map_err(duckdb_error)?;
+        Ok(DuckDbCatalog {
+            conn: std::sync::Mutex::new(conn),
+            transaction_lock: Arc::new(Mutex::new(())),
+        })
+    }
+
+    fn connect(&self) -> ILResult<duckdb::Connection> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| ILError::CatalogError(e.to_string()))?;
+        conn.try_clone().map_err(duckdb_error)
+    }
+}
+
+#[async_trait::async_trait]
+impl Catalog for DuckDbCatalog {
+    fn database(&self) -> CatalogDatabase {
+        CatalogDatabase::DuckDb
+    }
+
+    async fn query(&self, sql: &str, schema: CatalogSchemaRef) -> ILResult<RowStream<'static>> {
+        debug!("duckdb query: {sql}");
+        let conn = self.connect()?;
+        let rows = query_rows(&conn, sql, &schema)?;
+        Ok(Box::pin(futures::stream::iter(rows).map(Ok)))
+    }
+
+    async fn transaction(&self) -> ILResult<Box<dyn Transaction>> {
+        self.begin_transaction(IsolationLevel::Serializable).await
+    }
+
+    async fn begin_transaction(&self, level: IsolationLevel) -> ILResult<Box<dyn Transaction>> {
+        check_isolation_level(level)?;
+        let guard = self.transaction_lock.clone().lock_owned().await;
+        let conn = self.connect()?;
+        conn.execute_batch("BEGIN TRANSACTION")
+            .map_err(duckdb_error)?;
+        Ok(Box::new(DuckDbTransaction {
+            conn,
+            done: false,
+            guard: Some(guard),
+        }))
+    }
+}
+
+#[derive(Debug)]
+pub struct DuckDbTransaction {
+    conn: duckdb::Connection,
+    done: bool,
+    // Released once the transaction is committed or rolled back
+    guard: Option<OwnedMutexGuard<()>>,
+}
+
+impl DuckDbTransaction {
+    fn check_not_done(&self) -> ILResult<()> {
+        if self.done {
+            return Err(ILError::CatalogError(
+                "Transaction already committed or rolled back".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl Transaction for DuckDbTransaction {
+    async fn query(&mut self, sql: &str, schema: CatalogSchemaRef) -> ILResult<RowStream> {
+        debug!("duckdb txn query: {sql}");
+        self.check_not_done()?;
+        let rows = query_rows(&self.conn, sql, &schema)?;
+        Ok(Box::pin(futures::stream::iter(rows).map(Ok)))
+    }
+
+    async fn execute(&mut self, sql: &str) -> ILResult<usize> {
+        debug!("duckdb txn execute: {sql}");
+        self.check_not_done()?;
+        self.conn.execute(sql, []).map_err(duckdb_error)
+    }
+
+    async fn execute_batch(&mut self, sqls: &[String]) -> ILResult<()> {
+        debug!("duckdb txn execute batch: {:?}", sqls);
+        self.check_not_done()?;
+        self.conn
+            .execute_batch(sqls.join(";").as_str())
+            .map_err(duckdb_error)
+    }
+
+    async fn commit(&mut self) -> ILResult<()> {
+        debug!("duckdb txn commit");
+        self.check_not_done()?;
+        self.conn.execute_batch("COMMIT").map_err(duckdb_error)?;
+        self.done = true;
+        self.guard = None;
+        Ok(())
+    }
+
+    async fn rollback(&mut self) -> ILResult<()> {
+        debug!("duckdb txn rollback");
+        self.check_not_done()?;
+        self.conn.execute_batch("ROLLBACK").map_err(duckdb_error)?;
+        self.done = true;
+        self.guard = None;
+        Ok(())
+    }
+}
+
+impl Drop for DuckDbTransaction {
+    fn drop(&mut self) {
+        if self.done {
+            return;
+        }
+        // A failed statement may already have aborted the transaction
+        let _ = self.conn.execute_batch("ROLLBACK");
+    }
+}
+
+fn duckdb_error(e: duckdb::Error) -> ILError {
+    ILError::CatalogError(e.to_string())
+}
+
+/// Transactions of the catalog are serialized, so only serializable isolation can be honored.
+pub fn check_isolation_level(level: IsolationLevel) -> ILResult<()> {
+    match level {
+        IsolationLevel::Serializable => Ok(()),
+        IsolationLevel::ReadCommitted | IsolationLevel::RepeatableRead => {
+            Err(ILError::NotSupported(format!(
+                "duckdb only supports SERIALIZABLE isolation level, got {level}"
+            )))
+        }
+    }
+}
+
+fn query_rows(
+    conn: &duckdb::Connection,
+    sql: &str,
+    schema: &CatalogSchemaRef,
+) -> ILResult<Vec<Row>> {
+    let mut stmt = conn.prepare(sql).map_err(duckdb_error)?;
+    let mut duckdb_rows = stmt.query([]).map_err(duckdb_error)?;
+
+    let mut rows: Vec<Row> = Vec::new();
+    while let Some(duckdb_row) = duckdb_rows.next().map_err(duckdb_error)? {
+        let row = duckdb_row_to_row(duckdb_row, schema)?;
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+pub fn duckdb_row_to_row(duckdb_row: &duckdb::Row, schema: &CatalogSchemaRef) -> ILResult<Row> {
+    let mut row_values = Vec::new();
+    for (idx, field) in schema.columns.iter().enumerate() {
+        let scalar = match field.data_type {
+            CatalogDataType::Int16 => {
+                let v: Option<i16> = duckdb_row.get(idx).map_err(duckdb_error)?;
+                Scalar::Int16(v)
+            }
+            CatalogDataType::Int32 => {
+                let v: Option<i32> = duckdb_row.get(idx).map_err(duckdb_error)?;
+                Scalar::Int32(v)
+            }
+            CatalogDataType::Int64 => {
+                let v: Option<i64> = duckdb_row.get(idx).map_err(duckdb_error)?;
+                Scalar::Int64(v)
+            }
+            CatalogDataType::Float32 => {
+                let v: Option<f32> = duckdb_row.get(idx).map_err(duckdb_error)?;
+                Scalar::Float32(v)
+            }
+            CatalogDataType::Float64 => {
+                let v: Option<f64> = duckdb_row.get(idx).map_err(duckdb_error)?;
+                Scalar::Float64(v)
+            }
+            CatalogDataType::Utf8 => {
+                let v: Option<String> = duckdb_row.get(idx).map_err(duckdb_error)?;
+                Scalar::Utf8(v)
+            }
+            CatalogDataType::Binary => {
+                let v: Option<Vec<u8>> = duckdb_row.get(idx).map_err(duckdb_error)?;
+                Scalar::Binary(v)
+            }
+            CatalogDataType::Boolean => {
+                let v: Option<bool> = duckdb_row.get(idx).map_err(duckdb_error)?;
+                Scalar::Boolean(v)
+            }
+        };
+        if !field.nullable && scalar.is_null() {
+            return Err(ILError::CatalogError(format!(
+                "column {} is not nullable but got null value",
+                field.name
+            )));
+        }
+        row_values.push(scalar);
+    }
+    Ok(Row::new(schema.clone(), row_values))
+}
@@ -0,0 +1,183 @@
+use std::sync::Arc;
+
+use futures::StreamExt;
+use indexlake::{
+    ILError, ILResult,
+    catalog::{
+        CATALOG_VERSION, Catalog, CatalogDatabase, IsolationLevel, RowStream, Transaction,
+        catalog_migrations,
+    },
+    catalog::{CatalogSchemaRef, Row, Scalar},
+};
+use indexlake_catalog_sqlite::{check_isolation_level, sqlite_insert_rows, sqlite_row_to_row};
+use log::debug;
+use rusqlite::Connection;
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+/// In-process catalog that keeps all metadata in memory, intended for tests and ephemeral
+/// workloads.
+///
+/// Metadata lives in an in-memory SQLite database guarded by a mutex, so it shares the SQL
+/// dialect and transactional semantics of the SQLite catalog. A transaction holds the lock
+/// from begin until commit or rollback, so transactions are serialized.
+#[derive(Debug, Clone)]
+pub struct MemoryCatalog {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl MemoryCatalog {
+    pub fn new() -> Self {
+        let conn = Connection::open_in_memory().expect("failed to open in-memory sqlite");
+        for migration in catalog_migrations(CatalogDatabase::Sqlite) {
+            conn.execute_batch(migration.sql)
+                .expect("failed to init memory catalog");
+        }
+        conn.execute_batch(&format!(
+            "CREATE TABLE indexlake_catalog_version (version BIGINT NOT NULL);
+            INSERT INTO indexlake_catalog_version (version) VALUES ({CATALOG_VERSION})"
+        ))
+        .expect("failed to init memory catalog");
+        Self {
+            conn: Arc::new(Mutex::new(conn)),
+        }
+    }
+
+    /// Returns an independent copy of the committed catalog state.
+    pub async fn snapshot(&self) -> ILResult<MemoryCatalog> {
+        let src = self.conn.lock().await;
+        let mut dst =
+            Connection::open_in_memory().map_err(|e| ILError::CatalogError(e.to_string()))?;
+        rusqlite::backup::Backup::new(&src, &mut dst)
+            .and_then(|backup| backup.run_to_completion(128, std::time::Duration::ZERO, None))
+            .map_err(|e| ILError::CatalogError(e.to_string()))?;
+        Ok(MemoryCatalog {
+            conn: Arc::new(Mutex::new(dst)),
+        })
+    }
+}
+
+impl Default for MemoryCatalog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl Catalog for MemoryCatalog {
+    fn database(&self) -> CatalogDatabase {
+        CatalogDatabase::Sqlite
+    }
+
+    async fn query(&self, sql: &str, schema: CatalogSchemaRef) -> ILResult<RowStream<'static>> {
+        debug!("memory query: {sql}");
+        let conn = self.conn.lock().await;
+        let rows = query_rows(&conn, sql, &schema)?;
+        Ok(Box::pin(futures::stream::iter(rows).map(Ok)))
+    }
+
+    async fn transaction(&self) -> ILResult<Box<dyn Transaction>> {
+        let conn = self.conn.clone().lock_owned().await;
+        conn.execute_batch("BEGIN DEFERRED")
+            .map_err(|e| ILError::CatalogError(e.to_string()))?;
+        Ok(Box::new(MemoryTransaction { conn: Some(conn) }))
+    }
+
+    async fn begin_transaction(&self, level: IsolationLevel) -> ILResult<Box<dyn Transaction>> {
+        // Transactions are serialized by the connection lock
+        check_isolation_level(level)?;
+        self.transaction().await
+    }
+}
+
+#[derive(Debug)]
+pub struct MemoryTransaction {
+    // Released once the transaction is committed or rolled back
+    conn: Option<OwnedMutexGuard<Connection>>,
+}
+
+impl MemoryTransaction {
+    fn conn(&self) -> ILResult<&Connection> {
+        self.conn.as_deref().ok_or_else(|| {
+            ILError::CatalogError("Transaction already committed or rolled back".to_string())
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Transaction for MemoryTransaction {
+    async fn query(&mut self, sql: &str, schema: CatalogSchemaRef) -> ILResult<RowStream> {
+        debug!("memory txn query: {sql}");
+        let rows = query_rows(self.conn()?, sql, &schema)?;
+        Ok(Box::pin(futures::stream::iter(rows).map(Ok)))
+    }
+
+    async fn execute(&mut self, sql: &str) -> ILResult<usize> {
+        debug!("memory txn execute: {sql}");
+        self.conn()?
+            .execute(sql, [])
+            .map_err(|e| ILError::CatalogError(e.to_string()))
+    }
+
+    async fn execute_batch(&mut self, sqls: &[String]) -> ILResult<()> {
+        debug!("memory txn execute batch: {:?}", sqls);
+        self.conn()?
+            .execute_batch(sqls.join(";").as_str())
+            .map_err(|e| ILError::CatalogError(e.to_string()))
+    }
+
+    async fn insert_rows(
+        &mut self,
+        _database: CatalogDatabase,
+        table_name: &str,
+        columns: &[String],
+        rows: &[Vec<Scalar>],
+        _batch_size: usize,
+    ) -> ILResult<usize> {
+        debug!("memory txn insert {} rows into {table_name}", rows.len());
+        sqlite_insert_rows(self.conn()?, table_name, columns, rows)
+    }
+
+    async fn commit(&mut self) -> ILResult<()> {
+        debug!("memory txn commit");
+        self.conn()?
+            .execute_batch("COMMIT")
+            .map_err(|e| ILError::CatalogError(e.to_string()))?;
+        self.conn = None;
+        Ok(())
+    }
+
+    async fn rollback(&mut self) -> ILResult<()> {
+        debug!("memory txn rollback");
+        self.conn()?
+            .execute_batch("ROLLBACK")
+            .map_err(|e| ILError::CatalogError(e.to_string()))?;
+        self.conn = None;
+        Ok(())
+    }
+}
+
+impl Drop for MemoryTransaction {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            conn.execute_batch("ROLLBACK").unwrap();
+        }
+    }
+}
+
+fn query_rows(conn: &Connection, sql: &str, schema: &CatalogSchemaRef) -> ILResult<Vec<Row>> {
+    let mut stmt = conn
+        .prepare(sql)
+        .map_err(|e| ILError::CatalogError(e.to_string()))?;
+    let mut sqlite_rows = stmt
+        .query([])
+        .map_err(|e| ILError::CatalogError(e.to_string()))?;
+
+    let mut rows: Vec<Row> = Vec::new();
+    while let Some(sqlite_row) = sqlite_rows
+        .next()
+        .map_err(|e| ILError::CatalogError(e.to_string()))?
+    {
+        rows.push(sqlite_row_to_row(sqlite_row, schema)?);
+    }
+    Ok(rows)
+}
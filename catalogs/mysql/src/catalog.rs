@@ -0,0 +1,232 @@
+use futures::StreamExt;
+use indexlake::{
+    ILError, ILResult,
+    catalog::{Catalog, CatalogDatabase, IsolationLevel, RowStream, Transaction},
+    catalog::{CatalogDataType, CatalogSchemaRef, Row, Scalar},
+};
+use log::debug;
+use mysql_async::{Conn, OptsBuilder, Pool, prelude::FromValue, prelude::Queryable};
+
+/// Catalog backed by MySQL 8 (InnoDB). MariaDB 10.6+ speaks the same protocol and dialect
+/// and is supported as well.
+///
+/// Transactions run under the server default REPEATABLE READ isolation unless begun with
+/// [`Catalog::begin_transaction`]. Id allocation uses
+/// `SELECT ... FOR UPDATE` locking reads so concurrent writers are serialized until commit.
+///
+/// Note that MySQL implicitly commits the current transaction when executing DDL statements
+/// such as `CREATE TABLE` and `DROP TABLE`, so metadata written before a DDL statement in the
+/// same transaction can not be rolled back.
+#[derive(Debug, Clone)]
+pub struct MySqlCatalog {
+    pool: Pool,
+}
+
+impl MySqlCatalog {
+    pub async fn try_new(
+        host: &str,
+        port: u16,
+        user: &str,
+        password: &str,
+        database: Option<&str>,
+    ) -> ILResult<Self> {
+        let opts = OptsBuilder::default()
+            .ip_or_hostname(host)
+            .tcp_port(port)
+            .user(Some(user))
+            .pass(Some(password))
+            .db_name(database);
+        let pool = Pool::new(opts);
+        // Make sure the server is reachable
+        let conn = pool
+            .get_conn()
+            .await
+            .map_err(|e| ILError::CatalogError(e.to_string()))?;
+        drop(conn);
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait::async_trait]
+impl Catalog for MySqlCatalog {
+    fn database(&self) -> CatalogDatabase {
+        CatalogDatabase::MySql
+    }
+
+    async fn query(&self, sql: &str, schema: CatalogSchemaRef) -> ILResult<RowStream<'static>> {
+        debug!("mysql query: {sql}");
+        let mut conn = self
+            .pool
+            .get_conn()
+            .await
+            .map_err(|e| ILError::CatalogError(e.to_string()))?;
+        let rows = query_rows(&mut conn, sql, &schema).await?;
+        Ok(Box::pin(futures::stream::iter(rows).map(Ok)))
+    }
+
+    async fn transaction(&self) -> ILResult<Box<dyn Transaction>> {
+        let mut conn = self
+            .pool
+            .get_conn()
+            .await
+            .map_err(|e| ILError::CatalogError(e.to_string()))?;
+        conn.query_drop("START TRANSACTION")
+            .await
+            .map_err(|e| ILError::CatalogError(e.to_string()))?;
+        Ok(Box::new(MySqlTransaction { conn, done: false }))
+    }
+
+    async fn begin_transaction(&self, level: IsolationLevel) -> ILResult<Box<dyn Transaction>> {
+        let mut conn = self
+            .pool
+            .get_conn()
+            .await
+            .map_err(|e| ILError::CatalogError(e.to_string()))?;
+        // Applies to the next transaction of this session only
+        conn.query_drop(format!("SET TRANSACTION ISOLATION LEVEL {level}"))
+            .await
+            .map_err(|e| ILError::CatalogError(e.to_string()))?;
+        conn.query_drop("START TRANSACTION")
+            .await
+            .map_err(|e| ILError::CatalogError(e.to_string()))?;
+        Ok(Box::new(MySqlTransaction { conn, done: false }))
+    }
+}
+
+#[derive(Debug)]
+pub struct MySqlTransaction {
+    conn: Conn,
+    done: bool,
+}
+
+#[async_trait::async_trait]
+impl Transaction for MySqlTransaction {
+    async fn query(&mut self, sql: &str, schema: CatalogSchemaRef) -> ILResult<RowStream> {
+        debug!("mysql txn query: {sql}");
+        if self.done {
+            return Err(ILError::CatalogError(
+                "Transaction already committed or rolled back".to_string(),
+            ));
+        }
+        let rows = query_rows(&mut self.conn, sql, &schema).await?;
+        Ok(Box::pin(futures::stream::iter(rows).map(Ok)))
+    }
+
+    async fn execute(&mut self, sql: &str) -> ILResult<usize> {
+        debug!("mysql txn execute: {sql}");
+        if self.done {
+            return Err(ILError::CatalogError(
+                "Transaction already committed or rolled back".to_string(),
+            ));
+        }
+        self.conn
+            .query_drop(sql)
+            .await
+            .map_err(|e| ILError::CatalogError(e.to_string()))?;
+        Ok(self.conn.affected_rows() as usize)
+    }
+
+    async fn execute_batch(&mut self, sqls: &[String]) -> ILResult<()> {
+        debug!("mysql txn execute batch: {:?}", sqls);
+        if self.done {
+            return Err(ILError::CatalogError(
+                "Transaction already committed or rolled back".to_string(),
+            ));
+        }
+        for sql in sqls {
+            self.conn
+                .query_drop(sql)
+                .await
+                .map_err(|e| ILError::CatalogError(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    async fn commit(&mut self) -> ILResult<()> {
+        debug!("mysql txn commit");
+        if self.done {
+            return Err(ILError::CatalogError(
+                "Transaction already committed or rolled back".to_string(),
+            ));
+        }
+        self.conn
+            .query_drop("COMMIT")
+            .await
+            .map_err(|e| ILError::CatalogError(e.to_string()))?;
+        self.done = true;
+        Ok(())
+    }
+
+    async fn rollback(&mut self) -> ILResult<()> {
+        debug!("mysql txn rollback");
+        if self.done {
+            return Err(ILError::CatalogError(
+                "Transaction already committed or rolled back".to_string(),
+            ));
+        }
+        self.conn
+            .query_drop("ROLLBACK")
+            .await
+            .map_err(|e| ILError::CatalogError(e.to_string()))?;
+        self.done = true;
+        Ok(())
+    }
+}
+
+impl Drop for MySqlTransaction {
+    fn drop(&mut self) {
+        if self.done {
+            return;
+        }
+        tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(async move {
+                self.conn
+                    .query_drop("ROLLBACK")
+                    .await
+                    .expect("rollback failed");
+            });
+        });
+    }
+}
+
+async fn query_rows(conn: &mut Conn, sql: &str, schema: &CatalogSchemaRef) -> ILResult<Vec<Row>> {
+    let mysql_rows: Vec<mysql_async::Row> = conn
+        .query(sql)
+        .await
+        .map_err(|e| ILError::CatalogError(e.to_string()))?;
+    mysql_rows
+        .iter()
+        .map(|mysql_row| mysql_row_to_row(mysql_row, schema))
+        .collect()
+}
+
+fn get_value<T: FromValue>(mysql_row: &mysql_async::Row, idx: usize) -> ILResult<Option<T>> {
+    mysql_row
+        .get_opt::<Option<T>, _>(idx)
+        .ok_or_else(|| ILError::CatalogError(format!("column index {idx} out of range")))?
+        .map_err(|e| ILError::CatalogError(e.to_string()))
+}
+
+fn mysql_row_to_row(mysql_row: &mysql_async::Row, schema: &CatalogSchemaRef) -> ILResult<Row> {
+    let mut values = Vec::new();
+    for (idx, field) in schema.columns.iter().enumerate() {
+        let scalar = match field.data_type {
+            CatalogDataType::Int16 => Scalar::Int16(get_value(mysql_row, idx)?),
+            CatalogDataType::Int32 => Scalar::Int32(get_value(mysql_row, idx)?),
+            CatalogDataType::Int64 => Scalar::Int64(get_value(mysql_row, idx)?),
+            CatalogDataType::Float32 => Scalar::Float32(get_value(mysql_row, idx)?),
+            CatalogDataType::Float64 => Scalar::Float64(get_value(mysql_row, idx)?),
+            CatalogDataType::Utf8 => Scalar::Utf8(get_value(mysql_row, idx)?),
+            CatalogDataType::Binary => Scalar::Binary(get_value(mysql_row, idx)?),
+            CatalogDataType::Boolean => Scalar::Boolean(get_value(mysql_row, idx)?),
+        };
+        if !field.nullable && scalar.is_null() {
+            return Err(ILError::CatalogError(format!(
+                "column {} is not nullable but got null value",
+                field.name
+            )));
+        }
+        values.push(scalar);
+    }
+    Ok(Row::new(schema.clone(), values))
+}
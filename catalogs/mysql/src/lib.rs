@@ -0,0 +1,3 @@
+mod catalog;
+
+pub use catalog::*;
@@ -1,19 +1,36 @@
-use bb8::Pool;
-use bb8_postgres::{PostgresConnectionManager, tokio_postgres::NoTls};
+use bb8::{Pool, PooledConnection, RunError};
+use bb8_postgres::{
+    PostgresConnectionManager,
+    tokio_postgres::{self, config::SslMode, error::SqlState, types::ToSql},
+};
 use futures::StreamExt;
 use indexlake::{
     ILError, ILResult,
-    catalog::{Catalog, CatalogDatabase, RowStream, Transaction},
+    catalog::{Catalog, CatalogDatabase, IsolationLevel, PoolStatus, RetryPolicy},
     catalog::{CatalogDataType, CatalogSchemaRef, Row, Scalar},
+    catalog::{RowStream, Transaction},
 };
-use log::debug;
+use log::{debug, warn};
+use openssl::ssl::{SslConnector, SslFiletype, SslMethod, SslVerifyMode};
+use postgres_openssl::MakeTlsConnector;
+
+use crate::{PostgresCatalogBuilder, PostgresCatalogConfig, PostgresSslMode};
+
+type PostgresConnection = PooledConnection<'static, PostgresConnectionManager<MakeTlsConnector>>;
 
 #[derive(Debug, Clone)]
 pub struct PostgresCatalog {
-    pool: Pool<PostgresConnectionManager<NoTls>>,
+    pool: Pool<PostgresConnectionManager<MakeTlsConnector>>,
+    isolation_level: IsolationLevel,
+    transaction_retry: RetryPolicy,
+    read_only: bool,
 }
 
 impl PostgresCatalog {
+    pub fn builder() -> PostgresCatalogBuilder {
+        PostgresCatalogBuilder::new()
+    }
+
     pub async fn try_new(
         host: &str,
         port: u16,
@@ -21,20 +38,150 @@ impl PostgresCatalog {
         password: &str,
         dbname: Option<&str>,
     ) -> ILResult<Self> {
-        let mut config = bb8_postgres::tokio_postgres::config::Config::new();
-        config.host(host).port(port).user(user).password(password);
-        if let Some(dbname) = dbname {
-            config.dbname(dbname);
+        let config = PostgresCatalogConfig::new(host, port, user, password)
+            .with_dbname(dbname.map(|s| s.to_string()));
+        Self::try_new_with_config(config).await
+    }
+
+    pub async fn try_new_with_config(config: PostgresCatalogConfig) -> ILResult<Self> {
+        let mut pg_config = bb8_postgres::tokio_postgres::config::Config::new();
+        pg_config
+            .host(&config.host)
+            .port(config.port)
+            .user(&config.user)
+            .password(&config.password)
+            .connect_timeout(config.connect_timeout);
+        if let Some(dbname) = &config.dbname {
+            pg_config.dbname(dbname);
+        }
+        let mut options = Vec::new();
+        if let Some(schema) = &config.schema {
+            check_schema_name(schema)?;
+            options.push(format!("-c search_path={schema}"));
+        }
+        if config.read_only {
+            // Enforced by the server as well, in case writes bypass the read-only check
+            options.push("-c default_transaction_read_only=on".to_string());
+        }
+        if !options.is_empty() {
+            pg_config.options(options.join(" "));
         }
-        let manager = PostgresConnectionManager::new(config, NoTls);
+        pg_config.ssl_mode(match config.ssl_mode {
+            PostgresSslMode::Disable => SslMode::Disable,
+            PostgresSslMode::Prefer => SslMode::Prefer,
+            PostgresSslMode::Require | PostgresSslMode::VerifyCa | PostgresSslMode::VerifyFull => {
+                SslMode::Require
+            }
+        });
+        let manager = PostgresConnectionManager::new(pg_config, build_tls_connector(&config)?);
         let pool = Pool::builder()
+            .max_size(config.max_connections)
+            .min_idle(config.min_connections)
+            .connection_timeout(config.connect_timeout)
+            .idle_timeout(config.idle_timeout)
+            .test_on_check_out(config.test_on_check_out)
             .build(manager)
             .await
             .map_err(|e| ILError::CatalogError(e.to_string()))?;
-        Ok(Self { pool })
+        if let Some(schema) = &config.schema
+            && !config.read_only
+        {
+            let conn = pool.get().await.map_err(|e| match e {
+                RunError::TimedOut => {
+                    ILError::CatalogPoolExhausted("timed out creating catalog schema".to_string())
+                }
+                RunError::User(e) => pg_error(e),
+            })?;
+            conn.batch_execute(&format!("CREATE SCHEMA IF NOT EXISTS {schema}"))
+                .await
+                .map_err(pg_error)?;
+        }
+        Ok(Self {
+            pool,
+            isolation_level: config.isolation_level,
+            transaction_retry: config.transaction_retry,
+            read_only: config.read_only,
+        })
+    }
+
+    async fn get_conn(&self) -> ILResult<PostgresConnection> {
+        self.pool.get_owned().await.map_err(|e| match e {
+            RunError::TimedOut => ILError::CatalogPoolExhausted(format!(
+                "timed out waiting for a postgres connection, pool size: {}",
+                self.pool.state().connections
+            )),
+            RunError::User(e) => pg_error(e),
+        })
+    }
+
+    async fn query_raw(&self, sql: &str) -> ILResult<tokio_postgres::RowStream> {
+        let conn = self.get_conn().await?;
+        conn.query_raw(sql, Vec::<String>::new())
+            .await
+            .map_err(pg_error)
+    }
+
+    async fn start_transaction(&self, level: IsolationLevel) -> ILResult<PostgresConnection> {
+        let conn = self.get_conn().await?;
+        conn.batch_execute(&format!("START TRANSACTION ISOLATION LEVEL {level}"))
+            .await
+            .map_err(pg_error)?;
+        Ok(conn)
     }
 }
 
+/// The schema name is passed in the connection options and used unquoted, so only plain
+/// identifiers are accepted.
+fn check_schema_name(schema: &str) -> ILResult<()> {
+    let valid = schema
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && schema
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(ILError::InvalidInput(format!(
+            "Invalid postgres schema name {schema}, expected letters, digits and underscores"
+        )));
+    }
+    Ok(())
+}
+
+fn build_tls_connector(config: &PostgresCatalogConfig) -> ILResult<MakeTlsConnector> {
+    let map_err = |e: openssl::error::ErrorStack| ILError::CatalogError(e.to_string());
+    let mut builder = SslConnector::builder(SslMethod::tls()).map_err(map_err)?;
+    if let Some(root_cert) = &config.ssl_root_cert {
+        builder.set_ca_file(root_cert).map_err(map_err)?;
+    }
+    if let Some(client_cert) = &config.ssl_client_cert {
+        builder
+            .set_certificate_chain_file(client_cert)
+            .map_err(map_err)?;
+    }
+    if let Some(client_key) = &config.ssl_client_key {
+        builder
+            .set_private_key_file(client_key, SslFiletype::PEM)
+            .map_err(map_err)?;
+    }
+    let verify_mode = match config.ssl_mode {
+        PostgresSslMode::Disable | PostgresSslMode::Prefer | PostgresSslMode::Require => {
+            SslVerifyMode::NONE
+        }
+        PostgresSslMode::VerifyCa | PostgresSslMode::VerifyFull => SslVerifyMode::PEER,
+    };
+    builder.set_verify(verify_mode);
+
+    let mut connector = MakeTlsConnector::new(builder.build());
+    if config.ssl_mode != PostgresSslMode::VerifyFull {
+        connector.set_callback(|connect_config, _| {
+            connect_config.set_verify_hostname(false);
+            Ok(())
+        });
+    }
+    Ok(connector)
+}
+
 #[async_trait::async_trait]
 impl Catalog for PostgresCatalog {
     fn database(&self) -> CatalogDatabase {
@@ -43,39 +190,58 @@ impl Catalog for PostgresCatalog {
 
     async fn query(&self, sql: &str, schema: CatalogSchemaRef) -> ILResult<RowStream<'static>> {
         debug!("postgres query: {sql}");
-        let conn = self
-            .pool
-            .get_owned()
-            .await
-            .map_err(|e| ILError::CatalogError(e.to_string()))?;
-        let pg_row_stream = conn
-            .query_raw(sql, Vec::<String>::new())
-            .await
-            .map_err(|e| ILError::CatalogError(e.to_string()))?;
+        // Connections broken by e.g. a server restart are discarded by the pool, so retry
+        // once on a new connection
+        let pg_row_stream = match self.query_raw(sql).await {
+            Err(ILError::CatalogTransient(msg)) => {
+                warn!("postgres connection broken, reconnecting: {msg}");
+                self.query_raw(sql).await?
+            }
+            result => result?,
+        };
 
         let stream = pg_row_stream.map(move |row| {
-            let pg_row = row.map_err(|e| ILError::CatalogError(e.to_string()))?;
+            let pg_row = row.map_err(pg_error)?;
             pg_row_to_row(&pg_row, &schema)
         });
         Ok(Box::pin(stream))
     }
 
     async fn transaction(&self) -> ILResult<Box<dyn Transaction>> {
-        let conn = self
-            .pool
-            .get_owned()
-            .await
-            .map_err(|e| ILError::CatalogError(e.to_string()))?;
-        conn.batch_execute("START TRANSACTION")
-            .await
-            .map_err(|e| ILError::CatalogError(e.to_string()))?;
+        self.begin_transaction(self.isolation_level).await
+    }
+
+    async fn begin_transaction(&self, level: IsolationLevel) -> ILResult<Box<dyn Transaction>> {
+        let conn = match self.start_transaction(level).await {
+            Err(ILError::CatalogTransient(msg)) => {
+                warn!("postgres connection broken, reconnecting: {msg}");
+                self.start_transaction(level).await?
+            }
+            result => result?,
+        };
         Ok(Box::new(PostgresTransaction { conn, done: false }))
     }
+
+    fn transaction_retry_policy(&self) -> Option<RetryPolicy> {
+        Some(self.transaction_retry.clone())
+    }
+
+    fn read_only(&self) -> bool {
+        self.read_only
+    }
+
+    fn pool_status(&self) -> Option<PoolStatus> {
+        let state = self.pool.state();
+        Some(PoolStatus {
+            active_connections: state.connections - state.idle_connections,
+            idle_connections: state.idle_connections,
+        })
+    }
 }
 
 #[derive(Debug)]
 pub struct PostgresTransaction {
-    conn: bb8::PooledConnection<'static, PostgresConnectionManager<NoTls>>,
+    conn: PostgresConnection,
     done: bool,
 }
 
@@ -93,10 +259,10 @@ impl Transaction for PostgresTransaction {
             .conn
             .query_raw(sql, Vec::<String>::new())
             .await
-            .map_err(|e| ILError::CatalogError(e.to_string()))?;
+            .map_err(pg_error)?;
 
         let stream = pg_row_stream.map(move |row| {
-            let pg_row = row.map_err(|e| ILError::CatalogError(e.to_string()))?;
+            let pg_row = row.map_err(pg_error)?;
             pg_row_to_row(&pg_row, &schema)
         });
         Ok(Box::pin(stream))
@@ -113,7 +279,7 @@ impl Transaction for PostgresTransaction {
             .execute(sql, &[])
             .await
             .map(|r| r as usize)
-            .map_err(|e| ILError::CatalogError(e.to_string()))
+            .map_err(pg_error)
     }
 
     async fn execute_batch(&mut self, sqls: &[String]) -> ILResult<()> {
@@ -126,7 +292,55 @@ impl Transaction for PostgresTransaction {
         self.conn
             .batch_execute(sqls.join(";").as_str())
             .await
-            .map_err(|e| ILError::CatalogError(e.to_string()))
+            .map_err(pg_error)
+    }
+
+    async fn insert_rows(
+        &mut self,
+        _database: CatalogDatabase,
+        table_name: &str,
+        columns: &[String],
+        rows: &[Vec<Scalar>],
+        batch_size: usize,
+    ) -> ILResult<usize> {
+        debug!("postgres txn insert {} rows into {table_name}", rows.len());
+        if self.done {
+            return Err(ILError::CatalogError(
+                "Transaction already committed or rolled back".to_string(),
+            ));
+        }
+        // A statement binds at most 65535 parameters
+        let batch_size = batch_size.min(u16::MAX as usize / columns.len().max(1));
+        let mut count = 0;
+        for chunk in rows.chunks(batch_size.max(1)) {
+            let mut params = Vec::with_capacity(chunk.len() * columns.len());
+            let mut values = Vec::with_capacity(chunk.len());
+            for row in chunk {
+                let first_param = params.len() + 1;
+                for value in row {
+                    params.push(pg_param(value)?);
+                }
+                let placeholders = (first_param..=params.len())
+                    .map(|i| format!("${i}"))
+                    .collect::<Vec<_>>();
+                values.push(format!("({})", placeholders.join(", ")));
+            }
+            let sql = format!(
+                "INSERT INTO {table_name} ({}) VALUES {}",
+                columns.join(", "),
+                values.join(", ")
+            );
+            let param_refs = params
+                .iter()
+                .map(|param| param.as_ref() as &(dyn ToSql + Sync))
+                .collect::<Vec<_>>();
+            count += self
+                .conn
+                .execute(sql.as_str(), &param_refs)
+                .await
+                .map_err(pg_error)? as usize;
+        }
+        Ok(count)
     }
 
     async fn commit(&mut self) -> ILResult<()> {
@@ -136,10 +350,7 @@ impl Transaction for PostgresTransaction {
                 "Transaction already committed or rolled back".to_string(),
             ));
         }
-        self.conn
-            .batch_execute("COMMIT")
-            .await
-            .map_err(|e| ILError::CatalogError(e.to_string()))?;
+        self.conn.batch_execute("COMMIT").await.map_err(pg_error)?;
         self.done = true;
         Ok(())
     }
@@ -154,7 +365,7 @@ impl Transaction for PostgresTransaction {
         self.conn
             .batch_execute("ROLLBACK")
             .await
-            .map_err(|e| ILError::CatalogError(e.to_string()))?;
+            .map_err(pg_error)?;
         self.done = true;
         Ok(())
     }
@@ -176,6 +387,56 @@ impl Drop for PostgresTransaction {
     }
 }
 
+/// Parameter binding `value` to a column of its catalog data type, nulls are typed as well.
+fn pg_param(value: &Scalar) -> ILResult<Box<dyn ToSql + Sync + Send>> {
+    let param: Box<dyn ToSql + Sync + Send> = match value {
+        Scalar::Boolean(v) => Box::new(*v),
+        Scalar::Int16(v) => Box::new(*v),
+        Scalar::Int32(v) => Box::new(*v),
+        Scalar::Int64(v) => Box::new(*v),
+        Scalar::Float32(v) => Box::new(*v),
+        Scalar::Float64(v) => Box::new(*v),
+        Scalar::Utf8(v) => Box::new(v.clone()),
+        Scalar::Binary(v) => Box::new(v.clone()),
+        _ => {
+            return Err(ILError::InternalError(format!(
+                "Scalar {value:?} is not of a catalog data type"
+            )));
+        }
+    };
+    Ok(param)
+}
+
+/// Classify serialization failures and deadlocks as conflicts and connection losses as
+/// transient, so they can be retried. Other errors such as constraint violations or
+/// authentication failures are not retryable.
+fn pg_error(e: tokio_postgres::Error) -> ILError {
+    if let Some(code) = e.code()
+        && [
+            SqlState::T_R_SERIALIZATION_FAILURE,
+            SqlState::T_R_DEADLOCK_DETECTED,
+        ]
+        .contains(code)
+    {
+        return ILError::CatalogConflict(e.to_string());
+    }
+    if e.code() == Some(&SqlState::READ_ONLY_SQL_TRANSACTION) {
+        return ILError::CatalogReadOnly(e.to_string());
+    }
+    let transient = match e.code() {
+        Some(code) => [SqlState::ADMIN_SHUTDOWN, SqlState::CANNOT_CONNECT_NOW].contains(code),
+        None => {
+            e.is_closed()
+                || std::error::Error::source(&e).is_some_and(|source| source.is::<std::io::Error>())
+        }
+    };
+    if transient {
+        ILError::CatalogTransient(e.to_string())
+    } else {
+        ILError::CatalogError(e.to_string())
+    }
+}
+
 fn pg_row_to_row(
     pg_row: &bb8_postgres::tokio_postgres::Row,
     schema: &CatalogSchemaRef,
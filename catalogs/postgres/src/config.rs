@@ -0,0 +1,210 @@
+use std::{path::PathBuf, time::Duration};
+
+use indexlake::{
+    ILResult,
+    catalog::{IsolationLevel, RetryPolicy},
+};
+
+use crate::PostgresCatalog;
+
+#[derive(Debug, Clone, derive_with::With)]
+pub struct PostgresCatalogConfig {
+    pub host: String,
+    pub port: u16,
+    pub user: String,
+    pub password: String,
+    pub dbname: Option<String>,
+    /// Maximum number of connections managed by the pool.
+    pub max_connections: u32,
+    /// Minimum number of idle connections the pool tries to maintain.
+    pub min_connections: Option<u32>,
+    /// Timeout when waiting for a connection to be established or checked out.
+    pub connect_timeout: Duration,
+    /// Idle connections are closed after this duration.
+    pub idle_timeout: Option<Duration>,
+    /// Validate connections before handing them out, so connections broken by a server
+    /// restart are replaced instead of reused.
+    pub test_on_check_out: bool,
+    pub ssl_mode: PostgresSslMode,
+    /// PEM encoded root certificate used to verify the server certificate.
+    pub ssl_root_cert: Option<PathBuf>,
+    /// PEM encoded client certificate presented to the server.
+    pub ssl_client_cert: Option<PathBuf>,
+    /// PEM encoded private key of the client certificate.
+    pub ssl_client_key: Option<PathBuf>,
+    /// Isolation level of transactions begun by [`Catalog::transaction`](indexlake::catalog::Catalog::transaction).
+    /// Catalog ids are allocated from the current maximum, which is only safe against
+    /// concurrent writers under `Serializable`.
+    pub isolation_level: IsolationLevel,
+    /// Transactions failing with a serialization failure (SQLSTATE 40001) or deadlock (40P01)
+    /// are re-run according to this policy.
+    pub transaction_retry: RetryPolicy,
+    /// Open sessions with `default_transaction_read_only`, operations modifying tables fail
+    /// with [`ILError::CatalogReadOnly`](indexlake::ILError::CatalogReadOnly).
+    pub read_only: bool,
+    /// Schema holding the catalog tables, set as `search_path` of every session. It is created
+    /// on connect if missing. Defaults to the server `search_path`, usually `public`.
+    pub schema: Option<String>,
+}
+
+impl PostgresCatalogConfig {
+    pub fn new(
+        host: impl Into<String>,
+        port: u16,
+        user: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        Self {
+            host: host.into(),
+            port,
+            user: user.into(),
+            password: password.into(),
+            dbname: None,
+            max_connections: 10,
+            min_connections: None,
+            connect_timeout: Duration::from_secs(30),
+            idle_timeout: Some(Duration::from_secs(600)),
+            test_on_check_out: true,
+            ssl_mode: PostgresSslMode::Disable,
+            ssl_root_cert: None,
+            ssl_client_cert: None,
+            ssl_client_key: None,
+            isolation_level: IsolationLevel::Serializable,
+            transaction_retry: RetryPolicy::new(
+                10,
+                Duration::from_millis(10),
+                Duration::from_secs(1),
+            ),
+            read_only: false,
+            schema: None,
+        }
+    }
+}
+
+/// Mirrors libpq `sslmode`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostgresSslMode {
+    /// Plaintext connection.
+    Disable,
+    /// Try TLS first, fall back to plaintext, without certificate verification.
+    Prefer,
+    /// Require TLS without certificate verification.
+    Require,
+    /// Require TLS and verify the server certificate is signed by a trusted CA.
+    VerifyCa,
+    /// Like `VerifyCa`, and additionally verify the server hostname matches the certificate.
+    VerifyFull,
+}
+
+/// Builder for [`PostgresCatalog`], created by [`PostgresCatalog::builder`].
+#[derive(Debug, Clone)]
+pub struct PostgresCatalogBuilder {
+    config: PostgresCatalogConfig,
+}
+
+impl PostgresCatalogBuilder {
+    pub(crate) fn new() -> Self {
+        Self {
+            config: PostgresCatalogConfig::new("localhost", 5432, "postgres", ""),
+        }
+    }
+
+    pub fn host(mut self, host: impl Into<String>) -> Self {
+        self.config.host = host.into();
+        self
+    }
+
+    pub fn port(mut self, port: u16) -> Self {
+        self.config.port = port;
+        self
+    }
+
+    pub fn user(mut self, user: impl Into<String>) -> Self {
+        self.config.user = user.into();
+        self
+    }
+
+    pub fn password(mut self, password: impl Into<String>) -> Self {
+        self.config.password = password.into();
+        self
+    }
+
+    pub fn dbname(mut self, dbname: impl Into<String>) -> Self {
+        self.config.dbname = Some(dbname.into());
+        self
+    }
+
+    pub fn max_connections(mut self, max_connections: u32) -> Self {
+        self.config.max_connections = max_connections;
+        self
+    }
+
+    pub fn min_connections(mut self, min_connections: u32) -> Self {
+        self.config.min_connections = Some(min_connections);
+        self
+    }
+
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.config.connect_timeout = connect_timeout;
+        self
+    }
+
+    pub fn idle_timeout(mut self, idle_timeout: Option<Duration>) -> Self {
+        self.config.idle_timeout = idle_timeout;
+        self
+    }
+
+    pub fn test_on_check_out(mut self, test_on_check_out: bool) -> Self {
+        self.config.test_on_check_out = test_on_check_out;
+        self
+    }
+
+    pub fn ssl_mode(mut self, ssl_mode: PostgresSslMode) -> Self {
+        self.config.ssl_mode = ssl_mode;
+        self
+    }
+
+    pub fn ssl_root_cert(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.ssl_root_cert = Some(path.into());
+        self
+    }
+
+    pub fn ssl_client_cert(mut self, cert: impl Into<PathBuf>, key: impl Into<PathBuf>) -> Self {
+        self.config.ssl_client_cert = Some(cert.into());
+        self.config.ssl_client_key = Some(key.into());
+        self
+    }
+
+    pub fn isolation_level(mut self, isolation_level: IsolationLevel) -> Self {
+        self.config.isolation_level = isolation_level;
+        self
+    }
+
+    pub fn max_transaction_attempts(mut self, max_attempts: usize) -> Self {
+        self.config.transaction_retry.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn transaction_retry(mut self, policy: RetryPolicy) -> Self {
+        self.config.transaction_retry = policy;
+        self
+    }
+
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.config.read_only = read_only;
+        self
+    }
+
+    pub fn schema(mut self, schema: impl Into<String>) -> Self {
+        self.config.schema = Some(schema.into());
+        self
+    }
+
+    pub fn config(&self) -> &PostgresCatalogConfig {
+        &self.config
+    }
+
+    pub async fn build(self) -> ILResult<PostgresCatalog> {
+        PostgresCatalog::try_new_with_config(self.config).await
+    }
+}
@@ -1,19 +1,53 @@
 use futures::StreamExt;
 use indexlake::{
     ILError, ILResult,
-    catalog::{Catalog, CatalogDatabase, RowStream, Transaction},
+    catalog::{Catalog, CatalogDatabase, IsolationLevel, RowStream, Transaction},
     catalog::{CatalogDataType, CatalogSchemaRef, Row, Scalar},
 };
 use log::debug;
-use std::path::PathBuf;
+use rusqlite::OpenFlags;
+use std::{path::PathBuf, sync::Arc};
+use tokio::sync::{Mutex, OwnedMutexGuard};
 
+use crate::SqliteCatalogOptions;
+
+/// Catalog backed by a SQLite database file.
+///
+/// SQLite allows a single writer at a time, so transactions begun by this catalog are
+/// serialized within the process instead of failing with "database is locked" when a deferred
+/// transaction upgrades to a write. Other processes are waited for up to
+/// [`SqliteCatalogOptions::busy_timeout`].
 #[derive(Debug)]
 pub struct SqliteCatalog {
     path: PathBuf,
+    options: SqliteCatalogOptions,
+    read_only: bool,
+    transaction_lock: Arc<Mutex<()>>,
 }
 
 impl SqliteCatalog {
     pub fn try_new(path: impl Into<String>) -> ILResult<Self> {
+        Self::try_new_with_options(path, SqliteCatalogOptions::default())
+    }
+
+    pub fn try_new_with_options(
+        path: impl Into<String>,
+        options: SqliteCatalogOptions,
+    ) -> ILResult<Self> {
+        Self::try_new_with_mode(path, options, false)
+    }
+
+    /// Opens the database file with `SQLITE_OPEN_READONLY`, operations modifying tables fail
+    /// with [`ILError::CatalogReadOnly`].
+    pub fn try_new_read_only(path: impl Into<String>) -> ILResult<Self> {
+        Self::try_new_with_mode(path, SqliteCatalogOptions::default(), true)
+    }
+
+    fn try_new_with_mode(
+        path: impl Into<String>,
+        options: SqliteCatalogOptions,
+        read_only: bool,
+    ) -> ILResult<Self> {
         let path = PathBuf::from(path.into());
         if !path.exists() {
             return Err(ILError::CatalogError(format!(
@@ -21,7 +55,62 @@ impl SqliteCatalog {
                 path.display()
             )));
         }
-        Ok(SqliteCatalog { path })
+        let catalog = SqliteCatalog {
+            path,
+            options,
+            read_only,
+            transaction_lock: Arc::new(Mutex::new(())),
+        };
+        // Fail early on invalid files or options
+        catalog.open()?;
+        Ok(catalog)
+    }
+
+    pub fn options(&self) -> &SqliteCatalogOptions {
+        &self.options
+    }
+
+    fn open(&self) -> ILResult<rusqlite::Connection> {
+        let flags = if self.read_only {
+            OpenFlags::SQLITE_OPEN_READ_ONLY
+                | OpenFlags::SQLITE_OPEN_URI
+                | OpenFlags::SQLITE_OPEN_NO_MUTEX
+        } else {
+            OpenFlags::default()
+        };
+        let conn =
+            rusqlite::Connection::open_with_flags(&self.path, flags).map_err(sqlite_error)?;
+        conn.busy_timeout(self.options.busy_timeout)
+            .map_err(sqlite_error)?;
+        if !self.read_only {
+            // Returns the resulting journal mode
+            conn.query_row(
+                &format!(
+                    "PRAGMA journal_mode = {}",
+                    self.options.journal_mode.to_sql()
+                ),
+                [],
+                |_| Ok(()),
+            )
+            .map_err(sqlite_error)?;
+        }
+        conn.execute_batch(&format!(
+            "PRAGMA synchronous = {}",
+            self.options.synchronous.to_sql()
+        ))
+        .map_err(sqlite_error)?;
+        Ok(conn)
+    }
+
+    async fn begin(&self, begin_sql: &str) -> ILResult<Box<dyn Transaction>> {
+        let guard = self.transaction_lock.clone().lock_owned().await;
+        let conn = self.open()?;
+        conn.execute_batch(begin_sql).map_err(sqlite_error)?;
+        Ok(Box::new(SqliteTransaction {
+            conn,
+            done: false,
+            guard: Some(guard),
+        }))
     }
 }
 
@@ -33,8 +122,7 @@ impl Catalog for SqliteCatalog {
 
     async fn query(&self, sql: &str, schema: CatalogSchemaRef) -> ILResult<RowStream<'static>> {
         debug!("sqlite query: {sql}");
-        let conn = rusqlite::Connection::open(&self.path)
-            .map_err(|e| ILError::CatalogError(e.to_string()))?;
+        let conn = self.open()?;
         let mut stmt = conn
             .prepare(sql)
             .map_err(|e| ILError::CatalogError(e.to_string()))?;
@@ -54,11 +142,22 @@ impl Catalog for SqliteCatalog {
     }
 
     async fn transaction(&self) -> ILResult<Box<dyn Transaction>> {
-        let conn = rusqlite::Connection::open(&self.path)
-            .map_err(|e| ILError::CatalogError(e.to_string()))?;
-        conn.execute_batch("BEGIN DEFERRED")
-            .map_err(|e| ILError::CatalogError(e.to_string()))?;
-        Ok(Box::new(SqliteTransaction { conn, done: false }))
+        self.begin("BEGIN DEFERRED").await
+    }
+
+    async fn begin_transaction(&self, level: IsolationLevel) -> ILResult<Box<dyn Transaction>> {
+        check_isolation_level(level)?;
+        if self.read_only {
+            return Err(ILError::CatalogReadOnly(
+                "sqlite catalog is opened read-only".to_string(),
+            ));
+        }
+        // Take the write lock up front so concurrent writers wait instead of failing on upgrade
+        self.begin("BEGIN IMMEDIATE").await
+    }
+
+    fn read_only(&self) -> bool {
+        self.read_only
     }
 }
 
@@ -67,6 +166,8 @@ pub struct SqliteTransaction {
     // TODO use tokio rusqlite connection
     conn: rusqlite::Connection,
     done: bool,
+    // Released once the transaction is committed or rolled back
+    guard: Option<OwnedMutexGuard<()>>,
 }
 
 #[async_trait::async_trait]
@@ -104,9 +205,7 @@ impl Transaction for SqliteTransaction {
                 "Transaction already committed or rolled back".to_string(),
             ));
         }
-        self.conn
-            .execute(sql, [])
-            .map_err(|e| ILError::CatalogError(e.to_string()))
+        self.conn.execute(sql, []).map_err(sqlite_error)
     }
 
     async fn execute_batch(&mut self, sqls: &[String]) -> ILResult<()> {
@@ -118,7 +217,24 @@ impl Transaction for SqliteTransaction {
         }
         self.conn
             .execute_batch(sqls.join(";").as_str())
-            .map_err(|e| ILError::CatalogError(e.to_string()))
+            .map_err(sqlite_error)
+    }
+
+    async fn insert_rows(
+        &mut self,
+        _database: CatalogDatabase,
+        table_name: &str,
+        columns: &[String],
+        rows: &[Vec<Scalar>],
+        _batch_size: usize,
+    ) -> ILResult<usize> {
+        debug!("sqlite txn insert {} rows into {table_name}", rows.len());
+        if self.done {
+            return Err(ILError::CatalogError(
+                "Transaction already committed or rolled back".to_string(),
+            ));
+        }
+        sqlite_insert_rows(&self.conn, table_name, columns, rows)
     }
 
     async fn commit(&mut self) -> ILResult<()> {
@@ -132,6 +248,7 @@ impl Transaction for SqliteTransaction {
             .execute_batch("COMMIT")
             .map_err(|e| ILError::CatalogError(e.to_string()))?;
         self.done = true;
+        self.guard = None;
         Ok(())
     }
 
@@ -146,6 +263,7 @@ impl Transaction for SqliteTransaction {
             .execute_batch("ROLLBACK")
             .map_err(|e| ILError::CatalogError(e.to_string()))?;
         self.done = true;
+        self.guard = None;
         Ok(())
     }
 }
@@ -159,7 +277,75 @@ impl Drop for SqliteTransaction {
     }
 }
 
-fn sqlite_row_to_row(sqlite_row: &rusqlite::Row, schema: &CatalogSchemaRef) -> ILResult<Row> {
+fn sqlite_error(e: rusqlite::Error) -> ILError {
+    match e.sqlite_error_code() {
+        Some(rusqlite::ErrorCode::ReadOnly) => ILError::CatalogReadOnly(e.to_string()),
+        _ => ILError::CatalogError(e.to_string()),
+    }
+}
+
+/// Inserts the rows with a prepared statement executed per row. SQLite runs in process, so
+/// rows cost no round trip and are not batched, binding the values saves parsing them.
+pub fn sqlite_insert_rows(
+    conn: &rusqlite::Connection,
+    table_name: &str,
+    columns: &[String],
+    rows: &[Vec<Scalar>],
+) -> ILResult<usize> {
+    let placeholders = (1..=columns.len())
+        .map(|i| format!("?{i}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut stmt = conn
+        .prepare_cached(&format!(
+            "INSERT INTO {table_name} ({}) VALUES ({placeholders})",
+            columns.join(", ")
+        ))
+        .map_err(sqlite_error)?;
+    let mut count = 0;
+    for row in rows {
+        let values = row.iter().map(sqlite_value).collect::<ILResult<Vec<_>>>()?;
+        count += stmt
+            .execute(rusqlite::params_from_iter(values))
+            .map_err(sqlite_error)?;
+    }
+    Ok(count)
+}
+
+fn sqlite_value(value: &Scalar) -> ILResult<rusqlite::types::Value> {
+    use rusqlite::types::Value;
+    let value = match value {
+        Scalar::Boolean(v) => v.map(|v| Value::Integer(v as i64)),
+        Scalar::Int16(v) => v.map(|v| Value::Integer(v as i64)),
+        Scalar::Int32(v) => v.map(|v| Value::Integer(v as i64)),
+        Scalar::Int64(v) => v.map(Value::Integer),
+        // Stored like the SQL literal of the value, its shortest decimal representation
+        Scalar::Float32(v) => v.map(|v| Value::Real(v.to_string().parse().unwrap_or(v as f64))),
+        Scalar::Float64(v) => v.map(Value::Real),
+        Scalar::Utf8(v) => v.clone().map(Value::Text),
+        Scalar::Binary(v) => v.clone().map(Value::Blob),
+        _ => {
+            return Err(ILError::InternalError(format!(
+                "Scalar {value:?} is not of a catalog data type"
+            )));
+        }
+    };
+    Ok(value.unwrap_or(Value::Null))
+}
+
+/// SQLite allows a single writer at a time, so only serializable isolation can be honored.
+pub fn check_isolation_level(level: IsolationLevel) -> ILResult<()> {
+    match level {
+        IsolationLevel::Serializable => Ok(()),
+        IsolationLevel::ReadCommitted | IsolationLevel::RepeatableRead => {
+            Err(ILError::NotSupported(format!(
+                "sqlite only supports SERIALIZABLE isolation level, got {level}"
+            )))
+        }
+    }
+}
+
+pub fn sqlite_row_to_row(sqlite_row: &rusqlite::Row, schema: &CatalogSchemaRef) -> ILResult<Row> {
     let mut row_values = Vec::new();
     for (idx, field) in schema.columns.iter().enumerate() {
         let scalar = match field.data_type {
@@ -1,3 +1,5 @@
 mod catalog;
+mod config;
 
 pub use catalog::*;
+pub use config::*;
@@ -0,0 +1,236 @@
+use std::f64::consts::LN_2;
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray},
+    compute::cast,
+    datatypes::{DataType, Int64Type},
+};
+use indexlake::{ILError, ILResult, catalog::Scalar};
+
+/// Returns whether values of the type can be hashed into bloom filters.
+pub(crate) fn is_hashable(data_type: &DataType) -> bool {
+    (data_type.is_integer() && data_type != &DataType::UInt64)
+        || matches!(
+            data_type,
+            DataType::Utf8
+                | DataType::LargeUtf8
+                | DataType::Binary
+                | DataType::LargeBinary
+                | DataType::Date32
+                | DataType::Date64
+                | DataType::Timestamp(_, _)
+        )
+}
+
+/// Returns the hash of each value of the array, `None` for nulls.
+///
+/// Values are hashed with FNV-1a over a fixed byte encoding, so filters kept in the catalog
+/// stay valid across processes and releases.
+pub(crate) fn hash_values(array: &ArrayRef) -> ILResult<Vec<Option<u64>>> {
+    let hashes = match array.data_type() {
+        DataType::Utf8 | DataType::LargeUtf8 => cast(array, &DataType::LargeUtf8)?
+            .as_string::<i64>()
+            .iter()
+            .map(|value| value.map(|value| hash_bytes(value.as_bytes())))
+            .collect(),
+        DataType::Binary | DataType::LargeBinary => cast(array, &DataType::LargeBinary)?
+            .as_binary::<i64>()
+            .iter()
+            .map(|value| value.map(hash_bytes))
+            .collect(),
+        data_type if is_hashable(data_type) => cast(array, &DataType::Int64)?
+            .as_primitive::<Int64Type>()
+            .iter()
+            .map(|value| value.map(|value| hash_bytes(&value.to_le_bytes())))
+            .collect(),
+        data_type => {
+            return Err(ILError::IndexError(format!(
+                "Bloom index does not support data type {data_type}"
+            )));
+        }
+    };
+    Ok(hashes)
+}
+
+/// Returns the hash of a literal compared with a key column of `key_type`.
+pub(crate) fn hash_scalar(value: &Scalar, key_type: &DataType) -> ILResult<u64> {
+    let array = cast(&value.to_array_of_size(1)?, key_type)?;
+    if array.is_null(0) {
+        return Err(ILError::IndexError(format!(
+            "Can not hash {value} as {key_type}"
+        )));
+    }
+    Ok(hash_values(&array)?[0].expect("value is not null"))
+}
+
+fn hash_bytes(bytes: &[u8]) -> u64 {
+    let mut hash = 0xcbf29ce484222325u64;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// Bloom filter over the hashes of the values of a column. Each value sets `num_hashes` bits
+/// derived from its hash, a value whose bits are not all set was never inserted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BloomFilter {
+    num_hashes: u32,
+    bits: Vec<u64>,
+}
+
+impl BloomFilter {
+    /// Sizes the filter so that looking up values not inserted reports them present with
+    /// about `false_positive_rate` probability once `num_values` values are inserted.
+    pub(crate) fn with_capacity(num_values: usize, false_positive_rate: f64) -> Self {
+        let num_values = num_values.max(1) as f64;
+        let num_bits = (-num_values * false_positive_rate.ln() / (LN_2 * LN_2)).ceil();
+        let num_words = (num_bits / 64.0).ceil().max(1.0) as usize;
+        let num_hashes = (num_words as f64 * 64.0 / num_values * LN_2)
+            .round()
+            .max(1.0) as u32;
+        Self {
+            num_hashes,
+            bits: vec![0; num_words],
+        }
+    }
+
+    pub(crate) fn insert(&mut self, hash: u64) {
+        for bit in self.bit_positions(hash) {
+            self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+    }
+
+    /// Returns false if the value of the hash was definitely not inserted.
+    pub(crate) fn contains(&self, hash: u64) -> bool {
+        self.bit_positions(hash)
+            .all(|bit| self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+
+    /// Double hashing, the bits are spread by a second hash mixed from the first.
+    fn bit_positions(&self, hash: u64) -> impl Iterator<Item = u64> + use<> {
+        let num_bits = self.bits.len() as u64 * 64;
+        let step = mix(hash) | 1;
+        (0..self.num_hashes as u64).map(move |i| hash.wrapping_add(i.wrapping_mul(step)) % num_bits)
+    }
+
+    pub(crate) fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.num_hashes.to_le_bytes());
+        buf.extend_from_slice(&(self.bits.len() as u32).to_le_bytes());
+        for word in self.bits.iter() {
+            buf.extend_from_slice(&word.to_le_bytes());
+        }
+    }
+
+    /// Decodes a filter written by [`BloomFilter::encode`] from the start of `buf`, returning
+    /// the filter and the remaining bytes.
+    pub(crate) fn decode(buf: &[u8]) -> ILResult<(Self, &[u8])> {
+        let invalid = || ILError::IndexError("Invalid bloom filter encoding".to_string());
+        let num_hashes = u32::from_le_bytes(buf.get(0..4).ok_or_else(invalid)?.try_into().unwrap());
+        let num_words =
+            u32::from_le_bytes(buf.get(4..8).ok_or_else(invalid)?.try_into().unwrap()) as usize;
+        if num_hashes == 0 || num_words == 0 {
+            return Err(invalid());
+        }
+        let words_end = 8 + num_words * 8;
+        let bits = buf
+            .get(8..words_end)
+            .ok_or_else(invalid)?
+            .chunks_exact(8)
+            .map(|word| u64::from_le_bytes(word.try_into().unwrap()))
+            .collect();
+        Ok((Self { num_hashes, bits }, &buf[words_end..]))
+    }
+}
+
+/// splitmix64 finalizer.
+fn mix(hash: u64) -> u64 {
+    let mut x = hash;
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D049BB133111EB);
+    x ^ (x >> 31)
+}
+
+/// Encodes the filters of the key columns, in key column order.
+pub(crate) fn encode_filters(filters: &[BloomFilter]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    for filter in filters {
+        filter.encode(&mut buf);
+    }
+    buf
+}
+
+pub(crate) fn decode_filters(mut buf: &[u8], num_filters: usize) -> ILResult<Vec<BloomFilter>> {
+    let mut filters = Vec::with_capacity(num_filters);
+    for _ in 0..num_filters {
+        let (filter, rest) = BloomFilter::decode(buf)?;
+        filters.push(filter);
+        buf = rest;
+    }
+    if !buf.is_empty() {
+        return Err(ILError::IndexError(
+            "Invalid bloom filter encoding".to_string(),
+        ));
+    }
+    Ok(filters)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::{Int32Array, Int64Array, StringArray};
+
+    use super::*;
+
+    #[test]
+    fn test_false_positive_rate() {
+        for false_positive_rate in [0.1, 0.01, 0.001] {
+            let mut filter = BloomFilter::with_capacity(10000, false_positive_rate);
+            for value in 0..10000i64 {
+                filter.insert(hash_bytes(&value.to_le_bytes()));
+            }
+            assert!((0..10000i64).all(|value| filter.contains(hash_bytes(&value.to_le_bytes()))));
+            let false_positives = (10000..110000i64)
+                .filter(|value| filter.contains(hash_bytes(&value.to_le_bytes())))
+                .count();
+            let rate = false_positives as f64 / 100000.0;
+            assert!(
+                rate < false_positive_rate * 1.5,
+                "false positive rate {rate} exceeds {false_positive_rate}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_encode_filters() -> ILResult<()> {
+        let mut first = BloomFilter::with_capacity(10, 0.01);
+        first.insert(1);
+        let second = BloomFilter::with_capacity(1000, 0.5);
+        let filters = vec![first, second];
+        let encoded = encode_filters(&filters);
+        assert_eq!(decode_filters(&encoded, 2)?, filters);
+        assert!(decode_filters(&encoded, 1).is_err());
+        assert!(decode_filters(&encoded[..encoded.len() - 1], 2).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hash_values() -> ILResult<()> {
+        let int32: ArrayRef = Arc::new(Int32Array::from(vec![Some(1), None, Some(7)]));
+        let int64: ArrayRef = Arc::new(Int64Array::from(vec![Some(1), None, Some(7)]));
+        assert_eq!(hash_values(&int32)?, hash_values(&int64)?);
+        assert_eq!(hash_values(&int32)?[1], None);
+
+        let strings: ArrayRef = Arc::new(StringArray::from(vec!["a", "b"]));
+        assert_eq!(
+            hash_values(&strings)?[0],
+            Some(hash_scalar(
+                &Scalar::Utf8(Some("a".to_string())),
+                &DataType::Utf8
+            )?)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+use std::sync::Arc;
+
+use arrow::{
+    array::{Int64Array, RecordBatch},
+    datatypes::{DataType, Field, Schema, SchemaRef},
+};
+use indexlake::{
+    ILError, ILResult,
+    catalog::INTERNAL_ROW_ID_FIELD_NAME,
+    index::{IndexBuilder, IndexDefinationRef},
+    storage::OutputFile,
+    utils::extract_row_id_array_from_record_batch,
+};
+use parquet::{
+    arrow::AsyncArrowWriter,
+    file::{metadata::KeyValue, properties::WriterProperties},
+};
+
+use crate::{
+    BloomIndexParams,
+    bloom::{BloomFilter, encode_filters, hash_values},
+};
+
+/// Parquet key-value metadata holding the hex encoded bloom filters of the key columns, the
+/// same bytes as kept in the catalog.
+pub(crate) const FILTERS_METADATA_KEY: &str = "indexlake.bloom.filters";
+
+#[derive(Debug, Clone)]
+pub struct BloomIndexBuilder {
+    index_def: IndexDefinationRef,
+    row_ids: Vec<i64>,
+    /// Hashes of the non-null values of each key column.
+    hashes: Vec<Vec<u64>>,
+    encoded_filters: Option<Vec<u8>>,
+}
+
+impl BloomIndexBuilder {
+    pub fn new(index_def: IndexDefinationRef) -> Self {
+        let hashes = vec![Vec::new(); index_def.key_columns.len()];
+        Self {
+            index_def,
+            row_ids: Vec::new(),
+            hashes,
+            encoded_filters: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl IndexBuilder for BloomIndexBuilder {
+    fn update(&mut self, batch: &RecordBatch) -> ILResult<()> {
+        let row_id_array = extract_row_id_array_from_record_batch(batch)?;
+        self.row_ids.extend_from_slice(row_id_array.values());
+
+        for (key_column_name, hashes) in self.index_def.key_columns.iter().zip(&mut self.hashes) {
+            let key_column = batch.column_by_name(key_column_name).ok_or_else(|| {
+                ILError::IndexError(format!("Key column {key_column_name} not found in batch"))
+            })?;
+            hashes.extend(hash_values(key_column)?.into_iter().flatten());
+        }
+
+        Ok(())
+    }
+
+    async fn write(&mut self, output_file: OutputFile) -> ILResult<()> {
+        let params = self.index_def.downcast_params::<BloomIndexParams>()?;
+
+        let filters = self
+            .hashes
+            .iter()
+            .map(|hashes| {
+                let mut filter =
+                    BloomFilter::with_capacity(hashes.len(), params.false_positive_rate);
+                for hash in hashes {
+                    filter.insert(*hash);
+                }
+                filter
+            })
+            .collect::<Vec<_>>();
+        let encoded_filters = encode_filters(&filters);
+
+        let writer_properties = WriterProperties::builder()
+            .set_key_value_metadata(Some(vec![KeyValue::new(
+                FILTERS_METADATA_KEY.to_string(),
+                hex::encode(&encoded_filters),
+            )]))
+            .build();
+        let index_schema = index_schema();
+        let mut arrow_writer =
+            AsyncArrowWriter::try_new(output_file, index_schema.clone(), Some(writer_properties))?;
+        let batch = RecordBatch::try_new(
+            index_schema,
+            vec![Arc::new(Int64Array::from(std::mem::take(
+                &mut self.row_ids,
+            )))],
+        )?;
+        arrow_writer.write(&batch).await?;
+        arrow_writer.close().await?;
+
+        self.encoded_filters = Some(encoded_filters);
+        Ok(())
+    }
+
+    fn file_metadata(&self) -> ILResult<Option<Vec<u8>>> {
+        Ok(self.encoded_filters.clone())
+    }
+}
+
+fn index_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![Field::new(
+        INTERNAL_ROW_ID_FIELD_NAME,
+        DataType::Int64,
+        false,
+    )]))
+}
@@ -0,0 +1,233 @@
+use std::{collections::HashMap, sync::Arc};
+
+use arrow::{
+    array::{ArrayRef, AsArray, Int64Array},
+    datatypes::Int64Type,
+};
+use futures::TryStreamExt;
+use indexlake::{
+    ILError, ILResult,
+    catalog::Scalar,
+    expr::{BinaryOp, Expr},
+    index::{
+        FilterIndexEntries, Index, IndexBuilder, IndexDefination, IndexDefinationRef, IndexParams,
+        SearchIndexEntries, SearchQuery,
+    },
+    storage::InputFile,
+};
+use parquet::{arrow::ParquetRecordBatchStreamBuilder, file::metadata::ParquetMetaData};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    BloomIndexBuilder, FILTERS_METADATA_KEY,
+    bloom::{BloomFilter, decode_filters, hash_scalar, is_hashable},
+};
+
+/// Bloom filter index skipping data files on equality filters. Each data file gets a bloom
+/// filter per key column, kept in the catalog next to its index file, so scans skip the data
+/// files whose filters rule the looked up values out without reading any index file. Data
+/// files that may hold the values are read in full, the index does not narrow down rows.
+///
+/// Filters are sized for the configured false positive rate, the share of data files without
+/// a looked up value that are read anyway. Null keys are not indexed, filters on nulls are
+/// left to the scan.
+#[derive(Debug, Clone)]
+pub struct BloomIndex;
+
+#[async_trait::async_trait]
+impl Index for BloomIndex {
+    fn kind(&self) -> &str {
+        "bloom"
+    }
+
+    fn decode_params(&self, value: &str) -> ILResult<Arc<dyn IndexParams>> {
+        let params = serde_json::from_str::<BloomIndexParams>(value)
+            .map_err(|e| ILError::IndexError(format!("Failed to parse BloomIndexParams: {e}")))?;
+        Ok(Arc::new(params))
+    }
+
+    fn supports(&self, index_def: &IndexDefination) -> ILResult<()> {
+        if index_def.key_columns.is_empty() {
+            return Err(ILError::IndexError(
+                "Bloom index requires at least one key column".to_string(),
+            ));
+        }
+        for key_field in index_def.key_fields()? {
+            let key_type = key_field.data_type();
+            if !is_hashable(key_type) {
+                return Err(ILError::IndexError(format!(
+                    "Bloom index key column must be an integer / string / binary / date / timestamp column, got {key_type}"
+                )));
+            }
+        }
+        if !index_def.include_columns.is_empty() {
+            return Err(ILError::IndexError(
+                "Bloom index does not support include columns".to_string(),
+            ));
+        }
+        let params = index_def.downcast_params::<BloomIndexParams>()?;
+        if !(params.false_positive_rate > 0.0 && params.false_positive_rate < 1.0) {
+            return Err(ILError::IndexError(format!(
+                "Bloom index false positive rate must be between 0 and 1 exclusive, got {}",
+                params.false_positive_rate
+            )));
+        }
+        Ok(())
+    }
+
+    fn builder(&self, index_def: &IndexDefinationRef) -> ILResult<Box<dyn IndexBuilder>> {
+        Ok(Box::new(BloomIndexBuilder::new(index_def.clone())))
+    }
+
+    fn supports_search(
+        &self,
+        _index_def: &IndexDefination,
+        _query: &dyn SearchQuery,
+    ) -> ILResult<bool> {
+        Ok(false)
+    }
+
+    async fn search(
+        &self,
+        _index_def: &IndexDefination,
+        _index_file: InputFile,
+        _query: &dyn SearchQuery,
+    ) -> ILResult<SearchIndexEntries> {
+        Err(ILError::NotSupported(
+            "Bloom index does not support search".to_string(),
+        ))
+    }
+
+    fn supports_filter(&self, index_def: &IndexDefination, filter: &Expr) -> ILResult<bool> {
+        Ok(key_equality(&index_def.key_columns, filter).is_some())
+    }
+
+    /// Returns every row of the data file, or none if its filters rule the values out.
+    async fn filter(
+        &self,
+        index_def: &IndexDefination,
+        index_file: InputFile,
+        filters: &[Expr],
+    ) -> ILResult<FilterIndexEntries> {
+        let arrow_reader_builder = ParquetRecordBatchStreamBuilder::new(index_file).await?;
+        let bloom_filters = read_filters(arrow_reader_builder.metadata(), index_def)?;
+
+        let mut row_id_arrays: Vec<ArrayRef> = Vec::new();
+        if may_match(index_def, &bloom_filters, filters)? {
+            let mut batch_stream = arrow_reader_builder.build()?;
+            while let Some(batch) = batch_stream.try_next().await? {
+                row_id_arrays.push(batch.column(0).clone());
+            }
+        }
+        let row_ids = if row_id_arrays.is_empty() {
+            Int64Array::from(Vec::<i64>::new())
+        } else {
+            arrow::compute::concat(
+                &row_id_arrays
+                    .iter()
+                    .map(|array| array.as_ref())
+                    .collect::<Vec<_>>(),
+            )?
+            .as_primitive::<Int64Type>()
+            .clone()
+        };
+
+        Ok(FilterIndexEntries {
+            row_ids,
+            include_columns: HashMap::new(),
+        })
+    }
+
+    fn filter_file_metadata(
+        &self,
+        index_def: &IndexDefination,
+        metadata: &[u8],
+        filters: &[Expr],
+    ) -> ILResult<Option<bool>> {
+        let bloom_filters = decode_filters(metadata, index_def.key_columns.len())?;
+        Ok(Some(may_match(index_def, &bloom_filters, filters)?))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BloomIndexParams {
+    /// Probability that a data file without a looked up value is read anyway. Lower rates
+    /// make larger filters, about 10 bits per value for 1%.
+    pub false_positive_rate: f64,
+}
+
+impl Default for BloomIndexParams {
+    fn default() -> Self {
+        Self {
+            false_positive_rate: 0.01,
+        }
+    }
+}
+
+impl IndexParams for BloomIndexParams {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn encode(&self) -> ILResult<String> {
+        serde_json::to_string(self)
+            .map_err(|e| ILError::IndexError(format!("Failed to serialize BloomIndexParams: {e}")))
+    }
+}
+
+/// Returns false if the filters of a data file rule out a value the filters look up.
+fn may_match(
+    index_def: &IndexDefination,
+    bloom_filters: &[BloomFilter],
+    filters: &[Expr],
+) -> ILResult<bool> {
+    let key_fields = index_def.key_fields()?;
+    for filter in filters {
+        let Some((key_idx, value)) = key_equality(&index_def.key_columns, filter) else {
+            continue;
+        };
+        let hash = hash_scalar(value, key_fields[key_idx].data_type())?;
+        if !bloom_filters[key_idx].contains(hash) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Returns the key column `filter` compares for equality and the value it compares it with, if
+/// it is a non-null literal.
+fn key_equality<'a>(key_columns: &[String], filter: &'a Expr) -> Option<(usize, &'a Scalar)> {
+    let Expr::BinaryExpr(binary) = filter else {
+        return None;
+    };
+    if binary.op != BinaryOp::Eq {
+        return None;
+    }
+    let (name, value) = match (binary.left.as_ref(), binary.right.as_ref()) {
+        (Expr::Column(name), Expr::Literal(value)) => (name, value),
+        (Expr::Literal(value), Expr::Column(name)) => (name, value),
+        _ => return None,
+    };
+    if value.is_null() {
+        return None;
+    }
+    let key_idx = key_columns
+        .iter()
+        .position(|key_column| key_column == name)?;
+    Some((key_idx, value))
+}
+
+fn read_filters(
+    metadata: &ParquetMetaData,
+    index_def: &IndexDefination,
+) -> ILResult<Vec<BloomFilter>> {
+    let filters_hex = metadata
+        .file_metadata()
+        .key_value_metadata()
+        .and_then(|kvs| kvs.iter().find(|kv| kv.key == FILTERS_METADATA_KEY))
+        .and_then(|kv| kv.value.as_ref())
+        .ok_or_else(|| ILError::IndexError("Bloom index file has no filters".to_string()))?;
+    let encoded = hex::decode(filters_hex)
+        .map_err(|e| ILError::IndexError(format!("Failed to decode bloom filters: {e}")))?;
+    decode_filters(&encoded, index_def.key_columns.len())
+}
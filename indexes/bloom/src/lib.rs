@@ -0,0 +1,6 @@
+mod bloom;
+mod builder;
+mod index;
+
+pub use builder::*;
+pub use index::*;
@@ -0,0 +1,142 @@
+use std::sync::Arc;
+
+use arrow::{
+    array::{ArrayRef, RecordBatch},
+    compute::{
+        SortColumn, concat_batches, filter_record_batch, is_not_null, lexsort_to_indices,
+        take_record_batch,
+    },
+    datatypes::{DataType, Field, Schema, SchemaRef},
+};
+use indexlake::{
+    ILError, ILResult,
+    catalog::{INTERNAL_ROW_ID_FIELD_NAME, Scalar},
+    index::{IndexBuilder, IndexDefinationRef},
+    storage::OutputFile,
+    utils::extract_row_id_array_from_record_batch,
+};
+use parquet::{
+    arrow::AsyncArrowWriter,
+    file::{metadata::KeyValue, properties::WriterProperties},
+};
+
+use crate::BTreeIndexParams;
+
+/// Parquet key-value metadata holding the first and last key of each leaf of the index file.
+pub(crate) const LEAVES_METADATA_KEY: &str = "indexlake.btree.leaves";
+
+#[derive(Debug, Clone)]
+pub struct BTreeIndexBuilder {
+    index_def: IndexDefinationRef,
+    index_schema: SchemaRef,
+    index_batches: Vec<RecordBatch>,
+}
+
+impl BTreeIndexBuilder {
+    pub fn try_new(index_def: IndexDefinationRef) -> ILResult<Self> {
+        let key_fields = index_def.key_fields()?;
+        let include_fields = index_def.include_fields()?;
+        let index_schema = index_schema(key_fields, include_fields);
+        Ok(Self {
+            index_def,
+            index_schema,
+            index_batches: Vec::new(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl IndexBuilder for BTreeIndexBuilder {
+    fn update(&mut self, batch: &RecordBatch) -> ILResult<()> {
+        let row_id_array = extract_row_id_array_from_record_batch(batch)?;
+
+        let mut arrays = vec![Arc::new(row_id_array) as ArrayRef];
+        for key_column_name in self.index_def.key_columns.iter() {
+            let key_column = batch.column_by_name(key_column_name).ok_or_else(|| {
+                ILError::IndexError(format!("Key column {key_column_name} not found in batch"))
+            })?;
+            arrays.push(key_column.clone());
+        }
+        for col_name in self.index_def.include_columns.iter() {
+            let array = batch.column_by_name(col_name).ok_or_else(|| {
+                ILError::IndexError(format!("Include column {col_name} not found in batch"))
+            })?;
+            arrays.push(array.clone());
+        }
+        self.index_batches
+            .push(RecordBatch::try_new(self.index_schema.clone(), arrays)?);
+
+        Ok(())
+    }
+
+    async fn write(&mut self, output_file: OutputFile) -> ILResult<()> {
+        let params = self.index_def.downcast_params::<BTreeIndexParams>()?;
+
+        // Null leading keys match no comparison the index is used for, they are left out
+        let batch = concat_batches(&self.index_schema, &self.index_batches)?;
+        let batch = filter_record_batch(&batch, &is_not_null(batch.column(1))?)?;
+        // Keys are sorted by the key columns in order, so rows sharing the leading columns are
+        // sorted by the next one
+        let key_count = self.index_def.key_columns.len();
+        let sort_columns = (1..=key_count)
+            .map(|idx| SortColumn {
+                values: batch.column(idx).clone(),
+                options: None,
+            })
+            .collect::<Vec<_>>();
+        let indices = lexsort_to_indices(&sort_columns, None)?;
+        let batch = take_record_batch(&batch, &indices)?;
+
+        let key_at = |row: usize| {
+            (1..=key_count)
+                .map(|idx| Scalar::try_from_array(batch.column(idx).as_ref(), row))
+                .collect::<ILResult<Vec<_>>>()
+        };
+        let mut leaves = Vec::new();
+        let mut offset = 0;
+        while offset < batch.num_rows() {
+            let length = params.leaf_size.min(batch.num_rows() - offset);
+            leaves.push((key_at(offset)?, key_at(offset + length - 1)?));
+            offset += length;
+        }
+        let leaves_json = serde_json::to_string(&leaves)
+            .map_err(|e| ILError::IndexError(format!("Failed to serialize btree leaves: {e}")))?;
+
+        let writer_properties = WriterProperties::builder()
+            .set_max_row_group_size(params.leaf_size)
+            .set_key_value_metadata(Some(vec![KeyValue::new(
+                LEAVES_METADATA_KEY.to_string(),
+                leaves_json,
+            )]))
+            .build();
+        let mut arrow_writer = AsyncArrowWriter::try_new(
+            output_file,
+            self.index_schema.clone(),
+            Some(writer_properties),
+        )?;
+
+        // Each leaf is flushed as its own row group, so leaves can be read on their own
+        let mut offset = 0;
+        while offset < batch.num_rows() {
+            let length = params.leaf_size.min(batch.num_rows() - offset);
+            arrow_writer.write(&batch.slice(offset, length)).await?;
+            arrow_writer.flush().await?;
+            offset += length;
+        }
+
+        arrow_writer.close().await?;
+
+        Ok(())
+    }
+}
+
+fn index_schema(key_fields: Vec<&Field>, include_fields: Vec<&Field>) -> SchemaRef {
+    let mut fields = vec![Field::new(
+        INTERNAL_ROW_ID_FIELD_NAME,
+        DataType::Int64,
+        false,
+    )];
+    fields.extend(key_fields.into_iter().cloned());
+    fields.extend(include_fields.into_iter().cloned());
+    Arc::new(Schema::new(fields))
+}
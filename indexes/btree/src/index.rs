@@ -0,0 +1,389 @@
+use std::{cmp::Ordering, collections::HashMap, sync::Arc};
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray, Int64Array, RecordBatch, new_empty_array},
+    compute::filter_record_batch,
+    datatypes::{DataType, Field, Int64Type, Schema},
+};
+use futures::TryStreamExt;
+use indexlake::{
+    ILError, ILResult,
+    catalog::Scalar,
+    expr::{BinaryExpr, BinaryOp, Expr, col, merge_filters},
+    index::{
+        FilterIndexEntries, Index, IndexBuilder, IndexDefination, IndexDefinationRef, IndexParams,
+        SearchIndexEntries, SearchQuery,
+    },
+    storage::InputFile,
+};
+use parquet::{arrow::ParquetRecordBatchStreamBuilder, file::metadata::ParquetMetaData};
+use serde::{Deserialize, Serialize};
+
+use crate::{BTreeIndexBuilder, LEAVES_METADATA_KEY};
+
+/// Sorted index over one or more scalar columns. Each data file gets an index file holding its
+/// keys in order, split into leaves whose first and last keys are kept in the file footer.
+/// Equality and range filters on the key columns only read the leaves whose range they overlap,
+/// data files with no overlapping leaf are skipped by scans.
+///
+/// Composite keys such as `(tenant_id, created_at)` are sorted by the key columns in order, so
+/// the index follows the leading-column rule: it is used when a filter compares the first key
+/// column, and narrows leaves down on the following columns as long as the columns before them
+/// are compared for equality. `tenant_id = 7 AND created_at > t` reads the leaves of tenant 7
+/// past `t`, `tenant_id = 7` alone all leaves of tenant 7. Filters on `created_at` alone do not
+/// use the index, scans read every data file.
+#[derive(Debug, Clone)]
+pub struct BTreeIndex;
+
+#[async_trait::async_trait]
+impl Index for BTreeIndex {
+    fn kind(&self) -> &str {
+        "btree"
+    }
+
+    fn decode_params(&self, value: &str) -> ILResult<Arc<dyn IndexParams>> {
+        let params = serde_json::from_str::<BTreeIndexParams>(value)
+            .map_err(|e| ILError::IndexError(format!("Failed to parse BTreeIndexParams: {e}")))?;
+        Ok(Arc::new(params))
+    }
+
+    fn supports(&self, index_def: &IndexDefination) -> ILResult<()> {
+        if index_def.key_columns.is_empty() {
+            return Err(ILError::IndexError(
+                "BTree index requires at least one key column".to_string(),
+            ));
+        }
+        for key_field in index_def.key_fields()? {
+            let key_type = key_field.data_type();
+            if !(key_type.is_integer()
+                || key_type.is_floating()
+                || matches!(
+                    key_type,
+                    DataType::Utf8
+                        | DataType::LargeUtf8
+                        | DataType::Date32
+                        | DataType::Date64
+                        | DataType::Timestamp(_, _)
+                ))
+            {
+                return Err(ILError::IndexError(format!(
+                    "BTree index key column must be an integer / float / string / date / timestamp column, got {key_type}"
+                )));
+            }
+        }
+        let params = index_def.downcast_params::<BTreeIndexParams>()?;
+        if params.leaf_size == 0 {
+            return Err(ILError::IndexError(
+                "BTree index leaf size must be greater than 0".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn builder(&self, index_def: &IndexDefinationRef) -> ILResult<Box<dyn IndexBuilder>> {
+        Ok(Box::new(BTreeIndexBuilder::try_new(index_def.clone())?))
+    }
+
+    fn supports_search(
+        &self,
+        _index_def: &IndexDefination,
+        _query: &dyn SearchQuery,
+    ) -> ILResult<bool> {
+        Ok(false)
+    }
+
+    async fn search(
+        &self,
+        _index_def: &IndexDefination,
+        _index_file: InputFile,
+        _query: &dyn SearchQuery,
+    ) -> ILResult<SearchIndexEntries> {
+        Err(ILError::NotSupported(
+            "BTree index does not support search".to_string(),
+        ))
+    }
+
+    fn supports_filter(&self, index_def: &IndexDefination, filter: &Expr) -> ILResult<bool> {
+        Ok(index_def
+            .key_columns
+            .iter()
+            .any(|key_column| key_comparison(key_column, filter).is_some()))
+    }
+
+    /// Keys are sorted by the leading key column first, without a filter on it every leaf may
+    /// match.
+    fn supports_filters(&self, index_def: &IndexDefination, filters: &[Expr]) -> ILResult<bool> {
+        Ok(filters
+            .iter()
+            .any(|filter| key_comparison(&index_def.key_columns[0], filter).is_some()))
+    }
+
+    async fn filter(
+        &self,
+        index_def: &IndexDefination,
+        index_file: InputFile,
+        filters: &[Expr],
+    ) -> ILResult<FilterIndexEntries> {
+        let key_fields = index_def
+            .key_fields()?
+            .into_iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        // Comparisons of each key column, in key order
+        let comparisons = key_fields
+            .iter()
+            .map(|key_field| {
+                filters
+                    .iter()
+                    .filter_map(|filter| key_comparison(key_field.name(), filter))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let arrow_reader_builder = ParquetRecordBatchStreamBuilder::new(index_file).await?;
+        let leaves = read_leaves(arrow_reader_builder.metadata())?;
+        let row_groups = leaves
+            .iter()
+            .enumerate()
+            .filter(|(_, (first, last))| leaf_may_match(&key_fields, &comparisons, first, last))
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+
+        let mut row_id_arrays: Vec<ArrayRef> = Vec::new();
+        let mut include_arrays: HashMap<String, Vec<ArrayRef>> = HashMap::new();
+        if !row_groups.is_empty() {
+            let predicate = merge_filters(filters.to_vec());
+            let mut batch_stream = arrow_reader_builder.with_row_groups(row_groups).build()?;
+            while let Some(batch) = batch_stream.try_next().await? {
+                let batch = match &predicate {
+                    Some(predicate) => filter_by_expr(&batch, predicate)?,
+                    None => batch,
+                };
+                row_id_arrays.push(batch.column(0).clone());
+                for col_name in index_def.include_columns.iter() {
+                    let array = batch.column_by_name(col_name).ok_or_else(|| {
+                        ILError::IndexError(format!("Include column {col_name} not found"))
+                    })?;
+                    include_arrays
+                        .entry(col_name.clone())
+                        .or_default()
+                        .push(array.clone());
+                }
+            }
+        }
+
+        let row_ids = if row_id_arrays.is_empty() {
+            Int64Array::from(Vec::<i64>::new())
+        } else {
+            concat_arrays(&row_id_arrays)?
+                .as_primitive::<Int64Type>()
+                .clone()
+        };
+        let mut include_columns = HashMap::new();
+        for field in index_def.include_fields()? {
+            let array = match include_arrays.get(field.name()) {
+                Some(arrays) => concat_arrays(arrays)?,
+                None => new_empty_array(field.data_type()),
+            };
+            include_columns.insert(field.name().clone(), array);
+        }
+
+        Ok(FilterIndexEntries {
+            row_ids,
+            include_columns,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BTreeIndexParams {
+    /// Number of keys per leaf, the unit read from the index file.
+    pub leaf_size: usize,
+}
+
+impl Default for BTreeIndexParams {
+    fn default() -> Self {
+        Self { leaf_size: 1024 }
+    }
+}
+
+impl IndexParams for BTreeIndexParams {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn encode(&self) -> ILResult<String> {
+        serde_json::to_string(self)
+            .map_err(|e| ILError::IndexError(format!("Failed to serialize BTreeIndexParams: {e}")))
+    }
+}
+
+/// Returns the comparison of `filter` as `key op value`, if it compares the key column with a
+/// non-null literal.
+fn key_comparison(key_column: &str, filter: &Expr) -> Option<(BinaryOp, Scalar)> {
+    let Expr::BinaryExpr(binary) = filter else {
+        return None;
+    };
+    let (op, value) = match (binary.left.as_ref(), binary.right.as_ref()) {
+        (Expr::Column(name), Expr::Literal(value)) if name == key_column => (binary.op, value),
+        (Expr::Literal(value), Expr::Column(name)) if name == key_column => {
+            let op = match binary.op {
+                BinaryOp::Lt => BinaryOp::Gt,
+                BinaryOp::LtEq => BinaryOp::GtEq,
+                BinaryOp::Gt => BinaryOp::Lt,
+                BinaryOp::GtEq => BinaryOp::LtEq,
+                op => op,
+            };
+            (op, value)
+        }
+        _ => return None,
+    };
+    if !matches!(
+        op,
+        BinaryOp::Eq | BinaryOp::Lt | BinaryOp::LtEq | BinaryOp::Gt | BinaryOp::GtEq
+    ) || value.is_null()
+    {
+        return None;
+    }
+    Some((op, value.clone()))
+}
+
+/// First and last key of each leaf.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Leaves {
+    Keys(Vec<(Vec<Scalar>, Vec<Scalar>)>),
+    // Index files written before composite keys hold single column keys
+    SingleKeys(Vec<(Scalar, Scalar)>),
+}
+
+fn read_leaves(metadata: &ParquetMetaData) -> ILResult<Vec<(Vec<Scalar>, Vec<Scalar>)>> {
+    let leaves_json = metadata
+        .file_metadata()
+        .key_value_metadata()
+        .and_then(|kvs| kvs.iter().find(|kv| kv.key == LEAVES_METADATA_KEY))
+        .and_then(|kv| kv.value.as_ref())
+        .ok_or_else(|| ILError::IndexError("BTree index file has no leaves".to_string()))?;
+    let leaves = serde_json::from_str(leaves_json)
+        .map_err(|e| ILError::IndexError(format!("Failed to parse btree leaves: {e}")))?;
+    Ok(match leaves {
+        Leaves::Keys(leaves) => leaves,
+        Leaves::SingleKeys(leaves) => leaves
+            .into_iter()
+            .map(|(first, last)| (vec![first], vec![last]))
+            .collect(),
+    })
+}
+
+/// Returns false if no key between the first and last key of a leaf matches the comparisons.
+///
+/// Keys are sorted column by column, so the keys of the leaf sharing the values compared for
+/// equality on the leading columns are bounded on the next column by the first and last key,
+/// where these share the values too, and unbounded otherwise. Columns after the first one not
+/// compared for equality bound nothing.
+fn leaf_may_match(
+    key_fields: &[Field],
+    comparisons: &[Vec<(BinaryOp, Scalar)>],
+    first: &[Scalar],
+    last: &[Scalar],
+) -> bool {
+    let mut prefix = Vec::new();
+    for (idx, key_field) in key_fields.iter().enumerate() {
+        let min = column_bound(key_fields, first, idx, &prefix);
+        let max = column_bound(key_fields, last, idx, &prefix);
+        if !comparisons[idx]
+            .iter()
+            .all(|(op, value)| range_may_match(key_field, min, max, *op, value))
+        {
+            return false;
+        }
+        match comparisons[idx].iter().find(|(op, _)| *op == BinaryOp::Eq) {
+            Some((_, value)) => prefix.push(value.clone()),
+            None => break,
+        }
+    }
+    true
+}
+
+/// Returns the value of column `idx` of a key if the columns before it equal `prefix`.
+fn column_bound<'a>(
+    key_fields: &[Field],
+    key: &'a [Scalar],
+    idx: usize,
+    prefix: &[Scalar],
+) -> Option<&'a Scalar> {
+    (prefix_order(key_fields, &key[..idx], prefix) == Some(Ordering::Equal))
+        .then(|| &key[idx])
+        .filter(|bound| !bound.is_null())
+}
+
+fn range_may_match(
+    key_field: &Field,
+    min: Option<&Scalar>,
+    max: Option<&Scalar>,
+    op: BinaryOp,
+    value: &Scalar,
+) -> bool {
+    let compare = |key: Option<&Scalar>, op| {
+        key.is_none_or(|key| evaluate(key_field, key, op, value).unwrap_or(true))
+    };
+    match op {
+        BinaryOp::Eq => compare(min, BinaryOp::LtEq) && compare(max, BinaryOp::GtEq),
+        BinaryOp::Lt | BinaryOp::LtEq => compare(min, op),
+        BinaryOp::Gt | BinaryOp::GtEq => compare(max, op),
+        _ => true,
+    }
+}
+
+/// Orders the leading columns of a key against `prefix`, `None` if they can not be compared.
+fn prefix_order(key_fields: &[Field], key: &[Scalar], prefix: &[Scalar]) -> Option<Ordering> {
+    for ((key_field, key), value) in key_fields.iter().zip(key).zip(prefix) {
+        if !evaluate(key_field, key, BinaryOp::Eq, value)? {
+            return match evaluate(key_field, key, BinaryOp::Lt, value)? {
+                true => Some(Ordering::Less),
+                false => Some(Ordering::Greater),
+            };
+        }
+    }
+    Some(Ordering::Equal)
+}
+
+// Compares with the expression evaluation of scans, so leaves are skipped exactly when scans
+// would filter out all of their keys. Returns `None` for comparisons that can not be evaluated.
+fn evaluate(key_field: &Field, key: &Scalar, op: BinaryOp, value: &Scalar) -> Option<bool> {
+    let expr = Expr::BinaryExpr(BinaryExpr {
+        left: Box::new(col(key_field.name())),
+        op,
+        right: Box::new(Expr::Literal(value.clone())),
+    });
+    let array = key
+        .to_array_of_size(1)
+        .and_then(|array| {
+            let schema = Arc::new(Schema::new(vec![key_field.clone()]));
+            let batch = RecordBatch::try_new(schema, vec![array])?;
+            expr.eval(&batch)?.into_array(1)
+        })
+        .ok()?;
+    let array = array.as_boolean_opt()?;
+    array.is_valid(0).then(|| array.value(0))
+}
+
+fn filter_by_expr(batch: &RecordBatch, predicate: &Expr) -> ILResult<RecordBatch> {
+    let array = predicate.eval(batch)?.into_array(batch.num_rows())?;
+    let bool_array = array.as_boolean_opt().ok_or_else(|| {
+        ILError::IndexError(format!(
+            "predicate should return BooleanArray, but got {:?}",
+            array.data_type()
+        ))
+    })?;
+    Ok(filter_record_batch(batch, bool_array)?)
+}
+
+fn concat_arrays(arrays: &[ArrayRef]) -> ILResult<ArrayRef> {
+    Ok(arrow::compute::concat(
+        &arrays
+            .iter()
+            .map(|array| array.as_ref())
+            .collect::<Vec<_>>(),
+    )?)
+}
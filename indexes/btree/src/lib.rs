@@ -0,0 +1,5 @@
+mod builder;
+mod index;
+
+pub use builder::*;
+pub use index::*;
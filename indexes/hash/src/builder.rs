@@ -0,0 +1,138 @@
+use std::sync::Arc;
+
+use arrow::{
+    array::{ArrayRef, RecordBatch, UInt64Array},
+    compute::{
+        concat_batches, filter_record_batch, is_not_null, sort_to_indices, take_record_batch,
+    },
+    datatypes::{DataType, Field, Schema, SchemaRef},
+};
+use indexlake::{
+    ILError, ILResult,
+    catalog::INTERNAL_ROW_ID_FIELD_NAME,
+    index::{IndexBuilder, IndexDefinationRef},
+    storage::OutputFile,
+    utils::extract_row_id_array_from_record_batch,
+};
+use parquet::{
+    arrow::AsyncArrowWriter,
+    file::{metadata::KeyValue, properties::WriterProperties},
+};
+
+use crate::{HashIndexParams, hash::hash_buckets};
+
+/// Parquet key-value metadata holding the bucket range of each row group of the index file.
+pub(crate) const GROUPS_METADATA_KEY: &str = "indexlake.hash.groups";
+
+pub(crate) const BUCKET_FIELD_NAME: &str = "_indexlake_hash_bucket";
+
+#[derive(Debug, Clone)]
+pub struct HashIndexBuilder {
+    index_def: IndexDefinationRef,
+    index_schema: SchemaRef,
+    index_batches: Vec<RecordBatch>,
+}
+
+impl HashIndexBuilder {
+    pub fn try_new(index_def: IndexDefinationRef) -> ILResult<Self> {
+        let key_fields = index_def.key_fields()?;
+        let include_fields = index_def.include_fields()?;
+        let index_schema = index_schema(key_fields[0], include_fields);
+        Ok(Self {
+            index_def,
+            index_schema,
+            index_batches: Vec::new(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl IndexBuilder for HashIndexBuilder {
+    fn update(&mut self, batch: &RecordBatch) -> ILResult<()> {
+        let params = self.index_def.downcast_params::<HashIndexParams>()?;
+        let row_id_array = extract_row_id_array_from_record_batch(batch)?;
+
+        let key_column_name = &self.index_def.key_columns[0];
+        let key_column = batch.column_by_name(key_column_name).ok_or_else(|| {
+            ILError::IndexError(format!("Key column {key_column_name} not found in batch"))
+        })?;
+        let buckets = UInt64Array::from(hash_buckets(key_column, params.num_buckets)?);
+
+        let mut arrays = vec![
+            Arc::new(row_id_array) as ArrayRef,
+            Arc::new(buckets) as ArrayRef,
+            key_column.clone(),
+        ];
+        for col_name in self.index_def.include_columns.iter() {
+            let array = batch.column_by_name(col_name).ok_or_else(|| {
+                ILError::IndexError(format!("Include column {col_name} not found in batch"))
+            })?;
+            arrays.push(array.clone());
+        }
+        self.index_batches
+            .push(RecordBatch::try_new(self.index_schema.clone(), arrays)?);
+
+        Ok(())
+    }
+
+    async fn write(&mut self, output_file: OutputFile) -> ILResult<()> {
+        let params = self.index_def.downcast_params::<HashIndexParams>()?;
+
+        // Null keys match no equality, they are left out of the index
+        let batch = concat_batches(&self.index_schema, &self.index_batches)?;
+        let batch = filter_record_batch(&batch, &is_not_null(batch.column(1))?)?;
+        let indices = sort_to_indices(batch.column(1), None, None)?;
+        let batch = take_record_batch(&batch, &indices)?;
+        let buckets = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<UInt64Array>()
+            .ok_or_else(|| ILError::IndexError("Bucket column is not UInt64".to_string()))?;
+
+        let mut groups = Vec::new();
+        let mut offset = 0;
+        while offset < batch.num_rows() {
+            let length = params.group_size.min(batch.num_rows() - offset);
+            groups.push((buckets.value(offset), buckets.value(offset + length - 1)));
+            offset += length;
+        }
+        let groups_json = serde_json::to_string(&groups)
+            .map_err(|e| ILError::IndexError(format!("Failed to serialize hash groups: {e}")))?;
+
+        let writer_properties = WriterProperties::builder()
+            .set_max_row_group_size(params.group_size)
+            .set_key_value_metadata(Some(vec![KeyValue::new(
+                GROUPS_METADATA_KEY.to_string(),
+                groups_json,
+            )]))
+            .build();
+        let mut arrow_writer = AsyncArrowWriter::try_new(
+            output_file,
+            self.index_schema.clone(),
+            Some(writer_properties),
+        )?;
+
+        // Each group is flushed as its own row group, so groups can be read on their own
+        let mut offset = 0;
+        while offset < batch.num_rows() {
+            let length = params.group_size.min(batch.num_rows() - offset);
+            arrow_writer.write(&batch.slice(offset, length)).await?;
+            arrow_writer.flush().await?;
+            offset += length;
+        }
+
+        arrow_writer.close().await?;
+
+        Ok(())
+    }
+}
+
+fn index_schema(key_field: &Field, include_fields: Vec<&Field>) -> SchemaRef {
+    let mut fields = vec![
+        Field::new(INTERNAL_ROW_ID_FIELD_NAME, DataType::Int64, false),
+        Field::new(BUCKET_FIELD_NAME, DataType::UInt64, true),
+        key_field.clone(),
+    ];
+    fields.extend(include_fields.into_iter().cloned());
+    Arc::new(Schema::new(fields))
+}
@@ -0,0 +1,104 @@
+use arrow::{
+    array::{Array, ArrayRef, AsArray},
+    compute::cast,
+    datatypes::{DataType, Int64Type},
+};
+use indexlake::{ILError, ILResult, catalog::Scalar};
+
+/// Returns whether values of the type can be hashed into buckets.
+pub(crate) fn is_hashable(data_type: &DataType) -> bool {
+    (data_type.is_integer() && data_type != &DataType::UInt64)
+        || matches!(
+            data_type,
+            DataType::Utf8
+                | DataType::LargeUtf8
+                | DataType::Binary
+                | DataType::LargeBinary
+                | DataType::Date32
+                | DataType::Date64
+                | DataType::Timestamp(_, _)
+        )
+}
+
+/// Returns the bucket of each value of the array, `None` for nulls.
+///
+/// Values are hashed with FNV-1a over a fixed byte encoding, so buckets written to index
+/// files stay valid across processes and releases.
+pub(crate) fn hash_buckets(array: &ArrayRef, num_buckets: u64) -> ILResult<Vec<Option<u64>>> {
+    let bucket = |bytes: &[u8]| fnv1a(bytes) % num_buckets;
+    let buckets = match array.data_type() {
+        DataType::Utf8 | DataType::LargeUtf8 => cast(array, &DataType::LargeUtf8)?
+            .as_string::<i64>()
+            .iter()
+            .map(|value| value.map(|value| bucket(value.as_bytes())))
+            .collect(),
+        DataType::Binary | DataType::LargeBinary => cast(array, &DataType::LargeBinary)?
+            .as_binary::<i64>()
+            .iter()
+            .map(|value| value.map(bucket))
+            .collect(),
+        data_type if is_hashable(data_type) => cast(array, &DataType::Int64)?
+            .as_primitive::<Int64Type>()
+            .iter()
+            .map(|value| value.map(|value| bucket(&value.to_le_bytes())))
+            .collect(),
+        data_type => {
+            return Err(ILError::IndexError(format!(
+                "Hash index does not support data type {data_type}"
+            )));
+        }
+    };
+    Ok(buckets)
+}
+
+/// Returns the bucket of a literal compared with a key column of `key_type`.
+pub(crate) fn hash_scalar(value: &Scalar, key_type: &DataType, num_buckets: u64) -> ILResult<u64> {
+    let array = cast(&value.to_array_of_size(1)?, key_type)?;
+    if array.is_null(0) {
+        return Err(ILError::IndexError(format!(
+            "Can not hash {value} as {key_type}"
+        )));
+    }
+    Ok(hash_buckets(&array, num_buckets)?[0].expect("value is not null"))
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash = 0xcbf29ce484222325u64;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::{Date32Array, Int32Array, Int64Array, StringArray};
+
+    use super::*;
+
+    #[test]
+    fn test_hash_buckets() -> ILResult<()> {
+        let int32: ArrayRef = Arc::new(Int32Array::from(vec![Some(1), None, Some(7)]));
+        let int64: ArrayRef = Arc::new(Int64Array::from(vec![Some(1), None, Some(7)]));
+        assert_eq!(hash_buckets(&int32, 1024)?, hash_buckets(&int64, 1024)?);
+        assert_eq!(hash_buckets(&int32, 1024)?[1], None);
+
+        let dates: ArrayRef = Arc::new(Date32Array::from(vec![19000]));
+        assert!(hash_buckets(&dates, 1024)?[0].is_some());
+
+        let strings: ArrayRef = Arc::new(StringArray::from(vec!["a", "b"]));
+        assert_eq!(hash_buckets(&strings, 1)?, vec![Some(0), Some(0)]);
+        assert_eq!(
+            hash_buckets(&strings, 1024)?[0],
+            Some(hash_scalar(
+                &Scalar::Utf8(Some("a".to_string())),
+                &DataType::Utf8,
+                1024
+            )?)
+        );
+        Ok(())
+    }
+}
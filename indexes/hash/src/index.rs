@@ -1,16 +1,38 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
+use arrow::{
+    array::{ArrayRef, AsArray, Int64Array, RecordBatch, new_empty_array},
+    compute::filter_record_batch,
+    datatypes::Int64Type,
+};
+use futures::TryStreamExt;
 use indexlake::{
-    ILError, ILResult, RecordBatchStream,
-    expr::Expr,
+    ILError, ILResult,
+    catalog::Scalar,
+    expr::{BinaryOp, Expr, merge_filters},
     index::{
         FilterIndexEntries, Index, IndexBuilder, IndexDefination, IndexDefinationRef, IndexParams,
         SearchIndexEntries, SearchQuery,
     },
-    storage::{InputFile, OutputFile},
+    storage::InputFile,
+};
+use parquet::{arrow::ParquetRecordBatchStreamBuilder, file::metadata::ParquetMetaData};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    GROUPS_METADATA_KEY, HashIndexBuilder,
+    hash::{hash_scalar, is_hashable},
 };
 
-#[derive(Debug)]
+/// Hash index for equality lookups on a column. Each data file gets an index file holding its
+/// keys ordered by hash bucket, split into groups whose bucket ranges are kept in the file
+/// footer. A lookup only reads the groups covering the bucket of the looked up value, or of
+/// any value of an `IN` list, data files with no such group are skipped by scans.
+///
+/// Values colliding into the same bucket are told apart by the keys stored next to the
+/// buckets, so collisions only cost reading more of the index file. Null keys are not
+/// indexed, filters on nulls are left to the scan.
+#[derive(Debug, Clone)]
 pub struct HashIndex;
 
 #[async_trait::async_trait]
@@ -20,30 +42,60 @@ impl Index for HashIndex {
     }
 
     fn decode_params(&self, value: &str) -> ILResult<Arc<dyn IndexParams>> {
-        todo!()
+        let params = serde_json::from_str::<HashIndexParams>(value)
+            .map_err(|e| ILError::IndexError(format!("Failed to parse HashIndexParams: {e}")))?;
+        Ok(Arc::new(params))
     }
 
     fn supports(&self, index_def: &IndexDefination) -> ILResult<()> {
+        if index_def.key_columns.len() != 1 {
+            return Err(ILError::IndexError(
+                "Hash index requires exactly one key column".to_string(),
+            ));
+        }
+        let key_field = index_def
+            .table_schema
+            .field_with_name(&index_def.key_columns[0])?;
+        let key_type = key_field.data_type();
+        if !is_hashable(key_type) {
+            return Err(ILError::IndexError(format!(
+                "Hash index key column must be an integer / string / binary / date / timestamp column, got {key_type}"
+            )));
+        }
+        let params = index_def.downcast_params::<HashIndexParams>()?;
+        if params.num_buckets == 0 || params.group_size == 0 {
+            return Err(ILError::IndexError(
+                "Hash index bucket count and group size must be greater than 0".to_string(),
+            ));
+        }
         Ok(())
     }
 
     fn builder(&self, index_def: &IndexDefinationRef) -> ILResult<Box<dyn IndexBuilder>> {
-        todo!()
+        Ok(Box::new(HashIndexBuilder::try_new(index_def.clone())?))
+    }
+
+    fn supports_search(
+        &self,
+        _index_def: &IndexDefination,
+        _query: &dyn SearchQuery,
+    ) -> ILResult<bool> {
+        Ok(false)
     }
 
     async fn search(
         &self,
-        index_def: &IndexDefination,
-        index_file: InputFile,
-        query: &dyn SearchQuery,
+        _index_def: &IndexDefination,
+        _index_file: InputFile,
+        _query: &dyn SearchQuery,
     ) -> ILResult<SearchIndexEntries> {
-        Err(ILError::NotSupported(format!(
-            "Hash index does not support search"
-        )))
+        Err(ILError::NotSupported(
+            "Hash index does not support search".to_string(),
+        ))
     }
 
     fn supports_filter(&self, index_def: &IndexDefination, filter: &Expr) -> ILResult<bool> {
-        todo!()
+        Ok(key_values(&index_def.key_columns[0], filter).is_some())
     }
 
     async fn filter(
@@ -52,12 +104,100 @@ impl Index for HashIndex {
         index_file: InputFile,
         filters: &[Expr],
     ) -> ILResult<FilterIndexEntries> {
-        todo!()
+        let params = index_def.downcast_params::<HashIndexParams>()?;
+        let key_field = index_def.key_fields()?[0].clone();
+        // Each filter is satisfied by any of its buckets
+        let filter_buckets = filters
+            .iter()
+            .filter_map(|filter| key_values(key_field.name(), filter))
+            .map(|values| {
+                let mut buckets = values
+                    .into_iter()
+                    .map(|value| hash_scalar(value, key_field.data_type(), params.num_buckets))
+                    .collect::<ILResult<Vec<_>>>()?;
+                buckets.sort_unstable();
+                Ok(buckets)
+            })
+            .collect::<ILResult<Vec<_>>>()?;
+
+        let arrow_reader_builder = ParquetRecordBatchStreamBuilder::new(index_file).await?;
+        let groups = read_groups(arrow_reader_builder.metadata())?;
+        let row_groups = groups
+            .iter()
+            .enumerate()
+            .filter(|(_, (first, last))| {
+                filter_buckets.iter().all(|buckets| {
+                    let idx = buckets.partition_point(|bucket| bucket < first);
+                    buckets.get(idx).is_some_and(|bucket| bucket <= last)
+                })
+            })
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+
+        let mut row_id_arrays: Vec<ArrayRef> = Vec::new();
+        let mut include_arrays: HashMap<String, Vec<ArrayRef>> = HashMap::new();
+        if !row_groups.is_empty() {
+            // Keys are compared again, so values colliding into the bucket are left out
+            let predicate = merge_filters(filters.to_vec());
+            let mut batch_stream = arrow_reader_builder.with_row_groups(row_groups).build()?;
+            while let Some(batch) = batch_stream.try_next().await? {
+                let batch = match &predicate {
+                    Some(predicate) => filter_by_expr(&batch, predicate)?,
+                    None => batch,
+                };
+                row_id_arrays.push(batch.column(0).clone());
+                for col_name in index_def.include_columns.iter() {
+                    let array = batch.column_by_name(col_name).ok_or_else(|| {
+                        ILError::IndexError(format!("Include column {col_name} not found"))
+                    })?;
+                    include_arrays
+                        .entry(col_name.clone())
+                        .or_default()
+                        .push(array.clone());
+                }
+            }
+        }
+
+        let row_ids = if row_id_arrays.is_empty() {
+            Int64Array::from(Vec::<i64>::new())
+        } else {
+            concat_arrays(&row_id_arrays)?
+                .as_primitive::<Int64Type>()
+                .clone()
+        };
+        let mut include_columns = HashMap::new();
+        for field in index_def.include_fields()? {
+            let array = match include_arrays.get(field.name()) {
+                Some(arrays) => concat_arrays(arrays)?,
+                None => new_empty_array(field.data_type()),
+            };
+            include_columns.insert(field.name().clone(), array);
+        }
+
+        Ok(FilterIndexEntries {
+            row_ids,
+            include_columns,
+        })
     }
 }
 
-#[derive(Debug)]
-pub struct HashIndexParams;
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HashIndexParams {
+    /// Number of buckets keys are hashed into. Fewer buckets make distinct keys share a
+    /// bucket more often, which makes lookups read more of the index file.
+    pub num_buckets: u64,
+    /// Number of keys per group, the unit read from the index file.
+    pub group_size: usize,
+}
+
+impl Default for HashIndexParams {
+    fn default() -> Self {
+        Self {
+            num_buckets: 1 << 32,
+            group_size: 1024,
+        }
+    }
+}
 
 impl IndexParams for HashIndexParams {
     fn as_any(&self) -> &dyn std::any::Any {
@@ -65,6 +205,73 @@ impl IndexParams for HashIndexParams {
     }
 
     fn encode(&self) -> ILResult<String> {
-        Ok("NONE".to_string())
+        serde_json::to_string(self)
+            .map_err(|e| ILError::IndexError(format!("Failed to serialize HashIndexParams: {e}")))
+    }
+}
+
+/// Returns the values `filter` compares the key column with for equality, for `key = value`
+/// and `key IN (values)` filters whose values are non-null literals.
+fn key_values<'a>(key_column: &str, filter: &'a Expr) -> Option<Vec<&'a Scalar>> {
+    if let Expr::InList(in_list) = filter {
+        if in_list.negated
+            || !matches!(in_list.expr.as_ref(), Expr::Column(name) if name == key_column)
+        {
+            return None;
+        }
+        return in_list
+            .list
+            .iter()
+            .map(|expr| match expr {
+                Expr::Literal(value) if !value.is_null() => Some(value),
+                _ => None,
+            })
+            .collect();
+    }
+    let Expr::BinaryExpr(binary) = filter else {
+        return None;
+    };
+    if binary.op != BinaryOp::Eq {
+        return None;
+    }
+    let value = match (binary.left.as_ref(), binary.right.as_ref()) {
+        (Expr::Column(name), Expr::Literal(value)) if name == key_column => value,
+        (Expr::Literal(value), Expr::Column(name)) if name == key_column => value,
+        _ => return None,
+    };
+    if value.is_null() {
+        return None;
     }
+    Some(vec![value])
+}
+
+fn read_groups(metadata: &ParquetMetaData) -> ILResult<Vec<(u64, u64)>> {
+    let groups_json = metadata
+        .file_metadata()
+        .key_value_metadata()
+        .and_then(|kvs| kvs.iter().find(|kv| kv.key == GROUPS_METADATA_KEY))
+        .and_then(|kv| kv.value.as_ref())
+        .ok_or_else(|| ILError::IndexError("Hash index file has no groups".to_string()))?;
+    serde_json::from_str(groups_json)
+        .map_err(|e| ILError::IndexError(format!("Failed to parse hash groups: {e}")))
+}
+
+fn filter_by_expr(batch: &RecordBatch, predicate: &Expr) -> ILResult<RecordBatch> {
+    let array = predicate.eval(batch)?.into_array(batch.num_rows())?;
+    let bool_array = array.as_boolean_opt().ok_or_else(|| {
+        ILError::IndexError(format!(
+            "predicate should return BooleanArray, but got {:?}",
+            array.data_type()
+        ))
+    })?;
+    Ok(filter_record_batch(batch, bool_array)?)
+}
+
+fn concat_arrays(arrays: &[ArrayRef]) -> ILResult<ArrayRef> {
+    Ok(arrow::compute::concat(
+        &arrays
+            .iter()
+            .map(|array| array.as_ref())
+            .collect::<Vec<_>>(),
+    )?)
 }
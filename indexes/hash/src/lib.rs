@@ -1,3 +1,6 @@
+mod builder;
+mod hash;
 mod index;
 
+pub use builder::*;
 pub use index::*;
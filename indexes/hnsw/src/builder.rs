@@ -0,0 +1,172 @@
+use std::sync::Arc;
+
+use arrow::{
+    array::{
+        Array, AsArray, FixedSizeListArray, Float32Array, Int64Array, ListBuilder, RecordBatch,
+        UInt32Builder,
+    },
+    datatypes::{DataType, Field, Float32Type, Schema, SchemaRef},
+};
+use indexlake::{
+    ILError, ILResult,
+    catalog::INTERNAL_ROW_ID_FIELD_NAME,
+    index::{IndexBuilder, IndexDefinationRef},
+    storage::OutputFile,
+    utils::extract_row_id_array_from_record_batch,
+};
+use parquet::{
+    arrow::AsyncArrowWriter,
+    file::{metadata::KeyValue, properties::WriterProperties},
+};
+
+use crate::{
+    HnswIndexParams,
+    hnsw::{HnswGraph, random_level},
+};
+
+/// Parquet key-value metadata holding the node the searches of the graph start from.
+pub(crate) const ENTRY_POINT_METADATA_KEY: &str = "indexlake.hnsw.entry_point";
+
+#[derive(Debug, Clone)]
+pub struct HnswIndexBuilder {
+    index_def: IndexDefinationRef,
+    row_ids: Vec<i64>,
+    vectors: Vec<f32>,
+}
+
+impl HnswIndexBuilder {
+    pub fn new(index_def: IndexDefinationRef) -> Self {
+        Self {
+            index_def,
+            row_ids: Vec::new(),
+            vectors: Vec::new(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl IndexBuilder for HnswIndexBuilder {
+    fn update(&mut self, batch: &RecordBatch) -> ILResult<()> {
+        let params = self.index_def.downcast_params::<HnswIndexParams>()?;
+        let row_id_array = extract_row_id_array_from_record_batch(batch)?;
+
+        let key_column_name = &self.index_def.key_columns[0];
+        let key_column = batch.column_by_name(key_column_name).ok_or_else(|| {
+            ILError::IndexError(format!("Key column {key_column_name} not found in batch"))
+        })?;
+        let vector_array = downcast_vector_array(key_column, params.dimension)?;
+
+        // Null vectors are never near anything, they are left out of the graph
+        for (row_id, vector) in row_id_array.values().iter().zip(vector_array.iter()) {
+            let Some(vector) = vector else {
+                continue;
+            };
+            if vector.null_count() > 0 {
+                return Err(ILError::IndexError(format!(
+                    "Vector of row {row_id} has null elements"
+                )));
+            }
+            self.row_ids.push(*row_id);
+            self.vectors
+                .extend_from_slice(vector.as_primitive::<Float32Type>().values());
+        }
+
+        Ok(())
+    }
+
+    async fn write(&mut self, output_file: OutputFile) -> ILResult<()> {
+        let params = self.index_def.downcast_params::<HnswIndexParams>()?;
+
+        let mut graph = HnswGraph::new(params.metric, params.dimension);
+        for (node, row_id) in self.row_ids.iter().enumerate() {
+            let vector = &self.vectors[node * params.dimension..(node + 1) * params.dimension];
+            graph.insert(
+                vector,
+                random_level(*row_id as u64, params.m),
+                params.m,
+                params.ef_construction,
+            );
+        }
+
+        let entry_point_json = serde_json::to_string(&graph.entry_point()).map_err(|e| {
+            ILError::IndexError(format!("Failed to serialize hnsw entry point: {e}"))
+        })?;
+        let writer_properties = WriterProperties::builder()
+            .set_key_value_metadata(Some(vec![KeyValue::new(
+                ENTRY_POINT_METADATA_KEY.to_string(),
+                entry_point_json,
+            )]))
+            .build();
+        let index_schema = index_schema(params.dimension);
+        let mut arrow_writer =
+            AsyncArrowWriter::try_new(output_file, index_schema.clone(), Some(writer_properties))?;
+
+        // Nodes are written in order, the position of a row in the file is its node
+        let mut neighbors_builder = ListBuilder::new(ListBuilder::new(UInt32Builder::new()));
+        for node in 0..graph.len() as u32 {
+            for layer in graph.neighbors(node) {
+                neighbors_builder.values().values().append_slice(layer);
+                neighbors_builder.values().append(true);
+            }
+            neighbors_builder.append(true);
+        }
+        let vector_array = FixedSizeListArray::try_new(
+            vector_item_field(),
+            params.dimension as i32,
+            Arc::new(Float32Array::from(std::mem::take(&mut self.vectors))),
+            None,
+        )?;
+        let batch = RecordBatch::try_new(
+            index_schema,
+            vec![
+                Arc::new(Int64Array::from(std::mem::take(&mut self.row_ids))),
+                Arc::new(vector_array),
+                Arc::new(neighbors_builder.finish()),
+            ],
+        )?;
+        arrow_writer.write(&batch).await?;
+
+        arrow_writer.close().await?;
+
+        Ok(())
+    }
+}
+
+pub(crate) fn downcast_vector_array(
+    array: &dyn Array,
+    dimension: usize,
+) -> ILResult<&FixedSizeListArray> {
+    match array.data_type() {
+        DataType::FixedSizeList(item, size)
+            if item.data_type() == &DataType::Float32 && *size as usize == dimension =>
+        {
+            Ok(array.as_fixed_size_list())
+        }
+        data_type => Err(ILError::IndexError(format!(
+            "Hnsw index key column must be a FixedSizeList<Float32> of {dimension} elements, got {data_type}"
+        ))),
+    }
+}
+
+fn vector_item_field() -> Arc<Field> {
+    Arc::new(Field::new_list_field(DataType::Float32, true))
+}
+
+fn index_schema(dimension: usize) -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new(INTERNAL_ROW_ID_FIELD_NAME, DataType::Int64, false),
+        Field::new(
+            "vector",
+            DataType::FixedSizeList(vector_item_field(), dimension as i32),
+            false,
+        ),
+        Field::new(
+            "neighbors",
+            DataType::List(Arc::new(Field::new_list_field(
+                DataType::List(Arc::new(Field::new_list_field(DataType::UInt32, true))),
+                true,
+            ))),
+            false,
+        ),
+    ]))
+}
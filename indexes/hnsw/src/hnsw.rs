@@ -0,0 +1,319 @@
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{BinaryHeap, HashSet},
+};
+
+use crate::DistanceMetric;
+
+/// Upper bound of node levels, far above the levels reached by billions of nodes.
+const MAX_LEVEL: usize = 16;
+
+/// Hierarchical navigable small world graph. Nodes are numbered in insertion order, every node
+/// links to its nearest neighbors on each layer from 0 up to its level. Searches descend
+/// greedily from the entry point on the top layer and widen on layer 0.
+#[derive(Debug, Clone)]
+pub(crate) struct HnswGraph {
+    metric: DistanceMetric,
+    dimension: usize,
+    /// Vectors of the nodes, laid out one after the other.
+    vectors: Vec<f32>,
+    /// Neighbors of each node on each of its layers.
+    neighbors: Vec<Vec<Vec<u32>>>,
+    entry_point: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Candidate {
+    distance: f32,
+    node: u32,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance
+            .total_cmp(&other.distance)
+            .then(self.node.cmp(&other.node))
+    }
+}
+
+impl HnswGraph {
+    pub(crate) fn new(metric: DistanceMetric, dimension: usize) -> Self {
+        Self {
+            metric,
+            dimension,
+            vectors: Vec::new(),
+            neighbors: Vec::new(),
+            entry_point: None,
+        }
+    }
+
+    pub(crate) fn from_parts(
+        metric: DistanceMetric,
+        dimension: usize,
+        vectors: Vec<f32>,
+        neighbors: Vec<Vec<Vec<u32>>>,
+        entry_point: Option<u32>,
+    ) -> Self {
+        Self {
+            metric,
+            dimension,
+            vectors,
+            neighbors,
+            entry_point,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.neighbors.len()
+    }
+
+    pub(crate) fn vector(&self, node: u32) -> &[f32] {
+        let start = node as usize * self.dimension;
+        &self.vectors[start..start + self.dimension]
+    }
+
+    pub(crate) fn neighbors(&self, node: u32) -> &[Vec<u32>] {
+        &self.neighbors[node as usize]
+    }
+
+    pub(crate) fn entry_point(&self) -> Option<u32> {
+        self.entry_point
+    }
+
+    fn level(&self, node: u32) -> usize {
+        self.neighbors[node as usize].len() - 1
+    }
+
+    fn distance_to(&self, query: &[f32], node: u32) -> f32 {
+        self.metric.distance(query, self.vector(node))
+    }
+
+    /// Adds a node on layers 0 to `level`, linked to its `m` nearest nodes on each layer.
+    pub(crate) fn insert(
+        &mut self,
+        vector: &[f32],
+        level: usize,
+        m: usize,
+        ef_construction: usize,
+    ) {
+        let node = self.len() as u32;
+        self.vectors.extend_from_slice(vector);
+        self.neighbors.push(vec![Vec::new(); level + 1]);
+        let Some(entry_point) = self.entry_point else {
+            self.entry_point = Some(node);
+            return;
+        };
+
+        let entry_level = self.level(entry_point);
+        let mut entry_points = vec![Candidate {
+            distance: self.distance_to(vector, entry_point),
+            node: entry_point,
+        }];
+        for layer in (level + 1..=entry_level).rev() {
+            entry_points = self.search_layer(vector, entry_points, 1, layer);
+        }
+        for layer in (0..=level.min(entry_level)).rev() {
+            let candidates = self.search_layer(vector, entry_points, ef_construction, layer);
+            // Layer 0 holds every node, it gets twice the links to stay well connected
+            let max_links = if layer == 0 { 2 * m } else { m };
+            let selected = candidates
+                .iter()
+                .take(m)
+                .map(|candidate| candidate.node)
+                .collect::<Vec<_>>();
+            for neighbor in selected.iter() {
+                self.neighbors[*neighbor as usize][layer].push(node);
+                if self.neighbors[*neighbor as usize][layer].len() > max_links {
+                    self.prune(*neighbor, layer, max_links);
+                }
+            }
+            self.neighbors[node as usize][layer] = selected;
+            entry_points = candidates;
+        }
+        if level > entry_level {
+            self.entry_point = Some(node);
+        }
+    }
+
+    /// Keeps the `max_links` nearest neighbors of the node on the layer.
+    fn prune(&mut self, node: u32, layer: usize, max_links: usize) {
+        let vector = self.vector(node);
+        let mut candidates = self.neighbors[node as usize][layer]
+            .iter()
+            .map(|neighbor| Candidate {
+                distance: self.metric.distance(vector, self.vector(*neighbor)),
+                node: *neighbor,
+            })
+            .collect::<Vec<_>>();
+        candidates.sort();
+        candidates.truncate(max_links);
+        self.neighbors[node as usize][layer] = candidates
+            .into_iter()
+            .map(|candidate| candidate.node)
+            .collect();
+    }
+
+    /// Returns the nodes nearest to `query` with their distances, nearest first. Up to `ef`
+    /// candidates are kept on layer 0, larger values find more of the true nearest nodes.
+    pub(crate) fn search(&self, query: &[f32], k: usize, ef: usize) -> Vec<(u32, f32)> {
+        let Some(entry_point) = self.entry_point else {
+            return Vec::new();
+        };
+        let mut entry_points = vec![Candidate {
+            distance: self.distance_to(query, entry_point),
+            node: entry_point,
+        }];
+        for layer in (1..=self.level(entry_point)).rev() {
+            entry_points = self.search_layer(query, entry_points, 1, layer);
+        }
+        self.search_layer(query, entry_points, ef.max(k), 0)
+            .into_iter()
+            .take(k)
+            .map(|candidate| (candidate.node, candidate.distance))
+            .collect()
+    }
+
+    /// Best-first search of a layer from the entry points, returning up to `ef` nearest nodes,
+    /// nearest first.
+    fn search_layer(
+        &self,
+        query: &[f32],
+        entry_points: Vec<Candidate>,
+        ef: usize,
+        layer: usize,
+    ) -> Vec<Candidate> {
+        let mut visited = entry_points
+            .iter()
+            .map(|candidate| candidate.node)
+            .collect::<HashSet<_>>();
+        let mut candidates = entry_points
+            .iter()
+            .copied()
+            .map(Reverse)
+            .collect::<BinaryHeap<_>>();
+        let mut results = entry_points.into_iter().collect::<BinaryHeap<_>>();
+        while results.len() > ef {
+            results.pop();
+        }
+
+        while let Some(Reverse(candidate)) = candidates.pop() {
+            let furthest = results.peek().map(|result| result.distance);
+            if results.len() >= ef && furthest.is_some_and(|d| candidate.distance > d) {
+                break;
+            }
+            for neighbor in self.neighbors[candidate.node as usize][layer].iter() {
+                if !visited.insert(*neighbor) {
+                    continue;
+                }
+                let distance = self.distance_to(query, *neighbor);
+                let furthest = results.peek().map(|result| result.distance);
+                if results.len() < ef || furthest.is_some_and(|d| distance < d) {
+                    let candidate = Candidate {
+                        distance,
+                        node: *neighbor,
+                    };
+                    candidates.push(Reverse(candidate));
+                    results.push(candidate);
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+        results.into_sorted_vec()
+    }
+}
+
+/// Level of a node drawn from an exponential distribution seeded by `seed`, so rebuilding the
+/// index of the same rows gives the same graph. One in `m` nodes reaches each next level.
+pub(crate) fn random_level(seed: u64, m: usize) -> usize {
+    // splitmix64
+    let mut x = seed.wrapping_add(0x9E3779B97F4A7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D049BB133111EB);
+    x ^= x >> 31;
+    // Uniform in (0, 1]
+    let uniform = ((x >> 11) + 1) as f64 / (1u64 << 53) as f64;
+    let level = -uniform.ln() / (m as f64).ln();
+    (level as usize).min(MAX_LEVEL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vectors(count: usize, dimension: usize, seed: u64) -> Vec<Vec<f32>> {
+        let mut state = seed;
+        (0..count)
+            .map(|_| {
+                (0..dimension)
+                    .map(|_| {
+                        state = state
+                            .wrapping_mul(6364136223846793005)
+                            .wrapping_add(1442695040888963407);
+                        (state >> 40) as f32 / (1u64 << 24) as f32 - 0.5
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_hnsw_recall() {
+        for metric in [DistanceMetric::L2, DistanceMetric::Cosine] {
+            let data = vectors(1000, 16, 1);
+            let mut graph = HnswGraph::new(metric, 16);
+            for (i, vector) in data.iter().enumerate() {
+                graph.insert(vector, random_level(i as u64, 8), 8, 64);
+            }
+            assert_eq!(graph.len(), 1000);
+
+            let mut found = 0;
+            let queries = vectors(20, 16, 2);
+            for query in queries.iter() {
+                let mut exact = (0..data.len() as u32)
+                    .map(|node| (node, metric.distance(query, &data[node as usize])))
+                    .collect::<Vec<_>>();
+                exact.sort_by(|a, b| a.1.total_cmp(&b.1));
+                let exact = exact[..10]
+                    .iter()
+                    .map(|(node, _)| *node)
+                    .collect::<HashSet<_>>();
+
+                let result = graph.search(query, 10, 64);
+                assert_eq!(result.len(), 10);
+                assert!(result.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+                found += result
+                    .iter()
+                    .filter(|(node, _)| exact.contains(node))
+                    .count();
+            }
+            let recall = found as f64 / (queries.len() * 10) as f64;
+            assert!(recall >= 0.95, "recall {recall} of {metric:?} is too low");
+        }
+    }
+
+    #[test]
+    fn test_random_level() {
+        let levels = (0..10000)
+            .map(|seed| random_level(seed, 16))
+            .collect::<Vec<_>>();
+        let above_zero = levels.iter().filter(|level| **level > 0).count();
+        assert!((400..900).contains(&above_zero));
+        assert_eq!(random_level(42, 16), random_level(42, 16));
+    }
+}
@@ -0,0 +1,229 @@
+use std::{collections::HashMap, sync::Arc};
+
+use arrow::{
+    array::{AsArray, Float64Array, Int64Array},
+    datatypes::{DataType, Float32Type, Int64Type, UInt32Type},
+};
+use futures::TryStreamExt;
+use indexlake::{
+    ILError, ILResult,
+    expr::Expr,
+    index::{
+        FilterIndexEntries, Index, IndexBuilder, IndexDefination, IndexDefinationRef, IndexParams,
+        SearchIndexEntries, SearchQuery,
+    },
+    storage::InputFile,
+};
+use parquet::{arrow::ParquetRecordBatchStreamBuilder, file::metadata::ParquetMetaData};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    DistanceMetric, ENTRY_POINT_METADATA_KEY, HnswIndexBuilder, VectorQuery, downcast_vector_array,
+    hnsw::HnswGraph,
+};
+
+/// Approximate nearest neighbor index on a `FixedSizeList<Float32>` vector column, searched
+/// with [`VectorQuery`]. Each data file gets an index file holding the HNSW graph of its
+/// vectors: the vector and the neighbors of every node on each of its layers. A search loads
+/// the graph and walks it from the entry point kept in the file footer.
+#[derive(Debug, Clone)]
+pub struct HnswIndex;
+
+#[async_trait::async_trait]
+impl Index for HnswIndex {
+    fn kind(&self) -> &str {
+        "hnsw"
+    }
+
+    fn decode_params(&self, value: &str) -> ILResult<Arc<dyn IndexParams>> {
+        let params = serde_json::from_str::<HnswIndexParams>(value)
+            .map_err(|e| ILError::IndexError(format!("Failed to parse HnswIndexParams: {e}")))?;
+        Ok(Arc::new(params))
+    }
+
+    fn supports(&self, index_def: &IndexDefination) -> ILResult<()> {
+        if index_def.key_columns.len() != 1 {
+            return Err(ILError::IndexError(
+                "Hnsw index requires exactly one key column".to_string(),
+            ));
+        }
+        let params = index_def.downcast_params::<HnswIndexParams>()?;
+        if params.dimension == 0 || params.m < 2 || params.ef_construction == 0 {
+            return Err(ILError::IndexError(
+                "Hnsw index dimension and ef_construction must be greater than 0, m at least 2"
+                    .to_string(),
+            ));
+        }
+        let key_field = index_def
+            .table_schema
+            .field_with_name(&index_def.key_columns[0])?;
+        let key_type = key_field.data_type();
+        if !matches!(key_type, DataType::FixedSizeList(item, size)
+            if item.data_type() == &DataType::Float32 && *size as usize == params.dimension)
+        {
+            return Err(ILError::IndexError(format!(
+                "Hnsw index key column must be a FixedSizeList<Float32> of {} elements, got {key_type}",
+                params.dimension
+            )));
+        }
+        if !index_def.include_columns.is_empty() {
+            return Err(ILError::IndexError(
+                "Hnsw index does not support include columns".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn builder(&self, index_def: &IndexDefinationRef) -> ILResult<Box<dyn IndexBuilder>> {
+        Ok(Box::new(HnswIndexBuilder::new(index_def.clone())))
+    }
+
+    fn supports_search(
+        &self,
+        index_def: &IndexDefination,
+        query: &dyn SearchQuery,
+    ) -> ILResult<bool> {
+        let params = index_def.downcast_params::<HnswIndexParams>()?;
+        Ok(query
+            .as_any()
+            .downcast_ref::<VectorQuery>()
+            .is_some_and(|query| {
+                query.metric == params.metric && query.vector.len() == params.dimension
+            }))
+    }
+
+    async fn search(
+        &self,
+        index_def: &IndexDefination,
+        index_file: InputFile,
+        query: &dyn SearchQuery,
+    ) -> ILResult<SearchIndexEntries> {
+        let params = index_def.downcast_params::<HnswIndexParams>()?;
+        let query = query
+            .as_any()
+            .downcast_ref::<VectorQuery>()
+            .ok_or_else(|| {
+                ILError::IndexError(format!("Hnsw index does not support query {query:?}"))
+            })?;
+        if query.metric != params.metric || query.vector.len() != params.dimension {
+            return Err(ILError::IndexError(format!(
+                "Hnsw index of {} dimension {:?} vectors can not answer {} dimension {:?} query",
+                params.dimension,
+                params.metric,
+                query.vector.len(),
+                query.metric
+            )));
+        }
+
+        let arrow_reader_builder = ParquetRecordBatchStreamBuilder::new(index_file).await?;
+        let entry_point = read_entry_point(arrow_reader_builder.metadata())?;
+        let mut row_ids = Vec::new();
+        let mut vectors = Vec::new();
+        let mut neighbors = Vec::new();
+        let mut batch_stream = arrow_reader_builder.build()?;
+        while let Some(batch) = batch_stream.try_next().await? {
+            row_ids.extend_from_slice(batch.column(0).as_primitive::<Int64Type>().values());
+            let vector_array = downcast_vector_array(batch.column(1), params.dimension)?;
+            vectors.extend_from_slice(vector_array.values().as_primitive::<Float32Type>().values());
+            for node_neighbors in batch.column(2).as_list::<i32>().iter() {
+                let node_neighbors = node_neighbors.ok_or_else(|| {
+                    ILError::IndexError("Hnsw index node has null neighbors".to_string())
+                })?;
+                let layers = node_neighbors
+                    .as_list::<i32>()
+                    .iter()
+                    .map(|layer| {
+                        layer
+                            .map(|layer| layer.as_primitive::<UInt32Type>().values().to_vec())
+                            .unwrap_or_default()
+                    })
+                    .collect::<Vec<_>>();
+                neighbors.push(layers);
+            }
+        }
+        let graph = HnswGraph::from_parts(
+            params.metric,
+            params.dimension,
+            vectors,
+            neighbors,
+            entry_point,
+        );
+
+        let (nodes, distances): (Vec<_>, Vec<_>) = graph
+            .search(&query.vector, query.k, params.ef_search)
+            .into_iter()
+            .map(|(node, distance)| (row_ids[node as usize], distance as f64))
+            .unzip();
+
+        Ok(SearchIndexEntries {
+            row_ids: Int64Array::from(nodes),
+            scores: Float64Array::from(distances),
+            score_higher_is_better: false,
+            include_columns: HashMap::new(),
+        })
+    }
+
+    fn supports_filter(&self, _index_def: &IndexDefination, _filter: &Expr) -> ILResult<bool> {
+        Ok(false)
+    }
+
+    async fn filter(
+        &self,
+        _index_def: &IndexDefination,
+        _index_file: InputFile,
+        _filters: &[Expr],
+    ) -> ILResult<FilterIndexEntries> {
+        Err(ILError::NotSupported(
+            "Hnsw index does not support filter".to_string(),
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HnswIndexParams {
+    /// Number of elements of the indexed vectors.
+    pub dimension: usize,
+    /// Metric the graph is built with, only queries with the same metric use the index.
+    pub metric: DistanceMetric,
+    /// Number of neighbors linked to each node, twice as many on layer 0. More links give
+    /// better recall for larger index files and slower builds.
+    pub m: usize,
+    /// Number of candidates considered when linking a node.
+    pub ef_construction: usize,
+    /// Number of candidates considered by searches, at least the `k` of the query.
+    pub ef_search: usize,
+}
+
+impl HnswIndexParams {
+    pub fn new(dimension: usize, metric: DistanceMetric) -> Self {
+        Self {
+            dimension,
+            metric,
+            m: 16,
+            ef_construction: 100,
+            ef_search: 64,
+        }
+    }
+}
+
+impl IndexParams for HnswIndexParams {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn encode(&self) -> ILResult<String> {
+        serde_json::to_string(self)
+            .map_err(|e| ILError::IndexError(format!("Failed to serialize HnswIndexParams: {e}")))
+    }
+}
+
+fn read_entry_point(metadata: &ParquetMetaData) -> ILResult<Option<u32>> {
+    let entry_point_json = metadata
+        .file_metadata()
+        .key_value_metadata()
+        .and_then(|kvs| kvs.iter().find(|kv| kv.key == ENTRY_POINT_METADATA_KEY))
+        .and_then(|kv| kv.value.as_ref())
+        .ok_or_else(|| ILError::IndexError("Hnsw index file has no entry point".to_string()))?;
+    serde_json::from_str(entry_point_json)
+        .map_err(|e| ILError::IndexError(format!("Failed to parse hnsw entry point: {e}")))
+}
@@ -1 +1,8 @@
+mod builder;
+mod hnsw;
+mod index;
+mod query;
 
+pub use builder::*;
+pub use index::*;
+pub use query::*;
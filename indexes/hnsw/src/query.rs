@@ -0,0 +1,64 @@
+use indexlake::index::SearchQuery;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DistanceMetric {
+    /// Euclidean distance.
+    L2,
+    /// One minus the cosine similarity, between 0 and 2. Zero vectors are at distance 1 of
+    /// every vector.
+    Cosine,
+}
+
+impl DistanceMetric {
+    pub fn distance(&self, a: &[f32], b: &[f32]) -> f32 {
+        match self {
+            DistanceMetric::L2 => a
+                .iter()
+                .zip(b.iter())
+                .map(|(x, y)| (x - y) * (x - y))
+                .sum::<f32>()
+                .sqrt(),
+            DistanceMetric::Cosine => {
+                let mut dot = 0.0;
+                let mut norm_a = 0.0;
+                let mut norm_b = 0.0;
+                for (x, y) in a.iter().zip(b.iter()) {
+                    dot += x * y;
+                    norm_a += x * x;
+                    norm_b += y * y;
+                }
+                if norm_a == 0.0 || norm_b == 0.0 {
+                    return 1.0;
+                }
+                1.0 - dot / (norm_a.sqrt() * norm_b.sqrt())
+            }
+        }
+    }
+}
+
+/// Nearest neighbor search on the vector column of an HNSW index. Returns the `k` rows closest
+/// to `vector` by `metric`, closest first. The search is approximate, a close row may be
+/// missed. Only indexes built with the same metric answer the query.
+#[derive(Debug, Clone)]
+pub struct VectorQuery {
+    pub vector: Vec<f32>,
+    pub k: usize,
+    pub metric: DistanceMetric,
+}
+
+impl VectorQuery {
+    pub fn new(vector: Vec<f32>, k: usize, metric: DistanceMetric) -> Self {
+        Self { vector, k, metric }
+    }
+}
+
+impl SearchQuery for VectorQuery {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn limit(&self) -> Option<usize> {
+        Some(self.k)
+    }
+}
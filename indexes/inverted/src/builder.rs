@@ -0,0 +1,124 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use arrow::{
+    array::{AsArray, Int64Array, RecordBatch, StringArray, UInt32Array},
+    compute::cast,
+    datatypes::{DataType, Field, Schema, SchemaRef},
+};
+use indexlake::{
+    ILError, ILResult,
+    catalog::INTERNAL_ROW_ID_FIELD_NAME,
+    index::{IndexBuilder, IndexDefinationRef},
+    storage::OutputFile,
+    utils::extract_row_id_array_from_record_batch,
+};
+use parquet::{
+    arrow::AsyncArrowWriter,
+    file::{metadata::KeyValue, properties::WriterProperties},
+};
+
+use crate::{InvertedIndexParams, Tokenizer};
+
+/// Parquet key-value metadata holding the term range of each row group of the index file.
+pub(crate) const GROUPS_METADATA_KEY: &str = "indexlake.inverted.groups";
+
+/// Postings keyed by term and row id, holding the frequency of the term in the row.
+type Postings = BTreeMap<(String, i64), u32>;
+
+#[derive(Debug, Clone)]
+pub struct InvertedIndexBuilder {
+    index_def: IndexDefinationRef,
+    tokenizer: Arc<dyn Tokenizer>,
+    postings: Postings,
+}
+
+impl InvertedIndexBuilder {
+    pub fn new(index_def: IndexDefinationRef, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        Self {
+            index_def,
+            tokenizer,
+            postings: BTreeMap::new(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl IndexBuilder for InvertedIndexBuilder {
+    fn update(&mut self, batch: &RecordBatch) -> ILResult<()> {
+        let row_id_array = extract_row_id_array_from_record_batch(batch)?;
+
+        let key_column_name = &self.index_def.key_columns[0];
+        let key_column = batch.column_by_name(key_column_name).ok_or_else(|| {
+            ILError::IndexError(format!("Key column {key_column_name} not found in batch"))
+        })?;
+        let key_column = cast(key_column, &DataType::LargeUtf8)?;
+
+        // Null texts hold no terms, they are left out of the index
+        for (row_id, text) in row_id_array
+            .values()
+            .iter()
+            .zip(key_column.as_string::<i64>().iter())
+        {
+            let Some(text) = text else {
+                continue;
+            };
+            for term in self.tokenizer.tokenize(text) {
+                *self.postings.entry((term, *row_id)).or_default() += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn write(&mut self, output_file: OutputFile) -> ILResult<()> {
+        let params = self.index_def.downcast_params::<InvertedIndexParams>()?;
+        let postings = std::mem::take(&mut self.postings)
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        let groups = postings
+            .chunks(params.group_size)
+            .map(|chunk| (chunk[0].0.0.clone(), chunk[chunk.len() - 1].0.0.clone()))
+            .collect::<Vec<_>>();
+        let groups_json = serde_json::to_string(&groups).map_err(|e| {
+            ILError::IndexError(format!("Failed to serialize inverted index groups: {e}"))
+        })?;
+
+        let writer_properties = WriterProperties::builder()
+            .set_max_row_group_size(params.group_size)
+            .set_key_value_metadata(Some(vec![KeyValue::new(
+                GROUPS_METADATA_KEY.to_string(),
+                groups_json,
+            )]))
+            .build();
+        let index_schema = index_schema();
+        let mut arrow_writer =
+            AsyncArrowWriter::try_new(output_file, index_schema.clone(), Some(writer_properties))?;
+
+        // Each group is flushed as its own row group, so groups can be read on their own
+        for chunk in postings.chunks(params.group_size) {
+            let terms = StringArray::from_iter_values(chunk.iter().map(|((term, _), _)| term));
+            let row_ids =
+                Int64Array::from_iter_values(chunk.iter().map(|((_, row_id), _)| *row_id));
+            let frequencies = UInt32Array::from_iter_values(chunk.iter().map(|(_, tf)| *tf));
+            let batch = RecordBatch::try_new(
+                index_schema.clone(),
+                vec![Arc::new(terms), Arc::new(row_ids), Arc::new(frequencies)],
+            )?;
+            arrow_writer.write(&batch).await?;
+            arrow_writer.flush().await?;
+        }
+
+        arrow_writer.close().await?;
+
+        Ok(())
+    }
+}
+
+fn index_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("term", DataType::Utf8, false),
+        Field::new(INTERNAL_ROW_ID_FIELD_NAME, DataType::Int64, false),
+        Field::new("term_frequency", DataType::UInt32, false),
+    ]))
+}
@@ -0,0 +1,252 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::Arc,
+};
+
+use arrow::{
+    array::{AsArray, Float64Array, Int64Array},
+    datatypes::{DataType, Int64Type, UInt32Type},
+};
+use futures::TryStreamExt;
+use indexlake::{
+    ILError, ILResult,
+    expr::Expr,
+    index::{
+        FilterIndexEntries, Index, IndexBuilder, IndexDefination, IndexDefinationRef, IndexParams,
+        SearchIndexEntries, SearchQuery,
+    },
+    storage::InputFile,
+};
+use parquet::{arrow::ParquetRecordBatchStreamBuilder, file::metadata::ParquetMetaData};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    GROUPS_METADATA_KEY, InvertedIndexBuilder, MatchMode, MatchQuery, Tokenizer,
+    WhitespaceTokenizer,
+};
+
+/// Full-text index on a string column, searched with [`MatchQuery`]. Each data file gets an
+/// index file holding the postings of its terms ordered by term, split into groups whose term
+/// ranges are kept in the file footer. A search only reads the groups covering its terms.
+///
+/// Tokenizers are registered by name with [`InvertedIndex::with_tokenizer`], the index params
+/// name the one used, `whitespace` by default.
+#[derive(Debug, Clone)]
+pub struct InvertedIndex {
+    tokenizers: HashMap<String, Arc<dyn Tokenizer>>,
+}
+
+impl InvertedIndex {
+    pub fn new() -> Self {
+        Self {
+            tokenizers: HashMap::new(),
+        }
+        .with_tokenizer("whitespace", Arc::new(WhitespaceTokenizer))
+    }
+
+    pub fn with_tokenizer(
+        mut self,
+        name: impl Into<String>,
+        tokenizer: Arc<dyn Tokenizer>,
+    ) -> Self {
+        self.tokenizers.insert(name.into(), tokenizer);
+        self
+    }
+
+    fn tokenizer(&self, index_def: &IndexDefination) -> ILResult<Arc<dyn Tokenizer>> {
+        let params = index_def.downcast_params::<InvertedIndexParams>()?;
+        self.tokenizers
+            .get(&params.tokenizer)
+            .cloned()
+            .ok_or_else(|| {
+                ILError::IndexError(format!("Tokenizer {} is not registered", params.tokenizer))
+            })
+    }
+}
+
+impl Default for InvertedIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl Index for InvertedIndex {
+    fn kind(&self) -> &str {
+        "inverted"
+    }
+
+    fn decode_params(&self, value: &str) -> ILResult<Arc<dyn IndexParams>> {
+        let params = serde_json::from_str::<InvertedIndexParams>(value).map_err(|e| {
+            ILError::IndexError(format!("Failed to parse InvertedIndexParams: {e}"))
+        })?;
+        Ok(Arc::new(params))
+    }
+
+    fn supports(&self, index_def: &IndexDefination) -> ILResult<()> {
+        if index_def.key_columns.len() != 1 {
+            return Err(ILError::IndexError(
+                "Inverted index requires exactly one key column".to_string(),
+            ));
+        }
+        let key_field = index_def
+            .table_schema
+            .field_with_name(&index_def.key_columns[0])?;
+        if !matches!(key_field.data_type(), DataType::Utf8 | DataType::LargeUtf8) {
+            return Err(ILError::IndexError(format!(
+                "Inverted index key column must be a string / large string column, got {}",
+                key_field.data_type()
+            )));
+        }
+        if !index_def.include_columns.is_empty() {
+            return Err(ILError::IndexError(
+                "Inverted index does not support include columns".to_string(),
+            ));
+        }
+        let params = index_def.downcast_params::<InvertedIndexParams>()?;
+        if params.group_size == 0 {
+            return Err(ILError::IndexError(
+                "Inverted index group size must be greater than 0".to_string(),
+            ));
+        }
+        self.tokenizer(index_def)?;
+        Ok(())
+    }
+
+    fn builder(&self, index_def: &IndexDefinationRef) -> ILResult<Box<dyn IndexBuilder>> {
+        Ok(Box::new(InvertedIndexBuilder::new(
+            index_def.clone(),
+            self.tokenizer(index_def)?,
+        )))
+    }
+
+    fn supports_search(
+        &self,
+        _index_def: &IndexDefination,
+        query: &dyn SearchQuery,
+    ) -> ILResult<bool> {
+        Ok(query.as_any().is::<MatchQuery>())
+    }
+
+    async fn search(
+        &self,
+        index_def: &IndexDefination,
+        index_file: InputFile,
+        query: &dyn SearchQuery,
+    ) -> ILResult<SearchIndexEntries> {
+        let query = query.as_any().downcast_ref::<MatchQuery>().ok_or_else(|| {
+            ILError::IndexError(format!("Inverted index does not support query {query:?}"))
+        })?;
+        let tokenizer = self.tokenizer(index_def)?;
+        let terms = query
+            .terms
+            .iter()
+            .flat_map(|term| tokenizer.tokenize(term))
+            .collect::<BTreeSet<_>>();
+
+        // Number of distinct query terms and summed term frequencies of each row
+        let mut matches: HashMap<i64, (usize, u64)> = HashMap::new();
+        if !terms.is_empty() {
+            let arrow_reader_builder = ParquetRecordBatchStreamBuilder::new(index_file).await?;
+            let groups = read_groups(arrow_reader_builder.metadata())?;
+            let row_groups = groups
+                .iter()
+                .enumerate()
+                .filter(|(_, (first, last))| {
+                    terms.range(first.clone()..=last.clone()).next().is_some()
+                })
+                .map(|(idx, _)| idx)
+                .collect::<Vec<_>>();
+            if !row_groups.is_empty() {
+                let mut batch_stream = arrow_reader_builder.with_row_groups(row_groups).build()?;
+                while let Some(batch) = batch_stream.try_next().await? {
+                    let term_array = batch.column(0).as_string::<i32>();
+                    let row_id_array = batch.column(1).as_primitive::<Int64Type>();
+                    let tf_array = batch.column(2).as_primitive::<UInt32Type>();
+                    for offset in 0..batch.num_rows() {
+                        if terms.contains(term_array.value(offset)) {
+                            let entry = matches.entry(row_id_array.value(offset)).or_default();
+                            entry.0 += 1;
+                            entry.1 += tf_array.value(offset) as u64;
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut row_ids = Vec::new();
+        let mut scores = Vec::new();
+        for (row_id, (matched_terms, tf)) in matches {
+            let matched = match query.mode {
+                MatchMode::AnyOf => matched_terms > 0,
+                MatchMode::AllOf => matched_terms == terms.len(),
+            };
+            if matched {
+                row_ids.push(row_id);
+                scores.push(tf as f64);
+            }
+        }
+
+        Ok(SearchIndexEntries {
+            row_ids: Int64Array::from(row_ids),
+            scores: Float64Array::from(scores),
+            score_higher_is_better: true,
+            include_columns: HashMap::new(),
+        })
+    }
+
+    fn supports_filter(&self, _index_def: &IndexDefination, _filter: &Expr) -> ILResult<bool> {
+        Ok(false)
+    }
+
+    async fn filter(
+        &self,
+        _index_def: &IndexDefination,
+        _index_file: InputFile,
+        _filters: &[Expr],
+    ) -> ILResult<FilterIndexEntries> {
+        Err(ILError::NotSupported(
+            "Inverted index does not support filter".to_string(),
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvertedIndexParams {
+    /// Name of the tokenizer registered with [`InvertedIndex::with_tokenizer`].
+    pub tokenizer: String,
+    /// Number of postings per group, the unit read from the index file.
+    pub group_size: usize,
+}
+
+impl Default for InvertedIndexParams {
+    fn default() -> Self {
+        Self {
+            tokenizer: "whitespace".to_string(),
+            group_size: 1024,
+        }
+    }
+}
+
+impl IndexParams for InvertedIndexParams {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn encode(&self) -> ILResult<String> {
+        serde_json::to_string(self).map_err(|e| {
+            ILError::IndexError(format!("Failed to serialize InvertedIndexParams: {e}"))
+        })
+    }
+}
+
+fn read_groups(metadata: &ParquetMetaData) -> ILResult<Vec<(String, String)>> {
+    let groups_json = metadata
+        .file_metadata()
+        .key_value_metadata()
+        .and_then(|kvs| kvs.iter().find(|kv| kv.key == GROUPS_METADATA_KEY))
+        .and_then(|kv| kv.value.as_ref())
+        .ok_or_else(|| ILError::IndexError("Inverted index file has no groups".to_string()))?;
+    serde_json::from_str(groups_json)
+        .map_err(|e| ILError::IndexError(format!("Failed to parse inverted index groups: {e}")))
+}
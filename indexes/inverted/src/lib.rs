@@ -0,0 +1,9 @@
+mod builder;
+mod index;
+mod query;
+mod tokenizer;
+
+pub use builder::*;
+pub use index::*;
+pub use query::*;
+pub use tokenizer::*;
@@ -0,0 +1,43 @@
+use indexlake::index::SearchQuery;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchMode {
+    /// Rows containing at least one of the terms.
+    AnyOf,
+    /// Rows containing every term.
+    AllOf,
+}
+
+/// Keyword search on the column of an inverted index. Terms are tokenized with the tokenizer
+/// of the index, matching rows are ranked by the summed frequencies of the terms they hold.
+#[derive(Debug, Clone)]
+pub struct MatchQuery {
+    pub terms: Vec<String>,
+    pub mode: MatchMode,
+    pub limit: Option<usize>,
+}
+
+impl MatchQuery {
+    pub fn new(terms: Vec<String>, mode: MatchMode) -> Self {
+        Self {
+            terms,
+            mode,
+            limit: None,
+        }
+    }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+impl SearchQuery for MatchQuery {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+}
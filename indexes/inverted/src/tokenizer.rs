@@ -0,0 +1,36 @@
+use std::fmt::Debug;
+
+/// Splits text into the terms indexed and looked up by the inverted index. A tokenizer must
+/// stay the same for the lifetime of an index, as index files keep the terms it produced.
+pub trait Tokenizer: Debug + Send + Sync {
+    fn tokenize(&self, text: &str) -> Vec<String>;
+}
+
+/// Splits on whitespace, strips leading and trailing punctuation and lowercases. Stop words
+/// are kept, so every word of a text can be matched.
+#[derive(Debug, Clone, Default)]
+pub struct WhitespaceTokenizer;
+
+impl Tokenizer for WhitespaceTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<String> {
+        text.split_whitespace()
+            .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()))
+            .filter(|word| !word.is_empty())
+            .map(|word| word.to_lowercase())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_whitespace_tokenizer() {
+        assert_eq!(
+            WhitespaceTokenizer.tokenize("The quick, brown Fox -- jumps!"),
+            vec!["the", "quick", "brown", "fox", "jumps"]
+        );
+        assert!(WhitespaceTokenizer.tokenize("  ... ").is_empty());
+    }
+}
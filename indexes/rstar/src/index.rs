@@ -60,6 +60,14 @@ impl Index for RStarIndex {
         Ok(Box::new(RStarIndexBuilder::try_new(index_def.clone())?))
     }
 
+    fn supports_search(
+        &self,
+        _index_def: &IndexDefination,
+        _query: &dyn SearchQuery,
+    ) -> ILResult<bool> {
+        Ok(false)
+    }
+
     async fn search(
         &self,
         _index_def: &IndexDefination,
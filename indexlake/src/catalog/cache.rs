@@ -0,0 +1,270 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use futures::{StreamExt, TryStreamExt};
+use log::debug;
+
+use crate::{
+    ILResult,
+    catalog::{Catalog, CatalogDatabase, CatalogHealth, CatalogSchemaRef, IsolationLevel},
+    catalog::{PoolStatus, RetryPolicy, Row, RowStream, Scalar, Transaction},
+};
+
+/// Catalog tables holding table definitions, which only change through DDL operations.
+const METADATA_TABLES: [&str; 4] = [
+    "indexlake_namespace",
+    "indexlake_table",
+    "indexlake_field",
+    "indexlake_index",
+];
+
+/// Catalog wrapper that memoizes queries on table definitions (namespaces, tables, fields and
+/// indexes) for `ttl`, so loading a table does not cost catalog round trips every time.
+///
+/// Queries on row metadata, inline rows and data files always go to the inner catalog, as well
+/// as all queries within transactions. Committing a transaction that modified table
+/// definitions through this catalog invalidates the cache. Changes made by other processes are
+/// observed once cached entries expire.
+#[derive(Debug, Clone)]
+pub struct CachedCatalog {
+    inner: Arc<dyn Catalog>,
+    cache: Arc<MetadataCache>,
+}
+
+impl CachedCatalog {
+    pub fn new(inner: Arc<dyn Catalog>, ttl: Duration) -> Self {
+        Self {
+            inner,
+            cache: Arc::new(MetadataCache {
+                ttl,
+                state: Mutex::new(CacheState::default()),
+            }),
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.cache.ttl
+    }
+
+    /// Drops all cached entries, e.g. after table definitions were changed by another process.
+    pub fn invalidate(&self) {
+        self.cache.invalidate();
+    }
+}
+
+#[derive(Debug)]
+struct MetadataCache {
+    ttl: Duration,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: HashMap<String, CacheEntry>,
+    // Bumped on invalidation so queries started before it do not cache stale rows
+    generation: u64,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    rows: Vec<Row>,
+    expires_at: Instant,
+}
+
+impl MetadataCache {
+    fn get(&self, sql: &str) -> Result<Vec<Row>, u64> {
+        let mut state = self.state.lock().unwrap();
+        match state.entries.get(sql) {
+            Some(entry) if entry.expires_at > Instant::now() => Ok(entry.rows.clone()),
+            Some(_) => {
+                state.entries.remove(sql);
+                Err(state.generation)
+            }
+            None => Err(state.generation),
+        }
+    }
+
+    fn put(&self, sql: &str, rows: Vec<Row>, generation: u64) {
+        let mut state = self.state.lock().unwrap();
+        if state.generation == generation {
+            state.entries.insert(
+                sql.to_string(),
+                CacheEntry {
+                    rows,
+                    expires_at: Instant::now() + self.ttl,
+                },
+            );
+        }
+    }
+
+    fn invalidate(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.entries.clear();
+        state.generation += 1;
+    }
+}
+
+fn referenced_catalog_tables(sql: &str) -> impl Iterator<Item = &str> {
+    sql.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .filter(|token| token.starts_with("indexlake_"))
+}
+
+fn is_cacheable_query(sql: &str) -> bool {
+    let is_select = sql
+        .trim_start()
+        .get(..6)
+        .is_some_and(|keyword| keyword.eq_ignore_ascii_case("SELECT"));
+    let mut tables = referenced_catalog_tables(sql).peekable();
+    is_select && tables.peek().is_some() && tables.all(|table| METADATA_TABLES.contains(&table))
+}
+
+fn modifies_metadata(sql: &str) -> bool {
+    referenced_catalog_tables(sql).any(|table| METADATA_TABLES.contains(&table))
+}
+
+#[async_trait::async_trait]
+impl Catalog for CachedCatalog {
+    fn database(&self) -> CatalogDatabase {
+        self.inner.database()
+    }
+
+    async fn query(&self, sql: &str, schema: CatalogSchemaRef) -> ILResult<RowStream<'static>> {
+        if !is_cacheable_query(sql) {
+            return self.inner.query(sql, schema).await;
+        }
+        let generation = match self.cache.get(sql) {
+            Ok(rows) => {
+                debug!("catalog cache hit: {sql}");
+                return Ok(Box::pin(futures::stream::iter(rows).map(Ok)));
+            }
+            Err(generation) => generation,
+        };
+        let rows = self
+            .inner
+            .query(sql, schema)
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+        self.cache.put(sql, rows.clone(), generation);
+        Ok(Box::pin(futures::stream::iter(rows).map(Ok)))
+    }
+
+    async fn transaction(&self) -> ILResult<Box<dyn Transaction>> {
+        let inner = self.inner.transaction().await?;
+        Ok(Box::new(CachedTransaction::new(inner, self.cache.clone())))
+    }
+
+    async fn begin_transaction(&self, level: IsolationLevel) -> ILResult<Box<dyn Transaction>> {
+        let inner = self.inner.begin_transaction(level).await?;
+        Ok(Box::new(CachedTransaction::new(inner, self.cache.clone())))
+    }
+
+    fn transaction_retry_policy(&self) -> Option<RetryPolicy> {
+        self.inner.transaction_retry_policy()
+    }
+
+    async fn health_check(&self) -> ILResult<CatalogHealth> {
+        self.inner.health_check().await
+    }
+
+    fn pool_status(&self) -> Option<PoolStatus> {
+        self.inner.pool_status()
+    }
+
+    fn read_only(&self) -> bool {
+        self.inner.read_only()
+    }
+}
+
+#[derive(Debug)]
+struct CachedTransaction {
+    inner: Box<dyn Transaction>,
+    cache: Arc<MetadataCache>,
+    modifies_metadata: bool,
+}
+
+impl CachedTransaction {
+    fn new(inner: Box<dyn Transaction>, cache: Arc<MetadataCache>) -> Self {
+        Self {
+            inner,
+            cache,
+            modifies_metadata: false,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Transaction for CachedTransaction {
+    async fn query<'a>(
+        &'a mut self,
+        sql: &str,
+        schema: CatalogSchemaRef,
+    ) -> ILResult<RowStream<'a>> {
+        self.inner.query(sql, schema).await
+    }
+
+    async fn execute(&mut self, sql: &str) -> ILResult<usize> {
+        self.modifies_metadata |= modifies_metadata(sql);
+        self.inner.execute(sql).await
+    }
+
+    async fn execute_batch(&mut self, sqls: &[String]) -> ILResult<()> {
+        self.modifies_metadata |= sqls.iter().any(|sql| modifies_metadata(sql));
+        self.inner.execute_batch(sqls).await
+    }
+
+    async fn insert_rows(
+        &mut self,
+        database: CatalogDatabase,
+        table_name: &str,
+        columns: &[String],
+        rows: &[Vec<Scalar>],
+        batch_size: usize,
+    ) -> ILResult<usize> {
+        self.modifies_metadata |= METADATA_TABLES.contains(&table_name);
+        self.inner
+            .insert_rows(database, table_name, columns, rows, batch_size)
+            .await
+    }
+
+    async fn commit(&mut self) -> ILResult<()> {
+        let result = self.inner.commit().await;
+        // The outcome of a failed commit is unknown, so invalidate in any case
+        if self.modifies_metadata {
+            self.cache.invalidate();
+        }
+        result
+    }
+
+    async fn rollback(&mut self) -> ILResult<()> {
+        self.inner.rollback().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_cacheable_query() {
+        assert!(is_cacheable_query(
+            "SELECT table_id FROM indexlake_table WHERE table_name = 'test_table'"
+        ));
+        assert!(is_cacheable_query(
+            "select field_id from indexlake_field where table_id = 1"
+        ));
+        assert!(!is_cacheable_query(
+            "SELECT COUNT(1) FROM indexlake_inline_row_1"
+        ));
+        assert!(!is_cacheable_query(
+            "SELECT data_file_id FROM indexlake_data_file WHERE table_id = 1"
+        ));
+        assert!(!is_cacheable_query(
+            "UPDATE indexlake_table SET table_name = 'a' WHERE table_id = 1"
+        ));
+        assert!(!is_cacheable_query("SELECT 1"));
+    }
+}
@@ -2,6 +2,8 @@
 pub enum CatalogDatabase {
     Sqlite,
     Postgres,
+    MySql,
+    DuckDb,
 }
 
 impl CatalogDatabase {
@@ -9,6 +11,8 @@ impl CatalogDatabase {
         match self {
             CatalogDatabase::Sqlite => format!("`{}`", ident),
             CatalogDatabase::Postgres => format!("\"{}\"", ident),
+            CatalogDatabase::MySql => format!("`{ident}`"),
+            CatalogDatabase::DuckDb => format!("\"{ident}\""),
         }
     }
 
@@ -16,6 +20,26 @@ impl CatalogDatabase {
         match self {
             CatalogDatabase::Sqlite => format!("X'{}'", hex::encode(value)),
             CatalogDatabase::Postgres => format!("E'\\\\x{}'", hex::encode(value)),
+            CatalogDatabase::MySql => format!("X'{}'", hex::encode(value)),
+            CatalogDatabase::DuckDb => format!(
+                "'{}'::BLOB",
+                value
+                    .iter()
+                    .map(|byte| format!("\\x{byte:02X}"))
+                    .collect::<String>()
+            ),
+        }
+    }
+
+    /// Locking clause appended to the id allocation queries inside a transaction.
+    ///
+    /// MySQL's default REPEATABLE READ isolation serves plain `SELECT`s from the transaction
+    /// snapshot, so concurrent transactions would allocate the same id. A locking read sees the
+    /// latest committed rows and blocks other writers until commit.
+    pub(crate) fn sql_for_update(&self) -> &'static str {
+        match self {
+            CatalogDatabase::Sqlite | CatalogDatabase::Postgres | CatalogDatabase::DuckDb => "",
+            CatalogDatabase::MySql => " FOR UPDATE",
         }
     }
 }
@@ -25,6 +49,8 @@ impl std::fmt::Display for CatalogDatabase {
         match self {
             CatalogDatabase::Sqlite => write!(f, "SQLite"),
             CatalogDatabase::Postgres => write!(f, "Postgres"),
+            CatalogDatabase::MySql => write!(f, "MySQL"),
+            CatalogDatabase::DuckDb => write!(f, "DuckDB"),
         }
     }
 }
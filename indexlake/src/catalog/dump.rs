@@ -0,0 +1,414 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow::datatypes::{Field, Fields};
+use futures::TryStreamExt;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ILError, ILResult,
+    catalog::{
+        CATALOG_VERSION, Catalog, CatalogDataType, CatalogDatabase, CatalogSchema, Column,
+        FIELD_DROPPED_METADATA_KEY, INTERNAL_ROW_ID_FIELD_NAME, Row, Scalar, Transaction,
+        TransactionHelper, decode_data_type, get_catalog_version, migrate_catalog,
+    },
+};
+
+const DUMP_INSERT_BATCH_SIZE: usize = 1000;
+
+/// Self-contained copy of the catalog metadata: namespaces, tables, fields, indexes, data
+/// files, snapshots, row history, row metadata and inline rows. Data files in storage are not
+/// part of the dump.
+///
+/// The dump is a copy of the catalog tables and does not depend on the database it was
+/// exported from, so it can be imported into a catalog of any backend with the same
+/// [`CATALOG_VERSION`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogDump {
+    /// Catalog schema version of the exported catalog.
+    pub catalog_version: i64,
+    pub tables: Vec<CatalogTableDump>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogTableDump {
+    pub name: String,
+    pub columns: Vec<Column>,
+    pub rows: Vec<Vec<Scalar>>,
+}
+
+impl CatalogDump {
+    pub fn to_json(&self) -> ILResult<String> {
+        serde_json::to_string(self)
+            .map_err(|e| ILError::InternalError(format!("Failed to serialize catalog dump: {e}")))
+    }
+
+    pub fn from_json(json: &str) -> ILResult<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| ILError::InvalidInput(format!("Failed to parse catalog dump: {e}")))
+    }
+
+    fn table(&self, name: &str) -> Option<&CatalogTableDump> {
+        self.tables.iter().find(|table| table.name == name)
+    }
+}
+
+/// Catalog tables with a fixed schema. `indexlake_dump_task` only holds in-flight dump task
+/// markers and is left out.
+fn metadata_tables() -> Vec<(&'static str, CatalogSchema)> {
+    use CatalogDataType::*;
+    let schema = |columns: &[(&str, CatalogDataType)]| {
+        CatalogSchema::new(
+            columns
+                .iter()
+                .map(|(name, data_type)| Column::new(*name, data_type.clone(), false))
+                .collect(),
+        )
+    };
+    vec![
+        (
+            "indexlake_namespace",
+            CatalogSchema::new(vec![
+                Column::new("namespace_id", Int64, false),
+                Column::new("namespace_name", Utf8, false),
+                Column::new("properties", Utf8, true),
+            ]),
+        ),
+        (
+            "indexlake_table",
+            CatalogSchema::new(vec![
+                Column::new("table_id", Int64, false),
+                Column::new("table_name", Utf8, false),
+                Column::new("namespace_id", Int64, false),
+                Column::new("config", Utf8, false),
+                Column::new("schema_version", Int64, true),
+            ]),
+        ),
+        (
+            "indexlake_field",
+            schema(&[
+                ("field_id", Int64),
+                ("table_id", Int64),
+                ("field_name", Utf8),
+                ("data_type", Utf8),
+                ("nullable", Boolean),
+                ("metadata", Utf8),
+            ]),
+        ),
+        (
+            "indexlake_data_file",
+            CatalogSchema::new(vec![
+                Column::new("data_file_id", Int64, false),
+                Column::new("table_id", Int64, false),
+                Column::new("relative_path", Utf8, false),
+                Column::new("file_size_bytes", Int64, false),
+                Column::new("record_count", Int64, false),
+                Column::new("row_ids", Binary, false),
+                Column::new("partition_values", Utf8, true),
+                Column::new("checksum", Int64, true),
+                Column::new("column_stats", Utf8, true),
+            ]),
+        ),
+        (
+            "indexlake_index",
+            CatalogSchema::new(vec![
+                Column::new("index_id", Int64, false),
+                Column::new("table_id", Int64, false),
+                Column::new("index_name", Utf8, false),
+                Column::new("index_kind", Utf8, false),
+                Column::new("key_field_ids", Utf8, false),
+                Column::new("include_field_ids", Utf8, false),
+                Column::new("params", Utf8, false),
+                Column::new("where_predicate", Utf8, true),
+            ]),
+        ),
+        (
+            "indexlake_index_file",
+            CatalogSchema::new(vec![
+                Column::new("index_file_id", Int64, false),
+                Column::new("index_id", Int64, false),
+                Column::new("data_file_id", Int64, false),
+                Column::new("relative_path", Utf8, false),
+                Column::new("metadata", Binary, true),
+            ]),
+        ),
+        (
+            "indexlake_snapshot",
+            CatalogSchema::new(vec![
+                Column::new("snapshot_id", Int64, false),
+                Column::new("table_id", Int64, false),
+                Column::new("timestamp_ms", Int64, false),
+                Column::new("max_row_id", Int64, false),
+                Column::new("operation", Utf8, true),
+                Column::new("added_rows", Int64, true),
+                Column::new("updated_rows", Int64, true),
+                Column::new("removed_rows", Int64, true),
+                Column::new("expired", Boolean, true),
+            ]),
+        ),
+        (
+            "indexlake_row_history",
+            CatalogSchema::new(vec![
+                Column::new("table_id", Int64, false),
+                Column::new("row_id", Int64, false),
+                Column::new("end_snapshot_id", Int64, false),
+                Column::new("location", Utf8, false),
+                Column::new("row_values", Binary, true),
+            ]),
+        ),
+    ]
+}
+
+fn row_metadata_schema() -> CatalogSchema {
+    CatalogSchema::new(vec![
+        Column::new(INTERNAL_ROW_ID_FIELD_NAME, CatalogDataType::Int64, false),
+        Column::new("location", CatalogDataType::Utf8, false),
+        Column::new("deleted", CatalogDataType::Boolean, false),
+    ])
+}
+
+/// Columns of the inline row tables, dropped fields are no longer part of them.
+fn inline_row_fields(field_table: &CatalogTableDump) -> ILResult<HashMap<i64, Vec<Field>>> {
+    let schema = Arc::new(CatalogSchema::new(field_table.columns.clone()));
+    let mut table_fields: HashMap<i64, Vec<Field>> = HashMap::new();
+    for values in &field_table.rows {
+        let row = Row::new(schema.clone(), values.clone());
+        let table_id = row.int64(1)?.expect("table_id is not null");
+        let field_name = row.utf8(2)?.expect("field_name is not null");
+        let data_type = decode_data_type(row.utf8(3)?.expect("data_type is not null"))?;
+        let nullable = row.boolean(4)?.expect("nullable is not null");
+        let metadata: HashMap<String, String> =
+            serde_json::from_str(row.utf8(5)?.expect("metadata is not null")).map_err(|e| {
+                ILError::InternalError(format!("Failed to deserialize field metadata: {e:?}"))
+            })?;
+        if metadata.contains_key(FIELD_DROPPED_METADATA_KEY) {
+            continue;
+        }
+        table_fields
+            .entry(table_id)
+            .or_default()
+            .push(Field::new(field_name, data_type, nullable));
+    }
+    Ok(table_fields)
+}
+
+fn inline_row_schema(fields: &[Field]) -> ILResult<CatalogSchema> {
+    let mut columns = vec![Column::new(
+        INTERNAL_ROW_ID_FIELD_NAME,
+        CatalogDataType::Int64,
+        false,
+    )];
+    for field in fields {
+        columns.push(Column::new(
+            field.name().clone(),
+            CatalogDataType::from_arrow(field.data_type())?,
+            field.is_nullable(),
+        ));
+    }
+    Ok(CatalogSchema::new(columns))
+}
+
+fn table_ids(dump_table: &CatalogTableDump) -> ILResult<Vec<i64>> {
+    let schema = Arc::new(CatalogSchema::new(dump_table.columns.clone()));
+    dump_table
+        .rows
+        .iter()
+        .map(|values| {
+            Ok(Row::new(schema.clone(), values.clone())
+                .int64(0)?
+                .expect("table_id is not null"))
+        })
+        .collect()
+}
+
+async fn dump_table(
+    transaction: &mut dyn Transaction,
+    database: CatalogDatabase,
+    name: String,
+    schema: CatalogSchema,
+) -> ILResult<CatalogTableDump> {
+    let sql = format!(
+        "SELECT {} FROM {name} ORDER BY {}",
+        schema.select_items(database).join(", "),
+        database.sql_identifier(&schema.columns[0].name)
+    );
+    let rows = transaction
+        .query(&sql, Arc::new(schema.clone()))
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    Ok(CatalogTableDump {
+        name,
+        columns: schema.columns,
+        rows: rows.into_iter().map(|row| row.values).collect(),
+    })
+}
+
+pub(crate) async fn export_catalog_dump<C: Catalog + ?Sized>(catalog: &C) -> ILResult<CatalogDump> {
+    let catalog_version = get_catalog_version(catalog).await?;
+    if catalog_version != CATALOG_VERSION {
+        return Err(ILError::CatalogError(format!(
+            "catalog version {catalog_version} does not match the version {CATALOG_VERSION} supported by this library, migrate the catalog first"
+        )));
+    }
+
+    let database = catalog.database();
+    let mut transaction = catalog.transaction().await?;
+    let mut tables = Vec::new();
+    for (name, schema) in metadata_tables() {
+        tables.push(dump_table(transaction.as_mut(), database, name.to_string(), schema).await?);
+    }
+
+    let table_table = tables
+        .iter()
+        .find(|table| table.name == "indexlake_table")
+        .expect("indexlake_table is dumped");
+    let field_table = tables
+        .iter()
+        .find(|table| table.name == "indexlake_field")
+        .expect("indexlake_field is dumped");
+    let table_ids = table_ids(table_table)?;
+    let mut table_fields = inline_row_fields(field_table)?;
+    for table_id in table_ids {
+        tables.push(
+            dump_table(
+                transaction.as_mut(),
+                database,
+                format!("indexlake_row_metadata_{table_id}"),
+                row_metadata_schema(),
+            )
+            .await?,
+        );
+        let fields = table_fields.remove(&table_id).unwrap_or_default();
+        tables.push(
+            dump_table(
+                transaction.as_mut(),
+                database,
+                format!("indexlake_inline_row_{table_id}"),
+                inline_row_schema(&fields)?,
+            )
+            .await?,
+        );
+    }
+    transaction.commit().await?;
+
+    Ok(CatalogDump {
+        catalog_version,
+        tables,
+    })
+}
+
+pub(crate) async fn import_catalog_dump<C: Catalog + ?Sized>(
+    catalog: &C,
+    dump: &CatalogDump,
+) -> ILResult<()> {
+    if catalog.read_only() {
+        return Err(ILError::CatalogReadOnly(
+            "can not import a dump into a read-only catalog".to_string(),
+        ));
+    }
+    if dump.catalog_version != CATALOG_VERSION {
+        return Err(ILError::InvalidInput(format!(
+            "catalog dump version {} does not match the version {CATALOG_VERSION} supported by this library",
+            dump.catalog_version
+        )));
+    }
+    migrate_catalog(catalog).await?;
+
+    let mut tx_helper = TransactionHelper {
+        transaction: catalog.transaction().await?,
+        database: catalog.database(),
+        commit_hooks: Vec::new(),
+    };
+    if tx_helper.get_max_namespace_id().await? > 0 || tx_helper.get_max_table_id().await? > 0 {
+        return Err(ILError::InvalidInput(
+            "catalog dump can only be imported into an empty catalog".to_string(),
+        ));
+    }
+
+    let mut imported = Vec::new();
+    for (name, _) in metadata_tables() {
+        if let Some(dump_table) = dump.table(name) {
+            insert_dump_rows(&mut tx_helper, dump_table).await?;
+            imported.push(name.to_string());
+        }
+    }
+
+    let table_fields = match dump.table("indexlake_field") {
+        Some(field_table) => inline_row_fields(field_table)?,
+        None => HashMap::new(),
+    };
+    let table_ids = match dump.table("indexlake_table") {
+        Some(table_table) => table_ids(table_table)?,
+        None => Vec::new(),
+    };
+    for table_id in table_ids {
+        let fields = table_fields.get(&table_id).cloned().unwrap_or_default();
+        tx_helper.create_row_metadata_table(table_id).await?;
+        tx_helper
+            .create_inline_row_table(table_id, &Fields::from(fields))
+            .await?;
+        for name in [
+            format!("indexlake_row_metadata_{table_id}"),
+            format!("indexlake_inline_row_{table_id}"),
+        ] {
+            if let Some(dump_table) = dump.table(&name) {
+                insert_dump_rows(&mut tx_helper, dump_table).await?;
+            }
+            imported.push(name);
+        }
+    }
+
+    if let Some(unknown) = dump
+        .tables
+        .iter()
+        .find(|table| !imported.contains(&table.name))
+    {
+        return Err(ILError::InvalidInput(format!(
+            "catalog dump contains unknown table {}",
+            unknown.name
+        )));
+    }
+    tx_helper.commit().await
+}
+
+async fn insert_dump_rows(
+    tx_helper: &mut TransactionHelper,
+    dump_table: &CatalogTableDump,
+) -> ILResult<usize> {
+    let database = tx_helper.database;
+    let values = dump_table
+        .rows
+        .iter()
+        .map(|row| {
+            if row.len() != dump_table.columns.len() {
+                return Err(ILError::InvalidInput(format!(
+                    "catalog dump row of table {} has {} values, expected {}",
+                    dump_table.name,
+                    row.len(),
+                    dump_table.columns.len()
+                )));
+            }
+            Ok(format!(
+                "({})",
+                row.iter()
+                    .map(|value| value.to_sql(database))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        })
+        .collect::<ILResult<Vec<_>>>()?;
+    let insert_prefix = format!(
+        "INSERT INTO {} ({}) VALUES ",
+        dump_table.name,
+        dump_table
+            .columns
+            .iter()
+            .map(|column| database.sql_identifier(&column.name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    tx_helper
+        .insert_values_in_batches(&insert_prefix, &values, DUMP_INSERT_BATCH_SIZE)
+        .await
+}
@@ -0,0 +1,47 @@
+use std::{sync::Arc, time::Duration, time::Instant};
+
+use futures::TryStreamExt;
+
+use crate::{
+    ILError, ILResult,
+    catalog::{Catalog, CatalogDataType, CatalogDatabase, CatalogSchema, Column},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatalogHealth {
+    /// Round trip time of the health check query.
+    pub latency: Duration,
+    /// Version reported by the catalog database.
+    pub version: String,
+}
+
+pub(crate) async fn check_catalog_health<C: Catalog + ?Sized>(
+    catalog: &C,
+) -> ILResult<CatalogHealth> {
+    let sql = match catalog.database() {
+        CatalogDatabase::Sqlite => "SELECT sqlite_version()",
+        CatalogDatabase::Postgres | CatalogDatabase::DuckDb => "SELECT version()",
+        CatalogDatabase::MySql => "SELECT VERSION()",
+    };
+    let schema = Arc::new(CatalogSchema::new(vec![Column::new(
+        "version",
+        CatalogDataType::Utf8,
+        false,
+    )]));
+    let now = Instant::now();
+    let rows = catalog
+        .query(sql, schema)
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    let latency = now.elapsed();
+    let version = rows
+        .first()
+        .and_then(|row| row.utf8(0).transpose())
+        .transpose()?
+        .ok_or_else(|| ILError::CatalogError("health check returned no version".to_string()))?;
+    Ok(CatalogHealth {
+        latency,
+        version: version.to_string(),
+    })
+}
@@ -0,0 +1,68 @@
+use arrow::datatypes::Field;
+
+use crate::{
+    ILResult,
+    catalog::{CatalogDataType, Scalar, TransactionHelper},
+};
+
+impl TransactionHelper {
+    pub(crate) async fn add_inline_row_column(
+        &mut self,
+        table_id: i64,
+        field: &Field,
+    ) -> ILResult<()> {
+        self.transaction
+            .execute(&format!(
+                "ALTER TABLE indexlake_inline_row_{table_id} ADD COLUMN {} {} NULL",
+                self.database.sql_identifier(field.name()),
+                CatalogDataType::from_arrow(field.data_type())?.to_sql(self.database),
+            ))
+            .await?;
+        Ok(())
+    }
+
+    pub(crate) async fn fill_inline_row_column(
+        &mut self,
+        table_id: i64,
+        field_name: &str,
+        value: &Scalar,
+    ) -> ILResult<usize> {
+        self.transaction
+            .execute(&format!(
+                "UPDATE indexlake_inline_row_{table_id} SET {} = {}",
+                self.database.sql_identifier(field_name),
+                value.to_sql(self.database),
+            ))
+            .await
+    }
+
+    pub(crate) async fn drop_inline_row_column(
+        &mut self,
+        table_id: i64,
+        field_name: &str,
+    ) -> ILResult<()> {
+        self.transaction
+            .execute(&format!(
+                "ALTER TABLE indexlake_inline_row_{table_id} DROP COLUMN {}",
+                self.database.sql_identifier(field_name),
+            ))
+            .await?;
+        Ok(())
+    }
+
+    pub(crate) async fn rename_inline_row_column(
+        &mut self,
+        table_id: i64,
+        old_name: &str,
+        new_name: &str,
+    ) -> ILResult<()> {
+        self.transaction
+            .execute(&format!(
+                "ALTER TABLE indexlake_inline_row_{table_id} RENAME COLUMN {} TO {}",
+                self.database.sql_identifier(old_name),
+                self.database.sql_identifier(new_name),
+            ))
+            .await?;
+        Ok(())
+    }
+}
@@ -12,9 +12,11 @@ impl TransactionHelper {
                 "
             CREATE TABLE indexlake_row_metadata_{table_id} (
                 {INTERNAL_ROW_ID_FIELD_NAME} BIGINT PRIMARY KEY,
-                location VARCHAR,
-                deleted BOOLEAN
-            )"
+                location {},
+                deleted {}
+            )",
+                CatalogDataType::Utf8.to_sql(self.database),
+                CatalogDataType::Boolean.to_sql(self.database),
             ))
             .await?;
         Ok(())
@@ -47,4 +49,24 @@ impl TransactionHelper {
             .await?;
         Ok(())
     }
+
+    /// Creates the table mapping the key hashes of the unique constraints of a table to the rows
+    /// holding them. Its primary key rejects a key taken by another row, committed or not.
+    pub(crate) async fn create_unique_key_table(&mut self, table_id: i64) -> ILResult<()> {
+        self.transaction
+            .execute_batch(&[
+                format!(
+                    "
+            CREATE TABLE indexlake_unique_key_{table_id} (
+                key_hash VARCHAR(64) PRIMARY KEY,
+                {INTERNAL_ROW_ID_FIELD_NAME} BIGINT NOT NULL
+            )"
+                ),
+                format!(
+                    "CREATE INDEX indexlake_unique_key_{table_id}_row_id ON indexlake_unique_key_{table_id} ({INTERNAL_ROW_ID_FIELD_NAME})"
+                ),
+            ])
+            .await?;
+        Ok(())
+    }
 }
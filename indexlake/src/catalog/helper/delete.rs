@@ -10,6 +10,9 @@ impl TransactionHelper {
         table_id: i64,
         row_ids: &[i64],
     ) -> ILResult<usize> {
+        if row_ids.is_empty() {
+            return Ok(0);
+        }
         self.transaction
             .execute(&format!(
                 "DELETE FROM indexlake_inline_row_{table_id} WHERE {} IN ({})",
@@ -36,6 +39,40 @@ impl TransactionHelper {
             .await
     }
 
+    pub(crate) async fn delete_unique_keys_by_row_ids(
+        &mut self,
+        table_id: i64,
+        row_ids: &[i64],
+    ) -> ILResult<usize> {
+        if row_ids.is_empty() {
+            return Ok(0);
+        }
+        self.transaction
+            .execute(&format!(
+                "DELETE FROM indexlake_unique_key_{table_id} WHERE {} IN ({})",
+                INTERNAL_ROW_ID_FIELD_NAME,
+                row_ids
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+            .await
+    }
+
+    pub(crate) async fn delete_unique_keys_by_condition(
+        &mut self,
+        table_id: i64,
+        row_id_condition: &Expr,
+    ) -> ILResult<usize> {
+        self.transaction
+            .execute(&format!(
+                "DELETE FROM indexlake_unique_key_{table_id} WHERE {}",
+                row_id_condition.to_sql(self.database)?
+            ))
+            .await
+    }
+
     pub(crate) async fn delete_dump_task(&mut self, table_id: i64) -> ILResult<usize> {
         self.transaction
             .execute(&format!(
@@ -52,6 +89,127 @@ impl TransactionHelper {
             .await
     }
 
+    pub(crate) async fn delete_data_files_by_ids(
+        &mut self,
+        data_file_ids: &[i64],
+    ) -> ILResult<usize> {
+        if data_file_ids.is_empty() {
+            return Ok(0);
+        }
+        self.transaction
+            .execute(&format!(
+                "DELETE FROM indexlake_data_file WHERE data_file_id IN ({})",
+                data_file_ids
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+            .await
+    }
+
+    pub(crate) async fn delete_index_files_by_data_file_ids(
+        &mut self,
+        data_file_ids: &[i64],
+    ) -> ILResult<usize> {
+        if data_file_ids.is_empty() {
+            return Ok(0);
+        }
+        self.transaction
+            .execute(&format!(
+                "DELETE FROM indexlake_index_file WHERE data_file_id IN ({})",
+                data_file_ids
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+            .await
+    }
+
+    pub(crate) async fn delete_index_files_by_ids(
+        &mut self,
+        index_file_ids: &[i64],
+    ) -> ILResult<usize> {
+        if index_file_ids.is_empty() {
+            return Ok(0);
+        }
+        self.transaction
+            .execute(&format!(
+                "DELETE FROM indexlake_index_file WHERE index_file_id IN ({})",
+                index_file_ids
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+            .await
+    }
+
+    pub(crate) async fn delete_all_index_files(&mut self, table_id: i64) -> ILResult<usize> {
+        self.transaction
+            .execute(&format!(
+                "DELETE FROM indexlake_index_file WHERE index_id IN (SELECT index_id FROM indexlake_index WHERE table_id = {table_id})"
+            ))
+            .await
+    }
+
+    pub(crate) async fn delete_index_files_by_index_id(
+        &mut self,
+        index_id: i64,
+    ) -> ILResult<usize> {
+        self.transaction
+            .execute(&format!(
+                "DELETE FROM indexlake_index_file WHERE index_id = {index_id}"
+            ))
+            .await
+    }
+
+    pub(crate) async fn delete_index(&mut self, index_id: i64) -> ILResult<usize> {
+        self.transaction
+            .execute(&format!(
+                "DELETE FROM indexlake_index WHERE index_id = {index_id}"
+            ))
+            .await
+    }
+
+    pub(crate) async fn delete_all_indexes(&mut self, table_id: i64) -> ILResult<usize> {
+        self.transaction
+            .execute(&format!(
+                "DELETE FROM indexlake_index WHERE table_id = {table_id}"
+            ))
+            .await
+    }
+
+    pub(crate) async fn delete_row_metadatas_by_row_ids(
+        &mut self,
+        table_id: i64,
+        row_ids: &[i64],
+    ) -> ILResult<usize> {
+        if row_ids.is_empty() {
+            return Ok(0);
+        }
+        self.transaction
+            .execute(&format!(
+                "DELETE FROM indexlake_row_metadata_{table_id} WHERE {} IN ({})",
+                INTERNAL_ROW_ID_FIELD_NAME,
+                row_ids
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+            .await
+    }
+
+    pub(crate) async fn delete_namespace(&mut self, namespace_id: i64) -> ILResult<usize> {
+        self.transaction
+            .execute(&format!(
+                "DELETE FROM indexlake_namespace WHERE namespace_id = {namespace_id}"
+            ))
+            .await
+    }
+
     pub(crate) async fn delete_table(&mut self, table_id: i64) -> ILResult<usize> {
         self.transaction
             .execute(&format!(
@@ -67,4 +225,53 @@ impl TransactionHelper {
             ))
             .await
     }
+
+    pub(crate) async fn delete_snapshots_by_ids(
+        &mut self,
+        snapshot_ids: &[i64],
+    ) -> ILResult<usize> {
+        if snapshot_ids.is_empty() {
+            return Ok(0);
+        }
+        self.transaction
+            .execute(&format!(
+                "DELETE FROM indexlake_snapshot WHERE snapshot_id IN ({})",
+                snapshot_ids
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+            .await
+    }
+
+    pub(crate) async fn delete_all_snapshots(&mut self, table_id: i64) -> ILResult<usize> {
+        self.transaction
+            .execute(&format!(
+                "DELETE FROM indexlake_snapshot WHERE table_id = {table_id}"
+            ))
+            .await
+    }
+
+    /// Deletes row versions that ended at or before `snapshot_id`, no snapshot from
+    /// `snapshot_id` on can see them.
+    pub(crate) async fn delete_row_histories_ended_by(
+        &mut self,
+        table_id: i64,
+        snapshot_id: i64,
+    ) -> ILResult<usize> {
+        self.transaction
+            .execute(&format!(
+                "DELETE FROM indexlake_row_history WHERE table_id = {table_id} AND end_snapshot_id <= {snapshot_id}"
+            ))
+            .await
+    }
+
+    pub(crate) async fn delete_all_row_histories(&mut self, table_id: i64) -> ILResult<usize> {
+        self.transaction
+            .execute(&format!(
+                "DELETE FROM indexlake_row_history WHERE table_id = {table_id}"
+            ))
+            .await
+    }
 }
@@ -15,4 +15,11 @@ impl TransactionHelper {
             .await?;
         Ok(())
     }
+
+    pub(crate) async fn drop_unique_key_table(&mut self, table_id: i64) -> ILResult<()> {
+        self.transaction
+            .execute_batch(&[format!("DROP TABLE indexlake_unique_key_{table_id}")])
+            .await?;
+        Ok(())
+    }
 }
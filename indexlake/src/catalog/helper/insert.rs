@@ -1,8 +1,9 @@
 use crate::{
     ILError, ILResult,
     catalog::{
-        DataFileRecord, INTERNAL_ROW_ID_FIELD_NAME, IndexFileRecord, IndexRecord,
-        RowMetadataRecord, TableRecord, TransactionHelper,
+        CatalogDatabase, DataFileRecord, INTERNAL_ROW_ID_FIELD_NAME, IndexFileRecord, IndexRecord,
+        NamespaceRecord, RowHistoryRecord, RowMetadataRecord, Scalar, SnapshotRecord, TableRecord,
+        TransactionHelper, encode_data_type,
     },
 };
 use arrow::datatypes::Fields;
@@ -10,12 +11,13 @@ use arrow::datatypes::Fields;
 impl TransactionHelper {
     pub(crate) async fn insert_namespace(
         &mut self,
-        namespace_id: i64,
-        namespace_name: &str,
+        namespace_record: &NamespaceRecord,
     ) -> ILResult<()> {
         self.transaction
             .execute(&format!(
-                "INSERT INTO indexlake_namespace (namespace_id, namespace_name) VALUES ({namespace_id}, '{namespace_name}')"
+                "INSERT INTO indexlake_namespace ({}) VALUES {}",
+                NamespaceRecord::select_items().join(", "),
+                namespace_record.to_sql()?
             ))
             .await?;
         Ok(())
@@ -44,7 +46,7 @@ impl TransactionHelper {
             values.push(format!(
                 "({field_id}, {table_id}, '{}', '{}', {}, '{}')",
                 field.name(),
-                field.data_type().to_string(),
+                encode_data_type(field.data_type())?,
                 field.is_nullable(),
                 serde_json::to_string(&field.metadata()).map_err(|e| ILError::InternalError(
                     format!("Failed to serialize field metadata: {e:?}")
@@ -63,34 +65,118 @@ impl TransactionHelper {
         Ok(())
     }
 
-    pub(crate) async fn insert_inline_rows(
+    /// Copies the fields of the table `source_table_id`, dropped ones included, to the table
+    /// `table_id`. Copies get the field id of their source plus `field_id_offset`.
+    pub(crate) async fn insert_fields_from(
         &mut self,
+        source_table_id: i64,
         table_id: i64,
-        field_names: &[String],
-        values: Vec<String>,
-    ) -> ILResult<()> {
+        field_id_offset: i64,
+    ) -> ILResult<usize> {
         self.transaction
             .execute(&format!(
-                "INSERT INTO indexlake_inline_row_{table_id} ({}) VALUES {}",
-                field_names
-                    .iter()
-                    .map(|name| self.database.sql_identifier(name))
-                    .collect::<Vec<_>>()
-                    .join(", "),
-                values.join(", ")
+                "INSERT INTO indexlake_field (field_id, table_id, field_name, data_type, nullable, metadata)
+                SELECT field_id + {field_id_offset}, {table_id}, field_name, data_type, nullable, metadata
+                FROM indexlake_field WHERE table_id = {source_table_id}"
             ))
-            .await?;
-        Ok(())
+            .await
+    }
+
+    /// Copies the `columns` of all rows of the catalog table `source` to the catalog table
+    /// `target` within the catalog, without reading them.
+    pub(crate) async fn insert_rows_from(
+        &mut self,
+        source: &str,
+        target: &str,
+        columns: &[&str],
+    ) -> ILResult<usize> {
+        let columns = columns
+            .iter()
+            .map(|name| self.database.sql_identifier(name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.transaction
+            .execute(&format!(
+                "INSERT INTO {target} ({columns}) SELECT {columns} FROM {source}"
+            ))
+            .await
+    }
+
+    pub(crate) async fn insert_inline_rows(
+        &mut self,
+        table_id: i64,
+        field_names: &[String],
+        rows: &[Vec<Scalar>],
+        batch_size: usize,
+    ) -> ILResult<usize> {
+        let columns = field_names
+            .iter()
+            .map(|name| self.database.sql_identifier(name))
+            .collect::<Vec<_>>();
+        self.insert_rows(
+            &format!("indexlake_inline_row_{table_id}"),
+            &columns,
+            rows,
+            batch_size,
+        )
+        .await
     }
 
     pub(crate) async fn insert_row_metadatas(
         &mut self,
         table_id: i64,
         metadatas: &[RowMetadataRecord],
-    ) -> ILResult<()> {
-        let values = metadatas.iter().map(|m| m.to_sql()).collect::<Vec<_>>();
-        self.transaction.execute(&format!("INSERT INTO indexlake_row_metadata_{table_id} ({INTERNAL_ROW_ID_FIELD_NAME}, location, deleted) VALUES {}", values.join(", "))).await?;
-        Ok(())
+        batch_size: usize,
+    ) -> ILResult<usize> {
+        let rows = metadatas.iter().map(|m| m.to_values()).collect::<Vec<_>>();
+        let columns = RowMetadataRecord::select_items()
+            .into_iter()
+            .map(|column| column.to_string())
+            .collect::<Vec<_>>();
+        self.insert_rows(
+            &format!("indexlake_row_metadata_{table_id}"),
+            &columns,
+            &rows,
+            batch_size,
+        )
+        .await
+    }
+
+    /// Inserts the `(key_hash, row_id)` pairs, skipping the keys already taken. Waits for
+    /// concurrent transactions inserting the same keys to finish. Returns the number of keys
+    /// inserted.
+    pub(crate) async fn insert_unique_keys(
+        &mut self,
+        table_id: i64,
+        keys: &[(String, i64)],
+        batch_size: usize,
+    ) -> ILResult<usize> {
+        if batch_size == 0 {
+            return Err(ILError::InvalidInput(
+                "insert batch size must be greater than 0".to_string(),
+            ));
+        }
+        let (insert, on_conflict) = match self.database {
+            CatalogDatabase::MySql => ("INSERT IGNORE INTO", ""),
+            CatalogDatabase::Sqlite | CatalogDatabase::Postgres | CatalogDatabase::DuckDb => {
+                ("INSERT INTO", " ON CONFLICT DO NOTHING")
+            }
+        };
+        let mut count = 0;
+        for chunk in keys.chunks(batch_size) {
+            let values = chunk
+                .iter()
+                .map(|(key_hash, row_id)| format!("('{key_hash}', {row_id})"))
+                .collect::<Vec<_>>();
+            count += self
+                .transaction
+                .execute(&format!(
+                    "{insert} indexlake_unique_key_{table_id} (key_hash, {INTERNAL_ROW_ID_FIELD_NAME}) VALUES {}{on_conflict}",
+                    values.join(", ")
+                ))
+                .await?;
+        }
+        Ok(count)
     }
 
     pub(crate) async fn insert_dump_task(&mut self, table_id: i64) -> ILResult<usize> {
@@ -101,20 +187,38 @@ impl TransactionHelper {
             .await
     }
 
+    /// Inserts the dump task of the table unless another task holds it, returning whether it
+    /// was inserted.
+    pub(crate) async fn try_insert_dump_task(&mut self, table_id: i64) -> ILResult<bool> {
+        let (insert, on_conflict) = match self.database {
+            CatalogDatabase::MySql => ("INSERT IGNORE INTO", ""),
+            CatalogDatabase::Sqlite | CatalogDatabase::Postgres | CatalogDatabase::DuckDb => {
+                ("INSERT INTO", " ON CONFLICT DO NOTHING")
+            }
+        };
+        let inserted = self
+            .transaction
+            .execute(&format!(
+                "{insert} indexlake_dump_task (table_id) VALUES ({table_id}){on_conflict}"
+            ))
+            .await?;
+        Ok(inserted > 0)
+    }
+
     pub(crate) async fn insert_data_files(
         &mut self,
         data_files: &[DataFileRecord],
+        batch_size: usize,
     ) -> ILResult<usize> {
         let values = data_files
             .iter()
             .map(|r| r.to_sql(self.database))
-            .collect::<Vec<_>>();
-        self.transaction
-            .execute(&format!(
-                "INSERT INTO indexlake_data_file ({}) VALUES {}",
-                DataFileRecord::select_items().join(", "),
-                values.join(", ")
-            ))
+            .collect::<ILResult<Vec<_>>>()?;
+        let insert_prefix = format!(
+            "INSERT INTO indexlake_data_file ({}) VALUES ",
+            DataFileRecord::select_items().join(", "),
+        );
+        self.insert_values_in_batches(&insert_prefix, &values, batch_size)
             .await
     }
 
@@ -123,7 +227,7 @@ impl TransactionHelper {
             .execute(&format!(
                 "INSERT INTO indexlake_index ({}) VALUES {}",
                 IndexRecord::select_items().join(", "),
-                index_record.to_sql()
+                index_record.to_sql()?
             ))
             .await
     }
@@ -135,7 +239,10 @@ impl TransactionHelper {
         if index_files.is_empty() {
             return Ok(0);
         }
-        let values = index_files.iter().map(|r| r.to_sql()).collect::<Vec<_>>();
+        let values = index_files
+            .iter()
+            .map(|r| r.to_sql(self.database))
+            .collect::<Vec<_>>();
         self.transaction
             .execute(&format!(
                 "INSERT INTO indexlake_index_file ({}) VALUES {}",
@@ -144,4 +251,74 @@ impl TransactionHelper {
             ))
             .await
     }
+
+    pub(crate) async fn insert_snapshot(&mut self, snapshot: &SnapshotRecord) -> ILResult<usize> {
+        self.transaction
+            .execute(&format!(
+                "INSERT INTO indexlake_snapshot ({}) VALUES {}",
+                SnapshotRecord::select_items().join(", "),
+                snapshot.to_sql()
+            ))
+            .await
+    }
+
+    pub(crate) async fn insert_row_histories(
+        &mut self,
+        row_histories: &[RowHistoryRecord],
+        batch_size: usize,
+    ) -> ILResult<usize> {
+        let values = row_histories
+            .iter()
+            .map(|r| r.to_sql(self.database))
+            .collect::<Vec<_>>();
+        let insert_prefix = format!(
+            "INSERT INTO indexlake_row_history ({}) VALUES ",
+            RowHistoryRecord::select_items().join(", "),
+        );
+        self.insert_values_in_batches(&insert_prefix, &values, batch_size)
+            .await
+    }
+
+    /// Insert rows through [`Transaction::insert_rows`], which batches them per the backend.
+    ///
+    /// [`Transaction::insert_rows`]: crate::catalog::Transaction::insert_rows
+    async fn insert_rows(
+        &mut self,
+        table_name: &str,
+        columns: &[String],
+        rows: &[Vec<Scalar>],
+        batch_size: usize,
+    ) -> ILResult<usize> {
+        if batch_size == 0 {
+            return Err(ILError::InvalidInput(
+                "insert batch size must be greater than 0".to_string(),
+            ));
+        }
+        self.transaction
+            .insert_rows(self.database, table_name, columns, rows, batch_size)
+            .await
+    }
+
+    /// Insert rows with one multi-row `INSERT` statement per `batch_size` values, so a single
+    /// statement never exceeds the size limits of the catalog database.
+    pub(crate) async fn insert_values_in_batches(
+        &mut self,
+        insert_prefix: &str,
+        values: &[String],
+        batch_size: usize,
+    ) -> ILResult<usize> {
+        if batch_size == 0 {
+            return Err(ILError::InvalidInput(
+                "insert batch size must be greater than 0".to_string(),
+            ));
+        }
+        let mut count = 0;
+        for chunk in values.chunks(batch_size) {
+            count += self
+                .transaction
+                .execute(&format!("{insert_prefix}{}", chunk.join(", ")))
+                .await?;
+        }
+        Ok(count)
+    }
 }
@@ -1,3 +1,4 @@
+mod alter;
 mod create;
 mod delete;
 mod drop;
@@ -8,10 +9,11 @@ mod update;
 
 use std::sync::Arc;
 
-use futures::TryStreamExt;
+use futures::{TryStreamExt, future::BoxFuture};
+use log::warn;
 
 use crate::{
-    ILResult,
+    ILError, ILResult,
     catalog::{Catalog, CatalogDatabase, Transaction},
     catalog::{CatalogSchemaRef, Row},
 };
@@ -19,6 +21,7 @@ use crate::{
 pub(crate) struct TransactionHelper {
     pub(crate) transaction: Box<dyn Transaction>,
     pub(crate) database: CatalogDatabase,
+    pub(crate) commit_hooks: Vec<Box<dyn FnOnce() + Send>>,
 }
 
 impl TransactionHelper {
@@ -27,9 +30,46 @@ impl TransactionHelper {
         Ok(Self {
             transaction,
             database: catalog.database(),
+            commit_hooks: Vec::new(),
         })
     }
 
+    /// Run `f` in a new transaction, `f` is expected to commit it. Transactions aborted by a
+    /// conflicting concurrent transaction are re-run per [`Catalog::transaction_retry_policy`],
+    /// so `f` must not have side effects outside of the catalog transaction. Files `f` writes go
+    /// to unique paths, see [`DataFileRecord::build_unique_relative_path`], so that attempts
+    /// never overwrite each other's files, the files of failed attempts are left to vacuum.
+    ///
+    /// [`DataFileRecord::build_unique_relative_path`]: crate::catalog::DataFileRecord::build_unique_relative_path
+    ///
+    /// Meant for write operations, fails right away on read-only catalogs.
+    pub(crate) async fn run<'a, T>(
+        catalog: &Arc<dyn Catalog>,
+        mut f: impl FnMut(TransactionHelper) -> BoxFuture<'a, ILResult<T>>,
+    ) -> ILResult<T> {
+        check_writable(catalog)?;
+        let policy = catalog.transaction_retry_policy();
+        let mut attempt = 1;
+        loop {
+            let result = match TransactionHelper::new(catalog).await {
+                Ok(tx_helper) => f(tx_helper).await,
+                Err(e) => Err(e),
+            };
+            match (result, &policy) {
+                (Err(e), Some(policy)) if e.is_conflict() && attempt < policy.max_attempts => {
+                    let delay = policy.delay(attempt);
+                    warn!(
+                        "catalog transaction failed on attempt {attempt}/{}, retrying in {delay:?}: {e}",
+                        policy.max_attempts
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                (result, _) => return result,
+            }
+        }
+    }
+
     pub(crate) async fn query_rows(
         &mut self,
         sql: &str,
@@ -40,7 +80,16 @@ impl TransactionHelper {
     }
 
     pub(crate) async fn commit(&mut self) -> ILResult<()> {
-        self.transaction.commit().await
+        self.transaction.commit().await?;
+        for f in self.commit_hooks.drain(..) {
+            f();
+        }
+        Ok(())
+    }
+
+    /// Runs `f` once the transaction has committed, never if it is rolled back or fails.
+    pub(crate) fn after_commit(&mut self, f: impl FnOnce() + Send + 'static) {
+        self.commit_hooks.push(Box::new(f));
     }
 
     pub(crate) async fn rollback(&mut self) -> ILResult<()> {
@@ -66,3 +115,12 @@ impl CatalogHelper {
         stream.try_collect::<Vec<_>>().await
     }
 }
+
+pub(crate) fn check_writable(catalog: &Arc<dyn Catalog>) -> ILResult<()> {
+    if catalog.read_only() {
+        return Err(ILError::CatalogReadOnly(
+            "catalog is opened read-only".to_string(),
+        ));
+    }
+    Ok(())
+}
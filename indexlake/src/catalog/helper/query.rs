@@ -1,22 +1,33 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 use std::{collections::HashMap, sync::Arc};
 
-use arrow::datatypes::{DataType, Field, FieldRef};
+use arrow::datatypes::{Field, FieldRef};
 use tokio::time::error::Elapsed;
 
 use crate::catalog::{
-    CatalogHelper, DataFileRecord, IndexRecord, RowLocation, RowMetadataRecord, Scalar,
+    CatalogHelper, DataFileRecord, FIELD_DEFAULT_VALUE_METADATA_KEY, FIELD_DROPPED_METADATA_KEY,
+    IndexFileRecord, IndexRecord, RowHistoryRecord, RowLocation, RowMetadataRecord, Scalar,
+    SnapshotRecord,
 };
 use crate::expr::{Expr, col, lit};
 use crate::{
     ILError, ILResult,
     catalog::{
         CatalogDataType, CatalogSchema, CatalogSchemaRef, Column, INTERNAL_ROW_ID_FIELD_NAME, Row,
+        decode_data_type,
     },
-    catalog::{RowStream, TableRecord, TransactionHelper},
-    table::TableConfig,
+    catalog::{NamespaceRecord, RowStream, TableRecord, TransactionHelper},
+    table::{ColumnDefault, TableConfig, split_column_default},
 };
 
+/// Row ids listed by a single `IN (...)` condition, longer lists are queried in chunks so
+/// statements stay within the size limits of the catalog database.
+const ROW_ID_CHUNK_SIZE: usize = 10_000;
+
+fn row_ids_condition(row_ids: &[i64]) -> Expr {
+    col(INTERNAL_ROW_ID_FIELD_NAME).in_list(row_ids.iter().copied().map(lit).collect(), false)
+}
+
 impl TransactionHelper {
     pub(crate) async fn get_max_namespace_id(&mut self) -> ILResult<i64> {
         let schema = Arc::new(CatalogSchema::new(vec![Column::new(
@@ -25,7 +36,13 @@ impl TransactionHelper {
             true,
         )]));
         let rows = self
-            .query_rows("SELECT MAX(namespace_id) FROM indexlake_namespace", schema)
+            .query_rows(
+                &format!(
+                    "SELECT MAX(namespace_id) FROM indexlake_namespace{}",
+                    self.database.sql_for_update()
+                ),
+                schema,
+            )
             .await?;
         if rows.is_empty() {
             Ok(0)
@@ -58,6 +75,43 @@ impl TransactionHelper {
         }
     }
 
+    /// Tables of the namespace ordered by name, locked until the transaction ends.
+    pub(crate) async fn list_namespace_tables(
+        &mut self,
+        namespace_id: i64,
+    ) -> ILResult<Vec<TableRecord>> {
+        let rows = self
+            .query_rows(
+                &format!(
+                    "SELECT {} FROM indexlake_table WHERE namespace_id = {namespace_id} ORDER BY table_name{}",
+                    TableRecord::select_items().join(", "),
+                    self.database.sql_for_update()
+                ),
+                table_schema(),
+            )
+            .await?;
+        rows.iter().map(parse_table).collect()
+    }
+
+    /// Table of the namespace named `table_name`, locked until the transaction ends.
+    pub(crate) async fn get_table(
+        &mut self,
+        namespace_id: i64,
+        table_name: &str,
+    ) -> ILResult<Option<TableRecord>> {
+        let rows = self
+            .query_rows(
+                &format!(
+                    "SELECT {} FROM indexlake_table WHERE namespace_id = {namespace_id} AND table_name = '{table_name}'{}",
+                    TableRecord::select_items().join(", "),
+                    self.database.sql_for_update()
+                ),
+                table_schema(),
+            )
+            .await?;
+        rows.first().map(parse_table).transpose()
+    }
+
     pub(crate) async fn get_max_table_id(&mut self) -> ILResult<i64> {
         let schema = Arc::new(CatalogSchema::new(vec![Column::new(
             "max_table_id",
@@ -65,7 +119,13 @@ impl TransactionHelper {
             true,
         )]));
         let rows = self
-            .query_rows("SELECT MAX(table_id) FROM indexlake_table", schema)
+            .query_rows(
+                &format!(
+                    "SELECT MAX(table_id) FROM indexlake_table{}",
+                    self.database.sql_for_update()
+                ),
+                schema,
+            )
             .await?;
         if rows.is_empty() {
             Ok(0)
@@ -89,6 +149,61 @@ impl TransactionHelper {
         Ok(rows.len() > 0)
     }
 
+    pub(crate) async fn get_table_id(
+        &mut self,
+        namespace_id: i64,
+        table_name: &str,
+    ) -> ILResult<Option<i64>> {
+        let schema = Arc::new(CatalogSchema::new(vec![Column::new(
+            "table_id",
+            CatalogDataType::Int64,
+            false,
+        )]));
+        let rows = self.query_rows(&format!("SELECT table_id FROM indexlake_table WHERE namespace_id = {namespace_id} AND table_name = '{table_name}'"), schema).await?;
+        match rows.first() {
+            Some(row) => row.int64(0),
+            None => Ok(None),
+        }
+    }
+
+    /// Also finds dropped fields.
+    pub(crate) async fn get_field_id(
+        &mut self,
+        table_id: i64,
+        field_name: &str,
+    ) -> ILResult<Option<i64>> {
+        let schema = Arc::new(CatalogSchema::new(vec![Column::new(
+            "field_id",
+            CatalogDataType::Int64,
+            false,
+        )]));
+        let rows = self.query_rows(&format!("SELECT field_id FROM indexlake_field WHERE table_id = {table_id} AND field_name = '{field_name}'"), schema).await?;
+        match rows.first() {
+            Some(row) => row.int64(0),
+            None => Ok(None),
+        }
+    }
+
+    pub(crate) async fn get_table_schema_version(&mut self, table_id: i64) -> ILResult<i64> {
+        let schema = Arc::new(CatalogSchema::new(vec![Column::new(
+            "schema_version",
+            CatalogDataType::Int64,
+            true,
+        )]));
+        let rows = self
+            .query_rows(
+                &format!("SELECT schema_version FROM indexlake_table WHERE table_id = {table_id}"),
+                schema,
+            )
+            .await?;
+        match rows.first() {
+            Some(row) => Ok(row.int64(0)?.unwrap_or(0)),
+            None => Err(ILError::InternalError(format!(
+                "Table {table_id} not found in catalog"
+            ))),
+        }
+    }
+
     pub(crate) async fn get_max_field_id(&mut self) -> ILResult<i64> {
         let schema = Arc::new(CatalogSchema::new(vec![Column::new(
             "max_field_id",
@@ -97,7 +212,13 @@ impl TransactionHelper {
         )]));
 
         let rows = self
-            .query_rows("SELECT MAX(field_id) FROM indexlake_field", schema)
+            .query_rows(
+                &format!(
+                    "SELECT MAX(field_id) FROM indexlake_field{}",
+                    self.database.sql_for_update()
+                ),
+                schema,
+            )
             .await?;
         if rows.is_empty() {
             Ok(0)
@@ -115,16 +236,32 @@ impl TransactionHelper {
         )]));
         let rows = self
             .query_rows(
-                &format!("SELECT MAX({INTERNAL_ROW_ID_FIELD_NAME}) FROM indexlake_row_metadata_{table_id}"),
+                &format!(
+                    "SELECT MAX({INTERNAL_ROW_ID_FIELD_NAME}) FROM indexlake_row_metadata_{table_id}{}",
+                    self.database.sql_for_update()
+                ),
+                schema.clone(),
+            )
+            .await?;
+        let max_row_id = match rows.first() {
+            Some(row) => row.int64(0)?.unwrap_or(0),
+            None => 0,
+        };
+        // Row metadata of deleted rows is removed by compaction and truncate, the snapshots
+        // keep their ids from being reused, which would make them visible in earlier snapshots
+        let rows = self
+            .query_rows(
+                &format!(
+                    "SELECT MAX(max_row_id) FROM indexlake_snapshot WHERE table_id = {table_id}"
+                ),
                 schema,
             )
             .await?;
-        if rows.is_empty() {
-            Ok(0)
-        } else {
-            let max_row_id = rows[0].int64(0)?;
-            Ok(max_row_id.unwrap_or(0))
-        }
+        let snapshot_max_row_id = match rows.first() {
+            Some(row) => row.int64(0)?.unwrap_or(0),
+            None => 0,
+        };
+        Ok(max_row_id.max(snapshot_max_row_id))
     }
 
     pub(crate) async fn scan_row_metadata(
@@ -252,6 +389,43 @@ impl TransactionHelper {
         Ok(row_ids)
     }
 
+    /// Row ids holding the given unique key hashes, by key hash. Keys committed by concurrent
+    /// transactions are seen on all catalogs.
+    pub(crate) async fn get_unique_key_row_ids(
+        &mut self,
+        table_id: i64,
+        key_hashes: &[&str],
+    ) -> ILResult<HashMap<String, i64>> {
+        if key_hashes.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let schema = Arc::new(CatalogSchema::new(vec![
+            Column::new("key_hash", CatalogDataType::Utf8, false),
+            Column::new(INTERNAL_ROW_ID_FIELD_NAME, CatalogDataType::Int64, false),
+        ]));
+        let rows = self
+            .query_rows(
+                &format!(
+                    "SELECT key_hash, {INTERNAL_ROW_ID_FIELD_NAME} FROM indexlake_unique_key_{table_id} WHERE key_hash IN ({}){}",
+                    key_hashes
+                        .iter()
+                        .map(|key_hash| format!("'{key_hash}'"))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    self.database.sql_for_update()
+                ),
+                schema,
+            )
+            .await?;
+        let mut row_ids = HashMap::with_capacity(rows.len());
+        for row in rows {
+            let key_hash = row.utf8(0)?.expect("key_hash is not null").clone();
+            let row_id = row.int64(1)?.expect("row_id is not null");
+            row_ids.insert(key_hash, row_id);
+        }
+        Ok(row_ids)
+    }
+
     pub(crate) async fn get_max_data_file_id(&mut self) -> ILResult<i64> {
         let schema = Arc::new(CatalogSchema::new(vec![Column::new(
             "max_data_file_id",
@@ -259,7 +433,13 @@ impl TransactionHelper {
             true,
         )]));
         let rows = self
-            .query_rows("SELECT MAX(data_file_id) FROM indexlake_data_file", schema)
+            .query_rows(
+                &format!(
+                    "SELECT MAX(data_file_id) FROM indexlake_data_file{}",
+                    self.database.sql_for_update()
+                ),
+                schema,
+            )
             .await?;
         if rows.is_empty() {
             Ok(0)
@@ -269,52 +449,196 @@ impl TransactionHelper {
         }
     }
 
-    pub(crate) async fn get_data_files(&mut self, table_id: i64) -> ILResult<Vec<DataFileRecord>> {
-        let schema = Arc::new(CatalogSchema::new(vec![
-            Column::new("data_file_id", CatalogDataType::Int64, false),
-            Column::new("table_id", CatalogDataType::Int64, false),
-            Column::new("relative_path", CatalogDataType::Utf8, false),
-            Column::new("file_size_bytes", CatalogDataType::Int64, false),
-            Column::new("record_count", CatalogDataType::Int64, false),
-            Column::new("row_ids", CatalogDataType::Binary, false),
-        ]));
+    pub(crate) async fn get_max_snapshot_id(&mut self) -> ILResult<i64> {
+        let schema = Arc::new(CatalogSchema::new(vec![Column::new(
+            "max_snapshot_id",
+            CatalogDataType::Int64,
+            true,
+        )]));
         let rows = self
             .query_rows(
                 &format!(
-                    "SELECT {} FROM indexlake_data_file WHERE table_id = {table_id}",
-                    DataFileRecord::select_items().join(", ")
+                    "SELECT MAX(snapshot_id) FROM indexlake_snapshot{}",
+                    self.database.sql_for_update()
                 ),
                 schema,
             )
             .await?;
-        let mut data_files = Vec::with_capacity(rows.len());
-        for row in rows {
-            let data_file_id = row.int64(0)?.expect("data_file_id is not null");
-            let table_id = row.int64(1)?.expect("table_id is not null");
-            let relative_path = row.utf8(2)?.expect("relative_path is not null").to_string();
-            let file_size_bytes = row.int64(3)?.expect("file_size_bytes is not null");
-            let record_count = row.int64(4)?.expect("record_count is not null");
-            let row_ids_bytes = row.binary(5)?.expect("row_ids is not null");
-            if row_ids_bytes.len() % 8 != 0 {
-                return Err(ILError::InternalError(format!(
-                    "row_ids is not a multiple of 8: {}",
-                    row_ids_bytes.len()
-                )));
+        if rows.is_empty() {
+            Ok(0)
+        } else {
+            let max_snapshot_id = rows[0].int64(0)?;
+            Ok(max_snapshot_id.unwrap_or(0))
+        }
+    }
+
+    /// Latest snapshot of the table, locked until the transaction ends on catalogs that lock
+    /// rows read for update.
+    pub(crate) async fn get_current_snapshot_id(&mut self, table_id: i64) -> ILResult<i64> {
+        let schema = Arc::new(CatalogSchema::new(vec![Column::new(
+            "current_snapshot_id",
+            CatalogDataType::Int64,
+            true,
+        )]));
+        let rows = self
+            .query_rows(
+                &format!(
+                    "SELECT MAX(snapshot_id) FROM indexlake_snapshot WHERE table_id = {table_id}{}",
+                    self.database.sql_for_update()
+                ),
+                schema,
+            )
+            .await?;
+        if rows.is_empty() {
+            Ok(0)
+        } else {
+            let current_snapshot_id = rows[0].int64(0)?;
+            Ok(current_snapshot_id.unwrap_or(0))
+        }
+    }
+
+    pub(crate) async fn get_snapshots(&mut self, table_id: i64) -> ILResult<Vec<SnapshotRecord>> {
+        let rows = self
+            .query_rows(&snapshots_sql(table_id), snapshot_schema())
+            .await?;
+        rows.iter().map(parse_snapshot).collect()
+    }
+
+    pub(crate) async fn get_row_histories(
+        &mut self,
+        table_id: i64,
+    ) -> ILResult<Vec<RowHistoryRecord>> {
+        let rows = self
+            .query_rows(
+                &format!(
+                    "SELECT {} FROM indexlake_row_history WHERE table_id = {table_id}",
+                    RowHistoryRecord::select_items().join(", ")
+                ),
+                row_history_schema(),
+            )
+            .await?;
+        rows.iter().map(parse_row_history).collect()
+    }
+
+    /// Counts the row versions ended by `snapshot_id`, and among them those of rows that are
+    /// still undeleted, that is rows updated in place.
+    pub(crate) async fn count_replaced_rows(
+        &mut self,
+        table_id: i64,
+        snapshot_id: i64,
+    ) -> ILResult<(i64, i64)> {
+        let schema = Arc::new(CatalogSchema::new(vec![Column::new(
+            "count",
+            CatalogDataType::Int64,
+            false,
+        )]));
+        let ended_sql = format!(
+            "SELECT COUNT(1) FROM indexlake_row_history WHERE table_id = {table_id} AND end_snapshot_id = {snapshot_id}"
+        );
+        let rows = self.query_rows(&ended_sql, schema.clone()).await?;
+        let ended = rows[0].int64(0)?.expect("count is not null");
+        if ended == 0 {
+            return Ok((0, 0));
+        }
+
+        let undeleted = col("deleted").eq(lit(false));
+        let rows = self
+            .query_rows(
+                &format!(
+                    "{ended_sql} AND row_id IN (SELECT {INTERNAL_ROW_ID_FIELD_NAME} FROM indexlake_row_metadata_{table_id} WHERE {})",
+                    undeleted.to_sql(self.database)?
+                ),
+                schema,
+            )
+            .await?;
+        let updated = rows[0].int64(0)?.expect("count is not null");
+        Ok((ended, updated))
+    }
+
+    pub(crate) async fn get_data_files(&mut self, table_id: i64) -> ILResult<Vec<DataFileRecord>> {
+        let rows = self
+            .query_rows(&data_files_sql(table_id), data_file_schema())
+            .await?;
+        rows.iter().map(parse_data_file).collect()
+    }
+
+    pub(crate) async fn get_index_files(
+        &mut self,
+        table_id: i64,
+    ) -> ILResult<Vec<IndexFileRecord>> {
+        let rows = self
+            .query_rows(&index_files_sql(table_id), index_file_schema())
+            .await?;
+        rows.iter().map(parse_index_file).collect()
+    }
+
+    pub(crate) async fn get_table_indexes(&mut self, table_id: i64) -> ILResult<Vec<IndexRecord>> {
+        let rows = self
+            .query_rows(&table_indexes_sql(table_id), index_schema())
+            .await?;
+        rows.iter().map(parse_index).collect()
+    }
+
+    /// Relative paths of the files under `dir` that data files, index files or row histories of
+    /// any table refer to. Cloned tables refer to files under the directory of their source.
+    pub(crate) async fn get_referenced_paths(&mut self, dir: &str) -> ILResult<HashSet<String>> {
+        self.query_referenced_paths(dir, "").await
+    }
+
+    /// Like [`TransactionHelper::get_referenced_paths`], leaving out the row history of the table
+    /// ended by `snapshot_id` or an earlier snapshot, which expiring snapshots up to it deletes.
+    pub(crate) async fn get_referenced_paths_after_expiry(
+        &mut self,
+        dir: &str,
+        table_id: i64,
+        snapshot_id: i64,
+    ) -> ILResult<HashSet<String>> {
+        let history_filter =
+            format!(" AND NOT (table_id = {table_id} AND end_snapshot_id <= {snapshot_id})");
+        self.query_referenced_paths(dir, &history_filter).await
+    }
+
+    async fn query_referenced_paths(
+        &mut self,
+        dir: &str,
+        history_filter: &str,
+    ) -> ILResult<HashSet<String>> {
+        let schema = Arc::new(CatalogSchema::new(vec![Column::new(
+            "relative_path",
+            CatalogDataType::Utf8,
+            false,
+        )]));
+        // LIKE wildcards in the directory only widen the match, paths are checked below
+        let pattern = format!("{dir}/%");
+        let mut paths = Vec::new();
+        for sql in [
+            format!(
+                "SELECT relative_path FROM indexlake_data_file WHERE relative_path LIKE '{pattern}'"
+            ),
+            format!(
+                "SELECT relative_path FROM indexlake_index_file WHERE relative_path LIKE '{pattern}'"
+            ),
+        ] {
+            for row in self.query_rows(&sql, schema.clone()).await? {
+                paths.push(row.utf8(0)?.expect("relative_path is not null").clone());
+            }
+        }
+        let sql = format!(
+            "SELECT location FROM indexlake_row_history WHERE location LIKE 'parquet:{pattern}'{history_filter}"
+        );
+        for row in self.query_rows(&sql, schema).await? {
+            let location = row.utf8(0)?.expect("location is not null");
+            if let RowLocation::Parquet { relative_path, .. } = location.parse()? {
+                paths.push(relative_path);
             }
-            let row_ids = row_ids_bytes
-                .chunks_exact(8)
-                .map(|bytes| i64::from_le_bytes(bytes.try_into().unwrap()))
-                .collect::<Vec<_>>();
-            data_files.push(DataFileRecord {
-                data_file_id,
-                table_id,
-                relative_path,
-                file_size_bytes,
-                record_count,
-                row_ids,
-            });
         }
-        Ok(data_files)
+        Ok(paths
+            .into_iter()
+            .filter(|path| {
+                path.strip_prefix(dir)
+                    .is_some_and(|rest| rest.starts_with('/'))
+            })
+            .collect())
     }
 
     pub(crate) async fn index_name_exists(
@@ -338,7 +662,13 @@ impl TransactionHelper {
             true,
         )]));
         let rows = self
-            .query_rows("SELECT MAX(index_id) FROM indexlake_index", schema)
+            .query_rows(
+                &format!(
+                    "SELECT MAX(index_id) FROM indexlake_index{}",
+                    self.database.sql_for_update()
+                ),
+                schema,
+            )
             .await?;
         if rows.is_empty() {
             Ok(0)
@@ -356,7 +686,10 @@ impl TransactionHelper {
         )]));
         let rows = self
             .query_rows(
-                "SELECT MAX(index_file_id) FROM indexlake_index_file",
+                &format!(
+                    "SELECT MAX(index_file_id) FROM indexlake_index_file{}",
+                    self.database.sql_for_update()
+                ),
                 schema,
             )
             .await?;
@@ -393,46 +726,90 @@ impl CatalogHelper {
         }
     }
 
+    /// All namespaces ordered by name.
+    pub(crate) async fn list_namespaces(&self) -> ILResult<Vec<NamespaceRecord>> {
+        let rows = self
+            .query_rows(
+                &format!(
+                    "SELECT {} FROM indexlake_namespace ORDER BY namespace_name",
+                    NamespaceRecord::select_items().join(", ")
+                ),
+                namespace_schema(),
+            )
+            .await?;
+        rows.iter().map(parse_namespace).collect()
+    }
+
     pub(crate) async fn get_table(
         &self,
         namespace_id: i64,
         table_name: &str,
     ) -> ILResult<Option<TableRecord>> {
-        let schema = Arc::new(CatalogSchema::new(vec![
-            Column::new("table_id", CatalogDataType::Int64, false),
-            Column::new("table_name", CatalogDataType::Utf8, false),
-            Column::new("namespace_id", CatalogDataType::Int64, false),
-            Column::new("config", CatalogDataType::Utf8, false),
-        ]));
         let rows = self
             .query_rows(
                 &format!("SELECT {} FROM indexlake_table WHERE namespace_id = {namespace_id} AND table_name = '{table_name}'", TableRecord::select_items().join(", ")),
-                schema,
+                table_schema(),
             )
             .await?;
-        if rows.is_empty() {
-            Ok(None)
-        } else {
-            let table_id = rows[0].int64(0)?.expect("table_id is not null");
-            let table_name = rows[0].utf8(1)?.expect("table_name is not null");
-            let namespace_id = rows[0].int64(2)?.expect("namespace_id is not null");
-            let config_str = rows[0].utf8(3)?.expect("config is not null");
-            let config: TableConfig = serde_json::from_str(&config_str).map_err(|e| {
-                ILError::InternalError(format!("Failed to deserialize table config: {e:?}"))
-            })?;
-            Ok(Some(TableRecord {
-                table_id,
-                table_name: table_name.clone(),
-                namespace_id,
-                config,
-            }))
+        rows.first().map(parse_table).transpose()
+    }
+
+    /// Tables of the namespace ordered by name, optionally restricted to names starting with
+    /// `prefix` and sorting after `start_after`.
+    pub(crate) async fn list_tables(
+        &self,
+        namespace_id: i64,
+        prefix: Option<&str>,
+        start_after: Option<&str>,
+        limit: Option<usize>,
+    ) -> ILResult<Vec<TableRecord>> {
+        let mut conditions = vec![format!("namespace_id = {namespace_id}")];
+        if let Some(prefix) = prefix {
+            // Avoid LIKE whose escaping rules differ between databases
+            conditions.push(format!(
+                "SUBSTR(table_name, 1, {}) = '{prefix}'",
+                prefix.chars().count()
+            ));
+        }
+        if let Some(start_after) = start_after {
+            conditions.push(format!("table_name > '{start_after}'"));
         }
+        let limit_clause = if let Some(limit) = limit {
+            format!(" LIMIT {limit}")
+        } else {
+            "".to_string()
+        };
+        let rows = self
+            .query_rows(
+                &format!(
+                    "SELECT {} FROM indexlake_table WHERE {} ORDER BY table_name{limit_clause}",
+                    TableRecord::select_items().join(", "),
+                    conditions.join(" AND ")
+                ),
+                table_schema(),
+            )
+            .await?;
+        rows.iter().map(parse_table).collect()
     }
 
     pub(crate) async fn get_table_fields(
         &self,
         table_id: i64,
     ) -> ILResult<BTreeMap<i64, FieldRef>> {
+        let (field_map, _, _) = self.get_table_fields_with_defaults(table_id).await?;
+        Ok(field_map)
+    }
+
+    /// Returns the fields, the default values of fields added after table creation and the
+    /// defaults applied to inserted rows, both keyed by field name.
+    pub(crate) async fn get_table_fields_with_defaults(
+        &self,
+        table_id: i64,
+    ) -> ILResult<(
+        BTreeMap<i64, FieldRef>,
+        HashMap<String, Scalar>,
+        HashMap<String, ColumnDefault>,
+    )> {
         let catalog_schema = Arc::new(CatalogSchema::new(vec![
             Column::new("field_id", CatalogDataType::Int64, false),
             Column::new("field_name", CatalogDataType::Utf8, false),
@@ -447,83 +824,79 @@ impl CatalogHelper {
             )
             .await?;
         let mut field_map = BTreeMap::new();
+        let mut field_defaults = HashMap::new();
+        let mut column_defaults = HashMap::new();
         for row in rows {
             let field_id = row.int64(0)?.expect("field_id is not null");
             let field_name = row.utf8(1)?.expect("field_name is not null");
             let data_type_str = row.utf8(2)?.expect("data_type is not null");
-            let data_type = data_type_str.parse::<DataType>()?;
+            let data_type = decode_data_type(data_type_str)?;
             let nullable = row.boolean(3)?.expect("nullable is not null");
             let metadata_str = row.utf8(4)?.expect("metadata is not null");
-            let metadata: HashMap<String, String> =
-                serde_json::from_str(&metadata_str).map_err(|e| {
+            let mut metadata: HashMap<String, String> = serde_json::from_str(metadata_str)
+                .map_err(|e| {
                     ILError::InternalError(format!("Failed to deserialize field metadata: {e:?}"))
                 })?;
-            field_map.insert(
-                field_id,
-                Arc::new(Field::new(field_name, data_type, nullable).with_metadata(metadata)),
-            );
+            if metadata.contains_key(FIELD_DROPPED_METADATA_KEY) {
+                continue;
+            }
+            if let Some(default_str) = metadata.remove(FIELD_DEFAULT_VALUE_METADATA_KEY) {
+                let default: Scalar = serde_json::from_str(&default_str).map_err(|e| {
+                    ILError::InternalError(format!(
+                        "Failed to deserialize field default value: {e:?}"
+                    ))
+                })?;
+                field_defaults.insert(field_name.clone(), default);
+            }
+            let field = Field::new(field_name, data_type, nullable).with_metadata(metadata);
+            let (field, column_default) = split_column_default(&field)?;
+            if let Some(column_default) = column_default {
+                column_defaults.insert(field_name.clone(), column_default);
+            }
+            field_map.insert(field_id, Arc::new(field));
         }
-        Ok(field_map)
+        Ok((field_map, field_defaults, column_defaults))
     }
 
     pub(crate) async fn get_table_indexes(&self, table_id: i64) -> ILResult<Vec<IndexRecord>> {
-        let catalog_schema = Arc::new(CatalogSchema::new(vec![
-            Column::new("index_id", CatalogDataType::Int64, false),
-            Column::new("index_name", CatalogDataType::Utf8, false),
-            Column::new("table_id", CatalogDataType::Int64, false),
-            Column::new("kind", CatalogDataType::Utf8, false),
-            Column::new("key_field_ids", CatalogDataType::Utf8, false),
-            Column::new("include_field_ids", CatalogDataType::Utf8, false),
-            Column::new("params", CatalogDataType::Utf8, false),
-        ]));
+        let rows = self
+            .query_rows(&table_indexes_sql(table_id), index_schema())
+            .await?;
+        rows.iter().map(parse_index).collect()
+    }
+
+    pub(crate) async fn count_inline_rows(&self, table_id: i64, filters: &[Expr]) -> ILResult<i64> {
+        let schema = Arc::new(CatalogSchema::new(vec![Column::new(
+            "count",
+            CatalogDataType::Int64,
+            false,
+        )]));
         let rows = self
             .query_rows(
                 &format!(
-                    "SELECT {} FROM indexlake_index WHERE table_id = {table_id}",
-                    IndexRecord::select_items().join(", ")
+                    "SELECT COUNT(1) FROM indexlake_inline_row_{table_id}{}",
+                    self.where_clause(filters)?
                 ),
-                catalog_schema,
+                schema,
             )
             .await?;
-        let mut indexes = Vec::with_capacity(rows.len());
-        for row in rows {
-            let index_id = row.int64(0)?.expect("index_id is not null");
-            let index_name = row.utf8(1)?.expect("index_name is not null");
-            let table_id = row.int64(2)?.expect("table_id is not null");
-            let kind = row.utf8(3)?.expect("kind is not null");
-            let key_field_ids_str = row.utf8(4)?.expect("key_field_ids is not null");
-            let key_field_ids = key_field_ids_str
-                .split(",")
-                .map(|id| id.parse::<i64>().unwrap())
-                .collect::<Vec<_>>();
-            let include_field_ids_str = row.utf8(5)?.expect("include_field_ids is not null");
-            let include_field_ids = include_field_ids_str
-                .split(",")
-                .map(|id| id.parse::<i64>().unwrap())
-                .collect::<Vec<_>>();
-            let params = row.utf8(6)?.expect("params is not null");
-            indexes.push(IndexRecord {
-                index_id,
-                index_name: index_name.clone(),
-                index_kind: kind.clone(),
-                table_id,
-                key_field_ids,
-                include_field_ids,
-                params: params.clone(),
-            });
-        }
-        Ok(indexes)
+        let count = rows[0].int64(0)?.expect("count is not null");
+        Ok(count)
     }
 
-    pub(crate) async fn count_inline_rows(&self, table_id: i64) -> ILResult<i64> {
+    pub(crate) async fn count_undeleted_rows(&self, table_id: i64) -> ILResult<i64> {
         let schema = Arc::new(CatalogSchema::new(vec![Column::new(
             "count",
             CatalogDataType::Int64,
             false,
         )]));
+        let undeleted = col("deleted").eq(lit(false));
         let rows = self
             .query_rows(
-                &format!("SELECT COUNT(1) FROM indexlake_inline_row_{table_id}"),
+                &format!(
+                    "SELECT COUNT(1) FROM indexlake_row_metadata_{table_id} WHERE {}",
+                    undeleted.to_sql(self.catalog.database())?
+                ),
                 schema,
             )
             .await?;
@@ -531,6 +904,58 @@ impl CatalogHelper {
         Ok(count)
     }
 
+    /// Counts the deleted rows among `row_ids`.
+    pub(crate) async fn count_deleted_rows(&self, table_id: i64, row_ids: &[i64]) -> ILResult<i64> {
+        let schema = Arc::new(CatalogSchema::new(vec![Column::new(
+            "count",
+            CatalogDataType::Int64,
+            false,
+        )]));
+        let deleted = col("deleted").eq(lit(true));
+        let mut count = 0;
+        for chunk in row_ids.chunks(ROW_ID_CHUNK_SIZE) {
+            let condition = deleted.clone().and(row_ids_condition(chunk));
+            let rows = self
+                .query_rows(
+                    &format!(
+                        "SELECT COUNT(1) FROM indexlake_row_metadata_{table_id} WHERE {}",
+                        condition.to_sql(self.catalog.database())?
+                    ),
+                    schema.clone(),
+                )
+                .await?;
+            count += rows[0].int64(0)?.expect("count is not null");
+        }
+        Ok(count)
+    }
+
+    /// Returns the row metadata of the undeleted rows among `row_ids`.
+    pub(crate) async fn scan_undeleted_row_metadata_by_row_ids(
+        &self,
+        table_id: i64,
+        row_ids: &[i64],
+    ) -> ILResult<Vec<RowMetadataRecord>> {
+        let undeleted = col("deleted").eq(lit(false));
+        let mut records = Vec::new();
+        for chunk in row_ids.chunks(ROW_ID_CHUNK_SIZE) {
+            let condition = undeleted.clone().and(row_ids_condition(chunk));
+            records.extend(self.scan_row_metadata(table_id, &condition, None).await?);
+        }
+        Ok(records)
+    }
+
+    fn where_clause(&self, filters: &[Expr]) -> ILResult<String> {
+        if filters.is_empty() {
+            return Ok("".to_string());
+        }
+        let filters_str = filters
+            .iter()
+            .map(|f| f.to_sql(self.catalog.database()))
+            .collect::<Result<Vec<_>, _>>()?
+            .join(" AND ");
+        Ok(format!(" WHERE {filters_str}"))
+    }
+
     pub(crate) async fn scan_inline_rows(
         &self,
         table_id: i64,
@@ -538,16 +963,7 @@ impl CatalogHelper {
         filters: &[Expr],
         limit: Option<usize>,
     ) -> ILResult<Vec<Row>> {
-        let where_clause = if filters.is_empty() {
-            "".to_string()
-        } else {
-            let filters_str = filters
-                .iter()
-                .map(|f| f.to_sql(self.catalog.database()))
-                .collect::<Result<Vec<_>, _>>()?
-                .join(" AND ");
-            format!(" WHERE {filters_str}")
-        };
+        let where_clause = self.where_clause(filters)?;
         let limit_clause = if let Some(limit) = limit {
             format!(" LIMIT {limit}")
         } else {
@@ -619,3 +1035,296 @@ impl CatalogHelper {
         Ok(records)
     }
 }
+
+impl CatalogHelper {
+    pub(crate) async fn get_data_files(&self, table_id: i64) -> ILResult<Vec<DataFileRecord>> {
+        let rows = self
+            .query_rows(&data_files_sql(table_id), data_file_schema())
+            .await?;
+        rows.iter().map(parse_data_file).collect()
+    }
+
+    pub(crate) async fn get_index_files(&self, table_id: i64) -> ILResult<Vec<IndexFileRecord>> {
+        let rows = self
+            .query_rows(&index_files_sql(table_id), index_file_schema())
+            .await?;
+        rows.iter().map(parse_index_file).collect()
+    }
+
+    pub(crate) async fn get_snapshots(&self, table_id: i64) -> ILResult<Vec<SnapshotRecord>> {
+        let rows = self
+            .query_rows(&snapshots_sql(table_id), snapshot_schema())
+            .await?;
+        rows.iter().map(parse_snapshot).collect()
+    }
+
+    /// Row versions that were still live at `snapshot`, ordered by the snapshot they ended at.
+    pub(crate) async fn scan_row_histories_at(
+        &self,
+        snapshot: &SnapshotRecord,
+    ) -> ILResult<Vec<RowHistoryRecord>> {
+        let rows = self
+            .query_rows(
+                &format!(
+                    "SELECT {} FROM indexlake_row_history WHERE table_id = {} AND end_snapshot_id > {} AND row_id <= {} ORDER BY end_snapshot_id",
+                    RowHistoryRecord::select_items().join(", "),
+                    snapshot.table_id,
+                    snapshot.snapshot_id,
+                    snapshot.max_row_id
+                ),
+                row_history_schema(),
+            )
+            .await?;
+        rows.iter().map(parse_row_history).collect()
+    }
+}
+
+fn namespace_schema() -> CatalogSchemaRef {
+    Arc::new(CatalogSchema::new(vec![
+        Column::new("namespace_id", CatalogDataType::Int64, false),
+        Column::new("namespace_name", CatalogDataType::Utf8, false),
+        Column::new("properties", CatalogDataType::Utf8, true),
+    ]))
+}
+
+fn parse_namespace(row: &Row) -> ILResult<NamespaceRecord> {
+    let namespace_id = row.int64(0)?.expect("namespace_id is not null");
+    let namespace_name = row.utf8(1)?.expect("namespace_name is not null");
+    // namespaces created before properties were kept have none
+    let properties = match row.utf8(2)? {
+        Some(properties_str) => serde_json::from_str(properties_str).map_err(|e| {
+            ILError::InternalError(format!("Failed to deserialize namespace properties: {e:?}"))
+        })?,
+        None => HashMap::new(),
+    };
+    Ok(NamespaceRecord {
+        namespace_id,
+        namespace_name: namespace_name.clone(),
+        properties,
+    })
+}
+
+fn table_schema() -> CatalogSchemaRef {
+    Arc::new(CatalogSchema::new(vec![
+        Column::new("table_id", CatalogDataType::Int64, false),
+        Column::new("table_name", CatalogDataType::Utf8, false),
+        Column::new("namespace_id", CatalogDataType::Int64, false),
+        Column::new("config", CatalogDataType::Utf8, false),
+        Column::new("schema_version", CatalogDataType::Int64, true),
+    ]))
+}
+
+fn parse_table(row: &Row) -> ILResult<TableRecord> {
+    let table_id = row.int64(0)?.expect("table_id is not null");
+    let table_name = row.utf8(1)?.expect("table_name is not null");
+    let namespace_id = row.int64(2)?.expect("namespace_id is not null");
+    let config_str = row.utf8(3)?.expect("config is not null");
+    let config: TableConfig = serde_json::from_str(config_str).map_err(|e| {
+        ILError::InternalError(format!("Failed to deserialize table config: {e:?}"))
+    })?;
+    // tables created before schema versions were kept are at version 0
+    let schema_version = row.int64(4)?.unwrap_or(0);
+    Ok(TableRecord {
+        table_id,
+        table_name: table_name.clone(),
+        namespace_id,
+        config,
+        schema_version,
+    })
+}
+
+fn data_files_sql(table_id: i64) -> String {
+    format!(
+        "SELECT {} FROM indexlake_data_file WHERE table_id = {table_id}",
+        DataFileRecord::select_items().join(", ")
+    )
+}
+
+fn data_file_schema() -> CatalogSchemaRef {
+    Arc::new(CatalogSchema::new(vec![
+        Column::new("data_file_id", CatalogDataType::Int64, false),
+        Column::new("table_id", CatalogDataType::Int64, false),
+        Column::new("relative_path", CatalogDataType::Utf8, false),
+        Column::new("file_size_bytes", CatalogDataType::Int64, false),
+        Column::new("record_count", CatalogDataType::Int64, false),
+        Column::new("row_ids", CatalogDataType::Binary, false),
+        Column::new("partition_values", CatalogDataType::Utf8, true),
+        Column::new("checksum", CatalogDataType::Int64, true),
+        Column::new("column_stats", CatalogDataType::Utf8, true),
+    ]))
+}
+
+fn parse_data_file(row: &Row) -> ILResult<DataFileRecord> {
+    let row_ids_bytes = row.binary(5)?.expect("row_ids is not null");
+    if row_ids_bytes.len() % 8 != 0 {
+        return Err(ILError::InternalError(format!(
+            "row_ids is not a multiple of 8: {}",
+            row_ids_bytes.len()
+        )));
+    }
+    let row_ids = row_ids_bytes
+        .chunks_exact(8)
+        .map(|bytes| i64::from_le_bytes(bytes.try_into().unwrap()))
+        .collect::<Vec<_>>();
+    let partition_values = match row.utf8(6)? {
+        Some(values_str) => Some(serde_json::from_str(values_str).map_err(|e| {
+            ILError::InternalError(format!("Failed to deserialize partition values: {e:?}"))
+        })?),
+        None => None,
+    };
+    let column_stats = match row.utf8(8)? {
+        Some(stats_str) => Some(serde_json::from_str(stats_str).map_err(|e| {
+            ILError::InternalError(format!("Failed to deserialize column stats: {e:?}"))
+        })?),
+        None => None,
+    };
+    Ok(DataFileRecord {
+        data_file_id: row.int64(0)?.expect("data_file_id is not null"),
+        table_id: row.int64(1)?.expect("table_id is not null"),
+        relative_path: row.utf8(2)?.expect("relative_path is not null").to_string(),
+        file_size_bytes: row.int64(3)?.expect("file_size_bytes is not null"),
+        record_count: row.int64(4)?.expect("record_count is not null"),
+        row_ids,
+        partition_values,
+        checksum: row.int64(7)?.map(|checksum| checksum as u32),
+        column_stats,
+    })
+}
+
+fn table_indexes_sql(table_id: i64) -> String {
+    format!(
+        "SELECT {} FROM indexlake_index WHERE table_id = {table_id}",
+        IndexRecord::select_items().join(", ")
+    )
+}
+
+fn index_schema() -> CatalogSchemaRef {
+    Arc::new(CatalogSchema::new(vec![
+        Column::new("index_id", CatalogDataType::Int64, false),
+        Column::new("index_name", CatalogDataType::Utf8, false),
+        Column::new("index_kind", CatalogDataType::Utf8, false),
+        Column::new("table_id", CatalogDataType::Int64, false),
+        Column::new("key_field_ids", CatalogDataType::Utf8, false),
+        Column::new("include_field_ids", CatalogDataType::Utf8, false),
+        Column::new("params", CatalogDataType::Utf8, false),
+        Column::new("where_predicate", CatalogDataType::Utf8, true),
+    ]))
+}
+
+fn parse_index(row: &Row) -> ILResult<IndexRecord> {
+    let index_name = row.utf8(1)?.expect("index_name is not null");
+    let key_field_ids = row
+        .utf8(4)?
+        .expect("key_field_ids is not null")
+        .split(",")
+        .map(|id| id.parse::<i64>().unwrap())
+        .collect::<Vec<_>>();
+    // Indexes without include columns store an empty list
+    let include_field_ids = row
+        .utf8(5)?
+        .expect("include_field_ids is not null")
+        .split(",")
+        .filter(|id| !id.is_empty())
+        .map(|id| id.parse::<i64>().unwrap())
+        .collect::<Vec<_>>();
+    let where_predicate = match row.utf8(7)? {
+        Some(predicate_str) => Some(serde_json::from_str(predicate_str).map_err(|e| {
+            ILError::InternalError(format!(
+                "Failed to deserialize predicate of index {index_name}: {e:?}"
+            ))
+        })?),
+        None => None,
+    };
+    Ok(IndexRecord {
+        index_id: row.int64(0)?.expect("index_id is not null"),
+        index_name: index_name.clone(),
+        index_kind: row.utf8(2)?.expect("index_kind is not null").clone(),
+        table_id: row.int64(3)?.expect("table_id is not null"),
+        key_field_ids,
+        include_field_ids,
+        params: row.utf8(6)?.expect("params is not null").clone(),
+        where_predicate,
+    })
+}
+
+fn index_files_sql(table_id: i64) -> String {
+    format!(
+        "SELECT {} FROM indexlake_index_file WHERE index_id IN (SELECT index_id FROM indexlake_index WHERE table_id = {table_id})",
+        IndexFileRecord::select_items().join(", ")
+    )
+}
+
+fn index_file_schema() -> CatalogSchemaRef {
+    Arc::new(CatalogSchema::new(vec![
+        Column::new("index_file_id", CatalogDataType::Int64, false),
+        Column::new("index_id", CatalogDataType::Int64, false),
+        Column::new("data_file_id", CatalogDataType::Int64, false),
+        Column::new("relative_path", CatalogDataType::Utf8, false),
+        Column::new("metadata", CatalogDataType::Binary, true),
+    ]))
+}
+
+fn parse_index_file(row: &Row) -> ILResult<IndexFileRecord> {
+    Ok(IndexFileRecord {
+        index_file_id: row.int64(0)?.expect("index_file_id is not null"),
+        index_id: row.int64(1)?.expect("index_id is not null"),
+        data_file_id: row.int64(2)?.expect("data_file_id is not null"),
+        relative_path: row.utf8(3)?.expect("relative_path is not null").to_string(),
+        metadata: row.binary(4)?.cloned(),
+    })
+}
+
+fn snapshots_sql(table_id: i64) -> String {
+    format!(
+        "SELECT {} FROM indexlake_snapshot WHERE table_id = {table_id} ORDER BY snapshot_id",
+        SnapshotRecord::select_items().join(", ")
+    )
+}
+
+fn snapshot_schema() -> CatalogSchemaRef {
+    Arc::new(CatalogSchema::new(vec![
+        Column::new("snapshot_id", CatalogDataType::Int64, false),
+        Column::new("table_id", CatalogDataType::Int64, false),
+        Column::new("timestamp_ms", CatalogDataType::Int64, false),
+        Column::new("max_row_id", CatalogDataType::Int64, false),
+        Column::new("operation", CatalogDataType::Utf8, true),
+        Column::new("added_rows", CatalogDataType::Int64, true),
+        Column::new("updated_rows", CatalogDataType::Int64, true),
+        Column::new("removed_rows", CatalogDataType::Int64, true),
+        Column::new("expired", CatalogDataType::Boolean, true),
+    ]))
+}
+
+fn parse_snapshot(row: &Row) -> ILResult<SnapshotRecord> {
+    Ok(SnapshotRecord {
+        snapshot_id: row.int64(0)?.expect("snapshot_id is not null"),
+        table_id: row.int64(1)?.expect("table_id is not null"),
+        timestamp_ms: row.int64(2)?.expect("timestamp_ms is not null"),
+        max_row_id: row.int64(3)?.expect("max_row_id is not null"),
+        operation: row.utf8(4)?.map(|operation| operation.to_string()),
+        added_rows: row.int64(5)?.unwrap_or(0),
+        updated_rows: row.int64(6)?.unwrap_or(0),
+        removed_rows: row.int64(7)?.unwrap_or(0),
+        expired: row.boolean(8)?.unwrap_or(false),
+    })
+}
+
+fn row_history_schema() -> CatalogSchemaRef {
+    Arc::new(CatalogSchema::new(vec![
+        Column::new("table_id", CatalogDataType::Int64, false),
+        Column::new("row_id", CatalogDataType::Int64, false),
+        Column::new("end_snapshot_id", CatalogDataType::Int64, false),
+        Column::new("location", CatalogDataType::Utf8, false),
+        Column::new("row_values", CatalogDataType::Binary, true),
+    ]))
+}
+
+fn parse_row_history(row: &Row) -> ILResult<RowHistoryRecord> {
+    Ok(RowHistoryRecord {
+        table_id: row.int64(0)?.expect("table_id is not null"),
+        row_id: row.int64(1)?.expect("row_id is not null"),
+        end_snapshot_id: row.int64(2)?.expect("end_snapshot_id is not null"),
+        location: row.utf8(3)?.expect("location is not null").parse()?,
+        row_values: row.binary(4)?.cloned(),
+    })
+}
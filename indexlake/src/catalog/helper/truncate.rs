@@ -4,12 +4,13 @@ use crate::{ILResult, catalog::CatalogDatabase};
 impl TransactionHelper {
     pub(crate) async fn truncate_inline_row_table(&mut self, table_id: i64) -> ILResult<()> {
         match self.database {
-            CatalogDatabase::Sqlite => {
+            // TRUNCATE is DDL in MySQL and would implicitly commit the transaction
+            CatalogDatabase::Sqlite | CatalogDatabase::MySql => {
                 self.transaction
                     .execute_batch(&[format!("DELETE FROM indexlake_inline_row_{table_id}")])
                     .await
             }
-            CatalogDatabase::Postgres => {
+            CatalogDatabase::Postgres | CatalogDatabase::DuckDb => {
                 self.transaction
                     .execute_batch(&[format!("TRUNCATE TABLE indexlake_inline_row_{table_id}")])
                     .await
@@ -19,16 +20,33 @@ impl TransactionHelper {
 
     pub(crate) async fn truncate_row_metadata_table(&mut self, table_id: i64) -> ILResult<()> {
         match self.database {
-            CatalogDatabase::Sqlite => {
+            // TRUNCATE is DDL in MySQL and would implicitly commit the transaction
+            CatalogDatabase::Sqlite | CatalogDatabase::MySql => {
                 self.transaction
                     .execute_batch(&[format!("DELETE FROM indexlake_row_metadata_{table_id}")])
                     .await
             }
-            CatalogDatabase::Postgres => {
+            CatalogDatabase::Postgres | CatalogDatabase::DuckDb => {
                 self.transaction
                     .execute_batch(&[format!("TRUNCATE TABLE indexlake_row_metadata_{table_id}")])
                     .await
             }
         }
     }
+
+    pub(crate) async fn truncate_unique_key_table(&mut self, table_id: i64) -> ILResult<()> {
+        match self.database {
+            // TRUNCATE is DDL in MySQL and would implicitly commit the transaction
+            CatalogDatabase::Sqlite | CatalogDatabase::MySql => {
+                self.transaction
+                    .execute_batch(&[format!("DELETE FROM indexlake_unique_key_{table_id}")])
+                    .await
+            }
+            CatalogDatabase::Postgres | CatalogDatabase::DuckDb => {
+                self.transaction
+                    .execute_batch(&[format!("TRUNCATE TABLE indexlake_unique_key_{table_id}")])
+                    .await
+            }
+        }
+    }
 }
@@ -1,12 +1,155 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use crate::{
-    ILResult,
-    catalog::{INTERNAL_ROW_ID_FIELD_NAME, Scalar, TransactionHelper},
+    ILError, ILResult,
+    catalog::{ColumnStats, INTERNAL_ROW_ID_FIELD_NAME, RowHistoryRecord, TransactionHelper},
     expr::Expr,
+    table::TableConfig,
 };
 
 impl TransactionHelper {
+    pub(crate) async fn update_namespace_name(
+        &mut self,
+        namespace_id: i64,
+        namespace_name: &str,
+    ) -> ILResult<usize> {
+        self.transaction
+            .execute(&format!(
+                "UPDATE indexlake_namespace SET namespace_name = '{namespace_name}' WHERE namespace_id = {namespace_id}"
+            ))
+            .await
+    }
+
+    pub(crate) async fn update_table_name(
+        &mut self,
+        table_id: i64,
+        table_name: &str,
+    ) -> ILResult<usize> {
+        self.transaction
+            .execute(&format!(
+                "UPDATE indexlake_table SET table_name = '{table_name}' WHERE table_id = {table_id}"
+            ))
+            .await
+    }
+
+    pub(crate) async fn update_table_config(
+        &mut self,
+        table_id: i64,
+        config: &TableConfig,
+    ) -> ILResult<usize> {
+        let config_str = serde_json::to_string(config).map_err(|e| {
+            ILError::InternalError(format!("Failed to serialize table config: {e:?}"))
+        })?;
+        self.transaction
+            .execute(&format!(
+                "UPDATE indexlake_table SET config = '{}' WHERE table_id = {table_id}",
+                config_str.replace('\'', "''")
+            ))
+            .await
+    }
+
+    pub(crate) async fn bump_table_schema_version(&mut self, table_id: i64) -> ILResult<usize> {
+        self.transaction
+            .execute(&format!(
+                "UPDATE indexlake_table SET schema_version = COALESCE(schema_version, 0) + 1 WHERE table_id = {table_id}"
+            ))
+            .await
+    }
+
+    /// Points the data file, index files, row metadata and row histories of the table that
+    /// refer to the file at `old_path` to `new_path`.
+    pub(crate) async fn update_file_path(
+        &mut self,
+        table_id: i64,
+        old_path: &str,
+        new_path: &str,
+    ) -> ILResult<()> {
+        let old_location = format!("parquet:{old_path}:");
+        let new_location = format!("parquet:{new_path}:");
+        // Avoid LIKE whose escaping rules differ between databases
+        let location_matches = format!(
+            "SUBSTR(location, 1, {}) = '{old_location}'",
+            old_location.chars().count()
+        );
+        let set_location =
+            format!("location = REPLACE(location, '{old_location}', '{new_location}')");
+        self.transaction
+            .execute_batch(&[
+                format!("UPDATE indexlake_data_file SET relative_path = '{new_path}' WHERE table_id = {table_id} AND relative_path = '{old_path}'"),
+                format!("UPDATE indexlake_index_file SET relative_path = '{new_path}' WHERE relative_path = '{old_path}' AND index_id IN (SELECT index_id FROM indexlake_index WHERE table_id = {table_id})"),
+                format!("UPDATE indexlake_row_metadata_{table_id} SET {set_location} WHERE {location_matches}"),
+                format!("UPDATE indexlake_row_history SET {set_location} WHERE table_id = {table_id} AND {location_matches}"),
+            ])
+            .await
+    }
+
+    pub(crate) async fn update_field_name(
+        &mut self,
+        field_id: i64,
+        field_name: &str,
+    ) -> ILResult<usize> {
+        self.transaction
+            .execute(&format!(
+                "UPDATE indexlake_field SET field_name = '{field_name}' WHERE field_id = {field_id}"
+            ))
+            .await
+    }
+
+    pub(crate) async fn update_field_metadata(
+        &mut self,
+        field_id: i64,
+        metadata: &HashMap<String, String>,
+    ) -> ILResult<usize> {
+        let metadata_str = serde_json::to_string(metadata).map_err(|e| {
+            ILError::InternalError(format!("Failed to serialize field metadata: {e:?}"))
+        })?;
+        self.transaction
+            .execute(&format!(
+                "UPDATE indexlake_field SET metadata = '{metadata_str}' WHERE field_id = {field_id}"
+            ))
+            .await
+    }
+
+    pub(crate) async fn update_data_file_column_stats(
+        &mut self,
+        data_file_id: i64,
+        column_stats: &BTreeMap<String, ColumnStats>,
+    ) -> ILResult<usize> {
+        let stats_str = serde_json::to_string(column_stats).map_err(|e| {
+            ILError::InternalError(format!("Failed to serialize column stats: {e:?}"))
+        })?;
+        self.transaction
+            .execute(&format!(
+                "UPDATE indexlake_data_file SET column_stats = '{}' WHERE data_file_id = {data_file_id}",
+                stats_str.replace('\'', "''")
+            ))
+            .await
+    }
+
+    pub(crate) async fn update_row_history_values(
+        &mut self,
+        row_history: &RowHistoryRecord,
+    ) -> ILResult<usize> {
+        let row_values_sql = match &row_history.row_values {
+            Some(row_values) => self.database.sql_binary_value(row_values),
+            None => "NULL".to_string(),
+        };
+        self.transaction
+            .execute(&format!(
+                "UPDATE indexlake_row_history SET row_values = {row_values_sql} WHERE table_id = {} AND row_id = {} AND end_snapshot_id = {}",
+                row_history.table_id, row_history.row_id, row_history.end_snapshot_id
+            ))
+            .await
+    }
+
+    pub(crate) async fn mark_snapshot_expired(&mut self, snapshot_id: i64) -> ILResult<usize> {
+        self.transaction
+            .execute(&format!(
+                "UPDATE indexlake_snapshot SET expired = TRUE WHERE snapshot_id = {snapshot_id}"
+            ))
+            .await
+    }
+
     pub(crate) async fn mark_rows_deleted_by_row_ids(
         &mut self,
         table_id: i64,
@@ -29,38 +172,12 @@ impl TransactionHelper {
         condition: &Expr,
     ) -> ILResult<usize> {
         let sql = format!(
-            "UPDATE indexlake_row_metadata_{table_id} SET deleted = TRUE WHERE {}",
+            "UPDATE indexlake_row_metadata_{table_id} SET deleted = TRUE WHERE ({}) AND deleted = FALSE",
             condition.to_sql(self.database)?
         );
         self.transaction.execute(&sql).await
     }
 
-    pub(crate) async fn update_inline_rows(
-        &mut self,
-        table_id: i64,
-        set_map: &HashMap<String, Scalar>,
-        condition: &Expr,
-    ) -> ILResult<()> {
-        let mut set_strs = Vec::new();
-        for (field_name, new_value) in set_map {
-            set_strs.push(format!(
-                "{} = {}",
-                self.database.sql_identifier(field_name),
-                new_value.to_sql(self.database),
-            ));
-        }
-
-        self.transaction
-            .execute(&format!(
-                "UPDATE indexlake_inline_row_{table_id} SET {} WHERE {}",
-                set_strs.join(", "),
-                condition.to_sql(self.database)?
-            ))
-            .await?;
-
-        Ok(())
-    }
-
     pub(crate) async fn update_row_locations(
         &mut self,
         table_id: i64,
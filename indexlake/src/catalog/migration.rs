@@ -0,0 +1,391 @@
+use std::sync::Arc;
+
+use futures::TryStreamExt;
+
+use crate::{
+    ILError, ILResult,
+    catalog::{Catalog, CatalogDataType, CatalogDatabase, CatalogSchema, Column, Transaction},
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+macro_rules! migration {
+    ($version:literal, $name:literal, $path:literal) => {
+        Migration {
+            version: $version,
+            name: $name,
+            sql: include_str!($path),
+        }
+    };
+}
+
+static SQLITE_MIGRATIONS: &[Migration] = &[
+    migration!(
+        1,
+        "init_catalog",
+        "migrations/sqlite/v0001_init_catalog.sql"
+    ),
+    migration!(
+        2,
+        "add_table_id_indexes",
+        "migrations/sqlite/v0002_add_table_id_indexes.sql"
+    ),
+    migration!(
+        3,
+        "add_snapshots",
+        "migrations/sqlite/v0003_add_snapshots.sql"
+    ),
+    migration!(
+        4,
+        "add_data_file_partition_values",
+        "migrations/sqlite/v0004_add_data_file_partition_values.sql"
+    ),
+    migration!(
+        5,
+        "add_index_file_metadata",
+        "migrations/sqlite/v0005_add_index_file_metadata.sql"
+    ),
+    migration!(
+        6,
+        "add_data_file_checksum",
+        "migrations/sqlite/v0006_add_data_file_checksum.sql"
+    ),
+    migration!(
+        7,
+        "add_index_where_predicate",
+        "migrations/sqlite/v0007_add_index_where_predicate.sql"
+    ),
+    migration!(
+        8,
+        "add_data_file_column_stats",
+        "migrations/sqlite/v0008_add_data_file_column_stats.sql"
+    ),
+    migration!(
+        9,
+        "add_table_schema_version",
+        "migrations/sqlite/v0009_add_table_schema_version.sql"
+    ),
+    migration!(
+        10,
+        "add_snapshot_operations",
+        "migrations/sqlite/v0010_add_snapshot_operations.sql"
+    ),
+    migration!(
+        11,
+        "add_namespace_properties",
+        "migrations/sqlite/v0011_add_namespace_properties.sql"
+    ),
+];
+
+static POSTGRES_MIGRATIONS: &[Migration] = &[
+    migration!(
+        1,
+        "init_catalog",
+        "migrations/postgres/v0001_init_catalog.sql"
+    ),
+    migration!(
+        2,
+        "add_table_id_indexes",
+        "migrations/postgres/v0002_add_table_id_indexes.sql"
+    ),
+    migration!(
+        3,
+        "add_snapshots",
+        "migrations/postgres/v0003_add_snapshots.sql"
+    ),
+    migration!(
+        4,
+        "add_data_file_partition_values",
+        "migrations/postgres/v0004_add_data_file_partition_values.sql"
+    ),
+    migration!(
+        5,
+        "add_index_file_metadata",
+        "migrations/postgres/v0005_add_index_file_metadata.sql"
+    ),
+    migration!(
+        6,
+        "add_data_file_checksum",
+        "migrations/postgres/v0006_add_data_file_checksum.sql"
+    ),
+    migration!(
+        7,
+        "add_index_where_predicate",
+        "migrations/postgres/v0007_add_index_where_predicate.sql"
+    ),
+    migration!(
+        8,
+        "add_data_file_column_stats",
+        "migrations/postgres/v0008_add_data_file_column_stats.sql"
+    ),
+    migration!(
+        9,
+        "add_table_schema_version",
+        "migrations/postgres/v0009_add_table_schema_version.sql"
+    ),
+    migration!(
+        10,
+        "add_snapshot_operations",
+        "migrations/postgres/v0010_add_snapshot_operations.sql"
+    ),
+    migration!(
+        11,
+        "add_namespace_properties",
+        "migrations/postgres/v0011_add_namespace_properties.sql"
+    ),
+];
+
+static MYSQL_MIGRATIONS: &[Migration] = &[
+    migration!(1, "init_catalog", "migrations/mysql/v0001_init_catalog.sql"),
+    migration!(
+        2,
+        "add_table_id_indexes",
+        "migrations/mysql/v0002_add_table_id_indexes.sql"
+    ),
+    migration!(
+        3,
+        "add_snapshots",
+        "migrations/mysql/v0003_add_snapshots.sql"
+    ),
+    migration!(
+        4,
+        "add_data_file_partition_values",
+        "migrations/mysql/v0004_add_data_file_partition_values.sql"
+    ),
+    migration!(
+        5,
+        "add_index_file_metadata",
+        "migrations/mysql/v0005_add_index_file_metadata.sql"
+    ),
+    migration!(
+        6,
+        "add_data_file_checksum",
+        "migrations/mysql/v0006_add_data_file_checksum.sql"
+    ),
+    migration!(
+        7,
+        "add_index_where_predicate",
+        "migrations/mysql/v0007_add_index_where_predicate.sql"
+    ),
+    migration!(
+        8,
+        "add_data_file_column_stats",
+        "migrations/mysql/v0008_add_data_file_column_stats.sql"
+    ),
+    migration!(
+        9,
+        "add_table_schema_version",
+        "migrations/mysql/v0009_add_table_schema_version.sql"
+    ),
+    migration!(
+        10,
+        "add_snapshot_operations",
+        "migrations/mysql/v0010_add_snapshot_operations.sql"
+    ),
+    migration!(
+        11,
+        "add_namespace_properties",
+        "migrations/mysql/v0011_add_namespace_properties.sql"
+    ),
+];
+
+static DUCKDB_MIGRATIONS: &[Migration] = &[
+    migration!(
+        1,
+        "init_catalog",
+        "migrations/duckdb/v0001_init_catalog.sql"
+    ),
+    migration!(
+        2,
+        "add_table_id_indexes",
+        "migrations/duckdb/v0002_add_table_id_indexes.sql"
+    ),
+    migration!(
+        3,
+        "add_snapshots",
+        "migrations/duckdb/v0003_add_snapshots.sql"
+    ),
+    migration!(
+        4,
+        "add_data_file_partition_values",
+        "migrations/duckdb/v0004_add_data_file_partition_values.sql"
+    ),
+    migration!(
+        5,
+        "add_index_file_metadata",
+        "migrations/duckdb/v0005_add_index_file_metadata.sql"
+    ),
+    migration!(
+        6,
+        "add_data_file_checksum",
+        "migrations/duckdb/v0006_add_data_file_checksum.sql"
+    ),
+    migration!(
+        7,
+        "add_index_where_predicate",
+        "migrations/duckdb/v0007_add_index_where_predicate.sql"
+    ),
+    migration!(
+        8,
+        "add_data_file_column_stats",
+        "migrations/duckdb/v0008_add_data_file_column_stats.sql"
+    ),
+    migration!(
+        9,
+        "add_table_schema_version",
+        "migrations/duckdb/v0009_add_table_schema_version.sql"
+    ),
+    migration!(
+        10,
+        "add_snapshot_operations",
+        "migrations/duckdb/v0010_add_snapshot_operations.sql"
+    ),
+    migration!(
+        11,
+        "add_namespace_properties",
+        "migrations/duckdb/v0011_add_namespace_properties.sql"
+    ),
+];
+
+/// Catalog schema version this library expects.
+pub const CATALOG_VERSION: i64 = 11;
+
+/// Ordered catalog schema migrations of the given database.
+pub fn catalog_migrations(database: CatalogDatabase) -> &'static [Migration] {
+    match database {
+        CatalogDatabase::Sqlite => SQLITE_MIGRATIONS,
+        CatalogDatabase::Postgres => POSTGRES_MIGRATIONS,
+        CatalogDatabase::MySql => MYSQL_MIGRATIONS,
+        CatalogDatabase::DuckDb => DUCKDB_MIGRATIONS,
+    }
+}
+
+impl Migration {
+    /// Split the script into single statements, as not every driver accepts multi-statement
+    /// queries.
+    pub fn statements(&self) -> Vec<String> {
+        self.sql
+            .split(';')
+            .map(|stmt| stmt.trim())
+            .filter(|stmt| !stmt.is_empty())
+            .map(|stmt| stmt.to_string())
+            .collect()
+    }
+}
+
+pub(crate) async fn migrate_catalog<C: Catalog + ?Sized>(catalog: &C) -> ILResult<()> {
+    let database = catalog.database();
+    let current_version = get_catalog_version(catalog).await?;
+    if current_version > CATALOG_VERSION {
+        return Err(ILError::CatalogError(format!(
+            "catalog version {current_version} is newer than the version {CATALOG_VERSION} supported by this library, please upgrade indexlake"
+        )));
+    }
+    if current_version == CATALOG_VERSION {
+        return Ok(());
+    }
+
+    let mut transaction = catalog.transaction().await?;
+    transaction
+        .execute("CREATE TABLE IF NOT EXISTS indexlake_catalog_version (version BIGINT NOT NULL)")
+        .await?;
+    for migration in catalog_migrations(database) {
+        if migration.version <= current_version {
+            continue;
+        }
+        log::info!(
+            "applying catalog migration v{} {}",
+            migration.version,
+            migration.name
+        );
+        transaction.execute_batch(&migration.statements()).await?;
+    }
+    set_catalog_version(transaction.as_mut(), CATALOG_VERSION).await?;
+    transaction.commit().await
+}
+
+/// Returns 0 for an empty catalog. Catalogs created from `init_catalog.sql` before version
+/// tracking existed are treated as version 1.
+pub(crate) async fn get_catalog_version<C: Catalog + ?Sized>(catalog: &C) -> ILResult<i64> {
+    if table_exists(catalog, "indexlake_catalog_version").await? {
+        let schema = Arc::new(CatalogSchema::new(vec![Column::new(
+            "version",
+            CatalogDataType::Int64,
+            true,
+        )]));
+        let rows = catalog
+            .query("SELECT MAX(version) FROM indexlake_catalog_version", schema)
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+        if let Some(version) = rows.first().and_then(|row| row.int64(0).transpose()) {
+            return version;
+        }
+    }
+    if table_exists(catalog, "indexlake_namespace").await? {
+        Ok(1)
+    } else {
+        Ok(0)
+    }
+}
+
+async fn set_catalog_version(transaction: &mut dyn Transaction, version: i64) -> ILResult<()> {
+    transaction
+        .execute_batch(&[
+            "DELETE FROM indexlake_catalog_version".to_string(),
+            format!("INSERT INTO indexlake_catalog_version (version) VALUES ({version})"),
+        ])
+        .await
+}
+
+async fn table_exists<C: Catalog + ?Sized>(catalog: &C, table_name: &str) -> ILResult<bool> {
+    let sql = match catalog.database() {
+        CatalogDatabase::Sqlite => {
+            format!("SELECT name FROM sqlite_master WHERE type = 'table' AND name = '{table_name}'")
+        }
+        CatalogDatabase::Postgres | CatalogDatabase::DuckDb => format!(
+            "SELECT table_name::TEXT FROM information_schema.tables WHERE table_schema = current_schema() AND table_name = '{table_name}'"
+        ),
+        CatalogDatabase::MySql => format!(
+            "SELECT table_name FROM information_schema.tables WHERE table_schema = DATABASE() AND table_name = '{table_name}'"
+        ),
+    };
+    let schema = Arc::new(CatalogSchema::new(vec![Column::new(
+        "table_name",
+        CatalogDataType::Utf8,
+        false,
+    )]));
+    let rows = catalog
+        .query(&sql, schema)
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    Ok(!rows.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalog_migrations() {
+        for database in [
+            CatalogDatabase::Sqlite,
+            CatalogDatabase::Postgres,
+            CatalogDatabase::MySql,
+            CatalogDatabase::DuckDb,
+        ] {
+            let migrations = catalog_migrations(database);
+            assert_eq!(migrations.len() as i64, CATALOG_VERSION);
+            for (idx, migration) in migrations.iter().enumerate() {
+                assert_eq!(migration.version, idx as i64 + 1);
+                assert!(!migration.statements().is_empty());
+            }
+        }
+    }
+}
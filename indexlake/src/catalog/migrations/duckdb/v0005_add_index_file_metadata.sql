@@ -0,0 +1 @@
+ALTER TABLE indexlake_index_file ADD COLUMN metadata BLOB NULL;
@@ -0,0 +1 @@
+ALTER TABLE indexlake_namespace ADD COLUMN properties VARCHAR NULL;
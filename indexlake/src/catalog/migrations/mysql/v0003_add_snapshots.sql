@@ -0,0 +1,18 @@
+CREATE TABLE indexlake_snapshot (
+    snapshot_id BIGINT PRIMARY KEY,
+    table_id BIGINT NOT NULL,
+    timestamp_ms BIGINT NOT NULL,
+    max_row_id BIGINT NOT NULL
+);
+
+CREATE INDEX indexlake_snapshot_table_id_idx ON indexlake_snapshot (table_id);
+
+CREATE TABLE indexlake_row_history (
+    table_id BIGINT NOT NULL,
+    row_id BIGINT NOT NULL,
+    end_snapshot_id BIGINT NOT NULL,
+    location TEXT NOT NULL,
+    row_values LONGBLOB NULL
+);
+
+CREATE INDEX indexlake_row_history_table_id_idx ON indexlake_row_history (table_id, end_snapshot_id);
@@ -0,0 +1,5 @@
+ALTER TABLE indexlake_snapshot ADD COLUMN operation TEXT NULL;
+ALTER TABLE indexlake_snapshot ADD COLUMN added_rows BIGINT NULL;
+ALTER TABLE indexlake_snapshot ADD COLUMN updated_rows BIGINT NULL;
+ALTER TABLE indexlake_snapshot ADD COLUMN removed_rows BIGINT NULL;
+ALTER TABLE indexlake_snapshot ADD COLUMN expired BOOLEAN NULL;
@@ -0,0 +1,5 @@
+CREATE INDEX indexlake_field_table_id_idx ON indexlake_field (table_id);
+
+CREATE INDEX indexlake_data_file_table_id_idx ON indexlake_data_file (table_id);
+
+CREATE INDEX indexlake_index_table_id_idx ON indexlake_index (table_id);
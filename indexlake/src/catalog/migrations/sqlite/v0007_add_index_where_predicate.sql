@@ -0,0 +1 @@
+ALTER TABLE indexlake_index ADD COLUMN where_predicate VARCHAR NULL;
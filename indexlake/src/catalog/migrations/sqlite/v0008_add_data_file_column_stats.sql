@@ -0,0 +1 @@
+ALTER TABLE indexlake_data_file ADD COLUMN column_stats VARCHAR NULL;
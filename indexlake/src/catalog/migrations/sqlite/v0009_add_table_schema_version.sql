@@ -0,0 +1 @@
+ALTER TABLE indexlake_table ADD COLUMN schema_version BIGINT NULL;
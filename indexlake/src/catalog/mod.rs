@@ -1,13 +1,23 @@
+mod cache;
 mod database;
+mod dump;
+mod health;
 mod helper;
+mod migration;
 mod record;
+mod retry;
 mod row;
 mod scalar;
 mod schema;
 
+pub use cache::*;
 pub use database::*;
+pub use dump::*;
+pub use health::*;
 pub(crate) use helper::*;
+pub use migration::*;
 pub(crate) use record::*;
+pub use retry::*;
 pub use row::*;
 pub use scalar::*;
 pub use schema::*;
@@ -25,6 +35,16 @@ use std::{
 pub type RowStream<'a> = Pin<Box<dyn Stream<Item = ILResult<Row>> + Send + 'a>>;
 
 pub static INTERNAL_ROW_ID_FIELD_NAME: &str = "_indexlake_row_id";
+/// Field metadata key the default value of an added column is persisted under. It is not
+/// part of the field metadata exposed in the table schema.
+pub(crate) static FIELD_DEFAULT_VALUE_METADATA_KEY: &str = "indexlake.default_value";
+/// Field metadata key marking a dropped column. Dropped fields are kept in the catalog so their
+/// name is not reused while data files still contain the column.
+pub(crate) static FIELD_DROPPED_METADATA_KEY: &str = "indexlake.dropped";
+/// Field metadata key the default applied to inserted rows is declared and persisted under, see
+/// [`ColumnDefault`](crate::table::ColumnDefault). It is not part of the field metadata
+/// exposed in the table schema.
+pub(crate) static FIELD_COLUMN_DEFAULT_METADATA_KEY: &str = "indexlake.column_default";
 pub static INTERNAL_ROW_ID_FIELD_REF: LazyLock<FieldRef> = LazyLock::new(|| {
     Arc::new(Field::new(
         INTERNAL_ROW_ID_FIELD_NAME,
@@ -41,6 +61,98 @@ pub trait Catalog: Debug + Send + Sync {
 
     /// Begin a new transaction.
     async fn transaction(&self) -> ILResult<Box<dyn Transaction>>;
+
+    /// Begin a new transaction at the given isolation level, see [`IsolationLevel`] for the
+    /// levels each backend honors.
+    async fn begin_transaction(&self, level: IsolationLevel) -> ILResult<Box<dyn Transaction>>;
+
+    /// Retry policy for transactions aborted by a conflicting concurrent transaction, see
+    /// [`ILError::is_conflict`](crate::ILError::is_conflict). Write operations re-run the
+    /// whole transaction accordingly. `None` surfaces conflicts to the caller right away.
+    fn transaction_retry_policy(&self) -> Option<RetryPolicy> {
+        None
+    }
+
+    /// Read-only catalogs reject operations modifying tables with
+    /// [`ILError::CatalogReadOnly`](crate::ILError::CatalogReadOnly) before touching the catalog
+    /// or storage. Scans and metadata queries keep working.
+    fn read_only(&self) -> bool {
+        false
+    }
+
+    /// Connection pool status, `None` if the catalog is not backed by a pool.
+    fn pool_status(&self) -> Option<PoolStatus> {
+        None
+    }
+
+    /// Check the catalog database is reachable with a trivial version query.
+    async fn health_check(&self) -> ILResult<CatalogHealth> {
+        check_catalog_health(self).await
+    }
+
+    /// Apply pending catalog schema migrations in a single transaction.
+    ///
+    /// Fails if the catalog was created by a newer indexlake version. Note that MySQL commits
+    /// implicitly after DDL statements, so a failed migration there is not rolled back.
+    async fn migrate(&self) -> ILResult<()> {
+        migrate_catalog(self).await
+    }
+
+    /// Current catalog schema version, 0 for an empty catalog.
+    async fn catalog_version(&self) -> ILResult<i64> {
+        get_catalog_version(self).await
+    }
+
+    /// Export namespaces, tables, fields, indexes, data file and row metadata and inline rows
+    /// into a [`CatalogDump`]. Data files in storage are left untouched.
+    async fn export_dump(&self) -> ILResult<CatalogDump> {
+        export_catalog_dump(self).await
+    }
+
+    /// Load a [`CatalogDump`] of a catalog of any backend into this catalog, which is migrated
+    /// first and must not contain any namespace or table yet. The dumped data files must be
+    /// reachable through the storage used with this catalog.
+    async fn import_dump(&self, dump: &CatalogDump) -> ILResult<()> {
+        import_catalog_dump(self, dump).await
+    }
+}
+
+/// Transaction isolation level.
+///
+/// - Postgres honors all levels. [`Catalog::transaction`] uses `Serializable` unless configured
+///   otherwise, serialization failures are retried per [`Catalog::transaction_retry_policy`].
+/// - MySQL/MariaDB (InnoDB) honors all levels, `RepeatableRead` is its default.
+/// - SQLite, DuckDB and the memory catalog run one transaction at a time, so every transaction
+///   is serializable. They accept `Serializable` and reject weaker levels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IsolationLevel {
+    ReadCommitted,
+    RepeatableRead,
+    Serializable,
+}
+
+impl IsolationLevel {
+    pub fn to_sql(&self) -> &'static str {
+        match self {
+            IsolationLevel::ReadCommitted => "READ COMMITTED",
+            IsolationLevel::RepeatableRead => "REPEATABLE READ",
+            IsolationLevel::Serializable => "SERIALIZABLE",
+        }
+    }
+}
+
+impl std::fmt::Display for IsolationLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_sql())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolStatus {
+    /// Connections currently checked out of the pool.
+    pub active_connections: u32,
+    /// Connections sitting idle in the pool.
+    pub idle_connections: u32,
 }
 
 // Transaction should be rolled back when dropped.
@@ -59,6 +171,39 @@ pub trait Transaction: Debug + Send {
     /// Execute a batch of SQL statements.
     async fn execute_batch(&mut self, sqls: &[String]) -> ILResult<()>;
 
+    /// Insert `rows` into the catalog table `table_name` with at most `batch_size` rows per
+    /// statement, returning the number of inserted rows. `columns` are quoted identifiers and
+    /// the values are of the [`CatalogDataType`]s. The default inlines the values into a
+    /// multi-row `INSERT ... VALUES` statement per batch, backends may bind them to prepared
+    /// statements instead.
+    async fn insert_rows(
+        &mut self,
+        database: CatalogDatabase,
+        table_name: &str,
+        columns: &[String],
+        rows: &[Vec<Scalar>],
+        batch_size: usize,
+    ) -> ILResult<usize> {
+        let insert_prefix = format!("INSERT INTO {table_name} ({}) VALUES ", columns.join(", "));
+        let mut count = 0;
+        for chunk in rows.chunks(batch_size) {
+            let values = chunk
+                .iter()
+                .map(|row| {
+                    let values = row
+                        .iter()
+                        .map(|value| value.to_sql(database))
+                        .collect::<Vec<_>>();
+                    format!("({})", values.join(", "))
+                })
+                .collect::<Vec<_>>();
+            count += self
+                .execute(&format!("{insert_prefix}{}", values.join(", ")))
+                .await?;
+        }
+        Ok(count)
+    }
+
     /// Commit the transaction.
     async fn commit(&mut self) -> ILResult<()>;
 
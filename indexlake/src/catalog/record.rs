@@ -1,17 +1,50 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
 
 use crate::{
     ILError, ILResult,
-    catalog::{CatalogDatabase, INTERNAL_ROW_ID_FIELD_NAME},
+    catalog::{CatalogDatabase, INTERNAL_ROW_ID_FIELD_NAME, Scalar},
+    expr::Expr,
     table::TableConfig,
 };
 
+#[derive(Debug, Clone)]
+pub(crate) struct NamespaceRecord {
+    pub(crate) namespace_id: i64,
+    pub(crate) namespace_name: String,
+    pub(crate) properties: HashMap<String, String>,
+}
+
+impl NamespaceRecord {
+    pub(crate) fn to_sql(&self) -> ILResult<String> {
+        let properties_str = serde_json::to_string(&self.properties).map_err(|e| {
+            ILError::InternalError(format!("Failed to serialize namespace properties: {e:?}"))
+        })?;
+        Ok(format!(
+            "({}, '{}', '{}')",
+            self.namespace_id,
+            self.namespace_name,
+            properties_str.replace('\'', "''")
+        ))
+    }
+
+    pub(crate) fn select_items() -> Vec<&'static str> {
+        vec!["namespace_id", "namespace_name", "properties"]
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct TableRecord {
     pub(crate) table_id: i64,
     pub(crate) table_name: String,
     pub(crate) namespace_id: i64,
     pub(crate) config: TableConfig,
+    /// Number of schema changes of the table, bumped on every added or dropped column.
+    pub(crate) schema_version: i64,
 }
 
 impl TableRecord {
@@ -20,13 +53,23 @@ impl TableRecord {
             ILError::InternalError(format!("Failed to serialize table config: {e:?}"))
         })?;
         Ok(format!(
-            "({}, '{}', {}, '{}')",
-            self.table_id, self.table_name, self.namespace_id, config_str
+            "({}, '{}', {}, '{}', {})",
+            self.table_id,
+            self.table_name,
+            self.namespace_id,
+            config_str.replace('\'', "''"),
+            self.schema_version
         ))
     }
 
     pub(crate) fn select_items() -> Vec<&'static str> {
-        vec!["table_id", "table_name", "namespace_id", "config"]
+        vec![
+            "table_id",
+            "table_name",
+            "namespace_id",
+            "config",
+            "schema_version",
+        ]
     }
 }
 
@@ -38,10 +81,26 @@ pub(crate) struct DataFileRecord {
     pub(crate) file_size_bytes: i64,
     pub(crate) record_count: i64,
     pub(crate) row_ids: Vec<i64>,
+    /// Partition of the rows of the file, `None` for unpartitioned tables.
+    pub(crate) partition_values: Option<Vec<Scalar>>,
+    /// CRC32C of the file content, `None` for files written before checksums were kept.
+    pub(crate) checksum: Option<u32>,
+    /// Statistics of the columns by name, `None` for files written before statistics were kept.
+    pub(crate) column_stats: Option<BTreeMap<String, ColumnStats>>,
+}
+
+/// Statistics of a column within a data file, used to skip files that can't match a filter.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct ColumnStats {
+    /// Smallest non-null value, `None` when unknown or when all values are null.
+    pub(crate) min: Option<Scalar>,
+    /// Largest non-null value, `None` when unknown or when all values are null.
+    pub(crate) max: Option<Scalar>,
+    pub(crate) null_count: u64,
 }
 
 impl DataFileRecord {
-    pub(crate) fn to_sql(&self, database: CatalogDatabase) -> String {
+    pub(crate) fn to_sql(&self, database: CatalogDatabase) -> ILResult<String> {
         let row_ids_bytes = self
             .row_ids
             .iter()
@@ -49,15 +108,40 @@ impl DataFileRecord {
             .flatten()
             .collect::<Vec<_>>();
         let row_ids_sql = database.sql_binary_value(&row_ids_bytes);
-        format!(
-            "({}, {}, '{}', {}, {}, {})",
+        let partition_values_sql = match &self.partition_values {
+            Some(values) => {
+                let values_str = serde_json::to_string(values).map_err(|e| {
+                    ILError::InternalError(format!("Failed to serialize partition values: {e:?}"))
+                })?;
+                format!("'{}'", values_str.replace('\'', "''"))
+            }
+            None => "NULL".to_string(),
+        };
+        let checksum_sql = match self.checksum {
+            Some(checksum) => checksum.to_string(),
+            None => "NULL".to_string(),
+        };
+        let column_stats_sql = match &self.column_stats {
+            Some(stats) => {
+                let stats_str = serde_json::to_string(stats).map_err(|e| {
+                    ILError::InternalError(format!("Failed to serialize column stats: {e:?}"))
+                })?;
+                format!("'{}'", stats_str.replace('\'', "''"))
+            }
+            None => "NULL".to_string(),
+        };
+        Ok(format!(
+            "({}, {}, '{}', {}, {}, {}, {}, {}, {})",
             self.data_file_id,
             self.table_id,
             self.relative_path,
             self.file_size_bytes,
             self.record_count,
-            row_ids_sql
-        )
+            row_ids_sql,
+            partition_values_sql,
+            checksum_sql,
+            column_stats_sql
+        ))
     }
 
     pub(crate) fn select_items() -> Vec<&'static str> {
@@ -68,15 +152,26 @@ impl DataFileRecord {
             "file_size_bytes",
             "record_count",
             "row_ids",
+            "partition_values",
+            "checksum",
+            "column_stats",
         ]
     }
 
-    pub(crate) fn build_relative_path(
-        namespace_id: i64,
-        table_id: i64,
-        data_file_id: i64,
-    ) -> String {
-        format!("{}/{}/{}.parquet", namespace_id, table_id, data_file_id)
+    pub(crate) fn build_relative_path(table_dir: &str, data_file_id: i64) -> String {
+        format!("{}/{}.parquet", table_dir, data_file_id)
+    }
+
+    /// Path of a data file written within a catalog transaction that may be re-run. The uuid
+    /// keeps attempts and concurrent writers from overwriting each other's files, the files of
+    /// failed attempts are left to vacuum.
+    pub(crate) fn build_unique_relative_path(table_dir: &str, data_file_id: i64) -> String {
+        format!(
+            "{}/{}-{}.parquet",
+            table_dir,
+            data_file_id,
+            uuid::Uuid::new_v4()
+        )
     }
 }
 
@@ -96,13 +191,13 @@ impl RowMetadataRecord {
         }
     }
 
-    pub(crate) fn to_sql(&self) -> String {
-        format!(
-            "({}, '{}', {})",
-            self.row_id,
-            self.location.to_string(),
-            self.deleted
-        )
+    /// Values of the [`RowMetadataRecord::select_items`] columns.
+    pub(crate) fn to_values(&self) -> Vec<Scalar> {
+        vec![
+            Scalar::Int64(Some(self.row_id)),
+            Scalar::Utf8(Some(self.location.to_string())),
+            Scalar::Boolean(Some(self.deleted)),
+        ]
     }
 
     pub(crate) fn select_items() -> Vec<&'static str> {
@@ -177,10 +272,12 @@ pub(crate) struct IndexRecord {
     pub(crate) key_field_ids: Vec<i64>,
     pub(crate) include_field_ids: Vec<i64>,
     pub(crate) params: String,
+    /// Only rows matching the predicate are indexed, `None` for indexes of all rows.
+    pub(crate) where_predicate: Option<Expr>,
 }
 
 impl IndexRecord {
-    pub(crate) fn to_sql(&self) -> String {
+    pub(crate) fn to_sql(&self) -> ILResult<String> {
         let key_field_ids_str = self
             .key_field_ids
             .iter()
@@ -193,16 +290,26 @@ impl IndexRecord {
             .map(|id| id.to_string())
             .collect::<Vec<_>>()
             .join(",");
-        format!(
-            "({}, '{}', '{}', {}, '{}', '{}', '{}')",
+        let where_predicate_sql = match &self.where_predicate {
+            Some(predicate) => {
+                let predicate_str = serde_json::to_string(predicate).map_err(|e| {
+                    ILError::InternalError(format!("Failed to serialize index predicate: {e:?}"))
+                })?;
+                format!("'{}'", predicate_str.replace('\'', "''"))
+            }
+            None => "NULL".to_string(),
+        };
+        Ok(format!(
+            "({}, '{}', '{}', {}, '{}', '{}', '{}', {})",
             self.index_id,
             self.index_name,
             self.index_kind,
             self.table_id,
             key_field_ids_str,
             include_field_ids_str,
-            self.params
-        )
+            self.params,
+            where_predicate_sql
+        ))
     }
 
     pub(crate) fn select_items() -> Vec<&'static str> {
@@ -214,6 +321,7 @@ impl IndexRecord {
             "key_field_ids",
             "include_field_ids",
             "params",
+            "where_predicate",
         ]
     }
 }
@@ -223,30 +331,150 @@ pub(crate) struct IndexFileRecord {
     pub(crate) index_id: i64,
     pub(crate) data_file_id: i64,
     pub(crate) relative_path: String,
+    /// Summary of the index file kept in the catalog, see [`IndexBuilder::file_metadata`].
+    ///
+    /// [`IndexBuilder::file_metadata`]: crate::index::IndexBuilder::file_metadata
+    pub(crate) metadata: Option<Vec<u8>>,
 }
 
 impl IndexFileRecord {
-    pub(crate) fn to_sql(&self) -> String {
+    pub(crate) fn to_sql(&self, database: CatalogDatabase) -> String {
+        let metadata_sql = match &self.metadata {
+            Some(metadata) => database.sql_binary_value(metadata),
+            None => "NULL".to_string(),
+        };
         format!(
-            "({}, {}, {}, '{}')",
-            self.index_file_id, self.index_id, self.data_file_id, self.relative_path
+            "({}, {}, {}, '{}', {})",
+            self.index_file_id, self.index_id, self.data_file_id, self.relative_path, metadata_sql
         )
     }
 
     pub(crate) fn select_items() -> Vec<&'static str> {
-        vec!["index_file_id", "index_id", "data_file_id", "relative_path"]
+        vec![
+            "index_file_id",
+            "index_id",
+            "data_file_id",
+            "relative_path",
+            "metadata",
+        ]
     }
 
     pub(crate) fn build_relative_path(
-        namespace_id: i64,
-        table_id: i64,
+        table_dir: &str,
         data_file_id: i64,
         index_id: i64,
         index_file_id: i64,
     ) -> String {
         format!(
-            "{}/{}/{}-{}-{}.index",
-            namespace_id, table_id, data_file_id, index_id, index_file_id
+            "{}/{}-{}-{}.index",
+            table_dir, data_file_id, index_id, index_file_id
+        )
+    }
+
+    /// Like [`DataFileRecord::build_unique_relative_path`] for index files.
+    pub(crate) fn build_unique_relative_path(
+        table_dir: &str,
+        data_file_id: i64,
+        index_id: i64,
+        index_file_id: i64,
+    ) -> String {
+        format!(
+            "{}/{}-{}-{}-{}.index",
+            table_dir,
+            data_file_id,
+            index_id,
+            index_file_id,
+            uuid::Uuid::new_v4()
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct SnapshotRecord {
+    pub(crate) snapshot_id: i64,
+    pub(crate) table_id: i64,
+    pub(crate) timestamp_ms: i64,
+    /// Row ids are allocated in increasing order, rows with a greater id were inserted after
+    /// the snapshot.
+    pub(crate) max_row_id: i64,
+    /// Kind of commit, `None` for snapshots committed before operations were recorded.
+    pub(crate) operation: Option<String>,
+    pub(crate) added_rows: i64,
+    pub(crate) updated_rows: i64,
+    pub(crate) removed_rows: i64,
+    /// Marks the latest expired snapshot of the table, kept so that reads of expired snapshots
+    /// can tell them from snapshots that never existed.
+    pub(crate) expired: bool,
+}
+
+impl SnapshotRecord {
+    pub(crate) fn to_sql(&self) -> String {
+        let operation_sql = match &self.operation {
+            Some(operation) => format!("'{operation}'"),
+            None => "NULL".to_string(),
+        };
+        format!(
+            "({}, {}, {}, {}, {}, {}, {}, {}, {})",
+            self.snapshot_id,
+            self.table_id,
+            self.timestamp_ms,
+            self.max_row_id,
+            operation_sql,
+            self.added_rows,
+            self.updated_rows,
+            self.removed_rows,
+            self.expired
         )
     }
+
+    pub(crate) fn select_items() -> Vec<&'static str> {
+        vec![
+            "snapshot_id",
+            "table_id",
+            "timestamp_ms",
+            "max_row_id",
+            "operation",
+            "added_rows",
+            "updated_rows",
+            "removed_rows",
+            "expired",
+        ]
+    }
+}
+
+/// A version of a row that a commit replaced or deleted, kept for scans of earlier snapshots.
+/// The version was live until `end_snapshot_id`, the previous version of the row, if any, ended
+/// at an earlier snapshot.
+#[derive(Debug, Clone)]
+pub(crate) struct RowHistoryRecord {
+    pub(crate) table_id: i64,
+    pub(crate) row_id: i64,
+    pub(crate) end_snapshot_id: i64,
+    pub(crate) location: RowLocation,
+    /// JSON object of the field values of inline rows, which are gone from the catalog once
+    /// replaced. Rows in data files are read from the file at `location`.
+    pub(crate) row_values: Option<Vec<u8>>,
+}
+
+impl RowHistoryRecord {
+    pub(crate) fn to_sql(&self, database: CatalogDatabase) -> String {
+        let row_values_sql = match &self.row_values {
+            Some(row_values) => database.sql_binary_value(row_values),
+            None => "NULL".to_string(),
+        };
+        format!(
+            "({}, {}, {}, '{}', {})",
+            self.table_id, self.row_id, self.end_snapshot_id, self.location, row_values_sql
+        )
+    }
+
+    pub(crate) fn select_items() -> Vec<&'static str> {
+        vec![
+            "table_id",
+            "row_id",
+            "end_snapshot_id",
+            "location",
+            "row_values",
+        ]
+    }
 }
@@ -0,0 +1,140 @@
+use std::{sync::Arc, time::Duration};
+
+use log::warn;
+
+use crate::{
+    ILResult,
+    catalog::{Catalog, CatalogDatabase, CatalogHealth, CatalogSchemaRef, IsolationLevel},
+    catalog::{PoolStatus, RowStream, Transaction},
+};
+
+/// Exponential backoff policy of [`RetryingCatalog`].
+#[derive(Debug, Clone, derive_with::With)]
+pub struct RetryPolicy {
+    /// Total attempts including the first one.
+    pub max_attempts: usize,
+    /// Delay before the first retry, doubled on every following retry.
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: usize, base_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            max_attempts,
+            base_delay,
+            max_delay,
+        }
+    }
+
+    /// Delay before the given retry, starting from 1.
+    pub fn delay(&self, retry: usize) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1) as u32);
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Catalog wrapper that retries operations failing with a transient error, see
+/// [`ILError::is_transient`](crate::ILError::is_transient).
+///
+/// Only operations without side effects are retried: queries and beginning a transaction.
+/// Statements and commits of a started transaction are never retried, as a lost connection
+/// leaves it unknown whether they were applied.
+#[derive(Debug, Clone)]
+pub struct RetryingCatalog {
+    inner: Arc<dyn Catalog>,
+    policy: RetryPolicy,
+}
+
+impl RetryingCatalog {
+    pub fn new(inner: Arc<dyn Catalog>, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    async fn retry<T, F, Fut>(&self, operation: &str, mut f: F) -> ILResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = ILResult<T>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match f().await {
+                Err(e) if e.is_transient() && attempt < self.policy.max_attempts => {
+                    let delay = self.policy.delay(attempt);
+                    warn!(
+                        "catalog {operation} failed on attempt {attempt}/{}, retrying in {delay:?}: {e}",
+                        self.policy.max_attempts
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Catalog for RetryingCatalog {
+    fn database(&self) -> CatalogDatabase {
+        self.inner.database()
+    }
+
+    async fn query(&self, sql: &str, schema: CatalogSchemaRef) -> ILResult<RowStream<'static>> {
+        self.retry("query", || self.inner.query(sql, schema.clone()))
+            .await
+    }
+
+    async fn transaction(&self) -> ILResult<Box<dyn Transaction>> {
+        self.retry("transaction", || self.inner.transaction()).await
+    }
+
+    async fn begin_transaction(&self, level: IsolationLevel) -> ILResult<Box<dyn Transaction>> {
+        self.retry("transaction", || self.inner.begin_transaction(level))
+            .await
+    }
+
+    fn transaction_retry_policy(&self) -> Option<RetryPolicy> {
+        self.inner.transaction_retry_policy()
+    }
+
+    async fn health_check(&self) -> ILResult<CatalogHealth> {
+        self.inner.health_check().await
+    }
+
+    fn pool_status(&self) -> Option<PoolStatus> {
+        self.inner.pool_status()
+    }
+
+    fn read_only(&self) -> bool {
+        self.inner.read_only()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_policy_delay() {
+        let policy = RetryPolicy::new(5, Duration::from_millis(100), Duration::from_millis(300));
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(200));
+        assert_eq!(policy.delay(3), Duration::from_millis(300));
+        assert_eq!(policy.delay(100), Duration::from_millis(300));
+    }
+}
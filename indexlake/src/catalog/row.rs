@@ -1,17 +1,25 @@
+use std::collections::HashMap;
 use std::fmt::Display;
+use std::sync::Arc;
 
 use crate::{
     ILError, ILResult,
-    catalog::{CatalogSchemaRef, INTERNAL_ROW_ID_FIELD_NAME, Scalar},
+    catalog::{CatalogSchemaRef, INTERNAL_ROW_ID_FIELD_NAME, Scalar, is_nested_data_type},
 };
 use arrow::array::{
-    Array, BinaryArray, BinaryBuilder, BooleanArray, BooleanBuilder, Float32Array, Float32Builder,
+    Array, ArrayBuilder, ArrayRef, BinaryArray, BinaryBuilder, BooleanArray, BooleanBuilder,
+    Decimal128Builder, Decimal256Builder, FixedSizeListBuilder, Float32Array, Float32Builder,
     Float64Array, Float64Builder, Int16Array, Int16Builder, Int32Array, Int32Builder, Int64Array,
-    Int64Builder, RecordBatch, RecordBatchOptions, StringArray, StringBuilder, make_builder,
+    Int64Builder, NullBuilder, RecordBatch, RecordBatchOptions, StringArray, StringBuilder,
+    TimestampMicrosecondBuilder, TimestampMillisecondBuilder, TimestampNanosecondBuilder,
+    TimestampSecondBuilder, make_builder, new_empty_array, new_null_array,
 };
-use arrow::datatypes::{DataType, SchemaRef};
+use arrow::compute::concat;
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit, i256};
+use arrow::ipc::reader::StreamReader;
+use arrow::ipc::writer::StreamWriter;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Row {
     pub schema: CatalogSchemaRef,
     pub values: Vec<Scalar>,
@@ -162,14 +170,189 @@ macro_rules! builder_append {
     }};
 }
 
+/// Encodes a vector as the little-endian bytes of its elements, the form vectors are kept in
+/// inline rows.
+pub(crate) fn encode_vector(array: &Float32Array) -> Vec<u8> {
+    array
+        .values()
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect()
+}
+
+pub(crate) fn decode_vector(bytes: &[u8]) -> ILResult<Vec<f32>> {
+    if bytes.len() % 4 != 0 {
+        return Err(ILError::InternalError(format!(
+            "Vector bytes length {} is not a multiple of 4",
+            bytes.len()
+        )));
+    }
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect())
+}
+
+/// Encodes a decimal of the unscaled value `value` and scale `scale` so that encodings compare
+/// bytewise as the numbers they hold, whatever their scales, the form decimals are kept in
+/// inline rows. Comparisons in the catalog databases then hold for literals of any scale.
+///
+/// Zero is a single byte, other numbers a sign byte, the exponent `e` of `0.d1d2.. * 10^e` in two
+/// bytes and the significant digits in ASCII, without trailing zeros. Exponent and digits of
+/// negative numbers are inverted and followed by a byte above every inverted digit.
+pub(crate) fn encode_decimal(value: i256, scale: i8) -> Vec<u8> {
+    if value == i256::ZERO {
+        return vec![DECIMAL_ZERO];
+    }
+    let negative = value.is_negative();
+    let abs = value.wrapping_abs().to_string();
+    let exponent = ((abs.len() as i16 - scale as i16) as u16 ^ 0x8000).to_be_bytes();
+    let digits = abs.trim_end_matches('0');
+
+    let mut bytes = Vec::with_capacity(digits.len() + 4);
+    if negative {
+        bytes.push(DECIMAL_NEGATIVE);
+        bytes.extend(exponent.iter().map(|byte| !byte));
+        bytes.extend(digits.bytes().map(|byte| !byte));
+        bytes.push(u8::MAX);
+    } else {
+        bytes.push(DECIMAL_POSITIVE);
+        bytes.extend(exponent);
+        bytes.extend(digits.bytes());
+    }
+    bytes
+}
+
+const DECIMAL_NEGATIVE: u8 = 0x40;
+const DECIMAL_ZERO: u8 = 0x80;
+const DECIMAL_POSITIVE: u8 = 0xC0;
+
+/// Decodes a decimal encoded by [`encode_decimal`] into its unscaled value at `scale`.
+pub(crate) fn decode_decimal(bytes: &[u8], scale: i8) -> ILResult<i256> {
+    let invalid =
+        || ILError::InternalError(format!("Invalid decimal bytes {}", hex::encode(bytes)));
+    let (negative, body) = match bytes.split_first() {
+        Some((&DECIMAL_ZERO, [])) => return Ok(i256::ZERO),
+        Some((&DECIMAL_POSITIVE, body)) => (false, body.to_vec()),
+        Some((&DECIMAL_NEGATIVE, [body @ .., u8::MAX])) => {
+            (true, body.iter().map(|byte| !byte).collect())
+        }
+        _ => return Err(invalid()),
+    };
+    if body.len() < 3 {
+        return Err(invalid());
+    }
+    let exponent = (u16::from_be_bytes([body[0], body[1]]) ^ 0x8000) as i16;
+    let digits = std::str::from_utf8(&body[2..]).map_err(|_| invalid())?;
+    // value = digits * 10^(exponent - digit count), unscaled by 10^scale
+    let zeros = exponent as i32 - digits.len() as i32 + scale as i32;
+    if zeros < 0 {
+        return Err(ILError::InternalError(format!(
+            "Decimal bytes {} hold more than {scale} fractional digits",
+            hex::encode(bytes)
+        )));
+    }
+    let sign = if negative { "-" } else { "" };
+    let value = format!("{sign}{digits}{}", "0".repeat(zeros as usize));
+    i256::from_string(&value).ok_or_else(invalid)
+}
+
+/// Nanoseconds in a tick of `unit`.
+pub(crate) fn time_unit_nanos(unit: TimeUnit) -> i128 {
+    match unit {
+        TimeUnit::Second => 1_000_000_000,
+        TimeUnit::Millisecond => 1_000_000,
+        TimeUnit::Microsecond => 1_000,
+        TimeUnit::Nanosecond => 1,
+    }
+}
+
+/// Encodes a timestamp of `unit` into bytes that compare as the instants they hold, whatever
+/// the unit and time zone: the nanoseconds since the epoch in 16 big-endian bytes with the
+/// sign bit flipped. Time zones only change how timestamps display, values are UTC.
+pub(crate) fn encode_timestamp(value: i64, unit: TimeUnit) -> Vec<u8> {
+    ((value as i128 * time_unit_nanos(unit)) ^ i128::MIN)
+        .to_be_bytes()
+        .to_vec()
+}
+
+/// Decodes a timestamp encoded by [`encode_timestamp`] into its value in `unit`.
+pub(crate) fn decode_timestamp(bytes: &[u8], unit: TimeUnit) -> ILResult<i64> {
+    let bytes: [u8; 16] = bytes.try_into().map_err(|_| {
+        ILError::InternalError(format!("Invalid timestamp bytes {}", hex::encode(bytes)))
+    })?;
+    let nanos = i128::from_be_bytes(bytes) ^ i128::MIN;
+    let factor = time_unit_nanos(unit);
+    if nanos % factor != 0 {
+        return Err(ILError::InternalError(format!(
+            "Timestamp bytes {} are not a whole number of {unit:?}s",
+            hex::encode(bytes)
+        )));
+    }
+    i64::try_from(nanos / factor).map_err(|_| {
+        ILError::InternalError(format!(
+            "Timestamp bytes {} overflow {unit:?}s",
+            hex::encode(bytes)
+        ))
+    })
+}
+
+/// Encodes the struct or list value at `index` of the array as an Arrow IPC stream of a single
+/// row, the form nested values are kept in inline rows. Nulls nested in the value are kept.
+pub(crate) fn encode_nested_value(array: &ArrayRef, index: usize) -> ILResult<Vec<u8>> {
+    let schema = Arc::new(Schema::new(vec![Field::new(
+        "value",
+        array.data_type().clone(),
+        true,
+    )]));
+    let batch = RecordBatch::try_new(schema.clone(), vec![array.slice(index, 1)])?;
+    let mut bytes = Vec::new();
+    let mut writer = StreamWriter::try_new(&mut bytes, &schema)?;
+    writer.write(&batch)?;
+    writer.finish()?;
+    drop(writer);
+    Ok(bytes)
+}
+
+/// Decodes a value encoded by [`encode_nested_value`] into an array of one row of `data_type`.
+pub(crate) fn decode_nested_value(bytes: &[u8], data_type: &DataType) -> ILResult<ArrayRef> {
+    let mut reader = StreamReader::try_new(bytes, None)?;
+    let batch = reader.next().transpose()?.ok_or_else(|| {
+        ILError::InternalError("Nested value bytes hold no record batch".to_string())
+    })?;
+    let array = batch.column(0);
+    if array.len() != 1 || array.data_type() != data_type {
+        return Err(ILError::InternalError(format!(
+            "Nested value of {} rows of type {} does not match type {data_type}",
+            array.len(),
+            array.data_type()
+        )));
+    }
+    Ok(array.clone())
+}
+
 pub fn rows_to_record_batch(schema: &SchemaRef, rows: &[Row]) -> ILResult<RecordBatch> {
     let mut array_builders = Vec::with_capacity(schema.fields.len());
-    for field in schema.fields.iter() {
-        array_builders.push(make_builder(field.data_type(), rows.len()));
+    // Values of nested columns are decoded as arrays of one row, concatenated at the end
+    let mut nested_values: HashMap<usize, Vec<ArrayRef>> = HashMap::new();
+    for (i, field) in schema.fields.iter().enumerate() {
+        if is_nested_data_type(field.data_type()) {
+            array_builders.push(Box::new(NullBuilder::new()) as Box<dyn ArrayBuilder>);
+            nested_values.insert(i, Vec::with_capacity(rows.len()));
+        } else {
+            array_builders.push(make_builder(field.data_type(), rows.len()));
+        }
     }
 
     for row in rows {
         for (i, field) in schema.fields.iter().enumerate() {
+            if let Some(values) = nested_values.get_mut(&i) {
+                values.push(match row.binary(i)? {
+                    Some(bytes) => decode_nested_value(bytes, field.data_type())?,
+                    None => new_null_array(field.data_type(), 1),
+                });
+                continue;
+            }
             match field.data_type() {
                 DataType::Boolean => {
                     builder_append!(
@@ -245,6 +428,111 @@ pub fn rows_to_record_batch(schema: &SchemaRef, rows: &[Row]) -> ILResult<Record
                         convert
                     );
                 }
+                DataType::Decimal128(_, scale) => {
+                    builder_append!(
+                        array_builders[i],
+                        Decimal128Builder,
+                        field,
+                        row,
+                        binary,
+                        i,
+                        |v: &Vec<u8>| decode_decimal(v, *scale)?.to_i128().ok_or_else(|| {
+                            ILError::InternalError(format!(
+                                "Decimal of field {} overflows 128 bits",
+                                field.name()
+                            ))
+                        })
+                    );
+                }
+                DataType::Decimal256(_, scale) => {
+                    builder_append!(
+                        array_builders[i],
+                        Decimal256Builder,
+                        field,
+                        row,
+                        binary,
+                        i,
+                        |v: &Vec<u8>| decode_decimal(v, *scale)
+                    );
+                }
+                DataType::Timestamp(unit, _) => {
+                    let convert = |v: &Vec<u8>| decode_timestamp(v, *unit);
+                    match unit {
+                        TimeUnit::Second => builder_append!(
+                            array_builders[i],
+                            TimestampSecondBuilder,
+                            field,
+                            row,
+                            binary,
+                            i,
+                            convert
+                        ),
+                        TimeUnit::Millisecond => builder_append!(
+                            array_builders[i],
+                            TimestampMillisecondBuilder,
+                            field,
+                            row,
+                            binary,
+                            i,
+                            convert
+                        ),
+                        TimeUnit::Microsecond => builder_append!(
+                            array_builders[i],
+                            TimestampMicrosecondBuilder,
+                            field,
+                            row,
+                            binary,
+                            i,
+                            convert
+                        ),
+                        TimeUnit::Nanosecond => builder_append!(
+                            array_builders[i],
+                            TimestampNanosecondBuilder,
+                            field,
+                            row,
+                            binary,
+                            i,
+                            convert
+                        ),
+                    }
+                }
+                DataType::FixedSizeList(_, size) => {
+                    let builder = array_builders[i]
+                        .as_any_mut()
+                        .downcast_mut::<FixedSizeListBuilder<Box<dyn ArrayBuilder>>>()
+                        .ok_or_else(|| {
+                            ILError::InternalError(format!(
+                                "Failed to downcast builder to FixedSizeListBuilder for {field:?}"
+                            ))
+                        })?;
+                    let vector = row.binary(i)?.map(|v| decode_vector(v)).transpose()?;
+                    let values = builder
+                        .values()
+                        .as_any_mut()
+                        .downcast_mut::<Float32Builder>()
+                        .ok_or_else(|| {
+                            ILError::InternalError(format!(
+                                "Failed to downcast values builder to Float32Builder for {field:?}"
+                            ))
+                        })?;
+                    match vector {
+                        Some(vector) if vector.len() == *size as usize => {
+                            values.append_slice(&vector);
+                            builder.append(true);
+                        }
+                        Some(vector) => {
+                            return Err(ILError::InternalError(format!(
+                                "Vector of field {} has {} elements, expected {size}",
+                                field.name(),
+                                vector.len()
+                            )));
+                        }
+                        None => {
+                            values.append_nulls(*size as usize);
+                            builder.append(false);
+                        }
+                    }
+                }
                 _ => todo!(),
             }
         }
@@ -252,8 +540,126 @@ pub fn rows_to_record_batch(schema: &SchemaRef, rows: &[Row]) -> ILResult<Record
 
     let columns = array_builders
         .into_iter()
-        .map(|mut builder| builder.finish())
-        .collect();
+        .enumerate()
+        .map(|(i, mut builder)| match nested_values.remove(&i) {
+            Some(values) if values.is_empty() => Ok(new_empty_array(schema.field(i).data_type())),
+            Some(values) => {
+                let values = values
+                    .iter()
+                    .map(|value| value.as_ref())
+                    .collect::<Vec<_>>();
+                Ok(concat(&values)?)
+            }
+            None => Ok(builder.finish()),
+        })
+        .collect::<ILResult<Vec<_>>>()?;
     RecordBatch::try_new(schema.clone(), columns)
         .map_err(|e| ILError::InternalError(format!("Failed to create record batch: {e:?}")))
 }
+
+#[cfg(test)]
+mod tests {
+    use arrow::datatypes::{TimeUnit, i256};
+
+    use crate::catalog::{decode_decimal, decode_timestamp, encode_decimal, encode_timestamp};
+
+    #[test]
+    fn test_decimal_encoding_order() {
+        // (unscaled value, scale) in ascending order of the numbers they hold
+        let decimals = [
+            (-123_456_789_012_345_678_901_234_567_890i128, 2),
+            (-100_000, 3),
+            (-1234, 2),
+            (-1205, 3),
+            (-12, 1),
+            (-12_000, 4),
+            (0, 5),
+            (1, 30),
+            (12, 4),
+            (1200, 4),
+            (1234, 4),
+            (99, 1),
+            (10, 0),
+            (123_456_789_012_345_678_901_234_567_890, 0),
+        ];
+        let encoded = decimals
+            .iter()
+            .map(|(value, scale)| encode_decimal(i256::from_i128(*value), *scale))
+            .collect::<Vec<_>>();
+        for (i, pair) in encoded.windows(2).enumerate() {
+            assert!(
+                pair[0] <= pair[1],
+                "{:?} > {:?}",
+                decimals[i],
+                decimals[i + 1]
+            );
+        }
+        // numbers of different scales encode the same
+        assert_eq!(encoded[8], encode_decimal(i256::from_i128(120), 5));
+        assert_eq!(encoded[4], encoded[5]);
+
+        for (value, scale) in decimals {
+            let bytes = encode_decimal(i256::from_i128(value), scale);
+            assert_eq!(
+                decode_decimal(&bytes, scale).unwrap(),
+                i256::from_i128(value)
+            );
+            assert_eq!(
+                decode_decimal(&bytes, scale + 2).unwrap(),
+                i256::from_i128(value * 100)
+            );
+        }
+        assert!(decode_decimal(&encode_decimal(i256::from_i128(1205), 3), 2).is_err());
+        let max = i256::from_string(&"9".repeat(76)).unwrap();
+        assert_eq!(decode_decimal(&encode_decimal(max, 10), 10).unwrap(), max);
+        assert_eq!(
+            decode_decimal(&encode_decimal(max.wrapping_neg(), 10), 10).unwrap(),
+            max.wrapping_neg()
+        );
+    }
+
+    #[test]
+    fn test_timestamp_encoding_order() {
+        // (value, unit) in ascending order of the instants they hold
+        let timestamps = [
+            (i64::MIN, TimeUnit::Second),
+            (-1_500, TimeUnit::Millisecond),
+            (-1, TimeUnit::Second),
+            (-1, TimeUnit::Nanosecond),
+            (0, TimeUnit::Second),
+            (1, TimeUnit::Microsecond),
+            (1_000_001, TimeUnit::Nanosecond),
+            (1_704_067_200, TimeUnit::Second),
+            (i64::MAX, TimeUnit::Nanosecond),
+            (i64::MAX, TimeUnit::Second),
+        ];
+        let encoded = timestamps
+            .iter()
+            .map(|(value, unit)| encode_timestamp(*value, *unit))
+            .collect::<Vec<_>>();
+        for (i, pair) in encoded.windows(2).enumerate() {
+            assert!(
+                pair[0] < pair[1],
+                "{:?} >= {:?}",
+                timestamps[i],
+                timestamps[i + 1]
+            );
+        }
+        // instants of different units encode the same
+        assert_eq!(
+            encode_timestamp(1_704_067_200, TimeUnit::Second),
+            encode_timestamp(1_704_067_200_000_000, TimeUnit::Microsecond)
+        );
+
+        for (value, unit) in timestamps {
+            assert_eq!(
+                decode_timestamp(&encode_timestamp(value, unit), unit).unwrap(),
+                value
+            );
+        }
+        // values finer than the unit do not decode
+        assert!(
+            decode_timestamp(&encode_timestamp(1, TimeUnit::Nanosecond), TimeUnit::Second).is_err()
+        );
+    }
+}
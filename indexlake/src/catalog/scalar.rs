@@ -3,15 +3,28 @@ use std::iter::repeat_n;
 use std::sync::Arc;
 
 use arrow::array::{
-    Array, ArrayRef, AsArray, BinaryArray, BooleanArray, Float32Array, Float64Array, Int16Array,
-    Int32Array, Int64Array, StringArray, new_null_array,
+    Array, ArrayRef, AsArray, BinaryArray, BooleanArray, Decimal128Array, Decimal256Array,
+    Float32Array, Float64Array, Int16Array, Int32Array, Int64Array, StringArray,
+    TimestampMicrosecondArray, TimestampMillisecondArray, TimestampNanosecondArray,
+    TimestampSecondArray, new_null_array,
 };
-use arrow::datatypes::{DataType, Float32Type, Float64Type, Int16Type, Int32Type, Int64Type};
+use arrow::datatypes::{
+    DataType, Decimal128Type, Decimal256Type, DecimalType, Float32Type, Float64Type, Int16Type,
+    Int32Type, Int64Type, TimeUnit, TimestampMicrosecondType, TimestampMillisecondType,
+    TimestampNanosecondType, TimestampSecondType, i256,
+};
+use arrow::util::display::array_value_to_string;
 use derive_visitor::{Drive, DriveMut};
+use serde::{Deserialize, Serialize};
 
-use crate::{ILError, ILResult, catalog::CatalogDatabase};
+use crate::{
+    ILError, ILResult,
+    catalog::{
+        CatalogDatabase, encode_decimal, encode_timestamp, is_nested_data_type, time_unit_nanos,
+    },
+};
 
-#[derive(Debug, Clone, Drive, DriveMut)]
+#[derive(Debug, Clone, Drive, DriveMut, Serialize, Deserialize)]
 pub enum Scalar {
     Boolean(Option<bool>),
     Int16(Option<i16>),
@@ -21,6 +34,24 @@ pub enum Scalar {
     Float64(Option<f64>),
     Utf8(Option<String>),
     Binary(Option<Vec<u8>>),
+    /// Unscaled value, precision and scale.
+    Decimal128(Option<i128>, u8, i8),
+    /// Unscaled value, precision and scale.
+    Decimal256(
+        #[drive(skip)]
+        #[serde(with = "serde_i256")]
+        Option<i256>,
+        u8,
+        i8,
+    ),
+    /// Value in the unit since the epoch in UTC, unit and time zone. The time zone only changes
+    /// how the value displays, timestamps of any unit and time zone compare as instants, those
+    /// without one as UTC.
+    Timestamp(
+        Option<i64>,
+        #[drive(skip)] TimeUnit,
+        #[drive(skip)] Option<String>,
+    ),
 }
 
 impl Scalar {
@@ -34,6 +65,13 @@ impl Scalar {
             DataType::Float64 => Scalar::Float64(None),
             DataType::Utf8 => Scalar::Utf8(None),
             DataType::Binary => Scalar::Binary(None),
+            DataType::Decimal128(precision, scale) => Scalar::Decimal128(None, *precision, *scale),
+            DataType::Decimal256(precision, scale) => Scalar::Decimal256(None, *precision, *scale),
+            DataType::Timestamp(unit, tz) => {
+                Scalar::Timestamp(None, *unit, tz.as_ref().map(|tz| tz.to_string()))
+            }
+            // Nested values are kept inline as binary
+            _ if is_nested_data_type(data_type) => Scalar::Binary(None),
             _ => {
                 return Err(ILError::NotSupported(format!(
                     "Cannot create null scalar for data type: {:?}",
@@ -52,6 +90,9 @@ impl Scalar {
             Scalar::Float64(v) => v.is_none(),
             Scalar::Utf8(v) => v.is_none(),
             Scalar::Binary(v) => v.is_none(),
+            Scalar::Decimal128(v, _, _) => v.is_none(),
+            Scalar::Decimal256(v, _, _) => v.is_none(),
+            Scalar::Timestamp(v, _, _) => v.is_none(),
         }
     }
 
@@ -83,6 +124,17 @@ impl Scalar {
             Scalar::Utf8(None) => "null".to_string(),
             Scalar::Binary(Some(value)) => database.sql_binary_value(value),
             Scalar::Binary(None) => "null".to_string(),
+            Scalar::Decimal128(Some(value), _, scale) => {
+                database.sql_binary_value(&encode_decimal(i256::from_i128(*value), *scale))
+            }
+            Scalar::Decimal256(Some(value), _, scale) => {
+                database.sql_binary_value(&encode_decimal(*value, *scale))
+            }
+            Scalar::Decimal128(None, _, _) | Scalar::Decimal256(None, _, _) => "null".to_string(),
+            Scalar::Timestamp(Some(value), unit, _) => {
+                database.sql_binary_value(&encode_timestamp(*value, *unit))
+            }
+            Scalar::Timestamp(None, _, _) => "null".to_string(),
         }
     }
 
@@ -123,6 +175,32 @@ impl Scalar {
                 }
                 None => Arc::new(repeat_n(None::<&str>, size).collect::<BinaryArray>()),
             },
+            Scalar::Decimal128(e, precision, scale) => Arc::new(
+                Decimal128Array::from(vec![*e; size])
+                    .with_precision_and_scale(*precision, *scale)?,
+            ),
+            Scalar::Decimal256(e, precision, scale) => Arc::new(
+                Decimal256Array::from(vec![*e; size])
+                    .with_precision_and_scale(*precision, *scale)?,
+            ),
+            Scalar::Timestamp(e, unit, tz) => {
+                let values = vec![*e; size];
+                let tz = tz.as_deref().map(Arc::<str>::from);
+                match unit {
+                    TimeUnit::Second => {
+                        Arc::new(TimestampSecondArray::from(values).with_timezone_opt(tz))
+                    }
+                    TimeUnit::Millisecond => {
+                        Arc::new(TimestampMillisecondArray::from(values).with_timezone_opt(tz))
+                    }
+                    TimeUnit::Microsecond => {
+                        Arc::new(TimestampMicrosecondArray::from(values).with_timezone_opt(tz))
+                    }
+                    TimeUnit::Nanosecond => {
+                        Arc::new(TimestampNanosecondArray::from(values).with_timezone_opt(tz))
+                    }
+                }
+            }
         })
     }
 
@@ -174,6 +252,33 @@ impl Scalar {
                 let array = array.as_binary_opt::<i32>().expect("Failed to cast array");
                 Scalar::Binary(Some(array.value(index).to_vec()))
             }
+            DataType::Decimal128(precision, scale) => {
+                let array = array
+                    .as_primitive_opt::<Decimal128Type>()
+                    .expect("Failed to cast array");
+                Scalar::Decimal128(Some(array.value(index)), *precision, *scale)
+            }
+            DataType::Decimal256(precision, scale) => {
+                let array = array
+                    .as_primitive_opt::<Decimal256Type>()
+                    .expect("Failed to cast array");
+                Scalar::Decimal256(Some(array.value(index)), *precision, *scale)
+            }
+            DataType::Timestamp(unit, tz) => {
+                let value = match unit {
+                    TimeUnit::Second => array.as_primitive::<TimestampSecondType>().value(index),
+                    TimeUnit::Millisecond => array
+                        .as_primitive::<TimestampMillisecondType>()
+                        .value(index),
+                    TimeUnit::Microsecond => array
+                        .as_primitive::<TimestampMicrosecondType>()
+                        .value(index),
+                    TimeUnit::Nanosecond => {
+                        array.as_primitive::<TimestampNanosecondType>().value(index)
+                    }
+                };
+                Scalar::Timestamp(Some(value), *unit, tz.as_ref().map(|tz| tz.to_string()))
+            }
             _ => todo!(),
         })
     }
@@ -188,8 +293,71 @@ impl Scalar {
             Scalar::Float64(_) => DataType::Float64,
             Scalar::Utf8(_) => DataType::Utf8,
             Scalar::Binary(_) => DataType::Binary,
+            Scalar::Decimal128(_, precision, scale) => DataType::Decimal128(*precision, *scale),
+            Scalar::Decimal256(_, precision, scale) => DataType::Decimal256(*precision, *scale),
+            Scalar::Timestamp(_, unit, tz) => {
+                DataType::Timestamp(*unit, tz.as_deref().map(Arc::<str>::from))
+            }
         }
     }
+
+    /// Unscaled value and scale of a decimal, widened to 256 bits.
+    fn decimal_value(&self) -> Option<(Option<i256>, i8)> {
+        match self {
+            Scalar::Decimal128(v, _, scale) => Some((v.map(i256::from_i128), *scale)),
+            Scalar::Decimal256(v, _, scale) => Some((*v, *scale)),
+            _ => None,
+        }
+    }
+
+    /// Nanoseconds since the epoch of a timestamp, widened so that no unit overflows.
+    fn timestamp_nanos(&self) -> Option<Option<i128>> {
+        match self {
+            Scalar::Timestamp(v, unit, _) => {
+                Some(v.map(|value| value as i128 * time_unit_nanos(*unit)))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Compares decimals by the numbers they hold, scaling the one of smaller scale up. `None` when
+/// scaling overflows, which only values far beyond any precision do.
+fn compare_decimals((v1, s1): (i256, i8), (v2, s2): (i256, i8)) -> Option<std::cmp::Ordering> {
+    let rescale = |value: i256, by: i8| {
+        i256::from_i128(10)
+            .checked_pow(by as u32)
+            .and_then(|factor| value.checked_mul(factor))
+    };
+    match s1.cmp(&s2) {
+        std::cmp::Ordering::Less => rescale(v1, s2 - s1)?.partial_cmp(&v2),
+        std::cmp::Ordering::Equal => v1.partial_cmp(&v2),
+        std::cmp::Ordering::Greater => v1.partial_cmp(&rescale(v2, s1 - s2)?),
+    }
+}
+
+/// Serializes 256-bit decimal values as their decimal strings.
+mod serde_i256 {
+    use arrow::datatypes::i256;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error};
+
+    pub(super) fn serialize<S: Serializer>(
+        value: &Option<i256>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value.map(|value| value.to_string()).serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<i256>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|value| {
+                i256::from_string(&value)
+                    .ok_or_else(|| D::Error::custom(format!("invalid i256 value {value}")))
+            })
+            .transpose()
+    }
 }
 
 impl PartialEq for Scalar {
@@ -217,6 +385,22 @@ impl PartialEq for Scalar {
             (Scalar::Utf8(_), _) => false,
             (Scalar::Binary(v1), Scalar::Binary(v2)) => v1.eq(v2),
             (Scalar::Binary(_), _) => false,
+            // Decimals of either width and any scale are equal when they hold the same number
+            (Scalar::Decimal128(..) | Scalar::Decimal256(..), _) => {
+                match (self.decimal_value(), other.decimal_value()) {
+                    (Some((Some(v1), s1)), Some((Some(v2), s2))) => {
+                        compare_decimals((v1, s1), (v2, s2))
+                            .is_some_and(|ordering| ordering.is_eq())
+                    }
+                    (Some((v1, _)), Some((v2, _))) => v1.is_none() && v2.is_none(),
+                    _ => false,
+                }
+            }
+            // Timestamps of any unit and time zone are equal when they hold the same instant
+            (Scalar::Timestamp(..), _) => match other.timestamp_nanos() {
+                Some(v2) => self.timestamp_nanos() == Some(v2),
+                None => false,
+            },
         }
     }
 }
@@ -248,6 +432,15 @@ impl PartialOrd for Scalar {
             (Scalar::Utf8(_), _) => None,
             (Scalar::Binary(v1), Scalar::Binary(v2)) => v1.partial_cmp(v2),
             (Scalar::Binary(_), _) => None,
+            (Scalar::Decimal128(..) | Scalar::Decimal256(..), _) => {
+                match (self.decimal_value()?, other.decimal_value()?) {
+                    ((Some(v1), s1), (Some(v2), s2)) => compare_decimals((v1, s1), (v2, s2)),
+                    ((v1, _), (v2, _)) => v1.is_some().partial_cmp(&v2.is_some()),
+                }
+            }
+            (Scalar::Timestamp(..), _) => self
+                .timestamp_nanos()?
+                .partial_cmp(&other.timestamp_nanos()?),
         }
     }
 }
@@ -271,6 +464,23 @@ impl Display for Scalar {
             Scalar::Utf8(None) => write!(f, "null"),
             Scalar::Binary(Some(value)) => write!(f, "{}", hex::encode(value)),
             Scalar::Binary(None) => write!(f, "null"),
+            Scalar::Decimal128(Some(value), precision, scale) => write!(
+                f,
+                "{}",
+                Decimal128Type::format_decimal(*value, *precision, *scale)
+            ),
+            Scalar::Decimal256(Some(value), precision, scale) => write!(
+                f,
+                "{}",
+                Decimal256Type::format_decimal(*value, *precision, *scale)
+            ),
+            Scalar::Decimal128(None, _, _) | Scalar::Decimal256(None, _, _) => write!(f, "null"),
+            Scalar::Timestamp(Some(_), _, _) => {
+                let array = self.to_array_of_size(1).map_err(|_| std::fmt::Error)?;
+                let value = array_value_to_string(&array, 0).map_err(|_| std::fmt::Error)?;
+                write!(f, "{value}")
+            }
+            Scalar::Timestamp(None, _, _) => write!(f, "null"),
         }
     }
 }
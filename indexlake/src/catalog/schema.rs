@@ -1,10 +1,11 @@
 use std::sync::Arc;
 
 use arrow::datatypes::{DataType, Schema};
+use serde::{Deserialize, Serialize};
 
 use crate::{ILError, ILResult, catalog::CatalogDatabase};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CatalogDataType {
     Boolean,
     Int16,
@@ -24,17 +25,26 @@ impl CatalogDataType {
             CatalogDataType::Int32 => "INTEGER".to_string(),
             CatalogDataType::Int64 => "BIGINT".to_string(),
             CatalogDataType::Float32 => match database {
-                CatalogDatabase::Sqlite => "FLOAT".to_string(),
+                CatalogDatabase::Sqlite | CatalogDatabase::DuckDb => "FLOAT".to_string(),
                 CatalogDatabase::Postgres => "FLOAT4".to_string(),
+                CatalogDatabase::MySql => "FLOAT".to_string(),
             },
             CatalogDataType::Float64 => match database {
-                CatalogDatabase::Sqlite => "DOUBLE".to_string(),
+                CatalogDatabase::Sqlite | CatalogDatabase::DuckDb => "DOUBLE".to_string(),
                 CatalogDatabase::Postgres => "FLOAT8".to_string(),
+                CatalogDatabase::MySql => "DOUBLE".to_string(),
+            },
+            CatalogDataType::Utf8 => match database {
+                CatalogDatabase::Sqlite | CatalogDatabase::Postgres | CatalogDatabase::DuckDb => {
+                    "VARCHAR".to_string()
+                }
+                // MySQL requires a length for VARCHAR
+                CatalogDatabase::MySql => "TEXT".to_string(),
             },
-            CatalogDataType::Utf8 => "VARCHAR".to_string(),
             CatalogDataType::Binary => match database {
-                CatalogDatabase::Sqlite => "BLOB".to_string(),
+                CatalogDatabase::Sqlite | CatalogDatabase::DuckDb => "BLOB".to_string(),
                 CatalogDatabase::Postgres => "BYTEA".to_string(),
+                CatalogDatabase::MySql => "LONGBLOB".to_string(),
             },
         }
     }
@@ -49,6 +59,17 @@ impl CatalogDataType {
             DataType::Float64 => Ok(CatalogDataType::Float64),
             DataType::Utf8 => Ok(CatalogDataType::Utf8),
             DataType::Binary => Ok(CatalogDataType::Binary),
+            // Vectors are kept inline as little-endian floats
+            DataType::FixedSizeList(item, _) if item.data_type() == &DataType::Float32 => {
+                Ok(CatalogDataType::Binary)
+            }
+            // Decimals are kept inline in an encoding that compares as the numbers
+            DataType::Decimal128(_, _) | DataType::Decimal256(_, _) => Ok(CatalogDataType::Binary),
+            // Timestamps are kept inline in an encoding that compares as the instants, so that
+            // filters in another unit or time zone compare right in the catalog
+            DataType::Timestamp(_, _) => Ok(CatalogDataType::Binary),
+            // Nested values are kept inline as Arrow IPC streams of a single row
+            _ if is_nested_data_type(datatype) => Ok(CatalogDataType::Binary),
             _ => Err(ILError::NotSupported(format!(
                 "Unsupported datatype: {datatype}"
             ))),
@@ -56,6 +77,40 @@ impl CatalogDataType {
     }
 }
 
+/// Whether values of the type are kept inline by [`encode_nested_value`](crate::catalog::encode_nested_value).
+/// Struct and list columns of any item type are.
+pub(crate) fn is_nested_data_type(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Struct(_) | DataType::List(_) | DataType::LargeList(_)
+    )
+}
+
+/// Encodes a data type for the field records of the catalog, in the syntax parsed by
+/// [`decode_data_type`]. `Display` of list types is not parseable, their item is written by type.
+/// Nested types are written as JSON, which keeps the names and nullability of their children.
+pub(crate) fn encode_data_type(data_type: &DataType) -> ILResult<String> {
+    match data_type {
+        DataType::FixedSizeList(item, size) => Ok(format!(
+            "FixedSizeList({size}, {})",
+            encode_data_type(item.data_type())?
+        )),
+        _ if is_nested_data_type(data_type) => serde_json::to_string(data_type).map_err(|e| {
+            ILError::InternalError(format!("Failed to serialize data type {data_type}: {e:?}"))
+        }),
+        _ => Ok(data_type.to_string()),
+    }
+}
+
+pub(crate) fn decode_data_type(value: &str) -> ILResult<DataType> {
+    if value.starts_with('{') {
+        return serde_json::from_str(value).map_err(|e| {
+            ILError::InternalError(format!("Failed to deserialize data type {value}: {e:?}"))
+        });
+    }
+    Ok(value.parse::<DataType>()?)
+}
+
 impl std::fmt::Display for CatalogDataType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -71,7 +126,7 @@ impl std::fmt::Display for CatalogDataType {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Column {
     pub name: String,
     pub data_type: CatalogDataType,
@@ -2,13 +2,31 @@ use arrow::datatypes::Schema;
 
 use crate::catalog::CatalogHelper;
 use crate::catalog::INTERNAL_ROW_ID_FIELD_REF;
+use crate::catalog::NamespaceRecord;
 use crate::catalog::TransactionHelper;
+use crate::catalog::check_writable;
 use crate::index::Index;
 use crate::index::IndexDefination;
-use crate::table::{Table, TableCreation, process_create_table};
+use crate::table::{
+    ExpireReport, LakeTransaction, ListOptions, StorageStats, Table, TableCreation,
+    TableDescription, TableIdent, TablePage, TransactionOptions, VacuumReport, process_clone_table,
+    process_create_table, process_describe_table, process_list_table_idents, process_list_tables,
+    process_storage_stats, process_table_drop, process_table_purge, process_vacuum_namespace,
+};
 use crate::{ILError, ILResult, catalog::Catalog, storage::Storage};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
+
+use log::error;
+use tokio::task::JoinHandle;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamespaceInfo {
+    pub namespace_id: i64,
+    pub namespace_name: String,
+    pub properties: HashMap<String, String>,
+}
 
 #[derive(Debug, Clone)]
 pub struct LakeClient {
@@ -35,25 +53,38 @@ impl LakeClient {
         Ok(())
     }
 
-    pub async fn create_namespace(&self, namespace_name: &str) -> ILResult<i64> {
-        let mut tx_helper = self.transaction_helper().await?;
+    /// Creates a namespace with arbitrary key/value `properties` kept in the catalog.
+    pub async fn create_namespace(
+        &self,
+        namespace_name: &str,
+        properties: HashMap<String, String>,
+    ) -> ILResult<i64> {
+        let properties = &properties;
+        TransactionHelper::run(&self.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                if tx_helper.get_namespace_id(namespace_name).await?.is_some() {
+                    return Err(ILError::InvalidInput(format!(
+                        "Namespace {namespace_name} already exists"
+                    )));
+                }
 
-        if let Some(_) = tx_helper.get_namespace_id(namespace_name).await? {
-            return Err(ILError::InvalidInput(format!(
-                "Namespace {namespace_name} already exists"
-            )));
-        }
-
-        let max_namespace_id = tx_helper.get_max_namespace_id().await?;
-        let namespace_id = max_namespace_id + 1;
+                let max_namespace_id = tx_helper.get_max_namespace_id().await?;
+                let namespace_id = max_namespace_id + 1;
 
-        tx_helper
-            .insert_namespace(namespace_id, namespace_name)
-            .await?;
+                tx_helper
+                    .insert_namespace(&NamespaceRecord {
+                        namespace_id,
+                        namespace_name: namespace_name.to_string(),
+                        properties: properties.clone(),
+                    })
+                    .await?;
 
-        tx_helper.commit().await?;
+                tx_helper.commit().await?;
 
-        Ok(namespace_id)
+                Ok(namespace_id)
+            })
+        })
+        .await
     }
 
     pub async fn get_namespace_id(&self, namespace_name: &str) -> ILResult<Option<i64>> {
@@ -63,11 +94,387 @@ impl LakeClient {
         Ok(namespace_id)
     }
 
+    pub async fn namespace_exists(&self, namespace_name: &str) -> ILResult<bool> {
+        Ok(self.get_namespace_id(namespace_name).await?.is_some())
+    }
+
+    /// Lists all namespaces with their properties, ordered by name.
+    pub async fn list_namespaces(&self) -> ILResult<Vec<NamespaceInfo>> {
+        let catalog_helper = CatalogHelper::new(self.catalog.clone());
+        let records = catalog_helper.list_namespaces().await?;
+        Ok(records
+            .into_iter()
+            .map(|record| NamespaceInfo {
+                namespace_id: record.namespace_id,
+                namespace_name: record.namespace_name,
+                properties: record.properties,
+            })
+            .collect())
+    }
+
+    /// Drops a namespace. A namespace holding tables can only be dropped with `cascade`, which
+    /// drops its tables in the same transaction like [`Table::drop`] does, otherwise the error
+    /// names the tables.
+    pub async fn drop_namespace(&self, namespace_name: &str, cascade: bool) -> ILResult<()> {
+        TransactionHelper::run(&self.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                let namespace_id = tx_helper
+                    .get_namespace_id(namespace_name)
+                    .await?
+                    .ok_or_else(|| {
+                        ILError::CatalogError(format!("Namespace {namespace_name} not found"))
+                    })?;
+                let tables = tx_helper.list_namespace_tables(namespace_id).await?;
+                if !tables.is_empty() && !cascade {
+                    let table_names = tables
+                        .iter()
+                        .map(|table| table.table_name.as_str())
+                        .collect::<Vec<_>>();
+                    return Err(ILError::InvalidInput(format!(
+                        "Namespace {namespace_name} is not empty, drop its tables {} first or drop it with cascade",
+                        table_names.join(", ")
+                    )));
+                }
+
+                for table in tables.iter() {
+                    process_table_drop(&mut tx_helper, table.table_id, &table.config).await?;
+                }
+                tx_helper.delete_namespace(namespace_id).await?;
+
+                tx_helper.commit().await?;
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    /// Renames a namespace. Tables keep their ids and data file paths, so loaded tables keep
+    /// working.
+    pub async fn rename_namespace(&self, old_name: &str, new_name: &str) -> ILResult<()> {
+        TransactionHelper::run(&self.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                let namespace_id =
+                    tx_helper.get_namespace_id(old_name).await?.ok_or_else(|| {
+                        ILError::CatalogError(format!("Namespace {old_name} not found"))
+                    })?;
+                if tx_helper.get_namespace_id(new_name).await?.is_some() {
+                    return Err(ILError::InvalidInput(format!(
+                        "Namespace {new_name} already exists"
+                    )));
+                }
+
+                tx_helper
+                    .update_namespace_name(namespace_id, new_name)
+                    .await?;
+
+                tx_helper.commit().await?;
+                Ok(())
+            })
+        })
+        .await
+    }
+
     pub async fn create_table(&self, table_creation: TableCreation) -> ILResult<i64> {
-        let mut tx_helper = self.transaction_helper().await?;
-        let table_id = process_create_table(&mut tx_helper, table_creation).await?;
-        tx_helper.commit().await?;
-        Ok(table_id)
+        let table_creation = &table_creation;
+        TransactionHelper::run(&self.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                let table_id = process_create_table(&mut tx_helper, table_creation.clone()).await?;
+                tx_helper.commit().await?;
+                Ok(table_id)
+            })
+        })
+        .await
+    }
+
+    /// Creates the table `target` as a copy of the table `source` in one transaction, in the
+    /// same or another namespace. Only catalog metadata is copied, the clone refers to the data
+    /// files and index files of the source. Writes, deletes and compactions of either table
+    /// afterwards change only that table, vacuum keeps files as long as any table refers to
+    /// them. Earlier snapshots of the source are not readable through the clone.
+    pub async fn clone_table(&self, source: &TableIdent, target: &TableIdent) -> ILResult<i64> {
+        let source_table = &self
+            .load_table(&source.namespace_name, &source.table_name)
+            .await?;
+        TransactionHelper::run(&self.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                let namespace_id = tx_helper
+                    .get_namespace_id(&target.namespace_name)
+                    .await?
+                    .ok_or_else(|| {
+                        ILError::CatalogError(format!(
+                            "Namespace {} not found",
+                            target.namespace_name
+                        ))
+                    })?;
+                let table_id =
+                    process_clone_table(&mut tx_helper, source_table, namespace_id, target).await?;
+                tx_helper.commit().await?;
+                Ok(table_id)
+            })
+        })
+        .await
+    }
+
+    /// Renames a table within its namespace in one transaction, failing if `new_name` is
+    /// taken. Tables can not be moved to another namespace, the directory of a table is named
+    /// after the namespace it was created in. Only the catalog name changes, data files stay
+    /// where they are and tables loaded before the rename keep working by id.
+    pub async fn rename_table(
+        &self,
+        namespace_name: &str,
+        old_name: &str,
+        new_name: &str,
+    ) -> ILResult<()> {
+        TransactionHelper::run(&self.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                let namespace_id = tx_helper
+                    .get_namespace_id(namespace_name)
+                    .await?
+                    .ok_or_else(|| {
+                        ILError::CatalogError(format!("Namespace {namespace_name} not found"))
+                    })?;
+                let table_id = tx_helper
+                    .get_table_id(namespace_id, old_name)
+                    .await?
+                    .ok_or_else(|| {
+                        ILError::CatalogError(format!(
+                            "Table {old_name} not found in namespace {namespace_name}"
+                        ))
+                    })?;
+                if tx_helper.table_name_exists(namespace_id, new_name).await? {
+                    return Err(ILError::InvalidInput(format!(
+                        "Table {new_name} already exists in namespace {namespace_name}"
+                    )));
+                }
+
+                tx_helper.update_table_name(table_id, new_name).await?;
+
+                tx_helper.commit().await?;
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    /// Drops a table from the catalog. With `purge`, the files under the table directory are
+    /// deleted from the storage once the drop committed, except those clones of the table refer
+    /// to. Scans still running on the table fail.
+    /// Otherwise they are left in the storage, for recovery or for
+    /// [`LakeClient::vacuum_namespace`].
+    pub async fn drop_table(&self, ident: &TableIdent, purge: bool) -> ILResult<()> {
+        let table_dir = TransactionHelper::run(&self.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                let namespace_id = tx_helper
+                    .get_namespace_id(&ident.namespace_name)
+                    .await?
+                    .ok_or_else(|| {
+                        ILError::CatalogError(format!(
+                            "Namespace {} not found",
+                            ident.namespace_name
+                        ))
+                    })?;
+                let table_record = tx_helper
+                    .get_table(namespace_id, &ident.table_name)
+                    .await?
+                    .ok_or_else(|| {
+                        ILError::CatalogError(format!(
+                            "Table {} not found in namespace {}",
+                            ident.table_name, ident.namespace_name
+                        ))
+                    })?;
+                process_table_drop(&mut tx_helper, table_record.table_id, &table_record.config)
+                    .await?;
+                tx_helper.commit().await?;
+                Ok(table_record
+                    .config
+                    .table_dir(namespace_id, table_record.table_id))
+            })
+        })
+        .await?;
+
+        if purge {
+            process_table_purge(&self.catalog, &self.storage, &table_dir).await?;
+        }
+        Ok(())
+    }
+
+    /// Lists tables of a namespace ordered by name, one page at a time.
+    pub async fn list_tables(
+        &self,
+        namespace_name: &str,
+        options: ListOptions,
+    ) -> ILResult<TablePage> {
+        let catalog_helper = CatalogHelper::new(self.catalog.clone());
+        let namespace_id = catalog_helper
+            .get_namespace_id(namespace_name)
+            .await?
+            .ok_or_else(|| {
+                ILError::CatalogError(format!("Namespace {namespace_name} not found"))
+            })?;
+        process_list_tables(&catalog_helper, namespace_id, &options).await
+    }
+
+    /// Lists all tables of a namespace ordered by name.
+    pub async fn list_table_idents(&self, namespace_name: &str) -> ILResult<Vec<TableIdent>> {
+        let catalog_helper = CatalogHelper::new(self.catalog.clone());
+        let namespace_id = catalog_helper
+            .get_namespace_id(namespace_name)
+            .await?
+            .ok_or_else(|| {
+                ILError::CatalogError(format!("Namespace {namespace_name} not found"))
+            })?;
+        process_list_table_idents(&catalog_helper, namespace_id, namespace_name).await
+    }
+
+    /// Describes a table from the catalog metadata, without loading the table.
+    pub async fn describe_table(&self, ident: &TableIdent) -> ILResult<TableDescription> {
+        let catalog_helper = CatalogHelper::new(self.catalog.clone());
+        let namespace_id = catalog_helper
+            .get_namespace_id(&ident.namespace_name)
+            .await?
+            .ok_or_else(|| {
+                ILError::CatalogError(format!("Namespace {} not found", ident.namespace_name))
+            })?;
+        process_describe_table(&catalog_helper, namespace_id, ident).await
+    }
+
+    /// Adds up the [`Table::storage_stats`] of every table of a namespace.
+    pub async fn namespace_storage_stats(&self, namespace_name: &str) -> ILResult<StorageStats> {
+        let catalog_helper = CatalogHelper::new(self.catalog.clone());
+        let namespace_id = catalog_helper
+            .get_namespace_id(namespace_name)
+            .await?
+            .ok_or_else(|| {
+                ILError::CatalogError(format!("Namespace {namespace_name} not found"))
+            })?;
+        let mut table_stats = Vec::new();
+        for table_record in catalog_helper
+            .list_tables(namespace_id, None, None, None)
+            .await?
+        {
+            table_stats.push(
+                process_storage_stats(
+                    &catalog_helper,
+                    &self.storage,
+                    &table_record
+                        .config
+                        .table_dir(namespace_id, table_record.table_id),
+                    table_record.table_id,
+                )
+                .await?,
+            );
+        }
+        Ok(StorageStats::merge(table_stats))
+    }
+
+    /// Runs [`Table::vacuum`] on every table of a namespace, then deletes the files under the
+    /// namespace directory that belong to no table, like the files of dropped tables, last
+    /// modified more than `retention` ago.
+    pub async fn vacuum_namespace(
+        &self,
+        namespace_name: &str,
+        retention: Duration,
+    ) -> ILResult<VacuumReport> {
+        self.vacuum_namespace_with(namespace_name, retention, false)
+            .await
+    }
+
+    /// Reports what [`LakeClient::vacuum_namespace`] would delete, without changing the catalog
+    /// or the storage. Works on read-only catalogs.
+    pub async fn vacuum_namespace_dry_run(
+        &self,
+        namespace_name: &str,
+        retention: Duration,
+    ) -> ILResult<VacuumReport> {
+        self.vacuum_namespace_with(namespace_name, retention, true)
+            .await
+    }
+
+    async fn vacuum_namespace_with(
+        &self,
+        namespace_name: &str,
+        retention: Duration,
+        dry_run: bool,
+    ) -> ILResult<VacuumReport> {
+        if !dry_run {
+            check_writable(&self.catalog)?;
+        }
+        let catalog_helper = CatalogHelper::new(self.catalog.clone());
+        let namespace_id = catalog_helper
+            .get_namespace_id(namespace_name)
+            .await?
+            .ok_or_else(|| {
+                ILError::CatalogError(format!("Namespace {namespace_name} not found"))
+            })?;
+        let mut tables = Vec::new();
+        for table_record in catalog_helper
+            .list_tables(namespace_id, None, None, None)
+            .await?
+        {
+            tables.push(
+                self.load_table(namespace_name, &table_record.table_name)
+                    .await?,
+            );
+        }
+        process_vacuum_namespace(
+            &self.catalog,
+            &self.storage,
+            namespace_id,
+            &tables,
+            retention,
+            dry_run,
+        )
+        .await
+    }
+
+    /// Runs [`Table::expire_rows`] on every table with a TTL and adds up their reports.
+    pub async fn expire_rows(&self) -> ILResult<ExpireReport> {
+        let catalog_helper = CatalogHelper::new(self.catalog.clone());
+        let mut report = ExpireReport::default();
+        for namespace in catalog_helper.list_namespaces().await? {
+            for table_record in catalog_helper
+                .list_tables(namespace.namespace_id, None, None, None)
+                .await?
+            {
+                if table_record.config.ttl.is_none() {
+                    continue;
+                }
+                let table = self
+                    .load_table(&namespace.namespace_name, &table_record.table_name)
+                    .await?;
+                let table_report = table.expire_rows().await?;
+                report.removed_rows += table_report.removed_rows;
+                report.removed_files += table_report.removed_files;
+                report.removed_bytes += table_report.removed_bytes;
+            }
+        }
+        Ok(report)
+    }
+
+    /// Spawns a task running [`LakeClient::expire_rows`] every `interval` until the returned
+    /// handle is aborted. Failed runs are logged and retried at the next interval.
+    pub fn spawn_row_expiration(&self, interval: Duration) -> JoinHandle<()> {
+        let client = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = client.expire_rows().await {
+                    error!("Failed to expire rows: {e:?}");
+                }
+            }
+        })
+    }
+
+    /// Begins a write transaction over tables loaded from this client, committing the rows
+    /// inserted into all of them at once, see [`LakeTransaction`].
+    pub fn begin(&self) -> LakeTransaction {
+        self.begin_with_options(TransactionOptions::default())
+    }
+
+    /// Begins a write transaction like [`LakeClient::begin`] with the given options.
+    pub fn begin_with_options(&self, options: TransactionOptions) -> LakeTransaction {
+        LakeTransaction::new(self.catalog.clone(), options)
     }
 
     pub async fn load_table(&self, namespace_name: &str, table_name: &str) -> ILResult<Table> {
@@ -89,8 +496,8 @@ impl LakeClient {
                 ))
             })?;
 
-        let field_map = catalog_helper
-            .get_table_fields(table_record.table_id)
+        let (field_map, field_defaults, column_defaults) = catalog_helper
+            .get_table_fields_with_defaults(table_record.table_id)
             .await?;
 
         let mut fields = field_map.values().cloned().collect::<Vec<_>>();
@@ -120,11 +527,15 @@ impl LakeClient {
             table_name: table_name.to_string(),
             field_map,
             schema,
+            field_defaults,
+            column_defaults,
+            schema_version: table_record.schema_version,
             indexes,
             config: Arc::new(table_record.config),
             catalog: self.catalog.clone(),
             storage: self.storage.clone(),
             index_kinds: self.index_kinds.clone(),
+            base_snapshot: None,
         })
     }
 }
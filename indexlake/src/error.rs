@@ -1,3 +1,5 @@
+use crate::catalog::Scalar;
+
 pub type ILResult<T> = Result<T, ILError>;
 
 #[derive(Debug)]
@@ -5,9 +7,111 @@ pub enum ILError {
     InternalError(String),
     NotSupported(String),
     CatalogError(String),
+    CatalogPoolExhausted(String),
+    /// Catalog failure that is expected to succeed when retried, e.g. a dropped connection or
+    /// a serialization failure.
+    CatalogTransient(String),
+    /// The transaction was aborted because it conflicted with a concurrent transaction, e.g. a
+    /// serialization failure or deadlock. Re-running the whole transaction may succeed.
+    CatalogConflict(String),
+    /// The catalog was opened read-only and the operation would modify it.
+    CatalogReadOnly(String),
     StorageError(String),
     IndexError(String),
     InvalidInput(String),
+    /// The content of a data file does not match the checksum recorded when it was written,
+    /// e.g. after bit-rot or a truncated upload.
+    ChecksumMismatch(ChecksumMismatch),
+    /// The snapshot read has expired, the row history it needs was removed by vacuum or by the
+    /// snapshot retention of the table.
+    SnapshotExpired(String),
+    /// A written row violates a NOT NULL column, a check constraint or a unique constraint of the
+    /// table, nothing was written.
+    ConstraintViolation(ConstraintViolation),
+    /// Another writer committed to the table after the base snapshot of the committing handle,
+    /// see [`Table::with_base_snapshot`](crate::table::Table::with_base_snapshot). Nothing was
+    /// committed, the write can be retried on top of the current snapshot.
+    CommitConflict(CommitConflict),
+    /// The schema of the table changed after the writer loaded it, e.g. by a concurrent column
+    /// change. Nothing was committed, reload the table and write again.
+    SchemaConflict(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecksumMismatch {
+    pub relative_path: String,
+    /// CRC32C recorded in the catalog when the file was written.
+    pub expected: u32,
+    /// CRC32C of the file as read from the storage.
+    pub actual: u32,
+}
+
+impl std::fmt::Display for ChecksumMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "data file {} has checksum {:08x}, expected {:08x}",
+            self.relative_path, self.actual, self.expected
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitConflict {
+    pub table_name: String,
+    /// Snapshot the rejected commit was based on.
+    pub base_snapshot_id: i64,
+    /// Current snapshot of the table when the commit was rejected.
+    pub current_snapshot_id: i64,
+}
+
+impl std::fmt::Display for CommitConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "table {} is at snapshot {}, the commit was based on snapshot {}",
+            self.table_name, self.current_snapshot_id, self.base_snapshot_id
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintViolation {
+    /// Name of the check or unique constraint, or `NOT NULL` for a non-nullable column.
+    pub constraint: String,
+    /// The non-nullable column, the columns the check constraint refers to, or the key columns
+    /// of the unique constraint.
+    pub columns: Vec<String>,
+    /// Index of the first violating row in the written batch, or in the scanned rows when the
+    /// rows of the table are validated.
+    pub row_index: usize,
+    /// Row id of the violating row if it already has one, e.g. an updated row.
+    pub row_id: Option<i64>,
+    /// Conflicting key values of a unique constraint, in the order of `columns`. Empty for
+    /// other constraints.
+    pub values: Vec<Scalar>,
+}
+
+impl std::fmt::Display for ConstraintViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "row {} violates constraint {} on columns {:?}",
+            self.row_index, self.constraint, self.columns
+        )?;
+        if !self.values.is_empty() {
+            let values = self
+                .values
+                .iter()
+                .map(|value| value.to_string())
+                .collect::<Vec<_>>();
+            write!(f, " with key ({})", values.join(", "))?;
+        }
+        if let Some(row_id) = self.row_id {
+            write!(f, " (row id {row_id})")?;
+        }
+        Ok(())
+    }
 }
 
 impl std::fmt::Display for ILError {
@@ -16,13 +120,39 @@ impl std::fmt::Display for ILError {
             ILError::InternalError(msg) => write!(f, "Internal error: {}", msg),
             ILError::NotSupported(msg) => write!(f, "Not supported: {}", msg),
             ILError::CatalogError(msg) => write!(f, "Catalog error: {}", msg),
+            ILError::CatalogPoolExhausted(msg) => write!(f, "Catalog pool exhausted: {msg}"),
+            ILError::CatalogTransient(msg) => write!(f, "Catalog transient error: {msg}"),
+            ILError::CatalogConflict(msg) => write!(f, "Catalog conflict: {msg}"),
+            ILError::CatalogReadOnly(msg) => write!(f, "Catalog read-only: {msg}"),
             ILError::StorageError(msg) => write!(f, "Storage error: {}", msg),
             ILError::IndexError(msg) => write!(f, "Index error: {}", msg),
             ILError::InvalidInput(msg) => write!(f, "Invalid input: {}", msg),
+            ILError::ChecksumMismatch(mismatch) => write!(f, "Checksum mismatch: {mismatch}"),
+            ILError::SnapshotExpired(msg) => write!(f, "Snapshot expired: {msg}"),
+            ILError::ConstraintViolation(violation) => {
+                write!(f, "Constraint violation: {violation}")
+            }
+            ILError::CommitConflict(conflict) => write!(f, "Commit conflict: {conflict}"),
+            ILError::SchemaConflict(msg) => write!(f, "Schema conflict: {msg}"),
         }
     }
 }
 
+impl ILError {
+    /// Whether the failed operation may succeed when retried.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            ILError::CatalogTransient(_) | ILError::CatalogConflict(_)
+        )
+    }
+
+    /// Whether the transaction failed because of a concurrent transaction and can be re-run.
+    pub fn is_conflict(&self) -> bool {
+        matches!(self, ILError::CatalogConflict(_))
+    }
+}
+
 impl std::error::Error for ILError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         None
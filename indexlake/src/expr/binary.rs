@@ -2,18 +2,19 @@ use std::sync::Arc;
 
 use arrow::{
     array::{ArrayRef, AsArray, BooleanArray, Datum, RecordBatch},
-    datatypes::{DataType, Schema},
+    datatypes::{DECIMAL128_MAX_PRECISION, DECIMAL256_MAX_PRECISION, DataType, Schema},
     error::ArrowError,
 };
 use derive_visitor::{Drive, DriveMut};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     ILError, ILResult,
-    catalog::{CatalogDatabase, Row, Scalar},
+    catalog::{CatalogDatabase, Row, Scalar, time_unit_nanos},
     expr::{ColumnarValue, Expr},
 };
 
-#[derive(Debug, Clone, Copy, Drive, DriveMut, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Drive, DriveMut, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BinaryOp {
     /// Expressions are equal
     Eq,
@@ -64,7 +65,7 @@ impl std::fmt::Display for BinaryOp {
 }
 
 /// Binary expression
-#[derive(Debug, Clone, Drive, DriveMut, PartialEq, Eq)]
+#[derive(Debug, Clone, Drive, DriveMut, PartialEq, Eq, Serialize, Deserialize)]
 pub struct BinaryExpr {
     /// Left-hand side of the expression
     pub left: Box<Expr>,
@@ -171,15 +172,71 @@ pub fn apply(
     }
 }
 
-/// Applies a binary [`Datum`] comparison kernel `f` to `lhs` and `rhs`
+/// Applies a binary [`Datum`] comparison kernel `f` to `lhs` and `rhs`. Decimals of different
+/// precisions or scales are cast to a type holding both first, timestamps of different units
+/// or time zones to the finer unit.
 pub fn apply_cmp(
     lhs: &ColumnarValue,
     rhs: &ColumnarValue,
     f: impl Fn(&dyn Datum, &dyn Datum) -> Result<BooleanArray, ArrowError>,
 ) -> ILResult<ColumnarValue> {
+    let (lhs_type, rhs_type) = (lhs.data_type(), rhs.data_type());
+    if let Some(data_type) = common_decimal_type(&lhs_type, &rhs_type)
+        .or_else(|| common_timestamp_type(&lhs_type, &rhs_type))
+    {
+        return apply(
+            &lhs.cast_to(&data_type)?,
+            &rhs.cast_to(&data_type)?,
+            |l, r| Ok(Arc::new(f(l, r)?)),
+        );
+    }
     apply(lhs, rhs, |l, r| Ok(Arc::new(f(l, r)?)))
 }
 
+/// Decimal type holding every value of two different decimal types without loss, as far as
+/// 256-bit decimals go.
+fn common_decimal_type(left: &DataType, right: &DataType) -> Option<DataType> {
+    let (DataType::Decimal128(p1, s1) | DataType::Decimal256(p1, s1)) = left else {
+        return None;
+    };
+    let (DataType::Decimal128(p2, s2) | DataType::Decimal256(p2, s2)) = right else {
+        return None;
+    };
+    if left == right {
+        return None;
+    }
+    let scale = (*s1).max(*s2);
+    let integer_digits = (*p1 as i16 - *s1 as i16).max(*p2 as i16 - *s2 as i16);
+    let precision = (integer_digits + scale as i16).max(1);
+    let is_256 =
+        matches!(left, DataType::Decimal256(_, _)) || matches!(right, DataType::Decimal256(_, _));
+    if !is_256 && precision <= DECIMAL128_MAX_PRECISION as i16 {
+        Some(DataType::Decimal128(precision as u8, scale))
+    } else {
+        Some(DataType::Decimal256(
+            precision.min(DECIMAL256_MAX_PRECISION as i16) as u8,
+            scale,
+        ))
+    }
+}
+
+/// Timestamp type of the finer unit of two different timestamp types, in the time zone of the
+/// left one. Time zones do not change the values, which are UTC.
+fn common_timestamp_type(left: &DataType, right: &DataType) -> Option<DataType> {
+    let (DataType::Timestamp(u1, tz), DataType::Timestamp(u2, _)) = (left, right) else {
+        return None;
+    };
+    if left == right {
+        return None;
+    }
+    let unit = if time_unit_nanos(*u1) <= time_unit_nanos(*u2) {
+        *u1
+    } else {
+        *u2
+    };
+    Some(DataType::Timestamp(unit, tz.clone()))
+}
+
 pub fn apply_boolean(
     lhs: &ColumnarValue,
     rhs: &ColumnarValue,
@@ -1,6 +1,6 @@
 use crate::{
     catalog::Scalar,
-    expr::{BinaryExpr, BinaryOp, Expr, like::LikeExpr},
+    expr::{BinaryExpr, BinaryOp, Expr, InList, like::LikeExpr},
 };
 
 impl Expr {
@@ -28,6 +28,30 @@ impl Expr {
         })
     }
 
+    pub fn gt_eq(self, other: Expr) -> Expr {
+        Expr::BinaryExpr(BinaryExpr {
+            left: Box::new(self),
+            op: BinaryOp::GtEq,
+            right: Box::new(other),
+        })
+    }
+
+    pub fn lt(self, other: Expr) -> Expr {
+        Expr::BinaryExpr(BinaryExpr {
+            left: Box::new(self),
+            op: BinaryOp::Lt,
+            right: Box::new(other),
+        })
+    }
+
+    pub fn lt_eq(self, other: Expr) -> Expr {
+        Expr::BinaryExpr(BinaryExpr {
+            left: Box::new(self),
+            op: BinaryOp::LtEq,
+            right: Box::new(other),
+        })
+    }
+
     pub fn plus(self, other: Expr) -> Expr {
         Expr::BinaryExpr(BinaryExpr {
             left: Box::new(self),
@@ -36,6 +60,30 @@ impl Expr {
         })
     }
 
+    pub fn minus(self, other: Expr) -> Expr {
+        Expr::BinaryExpr(BinaryExpr {
+            left: Box::new(self),
+            op: BinaryOp::Minus,
+            right: Box::new(other),
+        })
+    }
+
+    pub fn multiply(self, other: Expr) -> Expr {
+        Expr::BinaryExpr(BinaryExpr {
+            left: Box::new(self),
+            op: BinaryOp::Multiply,
+            right: Box::new(other),
+        })
+    }
+
+    pub fn divide(self, other: Expr) -> Expr {
+        Expr::BinaryExpr(BinaryExpr {
+            left: Box::new(self),
+            op: BinaryOp::Divide,
+            right: Box::new(other),
+        })
+    }
+
     pub fn and(self, other: Expr) -> Expr {
         Expr::BinaryExpr(BinaryExpr {
             left: Box::new(self),
@@ -44,6 +92,23 @@ impl Expr {
         })
     }
 
+    pub fn or(self, other: Expr) -> Expr {
+        Expr::BinaryExpr(BinaryExpr {
+            left: Box::new(self),
+            op: BinaryOp::Or,
+            right: Box::new(other),
+        })
+    }
+
+    /// Return `self IN (list)` or `self NOT IN (list)`
+    pub fn in_list(self, list: Vec<Expr>, negated: bool) -> Expr {
+        Expr::InList(InList {
+            expr: Box::new(self),
+            list,
+            negated,
+        })
+    }
+
     pub fn is_null(self) -> Expr {
         Expr::IsNull(Box::new(self))
     }
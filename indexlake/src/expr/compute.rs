@@ -1,10 +1,13 @@
+use std::collections::HashSet;
+
 use arrow::{
-    array::{BooleanArray, Datum, make_comparator},
+    array::{Array, ArrayRef, BooleanArray, Datum, make_comparator},
     buffer::NullBuffer,
     compute::SortOptions,
+    row::{RowConverter, SortField},
 };
 
-use crate::{ILError, ILResult, expr::BinaryOp};
+use crate::{ILError, ILResult, catalog::Scalar, expr::BinaryOp};
 
 /// Compare with eq with either nested or non-nested
 pub fn compare_with_eq(
@@ -70,3 +73,78 @@ pub fn compare_op_for_nested(
     let nulls = NullBuffer::union(l.nulls(), r.nulls());
     Ok(BooleanArray::new(values, nulls))
 }
+
+/// Evaluates `value IN (list)` by looking up each value in a hash set of the list, instead of
+/// comparing the whole array with every item. Returns `None` when a non-null item is not of the
+/// value type, such lists are left to the comparison kernels.
+pub(crate) fn in_scalar_list(value: &ArrayRef, list: &[&Scalar]) -> ILResult<Option<BooleanArray>> {
+    let mut items = Vec::with_capacity(list.len());
+    let mut has_null = false;
+    for scalar in list {
+        if scalar.is_null() {
+            has_null = true;
+        } else if &scalar.data_type() != value.data_type() {
+            return Ok(None);
+        } else {
+            items.push(scalar.to_array_of_size(1)?);
+        }
+    }
+
+    let converter = RowConverter::new(vec![SortField::new(value.data_type().clone())])?;
+    let item_rows = if items.is_empty() {
+        converter.empty_rows(0, 0)
+    } else {
+        let items =
+            arrow::compute::concat(&items.iter().map(|item| item.as_ref()).collect::<Vec<_>>())?;
+        converter.convert_columns(&[items])?
+    };
+    let item_set = item_rows.iter().collect::<HashSet<_>>();
+    let value_rows = converter.convert_columns(std::slice::from_ref(value))?;
+
+    // Same null semantics as ORing the comparisons: unknown unless found when either side is null
+    let found = (0..value.len())
+        .map(|i| {
+            if value.is_null(i) {
+                None
+            } else if item_set.contains(&value_rows.row(i)) {
+                Some(true)
+            } else if has_null {
+                None
+            } else {
+                Some(false)
+            }
+        })
+        .collect::<BooleanArray>();
+    Ok(Some(found))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::Int64Array;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_in_scalar_list() {
+        let value: ArrayRef = Arc::new(Int64Array::from(vec![Some(1), Some(2), None, Some(4)]));
+
+        let found = in_scalar_list(&value, &[&Scalar::Int64(Some(4)), &Scalar::Int64(Some(1))])
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            found,
+            BooleanArray::from(vec![Some(true), Some(false), None, Some(true)])
+        );
+
+        let found = in_scalar_list(&value, &[&Scalar::Int64(Some(2)), &Scalar::Int64(None)])
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            found,
+            BooleanArray::from(vec![None, Some(true), None, None])
+        );
+
+        let found = in_scalar_list(&value, &[&Scalar::Int32(Some(1))]).unwrap();
+        assert!(found.is_none());
+    }
+}
@@ -1,13 +1,14 @@
 use arrow::{array::RecordBatch, datatypes::DataType};
 use derive_visitor::{Drive, DriveMut};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     ILResult,
-    catalog::CatalogDatabase,
+    catalog::{CatalogDatabase, Scalar},
     expr::{ColumnarValue, Expr, apply_cmp},
 };
 
-#[derive(Debug, Clone, Drive, DriveMut, PartialEq, Eq)]
+#[derive(Debug, Clone, Drive, DriveMut, PartialEq, Eq, Serialize, Deserialize)]
 pub struct LikeExpr {
     negated: bool,
     case_insensitive: bool,
@@ -27,6 +28,11 @@ impl LikeExpr {
     pub(crate) fn to_sql(&self, database: CatalogDatabase) -> ILResult<String> {
         let expr = self.expr.to_sql(database.clone())?;
         let pattern = self.pattern.to_sql(database)?;
+        if self.case_insensitive && matches!(database, CatalogDatabase::MySql) {
+            // MySQL does not support ILIKE
+            let op = if self.negated { "NOT LIKE" } else { "LIKE" };
+            return Ok(format!("LOWER({expr}) {op} LOWER({pattern})"));
+        }
         match (self.negated, self.case_insensitive) {
             (true, true) => Ok(format!("{} NOT ILIKE {}", expr, pattern)),
             (true, false) => Ok(format!("{} NOT LIKE {}", expr, pattern)),
@@ -47,6 +53,24 @@ impl LikeExpr {
         }
     }
 
+    /// Column and literal prefix of a case sensitive `column LIKE 'prefix%'` expression, every
+    /// matching string starts with the prefix.
+    pub(crate) fn column_prefix(&self) -> Option<(&str, &str)> {
+        if self.negated || self.case_insensitive {
+            return None;
+        }
+        let (Expr::Column(name), Expr::Literal(Scalar::Utf8(Some(pattern)))) =
+            (self.expr.as_ref(), self.pattern.as_ref())
+        else {
+            return None;
+        };
+        let end = pattern.find(['%', '_', '\\']).unwrap_or(pattern.len());
+        if end == 0 {
+            return None;
+        }
+        Some((name, &pattern[..end]))
+    }
+
     #[allow(unused)]
     pub fn data_type(&self) -> ILResult<DataType> {
         Ok(DataType::Boolean)
@@ -125,6 +149,16 @@ mod tests {
             true,
         );
         assert_eq!(not_ilike_expr.to_sql(db).unwrap(), "`c1` NOT ILIKE 'a%'");
+
+        // ILIKE on MySQL
+        assert_eq!(
+            ilike_expr.to_sql(CatalogDatabase::MySql).unwrap(),
+            "LOWER(`c1`) LIKE LOWER('a%')"
+        );
+        assert_eq!(
+            not_ilike_expr.to_sql(CatalogDatabase::MySql).unwrap(),
+            "LOWER(`c1`) NOT LIKE LOWER('a%')"
+        );
     }
 
     #[test]
@@ -14,7 +14,7 @@ pub use visitor::*;
 use std::sync::Arc;
 
 use arrow::{
-    array::{ArrayRef, AsArray, BooleanArray, RecordBatch},
+    array::{ArrayRef, AsArray, BooleanArray, RecordBatch, make_array},
     buffer::BooleanBuffer,
     datatypes::{DataType, Schema},
     error::ArrowError,
@@ -22,6 +22,7 @@ use arrow::{
 use parquet::arrow::{ArrowSchemaConverter, ProjectionMask, arrow_reader::ArrowPredicate};
 
 use derive_visitor::{Drive, DriveMut};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     ILError, ILResult,
@@ -30,7 +31,7 @@ use crate::{
 };
 
 /// Represents logical expressions such as `A + 1`
-#[derive(Debug, Clone, Drive, DriveMut, PartialEq, Eq)]
+#[derive(Debug, Clone, Drive, DriveMut, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Expr {
     /// A named reference
     Column(String),
@@ -97,13 +98,31 @@ impl Expr {
                 let num_rows = batch.num_rows();
                 let value = in_list.expr.eval(batch)?.into_array(num_rows)?;
                 let is_nested = value.data_type().is_nested();
-                let found = in_list.list.iter().map(|expr| expr.eval(batch)).try_fold(
-                    BooleanArray::new(BooleanBuffer::new_unset(num_rows), None),
-                    |result, expr| -> ILResult<BooleanArray> {
-                        let rhs = compare_with_eq(&value, &expr?.into_array(num_rows)?, is_nested)?;
-                        Ok(arrow::compute::or_kleene(&result, &rhs)?)
-                    },
-                )?;
+                let literals = in_list
+                    .list
+                    .iter()
+                    .map(|expr| match expr {
+                        Expr::Literal(scalar) => Some(scalar),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>();
+                let found = match literals {
+                    Some(literals) if !is_nested && !literals.is_empty() => {
+                        in_scalar_list(&value, &literals)?
+                    }
+                    _ => None,
+                };
+                let found = match found {
+                    Some(found) => found,
+                    None => in_list.list.iter().map(|expr| expr.eval(batch)).try_fold(
+                        BooleanArray::new(BooleanBuffer::new_unset(num_rows), None),
+                        |result, expr| -> ILResult<BooleanArray> {
+                            let rhs =
+                                compare_with_eq(&value, &expr?.into_array(num_rows)?, is_nested)?;
+                            Ok(arrow::compute::or_kleene(&result, &rhs)?)
+                        },
+                    )?,
+                };
 
                 let r = if in_list.negated {
                     arrow::compute::not(&found)?
@@ -235,7 +254,7 @@ impl ArrowPredicate for ExprPredicate {
 }
 
 /// InList expression
-#[derive(Debug, Clone, Drive, DriveMut, PartialEq, Eq)]
+#[derive(Debug, Clone, Drive, DriveMut, PartialEq, Eq, Serialize, Deserialize)]
 pub struct InList {
     /// The expression to compare
     pub expr: Box<Expr>,
@@ -245,7 +264,7 @@ pub struct InList {
     pub negated: bool,
 }
 
-#[derive(Debug, Clone, Drive, DriveMut, PartialEq, Eq)]
+#[derive(Debug, Clone, Drive, DriveMut, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Function {
     pub name: String,
     pub args: Vec<Expr>,
@@ -268,4 +287,39 @@ impl ColumnarValue {
             ColumnarValue::Scalar(scalar) => scalar.to_array_of_size(num_rows)?,
         })
     }
+
+    pub fn data_type(&self) -> DataType {
+        match self {
+            ColumnarValue::Array(array) => array.data_type().clone(),
+            ColumnarValue::Scalar(scalar) => scalar.data_type(),
+        }
+    }
+
+    /// Casts the value to `data_type`. Timestamps keep their UTC values when cast to another
+    /// time zone, or from none, as statistics and inline rows compare them.
+    pub(crate) fn cast_to(&self, data_type: &DataType) -> ILResult<Self> {
+        Ok(match self {
+            ColumnarValue::Array(array) => ColumnarValue::Array(cast_array(array, data_type)?),
+            ColumnarValue::Scalar(scalar) => {
+                let array = cast_array(&scalar.to_array_of_size(1)?, data_type)?;
+                ColumnarValue::Scalar(Scalar::try_from_array(array.as_ref(), 0)?)
+            }
+        })
+    }
+}
+
+fn cast_array(array: &ArrayRef, data_type: &DataType) -> ILResult<ArrayRef> {
+    if let (DataType::Timestamp(_, from_tz), DataType::Timestamp(unit, to_tz)) =
+        (array.data_type(), data_type)
+        && from_tz != to_tz
+    {
+        let array = arrow::compute::cast(array, &DataType::Timestamp(*unit, from_tz.clone()))?;
+        let data = array
+            .to_data()
+            .into_builder()
+            .data_type(data_type.clone())
+            .build()?;
+        return Ok(make_array(data));
+    }
+    Ok(arrow::compute::cast(array, data_type)?)
 }
@@ -1,6 +1,9 @@
+use std::cmp::Ordering;
+
 use crate::{
     ILError, ILResult,
-    expr::{BinaryExpr, BinaryOp, Expr, lit},
+    catalog::Scalar,
+    expr::{BinaryExpr, BinaryOp, Expr, InList, lit},
 };
 
 pub fn split_binary_expr(expr: Expr, operator: BinaryOp) -> Vec<Expr> {
@@ -44,6 +47,144 @@ pub fn merge_filters(mut filters: Vec<Expr>) -> Option<Expr> {
     Some(expr)
 }
 
+/// Returns whether every row matching all of the filters matches the predicate as well. The check
+/// is conservative, each conjunct of the predicate has to equal one of the filters or follow from a
+/// comparison of the same column with a literal, e.g. `a = 5` implies `a > 3`, `a IN (1, 5)` and
+/// `a IS NOT NULL`. Anything else is reported as not implied.
+pub fn filters_imply(filters: &[Expr], predicate: &Expr) -> bool {
+    let filters = split_conjunction_filters(filters.to_vec());
+    split_binary_expr(predicate.clone(), BinaryOp::And)
+        .iter()
+        .all(|conjunct| {
+            filters
+                .iter()
+                .any(|filter| filter_implies(filter, conjunct))
+        })
+}
+
+/// What a filter requires of the values of a single column.
+enum ColumnConstraint {
+    Compare(BinaryOp, Scalar),
+    InList(Vec<Scalar>),
+    NotNull,
+}
+
+fn filter_implies(filter: &Expr, conjunct: &Expr) -> bool {
+    if filter == conjunct {
+        return true;
+    }
+    let (Some((filter_column, filter)), Some((conjunct_column, conjunct))) =
+        (column_constraint(filter), column_constraint(conjunct))
+    else {
+        return false;
+    };
+    if filter_column != conjunct_column {
+        return false;
+    }
+    match (filter, conjunct) {
+        // Comparisons with non-null literals only match non-null values
+        (_, ColumnConstraint::NotNull) => true,
+        (ColumnConstraint::NotNull, _) => false,
+        (ColumnConstraint::Compare(BinaryOp::Eq, value), conjunct) => {
+            constraint_matches(&conjunct, &value)
+        }
+        (ColumnConstraint::InList(values), conjunct) => values
+            .iter()
+            .all(|value| constraint_matches(&conjunct, value)),
+        (ColumnConstraint::Compare(op, value), ColumnConstraint::Compare(bound_op, bound)) => {
+            let ordering = value.partial_cmp(&bound);
+            match (op, bound_op) {
+                (BinaryOp::NotEq, BinaryOp::NotEq) => ordering == Some(Ordering::Equal),
+                (BinaryOp::Gt | BinaryOp::GtEq, BinaryOp::GtEq) | (BinaryOp::Gt, BinaryOp::Gt) => {
+                    matches!(ordering, Some(Ordering::Greater | Ordering::Equal))
+                }
+                (BinaryOp::GtEq, BinaryOp::Gt) => ordering == Some(Ordering::Greater),
+                (BinaryOp::Lt | BinaryOp::LtEq, BinaryOp::LtEq) | (BinaryOp::Lt, BinaryOp::Lt) => {
+                    matches!(ordering, Some(Ordering::Less | Ordering::Equal))
+                }
+                (BinaryOp::LtEq, BinaryOp::Lt) => ordering == Some(Ordering::Less),
+                _ => false,
+            }
+        }
+        (ColumnConstraint::Compare(..), ColumnConstraint::InList(_)) => false,
+    }
+}
+
+fn constraint_matches(constraint: &ColumnConstraint, value: &Scalar) -> bool {
+    match constraint {
+        ColumnConstraint::Compare(op, bound) => {
+            let ordering = value.partial_cmp(bound);
+            match op {
+                BinaryOp::Eq => ordering == Some(Ordering::Equal),
+                BinaryOp::NotEq => matches!(ordering, Some(Ordering::Less | Ordering::Greater)),
+                BinaryOp::Lt => ordering == Some(Ordering::Less),
+                BinaryOp::LtEq => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+                BinaryOp::Gt => ordering == Some(Ordering::Greater),
+                BinaryOp::GtEq => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+                _ => false,
+            }
+        }
+        ColumnConstraint::InList(values) => values.contains(value),
+        ColumnConstraint::NotNull => true,
+    }
+}
+
+fn column_constraint(expr: &Expr) -> Option<(&str, ColumnConstraint)> {
+    match expr {
+        Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
+            let (name, op, value) = match (left.as_ref(), right.as_ref()) {
+                (Expr::Column(name), Expr::Literal(value)) => (name, *op, value),
+                (Expr::Literal(value), Expr::Column(name)) => (name, flip_comparison(*op)?, value),
+                _ => return None,
+            };
+            let comparison = matches!(
+                op,
+                BinaryOp::Eq
+                    | BinaryOp::NotEq
+                    | BinaryOp::Lt
+                    | BinaryOp::LtEq
+                    | BinaryOp::Gt
+                    | BinaryOp::GtEq
+            );
+            (comparison && !value.is_null())
+                .then(|| (name.as_str(), ColumnConstraint::Compare(op, value.clone())))
+        }
+        Expr::InList(InList {
+            expr,
+            list,
+            negated: false,
+        }) => {
+            let Expr::Column(name) = expr.as_ref() else {
+                return None;
+            };
+            let values = list
+                .iter()
+                .map(|item| match item {
+                    Expr::Literal(value) if !value.is_null() => Some(value.clone()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Some((name.as_str(), ColumnConstraint::InList(values)))
+        }
+        Expr::IsNotNull(expr) => match expr.as_ref() {
+            Expr::Column(name) => Some((name.as_str(), ColumnConstraint::NotNull)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn flip_comparison(op: BinaryOp) -> Option<BinaryOp> {
+    match op {
+        BinaryOp::Eq | BinaryOp::NotEq => Some(op),
+        BinaryOp::Lt => Some(BinaryOp::Gt),
+        BinaryOp::LtEq => Some(BinaryOp::GtEq),
+        BinaryOp::Gt => Some(BinaryOp::Lt),
+        BinaryOp::GtEq => Some(BinaryOp::LtEq),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::expr::{col, lit};
@@ -62,4 +203,48 @@ mod tests {
         assert_eq!(exprs[1], expr2);
         assert_eq!(exprs[2], expr3);
     }
+
+    #[test]
+    fn test_filters_imply() {
+        let active = col("status").eq(lit("active".to_string()));
+        assert!(filters_imply(&[active.clone()], &active));
+        assert!(filters_imply(
+            &[col("name").eq(lit("Bob".to_string())).and(active.clone())],
+            &active
+        ));
+        assert!(filters_imply(
+            &[lit("active".to_string()).eq(col("status"))],
+            &active
+        ));
+        assert!(!filters_imply(&[], &active));
+        assert!(!filters_imply(
+            &[col("status").eq(lit("archived".to_string()))],
+            &active
+        ));
+        assert!(!filters_imply(
+            &[active.clone().or(col("age").gt(lit(3)))],
+            &active
+        ));
+
+        let adult = col("age").gt_eq(lit(18));
+        assert!(filters_imply(&[col("age").eq(lit(30))], &adult));
+        assert!(filters_imply(&[col("age").gt(lit(18))], &adult));
+        assert!(filters_imply(&[col("age").gt_eq(lit(20))], &adult));
+        assert!(filters_imply(&[lit(20).lt(col("age"))], &adult));
+        assert!(!filters_imply(&[col("age").gt(lit(17))], &adult));
+        assert!(!filters_imply(&[col("age").lt(lit(30))], &adult));
+        assert!(!filters_imply(&[col("age").eq(lit(30i64))], &adult));
+
+        let statuses = col("status").in_list(
+            vec![lit("active".to_string()), lit("pending".to_string())],
+            false,
+        );
+        assert!(filters_imply(&[active.clone()], &statuses));
+        assert!(!filters_imply(&[statuses.clone()], &active));
+        assert!(filters_imply(&[statuses], &col("status").is_not_null()));
+        assert!(filters_imply(
+            &[active.clone(), adult.clone()],
+            &adult.clone().and(active)
+        ));
+    }
 }
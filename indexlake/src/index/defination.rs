@@ -1,10 +1,11 @@
 use crate::{
     ILError, ILResult,
     catalog::IndexRecord,
+    expr::Expr,
     index::{Index, IndexParams},
 };
 use arrow::{
-    array::{ArrayRef, RecordBatch},
+    array::{ArrayRef, AsArray, RecordBatch},
     datatypes::{Field, FieldRef, SchemaRef},
 };
 use std::{
@@ -25,6 +26,8 @@ pub struct IndexDefination {
     pub key_columns: Vec<String>,
     pub include_columns: Vec<String>,
     pub params: Arc<dyn IndexParams>,
+    /// Only rows matching the predicate are indexed, `None` for indexes of all rows.
+    pub where_predicate: Option<Expr>,
 }
 
 impl IndexDefination {
@@ -69,6 +72,24 @@ impl IndexDefination {
         Ok(include_array_map)
     }
 
+    /// Returns the rows of the batch the index covers, the rows matching the where predicate.
+    pub fn indexed_rows(&self, record_batch: &RecordBatch) -> ILResult<RecordBatch> {
+        let Some(predicate) = &self.where_predicate else {
+            return Ok(record_batch.clone());
+        };
+        let array = predicate
+            .eval(record_batch)?
+            .into_array(record_batch.num_rows())?;
+        let mask = array.as_boolean_opt().ok_or_else(|| {
+            ILError::InternalError(format!(
+                "Predicate of index {} evaluated to {}, expected boolean",
+                self.name,
+                array.data_type()
+            ))
+        })?;
+        Ok(arrow::compute::filter_record_batch(record_batch, mask)?)
+    }
+
     pub fn downcast_params<T: 'static>(&self) -> ILResult<&T> {
         self.params.as_any().downcast_ref::<T>().ok_or_else(|| {
             ILError::InternalError(format!(
@@ -119,6 +140,7 @@ impl IndexDefination {
             key_columns,
             include_columns,
             params,
+            where_predicate: index_record.where_predicate.clone(),
         })
     }
 }
@@ -21,6 +21,14 @@ pub trait Index: Debug + Send + Sync {
 
     fn builder(&self, index_def: &IndexDefinationRef) -> ILResult<Box<dyn IndexBuilder>>;
 
+    /// Returns whether [`Index::search`] can answer the query, used by `Table::search` to pick
+    /// the index of the table to search.
+    fn supports_search(
+        &self,
+        index_def: &IndexDefination,
+        query: &dyn SearchQuery,
+    ) -> ILResult<bool>;
+
     async fn search(
         &self,
         index_def: &IndexDefination,
@@ -30,6 +38,13 @@ pub trait Index: Debug + Send + Sync {
 
     fn supports_filter(&self, index_def: &IndexDefination, filter: &Expr) -> ILResult<bool>;
 
+    /// Returns whether the filters of a scan that [`Index::supports_filter`] accepted one by one
+    /// are worth looking up together, e.g. composite indexes need a filter on their leading key
+    /// column. Filters of an index returning false are left to the scan.
+    fn supports_filters(&self, _index_def: &IndexDefination, _filters: &[Expr]) -> ILResult<bool> {
+        Ok(true)
+    }
+
     // TODO return a stream of entries
     async fn filter(
         &self,
@@ -37,6 +52,19 @@ pub trait Index: Debug + Send + Sync {
         index_file: InputFile,
         filters: &[Expr],
     ) -> ILResult<FilterIndexEntries>;
+
+    /// Checks the filters against the [`IndexBuilder::file_metadata`] of a data file, before
+    /// the index file is read. Returns `Some(false)` if no row of the data file can match,
+    /// `Some(true)` if any row may match, and `None` to look the rows up with
+    /// [`Index::filter`].
+    fn filter_file_metadata(
+        &self,
+        _index_def: &IndexDefination,
+        _metadata: &[u8],
+        _filters: &[Expr],
+    ) -> ILResult<Option<bool>> {
+        Ok(None)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -70,4 +98,10 @@ pub trait IndexBuilder: Debug + Send + Sync {
     fn update(&mut self, batch: &RecordBatch) -> ILResult<()>;
 
     async fn write(&mut self, output_file: OutputFile) -> ILResult<()>;
+
+    /// Small summary of the data file kept in the catalog next to the index file once it is
+    /// written, so scans can skip data files without reading their index files.
+    fn file_metadata(&self) -> ILResult<Option<Vec<u8>>> {
+        Ok(None)
+    }
 }
@@ -0,0 +1,60 @@
+use opendal::{Configurator, Operator, services::AzblobConfig};
+
+use crate::{ILError, ILResult};
+
+#[derive(Debug, Clone)]
+pub struct AzblobStorage {
+    config: AzblobConfig,
+    container: String,
+}
+
+impl AzblobStorage {
+    pub fn new(config: AzblobConfig, container: String) -> Self {
+        Self { config, container }
+    }
+
+    pub fn new_operator(&self) -> ILResult<Operator> {
+        check_container_name(&self.container)?;
+        let builder = self
+            .config
+            .clone()
+            .into_builder()
+            .container(&self.container);
+        Ok(Operator::new(builder)?.finish())
+    }
+}
+
+/// Container names must be 3 to 63 characters of lowercase letters, digits and non-consecutive
+/// hyphens, starting and ending with a letter or digit.
+fn check_container_name(container: &str) -> ILResult<()> {
+    let valid = (3..=63).contains(&container.len())
+        && container
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !container.starts_with('-')
+        && !container.ends_with('-')
+        && !container.contains("--");
+    if valid {
+        Ok(())
+    } else {
+        Err(ILError::InvalidInput(format!(
+            "Invalid azure blob container name: {container}"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_container_name() {
+        assert!(check_container_name("indexlake").is_ok());
+        assert!(check_container_name("index-lake-01").is_ok());
+        assert!(check_container_name("ab").is_err());
+        assert!(check_container_name("IndexLake").is_err());
+        assert!(check_container_name("-indexlake").is_err());
+        assert!(check_container_name("index--lake").is_err());
+        assert!(check_container_name("index_lake").is_err());
+    }
+}
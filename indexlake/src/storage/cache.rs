@@ -0,0 +1,360 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use bytes::Bytes;
+use log::warn;
+
+use crate::{ILError, ILResult};
+
+/// Local disk cache settings of [`Storage::new_s3_with_cache`](crate::storage::Storage::new_s3_with_cache).
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    /// Directory holding the cached byte ranges in its `indexlake-cache` subdirectory, which is
+    /// cleared on creation.
+    pub dir: PathBuf,
+    /// Least recently used ranges are evicted beyond this many bytes.
+    pub max_bytes: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Bytes currently held on disk.
+    pub used_bytes: u64,
+    /// Cached byte ranges currently held on disk.
+    pub entries: usize,
+}
+
+/// Local disk cache of immutable file byte ranges with LRU eviction.
+///
+/// A cached range is stored at `{cache_dir}/indexlake-cache/{relative_path}/{version}_{start}_{end}`,
+/// so all ranges of a file are dropped together when the file is deleted. The version identifies the
+/// content of the file, such as its etag, so an overwritten file is never served from ranges
+/// cached before.
+#[derive(Debug)]
+pub struct DiskCache {
+    cache_dir: PathBuf,
+    max_bytes: u64,
+    state: Mutex<CacheState>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: HashMap<CacheKey, CacheEntry>,
+    // Access tick to key, the first entry is the least recently used one
+    lru: BTreeMap<u64, CacheKey>,
+    tick: u64,
+    used_bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    relative_path: String,
+    version: String,
+    start: u64,
+    end: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CacheEntry {
+    size: u64,
+    tick: u64,
+}
+
+impl CacheState {
+    fn touch(&mut self, key: &CacheKey) -> bool {
+        self.tick += 1;
+        let tick = self.tick;
+        match self.entries.get_mut(key) {
+            Some(entry) => {
+                self.lru.remove(&entry.tick);
+                entry.tick = tick;
+                self.lru.insert(tick, key.clone());
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn insert(&mut self, key: CacheKey, size: u64) {
+        self.remove(&key);
+        self.tick += 1;
+        let tick = self.tick;
+        self.entries.insert(key.clone(), CacheEntry { size, tick });
+        self.lru.insert(tick, key);
+        self.used_bytes += size;
+    }
+
+    fn remove(&mut self, key: &CacheKey) -> bool {
+        match self.entries.remove(key) {
+            Some(entry) => {
+                self.lru.remove(&entry.tick);
+                self.used_bytes -= entry.size;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn pop_lru(&mut self) -> Option<CacheKey> {
+        let (_, key) = self.lru.pop_first()?;
+        if let Some(entry) = self.entries.remove(&key) {
+            self.used_bytes -= entry.size;
+        }
+        Some(key)
+    }
+}
+
+/// Subdirectory of the configured cache directory owned by the cache.
+const CACHE_SUBDIR: &str = "indexlake-cache";
+
+impl DiskCache {
+    /// Ranges are kept in the `indexlake-cache` subdirectory of `cache_dir`, any content left
+    /// there by a previous process is removed. Other content of `cache_dir` is left untouched.
+    pub fn try_new(cache_dir: impl Into<PathBuf>, max_bytes: u64) -> ILResult<Self> {
+        let cache_dir = cache_dir.into().join(CACHE_SUBDIR);
+        if cache_dir.exists() {
+            std::fs::remove_dir_all(&cache_dir).map_err(|e| {
+                ILError::StorageError(format!(
+                    "Failed to clear cache dir {}: {e}",
+                    cache_dir.display()
+                ))
+            })?;
+        }
+        std::fs::create_dir_all(&cache_dir).map_err(|e| {
+            ILError::StorageError(format!(
+                "Failed to create cache dir {}: {e}",
+                cache_dir.display()
+            ))
+        })?;
+        Ok(Self {
+            cache_dir,
+            max_bytes,
+            state: Mutex::new(CacheState::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        })
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let state = self.state.lock().unwrap();
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            used_bytes: state.used_bytes,
+            entries: state.entries.len(),
+        }
+    }
+
+    fn file_dir(&self, relative_path: &str) -> PathBuf {
+        self.cache_dir.join(relative_path.trim_start_matches('/'))
+    }
+
+    fn entry_path(&self, key: &CacheKey) -> PathBuf {
+        self.file_dir(&key.relative_path).join(format!(
+            "{}_{}_{}",
+            hex::encode(&key.version),
+            key.start,
+            key.end
+        ))
+    }
+
+    /// Returns the cached bytes of the range of the given version of the file, or loads them
+    /// with `load` and caches them.
+    pub(crate) async fn get_or_load<F, Fut>(
+        &self,
+        relative_path: &str,
+        version: &str,
+        range: Range<u64>,
+        load: F,
+    ) -> ILResult<Bytes>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = ILResult<Bytes>>,
+    {
+        let key = CacheKey {
+            relative_path: relative_path.to_string(),
+            version: version.to_string(),
+            start: range.start,
+            end: range.end,
+        };
+        let entry_path = self.entry_path(&key);
+
+        if self.state.lock().unwrap().touch(&key) {
+            match tokio::fs::read(&entry_path).await {
+                Ok(data) => {
+                    self.hits.fetch_add(1, Ordering::Relaxed);
+                    return Ok(Bytes::from(data));
+                }
+                Err(e) => {
+                    // Evicted or invalidated concurrently
+                    warn!("Failed to read cache entry {}: {e}", entry_path.display());
+                    self.state.lock().unwrap().remove(&key);
+                }
+            }
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let data = load().await?;
+        if (data.len() as u64) <= self.max_bytes
+            && let Err(e) = self.put(key, &entry_path, &data).await
+        {
+            warn!("Failed to write cache entry {}: {e}", entry_path.display());
+        }
+        Ok(data)
+    }
+
+    async fn put(&self, key: CacheKey, entry_path: &Path, data: &Bytes) -> ILResult<()> {
+        let map_err = |e: std::io::Error| ILError::StorageError(e.to_string());
+        if let Some(parent) = entry_path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(map_err)?;
+        }
+        // Write to a unique temp file first so concurrent readers never see partial content
+        let tmp_path = entry_path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+        tokio::fs::write(&tmp_path, data).await.map_err(map_err)?;
+        tokio::fs::rename(&tmp_path, entry_path)
+            .await
+            .map_err(map_err)?;
+
+        let mut evicted = Vec::new();
+        {
+            let mut state = self.state.lock().unwrap();
+            // Ranges of other versions of the file can no longer be read
+            evicted.extend(
+                state
+                    .entries
+                    .keys()
+                    .filter(|k| k.relative_path == key.relative_path && k.version != key.version)
+                    .cloned()
+                    .collect::<Vec<_>>(),
+            );
+            for stale in evicted.iter() {
+                state.remove(stale);
+            }
+            state.insert(key, data.len() as u64);
+            while state.used_bytes > self.max_bytes {
+                match state.pop_lru() {
+                    Some(key) => evicted.push(key),
+                    None => break,
+                }
+            }
+        }
+        for key in evicted {
+            let _ = tokio::fs::remove_file(self.entry_path(&key)).await;
+        }
+        Ok(())
+    }
+
+    /// Drop all cached ranges of files under `relative_path`, which can be a file or a
+    /// directory.
+    pub(crate) async fn invalidate(&self, relative_path: &str) {
+        let relative_path = relative_path.trim_end_matches('/');
+        let dir_prefix = format!("{relative_path}/");
+        {
+            let mut state = self.state.lock().unwrap();
+            let keys = state
+                .entries
+                .keys()
+                .filter(|key| {
+                    key.relative_path == relative_path || key.relative_path.starts_with(&dir_prefix)
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            for key in keys {
+                state.remove(&key);
+            }
+        }
+        let _ = tokio::fs::remove_dir_all(self.file_dir(relative_path)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn load(len: usize) -> ILResult<Bytes> {
+        Ok(Bytes::from(vec![1u8; len]))
+    }
+
+    #[tokio::test]
+    async fn test_disk_cache_lru_eviction() -> ILResult<()> {
+        let cache_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let cache = DiskCache::try_new(&cache_dir, 25)?;
+
+        cache.get_or_load("a", "v1", 0..10, || load(10)).await?;
+        cache.get_or_load("b", "v1", 0..10, || load(10)).await?;
+        // a becomes the most recently used entry
+        cache.get_or_load("a", "v1", 0..10, || load(10)).await?;
+        cache.get_or_load("c", "v1", 0..10, || load(10)).await?;
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 3);
+        assert_eq!(stats.entries, 2);
+        assert_eq!(stats.used_bytes, 20);
+
+        cache.get_or_load("a", "v1", 0..10, || load(10)).await?;
+        cache.get_or_load("b", "v1", 0..10, || load(10)).await?;
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 2);
+        assert_eq!(stats.misses, 4);
+
+        cache.invalidate("a").await;
+        assert!(!cache_dir.join(CACHE_SUBDIR).join("a").exists());
+        cache.get_or_load("a", "v1", 0..10, || load(10)).await?;
+        assert_eq!(cache.stats().misses, 5);
+
+        std::fs::remove_dir_all(cache_dir).unwrap();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_disk_cache_new_version() -> ILResult<()> {
+        let cache_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let cache = DiskCache::try_new(&cache_dir, 100)?;
+
+        cache.get_or_load("a", "v1", 0..10, || load(10)).await?;
+        cache.get_or_load("a", "v1", 0..10, || load(10)).await?;
+        let data = cache.get_or_load("a", "v2", 0..10, || load(5)).await?;
+        assert_eq!(data.len(), 5);
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 2);
+        assert_eq!(stats.entries, 1);
+        assert_eq!(stats.used_bytes, 5);
+
+        std::fs::remove_dir_all(cache_dir).unwrap();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_disk_cache_keeps_other_content() -> ILResult<()> {
+        let cache_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&cache_dir).unwrap();
+        std::fs::write(cache_dir.join("user_file"), b"data").unwrap();
+
+        let cache = DiskCache::try_new(&cache_dir, 100)?;
+        cache.get_or_load("a", "v1", 0..10, || load(10)).await?;
+        drop(cache);
+
+        // ranges of the previous cache are cleared, the content of the caller is kept
+        let cache = DiskCache::try_new(&cache_dir, 100)?;
+        assert!(!cache_dir.join(CACHE_SUBDIR).join("a").exists());
+        assert_eq!(std::fs::read(cache_dir.join("user_file")).unwrap(), b"data");
+        assert_eq!(cache.stats().entries, 0);
+
+        std::fs::remove_dir_all(cache_dir).unwrap();
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReadStats {
+    /// Number of times a file was opened for reading.
+    pub files_opened: u64,
+    /// Distinct relative paths opened for reading.
+    pub opened_paths: BTreeSet<String>,
+    /// Number of reads sent to the backing storage, reads served by a disk cache are not
+    /// counted.
+    pub read_requests: u64,
+    /// Bytes returned by the reads sent to the backing storage.
+    pub bytes_read: u64,
+    /// Row groups of data files decoded by scans, row groups pruned by their statistics are
+    /// not counted.
+    pub row_groups_read: u64,
+}
+
+/// Counts the files opened for reading and the reads sent through a storage.
+#[derive(Debug, Default)]
+pub struct ReadCounter {
+    opened: Mutex<BTreeMap<String, u64>>,
+    requests: AtomicU64,
+    bytes: AtomicU64,
+    row_groups: AtomicU64,
+}
+
+impl ReadCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn record_open(&self, relative_path: &str) {
+        let mut opened = self.opened.lock().unwrap();
+        *opened.entry(relative_path.to_string()).or_default() += 1;
+    }
+
+    pub(crate) fn record_request(&self, bytes: u64) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_row_groups(&self, count: u64) {
+        self.row_groups.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> ReadStats {
+        let opened = self.opened.lock().unwrap();
+        ReadStats {
+            files_opened: opened.values().sum(),
+            opened_paths: opened.keys().cloned().collect(),
+            read_requests: self.requests.load(Ordering::Relaxed),
+            bytes_read: self.bytes.load(Ordering::Relaxed),
+            row_groups_read: self.row_groups.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn reset(&self) {
+        self.opened.lock().unwrap().clear();
+        self.requests.store(0, Ordering::Relaxed);
+        self.bytes.store(0, Ordering::Relaxed);
+        self.row_groups.store(0, Ordering::Relaxed);
+    }
+}
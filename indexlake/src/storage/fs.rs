@@ -2,7 +2,7 @@ use std::path::PathBuf;
 
 use opendal::{Operator, services::FsConfig};
 
-use crate::ILResult;
+use crate::{ILError, ILResult};
 
 #[derive(Debug, Clone)]
 pub struct FsStorage {
@@ -19,4 +19,16 @@ impl FsStorage {
         cfg.root = Some(self.root.to_string_lossy().to_string());
         Ok(Operator::from_config(cfg)?.finish())
     }
+
+    /// `file://` URL of the file at `relative_path`, with a relative root resolved against the
+    /// current directory.
+    pub fn file_url(&self, relative_path: &str) -> ILResult<String> {
+        let path = std::path::absolute(self.root.join(relative_path)).map_err(|e| {
+            ILError::StorageError(format!("Failed to resolve path {relative_path}: {e}"))
+        })?;
+        let url = url::Url::from_file_path(&path).map_err(|_| {
+            ILError::StorageError(format!("Failed to build file URL of {}", path.display()))
+        })?;
+        Ok(url.to_string())
+    }
 }
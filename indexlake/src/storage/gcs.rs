@@ -0,0 +1,80 @@
+use opendal::{Configurator, Operator, services::GcsConfig};
+
+use crate::{ILError, ILResult};
+
+#[derive(Debug, Clone)]
+pub struct GcsStorage {
+    config: GcsConfig,
+    bucket: String,
+}
+
+impl GcsStorage {
+    pub fn try_new(config: GcsConfig, bucket: String) -> ILResult<Self> {
+        check_credentials(&config)?;
+        Ok(Self { config, bucket })
+    }
+
+    pub fn new_operator(&self) -> ILResult<Operator> {
+        let builder = self.config.clone().into_builder().bucket(&self.bucket);
+        Ok(Operator::new(builder)?.finish())
+    }
+}
+
+/// Opendal only resolves GCS credentials when the first request is signed, so a config
+/// without any source of credentials would build fine and fail on first IO.
+fn check_credentials(config: &GcsConfig) -> ILResult<()> {
+    if let Some(path) = &config.credential_path {
+        if !std::path::Path::new(path).is_file() {
+            return Err(ILError::InvalidInput(format!(
+                "GCS credential file {path} does not exist"
+            )));
+        }
+    }
+    let has_credentials = config.token.is_some()
+        || config.credential.is_some()
+        || config.credential_path.is_some()
+        || config.allow_anonymous
+        // Workload identity, the token is fetched from the VM metadata server
+        || !config.disable_vm_metadata
+        // GOOGLE_APPLICATION_CREDENTIALS or the gcloud default credentials file
+        || !config.disable_config_load;
+    if has_credentials {
+        Ok(())
+    } else {
+        Err(ILError::InvalidInput(
+            "GCS config has no credentials, set token, credential, credential_path or \
+             allow_anonymous, or enable VM metadata or config load"
+                .to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_without_credentials() -> GcsConfig {
+        let mut config = GcsConfig::default();
+        config.disable_vm_metadata = true;
+        config.disable_config_load = true;
+        config
+    }
+
+    #[test]
+    fn test_check_credentials() {
+        assert!(check_credentials(&config_without_credentials()).is_err());
+        assert!(check_credentials(&GcsConfig::default()).is_ok());
+
+        let mut config = config_without_credentials();
+        config.credential = Some("e30=".to_string());
+        assert!(check_credentials(&config).is_ok());
+
+        let mut config = config_without_credentials();
+        config.allow_anonymous = true;
+        assert!(check_credentials(&config).is_ok());
+
+        let mut config = config_without_credentials();
+        config.credential_path = Some("/nonexistent/indexlake/credential.json".to_string());
+        assert!(check_credentials(&config).is_err());
+    }
+}
@@ -0,0 +1,269 @@
+use std::sync::Arc;
+
+use opendal::raw::{
+    Access, Layer, LayeredAccess, OpDelete, OpList, OpRead, OpStat, OpWrite, RpDelete, RpList,
+    RpRead, RpStat, RpWrite, oio,
+};
+use opendal::{Buffer, Metadata, Result};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::{ILError, ILResult};
+
+/// Limits of the requests a storage sends at once, see [`Storage::with_concurrency_limits`].
+/// The limits are shared by every table using the storage. `None` leaves requests unlimited.
+///
+/// Reads hold their permit until the response body is consumed, stats and each page of a
+/// list hold it for the request. Writes hold a permit per part uploaded rather than for the
+/// whole file, so open files never wait on each other.
+///
+/// [`Storage::with_concurrency_limits`]: crate::storage::Storage::with_concurrency_limits
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StorageOptions {
+    /// Requests of any kind in flight at once.
+    pub max_concurrent_requests: Option<usize>,
+    /// Reads, stats and lists in flight at once.
+    pub max_concurrent_reads: Option<usize>,
+    /// Writes and deletes in flight at once.
+    pub max_concurrent_writes: Option<usize>,
+}
+
+/// Semaphores of the [`StorageOptions`] of a storage.
+#[derive(Debug)]
+pub(crate) struct StorageLimiter {
+    requests: Option<Arc<Semaphore>>,
+    reads: Option<Arc<Semaphore>>,
+    writes: Option<Arc<Semaphore>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum RequestKind {
+    Read,
+    Write,
+}
+
+impl StorageLimiter {
+    pub(crate) fn try_new(options: &StorageOptions) -> ILResult<Self> {
+        let semaphore = |name: &str, limit: Option<usize>| match limit {
+            Some(0) => Err(ILError::InvalidInput(format!(
+                "Storage option {name} must be greater than 0"
+            ))),
+            Some(limit) => Ok(Some(Arc::new(Semaphore::new(limit)))),
+            None => Ok(None),
+        };
+        Ok(Self {
+            requests: semaphore("max_concurrent_requests", options.max_concurrent_requests)?,
+            reads: semaphore("max_concurrent_reads", options.max_concurrent_reads)?,
+            writes: semaphore("max_concurrent_writes", options.max_concurrent_writes)?,
+        })
+    }
+
+    pub(crate) fn layer(self: &Arc<Self>) -> ConcurrencyLimitLayer {
+        ConcurrencyLimitLayer {
+            limiter: self.clone(),
+        }
+    }
+
+    /// Waits for the permits of a request, the one of its kind first and then the global one.
+    async fn acquire(&self, kind: RequestKind) -> Permits {
+        let kind_semaphore = match kind {
+            RequestKind::Read => &self.reads,
+            RequestKind::Write => &self.writes,
+        };
+        let mut permits = Vec::with_capacity(2);
+        for semaphore in [kind_semaphore, &self.requests].into_iter().flatten() {
+            let permit = semaphore
+                .clone()
+                .acquire_owned()
+                .await
+                .expect("storage semaphores are never closed");
+            permits.push(permit);
+        }
+        permits
+    }
+}
+
+/// Permits of a request in flight, released on drop.
+type Permits = Vec<OwnedSemaphorePermit>;
+
+pub(crate) struct ConcurrencyLimitLayer {
+    limiter: Arc<StorageLimiter>,
+}
+
+impl<A: Access> Layer<A> for ConcurrencyLimitLayer {
+    type LayeredAccess = ConcurrencyLimitAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccess {
+        ConcurrencyLimitAccessor {
+            inner,
+            limiter: self.limiter.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct ConcurrencyLimitAccessor<A> {
+    inner: A,
+    limiter: Arc<StorageLimiter>,
+}
+
+impl<A: Access> LayeredAccess for ConcurrencyLimitAccessor<A> {
+    type Inner = A;
+    type Reader = LimitedReader<A::Reader>;
+    type BlockingReader = A::BlockingReader;
+    type Writer = LimitedWriter<A::Writer>;
+    type BlockingWriter = A::BlockingWriter;
+    type Lister = LimitedLister<A::Lister>;
+    type BlockingLister = A::BlockingLister;
+    type Deleter = LimitedDeleter<A::Deleter>;
+    type BlockingDeleter = A::BlockingDeleter;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let permits = self.limiter.acquire(RequestKind::Read).await;
+        let (rp, inner) = self.inner.read(path, args).await?;
+        Ok((
+            rp,
+            LimitedReader {
+                inner,
+                _permits: permits,
+            },
+        ))
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let (rp, inner) = {
+            let _permits = self.limiter.acquire(RequestKind::Write).await;
+            self.inner.write(path, args).await?
+        };
+        Ok((
+            rp,
+            LimitedWriter {
+                inner,
+                limiter: self.limiter.clone(),
+            },
+        ))
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let _permits = self.limiter.acquire(RequestKind::Read).await;
+        self.inner.stat(path, args).await
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Lister)> {
+        let (rp, inner) = self.inner.list(path, args).await?;
+        Ok((
+            rp,
+            LimitedLister {
+                inner,
+                limiter: self.limiter.clone(),
+            },
+        ))
+    }
+
+    async fn delete(&self) -> Result<(RpDelete, Self::Deleter)> {
+        let (rp, inner) = self.inner.delete().await?;
+        Ok((
+            rp,
+            LimitedDeleter {
+                inner,
+                limiter: self.limiter.clone(),
+            },
+        ))
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.inner.blocking_read(path, args)
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.inner.blocking_write(path, args)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingLister)> {
+        self.inner.blocking_list(path, args)
+    }
+
+    fn blocking_delete(&self) -> Result<(RpDelete, Self::BlockingDeleter)> {
+        self.inner.blocking_delete()
+    }
+}
+
+pub(crate) struct LimitedReader<R> {
+    inner: R,
+    _permits: Permits,
+}
+
+impl<R: oio::Read> oio::Read for LimitedReader<R> {
+    async fn read(&mut self) -> Result<Buffer> {
+        self.inner.read().await
+    }
+}
+
+pub(crate) struct LimitedWriter<W> {
+    inner: W,
+    limiter: Arc<StorageLimiter>,
+}
+
+impl<W: oio::Write> oio::Write for LimitedWriter<W> {
+    async fn write(&mut self, bs: Buffer) -> Result<()> {
+        let _permits = self.limiter.acquire(RequestKind::Write).await;
+        self.inner.write(bs).await
+    }
+
+    async fn close(&mut self) -> Result<Metadata> {
+        let _permits = self.limiter.acquire(RequestKind::Write).await;
+        self.inner.close().await
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        let _permits = self.limiter.acquire(RequestKind::Write).await;
+        self.inner.abort().await
+    }
+}
+
+pub(crate) struct LimitedLister<L> {
+    inner: L,
+    limiter: Arc<StorageLimiter>,
+}
+
+impl<L: oio::List> oio::List for LimitedLister<L> {
+    async fn next(&mut self) -> Result<Option<oio::Entry>> {
+        let _permits = self.limiter.acquire(RequestKind::Read).await;
+        self.inner.next().await
+    }
+}
+
+pub(crate) struct LimitedDeleter<D> {
+    inner: D,
+    limiter: Arc<StorageLimiter>,
+}
+
+impl<D: oio::Delete> oio::Delete for LimitedDeleter<D> {
+    fn delete(&mut self, path: &str, args: OpDelete) -> Result<()> {
+        // Only queues the path, the request is sent by flush
+        self.inner.delete(path, args)
+    }
+
+    async fn flush(&mut self) -> Result<usize> {
+        let _permits = self.limiter.acquire(RequestKind::Write).await;
+        self.inner.flush().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zero_limit_is_invalid() {
+        assert!(StorageLimiter::try_new(&StorageOptions::default()).is_ok());
+        let options = StorageOptions {
+            max_concurrent_reads: Some(0),
+            ..Default::default()
+        };
+        assert!(StorageLimiter::try_new(&options).is_err());
+    }
+}
@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use opendal::{Operator, services::MemoryConfig};
+
+use crate::ILResult;
+
+/// Storage keeping files in process memory. Every instance has its own files, clones share
+/// them.
+#[derive(Debug, Clone)]
+pub struct MemoryStorage {
+    op: Operator,
+    // The memory service does not record modification times, files are stamped on creation
+    created: Arc<Mutex<HashMap<String, SystemTime>>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        let op = Operator::from_config(MemoryConfig::default())
+            .expect("failed to build memory operator")
+            .finish();
+        Self {
+            op,
+            created: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn new_operator(&self) -> ILResult<Operator> {
+        Ok(self.op.clone())
+    }
+
+    pub(crate) fn record_created(&self, relative_path: &str) {
+        self.created
+            .lock()
+            .expect("memory storage lock poisoned")
+            .insert(relative_path.to_string(), SystemTime::now());
+    }
+
+    pub(crate) fn created_at(&self, relative_path: &str) -> Option<SystemTime> {
+        self.created
+            .lock()
+            .expect("memory storage lock poisoned")
+            .get(relative_path)
+            .copied()
+    }
+}
+
+impl Default for MemoryStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,24 +1,159 @@
+mod azblob;
+mod cache;
+mod counter;
 mod fs;
+mod gcs;
+mod limit;
+mod memory;
+mod oss;
 mod parquet;
+mod retry;
 mod s3;
 
+pub use azblob::*;
+pub use cache::*;
+pub use counter::*;
 pub use fs::*;
+pub use gcs::*;
+pub use limit::*;
+pub use memory::*;
+pub use oss::*;
 pub use parquet::*;
+pub use retry::*;
 pub use s3::*;
 
-use std::path::PathBuf;
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 
-use opendal::{Operator, services::S3Config};
+use opendal::{
+    Operator,
+    services::{AzblobConfig, GcsConfig, OssConfig, S3Config},
+};
 
 use crate::{
     ILError, ILResult,
     storage::{fs::FsStorage, s3::S3Storage},
 };
 
+/// Part size of files created with [`Storage::create_file`], above the 5 MiB minimum part size
+/// of S3 multipart uploads.
+pub const DEFAULT_WRITE_PART_SIZE: usize = 8 * 1024 * 1024;
+
 #[derive(Debug, Clone)]
 pub enum Storage {
     Fs(FsStorage),
     S3(S3Storage),
+    Gcs(GcsStorage),
+    Azblob(AzblobStorage),
+    Oss(OssStorage),
+    Memory(MemoryStorage),
+    Cached(CachingStorage),
+    Counted(CountingStorage),
+    Retrying(RetryingStorage),
+    Layered(LayeredStorage),
+    Limited(LimitingStorage),
+}
+
+/// Storage decorator that serves data file reads through a local [`DiskCache`].
+///
+/// Cached byte ranges are keyed by the etag of the file (or its modification time and size
+/// where the backend has no etag), so a file overwritten by another writer is read again.
+/// Files written or deleted through this storage are dropped from the cache right away.
+#[derive(Debug, Clone)]
+pub struct CachingStorage {
+    inner: Box<Storage>,
+    cache: Arc<DiskCache>,
+}
+
+impl CachingStorage {
+    pub fn inner(&self) -> &Storage {
+        &self.inner
+    }
+
+    pub fn cache(&self) -> &Arc<DiskCache> {
+        &self.cache
+    }
+}
+
+/// Storage decorator that counts the files opened for reading, so tests and benchmarks can
+/// check which data files a scan touched.
+#[derive(Debug, Clone)]
+pub struct CountingStorage {
+    inner: Box<Storage>,
+    counter: Arc<ReadCounter>,
+}
+
+impl CountingStorage {
+    pub fn inner(&self) -> &Storage {
+        &self.inner
+    }
+
+    pub fn counter(&self) -> &Arc<ReadCounter> {
+        &self.counter
+    }
+}
+
+/// Storage decorator replacing the default [`StorageRetryPolicy`] of its inner storage.
+#[derive(Debug, Clone)]
+pub struct RetryingStorage {
+    inner: Box<Storage>,
+    policy: StorageRetryPolicy,
+}
+
+impl RetryingStorage {
+    pub fn inner(&self) -> &Storage {
+        &self.inner
+    }
+
+    pub fn policy(&self) -> &StorageRetryPolicy {
+        &self.policy
+    }
+}
+
+/// Storage decorator bounding the requests its inner storage sends at once, see
+/// [`StorageOptions`]. Clones share the limits.
+#[derive(Debug, Clone)]
+pub struct LimitingStorage {
+    inner: Box<Storage>,
+    options: StorageOptions,
+    limiter: Arc<StorageLimiter>,
+}
+
+impl LimitingStorage {
+    pub fn inner(&self) -> &Storage {
+        &self.inner
+    }
+
+    pub fn options(&self) -> &StorageOptions {
+        &self.options
+    }
+}
+
+type OperatorLayer = Arc<dyn Fn(Operator) -> Operator + Send + Sync>;
+
+/// Storage decorator adding an opendal layer to the operators of its inner storage, below
+/// the retry layer. Used for metrics, tracing or fault injection in tests.
+#[derive(Clone)]
+pub struct LayeredStorage {
+    inner: Box<Storage>,
+    layer: OperatorLayer,
+}
+
+impl LayeredStorage {
+    pub fn inner(&self) -> &Storage {
+        &self.inner
+    }
+}
+
+impl std::fmt::Debug for LayeredStorage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LayeredStorage")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Storage {
@@ -26,13 +161,210 @@ impl Storage {
         Storage::Fs(FsStorage::new(root.into()))
     }
 
+    /// Storage in process memory, dropped with the last clone of the storage. Each call
+    /// returns a storage with no files, separate from the others. Paired with the in-memory
+    /// catalog it gives ephemeral tables that never touch the disk.
+    pub fn new_memory() -> Self {
+        Storage::Memory(MemoryStorage::new())
+    }
+
     pub fn new_s3(config: S3Config, bucket: impl Into<String>) -> Self {
         Storage::S3(S3Storage::new(config, bucket.into()))
     }
 
+    /// S3 storage sending server-side encryption headers with every write. Without it,
+    /// objects are encrypted according to the bucket default encryption.
+    pub fn new_s3_with_encryption(
+        config: S3Config,
+        bucket: impl Into<String>,
+        encryption: S3ServerSideEncryption,
+    ) -> Self {
+        Storage::S3(S3Storage::new(config, bucket.into()).with_encryption(encryption))
+    }
+
+    /// S3 storage with a local read-through cache of data file byte ranges, see
+    /// [`Storage::with_disk_cache`]. Repeated scans of the same files are served from local
+    /// disk, only a HEAD request per opened file checks that the cached ranges are current.
+    pub fn new_s3_with_cache(
+        config: S3Config,
+        bucket: impl Into<String>,
+        cache: CacheConfig,
+    ) -> ILResult<Self> {
+        Storage::with_disk_cache(Storage::new_s3(config, bucket), cache.dir, cache.max_bytes)
+    }
+
+    /// Google Cloud Storage. Credentials are taken from `config.credential` (base64 encoded
+    /// service account JSON) or `config.credential_path`. Without them, the token is fetched
+    /// from the VM metadata server (workload identity) unless `disable_vm_metadata` is set.
+    /// Set `disable_config_load` to ignore `GOOGLE_APPLICATION_CREDENTIALS` and friends.
+    /// Fails if none of these sources of credentials is left.
+    pub fn new_gcs(config: GcsConfig, bucket: impl Into<String>) -> ILResult<Self> {
+        Ok(Storage::Gcs(GcsStorage::try_new(config, bucket.into())?))
+    }
+
+    /// Azure Blob Storage. Authenticates with `config.account_key` or `config.sas_token`, set
+    /// `config.endpoint` to point at Azurite. Data files are laid out with `/` separated
+    /// virtual directories within the container, same as the other backends.
+    pub fn new_azblob(config: AzblobConfig, container: impl Into<String>) -> Self {
+        Storage::Azblob(AzblobStorage::new(config, container.into()))
+    }
+
+    /// Aliyun OSS. `config.endpoint` is the endpoint of the bucket region, e.g.
+    /// `https://oss-cn-hangzhou.aliyuncs.com`, requests go to the virtual-hosted
+    /// `<bucket>.<endpoint host>`. Credentials are taken from `config.access_key_id` and
+    /// `config.access_key_secret`, or from the `ALIBABA_CLOUD_*` environment variables.
+    /// Fails if the endpoint is missing or the bucket name is invalid.
+    pub fn new_oss(config: OssConfig, bucket: impl Into<String>) -> ILResult<Self> {
+        Ok(Storage::Oss(OssStorage::try_new(config, bucket.into())?))
+    }
+
+    /// Cloudflare R2 through its S3 compatible API. `config.endpoint` must be set to the
+    /// account endpoint `https://<account id>.r2.cloudflarestorage.com`, the region defaults
+    /// to `auto` and requests are path style. Conditional writes are disabled, and configs
+    /// asking for SSE-S3 / SSE-KMS, requester pays or storage classes other than `STANDARD`
+    /// and `STANDARD_IA` are rejected since R2 does not support them.
+    pub fn new_r2(config: S3Config, bucket: impl Into<String>) -> ILResult<Self> {
+        Ok(Storage::new_s3(r2_config(config)?, bucket))
+    }
+
+    /// Wraps `inner` with a read-through cache of data file byte ranges kept in the
+    /// `indexlake-cache` subdirectory of `cache_dir`, evicting least recently used ranges once
+    /// more than `max_bytes` are cached. The subdirectory is cleared on creation, so `cache_dir`
+    /// must not be shared with other caching processes.
+    pub fn with_disk_cache(
+        inner: Storage,
+        cache_dir: impl Into<PathBuf>,
+        max_bytes: u64,
+    ) -> ILResult<Self> {
+        Ok(Storage::Cached(CachingStorage {
+            inner: Box::new(inner),
+            cache: Arc::new(DiskCache::try_new(cache_dir, max_bytes)?),
+        }))
+    }
+
+    /// Returns the disk cache counters if the storage is wrapped with a cache.
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.disk_cache().map(|cache| cache.stats())
+    }
+
+    /// Wraps `inner` to count the files opened for reading, see [`Storage::read_stats`].
+    pub fn with_read_counter(inner: Storage) -> Self {
+        Storage::Counted(CountingStorage {
+            inner: Box::new(inner),
+            counter: Arc::new(ReadCounter::new()),
+        })
+    }
+
+    /// Wraps `inner` to retry its IO with `policy` instead of the default policy, use
+    /// [`StorageRetryPolicy::disabled`] to fail on the first error.
+    pub fn with_retry_policy(inner: Storage, policy: StorageRetryPolicy) -> Self {
+        Storage::Retrying(RetryingStorage {
+            inner: Box::new(inner),
+            policy,
+        })
+    }
+
+    /// Wraps `inner` to apply `layer` to every operator it builds. Failures injected by the
+    /// layer are retried like failures of the service.
+    pub fn with_layer(
+        inner: Storage,
+        layer: impl Fn(Operator) -> Operator + Send + Sync + 'static,
+    ) -> Self {
+        Storage::Layered(LayeredStorage {
+            inner: Box::new(inner),
+            layer: Arc::new(layer),
+        })
+    }
+
+    /// Wraps `inner` to bound the requests in flight with `options`. Every read and write
+    /// path of the storage waits for a permit, shared by all tables using the returned
+    /// storage. Fails if a limit is 0.
+    pub fn with_concurrency_limits(inner: Storage, options: StorageOptions) -> ILResult<Self> {
+        let limiter = Arc::new(StorageLimiter::try_new(&options)?);
+        Ok(Storage::Limited(LimitingStorage {
+            inner: Box::new(inner),
+            options,
+            limiter,
+        }))
+    }
+
+    /// Returns the retry policy applied to the IO of the storage.
+    pub fn retry_policy(&self) -> StorageRetryPolicy {
+        match self {
+            Storage::Retrying(retrying) => retrying.policy.clone(),
+            Storage::Cached(cached) => cached.inner.retry_policy(),
+            Storage::Counted(counted) => counted.inner.retry_policy(),
+            Storage::Layered(layered) => layered.inner.retry_policy(),
+            Storage::Limited(limited) => limited.inner.retry_policy(),
+            _ => StorageRetryPolicy::default(),
+        }
+    }
+
+    /// Returns the read counters if the storage is wrapped with a read counter.
+    pub fn read_stats(&self) -> Option<ReadStats> {
+        self.read_counter().map(|counter| counter.stats())
+    }
+
+    /// Clears the read counters, does nothing if the storage has none.
+    pub fn reset_read_stats(&self) {
+        if let Some(counter) = self.read_counter() {
+            counter.reset();
+        }
+    }
+
+    fn read_counter(&self) -> Option<&Arc<ReadCounter>> {
+        match self {
+            Storage::Counted(counted) => Some(&counted.counter),
+            Storage::Cached(cached) => cached.inner.read_counter(),
+            Storage::Retrying(retrying) => retrying.inner.read_counter(),
+            Storage::Layered(layered) => layered.inner.read_counter(),
+            Storage::Limited(limited) => limited.inner.read_counter(),
+            _ => None,
+        }
+    }
+
+    fn disk_cache(&self) -> Option<&Arc<DiskCache>> {
+        match self {
+            Storage::Cached(cached) => Some(&cached.cache),
+            Storage::Counted(counted) => counted.inner.disk_cache(),
+            Storage::Retrying(retrying) => retrying.inner.disk_cache(),
+            Storage::Layered(layered) => layered.inner.disk_cache(),
+            Storage::Limited(limited) => limited.inner.disk_cache(),
+            _ => None,
+        }
+    }
+
+    fn fs(&self) -> Option<&FsStorage> {
+        match self {
+            Storage::Fs(fs) => Some(fs),
+            Storage::Cached(cached) => cached.inner.fs(),
+            Storage::Counted(counted) => counted.inner.fs(),
+            Storage::Retrying(retrying) => retrying.inner.fs(),
+            Storage::Layered(layered) => layered.inner.fs(),
+            Storage::Limited(limited) => limited.inner.fs(),
+            _ => None,
+        }
+    }
+
+    fn memory(&self) -> Option<&MemoryStorage> {
+        match self {
+            Storage::Memory(memory) => Some(memory),
+            Storage::Cached(cached) => cached.inner.memory(),
+            Storage::Counted(counted) => counted.inner.memory(),
+            Storage::Retrying(retrying) => retrying.inner.memory(),
+            Storage::Layered(layered) => layered.inner.memory(),
+            Storage::Limited(limited) => limited.inner.memory(),
+            _ => None,
+        }
+    }
+
     pub async fn delete(&self, relative_path: &str) -> ILResult<()> {
         let op = self.new_operator()?;
-        Ok(op.delete(relative_path).await?)
+        op.delete(relative_path).await?;
+        if let Some(cache) = self.disk_cache() {
+            cache.invalidate(relative_path).await;
+        }
+        Ok(())
     }
 
     pub async fn remove_dir_all(&self, relative_path: &str) -> ILResult<()> {
@@ -42,7 +374,11 @@ impl Storage {
         } else {
             format!("{relative_path}/")
         };
-        Ok(op.remove_all(&relative_path).await?)
+        op.remove_all(&relative_path).await?;
+        if let Some(cache) = self.disk_cache() {
+            cache.invalidate(&relative_path).await;
+        }
+        Ok(())
     }
 
     pub async fn exists(&self, relative_path: &str) -> ILResult<bool> {
@@ -50,49 +386,254 @@ impl Storage {
         Ok(op.exists(relative_path).await?)
     }
 
+    /// URL that clients outside the process can read the file at `relative_path` with for
+    /// `ttl`: a presigned GET URL on object stores, a `file://` URL that does not expire on
+    /// file systems. Memory storage has no such URL.
+    pub async fn presign_read(&self, relative_path: &str, ttl: Duration) -> ILResult<String> {
+        if let Some(fs) = self.fs() {
+            return fs.file_url(relative_path);
+        }
+        let op = self.new_operator()?;
+        if !op.info().full_capability().presign_read {
+            return Err(ILError::NotSupported(format!(
+                "Storage {} does not support presigned URLs",
+                op.info().scheme()
+            )));
+        }
+        let request = op.presign_read(relative_path, ttl).await?;
+        Ok(request.uri().to_string())
+    }
+
+    /// Lists the files under `prefix` and its subdirectories, an empty list if it does not
+    /// exist.
+    pub async fn list_files(&self, prefix: &str) -> ILResult<Vec<StorageFile>> {
+        let op = self.new_operator()?;
+        let prefix = if prefix.ends_with('/') {
+            prefix.to_string()
+        } else {
+            format!("{prefix}/")
+        };
+        let entries = match op.list_with(&prefix).recursive(true).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == opendal::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut files = Vec::new();
+        for entry in entries {
+            if entry.metadata().is_dir() {
+                continue;
+            }
+            // Not every service returns the modification time when listing
+            let metadata = match entry.metadata().last_modified() {
+                Some(_) => entry.metadata().clone(),
+                None => op.stat(entry.path()).await?,
+            };
+            let last_modified = match metadata.last_modified() {
+                Some(last_modified) => Some(SystemTime::from(last_modified)),
+                None => self
+                    .memory()
+                    .and_then(|memory| memory.created_at(entry.path())),
+            };
+            files.push(StorageFile {
+                relative_path: entry.path().to_string(),
+                size_bytes: metadata.content_length(),
+                last_modified,
+            });
+        }
+        Ok(files)
+    }
+
     pub(crate) fn new_operator(&self) -> ILResult<Operator> {
+        let op = self.layered_operator()?;
+        Ok(match self.retry_policy().layer() {
+            Some(layer) => op.layer(layer),
+            None => op,
+        })
+    }
+
+    fn layered_operator(&self) -> ILResult<Operator> {
         match self {
             Storage::Fs(fs) => fs.new_operator(),
             Storage::S3(s3) => s3.new_operator(),
+            Storage::Gcs(gcs) => gcs.new_operator(),
+            Storage::Azblob(azblob) => azblob.new_operator(),
+            Storage::Oss(oss) => oss.new_operator(),
+            Storage::Memory(memory) => memory.new_operator(),
+            Storage::Cached(cached) => cached.inner.layered_operator(),
+            Storage::Counted(counted) => counted.inner.layered_operator(),
+            Storage::Retrying(retrying) => retrying.inner.layered_operator(),
+            Storage::Layered(layered) => Ok((layered.layer)(layered.inner.layered_operator()?)),
+            Storage::Limited(limited) => Ok(limited
+                .inner
+                .layered_operator()?
+                .layer(limited.limiter.layer())),
         }
     }
 
     pub async fn create_file(&self, relative_path: &str) -> ILResult<OutputFile> {
+        self.create_file_with_part_size(relative_path, DEFAULT_WRITE_PART_SIZE)
+            .await
+    }
+
+    /// Creates a file written in parts of `part_size` bytes: a multipart upload on object
+    /// stores, appended chunks on file systems. Only the part being filled is buffered in
+    /// memory, so files of any size are written in bounded memory. Files not completed must be
+    /// [aborted](OutputFile::abort) so the parts already uploaded are cleaned up.
+    pub async fn create_file_with_part_size(
+        &self,
+        relative_path: &str,
+        part_size: usize,
+    ) -> ILResult<OutputFile> {
+        if part_size == 0 {
+            return Err(ILError::InvalidInput(
+                "Write part size must be greater than 0".to_string(),
+            ));
+        }
         let op = self.new_operator()?;
-        let writer = op.writer(relative_path).await?;
+        let writer = op.writer_with(relative_path).chunk(part_size).await?;
+        if let Some(memory) = self.memory() {
+            memory.record_created(relative_path);
+        }
+        if let Some(cache) = self.disk_cache() {
+            cache.invalidate(relative_path).await;
+        }
         Ok(OutputFile {
             op,
             relative_path: relative_path.to_string(),
             writer,
+            cache: self.disk_cache().cloned(),
+            checksum: 0,
         })
     }
 
+    /// Copies the file at `from` to `to`, streamed through in parts of
+    /// [`DEFAULT_WRITE_PART_SIZE`] bytes.
+    pub async fn copy_file(&self, from: &str, to: &str) -> ILResult<()> {
+        let input_file = self.open_file(from).await?;
+        let size_bytes = input_file.file_size_bytes().await?;
+        let mut output_file = self.create_file(to).await?;
+        let part_size = DEFAULT_WRITE_PART_SIZE as u64;
+        let mut offset = 0;
+        while offset < size_bytes {
+            let end = (offset + part_size).min(size_bytes);
+            let bytes = match input_file.read_range(offset..end).await {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    output_file.abort().await?;
+                    return Err(e);
+                }
+            };
+            if let Err(e) = output_file.writer.write(bytes).await {
+                output_file.abort().await?;
+                return Err(e.into());
+            }
+            offset = end;
+        }
+        output_file.writer.close().await?;
+        Ok(())
+    }
+
+    /// Computes the CRC32C of the file at `relative_path`, read in parts of
+    /// [`DEFAULT_WRITE_PART_SIZE`] bytes.
+    pub async fn checksum_file(&self, relative_path: &str) -> ILResult<u32> {
+        let input_file = self.open_file(relative_path).await?;
+        let size_bytes = input_file.file_size_bytes().await?;
+        let part_size = DEFAULT_WRITE_PART_SIZE as u64;
+        let mut checksum = 0;
+        let mut offset = 0;
+        while offset < size_bytes {
+            let end = (offset + part_size).min(size_bytes);
+            let bytes = input_file.read_range(offset..end).await?;
+            checksum = crc32c::crc32c_append(checksum, &bytes);
+            offset = end;
+        }
+        Ok(checksum)
+    }
+
     pub async fn open_file(&self, relative_path: &str) -> ILResult<InputFile> {
         let op = self.new_operator()?;
         let reader = op.reader(relative_path).await?;
+        if let Some(counter) = self.read_counter() {
+            counter.record_open(relative_path);
+        }
+        // Cached ranges are only used for the version of the file seen when opening it
+        let cache = match self.disk_cache() {
+            Some(cache) => {
+                let meta = op.stat(relative_path).await?;
+                let version = match (meta.etag(), meta.last_modified()) {
+                    (Some(etag), _) => etag.to_string(),
+                    (None, Some(last_modified)) => {
+                        format!("{last_modified}-{}", meta.content_length())
+                    }
+                    (None, None) => meta.content_length().to_string(),
+                };
+                Some(InputFileCache {
+                    cache: cache.clone(),
+                    version,
+                    size_bytes: meta.content_length(),
+                })
+            }
+            None => None,
+        };
         Ok(InputFile {
             op,
             relative_path: relative_path.to_string(),
             reader,
+            cache,
+            counter: self.read_counter().cloned(),
         })
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageFile {
+    pub relative_path: String,
+    pub size_bytes: u64,
+    pub last_modified: Option<SystemTime>,
+}
+
 /// Output file is used for writing to files.
 pub struct OutputFile {
     op: Operator,
     relative_path: String,
     writer: opendal::Writer,
+    cache: Option<Arc<DiskCache>>,
+    checksum: u32,
 }
 
 impl OutputFile {
+    /// CRC32C of the bytes written to the file through its parquet writer so far.
+    pub fn checksum(&self) -> u32 {
+        self.checksum
+    }
+
     pub async fn file_size_bytes(&self) -> ILResult<u64> {
         let meta = self.op.stat(&self.relative_path).await?;
         Ok(meta.content_length())
     }
 
     pub async fn delete(&self) -> ILResult<()> {
-        Ok(self.op.delete(&self.relative_path).await?)
+        self.op.delete(&self.relative_path).await?;
+        if let Some(cache) = &self.cache {
+            cache.invalidate(&self.relative_path).await;
+        }
+        Ok(())
+    }
+
+    /// Discards the file being written: aborts the multipart upload on object stores and
+    /// removes what was written so far. The file must not have been completed.
+    pub async fn abort(&mut self) -> ILResult<()> {
+        match self.writer.abort().await {
+            Ok(()) => {}
+            // File systems without an atomic write dir write in place and cannot abort
+            Err(e) if e.kind() == opendal::ErrorKind::Unsupported => {}
+            Err(e) => return Err(e.into()),
+        }
+        self.op.delete(&self.relative_path).await?;
+        if let Some(cache) = &self.cache {
+            cache.invalidate(&self.relative_path).await;
+        }
+        Ok(())
     }
 
     pub async fn write(&self, bytes: bytes::Bytes) -> ILResult<()> {
@@ -111,20 +652,70 @@ pub struct InputFile {
     op: Operator,
     relative_path: String,
     reader: opendal::Reader,
+    cache: Option<InputFileCache>,
+    counter: Option<Arc<ReadCounter>>,
+}
+
+/// Disk cache of the storage along with the version and size of the file when it was opened.
+struct InputFileCache {
+    cache: Arc<DiskCache>,
+    version: String,
+    size_bytes: u64,
 }
 
 impl InputFile {
     pub async fn file_size_bytes(&self) -> ILResult<u64> {
+        if let Some(cache) = &self.cache {
+            return Ok(cache.size_bytes);
+        }
         let meta = self.op.stat(&self.relative_path).await?;
         Ok(meta.content_length())
     }
 
     pub async fn delete(&self) -> ILResult<()> {
-        Ok(self.op.delete(&self.relative_path).await?)
+        self.op.delete(&self.relative_path).await?;
+        if let Some(cache) = &self.cache {
+            cache.cache.invalidate(&self.relative_path).await;
+        }
+        Ok(())
     }
 
     pub async fn read(&self) -> ILResult<bytes::Bytes> {
-        Ok(self.op.read(&self.relative_path).await?.to_bytes())
+        let bytes = self.op.read(&self.relative_path).await?.to_bytes();
+        self.record_request(&bytes);
+        Ok(bytes)
+    }
+
+    /// Reads a byte range, served from the disk cache if the storage has one.
+    pub async fn read_range(&self, range: std::ops::Range<u64>) -> ILResult<bytes::Bytes> {
+        match &self.cache {
+            Some(cache) => {
+                cache
+                    .cache
+                    .get_or_load(
+                        &self.relative_path,
+                        &cache.version,
+                        range.clone(),
+                        || async {
+                            let bytes = self.reader.read(range.clone()).await?.to_bytes();
+                            self.record_request(&bytes);
+                            Ok(bytes)
+                        },
+                    )
+                    .await
+            }
+            None => {
+                let bytes = self.reader.read(range).await?.to_bytes();
+                self.record_request(&bytes);
+                Ok(bytes)
+            }
+        }
+    }
+
+    fn record_request(&self, bytes: &bytes::Bytes) {
+        if let Some(counter) = &self.counter {
+            counter.record_request(bytes.len() as u64);
+        }
     }
 
     pub fn reader(&self) -> &opendal::Reader {
@@ -0,0 +1,76 @@
+use opendal::{Configurator, Operator, services::OssConfig};
+
+use crate::{ILError, ILResult};
+
+#[derive(Debug, Clone)]
+pub struct OssStorage {
+    config: OssConfig,
+    bucket: String,
+}
+
+impl OssStorage {
+    pub fn try_new(config: OssConfig, bucket: String) -> ILResult<Self> {
+        // Opendal has no default region for OSS, the endpoint picks it
+        if config.endpoint.as_deref().is_none_or(str::is_empty) {
+            return Err(ILError::InvalidInput(
+                "OSS config has no endpoint, set it to the endpoint of the bucket region, e.g. \
+                 https://oss-cn-hangzhou.aliyuncs.com"
+                    .to_string(),
+            ));
+        }
+        check_bucket_name(&bucket)?;
+        Ok(Self { config, bucket })
+    }
+
+    pub fn new_operator(&self) -> ILResult<Operator> {
+        let builder = self.config.clone().into_builder().bucket(&self.bucket);
+        Ok(Operator::new(builder)?.finish())
+    }
+}
+
+/// Bucket names must be 3 to 63 characters of lowercase letters, digits and hyphens, starting
+/// and ending with a letter or digit. They are part of the virtual-hosted endpoint.
+fn check_bucket_name(bucket: &str) -> ILResult<()> {
+    let valid = (3..=63).contains(&bucket.len())
+        && bucket
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !bucket.starts_with('-')
+        && !bucket.ends_with('-');
+    if valid {
+        Ok(())
+    } else {
+        Err(ILError::InvalidInput(format!(
+            "Invalid OSS bucket name: {bucket}"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_with_endpoint() -> OssConfig {
+        let mut config = OssConfig::default();
+        config.endpoint = Some("https://oss-cn-hangzhou.aliyuncs.com".to_string());
+        config
+    }
+
+    #[test]
+    fn test_check_bucket_name() {
+        assert!(check_bucket_name("indexlake").is_ok());
+        assert!(check_bucket_name("index-lake-01").is_ok());
+        assert!(check_bucket_name("ab").is_err());
+        assert!(check_bucket_name("IndexLake").is_err());
+        assert!(check_bucket_name("indexlake-").is_err());
+        assert!(check_bucket_name("index_lake").is_err());
+        assert!(check_bucket_name("index.lake").is_err());
+    }
+
+    #[test]
+    fn test_try_new_requires_endpoint() {
+        assert!(OssStorage::try_new(OssConfig::default(), "indexlake".to_string()).is_err());
+        assert!(OssStorage::try_new(config_with_endpoint(), "indexlake".to_string()).is_ok());
+        assert!(OssStorage::try_new(config_with_endpoint(), "Index_Lake".to_string()).is_err());
+    }
+}
@@ -1,14 +1,22 @@
 use std::{
     collections::{BTreeMap, HashMap},
     ops::Range,
+    pin::Pin,
     sync::Arc,
+    task::{Context, Poll},
 };
 
-use arrow::{array::RecordBatch, datatypes::SchemaRef};
-use futures::{StreamExt, TryStreamExt, future::BoxFuture};
+use arrow::{
+    array::{ArrayRef, AsArray, Int64Array, RecordBatch, RecordBatchOptions, new_null_array},
+    compute::{cast, filter_record_batch},
+    datatypes::SchemaRef,
+};
+use futures::{SinkExt, Stream, StreamExt, TryStreamExt, channel::mpsc, future::BoxFuture};
+use log::error;
 use parquet::{
     arrow::{
-        ArrowSchemaConverter, AsyncArrowWriter, ParquetRecordBatchStreamBuilder, ProjectionMask,
+        AsyncArrowWriter, PARQUET_FIELD_ID_META_KEY, ParquetRecordBatchStreamBuilder,
+        ProjectionMask,
         arrow_reader::{ArrowReaderOptions, RowFilter, RowSelection},
         async_reader::AsyncFileReader,
         async_writer::AsyncFileWriter,
@@ -18,12 +26,15 @@ use parquet::{
         properties::WriterProperties,
     },
 };
+use tokio::task::JoinHandle;
 
 use crate::{
     ILError, ILResult, RecordBatchStream,
-    catalog::RowLocation,
+    catalog::{INTERNAL_ROW_ID_FIELD_NAME, RowLocation, Scalar},
     expr::{Expr, ExprPredicate},
     storage::{InputFile, OutputFile, Storage},
+    table::{TableConfig, may_match_stats, row_group_column_stats},
+    utils::project_schema,
 };
 
 impl AsyncFileReader for InputFile {
@@ -32,12 +43,9 @@ impl AsyncFileReader for InputFile {
         range: Range<u64>,
     ) -> BoxFuture<'_, parquet::errors::Result<bytes::Bytes>> {
         Box::pin(async move {
-            let buffer = self
-                .reader
-                .read(range.start..range.end)
+            self.read_range(range)
                 .await
-                .map_err(|err| parquet::errors::ParquetError::External(Box::new(err)))?;
-            Ok(buffer.to_bytes())
+                .map_err(|err| parquet::errors::ParquetError::External(Box::new(err)))
         })
     }
 
@@ -65,6 +73,7 @@ impl AsyncFileReader for InputFile {
 
 impl AsyncFileWriter for OutputFile {
     fn write(&mut self, bs: bytes::Bytes) -> BoxFuture<'_, parquet::errors::Result<()>> {
+        self.checksum = crc32c::crc32c_append(self.checksum, &bs);
         Box::pin(async {
             self.writer
                 .write(bs)
@@ -85,82 +94,452 @@ impl AsyncFileWriter for OutputFile {
     }
 }
 
+/// Lets a parquet writer borrow the file, so the file can still be aborted if writing fails.
+impl AsyncFileWriter for &mut OutputFile {
+    fn write(&mut self, bs: bytes::Bytes) -> BoxFuture<'_, parquet::errors::Result<()>> {
+        AsyncFileWriter::write(&mut **self, bs)
+    }
+
+    fn complete(&mut self) -> BoxFuture<'_, parquet::errors::Result<()>> {
+        AsyncFileWriter::complete(&mut **self)
+    }
+}
+
+/// Writes a data file at `relative_path` with the row group size and compression of the table,
+/// `write` writing the rows with the parquet writer. Returns the output of `write`, the size of
+/// the file and its checksum. Row groups are uploaded as they are written, the upload is aborted
+/// if writing fails.
+pub(crate) async fn write_parquet_file<T>(
+    storage: &Storage,
+    config: &TableConfig,
+    relative_path: &str,
+    file_schema: SchemaRef,
+    write: impl AsyncFnOnce(&mut AsyncArrowWriter<&mut OutputFile>) -> ILResult<T>,
+) -> ILResult<(T, usize, u32)> {
+    let mut output_file = storage
+        .create_file_with_part_size(relative_path, config.data_file_part_size)
+        .await?;
+    match write_parquet(&mut output_file, config, file_schema, write).await {
+        Ok((output, file_size_bytes)) => Ok((output, file_size_bytes, output_file.checksum())),
+        Err(e) => {
+            if let Err(abort_err) = output_file.abort().await {
+                error!("Failed to abort writing data file {relative_path}: {abort_err:?}");
+            }
+            Err(e)
+        }
+    }
+}
+
+async fn write_parquet<T>(
+    output_file: &mut OutputFile,
+    config: &TableConfig,
+    file_schema: SchemaRef,
+    write: impl AsyncFnOnce(&mut AsyncArrowWriter<&mut OutputFile>) -> ILResult<T>,
+) -> ILResult<(T, usize)> {
+    let writer_properties = WriterProperties::builder()
+        .set_max_row_group_size(config.parquet_row_group_size)
+        .set_compression(config.compression.to_parquet()?)
+        .build();
+    let mut arrow_writer =
+        AsyncArrowWriter::try_new(output_file, file_schema, Some(writer_properties))?;
+    let output = write(&mut arrow_writer).await?;
+    // The footer is only counted once the writer is finished
+    arrow_writer.finish().await?;
+    Ok((output, arrow_writer.bytes_written()))
+}
+
+/// Reads the rows at the locations, each given with its row id. Row ids are taken from the
+/// files, except for files ingested in place that have no row id column.
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn read_parquet_files_by_locations(
     storage: Arc<Storage>,
     table_schema: SchemaRef,
     projection: Option<Vec<usize>>,
-    data_file_locations: Vec<RowLocation>,
+    data_file_locations: Vec<(i64, RowLocation)>,
     predicate: Option<Expr>,
+    field_defaults: &HashMap<String, Scalar>,
+    field_ids: &HashMap<String, i64>,
+    batch_size: Option<usize>,
 ) -> ILResult<RecordBatchStream> {
-    let projection_mask = match projection {
-        Some(projection) => {
-            let parquet_schema = ArrowSchemaConverter::new().convert(&table_schema)?;
-            ProjectionMask::roots(&parquet_schema, projection)
+    let projected_schema = Arc::new(project_schema(&table_schema, projection.as_ref())?);
+
+    let mut streams: Vec<RecordBatchStream> = Vec::new();
+    for (relative_path, locations) in group_locations_by_file(data_file_locations) {
+        let stream = read_parquet_file_by_locations(
+            &storage,
+            &relative_path,
+            locations,
+            &projected_schema,
+            predicate.as_ref(),
+            field_defaults,
+            field_ids,
+            batch_size,
+        )
+        .await?;
+        streams.push(stream);
+    }
+
+    Ok(Box::pin(futures::stream::select_all(streams)))
+}
+
+/// Reads the rows at the locations lazily, files are only opened once the stream gets to them.
+/// Without a concurrency, files are read one after another in the task polling the stream, so
+/// memory stays bounded by the batches of one row group. With one, that many files are read at
+/// once in tasks of their own, which are aborted when the stream is dropped. The stream ends
+/// after the first error.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn stream_parquet_files_by_locations(
+    storage: Arc<Storage>,
+    table_schema: SchemaRef,
+    projection: Option<Vec<usize>>,
+    data_file_locations: Vec<(i64, RowLocation)>,
+    predicate: Option<Expr>,
+    field_defaults: HashMap<String, Scalar>,
+    field_ids: HashMap<String, i64>,
+    batch_size: Option<usize>,
+    concurrency: Option<usize>,
+) -> ILResult<RecordBatchStream> {
+    let projected_schema = Arc::new(project_schema(&table_schema, projection.as_ref())?);
+    let mut files = group_locations_by_file(data_file_locations)
+        .into_iter()
+        .collect::<Vec<_>>();
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let read_file = move |(relative_path, locations): (String, Vec<(i64, RowLocation)>)| {
+        let storage = storage.clone();
+        let projected_schema = projected_schema.clone();
+        let predicate = predicate.clone();
+        let field_defaults = field_defaults.clone();
+        let field_ids = field_ids.clone();
+        async move {
+            read_parquet_file_by_locations(
+                &storage,
+                &relative_path,
+                locations,
+                &projected_schema,
+                predicate.as_ref(),
+                &field_defaults,
+                &field_ids,
+                batch_size,
+            )
+            .await
         }
-        None => ProjectionMask::all(),
     };
-
-    let arrow_predicate_opt = match predicate {
-        Some(expr) => Some(ExprPredicate::try_new(expr, projection_mask.clone())?),
-        None => None,
+    let stream: RecordBatchStream = match concurrency {
+        Some(concurrency) => Box::pin(
+            futures::stream::iter(files)
+                .map(move |file| FileTaskStream::spawn(read_file(file)))
+                .flatten_unordered(concurrency),
+        ),
+        None => Box::pin(futures::stream::iter(files).then(read_file).try_flatten()),
     };
+    Ok(Box::pin(stream.scan(false, |failed, item| {
+        if *failed {
+            return futures::future::ready(None);
+        }
+        *failed = item.is_err();
+        futures::future::ready(Some(item))
+    })))
+}
+
+/// Batches of a data file read by a task of its own, the task is aborted when dropped.
+struct FileTaskStream {
+    receiver: mpsc::Receiver<ILResult<RecordBatch>>,
+    handle: JoinHandle<()>,
+}
+
+impl FileTaskStream {
+    fn spawn(open: impl Future<Output = ILResult<RecordBatchStream>> + Send + 'static) -> Self {
+        // The task waits for each batch to be taken before decoding the next one
+        let (mut sender, receiver) = mpsc::channel(1);
+        let handle = tokio::spawn(async move {
+            let mut stream = match open.await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    let _ = sender.send(Err(e)).await;
+                    return;
+                }
+            };
+            while let Some(item) = stream.next().await {
+                let failed = item.is_err();
+                if sender.send(item).await.is_err() || failed {
+                    return;
+                }
+            }
+        });
+        Self { receiver, handle }
+    }
+}
 
-    let mut file_locations_map: HashMap<String, Vec<RowLocation>> = HashMap::new();
-    for location in data_file_locations {
+impl Stream for FileTaskStream {
+    type Item = ILResult<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_next_unpin(cx)
+    }
+}
+
+impl Drop for FileTaskStream {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+fn group_locations_by_file(
+    data_file_locations: Vec<(i64, RowLocation)>,
+) -> HashMap<String, Vec<(i64, RowLocation)>> {
+    let mut file_locations_map: HashMap<String, Vec<(i64, RowLocation)>> = HashMap::new();
+    for (row_id, location) in data_file_locations {
         if let RowLocation::Parquet { relative_path, .. } = &location {
             file_locations_map
                 .entry(relative_path.clone())
                 .or_default()
-                .push(location);
+                .push((row_id, location));
         }
     }
+    file_locations_map
+}
 
-    let mut streams = Vec::new();
-    for (relative_path, locations) in file_locations_map {
-        let input_file = storage.open_file(&relative_path).await?;
-        let mut arrow_reader_builder = ParquetRecordBatchStreamBuilder::new(input_file).await?;
-        let parquet_metadata = arrow_reader_builder.metadata();
-        let row_groups_metadata = parquet_metadata.row_groups();
-
-        let mut row_group_offsets_map: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
-        for location in locations {
-            if let RowLocation::Parquet {
-                row_group_index,
-                row_group_offset,
-                ..
-            } = &location
-            {
-                row_group_offsets_map
-                    .entry(*row_group_index)
-                    .or_default()
-                    .push(*row_group_offset);
-            }
+/// Reads the rows of one data file at the locations, in batches of `batch_size` rows or the
+/// default of the parquet reader.
+#[allow(clippy::too_many_arguments)]
+async fn read_parquet_file_by_locations(
+    storage: &Storage,
+    relative_path: &str,
+    locations: Vec<(i64, RowLocation)>,
+    projected_schema: &SchemaRef,
+    predicate: Option<&Expr>,
+    field_defaults: &HashMap<String, Scalar>,
+    field_ids: &HashMap<String, i64>,
+    batch_size: Option<usize>,
+) -> ILResult<RecordBatchStream> {
+    let input_file = storage.open_file(relative_path).await?;
+    let mut arrow_reader_builder = ParquetRecordBatchStreamBuilder::new(input_file).await?;
+    if let Some(batch_size) = batch_size {
+        arrow_reader_builder = arrow_reader_builder.with_batch_size(batch_size);
+    }
+    let file_schema = arrow_reader_builder.schema().clone();
+    let parquet_metadata = arrow_reader_builder.metadata();
+    let row_groups_metadata = parquet_metadata.row_groups();
+
+    let mut row_group_offsets_map: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    let mut located_row_ids = Vec::with_capacity(locations.len());
+    for (row_id, location) in locations {
+        if let RowLocation::Parquet {
+            row_group_index,
+            row_group_offset,
+            ..
+        } = location
+        {
+            row_group_offsets_map
+                .entry(row_group_index)
+                .or_default()
+                .push(row_group_offset);
+            located_row_ids.push(((row_group_index, row_group_offset), row_id));
         }
+    }
 
-        let row_groups = row_group_offsets_map.keys().copied().collect::<Vec<_>>();
+    // Files may lack columns added or still contain columns dropped after they were written
+    let file_columns = resolve_file_columns(&file_schema, projected_schema, field_ids);
 
-        let row_group_num_rows = row_groups_metadata
-            .iter()
-            .map(|rg| rg.num_rows() as usize)
-            .collect::<Vec<_>>();
-        let row_selection = build_row_selection(&row_group_num_rows, row_group_offsets_map)?;
+    // Second pruning stage after the data files: row groups whose statistics show that none of
+    // their rows can match the predicate are not decoded
+    if let Some(predicate) = predicate {
+        let row_group_stats = row_group_column_stats(
+            parquet_metadata,
+            &file_schema,
+            projected_schema,
+            &file_columns,
+        )?;
+        row_group_offsets_map.retain(|row_group_index, _| {
+            row_group_stats
+                .get(*row_group_index)
+                .zip(row_groups_metadata.get(*row_group_index))
+                .is_none_or(|(columns, row_group)| {
+                    may_match_stats(columns, row_group.num_rows() as u64, predicate)
+                })
+        });
+        located_row_ids.retain(|((row_group_index, _), _)| {
+            row_group_offsets_map.contains_key(row_group_index)
+        });
+    }
+
+    let row_groups = row_group_offsets_map.keys().copied().collect::<Vec<_>>();
+    if let Some(counter) = storage.read_counter() {
+        counter.record_row_groups(row_groups.len() as u64);
+    }
 
-        if let Some(arrow_predicate) = &arrow_predicate_opt {
+    let row_group_num_rows = row_groups_metadata
+        .iter()
+        .map(|rg| rg.num_rows() as usize)
+        .collect::<Vec<_>>();
+    let row_selection = build_row_selection(&row_group_num_rows, row_group_offsets_map)?;
+
+    let arrow_reader_builder = arrow_reader_builder
+        .with_row_groups(row_groups)
+        .with_row_selection(row_selection);
+
+    let mut file_projection = file_columns.iter().flatten().copied().collect::<Vec<_>>();
+    file_projection.sort();
+    // The reader yields the columns in file order, whatever the order of the projection
+    let batch_columns = file_columns
+        .iter()
+        .map(|file_idx| file_idx.map(|idx| file_projection.binary_search(&idx).expect("projected")))
+        .collect::<Vec<_>>();
+    let file_projection_mask =
+        ProjectionMask::roots(arrow_reader_builder.parquet_schema(), file_projection);
+    let arrow_reader_builder = arrow_reader_builder.with_projection(file_projection_mask.clone());
+    let projected_schema = projected_schema.clone();
+    let field_defaults = field_defaults.clone();
+
+    // The predicate can only be evaluated by the reader if the file has every column under
+    // its current name and type
+    let file_has_columns =
+        file_columns
+            .iter()
+            .zip(projected_schema.fields())
+            .all(|(file_idx, field)| {
+                file_idx.is_some_and(|idx| {
+                    let file_field = file_schema.field(idx);
+                    file_field.name() == field.name() && file_field.data_type() == field.data_type()
+                })
+            });
+    if file_has_columns {
+        let mut arrow_reader_builder = arrow_reader_builder;
+        if let Some(expr) = predicate {
+            let arrow_predicate = ExprPredicate::try_new(expr.clone(), file_projection_mask)?;
             arrow_reader_builder = arrow_reader_builder
-                .with_row_filter(RowFilter::new(vec![Box::new(arrow_predicate.clone())]));
+                .with_row_filter(RowFilter::new(vec![Box::new(arrow_predicate)]));
         }
-
         let stream = arrow_reader_builder
-            .with_row_groups(row_groups)
-            .with_row_selection(row_selection)
-            .with_projection(projection_mask.clone())
             .build()?
-            .map_err(ILError::from);
+            .map_err(ILError::from)
+            .and_then(move |batch| {
+                futures::future::ready(project_file_batch(
+                    &batch,
+                    &projected_schema,
+                    &batch_columns,
+                    &field_defaults,
+                    None,
+                ))
+            });
+        Ok(Box::pin(stream))
+    } else {
+        // Fill in the missing columns and rename the renamed ones before applying the
+        // predicate. Files ingested in place lack the row id column, the row ids of the
+        // selected rows are filled in following the order the reader yields them in
+        located_row_ids.sort();
+        let row_ids = located_row_ids
+            .into_iter()
+            .map(|(_, row_id)| row_id)
+            .collect::<Vec<_>>();
+        let mut read_rows = 0;
+        let predicate = predicate.cloned();
+        let stream = arrow_reader_builder
+            .build()?
+            .map_err(ILError::from)
+            .and_then(move |batch| {
+                let num_rows = batch.num_rows();
+                let batch = row_ids
+                    .get(read_rows..read_rows + num_rows)
+                    .ok_or_else(|| {
+                        ILError::InternalError(format!(
+                            "Read more rows than the {} locations of the data file",
+                            row_ids.len()
+                        ))
+                    })
+                    .and_then(|batch_row_ids| {
+                        project_file_batch(
+                            &batch,
+                            &projected_schema,
+                            &batch_columns,
+                            &field_defaults,
+                            Some(batch_row_ids),
+                        )
+                    })
+                    .and_then(|batch| match &predicate {
+                        Some(predicate) => filter_record_batch_by_expr(&batch, predicate),
+                        None => Ok(batch),
+                    });
+                read_rows += num_rows;
+                futures::future::ready(batch)
+            });
+        Ok(Box::pin(stream))
+    }
+}
 
-        streams.push(stream);
+/// Index in the file schema of the column of each projected field, `None` for columns the
+/// file lacks. Files written with field ids are matched by id so renamed columns resolve to
+/// their own data, files written before field ids were kept by name.
+fn resolve_file_columns(
+    file_schema: &SchemaRef,
+    projected_schema: &SchemaRef,
+    field_ids: &HashMap<String, i64>,
+) -> Vec<Option<usize>> {
+    let file_field_ids = file_schema
+        .fields()
+        .iter()
+        .map(|field| field.metadata().get(PARQUET_FIELD_ID_META_KEY))
+        .collect::<Vec<_>>();
+    let has_field_ids = file_field_ids.iter().any(Option::is_some);
+    projected_schema
+        .fields()
+        .iter()
+        .map(|field| match field_ids.get(field.name()) {
+            Some(field_id) if has_field_ids => {
+                let field_id = field_id.to_string();
+                file_field_ids.iter().position(|id| id == &Some(&field_id))
+            }
+            _ => file_schema.index_of(field.name()).ok(),
+        })
+        .collect()
+}
+
+/// Builds a batch of `schema` from the columns of `batch` read from a file, the column of each
+/// field being at its index in `batch_columns`. Columns of files added with a narrower type
+/// are cast, columns the file lacks are filled with their default value or nulls, the row id
+/// column with `row_ids` when given.
+fn project_file_batch(
+    batch: &RecordBatch,
+    schema: &SchemaRef,
+    batch_columns: &[Option<usize>],
+    field_defaults: &HashMap<String, Scalar>,
+    row_ids: Option<&[i64]>,
+) -> ILResult<RecordBatch> {
+    let mut columns = Vec::with_capacity(schema.fields().len());
+    for (field, batch_idx) in schema.fields().iter().zip(batch_columns) {
+        let column = match (batch_idx, row_ids) {
+            (Some(idx), _) if batch.column(*idx).data_type() != field.data_type() => {
+                cast(batch.column(*idx), field.data_type())?
+            }
+            (Some(idx), _) => batch.column(*idx).clone(),
+            (None, Some(row_ids)) if field.name() == INTERNAL_ROW_ID_FIELD_NAME => {
+                Arc::new(Int64Array::from(row_ids.to_vec())) as ArrayRef
+            }
+            (None, _) => match field_defaults.get(field.name()) {
+                Some(default) => default.to_array_of_size(batch.num_rows())?,
+                None => new_null_array(field.data_type(), batch.num_rows()),
+            },
+        };
+        columns.push(column);
     }
+    let options = RecordBatchOptions::new().with_row_count(Some(batch.num_rows()));
+    Ok(RecordBatch::try_new_with_options(
+        schema.clone(),
+        columns,
+        &options,
+    )?)
+}
 
-    Ok(Box::pin(futures::stream::select_all(streams)))
+fn filter_record_batch_by_expr(batch: &RecordBatch, predicate: &Expr) -> ILResult<RecordBatch> {
+    let array = predicate.eval(batch)?.into_array(batch.num_rows())?;
+    let bool_array = array.as_boolean_opt().ok_or_else(|| {
+        ILError::InternalError(format!(
+            "predicate should return BooleanArray, but got {:?}",
+            array.data_type()
+        ))
+    })?;
+    Ok(filter_record_batch(batch, bool_array)?)
 }
 
 fn build_row_selection(
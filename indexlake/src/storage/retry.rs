@@ -0,0 +1,91 @@
+use std::time::Duration;
+
+use log::warn;
+use opendal::layers::{RetryInterceptor, RetryLayer};
+
+/// Exponential backoff policy of storage IO, applied to reads, writes, stats, lists and
+/// deletes of every storage unless replaced with [`Storage::with_retry_policy`].
+///
+/// Errors are classified by opendal from the service response. Server errors (5xx), rate
+/// limiting (429), timeouts and connection resets are temporary and retried. All others are
+/// permanent and returned right away, such as `NotFound`, `PermissionDenied` (403),
+/// `ConfigInvalid` or `Unsupported`.
+///
+/// [`Storage::with_retry_policy`]: crate::storage::Storage::with_retry_policy
+#[derive(Debug, Clone, PartialEq, derive_with::With)]
+pub struct StorageRetryPolicy {
+    /// Total attempts including the first one, 1 disables retries.
+    pub max_attempts: usize,
+    /// Delay before the first retry, doubled on every following retry.
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Randomizes delays so clients failing together do not retry together.
+    pub jitter: bool,
+}
+
+impl StorageRetryPolicy {
+    pub fn new(max_attempts: usize, base_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            max_attempts,
+            base_delay,
+            max_delay,
+            jitter: true,
+        }
+    }
+
+    /// Fails on the first error.
+    pub fn disabled() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn layer(&self) -> Option<RetryLayer<RetryLogger>> {
+        if self.max_attempts <= 1 {
+            return None;
+        }
+        let layer = RetryLayer::new()
+            .with_max_times(self.max_attempts - 1)
+            .with_factor(2.0)
+            .with_min_delay(self.base_delay)
+            .with_max_delay(self.max_delay)
+            .with_notify(RetryLogger);
+        Some(if self.jitter {
+            layer.with_jitter()
+        } else {
+            layer
+        })
+    }
+}
+
+impl Default for StorageRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(5),
+            jitter: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RetryLogger;
+
+impl RetryInterceptor for RetryLogger {
+    fn intercept(&self, err: &opendal::Error, dur: Duration) {
+        warn!("storage operation failed, retrying in {dur:?}: {err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_policy_layer() {
+        assert!(StorageRetryPolicy::default().layer().is_some());
+        assert!(StorageRetryPolicy::disabled().layer().is_none());
+    }
+}
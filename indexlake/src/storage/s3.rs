@@ -2,19 +2,148 @@ use opendal::{Configurator, Operator, services::S3Config};
 
 use crate::{ILError, ILResult};
 
+/// Server-side encryption requested for every object written to S3.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum S3ServerSideEncryption {
+    /// SSE-S3, keys managed by S3 (`AES256`).
+    Aes256,
+    /// SSE-KMS (`aws:kms`), encrypted with the given KMS key or the bucket default key.
+    Kms { key_id: Option<String> },
+}
+
+impl S3ServerSideEncryption {
+    fn apply(&self, config: &mut S3Config) {
+        match self {
+            S3ServerSideEncryption::Aes256 => {
+                config.server_side_encryption = Some("AES256".to_string());
+                config.server_side_encryption_aws_kms_key_id = None;
+            }
+            S3ServerSideEncryption::Kms { key_id } => {
+                config.server_side_encryption = Some("aws:kms".to_string());
+                config.server_side_encryption_aws_kms_key_id = key_id.clone();
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct S3Storage {
     config: S3Config,
     bucket: String,
+    encryption: Option<S3ServerSideEncryption>,
 }
 
 impl S3Storage {
     pub fn new(config: S3Config, bucket: String) -> Self {
-        Self { config, bucket }
+        Self {
+            config,
+            bucket,
+            encryption: None,
+        }
+    }
+
+    pub fn with_encryption(mut self, encryption: S3ServerSideEncryption) -> Self {
+        self.encryption = Some(encryption);
+        self
     }
 
     pub fn new_operator(&self) -> ILResult<Operator> {
-        let builder = self.config.clone().into_builder().bucket(&self.bucket);
+        let mut config = self.config.clone();
+        if let Some(encryption) = &self.encryption {
+            encryption.apply(&mut config);
+        }
+        let builder = config.into_builder().bucket(&self.bucket);
         Ok(Operator::new(builder)?.finish())
     }
 }
+
+/// Storage classes Cloudflare R2 accepts.
+const R2_STORAGE_CLASSES: [&str; 2] = ["STANDARD", "STANDARD_IA"];
+
+/// Adapts an S3 config to Cloudflare R2, which is reached through its account endpoint
+/// (`https://<account id>.r2.cloudflarestorage.com`) with path style requests and region
+/// `auto`. Conditional writes are left out as R2 rejects `If-Match` on multipart uploads.
+/// Fails on features R2 does not support instead of on the first write.
+pub(crate) fn r2_config(mut config: S3Config) -> ILResult<S3Config> {
+    if config.endpoint.as_deref().is_none_or(str::is_empty) {
+        return Err(ILError::InvalidInput(
+            "R2 config has no endpoint, set it to https://<account id>.r2.cloudflarestorage.com"
+                .to_string(),
+        ));
+    }
+    if config.region.is_none() {
+        config.region = Some("auto".to_string());
+    }
+    config.enable_virtual_host_style = false;
+    config.disable_write_with_if_match = true;
+    config.enable_write_with_append = false;
+
+    if config.server_side_encryption.is_some()
+        || config.server_side_encryption_aws_kms_key_id.is_some()
+    {
+        return Err(ILError::InvalidInput(
+            "R2 encrypts every object and does not support SSE-S3 or SSE-KMS headers".to_string(),
+        ));
+    }
+    if config.enable_request_payer {
+        return Err(ILError::InvalidInput(
+            "R2 does not support requester pays".to_string(),
+        ));
+    }
+    if let Some(storage_class) = &config.default_storage_class {
+        if !R2_STORAGE_CLASSES.contains(&storage_class.as_str()) {
+            return Err(ILError::InvalidInput(format!(
+                "R2 does not support storage class {storage_class}, use one of {R2_STORAGE_CLASSES:?}"
+            )));
+        }
+    }
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn r2_endpoint_config() -> S3Config {
+        let mut config = S3Config::default();
+        config.endpoint = Some("https://account.r2.cloudflarestorage.com".to_string());
+        config
+    }
+
+    #[test]
+    fn test_r2_config() {
+        let config = r2_config(r2_endpoint_config()).unwrap();
+        assert_eq!(config.region.as_deref(), Some("auto"));
+        assert!(!config.enable_virtual_host_style);
+        assert!(config.disable_write_with_if_match);
+
+        // An explicit region is kept, e.g. for jurisdiction specific buckets or MinIO
+        let mut config = r2_endpoint_config();
+        config.region = Some("us-east-1".to_string());
+        config.enable_virtual_host_style = true;
+        let config = r2_config(config).unwrap();
+        assert_eq!(config.region.as_deref(), Some("us-east-1"));
+        assert!(!config.enable_virtual_host_style);
+    }
+
+    #[test]
+    fn test_r2_config_rejects_unsupported() {
+        assert!(r2_config(S3Config::default()).is_err());
+
+        let mut config = r2_endpoint_config();
+        S3ServerSideEncryption::Aes256.apply(&mut config);
+        assert!(r2_config(config).is_err());
+
+        let mut config = r2_endpoint_config();
+        config.enable_request_payer = true;
+        assert!(r2_config(config).is_err());
+
+        let mut config = r2_endpoint_config();
+        config.default_storage_class = Some("GLACIER".to_string());
+        assert!(r2_config(config).is_err());
+
+        let mut config = r2_endpoint_config();
+        config.default_storage_class = Some("STANDARD_IA".to_string());
+        assert!(r2_config(config).is_ok());
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::{HashMap, HashSet};
+
+use log::debug;
+use parquet::arrow::ParquetRecordBatchStreamBuilder;
+
+use crate::catalog::{DataFileRecord, TransactionHelper};
+use crate::index::IndexBuilder;
+use crate::table::{
+    IngestedRows, Table, check_ingestable, check_ingested_path, footer_column_stats, ingest_rows,
+    match_file_schema, parquet_row_metadatas, table_index_builders, write_index_files,
+};
+use crate::{ILError, ILResult};
+
+/// How the columns of files added by [`Table::add_files`] must match the columns of the table.
+/// Columns are matched by name in either case, and files may lack nullable columns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SchemaMatching {
+    /// Columns must have the type of the table column.
+    #[default]
+    Strict,
+    /// Columns may also have a narrower type that every value of converts to the table type
+    /// without loss, e.g. `Int32` for an `Int64` column. Values are cast when read.
+    SafeCast,
+}
+
+#[derive(Debug, Clone, Default, derive_with::With)]
+pub struct AddFilesOptions {
+    pub schema_matching: SchemaMatching,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AddFilesReport {
+    /// Files registered as data files of the table, in the order they were given.
+    pub added_files: Vec<AddedFile>,
+    /// Files left out, the others are added all the same.
+    pub rejected_files: Vec<RejectedFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddedFile {
+    pub path: String,
+    pub row_count: u64,
+    pub file_size_bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RejectedFile {
+    pub path: String,
+    /// Why the file was rejected, e.g. the differences between its schema and the table schema.
+    pub reason: String,
+}
+
+/// Registers existing parquet files where they are as data files of the table, like
+/// [`IngestOptions::in_place`](crate::table::IngestOptions::in_place) ingestion. Files that can
+/// not be read or do not match the table are reported instead of failing the others.
+///
+/// Rows get new row ids from the row counts of the footer. Files are only read through when
+/// the table has indexes to build or partitions to check, the column statistics otherwise come
+/// from the footer, and files are then registered without checksum. Index files are written
+/// under unique paths, so that re-runs of the catalog transaction and concurrent writers never
+/// overwrite each other's index files.
+pub(crate) async fn process_add_files(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    paths: &[String],
+    options: &AddFilesOptions,
+) -> ILResult<AddFilesReport> {
+    check_ingestable(table)?;
+    let mut registered = tx_helper
+        .get_data_files(table.table_id)
+        .await?
+        .into_iter()
+        .map(|data_file| data_file.relative_path)
+        .collect::<HashSet<_>>();
+
+    let mut report = AddFilesReport::default();
+    for path in paths {
+        let added = match check_ingested_path(table, path, &registered) {
+            Ok(()) => add_file(tx_helper, table, path, options).await?,
+            Err(e) => Err(e),
+        };
+        match added {
+            Ok(added_file) => {
+                registered.insert(path.clone());
+                report.added_files.push(added_file);
+            }
+            Err(e) => {
+                debug!(
+                    "Reject parquet file {path} added to table {}: {e}",
+                    table.table_id
+                );
+                report.rejected_files.push(RejectedFile {
+                    path: path.clone(),
+                    reason: e.to_string(),
+                });
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Adds the file to the table, returning the error that rejects the file as the inner result.
+/// Errors of the catalog fail the whole call.
+async fn add_file(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    path: &str,
+    options: &AddFilesOptions,
+) -> ILResult<Result<AddedFile, ILError>> {
+    let data_file_id = tx_helper.get_max_data_file_id().await? + 1;
+    let first_row_id = tx_helper.get_max_row_id(table.table_id).await? + 1;
+    let mut index_builders = table_index_builders(table)?;
+    let (rows, file_size_bytes, row_group_num_rows) =
+        match read_added_file(table, path, options, first_row_id, &mut index_builders).await {
+            Ok(read) => read,
+            Err(e) => return Ok(Err(e)),
+        };
+    let row_count = rows.row_ids.len() as u64;
+    debug!(
+        "Add parquet file {path} to table {} as data file {data_file_id} with {row_count} rows",
+        table.table_id,
+    );
+
+    let row_metadatas = parquet_row_metadatas(path, &rows.row_ids, row_group_num_rows);
+    tx_helper
+        .insert_data_files(
+            &[DataFileRecord {
+                data_file_id,
+                table_id: table.table_id,
+                relative_path: path.to_string(),
+                file_size_bytes: file_size_bytes as i64,
+                record_count: row_count as i64,
+                row_ids: rows.row_ids,
+                partition_values: rows.partition_values,
+                checksum: None,
+                column_stats: Some(rows.column_stats),
+            }],
+            table.config.catalog_insert_batch_size,
+        )
+        .await?;
+    write_index_files(tx_helper, table, data_file_id, &mut index_builders).await?;
+    tx_helper
+        .insert_row_metadatas(
+            table.table_id,
+            &row_metadatas,
+            table.config.catalog_insert_batch_size,
+        )
+        .await?;
+    Ok(Ok(AddedFile {
+        path: path.to_string(),
+        row_count,
+        file_size_bytes,
+    }))
+}
+
+/// Reads what the catalog needs of the file, returning its rows, its size and the number of
+/// rows of each of its row groups.
+async fn read_added_file(
+    table: &Table,
+    path: &str,
+    options: &AddFilesOptions,
+    first_row_id: i64,
+    index_builders: &mut HashMap<String, Box<dyn IndexBuilder>>,
+) -> ILResult<(IngestedRows, u64, Vec<usize>)> {
+    let input_file = table.storage.open_file(path).await?;
+    let file_size_bytes = input_file.file_size_bytes().await?;
+    let reader_builder = ParquetRecordBatchStreamBuilder::new(input_file).await?;
+    let file_columns = match_file_schema(
+        table,
+        path,
+        reader_builder.schema(),
+        options.schema_matching == SchemaMatching::SafeCast,
+    )?;
+    let row_group_num_rows = reader_builder
+        .metadata()
+        .row_groups()
+        .iter()
+        .map(|row_group| row_group.num_rows() as usize)
+        .collect::<Vec<_>>();
+
+    if index_builders.is_empty() && table.config.partition_by.is_empty() {
+        let num_rows = row_group_num_rows.iter().sum::<usize>() as i64;
+        let row_ids = (first_row_id..first_row_id + num_rows).collect::<Vec<_>>();
+        if let Some(column_stats) = footer_column_stats(
+            table,
+            reader_builder.metadata(),
+            reader_builder.schema(),
+            &file_columns,
+            &row_ids,
+        )? {
+            for field in table.schema.fields() {
+                if !field.is_nullable()
+                    && column_stats
+                        .get(field.name())
+                        .is_some_and(|stats| stats.null_count > 0)
+                {
+                    return Err(ILError::InvalidInput(format!(
+                        "Parquet file {path} has nulls in column {} that is not nullable in table {}",
+                        field.name(),
+                        table.table_name
+                    )));
+                }
+            }
+            let rows = IngestedRows {
+                row_ids,
+                partition_values: None,
+                column_stats,
+            };
+            return Ok((rows, file_size_bytes, row_group_num_rows));
+        }
+    }
+
+    let stream = reader_builder.build()?;
+    let rows = ingest_rows(
+        table,
+        path,
+        stream,
+        &file_columns,
+        first_row_id,
+        index_builders,
+        None,
+    )
+    .await?;
+    Ok((rows, file_size_bytes, row_group_num_rows))
+}
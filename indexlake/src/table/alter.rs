@@ -0,0 +1,362 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use arrow::datatypes::{Field, FieldRef, Fields};
+
+use crate::{
+    ILError, ILResult,
+    catalog::{CatalogDataType, FIELD_DEFAULT_VALUE_METADATA_KEY, FIELD_DROPPED_METADATA_KEY},
+    catalog::{INTERNAL_ROW_ID_FIELD_NAME, Scalar, TransactionHelper},
+    expr::visited_columns,
+    index::IndexDefinationRef,
+    table::{CheckConstraint, Table, TableConfig, split_column_default},
+};
+
+pub(crate) async fn process_add_column(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    field: &Field,
+    default: Option<&Scalar>,
+) -> ILResult<i64> {
+    if table.schema.field_with_name(field.name()).is_ok() {
+        return Err(ILError::InvalidInput(format!(
+            "Column {} already exists in table {}",
+            field.name(),
+            table.table_name
+        )));
+    }
+    if tx_helper
+        .get_field_id(table.table_id, field.name())
+        .await?
+        .is_some()
+    {
+        return Err(ILError::InvalidInput(format!(
+            "Column {} was dropped from table {} and can not be added again",
+            field.name(),
+            table.table_name
+        )));
+    }
+    if !field.is_nullable() {
+        return Err(ILError::InvalidInput(format!(
+            "Added column {} must be nullable",
+            field.name()
+        )));
+    }
+    if field
+        .metadata()
+        .contains_key(FIELD_DEFAULT_VALUE_METADATA_KEY)
+        || field.metadata().contains_key(FIELD_DROPPED_METADATA_KEY)
+    {
+        return Err(ILError::InvalidInput(format!(
+            "Field metadata keys {FIELD_DEFAULT_VALUE_METADATA_KEY} and {FIELD_DROPPED_METADATA_KEY} are reserved"
+        )));
+    }
+    CatalogDataType::from_arrow(field.data_type())?;
+    if let (field, Some(column_default)) = split_column_default(field)? {
+        column_default.check(&field)?;
+    }
+    if let Some(default) = default
+        && &default.data_type() != field.data_type()
+    {
+        return Err(ILError::InvalidInput(format!(
+            "Default value {default:?} does not match data type {} of column {}",
+            field.data_type(),
+            field.name()
+        )));
+    }
+
+    let field_id = tx_helper.get_max_field_id().await? + 1;
+    let mut catalog_field = field.clone();
+    if let Some(default) = default {
+        let default_str = serde_json::to_string(default).map_err(|e| {
+            ILError::InternalError(format!("Failed to serialize field default value: {e:?}"))
+        })?;
+        let mut metadata = field.metadata().clone();
+        metadata.insert(FIELD_DEFAULT_VALUE_METADATA_KEY.to_string(), default_str);
+        catalog_field = catalog_field.with_metadata(metadata);
+    }
+    tx_helper
+        .insert_fields(
+            table.table_id,
+            &[field_id],
+            &Fields::from(vec![Arc::new(catalog_field)]),
+        )
+        .await?;
+
+    tx_helper
+        .add_inline_row_column(table.table_id, field)
+        .await?;
+    if let Some(default) = default
+        && !default.is_null()
+    {
+        tx_helper
+            .fill_inline_row_column(table.table_id, field.name(), default)
+            .await?;
+    }
+    tx_helper.bump_table_schema_version(table.table_id).await?;
+
+    Ok(field_id)
+}
+
+/// Drops the column from the catalog and inline rows, data files keep it until rewritten.
+/// Indexes using the column are dropped along with it with `cascade`, otherwise the column can
+/// not be dropped.
+pub(crate) async fn process_drop_column(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    field_name: &str,
+    cascade: bool,
+) -> ILResult<i64> {
+    if field_name == INTERNAL_ROW_ID_FIELD_NAME {
+        return Err(ILError::InvalidInput(format!(
+            "Column {INTERNAL_ROW_ID_FIELD_NAME} is internal and can not be dropped"
+        )));
+    }
+    let (field_id, field) = find_field(table, field_name)?;
+    if table
+        .config
+        .primary_key
+        .iter()
+        .any(|name| name == field_name)
+    {
+        return Err(ILError::InvalidInput(format!(
+            "Column {field_name} is part of the primary key of table {}",
+            table.table_name
+        )));
+    }
+    if let Some(constraint) = constraints_using_column(table, field_name).first() {
+        return Err(ILError::InvalidInput(format!(
+            "Column {field_name} is used by check constraint {}, drop the constraint first",
+            constraint.name
+        )));
+    }
+    if let Some(constraint) = table
+        .config
+        .unique_constraints
+        .iter()
+        .find(|constraint| constraint.columns.iter().any(|name| name == field_name))
+    {
+        return Err(ILError::InvalidInput(format!(
+            "Column {field_name} is part of unique constraint {} of table {}",
+            constraint.name, table.table_name
+        )));
+    }
+    if table
+        .config
+        .clustering
+        .columns()
+        .iter()
+        .any(|name| name == field_name)
+    {
+        return Err(ILError::InvalidInput(format!(
+            "Column {field_name} is a clustering column of table {}",
+            table.table_name
+        )));
+    }
+    if let Some(ttl) = &table.config.ttl
+        && ttl.column == field_name
+    {
+        return Err(ILError::InvalidInput(format!(
+            "Column {field_name} is the TTL column of table {}, remove the TTL first",
+            table.table_name
+        )));
+    }
+    let indexes = indexes_using_column(table, field_name);
+    if let Some(index) = indexes.first()
+        && !cascade
+    {
+        return Err(ILError::InvalidInput(format!(
+            "Column {field_name} is used by index {}, drop the index first",
+            index.name
+        )));
+    }
+    if table.field_map.len() == 1 {
+        return Err(ILError::InvalidInput(format!(
+            "Can not drop the last column {field_name} of table {}",
+            table.table_name
+        )));
+    }
+
+    // Index files are left to vacuum once no index refers to them
+    for index in indexes {
+        tx_helper
+            .delete_index_files_by_index_id(index.index_id)
+            .await?;
+        tx_helper.delete_index(index.index_id).await?;
+    }
+    let mut metadata = field.metadata().clone();
+    metadata.insert(FIELD_DROPPED_METADATA_KEY.to_string(), "true".to_string());
+    tx_helper.update_field_metadata(field_id, &metadata).await?;
+    tx_helper
+        .drop_inline_row_column(table.table_id, field_name)
+        .await?;
+    tx_helper.bump_table_schema_version(table.table_id).await?;
+
+    Ok(field_id)
+}
+
+/// Renames the column in the catalog, inline rows, column stats of data files and values of
+/// replaced inline rows. Data files are matched by field id and keep the old name. Returns the
+/// field id of the column and the table config with the column renamed.
+pub(crate) async fn process_rename_column(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    old_name: &str,
+    new_name: &str,
+) -> ILResult<(i64, TableConfig)> {
+    if old_name == INTERNAL_ROW_ID_FIELD_NAME || new_name == INTERNAL_ROW_ID_FIELD_NAME {
+        return Err(ILError::InvalidInput(format!(
+            "Column {INTERNAL_ROW_ID_FIELD_NAME} is internal and can not be renamed"
+        )));
+    }
+    let (field_id, _) = find_field(table, old_name)?;
+    if table.schema.field_with_name(new_name).is_ok() {
+        return Err(ILError::InvalidInput(format!(
+            "Column {new_name} already exists in table {}",
+            table.table_name
+        )));
+    }
+    if tx_helper
+        .get_field_id(table.table_id, new_name)
+        .await?
+        .is_some()
+    {
+        return Err(ILError::InvalidInput(format!(
+            "Column {new_name} was dropped from table {} and can not be reused",
+            table.table_name
+        )));
+    }
+    // Index files and predicates refer to the columns by name
+    if let Some(index) = indexes_using_column(table, old_name).first() {
+        return Err(ILError::InvalidInput(format!(
+            "Column {old_name} is used by index {}, drop the index first",
+            index.name
+        )));
+    }
+    if let Some(constraint) = constraints_using_column(table, old_name).first() {
+        return Err(ILError::InvalidInput(format!(
+            "Column {old_name} is used by check constraint {}, drop the constraint first",
+            constraint.name
+        )));
+    }
+
+    let mut config = table.config.as_ref().clone();
+    // Unique keys are recorded by their values, they stay valid under the new name
+    if config
+        .primary_key
+        .iter()
+        .chain(config.partition_by.iter())
+        .chain(
+            config
+                .unique_constraints
+                .iter()
+                .flat_map(|c| c.columns.iter()),
+        )
+        .chain(config.ttl.iter().map(|ttl| &ttl.column))
+        .chain(config.clustering.columns().iter())
+        .any(|name| name == old_name)
+    {
+        for name in config
+            .primary_key
+            .iter_mut()
+            .chain(config.partition_by.iter_mut())
+            .chain(
+                config
+                    .unique_constraints
+                    .iter_mut()
+                    .flat_map(|c| c.columns.iter_mut()),
+            )
+            .chain(config.ttl.iter_mut().map(|ttl| &mut ttl.column))
+            .chain(config.clustering.columns_mut().iter_mut())
+        {
+            if name == old_name {
+                *name = new_name.to_string();
+            }
+        }
+        tx_helper
+            .update_table_config(table.table_id, &config)
+            .await?;
+    }
+
+    tx_helper.update_field_name(field_id, new_name).await?;
+    tx_helper
+        .rename_inline_row_column(table.table_id, old_name, new_name)
+        .await?;
+    for data_file in tx_helper.get_data_files(table.table_id).await? {
+        let Some(mut column_stats) = data_file.column_stats else {
+            continue;
+        };
+        if let Some(stats) = column_stats.remove(old_name) {
+            column_stats.insert(new_name.to_string(), stats);
+            tx_helper
+                .update_data_file_column_stats(data_file.data_file_id, &column_stats)
+                .await?;
+        }
+    }
+    for mut row_history in tx_helper.get_row_histories(table.table_id).await? {
+        let Some(row_values) = &row_history.row_values else {
+            continue;
+        };
+        let mut values: BTreeMap<String, Scalar> =
+            serde_json::from_slice(row_values).map_err(|e| {
+                ILError::InternalError(format!("Failed to deserialize row values: {e:?}"))
+            })?;
+        if let Some(value) = values.remove(old_name) {
+            values.insert(new_name.to_string(), value);
+            row_history.row_values = Some(serde_json::to_vec(&values).map_err(|e| {
+                ILError::InternalError(format!("Failed to serialize row values: {e:?}"))
+            })?);
+            tx_helper.update_row_history_values(&row_history).await?;
+        }
+    }
+    tx_helper.bump_table_schema_version(table.table_id).await?;
+
+    Ok((field_id, config))
+}
+
+fn find_field<'a>(table: &'a Table, field_name: &str) -> ILResult<(i64, &'a FieldRef)> {
+    table
+        .field_map
+        .iter()
+        .find(|(_, field)| field.name() == field_name)
+        .map(|(field_id, field)| (*field_id, field))
+        .ok_or_else(|| {
+            ILError::InvalidInput(format!(
+                "Column {field_name} not found in table {}",
+                table.table_name
+            ))
+        })
+}
+
+/// Indexes with the column as key or include column or in their predicate.
+pub(crate) fn indexes_using_column<'a>(
+    table: &'a Table,
+    field_name: &str,
+) -> Vec<&'a IndexDefinationRef> {
+    table
+        .indexes
+        .values()
+        .filter(|index| {
+            index.key_columns.iter().any(|name| name == field_name)
+                || index.include_columns.iter().any(|name| name == field_name)
+                || index.where_predicate.as_ref().is_some_and(|predicate| {
+                    visited_columns(predicate)
+                        .iter()
+                        .any(|name| name == field_name)
+                })
+        })
+        .collect()
+}
+
+/// Check constraints referring to the column.
+fn constraints_using_column<'a>(table: &'a Table, field_name: &str) -> Vec<&'a CheckConstraint> {
+    table
+        .config
+        .check_constraints
+        .iter()
+        .filter(|constraint| {
+            visited_columns(&constraint.expr)
+                .iter()
+                .any(|name| name == field_name)
+        })
+        .collect()
+}
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+
+use crate::catalog::{INTERNAL_ROW_ID_FIELD_NAME, IndexRecord, TableRecord, TransactionHelper};
+use crate::table::{SnapshotOperation, Table, TableIdent, record_snapshot};
+use crate::{ILError, ILResult};
+
+/// Creates the table `target` in the namespace `namespace_id` as a copy of `source` that
+/// refers to the data files and index files of the source instead of copying them. Fields,
+/// row metadata, inline rows, unique keys, data files and indexes are copied within the
+/// catalog, the clone starts with a single snapshot. Files written afterwards go to the
+/// directory of the table writing them, so either table changes only its own metadata.
+pub(crate) async fn process_clone_table(
+    tx_helper: &mut TransactionHelper,
+    source: &Table,
+    namespace_id: i64,
+    target: &TableIdent,
+) -> ILResult<i64> {
+    if tx_helper
+        .table_name_exists(namespace_id, &target.table_name)
+        .await?
+    {
+        return Err(ILError::InvalidInput(format!(
+            "Table {} already exists in namespace {}",
+            target.table_name, target.namespace_name
+        )));
+    }
+
+    let table_id = tx_helper.get_max_table_id().await? + 1;
+    // Copied fields get new ids, data files keep resolving columns by the ids of the source
+    let field_id_offset = tx_helper.get_max_field_id().await?;
+    let mut config = source.config.as_ref().clone();
+    config.storage_prefix = None;
+    config.file_field_ids = source
+        .field_map
+        .keys()
+        .map(|field_id| {
+            let file_field_id = source
+                .config
+                .file_field_ids
+                .get(field_id)
+                .unwrap_or(field_id);
+            (field_id + field_id_offset, *file_field_id)
+        })
+        .collect();
+    let has_unique_constraints = !config.unique_constraints.is_empty();
+    tx_helper
+        .insert_table(&TableRecord {
+            table_id,
+            table_name: target.table_name.clone(),
+            namespace_id,
+            config,
+            schema_version: 0,
+        })
+        .await?;
+    tx_helper
+        .insert_fields_from(source.table_id, table_id, field_id_offset)
+        .await?;
+
+    tx_helper.create_row_metadata_table(table_id).await?;
+    tx_helper
+        .insert_rows_from(
+            &format!("indexlake_row_metadata_{}", source.table_id),
+            &format!("indexlake_row_metadata_{table_id}"),
+            &[INTERNAL_ROW_ID_FIELD_NAME, "location", "deleted"],
+        )
+        .await?;
+    let fields = source
+        .field_map
+        .values()
+        .cloned()
+        .collect::<Vec<_>>()
+        .into();
+    tx_helper.create_inline_row_table(table_id, &fields).await?;
+    let mut inline_columns = vec![INTERNAL_ROW_ID_FIELD_NAME];
+    inline_columns.extend(fields.iter().map(|field| field.name().as_str()));
+    tx_helper
+        .insert_rows_from(
+            &format!("indexlake_inline_row_{}", source.table_id),
+            &format!("indexlake_inline_row_{table_id}"),
+            &inline_columns,
+        )
+        .await?;
+    if has_unique_constraints {
+        tx_helper.create_unique_key_table(table_id).await?;
+        tx_helper
+            .insert_rows_from(
+                &format!("indexlake_unique_key_{}", source.table_id),
+                &format!("indexlake_unique_key_{table_id}"),
+                &["key_hash", INTERNAL_ROW_ID_FIELD_NAME],
+            )
+            .await?;
+    }
+
+    let max_data_file_id = tx_helper.get_max_data_file_id().await?;
+    let mut data_file_ids = HashMap::new();
+    let mut data_files = tx_helper.get_data_files(source.table_id).await?;
+    for (i, data_file) in data_files.iter_mut().enumerate() {
+        let data_file_id = max_data_file_id + 1 + i as i64;
+        data_file_ids.insert(data_file.data_file_id, data_file_id);
+        data_file.data_file_id = data_file_id;
+        data_file.table_id = table_id;
+    }
+    if !data_files.is_empty() {
+        tx_helper
+            .insert_data_files(&data_files, source.config.catalog_insert_batch_size)
+            .await?;
+    }
+
+    let mut index_ids = HashMap::new();
+    let mut index_id = tx_helper.get_max_index_id().await?;
+    for index in tx_helper.get_table_indexes(source.table_id).await? {
+        index_id += 1;
+        index_ids.insert(index.index_id, index_id);
+        tx_helper
+            .insert_index(&IndexRecord {
+                index_id,
+                table_id,
+                key_field_ids: index
+                    .key_field_ids
+                    .iter()
+                    .map(|field_id| field_id + field_id_offset)
+                    .collect(),
+                include_field_ids: index
+                    .include_field_ids
+                    .iter()
+                    .map(|field_id| field_id + field_id_offset)
+                    .collect(),
+                ..index
+            })
+            .await?;
+    }
+    let max_index_file_id = tx_helper.get_max_index_file_id().await?;
+    let mut index_files = tx_helper.get_index_files(source.table_id).await?;
+    for (i, index_file) in index_files.iter_mut().enumerate() {
+        index_file.index_file_id = max_index_file_id + 1 + i as i64;
+        index_file.index_id = index_ids[&index_file.index_id];
+        index_file.data_file_id =
+            *data_file_ids.get(&index_file.data_file_id).ok_or_else(|| {
+                ILError::CatalogError(format!(
+                    "Data file {} of index file {} not found",
+                    index_file.data_file_id, index_file.relative_path
+                ))
+            })?;
+    }
+    tx_helper.insert_index_files(&index_files).await?;
+
+    let snapshot_id = tx_helper.get_max_snapshot_id().await? + 1;
+    record_snapshot(tx_helper, table_id, snapshot_id, SnapshotOperation::Clone).await?;
+    Ok(table_id)
+}
@@ -0,0 +1,194 @@
+use arrow::array::{ArrayRef, RecordBatch, UInt32Array};
+use arrow::compute::take_record_batch;
+use arrow::datatypes::SchemaRef;
+use arrow::row::{RowConverter, SortField};
+
+use crate::catalog::{INTERNAL_ROW_ID_FIELD_NAME, Row, rows_to_record_batch};
+use crate::table::Clustering;
+use crate::utils::has_duplicated_items;
+use crate::{ILError, ILResult};
+
+/// Z-values interleave the ranks of the clustered columns into 128 bits, at least 8 per column.
+const MAX_ZORDER_COLUMNS: usize = 16;
+
+pub(crate) fn check_clustering_columns(
+    schema: &SchemaRef,
+    clustering: &Clustering,
+) -> ILResult<()> {
+    let Clustering::ZOrder(columns) = clustering else {
+        return Ok(());
+    };
+    if columns.is_empty() || columns.len() > MAX_ZORDER_COLUMNS {
+        return Err(ILError::InvalidInput(format!(
+            "Z-order clustering needs between 1 and {MAX_ZORDER_COLUMNS} columns, got {}",
+            columns.len()
+        )));
+    }
+    if has_duplicated_items(columns.iter()) {
+        return Err(ILError::InvalidInput(format!(
+            "Duplicated columns in clustering columns {columns:?}"
+        )));
+    }
+    for name in columns {
+        if name == INTERNAL_ROW_ID_FIELD_NAME {
+            return Err(ILError::InvalidInput(format!(
+                "Column {INTERNAL_ROW_ID_FIELD_NAME} is internal and can not be clustered"
+            )));
+        }
+        let field = schema.field_with_name(name).map_err(|_| {
+            ILError::InvalidInput(format!(
+                "Clustering column {name} not found in table schema"
+            ))
+        })?;
+        if !RowConverter::supports_fields(&[SortField::new(field.data_type().clone())]) {
+            return Err(ILError::InvalidInput(format!(
+                "Clustering column {name} of type {} is not orderable",
+                field.data_type()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Reorders the rows of `batch` per the clustering, rows with equal clustering values keep
+/// their order.
+pub(crate) fn cluster_batch(clustering: &Clustering, batch: RecordBatch) -> ILResult<RecordBatch> {
+    match clustering {
+        Clustering::None => Ok(batch),
+        Clustering::ZOrder(columns) => {
+            let indices = zorder_indices(&batch, columns)?;
+            Ok(take_record_batch(&batch, &indices)?)
+        }
+    }
+}
+
+/// Reorders `rows` of the table per the clustering, like [`cluster_batch`].
+pub(crate) fn cluster_rows(
+    table_schema: &SchemaRef,
+    clustering: &Clustering,
+    rows: Vec<Row>,
+) -> ILResult<Vec<Row>> {
+    let Clustering::ZOrder(columns) = clustering else {
+        return Ok(rows);
+    };
+    let batch = rows_to_record_batch(table_schema, &rows)?;
+    let indices = zorder_indices(&batch, columns)?;
+    let mut rows = rows.into_iter().map(Some).collect::<Vec<_>>();
+    Ok(indices
+        .values()
+        .iter()
+        .filter_map(|idx| rows[*idx as usize].take())
+        .collect())
+}
+
+/// Indices of the rows of `batch` sorted by their Z-values over `columns`. The value of a row
+/// in each column is replaced by its rank among the values of the batch, so columns of any
+/// orderable type and range contribute equally, and the ranks are scaled to the same number of
+/// bits before being interleaved, the first column taking the most significant bit of each
+/// level.
+fn zorder_indices(batch: &RecordBatch, columns: &[String]) -> ILResult<UInt32Array> {
+    let bits = (128 / columns.len()).min(32);
+    let scale = (1u128 << bits) - 1;
+    let mut zvalues = vec![0u128; batch.num_rows()];
+    for (column_idx, name) in columns.iter().enumerate() {
+        let array = batch.column(batch.schema().index_of(name)?);
+        let ranks = dense_ranks(array)?;
+        let max_rank = ranks.iter().copied().max().unwrap_or_default().max(1) as u128;
+        for (zvalue, rank) in zvalues.iter_mut().zip(ranks) {
+            let scaled = rank as u128 * scale / max_rank;
+            for bit in 0..bits {
+                if scaled >> bit & 1 == 1 {
+                    *zvalue |= 1u128 << (bit * columns.len() + columns.len() - 1 - column_idx);
+                }
+            }
+        }
+    }
+    let mut indices = (0..batch.num_rows() as u32).collect::<Vec<_>>();
+    indices.sort_by_key(|idx| zvalues[*idx as usize]);
+    Ok(UInt32Array::from(indices))
+}
+
+/// Ranks of the values of `array` counting distinct values only, nulls first.
+fn dense_ranks(array: &ArrayRef) -> ILResult<Vec<u32>> {
+    let converter = RowConverter::new(vec![SortField::new(array.data_type().clone())])?;
+    let rows = converter.convert_columns(std::slice::from_ref(array))?;
+    let mut order = (0..rows.num_rows()).collect::<Vec<_>>();
+    order.sort_by(|a, b| rows.row(*a).cmp(&rows.row(*b)));
+    let mut ranks = vec![0; rows.num_rows()];
+    let mut rank = 0;
+    for (pos, idx) in order.iter().enumerate() {
+        if pos > 0 && rows.row(order[pos - 1]) != rows.row(*idx) {
+            rank += 1;
+        }
+        ranks[*idx] = rank;
+    }
+    Ok(ranks)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::{Int64Array, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    use super::*;
+
+    #[test]
+    fn test_zorder_indices() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("x", DataType::Int64, false),
+            Field::new("y", DataType::Utf8, false),
+        ]));
+        let (xs, ys): (Vec<i64>, Vec<String>) = (0..16)
+            .rev()
+            .map(|i| (i / 4 * 100, format!("y{}", i % 4)))
+            .unzip();
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(xs)),
+                Arc::new(StringArray::from(ys)),
+            ],
+        )
+        .unwrap();
+        let columns = vec!["x".to_string(), "y".to_string()];
+        let sorted = take_record_batch(&batch, &zorder_indices(&batch, &columns).unwrap()).unwrap();
+        let points = (0..sorted.num_rows())
+            .map(|i| {
+                let x = sorted
+                    .column(0)
+                    .as_any()
+                    .downcast_ref::<Int64Array>()
+                    .unwrap();
+                let y = sorted
+                    .column(1)
+                    .as_any()
+                    .downcast_ref::<StringArray>()
+                    .unwrap();
+                (x.value(i) / 100, y.value(i)[1..].parse::<i64>().unwrap())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            points,
+            vec![
+                (0, 0),
+                (0, 1),
+                (1, 0),
+                (1, 1),
+                (0, 2),
+                (0, 3),
+                (1, 2),
+                (1, 3),
+                (2, 0),
+                (2, 1),
+                (3, 0),
+                (3, 1),
+                (2, 2),
+                (2, 3),
+                (3, 2),
+                (3, 3),
+            ]
+        );
+    }
+}
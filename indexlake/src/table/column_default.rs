@@ -0,0 +1,170 @@
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use arrow::{
+    array::{ArrayRef, BinaryArray, Int64Array, StringArray},
+    compute::{is_null, kernels::zip::zip},
+    datatypes::{DataType, Field},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ILError, ILResult,
+    catalog::{FIELD_COLUMN_DEFAULT_METADATA_KEY, Scalar},
+};
+
+/// Value a column default fills in.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DefaultValue {
+    Literal(Scalar),
+    /// Milliseconds since the Unix epoch at write time, for `Int64` columns.
+    Now,
+    /// A random UUID per row, hyphenated for `Utf8` columns and its 16 bytes for `Binary`
+    /// columns.
+    Uuid,
+}
+
+/// When a column default is applied to inserted rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DefaultMode {
+    /// Only when the inserted batch lacks the column.
+    OnMissing,
+    /// When the inserted batch lacks the column or holds nulls in it.
+    OnNull,
+}
+
+/// Default of a column, materialized into the rows when they are inserted so scans read it
+/// like any other value. Declared on the field of the table schema at creation or of an added
+/// column with [`ColumnDefault::declare_on`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnDefault {
+    pub value: DefaultValue,
+    pub mode: DefaultMode,
+}
+
+impl ColumnDefault {
+    pub fn literal(value: Scalar) -> Self {
+        Self {
+            value: DefaultValue::Literal(value),
+            mode: DefaultMode::OnMissing,
+        }
+    }
+
+    pub fn now() -> Self {
+        Self {
+            value: DefaultValue::Now,
+            mode: DefaultMode::OnMissing,
+        }
+    }
+
+    pub fn uuid() -> Self {
+        Self {
+            value: DefaultValue::Uuid,
+            mode: DefaultMode::OnMissing,
+        }
+    }
+
+    pub fn with_mode(self, mode: DefaultMode) -> Self {
+        Self { mode, ..self }
+    }
+
+    /// Returns `field` declaring this default, it is persisted in the catalog along with the
+    /// field.
+    pub fn declare_on(&self, field: Field) -> ILResult<Field> {
+        self.check(&field)?;
+        let default_str = serde_json::to_string(self).map_err(|e| {
+            ILError::InternalError(format!("Failed to serialize column default: {e:?}"))
+        })?;
+        let mut metadata = field.metadata().clone();
+        metadata.insert(FIELD_COLUMN_DEFAULT_METADATA_KEY.to_string(), default_str);
+        Ok(field.with_metadata(metadata))
+    }
+
+    /// Checks the default produces values of the type of `field`, and no nulls if it is not
+    /// nullable.
+    pub(crate) fn check(&self, field: &Field) -> ILResult<()> {
+        let matches = match &self.value {
+            DefaultValue::Literal(value) => {
+                if value.is_null() && !field.is_nullable() {
+                    return Err(ILError::InvalidInput(format!(
+                        "Null default of column {} which is not nullable",
+                        field.name()
+                    )));
+                }
+                &value.data_type() == field.data_type()
+            }
+            DefaultValue::Now => field.data_type() == &DataType::Int64,
+            DefaultValue::Uuid => {
+                matches!(field.data_type(), DataType::Utf8 | DataType::Binary)
+            }
+        };
+        if !matches {
+            return Err(ILError::InvalidInput(format!(
+                "Default {:?} does not match data type {} of column {}",
+                self.value,
+                field.data_type(),
+                field.name()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Values of the default for `num_rows` rows of `data_type`.
+    pub(crate) fn evaluate(&self, data_type: &DataType, num_rows: usize) -> ILResult<ArrayRef> {
+        match &self.value {
+            DefaultValue::Literal(value) => value.to_array_of_size(num_rows),
+            DefaultValue::Now => {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_err(|e| ILError::InternalError(format!("Invalid system time: {e:?}")))?
+                    .as_millis() as i64;
+                Ok(Arc::new(Int64Array::from_value(now, num_rows)))
+            }
+            DefaultValue::Uuid => {
+                let uuids = (0..num_rows).map(|_| uuid::Uuid::new_v4());
+                match data_type {
+                    DataType::Binary => Ok(Arc::new(BinaryArray::from_iter_values(
+                        uuids.map(|uuid| uuid.into_bytes()),
+                    ))),
+                    _ => Ok(Arc::new(StringArray::from_iter_values(
+                        uuids.map(|uuid| uuid.to_string()),
+                    ))),
+                }
+            }
+        }
+    }
+
+    /// Applies the default to an inserted column, `None` for a column the batch lacks.
+    pub(crate) fn apply(
+        &self,
+        column: Option<&ArrayRef>,
+        data_type: &DataType,
+        num_rows: usize,
+    ) -> ILResult<ArrayRef> {
+        match column {
+            None => self.evaluate(data_type, num_rows),
+            Some(column) if self.mode == DefaultMode::OnNull && column.null_count() > 0 => {
+                let defaults = self.evaluate(data_type, num_rows)?;
+                Ok(zip(&is_null(column)?, &defaults, column)?)
+            }
+            Some(column) => Ok(column.clone()),
+        }
+    }
+}
+
+/// Splits the column default declared on `field` off its metadata.
+pub(crate) fn split_column_default(field: &Field) -> ILResult<(Field, Option<ColumnDefault>)> {
+    let mut metadata = field.metadata().clone();
+    let Some(default_str) = metadata.remove(FIELD_COLUMN_DEFAULT_METADATA_KEY) else {
+        return Ok((field.clone(), None));
+    };
+    let column_default: ColumnDefault = serde_json::from_str(&default_str).map_err(|e| {
+        ILError::InvalidInput(format!(
+            "Failed to deserialize default of column {}: {e:?}",
+            field.name()
+        ))
+    })?;
+    Ok((field.clone().with_metadata(metadata), Some(column_default)))
+}
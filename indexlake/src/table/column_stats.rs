@@ -0,0 +1,802 @@
+use std::collections::{BTreeMap, HashSet};
+
+use arrow::array::{Array, AsArray, RecordBatch};
+use arrow::compute::cast;
+use arrow::compute::{
+    max, max_binary, max_boolean, max_string, min, min_binary, min_boolean, min_string,
+};
+use arrow::datatypes::{
+    DataType, Decimal128Type, Decimal256Type, Float32Type, Float64Type, Int16Type, Int32Type,
+    Int64Type, SchemaRef,
+};
+use parquet::arrow::arrow_reader::statistics::StatisticsConverter;
+use parquet::file::metadata::ParquetMetaData;
+
+use crate::ILResult;
+use crate::catalog::{ColumnStats, DataFileRecord, INTERNAL_ROW_ID_FIELD_NAME, Scalar};
+use crate::expr::{BinaryOp, Expr};
+use crate::table::Table;
+
+/// Collects the statistics of every column of the batches written to a data file.
+pub(crate) struct ColumnStatsBuilder {
+    columns: BTreeMap<String, ColumnStatsAccumulator>,
+}
+
+struct ColumnStatsAccumulator {
+    stats: ColumnStats,
+    /// Whether `min` and `max` bound every non-null value seen so far.
+    bounded: bool,
+}
+
+impl ColumnStatsBuilder {
+    pub(crate) fn new() -> Self {
+        Self {
+            columns: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn update(&mut self, batch: &RecordBatch) {
+        for (field, array) in batch.schema().fields().iter().zip(batch.columns()) {
+            let accumulator = self.columns.entry(field.name().clone()).or_insert_with(|| {
+                ColumnStatsAccumulator {
+                    stats: ColumnStats {
+                        min: None,
+                        max: None,
+                        null_count: 0,
+                    },
+                    bounded: true,
+                }
+            });
+            accumulator.stats.null_count += array.null_count() as u64;
+            if !accumulator.bounded || array.null_count() == array.len() {
+                continue;
+            }
+            match min_max(array.as_ref()) {
+                Some((batch_min, batch_max)) => {
+                    let stats = &mut accumulator.stats;
+                    if stats.min.as_ref().is_none_or(|min| batch_min < *min) {
+                        stats.min = Some(batch_min);
+                    }
+                    if stats.max.as_ref().is_none_or(|max| batch_max > *max) {
+                        stats.max = Some(batch_max);
+                    }
+                }
+                None => {
+                    accumulator.stats.min = None;
+                    accumulator.stats.max = None;
+                    accumulator.bounded = false;
+                }
+            }
+        }
+    }
+
+    pub(crate) fn finish(self) -> BTreeMap<String, ColumnStats> {
+        self.columns
+            .into_iter()
+            .map(|(name, accumulator)| (name, accumulator.stats))
+            .collect()
+    }
+}
+
+/// Statistics of the columns of a parquet file with the rows `row_ids`, from the statistics
+/// of its row groups in the footer instead of its rows. `file_columns` holds the index in
+/// `file_schema` of each column of the table. Only boolean, integer, decimal and timestamp
+/// columns get bounds, writers may truncate those of strings and leave NaN out of those of floats. `None`
+/// if the footer lacks the null counts of a column.
+pub(crate) fn footer_column_stats(
+    table: &Table,
+    metadata: &ParquetMetaData,
+    file_schema: &SchemaRef,
+    file_columns: &[Option<usize>],
+    row_ids: &[i64],
+) -> ILResult<Option<BTreeMap<String, ColumnStats>>> {
+    let mut columns = BTreeMap::new();
+    let (Some(first_row_id), Some(last_row_id)) = (row_ids.first(), row_ids.last()) else {
+        return Ok(Some(columns));
+    };
+    let num_rows = row_ids.len() as u64;
+    let row_groups = metadata.row_groups();
+    for (field, file_idx) in table.schema.fields().iter().zip(file_columns) {
+        let stats = match file_idx {
+            Some(idx) => {
+                let Ok(converter) = StatisticsConverter::try_new(
+                    file_schema.field(*idx).name(),
+                    file_schema,
+                    metadata.file_metadata().schema_descr(),
+                ) else {
+                    return Ok(None);
+                };
+                let null_counts = converter.row_group_null_counts(row_groups)?;
+                if null_counts.null_count() > 0 {
+                    return Ok(None);
+                }
+                let mut stats = ColumnStats {
+                    min: None,
+                    max: None,
+                    null_count: null_counts.values().iter().sum(),
+                };
+                if stats.null_count < num_rows && has_exact_footer_bounds(field.data_type()) {
+                    let mins = cast(&converter.row_group_mins(row_groups)?, field.data_type())?;
+                    let maxes = cast(&converter.row_group_maxes(row_groups)?, field.data_type())?;
+                    // Row groups holding non-null values must all have bounds
+                    let bounded = row_groups.iter().enumerate().all(|(i, row_group)| {
+                        null_counts.value(i) == row_group.num_rows() as u64
+                            || (mins.is_valid(i) && maxes.is_valid(i))
+                    });
+                    if bounded
+                        && let (Some((min_value, _)), Some((_, max_value))) =
+                            (min_max(mins.as_ref()), min_max(maxes.as_ref()))
+                    {
+                        stats.min = Some(min_value);
+                        stats.max = Some(max_value);
+                    }
+                }
+                stats
+            }
+            None if field.name() == INTERNAL_ROW_ID_FIELD_NAME => ColumnStats {
+                min: Some(Scalar::Int64(Some(*first_row_id))),
+                max: Some(Scalar::Int64(Some(*last_row_id))),
+                null_count: 0,
+            },
+            None => match table.field_defaults.get(field.name()) {
+                Some(default) if !default.is_null() => ColumnStats {
+                    min: Some(default.clone()),
+                    max: Some(default.clone()),
+                    null_count: 0,
+                },
+                _ => ColumnStats {
+                    min: None,
+                    max: None,
+                    null_count: num_rows,
+                },
+            },
+        };
+        columns.insert(field.name().clone(), stats);
+    }
+    Ok(Some(columns))
+}
+
+/// Statistics of the columns of each row group of a parquet file, from its footer.
+/// `file_columns` holds the index in `file_schema` of each field of `schema`, the statistics
+/// are keyed by the field names. Columns the file lacks or holds with another type, and columns
+/// whose footer lacks null counts, get no statistics and prune nothing. Bounds are taken for
+/// the same types as [`footer_column_stats`] does.
+pub(crate) fn row_group_column_stats(
+    metadata: &ParquetMetaData,
+    file_schema: &SchemaRef,
+    schema: &SchemaRef,
+    file_columns: &[Option<usize>],
+) -> ILResult<Vec<BTreeMap<String, ColumnStats>>> {
+    let row_groups = metadata.row_groups();
+    let mut row_group_stats = vec![BTreeMap::new(); row_groups.len()];
+    for (field, file_idx) in schema.fields().iter().zip(file_columns) {
+        let Some(file_field) = file_idx.map(|idx| file_schema.field(idx)) else {
+            continue;
+        };
+        if file_field.data_type() != field.data_type() {
+            continue;
+        }
+        let Ok(converter) = StatisticsConverter::try_new(
+            file_field.name(),
+            file_schema,
+            metadata.file_metadata().schema_descr(),
+        ) else {
+            continue;
+        };
+        let null_counts = converter.row_group_null_counts(row_groups)?;
+        let bounds = if has_exact_footer_bounds(field.data_type()) {
+            Some((
+                converter.row_group_mins(row_groups)?,
+                converter.row_group_maxes(row_groups)?,
+            ))
+        } else {
+            None
+        };
+        for (i, columns) in row_group_stats.iter_mut().enumerate() {
+            if null_counts.is_null(i) {
+                continue;
+            }
+            let mut stats = ColumnStats {
+                min: None,
+                max: None,
+                null_count: null_counts.value(i),
+            };
+            if let Some((mins, maxes)) = &bounds
+                && mins.is_valid(i)
+                && maxes.is_valid(i)
+            {
+                stats.min = Some(Scalar::try_from_array(mins.as_ref(), i)?);
+                stats.max = Some(Scalar::try_from_array(maxes.as_ref(), i)?);
+            }
+            columns.insert(field.name().clone(), stats);
+        }
+    }
+    Ok(row_group_stats)
+}
+
+/// Whether parquet writers keep the exact bounds of the type in the footer, they may truncate
+/// those of strings and leave NaN out of those of floats.
+fn has_exact_footer_bounds(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Boolean
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::Decimal128(_, _)
+            | DataType::Decimal256(_, _)
+            | DataType::Timestamp(_, _)
+    )
+}
+
+/// Smallest and largest non-null values of the array, `None` for types without an order and
+/// for float arrays holding NaN or infinite values.
+fn min_max(array: &dyn Array) -> Option<(Scalar, Scalar)> {
+    let (min_value, max_value) = match array.data_type() {
+        DataType::Boolean => {
+            let array = array.as_boolean();
+            (
+                Scalar::from(min_boolean(array)?),
+                Scalar::from(max_boolean(array)?),
+            )
+        }
+        DataType::Int16 => {
+            let array = array.as_primitive::<Int16Type>();
+            (Scalar::from(min(array)?), Scalar::from(max(array)?))
+        }
+        DataType::Int32 => {
+            let array = array.as_primitive::<Int32Type>();
+            (Scalar::from(min(array)?), Scalar::from(max(array)?))
+        }
+        DataType::Int64 => {
+            let array = array.as_primitive::<Int64Type>();
+            (Scalar::from(min(array)?), Scalar::from(max(array)?))
+        }
+        DataType::Float32 => {
+            let array = array.as_primitive::<Float32Type>();
+            let (min_value, max_value) = (min(array)?, max(array)?);
+            if !min_value.is_finite() || !max_value.is_finite() {
+                return None;
+            }
+            (Scalar::from(min_value), Scalar::from(max_value))
+        }
+        DataType::Float64 => {
+            let array = array.as_primitive::<Float64Type>();
+            let (min_value, max_value) = (min(array)?, max(array)?);
+            if !min_value.is_finite() || !max_value.is_finite() {
+                return None;
+            }
+            (Scalar::from(min_value), Scalar::from(max_value))
+        }
+        DataType::Utf8 => {
+            let array = array.as_string::<i32>();
+            (
+                Scalar::from(min_string(array)?.to_string()),
+                Scalar::from(max_string(array)?.to_string()),
+            )
+        }
+        DataType::Binary => {
+            let array = array.as_binary::<i32>();
+            (
+                Scalar::from(min_binary(array)?.to_vec()),
+                Scalar::from(max_binary(array)?.to_vec()),
+            )
+        }
+        DataType::Decimal128(precision, scale) => {
+            let array = array.as_primitive::<Decimal128Type>();
+            (
+                Scalar::Decimal128(Some(min(array)?), *precision, *scale),
+                Scalar::Decimal128(Some(max(array)?), *precision, *scale),
+            )
+        }
+        DataType::Decimal256(precision, scale) => {
+            let array = array.as_primitive::<Decimal256Type>();
+            (
+                Scalar::Decimal256(Some(min(array)?), *precision, *scale),
+                Scalar::Decimal256(Some(max(array)?), *precision, *scale),
+            )
+        }
+        DataType::Timestamp(unit, tz) => {
+            // Values are UTC whatever the time zone, so they order as the instants
+            let values = cast(array, &DataType::Int64).ok()?;
+            let values = values.as_primitive::<Int64Type>();
+            let tz = tz.as_ref().map(|tz| tz.to_string());
+            (
+                Scalar::Timestamp(Some(min(values)?), *unit, tz.clone()),
+                Scalar::Timestamp(Some(max(values)?), *unit, tz),
+            )
+        }
+        _ => return None,
+    };
+    Some((min_value, max_value))
+}
+
+/// Returns the relative paths of the data files whose column statistics show that no row can
+/// match all `filters`. Files without statistics for a filtered column are never pruned.
+pub(crate) fn prune_data_files_by_stats(
+    filters: &[Expr],
+    data_files: &[DataFileRecord],
+) -> HashSet<String> {
+    let mut pruned = HashSet::new();
+    if filters.is_empty() {
+        return pruned;
+    }
+    for data_file in data_files {
+        let Some(column_stats) = &data_file.column_stats else {
+            continue;
+        };
+        let file_stats = FileStats {
+            columns: column_stats,
+            record_count: data_file.record_count as u64,
+        };
+        if filters.iter().any(|filter| !file_stats.may_match(filter)) {
+            pruned.insert(data_file.relative_path.clone());
+        }
+    }
+    pruned
+}
+
+/// Returns false only when none of `record_count` rows with the column statistics can satisfy
+/// `filter`.
+pub(crate) fn may_match_stats(
+    columns: &BTreeMap<String, ColumnStats>,
+    record_count: u64,
+    filter: &Expr,
+) -> bool {
+    FileStats {
+        columns,
+        record_count,
+    }
+    .may_match(filter)
+}
+
+/// Returns true only when every one of `record_count` rows with the column statistics
+/// satisfies `filter`.
+pub(crate) fn must_match_stats(
+    columns: &BTreeMap<String, ColumnStats>,
+    record_count: u64,
+    filter: &Expr,
+) -> bool {
+    FileStats {
+        columns,
+        record_count,
+    }
+    .must_match(filter)
+}
+
+struct FileStats<'a> {
+    columns: &'a BTreeMap<String, ColumnStats>,
+    record_count: u64,
+}
+
+impl FileStats<'_> {
+    /// Returns false only when no row of the file can satisfy `filter`.
+    fn may_match(&self, filter: &Expr) -> bool {
+        match filter {
+            Expr::BinaryExpr(binary) => match binary.op {
+                BinaryOp::And => self.may_match(&binary.left) && self.may_match(&binary.right),
+                BinaryOp::Or => self.may_match(&binary.left) || self.may_match(&binary.right),
+                _ => match (binary.left.as_ref(), binary.right.as_ref()) {
+                    (Expr::Column(name), Expr::Literal(value)) => {
+                        self.may_compare(name, binary.op, value)
+                    }
+                    (Expr::Literal(value), Expr::Column(name)) => match flip(binary.op) {
+                        Some(op) => self.may_compare(name, op, value),
+                        None => true,
+                    },
+                    _ => true,
+                },
+            },
+            Expr::InList(in_list) if !in_list.negated && !in_list.list.is_empty() => {
+                let Expr::Column(name) = in_list.expr.as_ref() else {
+                    return true;
+                };
+                in_list.list.iter().any(|item| match item {
+                    Expr::Literal(value) => self.may_compare(name, BinaryOp::Eq, value),
+                    _ => true,
+                })
+            }
+            Expr::IsNull(expr) => match expr.as_ref() {
+                Expr::Column(name) => self
+                    .columns
+                    .get(name)
+                    .is_none_or(|stats| stats.null_count > 0),
+                _ => true,
+            },
+            Expr::IsNotNull(expr) => match expr.as_ref() {
+                Expr::Column(name) => self
+                    .columns
+                    .get(name)
+                    .is_none_or(|stats| stats.null_count < self.record_count),
+                _ => true,
+            },
+            Expr::LikeExpr(like_expr) => match like_expr.column_prefix() {
+                Some((name, prefix)) => self.may_have_prefix(name, prefix),
+                None => true,
+            },
+            _ => true,
+        }
+    }
+
+    /// Returns true only when every row of the file satisfies `filter`.
+    fn must_match(&self, filter: &Expr) -> bool {
+        match filter {
+            Expr::Literal(Scalar::Boolean(Some(true))) => true,
+            Expr::BinaryExpr(binary) => match binary.op {
+                BinaryOp::And => self.must_match(&binary.left) && self.must_match(&binary.right),
+                // Or is null when either side is null, the other side must be known for every row
+                BinaryOp::Or => {
+                    (self.must_match(&binary.left) && self.never_null(&binary.right))
+                        || (self.must_match(&binary.right) && self.never_null(&binary.left))
+                }
+                _ => match (binary.left.as_ref(), binary.right.as_ref()) {
+                    (Expr::Column(name), Expr::Literal(value)) => {
+                        self.must_compare(name, binary.op, value)
+                    }
+                    (Expr::Literal(value), Expr::Column(name)) => match flip(binary.op) {
+                        Some(op) => self.must_compare(name, op, value),
+                        None => false,
+                    },
+                    _ => false,
+                },
+            },
+            Expr::InList(in_list) => {
+                let Expr::Column(name) = in_list.expr.as_ref() else {
+                    return false;
+                };
+                let mut values = in_list.list.iter().map(|item| match item {
+                    Expr::Literal(value) => Some(value),
+                    _ => None,
+                });
+                if in_list.negated {
+                    values.all(|value| {
+                        value.is_some_and(|value| self.must_compare(name, BinaryOp::NotEq, value))
+                    })
+                } else {
+                    values.any(|value| {
+                        value.is_some_and(|value| self.must_compare(name, BinaryOp::Eq, value))
+                    })
+                }
+            }
+            Expr::IsNull(expr) => match expr.as_ref() {
+                Expr::Column(name) => self
+                    .columns
+                    .get(name)
+                    .is_some_and(|stats| stats.null_count >= self.record_count),
+                _ => false,
+            },
+            Expr::IsNotNull(expr) => match expr.as_ref() {
+                Expr::Column(name) => self
+                    .columns
+                    .get(name)
+                    .is_some_and(|stats| stats.null_count == 0),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Whether `filter` evaluates to true or false for every row of the file, never to null.
+    fn never_null(&self, filter: &Expr) -> bool {
+        let no_nulls = |expr: &Expr| match expr {
+            Expr::Column(name) => self
+                .columns
+                .get(name)
+                .is_some_and(|stats| stats.null_count == 0),
+            Expr::Literal(value) => !value.is_null(),
+            _ => false,
+        };
+        match filter {
+            Expr::Literal(value) => !value.is_null(),
+            Expr::BinaryExpr(binary) => match binary.op {
+                BinaryOp::And | BinaryOp::Or => {
+                    self.never_null(&binary.left) && self.never_null(&binary.right)
+                }
+                _ => no_nulls(&binary.left) && no_nulls(&binary.right),
+            },
+            Expr::InList(in_list) => no_nulls(&in_list.expr) && in_list.list.iter().all(no_nulls),
+            Expr::IsNull(_) | Expr::IsNotNull(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether every row satisfies `column op value`, which rules out nulls in the column.
+    fn must_compare(&self, name: &str, op: BinaryOp, value: &Scalar) -> bool {
+        let Some(stats) = self.columns.get(name) else {
+            return false;
+        };
+        if value.is_null() || stats.null_count > 0 {
+            return false;
+        }
+        let (Some(min_value), Some(max_value)) = (&stats.min, &stats.max) else {
+            return false;
+        };
+        let (Some(min_cmp), Some(max_cmp)) =
+            (min_value.partial_cmp(value), max_value.partial_cmp(value))
+        else {
+            return false;
+        };
+        match op {
+            BinaryOp::Eq => min_cmp.is_eq() && max_cmp.is_eq(),
+            BinaryOp::NotEq => min_cmp.is_gt() || max_cmp.is_lt(),
+            BinaryOp::Lt => max_cmp.is_lt(),
+            BinaryOp::LtEq => max_cmp.is_le(),
+            BinaryOp::Gt => min_cmp.is_gt(),
+            BinaryOp::GtEq => min_cmp.is_ge(),
+            _ => false,
+        }
+    }
+
+    /// Whether a row may satisfy `column op value`, comparisons with null never hold.
+    fn may_compare(&self, name: &str, op: BinaryOp, value: &Scalar) -> bool {
+        let Some(stats) = self.columns.get(name) else {
+            return true;
+        };
+        if flip(op).is_none() {
+            return true;
+        }
+        if value.is_null() || stats.null_count >= self.record_count {
+            return false;
+        }
+        let (Some(min_value), Some(max_value)) = (&stats.min, &stats.max) else {
+            return true;
+        };
+        let (Some(min_cmp), Some(max_cmp)) =
+            (min_value.partial_cmp(value), max_value.partial_cmp(value))
+        else {
+            return true;
+        };
+        match op {
+            BinaryOp::Eq => min_cmp.is_le() && max_cmp.is_ge(),
+            BinaryOp::NotEq => !(min_cmp.is_eq() && max_cmp.is_eq()),
+            BinaryOp::Lt => min_cmp.is_lt(),
+            BinaryOp::LtEq => min_cmp.is_le(),
+            BinaryOp::Gt => max_cmp.is_gt(),
+            BinaryOp::GtEq => max_cmp.is_ge(),
+            _ => true,
+        }
+    }
+
+    /// Whether a string of the column may start with `prefix`.
+    fn may_have_prefix(&self, name: &str, prefix: &str) -> bool {
+        let Some(stats) = self.columns.get(name) else {
+            return true;
+        };
+        if stats.null_count >= self.record_count {
+            return false;
+        }
+        let (Some(Scalar::Utf8(Some(min_value))), Some(Scalar::Utf8(Some(max_value)))) =
+            (&stats.min, &stats.max)
+        else {
+            return true;
+        };
+        if max_value.as_str() < prefix {
+            return false;
+        }
+        // Strings starting with the prefix sort before the prefix with its last byte increased
+        let mut upper = prefix.as_bytes().to_vec();
+        while let Some(last) = upper.pop() {
+            if last < u8::MAX {
+                upper.push(last + 1);
+                return min_value.as_bytes() < upper.as_slice();
+            }
+        }
+        true
+    }
+}
+
+/// The operator with its operands swapped, `None` for operators that are not comparisons.
+fn flip(op: BinaryOp) -> Option<BinaryOp> {
+    match op {
+        BinaryOp::Eq => Some(BinaryOp::Eq),
+        BinaryOp::NotEq => Some(BinaryOp::NotEq),
+        BinaryOp::Lt => Some(BinaryOp::Gt),
+        BinaryOp::LtEq => Some(BinaryOp::GtEq),
+        BinaryOp::Gt => Some(BinaryOp::Lt),
+        BinaryOp::GtEq => Some(BinaryOp::LtEq),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::{Float64Array, Int32Array, StringArray};
+    use arrow::datatypes::{Field, Schema};
+
+    use super::*;
+    use crate::expr::{col, lit};
+
+    fn build_data_file(ids: Vec<Option<i32>>, names: Vec<Option<&str>>) -> DataFileRecord {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, true),
+            Field::new("name", DataType::Utf8, true),
+        ]));
+        let record_count = ids.len() as i64;
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int32Array::from(ids)),
+                Arc::new(StringArray::from(names)),
+            ],
+        )
+        .unwrap();
+        let mut builder = ColumnStatsBuilder::new();
+        builder.update(&batch.slice(0, 1));
+        builder.update(&batch.slice(1, batch.num_rows() - 1));
+        DataFileRecord {
+            data_file_id: 1,
+            table_id: 1,
+            relative_path: "1.parquet".to_string(),
+            file_size_bytes: 0,
+            record_count,
+            row_ids: vec![],
+            partition_values: None,
+            checksum: None,
+            column_stats: Some(builder.finish()),
+        }
+    }
+
+    fn may_match(data_file: &DataFileRecord, filter: Expr) -> bool {
+        prune_data_files_by_stats(&[filter], std::slice::from_ref(data_file)).is_empty()
+    }
+
+    #[test]
+    fn test_column_stats_builder() {
+        let data_file = build_data_file(
+            vec![Some(5), None, Some(-3), Some(9)],
+            vec![Some("b"), Some("a"), None, Some("c")],
+        );
+        let stats = data_file.column_stats.unwrap();
+        assert_eq!(
+            stats["id"],
+            ColumnStats {
+                min: Some(Scalar::from(-3)),
+                max: Some(Scalar::from(9)),
+                null_count: 1,
+            }
+        );
+        assert_eq!(
+            stats["name"],
+            ColumnStats {
+                min: Some(Scalar::from("a".to_string())),
+                max: Some(Scalar::from("c".to_string())),
+                null_count: 1,
+            }
+        );
+
+        // NaN leaves the bounds unknown
+        let schema = Arc::new(Schema::new(vec![Field::new("f", DataType::Float64, false)]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![Arc::new(Float64Array::from(vec![1.0, f64::NAN]))],
+        )
+        .unwrap();
+        let mut builder = ColumnStatsBuilder::new();
+        builder.update(&batch);
+        let stats = builder.finish();
+        assert_eq!(stats["f"].min, None);
+        assert_eq!(stats["f"].max, None);
+    }
+
+    #[test]
+    fn test_must_match_stats() {
+        let data_file = build_data_file(
+            vec![Some(10), Some(15), Some(20)],
+            vec![Some("banana"), Some("band"), None],
+        );
+        let stats = data_file.column_stats.as_ref().unwrap();
+        let must_match = |filter: Expr| must_match_stats(stats, 3, &filter);
+
+        assert!(must_match(col("id").gt_eq(lit(10))));
+        assert!(!must_match(col("id").gt(lit(10))));
+        assert!(must_match(lit(21).gt(col("id"))));
+        assert!(must_match(col("id").neq(lit(9))));
+        assert!(!must_match(col("id").neq(lit(15))));
+        assert!(must_match(col("id").in_list(vec![lit(1), lit(30)], true)));
+        assert!(!must_match(col("id").in_list(vec![lit(1), lit(30)], false)));
+        assert!(must_match(col("id").is_not_null()));
+        assert!(must_match(
+            col("id").lt(lit(5)).or(col("id").lt_eq(lit(20)))
+        ));
+        // a null on the other side of an or leaves the row out
+        assert!(!must_match(
+            col("id")
+                .lt_eq(lit(20))
+                .or(col("name").eq(lit("band".to_string())))
+        ));
+        assert!(!must_match(col("id").gt(lit(5)).and(col("id").lt(lit(20)))));
+
+        // nulls never satisfy a comparison
+        assert!(!must_match(col("name").gt_eq(lit("a".to_string()))));
+        assert!(!must_match(col("name").is_not_null()));
+        // a column without stats resolves nothing
+        assert!(!must_match(col("other").is_null()));
+
+        let single_value_file = build_data_file(vec![Some(7), Some(7)], vec![None, None]);
+        let stats = single_value_file.column_stats.as_ref().unwrap();
+        assert!(must_match_stats(stats, 2, &col("id").eq(lit(7))));
+        assert!(must_match_stats(
+            stats,
+            2,
+            &col("id").in_list(vec![lit(1), lit(7)], false)
+        ));
+        assert!(must_match_stats(stats, 2, &col("name").is_null()));
+    }
+
+    #[test]
+    fn test_prune_data_files_by_stats() {
+        let data_file = build_data_file(
+            vec![Some(10), Some(15), Some(20), None],
+            vec![Some("banana"), Some("band"), Some("bar"), None],
+        );
+
+        assert!(may_match(&data_file, col("id").eq(lit(15))));
+        assert!(!may_match(&data_file, col("id").eq(lit(21))));
+        assert!(!may_match(&data_file, lit(10).gt(col("id"))));
+        assert!(may_match(&data_file, lit(10).gt_eq(col("id"))));
+        assert!(!may_match(
+            &data_file,
+            col("id").eq(lit(Scalar::Int32(None)))
+        ));
+        assert!(may_match(
+            &data_file,
+            col("id").in_list(vec![lit(1), lit(12)], false)
+        ));
+        assert!(!may_match(
+            &data_file,
+            col("id").in_list(vec![lit(1), lit(30)], false)
+        ));
+        assert!(may_match(
+            &data_file,
+            col("id").in_list(vec![lit(1), lit(30)], true)
+        ));
+        assert!(may_match(&data_file, col("id").is_null()));
+        assert!(may_match(&data_file, col("id").is_not_null()));
+        assert!(!may_match(
+            &data_file,
+            col("id").lt(lit(5)).or(col("id").gt(lit(25)))
+        ));
+
+        assert!(may_match(
+            &data_file,
+            col("name").like(lit("ban%".to_string()))
+        ));
+        assert!(may_match(
+            &data_file,
+            col("name").like(lit("ba_%".to_string()))
+        ));
+        assert!(!may_match(
+            &data_file,
+            col("name").like(lit("bz%".to_string()))
+        ));
+        assert!(!may_match(
+            &data_file,
+            col("name").like(lit("apple%".to_string()))
+        ));
+        assert!(may_match(
+            &data_file,
+            col("name").not_like(lit("bz%".to_string()))
+        ));
+        assert!(may_match(
+            &data_file,
+            col("name").ilike(lit("BZ%".to_string()))
+        ));
+
+        // a column without stats prunes nothing
+        assert!(may_match(&data_file, col("other").eq(lit(100))));
+
+        let null_file = build_data_file(vec![None, None], vec![None, None]);
+        assert!(may_match(&null_file, col("id").is_null()));
+        assert!(!may_match(&null_file, col("id").is_not_null()));
+        assert!(!may_match(&null_file, col("id").neq(lit(1))));
+        assert!(!may_match(
+            &null_file,
+            col("name").like(lit("a%".to_string()))
+        ));
+
+        let non_null_file = build_data_file(vec![Some(1)], vec![Some("a")]);
+        assert!(!may_match(&non_null_file, col("id").is_null()));
+    }
+}
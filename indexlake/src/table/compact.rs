@@ -0,0 +1,428 @@
+use std::collections::HashMap;
+
+use arrow::array::{Int64Array, RecordBatch};
+use arrow::compute::{concat_batches, sort_to_indices, take_record_batch};
+use futures::TryStreamExt;
+use parquet::arrow::AsyncArrowWriter;
+
+use crate::catalog::{
+    DataFileRecord, INTERNAL_ROW_ID_FIELD_NAME, IndexFileRecord, RowLocation, RowMetadataRecord,
+    Scalar, TransactionHelper,
+};
+use crate::expr::{Expr, col, lit};
+use crate::index::IndexBuilder;
+use crate::storage::{OutputFile, read_parquet_files_by_locations, write_parquet_file};
+use crate::table::{
+    Clustering, ColumnStatsBuilder, Table, check_base_snapshot, cluster_batch, table_index_builders,
+};
+use crate::{ILError, ILResult};
+
+#[derive(Debug, Clone, derive_with::With)]
+pub struct CompactOptions {
+    /// Data files smaller than this many bytes are merged, into files of about this size.
+    pub target_file_size: u64,
+    /// Nothing is rewritten unless at least this many data files are below the target size.
+    pub min_input_files: usize,
+    /// Only data files with a row matching the predicate are rewritten.
+    pub predicate: Option<Expr>,
+    /// Data files merged into one file at most, bounding the files a merge reads at once when
+    /// many tiny files add up to less than the target size.
+    pub max_files_per_group: usize,
+}
+
+impl Default for CompactOptions {
+    fn default() -> Self {
+        Self {
+            target_file_size: 128 * 1024 * 1024,
+            min_input_files: 2,
+            predicate: None,
+            max_files_per_group: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompactReport {
+    /// Data files merged and replaced.
+    pub rewritten_files: usize,
+    /// Data files written by the merges.
+    pub written_files: usize,
+    /// Size of the replaced data files.
+    pub bytes_before: u64,
+    /// Size of the written data files.
+    pub bytes_after: u64,
+    /// Deleted rows left out of the written files.
+    pub dropped_rows: u64,
+}
+
+/// Merges small data files of the table into larger ones. Deleted rows are left out of the
+/// merged files and their row metadata is removed. Clustered tables merge up to
+/// `max_files_per_group` files of a partition at once whatever their size, writing their rows
+/// in clustering order into files of about the target size, so each written file holds a range
+/// of the clustering order. The merged files replace the old ones in the
+/// catalog within the transaction of `tx_helper`, the old files stay in storage so scans that
+/// started before can still read them. Data files committed by other transactions meanwhile are
+/// left as they are. Handles with a base snapshot fail if the table moved past it.
+pub(crate) async fn process_compact(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    options: &CompactOptions,
+) -> ILResult<CompactReport> {
+    if options.target_file_size == 0 {
+        return Err(ILError::InvalidInput(
+            "Compaction target file size must be greater than 0".to_string(),
+        ));
+    }
+    if options.max_files_per_group < 2 {
+        return Err(ILError::InvalidInput(
+            "Compaction max files per group must be at least 2".to_string(),
+        ));
+    }
+    check_base_snapshot(tx_helper, table).await?;
+    let mut report = CompactReport::default();
+
+    let mut data_files = tx_helper.get_data_files(table.table_id).await?;
+    data_files.sort_by_key(|data_file| data_file.data_file_id);
+
+    let non_inline = col("location").neq(lit(RowLocation::Inline.to_string()));
+    let mut file_row_metadatas: HashMap<String, Vec<RowMetadataRecord>> = HashMap::new();
+    for row_metadata in tx_helper
+        .scan_row_metadata(table.table_id, &non_inline)
+        .await?
+    {
+        if let RowLocation::Parquet { relative_path, .. } = &row_metadata.location {
+            file_row_metadatas
+                .entry(relative_path.clone())
+                .or_default()
+                .push(row_metadata);
+        }
+    }
+
+    let mut candidates = Vec::new();
+    for data_file in data_files {
+        if data_file.file_size_bytes as u64 >= options.target_file_size {
+            continue;
+        }
+        let row_metadatas = file_row_metadatas
+            .get(&data_file.relative_path)
+            .map(|row_metadatas| row_metadatas.as_slice())
+            .unwrap_or_default();
+        if let Some(predicate) = &options.predicate
+            && !has_matching_rows(table, row_metadatas, predicate).await?
+        {
+            continue;
+        }
+        candidates.push(data_file);
+    }
+    if candidates.len() < options.min_input_files.max(2) {
+        return Ok(report);
+    }
+
+    // Files of different partitions are never merged together
+    let mut partitions: Vec<Vec<DataFileRecord>> = Vec::new();
+    let mut partition_indexes = HashMap::new();
+    for data_file in candidates {
+        let key = serde_json::to_string(&data_file.partition_values).map_err(|e| {
+            ILError::InternalError(format!("Failed to serialize partition values: {e:?}"))
+        })?;
+        let idx = *partition_indexes.entry(key).or_insert_with(|| {
+            partitions.push(Vec::new());
+            partitions.len() - 1
+        });
+        partitions[idx].push(data_file);
+    }
+
+    let clustered = table.config.clustering != Clustering::None;
+    let mut groups: Vec<Vec<DataFileRecord>> = Vec::new();
+    for partition in partitions {
+        let mut group = Vec::new();
+        let mut group_size = 0;
+        for data_file in partition {
+            group_size += data_file.file_size_bytes as u64;
+            group.push(data_file);
+            if (!clustered && group_size >= options.target_file_size)
+                || group.len() >= options.max_files_per_group
+            {
+                groups.push(std::mem::take(&mut group));
+                group_size = 0;
+            }
+        }
+        groups.push(group);
+    }
+
+    for group in groups {
+        // A single file has nothing to be merged with
+        if group.len() < 2 {
+            continue;
+        }
+        let mut row_metadatas = Vec::new();
+        for data_file in &group {
+            if let Some(file_rows) = file_row_metadatas.remove(&data_file.relative_path) {
+                row_metadatas.extend(file_rows);
+            }
+        }
+        let group_size = group
+            .iter()
+            .map(|data_file| data_file.file_size_bytes as u64)
+            .sum::<u64>();
+        let file_count = if clustered {
+            group_size.div_ceil(options.target_file_size) as usize
+        } else {
+            1
+        };
+        let (written_bytes, dropped_rows) =
+            compact_data_files(tx_helper, table, &group, row_metadatas, file_count).await?;
+        report.rewritten_files += group.len();
+        report.bytes_before += group_size;
+        report.written_files += written_bytes.len();
+        report.bytes_after += written_bytes.iter().sum::<u64>();
+        report.dropped_rows += dropped_rows;
+    }
+    Ok(report)
+}
+
+async fn has_matching_rows(
+    table: &Table,
+    row_metadatas: &[RowMetadataRecord],
+    predicate: &Expr,
+) -> ILResult<bool> {
+    let locations = row_metadatas
+        .iter()
+        .filter(|row_metadata| !row_metadata.deleted)
+        .map(|row_metadata| (row_metadata.row_id, row_metadata.location.clone()))
+        .collect::<Vec<_>>();
+    if locations.is_empty() {
+        return Ok(false);
+    }
+    let batches = read_parquet_files_by_locations(
+        table.storage.clone(),
+        table.schema.clone(),
+        None,
+        locations,
+        Some(predicate.clone()),
+        &table.field_defaults,
+        &table.field_ids(),
+        None,
+    )
+    .await?
+    .try_collect::<Vec<_>>()
+    .await?;
+    Ok(batches.iter().any(|batch| batch.num_rows() > 0))
+}
+
+/// Replaces `data_files` by up to `file_count` files splitting their undeleted rows evenly,
+/// none if all their rows are deleted. Returns the sizes of the written files and the number of
+/// deleted rows left out.
+async fn compact_data_files(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    data_files: &[DataFileRecord],
+    row_metadatas: Vec<RowMetadataRecord>,
+    file_count: usize,
+) -> ILResult<(Vec<u64>, u64)> {
+    let (deleted, live): (Vec<_>, Vec<_>) = row_metadatas
+        .into_iter()
+        .partition(|row_metadata| row_metadata.deleted);
+
+    let mut written_bytes = Vec::new();
+    if !live.is_empty() {
+        let batches = read_parquet_files_by_locations(
+            table.storage.clone(),
+            table.schema.clone(),
+            None,
+            live.iter()
+                .map(|row_metadata| (row_metadata.row_id, row_metadata.location.clone()))
+                .collect(),
+            None,
+            &table.field_defaults,
+            &table.field_ids(),
+            None,
+        )
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+        let batch = concat_batches(&table.schema, &batches)?;
+        if batch.num_rows() != live.len() {
+            return Err(ILError::InternalError(format!(
+                "Read row count mismatch: {} rows read, expected {}",
+                batch.num_rows(),
+                live.len()
+            )));
+        }
+        let batch = match &table.config.clustering {
+            // Keep rows in row id order, as the dump task writes them
+            Clustering::None => {
+                let row_id_idx = table.schema.index_of(INTERNAL_ROW_ID_FIELD_NAME)?;
+                let indices = sort_to_indices(batch.column(row_id_idx), None, None)?;
+                take_record_batch(&batch, &indices)?
+            }
+            clustering => cluster_batch(clustering, batch)?,
+        };
+        let file_rows = batch.num_rows().div_ceil(file_count.max(1));
+        for offset in (0..batch.num_rows()).step_by(file_rows) {
+            let length = file_rows.min(batch.num_rows() - offset);
+            let file_size_bytes = write_compacted_file(
+                tx_helper,
+                table,
+                &batch.slice(offset, length),
+                data_files[0].partition_values.clone(),
+            )
+            .await?;
+            written_bytes.push(file_size_bytes as u64);
+        }
+    }
+
+    let deleted_row_ids = deleted
+        .iter()
+        .map(|row_metadata| row_metadata.row_id)
+        .collect::<Vec<_>>();
+    tx_helper
+        .delete_row_metadatas_by_row_ids(table.table_id, &deleted_row_ids)
+        .await?;
+
+    let data_file_ids = data_files
+        .iter()
+        .map(|data_file| data_file.data_file_id)
+        .collect::<Vec<_>>();
+    tx_helper
+        .delete_index_files_by_data_file_ids(&data_file_ids)
+        .await?;
+    tx_helper.delete_data_files_by_ids(&data_file_ids).await?;
+    Ok((written_bytes, deleted.len() as u64))
+}
+
+/// Writes the rows into a new data file with its index files, returning the size of the file.
+async fn write_compacted_file(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    batch: &RecordBatch,
+    partition_values: Option<Vec<Scalar>>,
+) -> ILResult<usize> {
+    let data_file_id = tx_helper.get_max_data_file_id().await? + 1;
+    // Compaction runs in a catalog transaction re-run on conflicts
+    let relative_path =
+        DataFileRecord::build_unique_relative_path(&table.table_dir(), data_file_id);
+
+    let mut index_builders = table_index_builders(table)?;
+
+    let ((location_map, row_ids), file_size_bytes, checksum) = write_parquet_file(
+        &table.storage,
+        &table.config,
+        &relative_path,
+        table.data_file_schema(),
+        async |arrow_writer| {
+            write_row_groups(
+                arrow_writer,
+                table,
+                &relative_path,
+                batch,
+                &mut index_builders,
+            )
+            .await
+        },
+    )
+    .await?;
+    let mut column_stats_builder = ColumnStatsBuilder::new();
+    column_stats_builder.update(batch);
+    let column_stats = column_stats_builder.finish();
+
+    tx_helper
+        .insert_data_files(
+            &[DataFileRecord {
+                data_file_id,
+                table_id: table.table_id,
+                relative_path,
+                file_size_bytes: file_size_bytes as i64,
+                record_count: row_ids.len() as i64,
+                row_ids,
+                partition_values,
+                checksum: Some(checksum),
+                column_stats: Some(column_stats),
+            }],
+            table.config.catalog_insert_batch_size,
+        )
+        .await?;
+
+    let mut index_file_id = tx_helper.get_max_index_file_id().await? + 1;
+    let mut index_file_records = Vec::new();
+    for (index_name, index_builder) in index_builders.iter_mut() {
+        let index_def = table
+            .indexes
+            .get(index_name)
+            .ok_or_else(|| ILError::InternalError(format!("Index {index_name} not found")))?;
+        let relative_path = IndexFileRecord::build_unique_relative_path(
+            &table.table_dir(),
+            data_file_id,
+            index_def.index_id,
+            index_file_id,
+        );
+        let output_file = table.storage.create_file(&relative_path).await?;
+        index_builder.write(output_file).await?;
+        index_file_records.push(IndexFileRecord {
+            index_file_id,
+            index_id: index_def.index_id,
+            data_file_id,
+            relative_path,
+            metadata: index_builder.file_metadata()?,
+        });
+        index_file_id += 1;
+    }
+    tx_helper.insert_index_files(&index_file_records).await?;
+
+    tx_helper
+        .update_row_locations(table.table_id, &location_map)
+        .await?;
+    Ok(file_size_bytes)
+}
+
+/// Writes the batch to the data file a row group at a time, returning the locations and ids
+/// of the rows.
+async fn write_row_groups(
+    arrow_writer: &mut AsyncArrowWriter<&mut OutputFile>,
+    table: &Table,
+    relative_path: &str,
+    batch: &RecordBatch,
+    index_builders: &mut HashMap<String, Box<dyn IndexBuilder>>,
+) -> ILResult<(HashMap<i64, String>, Vec<i64>)> {
+    let row_id_idx = table.schema.index_of(INTERNAL_ROW_ID_FIELD_NAME)?;
+
+    let mut location_map = HashMap::new();
+    let mut row_ids = Vec::with_capacity(batch.num_rows());
+    let row_group_size = table.config.parquet_row_group_size;
+    let mut offset = 0;
+    let mut row_group_idx = 0;
+    while offset < batch.num_rows() {
+        let length = row_group_size.min(batch.num_rows() - offset);
+        let row_group = batch.slice(offset, length);
+        let row_id_array = row_group
+            .column(row_id_idx)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .ok_or_else(|| ILError::InternalError("Row id column is not int64".to_string()))?;
+        for (row_group_offset, row_id) in row_id_array.values().iter().enumerate() {
+            location_map.insert(
+                *row_id,
+                RowLocation::Parquet {
+                    relative_path: relative_path.to_string(),
+                    row_group_index: row_group_idx,
+                    row_group_offset,
+                }
+                .to_string(),
+            );
+            row_ids.push(*row_id);
+        }
+
+        for (index_name, index_builder) in index_builders.iter_mut() {
+            let index_def = &table.indexes[index_name];
+            index_builder.update(&index_def.indexed_rows(&row_group)?)?;
+        }
+        arrow_writer.write(&row_group).await?;
+        // Each slice is flushed as its own row group, so row group indexes match the locations
+        arrow_writer.flush().await?;
+
+        offset += length;
+        row_group_idx += 1;
+    }
+
+    Ok((location_map, row_ids))
+}
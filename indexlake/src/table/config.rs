@@ -1,16 +1,232 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use parquet::basic::ZstdLevel;
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    ILError, ILResult,
+    storage::DEFAULT_WRITE_PART_SIZE,
+    table::{CheckConstraint, TtlPolicy, UniqueConstraint},
+};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TableConfig {
+    /// Number of inline rows at which they are dumped into a data file.
     pub inline_row_count_limit: usize,
+    /// Size in bytes of the inline rows at which they are dumped into a data file, whatever
+    /// their number. The size is estimated from the Arrow size of the rows of each insert.
+    #[serde(default = "default_inline_byte_limit")]
+    pub inline_byte_limit: usize,
     pub parquet_row_group_size: usize,
+    /// Maximum number of rows written by a single multi-row `INSERT` statement to the catalog.
+    /// Large inserts are split into several statements to stay within statement size and
+    /// parameter limits of the catalog database. Postgres further caps batches at its limit of
+    /// 65535 bound parameters, SQLite binds each row to a prepared statement instead.
+    #[serde(default = "default_catalog_insert_batch_size")]
+    pub catalog_insert_batch_size: usize,
+    /// Compression codec of data files. Tables created before the option existed are
+    /// uncompressed.
+    #[serde(default)]
+    pub compression: Compression,
+    /// Columns identifying a row for [`Table::upsert`](crate::table::Table::upsert). Key
+    /// columns must not be nullable. Plain inserts do not check keys for uniqueness.
+    #[serde(default)]
+    pub primary_key: Vec<String>,
+    /// Columns routing rows into per-partition data files. Data files only hold rows of a single
+    /// partition, scans skip files of partitions ruled out by their filters.
+    #[serde(default)]
+    pub partition_by: Vec<String>,
+    /// How the values of the partition columns map to partitions.
+    #[serde(default)]
+    pub partition_transform: PartitionTransform,
+    /// Order of the rows in the data files written by flushes and compactions, co-locating rows
+    /// with close values of the clustered columns so their files and row groups get narrow
+    /// column stats to prune by.
+    #[serde(default)]
+    pub clustering: Clustering,
+    /// Data files are written without index files, keeping index building off the write path.
+    /// Their index files are built by [`Table::sync_indexes`](crate::table::Table::sync_indexes)
+    /// or by a background task after every automatic dump. Scans read unindexed data files in
+    /// full, so results do not depend on the indexes being in sync.
+    #[serde(default)]
+    pub deferred_indexing: bool,
+    /// Size in bytes of the parts data files are uploaded in. Writing a data file holds about
+    /// one row group and one part in memory. S3 rejects parts below 5 MiB, except the last.
+    #[serde(default = "default_data_file_part_size")]
+    pub data_file_part_size: usize,
+    /// Directory of the data files and index files of the table in the storage,
+    /// `{namespace_id}/{table_id}` if not set. Lets bucket policies and lifecycle rules target
+    /// the files of a table. The directory must not be shared with other tables, vacuum deletes
+    /// the files under it that no table refers to.
+    #[serde(default)]
+    pub storage_prefix: Option<String>,
+    /// How long snapshots stay readable. Every commit expires the snapshots committed longer
+    /// ago along with the row history only they read, keeping history from growing with every
+    /// commit. Snapshots only expire through [`Table::vacuum`](crate::table::Table::vacuum) if
+    /// not set.
+    #[serde(default)]
+    pub snapshot_retention: Option<Duration>,
+    /// Expressions every inserted, upserted or updated row must evaluate to true, see
+    /// [`Table::add_check_constraint`](crate::table::Table::add_check_constraint).
+    #[serde(default)]
+    pub check_constraints: Vec<CheckConstraint>,
+    /// Columns no two rows may hold the same values of, see [`UniqueConstraint`]. Declared at
+    /// creation only.
+    #[serde(default)]
+    pub unique_constraints: Vec<UniqueConstraint>,
+    /// Retention of the rows by a timestamp column, enforced by
+    /// [`Table::expire_rows`](crate::table::Table::expire_rows). Changed with
+    /// [`Table::set_ttl`](crate::table::Table::set_ttl) without rewriting data.
+    #[serde(default)]
+    pub ttl: Option<TtlPolicy>,
+    /// Field ids of the columns of a clone to the field ids they have in the data files shared
+    /// with its source, see [`LakeClient::clone_table`](crate::LakeClient::clone_table). Set by
+    /// cloning only.
+    #[serde(default)]
+    pub file_field_ids: BTreeMap<i64, i64>,
+}
+
+fn default_inline_byte_limit() -> usize {
+    64 * 1024 * 1024
+}
+
+fn default_catalog_insert_batch_size() -> usize {
+    1000
+}
+
+fn default_data_file_part_size() -> usize {
+    DEFAULT_WRITE_PART_SIZE
 }
 
 impl Default for TableConfig {
     fn default() -> Self {
         Self {
             inline_row_count_limit: 10000,
+            inline_byte_limit: default_inline_byte_limit(),
             parquet_row_group_size: 1000,
+            catalog_insert_batch_size: default_catalog_insert_batch_size(),
+            compression: Compression::default(),
+            primary_key: Vec::new(),
+            partition_by: Vec::new(),
+            partition_transform: PartitionTransform::default(),
+            clustering: Clustering::default(),
+            deferred_indexing: false,
+            data_file_part_size: default_data_file_part_size(),
+            storage_prefix: None,
+            snapshot_retention: None,
+            check_constraints: Vec::new(),
+            unique_constraints: Vec::new(),
+            ttl: None,
+            file_field_ids: BTreeMap::new(),
         }
     }
 }
+
+impl TableConfig {
+    /// Directory holding the files of the table in the storage.
+    pub fn table_dir(&self, namespace_id: i64, table_id: i64) -> String {
+        match &self.storage_prefix {
+            Some(prefix) => prefix.clone(),
+            None => format!("{namespace_id}/{table_id}"),
+        }
+    }
+}
+
+/// Storage prefixes are relative paths of `/` separated segments. Quotes and colons are
+/// rejected as they would break the catalog SQL and row locations the paths end up in.
+pub(crate) fn check_storage_prefix(prefix: &str) -> ILResult<()> {
+    let valid_segment = |segment: &str| {
+        !segment.is_empty()
+            && segment != "."
+            && segment != ".."
+            && !segment.contains([':', '\'', '"', '\\'])
+    };
+    if !prefix.split('/').all(valid_segment) {
+        return Err(ILError::InvalidInput(format!(
+            "Invalid storage prefix {prefix:?}, must be a relative path without empty, . or .. segments, quotes, colons or backslashes"
+        )));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PartitionTransform {
+    /// Every distinct combination of partition column values is a partition.
+    #[default]
+    Identity,
+    /// Rows are spread over a fixed number of partitions by the hash of the partition column
+    /// values. Only equality filters on all partition columns prune partitions.
+    Hash { buckets: u32 },
+    /// Partition columns hold timestamps in milliseconds since the Unix epoch, rows are
+    /// partitioned by the UTC hour, day, month or year their timestamps fall in. Partitions are
+    /// identified by the timestamps of their start, range filters on the partition columns
+    /// prune partitions.
+    DateTrunc { unit: DateUnit },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DateUnit {
+    Hour,
+    Day,
+    Month,
+    Year,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Clustering {
+    /// Rows are written in row id order.
+    #[default]
+    None,
+    /// Rows are written in the Z-order of the values of the columns, interleaving the bits of
+    /// their ranks so that rows close in all the columns end up close in the file. Range
+    /// filters on any of the columns prune files, unlike sorting by the first column only.
+    ZOrder(Vec<String>),
+}
+
+impl Clustering {
+    /// Clustered columns, empty without clustering.
+    pub fn columns(&self) -> &[String] {
+        match self {
+            Clustering::None => &[],
+            Clustering::ZOrder(columns) => columns,
+        }
+    }
+
+    pub(crate) fn columns_mut(&mut self) -> &mut [String] {
+        match self {
+            Clustering::None => &mut [],
+            Clustering::ZOrder(columns) => columns,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Compression {
+    #[default]
+    Uncompressed,
+    Snappy,
+    /// Zstd with a compression level between 1 and 22.
+    Zstd {
+        level: i32,
+    },
+    Lz4,
+}
+
+impl Compression {
+    pub(crate) fn to_parquet(self) -> ILResult<parquet::basic::Compression> {
+        Ok(match self {
+            Compression::Uncompressed => parquet::basic::Compression::UNCOMPRESSED,
+            Compression::Snappy => parquet::basic::Compression::SNAPPY,
+            Compression::Zstd { level } => {
+                let level = ZstdLevel::try_new(level).map_err(|_| {
+                    ILError::InvalidInput(format!(
+                        "Invalid zstd compression level {level}, must be between 1 and 22"
+                    ))
+                })?;
+                parquet::basic::Compression::ZSTD(level)
+            }
+            Compression::Lz4 => parquet::basic::Compression::LZ4_RAW,
+        })
+    }
+}
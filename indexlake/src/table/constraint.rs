@@ -0,0 +1,177 @@
+use std::collections::HashSet;
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray, RecordBatch},
+    datatypes::{DataType, Field, Int64Type, Schema},
+};
+use futures::TryStreamExt;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ConstraintViolation, ILError, ILResult,
+    catalog::INTERNAL_ROW_ID_FIELD_NAME,
+    expr::{Expr, visited_columns},
+    table::{Table, TableScan},
+    utils::has_duplicated_items,
+};
+
+/// Name of the constraint reported for nulls in a non-nullable column.
+pub const NOT_NULL_CONSTRAINT: &str = "NOT NULL";
+
+/// Table-level constraint, an expression every row written to the table must evaluate to true
+/// for. Rows it evaluates to false or null for are rejected. Declared in
+/// [`TableConfig::check_constraints`](crate::table::TableConfig::check_constraints) at creation
+/// or added with [`Table::add_check_constraint`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckConstraint {
+    pub name: String,
+    pub expr: Expr,
+    /// The constraint was added without validating the rows the table held, those may violate
+    /// it. Rows written afterwards are checked all the same.
+    #[serde(default)]
+    pub not_valid: bool,
+}
+
+impl CheckConstraint {
+    pub fn new(name: impl Into<String>, expr: Expr) -> Self {
+        Self {
+            name: name.into(),
+            expr,
+            not_valid: false,
+        }
+    }
+}
+
+/// Checks the constraints have unique names and are boolean expressions over the columns of
+/// `schema`.
+pub(crate) fn check_constraint_definitions(
+    schema: &Schema,
+    constraints: &[CheckConstraint],
+) -> ILResult<()> {
+    if has_duplicated_items(constraints.iter().map(|constraint| &constraint.name)) {
+        return Err(ILError::InvalidInput(
+            "Duplicated check constraint names".to_string(),
+        ));
+    }
+    for constraint in constraints {
+        if constraint.name.is_empty() || constraint.name == NOT_NULL_CONSTRAINT {
+            return Err(ILError::InvalidInput(format!(
+                "Invalid check constraint name {:?}",
+                constraint.name
+            )));
+        }
+        for column in visited_columns(&constraint.expr) {
+            if column == INTERNAL_ROW_ID_FIELD_NAME || schema.field_with_name(&column).is_err() {
+                return Err(ILError::InvalidInput(format!(
+                    "Check constraint {} refers to unknown column {column}",
+                    constraint.name
+                )));
+            }
+        }
+        let data_type = constraint.expr.data_type(schema)?;
+        if data_type != DataType::Boolean {
+            return Err(ILError::InvalidInput(format!(
+                "Check constraint {} must be a boolean expression, but got {data_type}",
+                constraint.name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Fails on the first null of `column` if `field` is not nullable. `row_ids` are the ids of the
+/// rows if they already have ones.
+pub(crate) fn enforce_not_null(
+    field: &Field,
+    column: &ArrayRef,
+    row_ids: Option<&ArrayRef>,
+) -> ILResult<()> {
+    if field.is_nullable() || column.null_count() == 0 {
+        return Ok(());
+    }
+    let row_index = (0..column.len())
+        .find(|i| column.is_null(*i))
+        .expect("column has nulls");
+    Err(violation(
+        NOT_NULL_CONSTRAINT,
+        vec![field.name().clone()],
+        row_index,
+        row_ids,
+    ))
+}
+
+/// Fails on the first row of `record` violating one of `constraints`.
+pub(crate) fn enforce_check_constraints(
+    constraints: &[CheckConstraint],
+    record: &RecordBatch,
+) -> ILResult<()> {
+    for constraint in constraints {
+        if let Some(row_index) = first_violating_row(constraint, record)? {
+            let mut columns = visited_columns(&constraint.expr);
+            let mut seen = HashSet::new();
+            columns.retain(|column| seen.insert(column.clone()));
+            return Err(violation(
+                &constraint.name,
+                columns,
+                row_index,
+                record.column_by_name(INTERNAL_ROW_ID_FIELD_NAME),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Scans the rows of the table, failing on the first one violating `constraint`.
+pub(crate) async fn validate_check_constraint(
+    table: &Table,
+    constraint: &CheckConstraint,
+) -> ILResult<()> {
+    let mut stream = table.scan(TableScan::default()).await?;
+    let mut offset = 0;
+    while let Some(batch) = stream.try_next().await? {
+        match enforce_check_constraints(std::slice::from_ref(constraint), &batch) {
+            Ok(()) => offset += batch.num_rows(),
+            Err(ILError::ConstraintViolation(mut violation)) => {
+                violation.row_index += offset;
+                return Err(ILError::ConstraintViolation(violation));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+fn first_violating_row(
+    constraint: &CheckConstraint,
+    record: &RecordBatch,
+) -> ILResult<Option<usize>> {
+    let array = constraint
+        .expr
+        .eval(record)?
+        .into_array(record.num_rows())?;
+    let passed = array.as_boolean_opt().ok_or_else(|| {
+        ILError::InternalError(format!(
+            "Check constraint should return BooleanArray, but got {:?}",
+            array.data_type()
+        ))
+    })?;
+    Ok(passed.iter().position(|passed| passed != Some(true)))
+}
+
+fn violation(
+    constraint: &str,
+    columns: Vec<String>,
+    row_index: usize,
+    row_ids: Option<&ArrayRef>,
+) -> ILError {
+    let row_id = row_ids
+        .and_then(|row_ids| row_ids.as_primitive_opt::<Int64Type>())
+        .map(|row_ids| row_ids.value(row_index));
+    ILError::ConstraintViolation(ConstraintViolation {
+        constraint: constraint.to_string(),
+        columns,
+        row_index,
+        row_id,
+        values: Vec::new(),
+    })
+}
@@ -0,0 +1,84 @@
+use futures::TryStreamExt;
+
+use crate::ILResult;
+use crate::catalog::{CatalogHelper, INTERNAL_ROW_ID_FIELD_NAME};
+use crate::expr::{Expr, merge_filters, split_conjunction_filters, visited_columns};
+use crate::storage::read_parquet_files_by_locations;
+use crate::table::{Table, fully_matching_data_files, prune_data_files};
+
+/// Counts the rows matching `predicate` from the catalog where it can. Inline rows are counted
+/// by the catalog, rows of data files pruned by their partition or statistics are not counted,
+/// rows of data files their partition or statistics show to match entirely are their record
+/// count less their deleted rows. Only the remaining data files are read, with the columns of
+/// the predicate, at the locations of their undeleted rows.
+pub(crate) async fn process_count(table: &Table, predicate: Option<&Expr>) -> ILResult<u64> {
+    let catalog_helper = CatalogHelper::new(table.catalog.clone());
+    let Some(predicate) = predicate else {
+        let count = catalog_helper.count_undeleted_rows(table.table_id).await?;
+        return Ok(count as u64);
+    };
+    let filters = split_conjunction_filters(vec![predicate.clone()]);
+    let mut count = catalog_helper
+        .count_inline_rows(table.table_id, &filters)
+        .await? as u64;
+
+    let data_files = catalog_helper.get_data_files(table.table_id).await?;
+    let pruned_files = prune_data_files(&table.schema, &table.config, &filters, &data_files)?;
+    let matching_files =
+        fully_matching_data_files(&table.schema, &table.config, &filters, &data_files)?;
+
+    let mut matching_row_ids = Vec::new();
+    let mut read_row_ids = Vec::new();
+    for data_file in data_files.iter() {
+        if pruned_files.contains(&data_file.relative_path) {
+            continue;
+        }
+        if matching_files.contains(&data_file.relative_path) {
+            count += data_file.record_count as u64;
+            matching_row_ids.extend_from_slice(&data_file.row_ids);
+        } else {
+            read_row_ids.extend_from_slice(&data_file.row_ids);
+        }
+    }
+    count -= catalog_helper
+        .count_deleted_rows(table.table_id, &matching_row_ids)
+        .await? as u64;
+    if read_row_ids.is_empty() {
+        return Ok(count);
+    }
+
+    let read_locations = catalog_helper
+        .scan_undeleted_row_metadata_by_row_ids(table.table_id, &read_row_ids)
+        .await?
+        .into_iter()
+        .map(|meta| (meta.row_id, meta.location))
+        .collect::<Vec<_>>();
+    if read_locations.is_empty() {
+        return Ok(count);
+    }
+
+    let mut projection = vec![table.schema.index_of(INTERNAL_ROW_ID_FIELD_NAME)?];
+    for column in visited_columns(predicate) {
+        let idx = table.schema.index_of(&column)?;
+        if !projection.contains(&idx) {
+            projection.push(idx);
+        }
+    }
+    let stream = read_parquet_files_by_locations(
+        table.storage.clone(),
+        table.schema.clone(),
+        Some(projection),
+        read_locations,
+        merge_filters(filters),
+        &table.field_defaults,
+        &table.field_ids(),
+        None,
+    )
+    .await?;
+    let batches = stream.try_collect::<Vec<_>>().await?;
+    count += batches
+        .iter()
+        .map(|batch| batch.num_rows() as u64)
+        .sum::<u64>();
+    Ok(count)
+}
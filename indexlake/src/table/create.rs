@@ -3,13 +3,24 @@ use std::{
     sync::Arc,
 };
 
-use arrow::datatypes::{FieldRef, SchemaRef};
+use arrow::datatypes::{DataType, FieldRef, SchemaRef};
+use futures::TryStreamExt;
 
 use crate::{
     ILError, ILResult,
-    catalog::{IndexRecord, TableRecord, TransactionHelper},
-    index::{IndexDefination, IndexParams},
-    table::{Table, TableConfig},
+    catalog::{
+        DataFileRecord, IndexFileRecord, IndexRecord, RowLocation, TableRecord, TransactionHelper,
+    },
+    expr::{Expr, col, lit, visited_columns},
+    index::{Index, IndexDefination, IndexDefinationRef, IndexParams},
+    storage::read_parquet_files_by_locations,
+    table::{
+        SnapshotOperation, Table, TableConfig, check_clustering_columns,
+        check_constraint_definitions, check_partition_columns, check_storage_prefix,
+        check_ttl_policy, check_unique_constraint_definitions, record_snapshot,
+        split_column_default,
+    },
+    utils::has_duplicated_items,
 };
 
 #[derive(Debug, Clone)]
@@ -24,6 +35,45 @@ pub(crate) async fn process_create_table(
     tx_helper: &mut TransactionHelper,
     creation: TableCreation,
 ) -> ILResult<i64> {
+    if creation.config.catalog_insert_batch_size == 0 {
+        return Err(ILError::InvalidInput(
+            "catalog_insert_batch_size must be greater than 0".to_string(),
+        ));
+    }
+    if creation.config.inline_byte_limit == 0 {
+        return Err(ILError::InvalidInput(
+            "inline_byte_limit must be greater than 0".to_string(),
+        ));
+    }
+    if creation.config.data_file_part_size == 0 {
+        return Err(ILError::InvalidInput(
+            "data_file_part_size must be greater than 0".to_string(),
+        ));
+    }
+    creation.config.compression.to_parquet()?;
+    if !creation.config.file_field_ids.is_empty() {
+        return Err(ILError::InvalidInput(
+            "file_field_ids are set by cloning tables only".to_string(),
+        ));
+    }
+    if let Some(prefix) = &creation.config.storage_prefix {
+        check_storage_prefix(prefix)?;
+    }
+    check_primary_key(&creation.schema, &creation.config.primary_key)?;
+    check_partition_columns(&creation.schema, &creation.config)?;
+    check_clustering_columns(&creation.schema, &creation.config.clustering)?;
+    check_column_defaults(&creation.schema)?;
+    check_constraint_definitions(&creation.schema, &creation.config.check_constraints)?;
+    check_unique_constraint_definitions(
+        &creation.schema,
+        &creation.config.unique_constraints,
+        &creation.config.check_constraints,
+    )?;
+    if let Some(ttl) = &creation.config.ttl {
+        check_ttl_policy(&creation.schema, ttl)?;
+    }
+    let has_unique_constraints = !creation.config.unique_constraints.is_empty();
+
     let namespace_id = tx_helper
         .get_namespace_id(&creation.namespace_name)
         .await?
@@ -49,6 +99,7 @@ pub(crate) async fn process_create_table(
             table_name: creation.table_name,
             namespace_id,
             config: creation.config,
+            schema_version: 0,
         })
         .await?;
 
@@ -63,10 +114,52 @@ pub(crate) async fn process_create_table(
     tx_helper
         .create_inline_row_table(table_id, creation.schema.fields())
         .await?;
+    if has_unique_constraints {
+        tx_helper.create_unique_key_table(table_id).await?;
+    }
+
+    let snapshot_id = tx_helper.get_max_snapshot_id().await? + 1;
+    record_snapshot(
+        tx_helper,
+        table_id,
+        snapshot_id,
+        SnapshotOperation::CreateTable,
+    )
+    .await?;
 
     Ok(table_id)
 }
 
+fn check_column_defaults(schema: &SchemaRef) -> ILResult<()> {
+    for field in schema.fields() {
+        if let (field, Some(column_default)) = split_column_default(field)? {
+            column_default.check(&field)?;
+        }
+    }
+    Ok(())
+}
+
+fn check_primary_key(schema: &SchemaRef, primary_key: &[String]) -> ILResult<()> {
+    if has_duplicated_items(primary_key.iter()) {
+        return Err(ILError::InvalidInput(format!(
+            "Duplicated columns in primary key {primary_key:?}"
+        )));
+    }
+    for name in primary_key {
+        let field = schema.field_with_name(name).map_err(|_| {
+            ILError::InvalidInput(format!(
+                "Primary key column {name} not found in table schema"
+            ))
+        })?;
+        if field.is_nullable() {
+            return Err(ILError::InvalidInput(format!(
+                "Primary key column {name} must not be nullable"
+            )));
+        }
+    }
+    Ok(())
+}
+
 #[derive(Debug, Clone)]
 pub struct IndexCreation {
     pub name: String,
@@ -74,6 +167,9 @@ pub struct IndexCreation {
     pub key_columns: Vec<String>,
     pub include_columns: Vec<String>,
     pub params: Arc<dyn IndexParams>,
+    /// Makes a partial index of the rows matching the predicate. Scans use the index only when
+    /// their filters imply the predicate, see [`filters_imply`](crate::expr::filters_imply).
+    pub where_predicate: Option<Expr>,
 }
 
 pub(crate) async fn process_create_index(
@@ -93,6 +189,7 @@ pub(crate) async fn process_create_index(
         key_columns: creation.key_columns.clone(),
         include_columns: creation.include_columns.clone(),
         params: creation.params.clone(),
+        where_predicate: creation.where_predicate.clone(),
     };
 
     let index = table
@@ -100,6 +197,9 @@ pub(crate) async fn process_create_index(
         .get(&creation.kind)
         .ok_or_else(|| ILError::InvalidInput(format!("Index kind {} not found", creation.kind)))?;
     index.supports(&index_def)?;
+    if let Some(predicate) = &creation.where_predicate {
+        check_where_predicate(&table.schema, predicate)?;
+    }
 
     if tx_helper
         .index_name_exists(table.table_id, &creation.name)
@@ -123,17 +223,103 @@ pub(crate) async fn process_create_index(
             key_field_ids,
             include_field_ids,
             params: creation.params.encode()?,
+            where_predicate: creation.where_predicate.clone(),
         })
         .await?;
 
-    // TODO create index file
-    table
-        .indexes
-        .insert(creation.name.clone(), Arc::new(index_def));
+    let index_def = Arc::new(index_def);
+    let index = index.clone();
+    // Data files written before the index was created
+    let data_files = tx_helper.get_data_files(table.table_id).await?;
+    build_index_files(tx_helper, table, &index_def, index.as_ref(), data_files).await?;
+    table.indexes.insert(creation.name.clone(), index_def);
 
     Ok(index_id)
 }
 
+fn check_where_predicate(schema: &SchemaRef, predicate: &Expr) -> ILResult<()> {
+    for column in visited_columns(predicate) {
+        if schema.field_with_name(&column).is_err() {
+            return Err(ILError::InvalidInput(format!(
+                "Column {column} of index predicate {predicate} not found in table schema"
+            )));
+        }
+    }
+    let data_type = predicate.data_type(schema)?;
+    if data_type != DataType::Boolean {
+        return Err(ILError::InvalidInput(format!(
+            "Index predicate {predicate} must be boolean, got {data_type}"
+        )));
+    }
+    Ok(())
+}
+
+/// Indexes `data_files`, one index file per data file as the dump task does for new data files.
+/// Returns the number of index files written.
+pub(crate) async fn build_index_files(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    index_def: &IndexDefinationRef,
+    index: &dyn Index,
+    data_files: Vec<DataFileRecord>,
+) -> ILResult<usize> {
+    let non_inline = col("location").neq(lit(RowLocation::Inline.to_string()));
+    let mut file_locations: HashMap<String, Vec<(i64, RowLocation)>> = HashMap::new();
+    for row_metadata in tx_helper
+        .scan_row_metadata(table.table_id, &non_inline)
+        .await?
+    {
+        if let RowLocation::Parquet { relative_path, .. } = &row_metadata.location {
+            file_locations
+                .entry(relative_path.clone())
+                .or_default()
+                .push((row_metadata.row_id, row_metadata.location));
+        }
+    }
+
+    let mut index_file_id = tx_helper.get_max_index_file_id().await? + 1;
+    let mut index_file_records = Vec::new();
+    for data_file in data_files {
+        let locations = file_locations
+            .remove(&data_file.relative_path)
+            .unwrap_or_default();
+        let mut index_builder = index.builder(index_def)?;
+        let mut batch_stream = read_parquet_files_by_locations(
+            table.storage.clone(),
+            table.schema.clone(),
+            None,
+            locations,
+            None,
+            &table.field_defaults,
+            &table.field_ids(),
+            None,
+        )
+        .await?;
+        while let Some(batch) = batch_stream.try_next().await? {
+            index_builder.update(&index_def.indexed_rows(&batch)?)?;
+        }
+
+        let relative_path = IndexFileRecord::build_relative_path(
+            &table.table_dir(),
+            data_file.data_file_id,
+            index_def.index_id,
+            index_file_id,
+        );
+        let output_file = table.storage.create_file(&relative_path).await?;
+        index_builder.write(output_file).await?;
+        index_file_records.push(IndexFileRecord {
+            index_file_id,
+            index_id: index_def.index_id,
+            data_file_id: data_file.data_file_id,
+            relative_path,
+            metadata: index_builder.file_metadata()?,
+        });
+        index_file_id += 1;
+    }
+    tx_helper.insert_index_files(&index_file_records).await?;
+    Ok(index_file_records.len())
+}
+
 fn field_names_to_ids(field_map: &BTreeMap<i64, FieldRef>, names: &[String]) -> ILResult<Vec<i64>> {
     let mut field_ids = Vec::new();
     for name in names.iter() {
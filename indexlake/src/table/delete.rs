@@ -1,47 +1,70 @@
 use std::sync::Arc;
 
-use arrow::array::{AsArray, BooleanArray, Int64Array};
+use arrow::array::{AsArray, RecordBatch};
+use arrow::compute::filter_record_batch;
 use arrow::datatypes::{Int64Type, SchemaRef};
 use futures::StreamExt;
 
 use crate::catalog::{
-    CatalogSchema, INTERNAL_ROW_ID_FIELD_NAME, RowLocation, Scalar, TransactionHelper,
-    rows_to_record_batch,
+    CatalogSchema, INTERNAL_ROW_ID_FIELD_NAME, Scalar, TransactionHelper, rows_to_record_batch,
 };
-use crate::expr::{Expr, visited_columns};
-use crate::storage::Storage;
+use crate::expr::{Expr, col, lit, split_conjunction_filters, visited_columns};
 use crate::storage::read_parquet_files_by_locations;
-use crate::{ILError, ILResult};
+use crate::table::{
+    Table, assign_index_filters, delete_unique_keys, record_replaced_rows, select_data_file_rows,
+    selected_row_locations,
+};
+use crate::{ILError, ILResult, RecordBatchStream};
 
+/// Deletes the rows matching `condition`, keeping their current version as row history ending
+/// at `snapshot_id`.
 pub(crate) async fn process_delete(
     tx_helper: &mut TransactionHelper,
-    storage: Arc<Storage>,
-    table_id: i64,
-    table_schema: &SchemaRef,
+    table: &Table,
     condition: &Expr,
-) -> ILResult<()> {
+    snapshot_id: i64,
+) -> ILResult<usize> {
+    let table_id = table.table_id;
     if visited_columns(condition) == vec![INTERNAL_ROW_ID_FIELD_NAME] {
-        return process_delete_rows_by_row_id_condition(tx_helper, table_id, condition).await;
+        record_replaced_rows(tx_helper, table, condition.clone(), snapshot_id).await?;
+        return process_delete_rows_by_row_id_condition(tx_helper, table, condition).await;
     }
 
     let inline_row_ids =
-        find_matched_inline_row_ids(tx_helper, table_id, table_schema, condition).await?;
-    let data_file_row_ids =
-        find_matched_data_file_row_ids(tx_helper, storage, table_id, table_schema, condition)
-            .await?;
+        find_matched_inline_row_ids(tx_helper, table_id, &table.schema, condition).await?;
+    let data_file_row_ids = find_matched_data_file_row_ids(tx_helper, table, condition).await?;
 
     let row_ids = [inline_row_ids, data_file_row_ids].concat();
+    process_delete_rows_by_row_ids(tx_helper, table, &row_ids, snapshot_id).await
+}
 
-    // Delete rows by their internal row IDs
-    tx_helper
-        .mark_rows_deleted_by_row_ids(table_id, &row_ids)
+/// Deletes the rows with the given ids, keeping their current version as row history ending at
+/// `snapshot_id`.
+pub(crate) async fn process_delete_rows_by_row_ids(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    row_ids: &[i64],
+    snapshot_id: i64,
+) -> ILResult<usize> {
+    if row_ids.is_empty() {
+        return Ok(0);
+    }
+    let matched =
+        col(INTERNAL_ROW_ID_FIELD_NAME).in_list(row_ids.iter().copied().map(lit).collect(), false);
+    record_replaced_rows(tx_helper, table, matched, snapshot_id).await?;
+
+    // Rows in data files stay there, their row metadata marks them deleted until the files are
+    // rewritten
+    let deleted_count = tx_helper
+        .mark_rows_deleted_by_row_ids(table.table_id, row_ids)
         .await?;
 
     // Directly delete inline rows
     tx_helper
-        .delete_inline_rows_by_row_ids(table_id, &row_ids)
+        .delete_inline_rows_by_row_ids(table.table_id, row_ids)
         .await?;
-    Ok(())
+    delete_unique_keys(tx_helper, table, row_ids).await?;
+    Ok(deleted_count)
 }
 
 pub(crate) async fn find_matched_inline_row_ids(
@@ -50,7 +73,18 @@ pub(crate) async fn find_matched_inline_row_ids(
     table_schema: &SchemaRef,
     condition: &Expr,
 ) -> ILResult<Vec<i64>> {
-    let catalog_schema = Arc::new(CatalogSchema::from_arrow(&table_schema)?);
+    let batch = find_matched_inline_rows(tx_helper, table_id, table_schema, condition).await?;
+    row_ids_of_batch(&batch)
+}
+
+/// Reads the inline rows matching `condition`.
+pub(crate) async fn find_matched_inline_rows(
+    tx_helper: &mut TransactionHelper,
+    table_id: i64,
+    table_schema: &SchemaRef,
+    condition: &Expr,
+) -> ILResult<RecordBatch> {
+    let catalog_schema = Arc::new(CatalogSchema::from_arrow(table_schema)?);
     let rows = tx_helper
         .scan_inline_rows(table_id, &catalog_schema)
         .await?;
@@ -65,92 +99,95 @@ pub(crate) async fn find_matched_inline_row_ids(
             array.data_type()
         ))
     })?;
+    Ok(filter_record_batch(&record_batch, bool_array)?)
+}
 
+pub(crate) async fn find_matched_data_file_row_ids(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    condition: &Expr,
+) -> ILResult<Vec<i64>> {
+    let mut stream = read_matched_data_file_rows(tx_helper, table, condition).await?;
     let mut row_ids = Vec::new();
-    for (i, v) in bool_array.iter().enumerate() {
-        if let Some(v) = v
-            && v
-        {
-            row_ids.push(
-                rows[i]
-                    .int64(0)?
-                    .expect("Internal row ID should not be null"),
-            );
-        }
+    while let Some(batch) = stream.next().await {
+        row_ids.extend(row_ids_of_batch(&batch?)?);
     }
     Ok(row_ids)
 }
 
-pub(crate) async fn find_matched_data_file_row_ids(
+/// Reads the rows of data files matching `condition`. Only the data files whose statistics may
+/// match are read, and only the rows selected by the indexes supporting the condition.
+pub(crate) async fn read_matched_data_file_rows(
     tx_helper: &mut TransactionHelper,
-    storage: Arc<Storage>,
-    table_id: i64,
-    table_schema: &SchemaRef,
+    table: &Table,
     condition: &Expr,
-) -> ILResult<Vec<i64>> {
+) -> ILResult<RecordBatchStream> {
+    let filters = split_conjunction_filters(vec![condition.clone()]);
+    let index_filter_assignment =
+        assign_index_filters(&table.indexes, &table.index_kinds, &filters)?;
+    let data_files = tx_helper.get_data_files(table.table_id).await?;
+    let index_files = tx_helper.get_index_files(table.table_id).await?;
+    let file_row_ids = select_data_file_rows(
+        table,
+        data_files,
+        index_files,
+        &filters,
+        &index_filter_assignment,
+    )
+    .await?;
+    if file_row_ids.is_empty() {
+        return Ok(Box::pin(futures::stream::empty()));
+    }
+
     let row_metadata_condition =
         Expr::Column("deleted".to_string()).eq(Expr::Literal(Scalar::Boolean(Some(false))));
     let row_metadatas = tx_helper
-        .scan_row_metadata(table_id, &row_metadata_condition)
+        .scan_row_metadata(table.table_id, &row_metadata_condition)
         .await?;
-    let data_file_locations = row_metadatas
-        .into_iter()
-        .filter(|meta| matches!(meta.location, RowLocation::Parquet { .. }))
-        .map(|meta| meta.location)
-        .collect::<Vec<_>>();
-
-    let mut stream = read_parquet_files_by_locations(
-        storage,
-        table_schema.clone(),
+    let data_file_locations = selected_row_locations(row_metadatas, &file_row_ids);
+
+    // Batches are filtered by the condition while read
+    read_parquet_files_by_locations(
+        table.storage.clone(),
+        table.schema.clone(),
         None,
         data_file_locations,
         Some(condition.clone()),
+        &table.field_defaults,
+        &table.field_ids(),
+        None,
     )
-    .await?;
-
-    let mut row_ids = Vec::new();
-    while let Some(batch) = stream.next().await {
-        let batch = batch?;
+    .await
+}
 
-        let array = condition.eval(&batch)?.into_array(batch.num_rows())?;
-        let bool_array = array.as_boolean_opt().ok_or_else(|| {
+pub(crate) fn row_ids_of_batch(batch: &RecordBatch) -> ILResult<Vec<i64>> {
+    let row_id_array = batch
+        .column(0)
+        .as_primitive_opt::<Int64Type>()
+        .ok_or_else(|| {
             ILError::InternalError(format!(
-                "condition should return BooleanArray, but got {:?}",
-                array.data_type()
+                "row id array should be Int64Array, but got {:?}",
+                batch.column(0).data_type()
             ))
         })?;
-
-        let row_id_array = batch
-            .column(0)
-            .as_primitive_opt::<Int64Type>()
-            .ok_or_else(|| {
-                ILError::InternalError(format!(
-                    "row id array should be Int64Array, but got {:?}",
-                    batch.column(0).data_type()
-                ))
-            })?;
-
-        for (i, v) in bool_array.iter().enumerate() {
-            if let Some(v) = v
-                && v
-            {
-                row_ids.push(row_id_array.value(i));
-            }
-        }
-    }
-    Ok(row_ids)
+    Ok(row_id_array.values().to_vec())
 }
 
 pub(crate) async fn process_delete_rows_by_row_id_condition(
     tx_helper: &mut TransactionHelper,
-    table_id: i64,
+    table: &Table,
     row_id_condition: &Expr,
-) -> ILResult<()> {
-    tx_helper
-        .mark_rows_deleted_by_condition(table_id, row_id_condition)
+) -> ILResult<usize> {
+    let deleted_count = tx_helper
+        .mark_rows_deleted_by_condition(table.table_id, row_id_condition)
         .await?;
     tx_helper
-        .delete_inline_rows_by_condition(table_id, row_id_condition)
+        .delete_inline_rows_by_condition(table.table_id, row_id_condition)
         .await?;
-    Ok(())
+    if !table.config.unique_constraints.is_empty() {
+        tx_helper
+            .delete_unique_keys_by_condition(table.table_id, row_id_condition)
+            .await?;
+    }
+    Ok(deleted_count)
 }
@@ -1,18 +1,46 @@
+use std::sync::Arc;
+
 use crate::ILResult;
-use crate::catalog::TransactionHelper;
+use crate::catalog::{Catalog, TransactionHelper};
+use crate::storage::Storage;
+use crate::table::TableConfig;
 
 pub(crate) async fn process_table_drop(
     tx_helper: &mut TransactionHelper,
     table_id: i64,
+    config: &TableConfig,
 ) -> ILResult<()> {
     tx_helper.drop_row_metadata_table(table_id).await?;
     tx_helper.drop_inline_row_table(table_id).await?;
+    if !config.unique_constraints.is_empty() {
+        tx_helper.drop_unique_key_table(table_id).await?;
+    }
 
+    tx_helper.delete_all_index_files(table_id).await?;
+    tx_helper.delete_all_indexes(table_id).await?;
     tx_helper.delete_all_data_files(table_id).await?;
+    tx_helper.delete_all_row_histories(table_id).await?;
+    tx_helper.delete_all_snapshots(table_id).await?;
     tx_helper.delete_fields(table_id).await?;
     tx_helper.delete_table(table_id).await?;
 
-    // TODO clean up index data
+    Ok(())
+}
 
+/// Deletes the files under the directory of a dropped table, except those clones of the table
+/// refer to. Files ingested in place live outside of it and are left to their owners.
+pub(crate) async fn process_table_purge(
+    catalog: &Arc<dyn Catalog>,
+    storage: &Storage,
+    table_dir: &str,
+) -> ILResult<()> {
+    let mut tx_helper = TransactionHelper::new(catalog).await?;
+    let shared = tx_helper.get_referenced_paths(table_dir).await?;
+    tx_helper.commit().await?;
+    for file in storage.list_files(table_dir).await? {
+        if !shared.contains(&file.relative_path) {
+            storage.delete(&file.relative_path).await?;
+        }
+    }
     Ok(())
 }
@@ -1,46 +1,48 @@
-use std::{collections::HashMap, sync::Arc, time::Instant};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+    time::Instant,
+};
 
-use arrow::datatypes::SchemaRef;
+use arrow::{array::RecordBatch, datatypes::SchemaRef};
 use futures::{StreamExt, TryStreamExt};
 use log::{debug, error};
-use parquet::{arrow::AsyncArrowWriter, file::properties::WriterProperties};
+use parquet::arrow::AsyncArrowWriter;
 
 use crate::{
     ILError, ILResult,
     catalog::{
-        Catalog, CatalogSchema, DataFileRecord, IndexFileRecord, Row, RowStream, TransactionHelper,
-        rows_to_record_batch,
+        Catalog, CatalogSchema, ColumnStats, DataFileRecord, IndexFileRecord, Row, RowStream,
+        Scalar, TransactionHelper, rows_to_record_batch,
     },
     index::{Index, IndexBuilder, IndexDefination, IndexDefinationRef},
-    storage::Storage,
-    table::{Table, TableConfig},
+    storage::{OutputFile, Storage, write_parquet_file},
+    table::{
+        Clustering, ColumnStatsBuilder, Table, TableConfig, cluster_rows, group_rows_by_partition,
+        run_index_sync_task,
+    },
 };
 
-pub(crate) async fn spawn_dump_task(table: &Table) -> ILResult<()> {
+/// Dumps up to `inline_row_count_limit` inline rows into a data file in a spawned task, if there
+/// are at least `min_row_count` of them. The task then syncs the indexes of tables with
+/// deferred indexing.
+pub(crate) async fn spawn_dump_task(table: &Table, min_row_count: usize) -> ILResult<()> {
     let mut tx_helper = TransactionHelper::new(&table.catalog).await?;
     let dump_row_ids = tx_helper
         .scan_inline_row_ids_with_limit(table.table_id, table.config.inline_row_count_limit)
         .await?;
     tx_helper.commit().await?;
-    if dump_row_ids.len() < table.config.inline_row_count_limit {
+    if dump_row_ids.is_empty() || dump_row_ids.len() < min_row_count {
         debug!(
             "Table {} has less than {} inline rows, skip dump",
-            table.table_id, table.config.inline_row_count_limit
+            table.table_id, min_row_count
         );
         return Ok(());
     }
 
-    let dump_task = DumpTask {
-        namespace_id: table.namespace_id,
-        table_id: table.table_id,
-        table_schema: table.schema.clone(),
-        table_indexes: table.indexes.clone(),
-        index_kinds: table.index_kinds.clone(),
-        table_config: table.config.clone(),
-        catalog: table.catalog.clone(),
-        storage: table.storage.clone(),
-        dump_row_ids,
-    };
+    let dump_task = DumpTask::new(table, dump_row_ids);
+    // Tables with deferred indexing get the dumped data file indexed right after
+    let sync_table = table.config.deferred_indexing.then(|| table.clone());
     tokio::spawn(async move {
         let now = Instant::now();
         if let Err(e) = dump_task.run().await {
@@ -51,14 +53,32 @@ pub(crate) async fn spawn_dump_task(table: &Table) -> ILResult<()> {
             dump_task.table_id,
             now.elapsed().as_millis()
         );
+        if let Some(table) = sync_table
+            && let Err(e) = run_index_sync_task(&table).await
+        {
+            error!("Failed to sync indexes of table: {:?}", e);
+        }
     });
     Ok(())
 }
 
+/// Average size in bytes of the rows of `record`, at least 1.
+pub(crate) fn estimated_row_size(record: &RecordBatch) -> ILResult<usize> {
+    if record.num_rows() == 0 {
+        return Ok(1);
+    }
+    let mut size = 0;
+    for column in record.columns() {
+        size += column.to_data().get_slice_memory_size()?;
+    }
+    Ok((size / record.num_rows()).max(1))
+}
+
 pub(crate) struct DumpTask {
-    namespace_id: i64,
+    table_dir: String,
     table_id: i64,
     table_schema: SchemaRef,
+    data_file_schema: SchemaRef,
     table_indexes: HashMap<String, IndexDefinationRef>,
     index_kinds: HashMap<String, Arc<dyn Index>>,
     table_config: Arc<TableConfig>,
@@ -67,7 +87,34 @@ pub(crate) struct DumpTask {
     dump_row_ids: Vec<i64>,
 }
 
+struct DumpFile {
+    data_file_id: i64,
+    relative_path: String,
+    partition_values: Option<Vec<Scalar>>,
+    location_map: HashMap<i64, String>,
+    file_size_bytes: usize,
+    checksum: u32,
+    row_ids: Vec<i64>,
+    column_stats: BTreeMap<String, ColumnStats>,
+    index_builders: HashMap<String, Box<dyn IndexBuilder>>,
+}
+
 impl DumpTask {
+    pub(crate) fn new(table: &Table, dump_row_ids: Vec<i64>) -> Self {
+        Self {
+            table_dir: table.table_dir(),
+            table_id: table.table_id,
+            table_schema: table.schema.clone(),
+            data_file_schema: table.data_file_schema(),
+            table_indexes: table.indexes.clone(),
+            index_kinds: table.index_kinds.clone(),
+            table_config: table.config.clone(),
+            catalog: table.catalog.clone(),
+            storage: table.storage.clone(),
+            dump_row_ids,
+        }
+    }
+
     async fn run(&self) -> ILResult<()> {
         let mut tx_helper = TransactionHelper::new(&self.catalog).await?;
         if tx_helper.insert_dump_task(self.table_id).await.is_err() {
@@ -75,6 +122,18 @@ impl DumpTask {
             return Ok(());
         }
 
+        self.dump(&mut tx_helper).await?;
+
+        tx_helper.delete_dump_task(self.table_id).await?;
+
+        tx_helper.commit().await?;
+
+        Ok(())
+    }
+
+    /// Writes the dumped rows into data files and moves them there within the transaction of
+    /// `tx_helper`, which must hold the dump task of the table. Returns the paths of the files.
+    pub(crate) async fn dump(&self, tx_helper: &mut TransactionHelper) -> ILResult<Vec<String>> {
         let data_file_id = tx_helper.get_max_data_file_id().await? + 1;
 
         let catalog_schema = Arc::new(CatalogSchema::from_arrow(&self.table_schema)?);
@@ -82,22 +141,34 @@ impl DumpTask {
             .scan_inline_rows_by_row_ids(self.table_id, &catalog_schema, &self.dump_row_ids)
             .await?;
 
-        let relative_path =
-            DataFileRecord::build_relative_path(self.namespace_id, self.table_id, data_file_id);
-
-        let mut index_builders = HashMap::new();
-        for (index_name, index_def) in self.table_indexes.iter() {
-            let index_kind = self.index_kinds.get(&index_def.kind).ok_or_else(|| {
-                ILError::InternalError(format!("Index kind {} not found", index_def.kind))
-            })?;
-            let index_builder = index_kind.builder(index_def)?;
-            index_builders.insert(index_name.clone(), index_builder);
+        // Partitioned tables get a data file per partition, clustered tables reorder the rows
+        // of each file
+        let mut dump_files = Vec::new();
+        if self.table_config.partition_by.is_empty()
+            && self.table_config.clustering == Clustering::None
+        {
+            dump_files.push(self.write_dump_file(row_stream, data_file_id, None).await?);
+        } else {
+            let rows = row_stream.try_collect::<Vec<_>>().await?;
+            let groups = if self.table_config.partition_by.is_empty() {
+                vec![(None, rows)]
+            } else {
+                group_rows_by_partition(&self.table_schema, &self.table_config, rows)?
+            };
+            for (i, (partition_values, rows)) in groups.into_iter().enumerate() {
+                let rows = cluster_rows(&self.table_schema, &self.table_config.clustering, rows)?;
+                let row_stream = Box::pin(futures::stream::iter(rows.into_iter().map(Ok)));
+                dump_files.push(
+                    self.write_dump_file(row_stream, data_file_id + i as i64, partition_values)
+                        .await?,
+                );
+            }
         }
 
-        let (location_map, file_size_bytes, record_count) = self
-            .write_dump_file(row_stream, &relative_path, &mut index_builders)
-            .await?;
-
+        let record_count = dump_files
+            .iter()
+            .map(|dump_file| dump_file.row_ids.len())
+            .sum::<usize>();
         if record_count != self.dump_row_ids.len() {
             return Err(ILError::InternalError(format!(
                 "Read row count mismatch: {} rows read, expected {}",
@@ -106,28 +177,58 @@ impl DumpTask {
             )));
         }
 
+        let mut relative_paths = Vec::with_capacity(dump_files.len());
+        for dump_file in dump_files {
+            relative_paths.push(dump_file.relative_path.clone());
+            self.register_dump_file(tx_helper, dump_file).await?;
+        }
+
+        let deleted_count = tx_helper
+            .delete_inline_rows_by_row_ids(self.table_id, &self.dump_row_ids)
+            .await?;
+        if deleted_count != self.dump_row_ids.len() {
+            return Err(ILError::InternalError(format!(
+                "Delete row count mismatch: {} inline rows deleted, expected {}",
+                deleted_count,
+                self.dump_row_ids.len()
+            )));
+        }
+
+        Ok(relative_paths)
+    }
+
+    async fn register_dump_file(
+        &self,
+        tx_helper: &mut TransactionHelper,
+        mut dump_file: DumpFile,
+    ) -> ILResult<()> {
         tx_helper
-            .insert_data_files(&[DataFileRecord {
-                data_file_id,
-                table_id: self.table_id,
-                relative_path,
-                file_size_bytes: file_size_bytes as i64,
-                record_count: record_count as i64,
-                row_ids: self.dump_row_ids.clone(),
-            }])
+            .insert_data_files(
+                &[DataFileRecord {
+                    data_file_id: dump_file.data_file_id,
+                    table_id: self.table_id,
+                    relative_path: dump_file.relative_path,
+                    file_size_bytes: dump_file.file_size_bytes as i64,
+                    record_count: dump_file.row_ids.len() as i64,
+                    row_ids: dump_file.row_ids,
+                    partition_values: dump_file.partition_values,
+                    checksum: Some(dump_file.checksum),
+                    column_stats: Some(dump_file.column_stats),
+                }],
+                self.table_config.catalog_insert_batch_size,
+            )
             .await?;
 
         let mut index_file_id = tx_helper.get_max_index_file_id().await? + 1;
         let mut index_file_records = Vec::new();
-        for (index_name, index_builder) in index_builders.iter_mut() {
+        for (index_name, index_builder) in dump_file.index_builders.iter_mut() {
             let index_def = self
                 .table_indexes
                 .get(index_name)
                 .ok_or_else(|| ILError::InternalError(format!("Index {index_name} not found")))?;
             let relative_path = IndexFileRecord::build_relative_path(
-                self.namespace_id,
-                self.table_id,
-                data_file_id,
+                &self.table_dir,
+                dump_file.data_file_id,
                 index_def.index_id,
                 index_file_id,
             );
@@ -136,8 +237,9 @@ impl DumpTask {
             index_file_records.push(IndexFileRecord {
                 index_file_id,
                 index_id: index_def.index_id,
-                data_file_id,
+                data_file_id: dump_file.data_file_id,
                 relative_path,
+                metadata: index_builder.file_metadata()?,
             });
             index_file_id += 1;
         }
@@ -145,49 +247,78 @@ impl DumpTask {
         tx_helper.insert_index_files(&index_file_records).await?;
 
         tx_helper
-            .update_row_locations(self.table_id, &location_map)
+            .update_row_locations(self.table_id, &dump_file.location_map)
             .await?;
+        Ok(())
+    }
 
-        let deleted_count = tx_helper
-            .delete_inline_rows_by_row_ids(self.table_id, &self.dump_row_ids)
-            .await?;
-        if deleted_count != self.dump_row_ids.len() {
-            return Err(ILError::InternalError(format!(
-                "Delete row count mismatch: {} inline rows deleted, expected {}",
-                deleted_count,
-                self.dump_row_ids.len()
-            )));
-        }
+    async fn write_dump_file(
+        &self,
+        row_stream: RowStream<'_>,
+        data_file_id: i64,
+        partition_values: Option<Vec<Scalar>>,
+    ) -> ILResult<DumpFile> {
+        let relative_path = DataFileRecord::build_relative_path(&self.table_dir, data_file_id);
 
-        tx_helper.delete_dump_task(self.table_id).await?;
+        // With deferred indexing the data file is indexed when the indexes are synced
+        let deferred = self.table_config.deferred_indexing;
+        let mut index_builders = HashMap::new();
+        for (index_name, index_def) in self.table_indexes.iter().filter(|_| !deferred) {
+            let index_kind = self.index_kinds.get(&index_def.kind).ok_or_else(|| {
+                ILError::InternalError(format!("Index kind {} not found", index_def.kind))
+            })?;
+            let index_builder = index_kind.builder(index_def)?;
+            index_builders.insert(index_name.clone(), index_builder);
+        }
 
-        tx_helper.commit().await?;
+        let mut column_stats_builder = ColumnStatsBuilder::new();
+        let ((location_map, row_ids), file_size_bytes, checksum) = write_parquet_file(
+            &self.storage,
+            &self.table_config,
+            &relative_path,
+            self.data_file_schema.clone(),
+            async |arrow_writer| {
+                self.write_rows(
+                    arrow_writer,
+                    &relative_path,
+                    row_stream,
+                    &mut index_builders,
+                    &mut column_stats_builder,
+                )
+                .await
+            },
+        )
+        .await?;
 
-        Ok(())
+        Ok(DumpFile {
+            data_file_id,
+            relative_path,
+            partition_values,
+            location_map,
+            file_size_bytes,
+            checksum,
+            row_ids,
+            column_stats: column_stats_builder.finish(),
+            index_builders,
+        })
     }
 
-    async fn write_dump_file(
+    /// Writes the rows to the data file, returning the locations and ids of the rows. The
+    /// written rows are added to the index and column stats builders.
+    async fn write_rows(
         &self,
-        row_stream: RowStream<'_>,
+        arrow_writer: &mut AsyncArrowWriter<&mut OutputFile>,
         relative_path: &str,
+        row_stream: RowStream<'_>,
         index_builders: &mut HashMap<String, Box<dyn IndexBuilder>>,
-    ) -> ILResult<(HashMap<i64, String>, usize, usize)> {
+        column_stats_builder: &mut ColumnStatsBuilder,
+    ) -> ILResult<(HashMap<i64, String>, Vec<i64>)> {
         let mut location_map = HashMap::new();
-
-        let writer_properties = WriterProperties::builder()
-            .set_max_row_group_size(self.table_config.parquet_row_group_size)
-            .build();
-        let output_file = self.storage.create_file(relative_path).await?;
-        let mut arrow_writer = AsyncArrowWriter::try_new(
-            output_file,
-            self.table_schema.clone(),
-            Some(writer_properties),
-        )?;
+        let mut row_ids = Vec::new();
 
         let mut chunk_stream = row_stream.chunks(self.table_config.parquet_row_group_size);
 
         let mut row_group_idx = 0;
-        let mut record_count = 0;
         while let Some(row_chunk) = chunk_stream.next().await {
             let mut rows = Vec::with_capacity(row_chunk.len());
             for (row_group_offset, row) in row_chunk.into_iter().enumerate() {
@@ -200,23 +331,22 @@ impl DumpTask {
                         relative_path, row_group_idx, row_group_offset
                     ),
                 );
+                row_ids.push(row_id);
                 rows.push(row);
             }
             let record_batch = rows_to_record_batch(&self.table_schema, &rows)?;
 
-            for (_index_name, index_builder) in index_builders.iter_mut() {
-                index_builder.update(&record_batch)?;
+            for (index_name, index_builder) in index_builders.iter_mut() {
+                let index_def = &self.table_indexes[index_name];
+                index_builder.update(&index_def.indexed_rows(&record_batch)?)?;
             }
+            column_stats_builder.update(&record_batch);
 
             arrow_writer.write(&record_batch).await?;
 
-            record_count += record_batch.num_rows();
             row_group_idx += 1;
         }
 
-        let file_size_bytes = arrow_writer.bytes_written();
-        arrow_writer.close().await?;
-
-        Ok((location_map, file_size_bytes, record_count))
+        Ok((location_map, row_ids))
     }
 }
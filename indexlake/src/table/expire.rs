@@ -0,0 +1,139 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use arrow::datatypes::{DataType, Schema};
+use serde::{Deserialize, Serialize};
+
+use crate::catalog::{INTERNAL_ROW_ID_FIELD_NAME, RowLocation, TransactionHelper};
+use crate::expr::{col, lit};
+use crate::table::{
+    Table, delete_unique_keys, fully_matching_data_files, process_delete, record_replaced_rows,
+};
+use crate::{ILError, ILResult};
+
+/// Retention of the rows of a table, see [`Table::expire_rows`]. Rows whose timestamp is older
+/// than `retention` are expired, rows with a null timestamp never are.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TtlPolicy {
+    /// Int64 column of timestamps in milliseconds since the Unix epoch.
+    pub column: String,
+    pub retention: Duration,
+}
+
+impl TtlPolicy {
+    pub fn new(column: impl Into<String>, retention: Duration) -> Self {
+        Self {
+            column: column.into(),
+            retention,
+        }
+    }
+}
+
+pub(crate) fn check_ttl_policy(schema: &Schema, ttl: &TtlPolicy) -> ILResult<()> {
+    let field = schema
+        .field_with_name(&ttl.column)
+        .ok()
+        .filter(|_| ttl.column != INTERNAL_ROW_ID_FIELD_NAME)
+        .ok_or_else(|| {
+            ILError::InvalidInput(format!("TTL column {} not found in schema", ttl.column))
+        })?;
+    if field.data_type() != &DataType::Int64 {
+        return Err(ILError::InvalidInput(format!(
+            "TTL column {} must be Int64 timestamps in milliseconds, got {}",
+            ttl.column,
+            field.data_type()
+        )));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExpireReport {
+    /// Rows expired, in dropped data files or deleted one by one.
+    pub removed_rows: u64,
+    /// Data files dropped as all their rows expired.
+    pub removed_files: usize,
+    /// Size of the dropped data files. They stay in storage for earlier snapshots until
+    /// vacuumed.
+    pub removed_bytes: u64,
+}
+
+/// Expires the rows older than the TTL of the table. Data files whose partition or column
+/// statistics show every row expired are dropped from the catalog without being read, like
+/// [`Table::truncate`] drops all of them, the remaining expired rows are deleted.
+pub(crate) async fn process_expire_rows(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    snapshot_id: i64,
+) -> ILResult<ExpireReport> {
+    let ttl = table.config.ttl.as_ref().ok_or_else(|| {
+        ILError::InvalidInput(format!("Table {} has no TTL policy", table.table_name))
+    })?;
+    let cutoff = SystemTime::now()
+        .checked_sub(ttl.retention)
+        .unwrap_or(UNIX_EPOCH)
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64;
+    let condition = col(&ttl.column).lt(lit(cutoff));
+    let mut report = ExpireReport::default();
+
+    let data_files = tx_helper.get_data_files(table.table_id).await?;
+    let expired_paths = fully_matching_data_files(
+        &table.schema,
+        &table.config,
+        std::slice::from_ref(&condition),
+        &data_files,
+    )?;
+    let expired_files = data_files
+        .into_iter()
+        .filter(|data_file| expired_paths.contains(&data_file.relative_path))
+        .collect::<Vec<_>>();
+    if !expired_files.is_empty() {
+        let non_inline = col("location").neq(lit(RowLocation::Inline.to_string()));
+        let mut row_ids = Vec::new();
+        let mut undeleted_row_ids = Vec::new();
+        for meta in tx_helper
+            .scan_row_metadata(table.table_id, &non_inline)
+            .await?
+        {
+            if let RowLocation::Parquet { relative_path, .. } = &meta.location
+                && expired_paths.contains(relative_path)
+            {
+                row_ids.push(meta.row_id);
+                if !meta.deleted {
+                    undeleted_row_ids.push(meta.row_id);
+                }
+            }
+        }
+
+        // Earlier snapshots keep reading the rows from the row history
+        if !undeleted_row_ids.is_empty() {
+            let expired = col(INTERNAL_ROW_ID_FIELD_NAME)
+                .in_list(undeleted_row_ids.iter().copied().map(lit).collect(), false);
+            report.removed_rows +=
+                record_replaced_rows(tx_helper, table, expired, snapshot_id).await? as u64;
+            delete_unique_keys(tx_helper, table, &undeleted_row_ids).await?;
+        }
+        tx_helper
+            .delete_row_metadatas_by_row_ids(table.table_id, &row_ids)
+            .await?;
+
+        let data_file_ids = expired_files
+            .iter()
+            .map(|data_file| data_file.data_file_id)
+            .collect::<Vec<_>>();
+        tx_helper
+            .delete_index_files_by_data_file_ids(&data_file_ids)
+            .await?;
+        tx_helper.delete_data_files_by_ids(&data_file_ids).await?;
+        report.removed_files = expired_files.len();
+        report.removed_bytes = expired_files
+            .iter()
+            .map(|data_file| data_file.file_size_bytes as u64)
+            .sum();
+    }
+
+    // Rows of the dropped files are gone from the row metadata, only other rows are left
+    report.removed_rows += process_delete(tx_helper, table, &condition, snapshot_id).await? as u64;
+    Ok(report)
+}
@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow::array::{Array, RecordBatch, UInt32Array};
+use arrow::compute::take_record_batch;
+use arrow::datatypes::SchemaRef;
+use arrow::util::display::array_value_to_string;
+use futures::StreamExt;
+use log::error;
+use parquet::{arrow::AsyncArrowWriter, file::properties::WriterProperties};
+use serde::{Deserialize, Serialize};
+
+use crate::catalog::INTERNAL_ROW_ID_FIELD_NAME;
+use crate::storage::{OutputFile, Storage};
+use crate::table::{ScanAsOf, Table, TableScan, check_storage_prefix, find_snapshot_at};
+use crate::{ILError, ILResult, RecordBatchStream};
+
+/// Directory name of null partition values, as hive names them.
+pub const EXPORT_NULL_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+/// Name of the manifest written next to the exported files, see [`ExportManifest`].
+pub const EXPORT_MANIFEST_FILE_NAME: &str = "_manifest.json";
+
+#[derive(Debug, Clone, derive_with::With)]
+pub struct ExportOptions {
+    /// Scan of the exported rows, its columns or projection make the schema of the files.
+    pub scan: TableScan,
+    /// Size at which a file is completed and the next one started. Files end up somewhat larger
+    /// as the size is checked after each batch written.
+    pub target_file_size: u64,
+    /// Scanned columns whose values split the files into `{column}={value}` directories, nested
+    /// in this order. The columns are left out of the files as readers of hive partitioned
+    /// directories take them from the paths. Values are percent-encoded, nulls are written as
+    /// [`EXPORT_NULL_PARTITION`].
+    pub partition_by: Vec<String>,
+    /// Keeps the row id column in the files when the scan reads it, so that exported rows can
+    /// be matched with the rows of the table.
+    pub include_row_id: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            scan: TableScan::default(),
+            target_file_size: 128 * 1024 * 1024,
+            partition_by: Vec::new(),
+            include_row_id: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedFile {
+    /// Path of the file in the destination storage.
+    pub relative_path: String,
+    pub row_count: usize,
+    pub file_size_bytes: u64,
+}
+
+/// Contents of the [`EXPORT_MANIFEST_FILE_NAME`] file of an export, in JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportManifest {
+    /// Snapshot of the table the rows were read at, `None` for tables without snapshots.
+    pub snapshot_id: Option<i64>,
+    pub files: Vec<ExportedFile>,
+}
+
+/// Writes the rows of the scan to parquet files `{prefix}/part-00000.parquet`,
+/// `{prefix}/part-00001.parquet`, ... of `dest`, or to such files in the partition directories
+/// under `prefix`, compressed and in row groups as the data files of the table, and lists them
+/// in `{prefix}/_manifest.json`. Nothing is written if no row matches. Files written before a
+/// failure are deleted.
+///
+/// Scans of the current state are pinned to the latest snapshot, and scans at a timestamp to
+/// the snapshot they read, so that the files hold the rows of one snapshot whatever is written
+/// to the table meanwhile.
+pub(crate) async fn process_export_parquet(
+    table: &Table,
+    options: &ExportOptions,
+    dest: &Storage,
+    prefix: &str,
+) -> ILResult<Vec<ExportedFile>> {
+    if options.target_file_size == 0 {
+        return Err(ILError::InvalidInput(
+            "Export target file size must be greater than 0".to_string(),
+        ));
+    }
+    check_storage_prefix(prefix)?;
+    let scan_schema = options.scan.projected_schema(&table.schema)?;
+    let layout = ExportLayout::try_new(&scan_schema, options)?;
+
+    let (scan, snapshot_id) = pin_scan(table, options.scan.clone()).await?;
+    let stream = table.scan_stream(scan).await?;
+
+    let mut exported_files = Vec::new();
+    if let Err(e) = write_files(
+        table,
+        options,
+        &layout,
+        dest,
+        prefix,
+        stream,
+        &mut exported_files,
+    )
+    .await
+    {
+        for exported_file in exported_files {
+            if let Err(delete_err) = dest.delete(&exported_file.relative_path).await {
+                error!(
+                    "Failed to delete exported file {}: {delete_err:?}",
+                    exported_file.relative_path
+                );
+            }
+        }
+        return Err(e);
+    }
+    exported_files.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+
+    if !exported_files.is_empty() {
+        let manifest = ExportManifest {
+            snapshot_id,
+            files: exported_files.clone(),
+        };
+        let manifest_bytes = serde_json::to_vec_pretty(&manifest).map_err(|e| {
+            ILError::InternalError(format!("Failed to serialize export manifest: {e:?}"))
+        })?;
+        dest.create_file(&format!("{prefix}/{EXPORT_MANIFEST_FILE_NAME}"))
+            .await?
+            .write(manifest_bytes.into())
+            .await?;
+    }
+    Ok(exported_files)
+}
+
+/// Pins the scan to the snapshot it reads, returning the snapshot id.
+async fn pin_scan(table: &Table, scan: TableScan) -> ILResult<(TableScan, Option<i64>)> {
+    match scan.as_of {
+        Some(ScanAsOf::Snapshot(snapshot_id)) => Ok((scan, Some(snapshot_id))),
+        Some(ScanAsOf::Timestamp(timestamp)) => {
+            let snapshot_id = find_snapshot_at(table, timestamp).await?.snapshot_id;
+            Ok((scan.at_snapshot(snapshot_id), Some(snapshot_id)))
+        }
+        None => match table.current_snapshot_id().await? {
+            0 => Ok((scan, None)),
+            snapshot_id => Ok((scan.at_snapshot(snapshot_id), Some(snapshot_id))),
+        },
+    }
+}
+
+/// Where the columns of scanned batches go: into the files or into the partition paths.
+struct ExportLayout {
+    file_schema: SchemaRef,
+    file_columns: Vec<usize>,
+    partition_columns: Vec<(String, usize)>,
+}
+
+impl ExportLayout {
+    fn try_new(scan_schema: &SchemaRef, options: &ExportOptions) -> ILResult<Self> {
+        let mut partition_columns = Vec::new();
+        for name in &options.partition_by {
+            let index = scan_schema.index_of(name).map_err(|_| {
+                ILError::InvalidInput(format!(
+                    "Export partition column {name} is not a scanned column"
+                ))
+            })?;
+            if partition_columns.iter().any(|(_, i)| *i == index) {
+                return Err(ILError::InvalidInput(format!(
+                    "Export partition column {name} is given more than once"
+                )));
+            }
+            partition_columns.push((name.clone(), index));
+        }
+
+        let file_columns = scan_schema
+            .fields()
+            .iter()
+            .enumerate()
+            .filter(|(i, field)| {
+                partition_columns.iter().all(|(_, index)| index != i)
+                    && (options.include_row_id || field.name() != INTERNAL_ROW_ID_FIELD_NAME)
+            })
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        if file_columns.is_empty() {
+            return Err(ILError::InvalidInput(
+                "Export leaves no columns to write in the files".to_string(),
+            ));
+        }
+        Ok(Self {
+            file_schema: Arc::new(scan_schema.project(&file_columns)?),
+            file_columns,
+            partition_columns,
+        })
+    }
+
+    /// Splits the batch by partition directory, in the order the partitions first appear, and
+    /// leaves the columns that are not written out.
+    fn split(&self, batch: &RecordBatch) -> ILResult<Vec<(String, RecordBatch)>> {
+        if self.partition_columns.is_empty() {
+            return Ok(vec![(String::new(), batch.project(&self.file_columns)?)]);
+        }
+        let mut partitions: Vec<(String, Vec<u32>)> = Vec::new();
+        let mut partition_indices = HashMap::new();
+        for row in 0..batch.num_rows() {
+            let mut dir = String::new();
+            for (name, index) in &self.partition_columns {
+                let array = batch.column(*index);
+                let value = if array.is_null(row) {
+                    EXPORT_NULL_PARTITION.to_string()
+                } else {
+                    escape_partition_value(&array_value_to_string(array, row)?)
+                };
+                dir.push_str(&format!("{name}={value}/"));
+            }
+            let i = *partition_indices.entry(dir.clone()).or_insert_with(|| {
+                partitions.push((dir, Vec::new()));
+                partitions.len() - 1
+            });
+            partitions[i].1.push(row as u32);
+        }
+
+        let batch = batch.project(&self.file_columns)?;
+        partitions
+            .into_iter()
+            .map(|(dir, rows)| Ok((dir, take_record_batch(&batch, &UInt32Array::from(rows))?)))
+            .collect()
+    }
+}
+
+/// Percent-encodes the bytes of the value other than ASCII letters, digits, `-`, `_` and `.`,
+/// so that it makes one path segment of any storage.
+fn escape_partition_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.') {
+            escaped.push(byte as char);
+        } else {
+            escaped.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    escaped
+}
+
+/// File being written in a partition directory.
+struct OpenFile {
+    relative_path: String,
+    arrow_writer: AsyncArrowWriter<OutputFile>,
+    row_count: usize,
+}
+
+async fn write_files(
+    table: &Table,
+    options: &ExportOptions,
+    layout: &ExportLayout,
+    dest: &Storage,
+    prefix: &str,
+    mut stream: RecordBatchStream,
+    exported_files: &mut Vec<ExportedFile>,
+) -> ILResult<()> {
+    let writer_properties = WriterProperties::builder()
+        .set_max_row_group_size(table.config.parquet_row_group_size)
+        .set_compression(table.config.compression.to_parquet()?)
+        .build();
+
+    let mut open_files: HashMap<String, OpenFile> = HashMap::new();
+    // files started in each partition directory, completed or not
+    let mut file_counts: HashMap<String, usize> = HashMap::new();
+    while let Some(batch) = stream.next().await.transpose()? {
+        // scans yield empty batches of pruned files
+        if batch.num_rows() == 0 {
+            continue;
+        }
+        for (dir, batch) in layout.split(&batch)? {
+            let mut open_file = match open_files.remove(&dir) {
+                Some(open_file) => open_file,
+                None => {
+                    let file_count = file_counts.entry(dir.clone()).or_default();
+                    let relative_path = format!("{prefix}/{dir}part-{file_count:05}.parquet");
+                    *file_count += 1;
+                    // registered before writing so that a failure deletes it as well
+                    exported_files.push(ExportedFile {
+                        relative_path: relative_path.clone(),
+                        row_count: 0,
+                        file_size_bytes: 0,
+                    });
+                    OpenFile {
+                        arrow_writer: AsyncArrowWriter::try_new(
+                            dest.create_file(&relative_path).await?,
+                            layout.file_schema.clone(),
+                            Some(writer_properties.clone()),
+                        )?,
+                        relative_path,
+                        row_count: 0,
+                    }
+                }
+            };
+            open_file.arrow_writer.write(&batch).await?;
+            open_file.row_count += batch.num_rows();
+            let file_size =
+                open_file.arrow_writer.bytes_written() + open_file.arrow_writer.in_progress_size();
+            if file_size as u64 >= options.target_file_size {
+                close_file(open_file, exported_files).await?;
+            } else {
+                open_files.insert(dir, open_file);
+            }
+        }
+    }
+    for (_, open_file) in open_files.drain() {
+        close_file(open_file, exported_files).await?;
+    }
+    Ok(())
+}
+
+/// Completes the file and records its row count and size. The footer is only counted once the
+/// writer is closed.
+async fn close_file(open_file: OpenFile, exported_files: &mut [ExportedFile]) -> ILResult<()> {
+    let mut arrow_writer = open_file.arrow_writer;
+    arrow_writer.finish().await?;
+    let exported_file = exported_files
+        .iter_mut()
+        .find(|file| file.relative_path == open_file.relative_path)
+        .ok_or_else(|| {
+            ILError::InternalError(format!(
+                "Exported file {} is not registered",
+                open_file.relative_path
+            ))
+        })?;
+    exported_file.row_count = open_file.row_count;
+    exported_file.file_size_bytes = arrow_writer.bytes_written() as u64;
+    Ok(())
+}
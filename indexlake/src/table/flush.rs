@@ -0,0 +1,46 @@
+use std::collections::HashSet;
+
+use crate::{
+    ILResult,
+    catalog::TransactionHelper,
+    table::{DumpTask, Table},
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FlushReport {
+    /// Data files written, one per partition of the flushed rows in partitioned tables.
+    pub data_file_paths: Vec<String>,
+    /// Inline rows moved into the data files.
+    pub flushed_rows: u64,
+}
+
+/// Moves the inline rows of `row_ids` into data files within the transaction of `tx_helper`.
+/// Rows dumped meanwhile by an automatic dump task are skipped.
+pub(crate) async fn process_flush(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    row_ids: &[i64],
+) -> ILResult<FlushReport> {
+    // Waits for or conflicts with a running dump task, like concurrent flushes do
+    tx_helper.insert_dump_task(table.table_id).await?;
+
+    let inline_row_ids = tx_helper
+        .scan_inline_row_ids(table.table_id)
+        .await?
+        .into_iter()
+        .collect::<HashSet<_>>();
+    let dump_row_ids = row_ids
+        .iter()
+        .filter(|row_id| inline_row_ids.contains(row_id))
+        .copied()
+        .collect::<Vec<_>>();
+
+    let mut report = FlushReport::default();
+    if !dump_row_ids.is_empty() {
+        report.flushed_rows = dump_row_ids.len() as u64;
+        report.data_file_paths = DumpTask::new(table, dump_row_ids).dump(tx_helper).await?;
+    }
+
+    tx_helper.delete_dump_task(table.table_id).await?;
+    Ok(report)
+}
@@ -0,0 +1,507 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use arrow::array::{Array, ArrayRef, Int64Array, RecordBatch, new_null_array};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, SchemaRef};
+use futures::StreamExt;
+use log::debug;
+use parquet::arrow::async_reader::ParquetRecordBatchStream;
+use parquet::arrow::{AsyncArrowWriter, ParquetRecordBatchStreamBuilder};
+
+use crate::catalog::{
+    CatalogSchema, ColumnStats, DataFileRecord, INTERNAL_ROW_ID_FIELD_NAME, IndexFileRecord, Row,
+    RowLocation, RowMetadataRecord, Scalar, TransactionHelper,
+};
+use crate::index::IndexBuilder;
+use crate::storage::{InputFile, OutputFile, write_parquet_file};
+use crate::table::{ColumnStatsBuilder, Table, check_storage_prefix, row_partition_values};
+use crate::utils::has_duplicated_items;
+use crate::{ILError, ILResult};
+
+#[derive(Debug, Clone, Default, derive_with::With)]
+pub struct IngestOptions {
+    /// Registers the files where they are instead of copying them under the table directory.
+    /// Files registered in place must not be changed or deleted while the table refers to
+    /// them, vacuum and relocation leave them alone.
+    pub in_place: bool,
+}
+
+/// Registers existing parquet files as data files of the table. The files must have the
+/// columns of the table under the same names and types, in any order. Nullable columns they
+/// lack are read as their default value or nulls. Rows get new row ids, are added to the
+/// indexes and the column statistics of each file are computed for pruning.
+///
+/// Copied files are rewritten with the row ids, the field ids and the compression of the
+/// table. Files of partitioned tables must hold rows of a single partition.
+pub(crate) async fn process_ingest_parquet(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    paths: &[String],
+    options: &IngestOptions,
+) -> ILResult<()> {
+    check_ingestable(table)?;
+    if has_duplicated_items(paths.iter()) {
+        return Err(ILError::InvalidInput(format!(
+            "Duplicated parquet file paths {paths:?}"
+        )));
+    }
+    let registered = tx_helper
+        .get_data_files(table.table_id)
+        .await?
+        .into_iter()
+        .map(|data_file| data_file.relative_path)
+        .collect::<HashSet<_>>();
+    for path in paths {
+        check_ingested_path(table, path, &registered)?;
+    }
+
+    for path in paths {
+        ingest_file(tx_helper, table, path, options).await?;
+    }
+    Ok(())
+}
+
+/// Rejects tables whose keys would have to be checked against the rows of ingested files.
+pub(crate) fn check_ingestable(table: &Table) -> ILResult<()> {
+    if !table.config.primary_key.is_empty() {
+        return Err(ILError::NotSupported(format!(
+            "Ingesting parquet files into table {} with a primary key",
+            table.table_name
+        )));
+    }
+    if !table.config.unique_constraints.is_empty() {
+        return Err(ILError::NotSupported(format!(
+            "Ingesting parquet files into table {} with unique constraints",
+            table.table_name
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that `path` can be registered as a data file of the table next to the `registered`
+/// data files.
+pub(crate) fn check_ingested_path(
+    table: &Table,
+    path: &str,
+    registered: &HashSet<String>,
+) -> ILResult<()> {
+    // Paths end up in row locations the same way storage prefixes do
+    check_storage_prefix(path).map_err(|_| {
+        ILError::InvalidInput(format!(
+            "Invalid parquet file path {path:?}, must be a relative path without empty, . or .. segments, quotes, colons or backslashes"
+        ))
+    })?;
+    if registered.contains(path) {
+        return Err(ILError::InvalidInput(format!(
+            "Parquet file {path} is already a data file of table {}",
+            table.table_name
+        )));
+    }
+    Ok(())
+}
+
+/// Rows of an ingested file, as read into batches of the table schema.
+pub(crate) struct IngestedRows {
+    pub(crate) row_ids: Vec<i64>,
+    pub(crate) partition_values: Option<Vec<Scalar>>,
+    pub(crate) column_stats: BTreeMap<String, ColumnStats>,
+}
+
+async fn ingest_file(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    path: &str,
+    options: &IngestOptions,
+) -> ILResult<()> {
+    let input_file = table.storage.open_file(path).await?;
+    let input_file_size = input_file.file_size_bytes().await?;
+    let reader_builder = ParquetRecordBatchStreamBuilder::new(input_file).await?;
+    let file_columns = match_file_schema(table, path, reader_builder.schema(), false)?;
+    let row_group_num_rows = reader_builder
+        .metadata()
+        .row_groups()
+        .iter()
+        .map(|row_group| row_group.num_rows() as usize)
+        .collect::<Vec<_>>();
+    let stream = reader_builder.build()?;
+
+    let data_file_id = tx_helper.get_max_data_file_id().await? + 1;
+    let first_row_id = tx_helper.get_max_row_id(table.table_id).await? + 1;
+    let mut index_builders = table_index_builders(table)?;
+
+    // Copies are written in row groups of the table row group size, files registered in
+    // place keep their own row groups
+    let (relative_path, rows, file_size_bytes, checksum, row_group_num_rows) = if options.in_place {
+        let rows = ingest_rows(
+            table,
+            path,
+            stream,
+            &file_columns,
+            first_row_id,
+            &mut index_builders,
+            None,
+        )
+        .await?;
+        // Lets verification notice files changed after they were registered
+        let checksum = table.storage.checksum_file(path).await?;
+        (
+            path.to_string(),
+            rows,
+            input_file_size,
+            checksum,
+            row_group_num_rows,
+        )
+    } else {
+        // Ingestion runs in a catalog transaction re-run on conflicts
+        let relative_path =
+            DataFileRecord::build_unique_relative_path(&table.table_dir(), data_file_id);
+        let (rows, file_size_bytes, checksum) = write_parquet_file(
+            &table.storage,
+            &table.config,
+            &relative_path,
+            table.data_file_schema(),
+            async |arrow_writer| {
+                ingest_rows(
+                    table,
+                    path,
+                    stream,
+                    &file_columns,
+                    first_row_id,
+                    &mut index_builders,
+                    Some(arrow_writer),
+                )
+                .await
+            },
+        )
+        .await?;
+        let row_group_size = table.config.parquet_row_group_size;
+        let num_rows = rows.row_ids.len();
+        let row_group_num_rows = (0..num_rows.div_ceil(row_group_size))
+            .map(|i| row_group_size.min(num_rows - i * row_group_size))
+            .collect();
+        (
+            relative_path,
+            rows,
+            file_size_bytes as u64,
+            checksum,
+            row_group_num_rows,
+        )
+    };
+    debug!(
+        "Ingest parquet file {path} into table {} as data file {relative_path} with {} rows",
+        table.table_id,
+        rows.row_ids.len()
+    );
+
+    let row_metadatas = parquet_row_metadatas(&relative_path, &rows.row_ids, row_group_num_rows);
+    tx_helper
+        .insert_data_files(
+            &[DataFileRecord {
+                data_file_id,
+                table_id: table.table_id,
+                relative_path,
+                file_size_bytes: file_size_bytes as i64,
+                record_count: rows.row_ids.len() as i64,
+                row_ids: rows.row_ids,
+                partition_values: rows.partition_values,
+                checksum: Some(checksum),
+                column_stats: Some(rows.column_stats),
+            }],
+            table.config.catalog_insert_batch_size,
+        )
+        .await?;
+
+    write_index_files(tx_helper, table, data_file_id, &mut index_builders).await?;
+    tx_helper
+        .insert_row_metadatas(
+            table.table_id,
+            &row_metadatas,
+            table.config.catalog_insert_batch_size,
+        )
+        .await?;
+    Ok(())
+}
+
+/// Builders of the indexes of the table, for the rows of a data file. None with
+/// [`TableConfig::deferred_indexing`](crate::table::TableConfig::deferred_indexing), the data
+/// file is indexed when the indexes are synced.
+pub(crate) fn table_index_builders(
+    table: &Table,
+) -> ILResult<HashMap<String, Box<dyn IndexBuilder>>> {
+    let mut index_builders: HashMap<String, Box<dyn IndexBuilder>> = HashMap::new();
+    if table.config.deferred_indexing {
+        return Ok(index_builders);
+    }
+    for (index_name, index_def) in table.indexes.iter() {
+        let index_kind = table.index_kinds.get(&index_def.kind).ok_or_else(|| {
+            ILError::InternalError(format!("Index kind {} not found", index_def.kind))
+        })?;
+        index_builders.insert(index_name.clone(), index_kind.builder(index_def)?);
+    }
+    Ok(index_builders)
+}
+
+/// Row metadata of rows stored in the data file at `relative_path`, in the order of the rows
+/// in its row groups of `row_group_num_rows` rows.
+pub(crate) fn parquet_row_metadatas(
+    relative_path: &str,
+    row_ids: &[i64],
+    row_group_num_rows: Vec<usize>,
+) -> Vec<RowMetadataRecord> {
+    let mut row_metadatas = Vec::with_capacity(row_ids.len());
+    let mut row_ids = row_ids.iter();
+    for (row_group_index, num_rows) in row_group_num_rows.into_iter().enumerate() {
+        for (row_group_offset, row_id) in row_ids.by_ref().take(num_rows).enumerate() {
+            row_metadatas.push(RowMetadataRecord::new(
+                *row_id,
+                RowLocation::Parquet {
+                    relative_path: relative_path.to_string(),
+                    row_group_index,
+                    row_group_offset,
+                },
+            ));
+        }
+    }
+    row_metadatas
+}
+
+/// Writes the index files of the data file `data_file_id` under unique paths in the table
+/// directory and registers them.
+pub(crate) async fn write_index_files(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    data_file_id: i64,
+    index_builders: &mut HashMap<String, Box<dyn IndexBuilder>>,
+) -> ILResult<()> {
+    let mut index_file_id = tx_helper.get_max_index_file_id().await? + 1;
+    let mut index_file_records = Vec::new();
+    for (index_name, index_builder) in index_builders.iter_mut() {
+        let index_def = table
+            .indexes
+            .get(index_name)
+            .ok_or_else(|| ILError::InternalError(format!("Index {index_name} not found")))?;
+        let relative_path = IndexFileRecord::build_unique_relative_path(
+            &table.table_dir(),
+            data_file_id,
+            index_def.index_id,
+            index_file_id,
+        );
+        let output_file = table.storage.create_file(&relative_path).await?;
+        index_builder.write(output_file).await?;
+        index_file_records.push(IndexFileRecord {
+            index_file_id,
+            index_id: index_def.index_id,
+            data_file_id,
+            relative_path,
+            metadata: index_builder.file_metadata()?,
+        });
+        index_file_id += 1;
+    }
+    tx_helper.insert_index_files(&index_file_records).await?;
+    Ok(())
+}
+
+/// Reads the rows of the file into batches of the table schema with row ids from
+/// `first_row_id` on, adding them to the indexes, the column statistics and the writer.
+pub(crate) async fn ingest_rows(
+    table: &Table,
+    path: &str,
+    mut stream: ParquetRecordBatchStream<InputFile>,
+    file_columns: &[Option<usize>],
+    first_row_id: i64,
+    index_builders: &mut HashMap<String, Box<dyn IndexBuilder>>,
+    mut arrow_writer: Option<&mut AsyncArrowWriter<&mut OutputFile>>,
+) -> ILResult<IngestedRows> {
+    let partition_schema = if table.config.partition_by.is_empty() {
+        None
+    } else {
+        let indices = table
+            .config
+            .partition_by
+            .iter()
+            .map(|name| table.schema.index_of(name))
+            .collect::<Result<Vec<_>, _>>()?;
+        Some(Arc::new(CatalogSchema::from_arrow(
+            &table.schema.project(&indices)?,
+        )?))
+    };
+
+    let mut row_ids = Vec::new();
+    let mut partition_values = None;
+    let mut column_stats_builder = ColumnStatsBuilder::new();
+    while let Some(file_batch) = stream.next().await {
+        let file_batch = file_batch?;
+        let next_row_id = first_row_id + row_ids.len() as i64;
+        let batch_row_ids =
+            (next_row_id..next_row_id + file_batch.num_rows() as i64).collect::<Vec<_>>();
+        let batch = table_batch(table, path, &file_batch, file_columns, &batch_row_ids)?;
+
+        if let Some(partition_schema) = &partition_schema {
+            for row_idx in 0..batch.num_rows() {
+                let values = table
+                    .config
+                    .partition_by
+                    .iter()
+                    .map(|name| {
+                        let column = batch.column_by_name(name).expect("partition column");
+                        Scalar::try_from_array(column.as_ref(), row_idx)
+                    })
+                    .collect::<ILResult<Vec<_>>>()?;
+                let row = Row::new(partition_schema.clone(), values);
+                let row_partition = row_partition_values(&table.schema, &table.config, &row)?;
+                match &partition_values {
+                    None => partition_values = Some(row_partition),
+                    Some(file_partition) if file_partition != &row_partition => {
+                        return Err(ILError::InvalidInput(format!(
+                            "Parquet file {path} holds rows of several partitions of table {}, ingest a file per partition",
+                            table.table_name
+                        )));
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+
+        for (index_name, index_builder) in index_builders.iter_mut() {
+            let index_def = &table.indexes[index_name];
+            index_builder.update(&index_def.indexed_rows(&batch)?)?;
+        }
+        column_stats_builder.update(&batch);
+        if let Some(arrow_writer) = arrow_writer.as_mut() {
+            arrow_writer.write(&batch).await?;
+        }
+        row_ids.extend(batch_row_ids);
+    }
+
+    Ok(IngestedRows {
+        row_ids,
+        partition_values: partition_values.flatten(),
+        column_stats: column_stats_builder.finish(),
+    })
+}
+
+/// Returns the index in the file schema of the column of each table field, `None` for the
+/// row id and the columns the file lacks. Files whose columns differ from the table columns
+/// by more than lacking nullable ones are rejected, listing every difference. With
+/// `safe_casts`, file columns may have a type [`is_safe_cast`] to the table type.
+pub(crate) fn match_file_schema(
+    table: &Table,
+    path: &str,
+    file_schema: &SchemaRef,
+    safe_casts: bool,
+) -> ILResult<Vec<Option<usize>>> {
+    let mut file_columns = Vec::with_capacity(table.schema.fields().len());
+    let mut differences = Vec::new();
+    for field in table.schema.fields() {
+        if field.name() == INTERNAL_ROW_ID_FIELD_NAME {
+            file_columns.push(None);
+            continue;
+        }
+        let file_idx = file_schema.index_of(field.name()).ok();
+        match file_idx.map(|idx| file_schema.field(idx)) {
+            Some(file_field)
+                if file_field.data_type() != field.data_type()
+                    && !(safe_casts && is_safe_cast(file_field.data_type(), field.data_type())) =>
+            {
+                differences.push(format!(
+                    "column {} has type {} in the file, {} in the table",
+                    field.name(),
+                    file_field.data_type(),
+                    field.data_type()
+                ));
+            }
+            Some(_) => {}
+            None if field.is_nullable() => {}
+            None => differences.push(format!(
+                "missing column {}: {}",
+                field.name(),
+                field.data_type()
+            )),
+        }
+        file_columns.push(file_idx);
+    }
+    for file_field in file_schema.fields() {
+        if file_field.name() == INTERNAL_ROW_ID_FIELD_NAME
+            || table.schema.field_with_name(file_field.name()).is_err()
+        {
+            differences.push(format!(
+                "unexpected column {}: {}",
+                file_field.name(),
+                file_field.data_type()
+            ));
+        }
+    }
+
+    if !differences.is_empty() {
+        return Err(ILError::InvalidInput(format!(
+            "Schema of parquet file {path} does not match table {}:\n  {}",
+            table.table_name,
+            differences.join("\n  ")
+        )));
+    }
+    Ok(file_columns)
+}
+
+/// Whether every value of type `from` converts to a value of type `to` without loss.
+pub(crate) fn is_safe_cast(from: &DataType, to: &DataType) -> bool {
+    use DataType::*;
+    matches!(
+        (from, to),
+        (Int8, Int16 | Int32 | Int64 | Float32 | Float64)
+            | (Int16, Int32 | Int64 | Float32 | Float64)
+            | (Int32, Int64 | Float64)
+            | (
+                UInt8,
+                Int16 | Int32 | Int64 | UInt16 | UInt32 | UInt64 | Float32 | Float64
+            )
+            | (UInt16, Int32 | Int64 | UInt32 | UInt64 | Float32 | Float64)
+            | (UInt32, Int64 | UInt64 | Float64)
+            | (Float16, Float32 | Float64)
+            | (Float32, Float64)
+            | (Utf8, LargeUtf8 | Utf8View)
+            | (Binary, LargeBinary | BinaryView)
+            | (Date32, Date64)
+    )
+}
+
+/// Builds a batch of the table schema from a batch read from the file, casting the columns
+/// whose type differs.
+fn table_batch(
+    table: &Table,
+    path: &str,
+    file_batch: &RecordBatch,
+    file_columns: &[Option<usize>],
+    row_ids: &[i64],
+) -> ILResult<RecordBatch> {
+    let num_rows = file_batch.num_rows();
+    let mut columns = Vec::with_capacity(file_columns.len());
+    for (field, file_idx) in table.schema.fields().iter().zip(file_columns) {
+        let column: ArrayRef = match file_idx {
+            Some(idx) => {
+                let column = file_batch.column(*idx).clone();
+                if !field.is_nullable() && column.null_count() > 0 {
+                    return Err(ILError::InvalidInput(format!(
+                        "Parquet file {path} has nulls in column {} that is not nullable in table {}",
+                        field.name(),
+                        table.table_name
+                    )));
+                }
+                if column.data_type() == field.data_type() {
+                    column
+                } else {
+                    cast(&column, field.data_type())?
+                }
+            }
+            None if field.name() == INTERNAL_ROW_ID_FIELD_NAME => {
+                Arc::new(Int64Array::from(row_ids.to_vec()))
+            }
+            None => match table.field_defaults.get(field.name()) {
+                Some(default) => default.to_array_of_size(num_rows)?,
+                None => new_null_array(field.data_type(), num_rows),
+            },
+        };
+        columns.push(column);
+    }
+    Ok(RecordBatch::try_new(table.schema.clone(), columns)?)
+}
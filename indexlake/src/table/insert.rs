@@ -2,24 +2,29 @@ use std::sync::Arc;
 
 use arrow::{
     array::{
-        ArrayRef, BinaryArray, BooleanArray, Float32Array, Float64Array, Int16Array, Int32Array,
-        Int64Array, RecordBatch, StringArray,
+        Array, ArrayRef, AsArray, BinaryArray, BooleanArray, FixedSizeListArray, Float32Array,
+        Float64Array, Int16Array, Int32Array, Int64Array, RecordBatch, StringArray,
     },
-    datatypes::{DataType, Field, Schema},
+    datatypes::{DataType, Decimal128Type, Decimal256Type, Field, Int64Type, Schema, i256},
 };
 
 use crate::{
     ILError, ILResult,
-    catalog::{CatalogDatabase, RowLocation, RowMetadataRecord, TransactionHelper},
+    catalog::{
+        RowLocation, RowMetadataRecord, Scalar, TransactionHelper, encode_decimal,
+        encode_nested_value, encode_timestamp, encode_vector, is_nested_data_type,
+    },
+    table::{Table, insert_unique_keys, unique_keys},
     utils::record_batch_with_row_id,
 };
 
 // TODO bypass insert: save to parquet file
 pub(crate) async fn process_insert(
     tx_helper: &mut TransactionHelper,
-    table_id: i64,
+    table: &Table,
     record: &RecordBatch,
 ) -> ILResult<()> {
+    let (table_id, batch_size) = (table.table_id, table.config.catalog_insert_batch_size);
     let max_row_id = tx_helper.get_max_row_id(table_id).await?;
 
     // Generate row id for each row
@@ -29,13 +34,16 @@ pub(crate) async fn process_insert(
         .map(|id| RowMetadataRecord::new(*id, RowLocation::Inline))
         .collect::<Vec<_>>();
 
+    let keys = unique_keys(table, record, &row_ids, false)?;
+    insert_unique_keys(tx_helper, table, &keys).await?;
+
     let row_id_array = Int64Array::from(row_ids);
     let record = record_batch_with_row_id(record, row_id_array)?;
 
-    process_insert_into_inline_rows(tx_helper, table_id, &record).await?;
+    process_insert_into_inline_rows(tx_helper, table_id, &record, batch_size).await?;
 
     tx_helper
-        .insert_row_metadatas(table_id, &row_metadatas)
+        .insert_row_metadatas(table_id, &row_metadatas, batch_size)
         .await?;
     Ok(())
 }
@@ -44,11 +52,12 @@ pub(crate) async fn process_insert_into_inline_rows(
     tx_helper: &mut TransactionHelper,
     table_id: i64,
     record: &RecordBatch,
+    batch_size: usize,
 ) -> ILResult<()> {
     if record.num_rows() == 0 {
         return Ok(());
     }
-    let sql_values = record_batch_to_sql_values(&record, tx_helper.database)?;
+    let values = record_batch_to_catalog_values(record)?;
 
     let inline_field_names = record
         .schema()
@@ -58,15 +67,13 @@ pub(crate) async fn process_insert_into_inline_rows(
         .collect::<Vec<_>>();
 
     tx_helper
-        .insert_inline_rows(table_id, &inline_field_names, sql_values)
+        .insert_inline_rows(table_id, &inline_field_names, &values, batch_size)
         .await?;
     Ok(())
 }
 
-pub(crate) fn record_batch_to_sql_values(
-    record: &RecordBatch,
-    database: CatalogDatabase,
-) -> ILResult<Vec<String>> {
+/// Values of the rows of `record` in the catalog data types the inline rows are stored in.
+pub(crate) fn record_batch_to_catalog_values(record: &RecordBatch) -> ILResult<Vec<Vec<Scalar>>> {
     let mut column_values_list = Vec::with_capacity(record.num_columns());
     for (i, field) in record.schema().fields().iter().enumerate() {
         let mut column_values = Vec::with_capacity(record.num_rows());
@@ -78,12 +85,7 @@ pub(crate) fn record_batch_to_sql_values(
                         "Failed to downcast field {field:?} to BooleanArray"
                     ))
                 })?;
-                for v in array.iter() {
-                    column_values.push(match v {
-                        Some(v) => v.to_string(),
-                        None => "NULL".to_string(),
-                    });
-                }
+                column_values.extend(array.iter().map(Scalar::Boolean));
             }
             DataType::Int16 => {
                 let array = any_array.downcast_ref::<Int16Array>().ok_or_else(|| {
@@ -91,12 +93,7 @@ pub(crate) fn record_batch_to_sql_values(
                         "Failed to downcast field {field:?} to Int16Array"
                     ))
                 })?;
-                for v in array.iter() {
-                    column_values.push(match v {
-                        Some(v) => v.to_string(),
-                        None => "NULL".to_string(),
-                    });
-                }
+                column_values.extend(array.iter().map(Scalar::Int16));
             }
             DataType::Int32 => {
                 let array = any_array.downcast_ref::<Int32Array>().ok_or_else(|| {
@@ -104,12 +101,7 @@ pub(crate) fn record_batch_to_sql_values(
                         "Failed to downcast field {field:?} to Int32Array"
                     ))
                 })?;
-                for v in array.iter() {
-                    column_values.push(match v {
-                        Some(v) => v.to_string(),
-                        None => "NULL".to_string(),
-                    });
-                }
+                column_values.extend(array.iter().map(Scalar::Int32));
             }
             DataType::Int64 => {
                 let array = any_array.downcast_ref::<Int64Array>().ok_or_else(|| {
@@ -117,12 +109,7 @@ pub(crate) fn record_batch_to_sql_values(
                         "Failed to downcast field {field:?} to Int64Array"
                     ))
                 })?;
-                for v in array.iter() {
-                    column_values.push(match v {
-                        Some(v) => v.to_string(),
-                        None => "NULL".to_string(),
-                    });
-                }
+                column_values.extend(array.iter().map(Scalar::Int64));
             }
             DataType::Float32 => {
                 let array = any_array.downcast_ref::<Float32Array>().ok_or_else(|| {
@@ -130,12 +117,7 @@ pub(crate) fn record_batch_to_sql_values(
                         "Failed to downcast field {field:?} to Float32Array"
                     ))
                 })?;
-                for v in array.iter() {
-                    column_values.push(match v {
-                        Some(v) => v.to_string(),
-                        None => "NULL".to_string(),
-                    });
-                }
+                column_values.extend(array.iter().map(Scalar::Float32));
             }
             DataType::Float64 => {
                 let array = any_array.downcast_ref::<Float64Array>().ok_or_else(|| {
@@ -143,12 +125,7 @@ pub(crate) fn record_batch_to_sql_values(
                         "Failed to downcast field {field:?} to Float64Array"
                     ))
                 })?;
-                for v in array.iter() {
-                    column_values.push(match v {
-                        Some(v) => v.to_string(),
-                        None => "NULL".to_string(),
-                    });
-                }
+                column_values.extend(array.iter().map(Scalar::Float64));
             }
             DataType::Utf8 => {
                 let array = any_array.downcast_ref::<StringArray>().ok_or_else(|| {
@@ -156,12 +133,7 @@ pub(crate) fn record_batch_to_sql_values(
                         "Failed to downcast field {field:?} to StringArray"
                     ))
                 })?;
-                for v in array.iter() {
-                    column_values.push(match v {
-                        Some(v) => format!("'{}'", v),
-                        None => "NULL".to_string(),
-                    });
-                }
+                column_values.extend(array.iter().map(|v| Scalar::Utf8(v.map(|v| v.to_string()))));
             }
             DataType::Binary => {
                 let array = any_array.downcast_ref::<BinaryArray>().ok_or_else(|| {
@@ -169,10 +141,62 @@ pub(crate) fn record_batch_to_sql_values(
                         "Failed to downcast field {field:?} to BinaryArray"
                     ))
                 })?;
+                column_values.extend(array.iter().map(|v| Scalar::Binary(v.map(|v| v.to_vec()))));
+            }
+            DataType::Decimal128(_, scale) => {
+                let array = record.column(i).as_primitive::<Decimal128Type>();
+                column_values.extend(array.iter().map(|v| {
+                    Scalar::Binary(v.map(|v| encode_decimal(i256::from_i128(v), *scale)))
+                }));
+            }
+            DataType::Decimal256(_, scale) => {
+                let array = record.column(i).as_primitive::<Decimal256Type>();
+                column_values.extend(
+                    array
+                        .iter()
+                        .map(|v| Scalar::Binary(v.map(|v| encode_decimal(v, *scale)))),
+                );
+            }
+            DataType::Timestamp(unit, _) => {
+                // Timestamps of every unit cast to their values as Int64
+                let array = arrow::compute::cast(record.column(i), &DataType::Int64)?;
+                column_values.extend(
+                    array
+                        .as_primitive::<Int64Type>()
+                        .iter()
+                        .map(|v| Scalar::Binary(v.map(|v| encode_timestamp(v, *unit)))),
+                );
+            }
+            DataType::FixedSizeList(item, _) if item.data_type() == &DataType::Float32 => {
+                let array = any_array
+                    .downcast_ref::<FixedSizeListArray>()
+                    .ok_or_else(|| {
+                        ILError::InternalError(format!(
+                            "Failed to downcast field {field:?} to FixedSizeListArray"
+                        ))
+                    })?;
                 for v in array.iter() {
                     column_values.push(match v {
-                        Some(v) => database.sql_binary_value(v),
-                        None => "NULL".to_string(),
+                        Some(v) => {
+                            if v.null_count() > 0 {
+                                return Err(ILError::InvalidInput(format!(
+                                    "Vector of field {} has null elements",
+                                    field.name()
+                                )));
+                            }
+                            Scalar::Binary(Some(encode_vector(v.as_primitive())))
+                        }
+                        None => Scalar::Binary(None),
+                    });
+                }
+            }
+            data_type if is_nested_data_type(data_type) => {
+                let array = record.column(i);
+                for row_idx in 0..array.len() {
+                    column_values.push(if array.is_null(row_idx) {
+                        Scalar::Binary(None)
+                    } else {
+                        Scalar::Binary(Some(encode_nested_value(array, row_idx)?))
                     });
                 }
             }
@@ -183,16 +207,19 @@ pub(crate) fn record_batch_to_sql_values(
                 )));
             }
         }
-        column_values_list.push(column_values);
+        column_values_list.push(column_values.into_iter());
     }
-    let mut sql_values = Vec::with_capacity(record.num_rows());
-    for row_idx in 0..record.num_rows() {
-        let mut row_values = Vec::with_capacity(record.num_columns());
-        for column_values in column_values_list.iter() {
-            row_values.push(column_values[row_idx].clone());
-        }
-        let row_values_str = row_values.join(", ");
-        sql_values.push(format!("({})", row_values_str));
+    let mut rows = Vec::with_capacity(record.num_rows());
+    for _ in 0..record.num_rows() {
+        rows.push(
+            column_values_list
+                .iter_mut()
+                .map(|column_values| column_values.next())
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| {
+                    ILError::InternalError("Column of fewer values than rows".to_string())
+                })?,
+        );
     }
-    Ok(sql_values)
+    Ok(rows)
 }
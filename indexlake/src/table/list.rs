@@ -0,0 +1,265 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use arrow::datatypes::{Schema, SchemaRef};
+
+use crate::expr::Expr;
+use crate::table::{Clustering, PartitionTransform};
+use crate::{ILError, ILResult, catalog::CatalogHelper};
+
+#[derive(Debug, Clone, Default)]
+pub struct ListOptions {
+    /// Only list tables whose name starts with the prefix.
+    pub prefix: Option<String>,
+    /// Maximum number of tables returned in one page, all remaining tables if not set.
+    pub limit: Option<usize>,
+    /// `next_page_token` of the previous page.
+    pub page_token: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TableSummary {
+    pub table_id: i64,
+    pub table_name: String,
+    /// Table schema without the internal row id field.
+    pub schema: SchemaRef,
+    /// Number of undeleted rows.
+    pub row_count: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct TablePage {
+    /// Tables ordered by name.
+    pub tables: Vec<TableSummary>,
+    /// Set if there are more tables to list.
+    pub next_page_token: Option<String>,
+}
+
+pub(crate) async fn process_list_tables(
+    catalog_helper: &CatalogHelper,
+    namespace_id: i64,
+    options: &ListOptions,
+) -> ILResult<TablePage> {
+    if options.limit == Some(0) {
+        return Err(ILError::InvalidInput(
+            "List limit must be greater than 0".to_string(),
+        ));
+    }
+    let start_after = options
+        .page_token
+        .as_deref()
+        .map(decode_page_token)
+        .transpose()?;
+
+    // Fetch one extra table to find out whether there is a next page
+    let fetch_limit = options.limit.map(|limit| limit + 1);
+    let mut records = catalog_helper
+        .list_tables(
+            namespace_id,
+            options.prefix.as_deref(),
+            start_after.as_deref(),
+            fetch_limit,
+        )
+        .await?;
+    let has_more = options.limit.is_some_and(|limit| records.len() > limit);
+    if let Some(limit) = options.limit {
+        records.truncate(limit);
+    }
+    let next_page_token = if has_more {
+        records
+            .last()
+            .map(|record| encode_page_token(&record.table_name))
+    } else {
+        None
+    };
+
+    let mut tables = Vec::with_capacity(records.len());
+    for record in records {
+        let field_map = catalog_helper.get_table_fields(record.table_id).await?;
+        let schema = Arc::new(Schema::new(field_map.into_values().collect::<Vec<_>>()));
+        let row_count = catalog_helper.count_undeleted_rows(record.table_id).await?;
+        tables.push(TableSummary {
+            table_id: record.table_id,
+            table_name: record.table_name,
+            schema,
+            row_count,
+        });
+    }
+
+    Ok(TablePage {
+        tables,
+        next_page_token,
+    })
+}
+
+/// Names a table by its namespace and table name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TableIdent {
+    pub namespace_name: String,
+    pub table_name: String,
+}
+
+impl TableIdent {
+    pub fn new(namespace_name: impl Into<String>, table_name: impl Into<String>) -> Self {
+        Self {
+            namespace_name: namespace_name.into(),
+            table_name: table_name.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TableDescription {
+    pub ident: TableIdent,
+    pub table_id: i64,
+    /// Table schema without the internal row id field.
+    pub schema: SchemaRef,
+    /// Partition columns, empty for unpartitioned tables.
+    pub partition_by: Vec<String>,
+    pub partition_transform: PartitionTransform,
+    /// Order of the rows in the data files written by flushes and compactions.
+    pub clustering: Clustering,
+    /// Indexes ordered by name.
+    pub indexes: Vec<IndexDescription>,
+    /// Latest snapshot, `None` for tables created before snapshots were recorded.
+    pub current_snapshot_id: Option<i64>,
+    /// Number of undeleted rows.
+    pub row_count: i64,
+    /// Directory of the table files in the storage.
+    pub storage_prefix: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexDescription {
+    pub name: String,
+    pub kind: String,
+    pub key_columns: Vec<String>,
+    pub include_columns: Vec<String>,
+    /// Only rows matching the predicate are indexed, `None` for indexes of all rows.
+    pub where_predicate: Option<Expr>,
+    /// Data files without an index file of the index, written with
+    /// [`TableConfig::deferred_indexing`](crate::table::TableConfig::deferred_indexing) and not
+    /// synced yet. Scans read them in full.
+    pub unindexed_data_file_count: usize,
+}
+
+impl IndexDescription {
+    /// Whether every data file of the table is indexed.
+    pub fn is_synced(&self) -> bool {
+        self.unindexed_data_file_count == 0
+    }
+}
+
+pub(crate) async fn process_list_table_idents(
+    catalog_helper: &CatalogHelper,
+    namespace_id: i64,
+    namespace_name: &str,
+) -> ILResult<Vec<TableIdent>> {
+    let records = catalog_helper
+        .list_tables(namespace_id, None, None, None)
+        .await?;
+    Ok(records
+        .into_iter()
+        .map(|record| TableIdent::new(namespace_name, record.table_name))
+        .collect())
+}
+
+/// Describes a table from the catalog alone, without loading it or reading its files.
+pub(crate) async fn process_describe_table(
+    catalog_helper: &CatalogHelper,
+    namespace_id: i64,
+    ident: &TableIdent,
+) -> ILResult<TableDescription> {
+    let record = catalog_helper
+        .get_table(namespace_id, &ident.table_name)
+        .await?
+        .ok_or_else(|| {
+            ILError::CatalogError(format!(
+                "Table {} not found in namespace {}",
+                ident.table_name, ident.namespace_name
+            ))
+        })?;
+
+    let field_map = catalog_helper.get_table_fields(record.table_id).await?;
+    let field_name = |field_id: &i64| {
+        field_map
+            .get(field_id)
+            .map(|field| field.name().clone())
+            .ok_or_else(|| {
+                ILError::CatalogError(format!(
+                    "Field {field_id} of table {} not found",
+                    ident.table_name
+                ))
+            })
+    };
+    let data_file_ids = catalog_helper
+        .get_data_files(record.table_id)
+        .await?
+        .into_iter()
+        .map(|data_file| data_file.data_file_id)
+        .collect::<HashSet<_>>();
+    let index_files = catalog_helper.get_index_files(record.table_id).await?;
+    let mut indexes = Vec::new();
+    for index_record in catalog_helper.get_table_indexes(record.table_id).await? {
+        let indexed_data_file_count = index_files
+            .iter()
+            .filter(|index_file| {
+                index_file.index_id == index_record.index_id
+                    && data_file_ids.contains(&index_file.data_file_id)
+            })
+            .map(|index_file| index_file.data_file_id)
+            .collect::<HashSet<_>>()
+            .len();
+        indexes.push(IndexDescription {
+            key_columns: index_record
+                .key_field_ids
+                .iter()
+                .map(field_name)
+                .collect::<ILResult<_>>()?,
+            include_columns: index_record
+                .include_field_ids
+                .iter()
+                .map(field_name)
+                .collect::<ILResult<_>>()?,
+            name: index_record.index_name,
+            kind: index_record.index_kind,
+            where_predicate: index_record.where_predicate,
+            unindexed_data_file_count: data_file_ids.len() - indexed_data_file_count,
+        });
+    }
+    indexes.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let current_snapshot_id = catalog_helper
+        .get_snapshots(record.table_id)
+        .await?
+        .iter()
+        .filter(|snapshot| !snapshot.expired)
+        .map(|snapshot| snapshot.snapshot_id)
+        .max();
+    let row_count = catalog_helper.count_undeleted_rows(record.table_id).await?;
+    let storage_prefix = record.config.table_dir(namespace_id, record.table_id);
+
+    Ok(TableDescription {
+        ident: ident.clone(),
+        table_id: record.table_id,
+        schema: Arc::new(Schema::new(field_map.into_values().collect::<Vec<_>>())),
+        partition_by: record.config.partition_by,
+        partition_transform: record.config.partition_transform,
+        clustering: record.config.clustering,
+        indexes,
+        current_snapshot_id,
+        row_count,
+        storage_prefix,
+    })
+}
+
+fn encode_page_token(table_name: &str) -> String {
+    hex::encode(table_name)
+}
+
+fn decode_page_token(page_token: &str) -> ILResult<String> {
+    hex::decode(page_token)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(|| ILError::InvalidInput(format!("Invalid page token {page_token}")))
+}
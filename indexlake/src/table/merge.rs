@@ -0,0 +1,472 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow::array::{AsArray, BooleanArray, RecordBatch, UInt32Array};
+use arrow::datatypes::{DataType, Field, Int64Type, Schema, SchemaRef};
+use arrow::row::{RowConverter, SortField};
+use futures::StreamExt;
+
+use crate::catalog::{
+    CatalogSchema, INTERNAL_ROW_ID_FIELD_NAME, RowLocation, TransactionHelper, rows_to_record_batch,
+};
+use crate::expr::{BinaryOp, Expr, col, lit, split_conjunction_filters};
+use crate::storage::read_parquet_files_by_locations;
+use crate::table::{
+    CheckConstraint, SnapshotOperation, Table, commit_snapshot, enforce_check_constraints,
+    enforce_not_null, has_null_key, has_unique_column, key_arrays, process_delete_rows_by_row_ids,
+    process_insert, process_insert_into_inline_rows, record_replaced_rows, replace_unique_keys,
+};
+use crate::{ILError, ILResult};
+
+/// Prefix of the source columns in the expressions of a merge, see [`source_col`].
+pub const MERGE_SOURCE_PREFIX: &str = "source.";
+
+/// Refers to the column `name` of the source rows of a merge. Target columns are referred to by
+/// their plain name.
+pub fn source_col(name: &str) -> Expr {
+    col(&format!("{MERGE_SOURCE_PREFIX}{name}"))
+}
+
+/// Number of target rows updated and deleted, and of source rows inserted by a merge.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MergeResult {
+    pub updated: u64,
+    pub deleted: u64,
+    pub inserted: u64,
+}
+
+/// Merges source rows into a table, created by [`Table::merge`].
+///
+/// Source rows are matched to target rows by the [`MergeBuilder::on`] condition, a conjunction
+/// of equalities between target columns and [`source_col`]s. Null keys never match. Matched
+/// target rows meeting the [`MergeBuilder::when_matched_delete`] predicate are deleted, the
+/// other matched rows are updated by [`MergeBuilder::when_matched_update`]. Unmatched source
+/// rows are inserted by [`MergeBuilder::when_not_matched_insert`]. Each clause is optional,
+/// rows no clause applies to are left as they are. A target row matched by several source rows
+/// is an error when there is a matched clause, as it is unclear which one applies.
+///
+/// The target rows are read, and all changes committed, in one catalog transaction.
+#[derive(Debug, Clone)]
+pub struct MergeBuilder<'a> {
+    table: &'a Table,
+    source: RecordBatch,
+    on: Option<Expr>,
+    update: Option<HashMap<String, Expr>>,
+    delete: Option<Expr>,
+    insert: bool,
+}
+
+impl<'a> MergeBuilder<'a> {
+    pub(crate) fn new(table: &'a Table, source: RecordBatch) -> Self {
+        Self {
+            table,
+            source,
+            on: None,
+            update: None,
+            delete: None,
+            insert: false,
+        }
+    }
+
+    /// Matches source rows to target rows, e.g. `col("id").eq(source_col("id"))`.
+    pub fn on(mut self, condition: Expr) -> Self {
+        self.on = Some(condition);
+        self
+    }
+
+    /// Sets the target columns of matched rows to the expressions, which may refer to both target
+    /// and source columns.
+    pub fn when_matched_update(mut self, assignments: HashMap<String, Expr>) -> Self {
+        self.update = Some(assignments);
+        self
+    }
+
+    /// Deletes matched rows for which the predicate, over target and source columns, is true.
+    /// Rows deleted are not updated.
+    pub fn when_matched_delete(mut self, predicate: Expr) -> Self {
+        self.delete = Some(predicate);
+        self
+    }
+
+    /// Inserts the source rows matching no target row, the source must have the table schema
+    /// apart from nullable columns it lacks, which are filled with their default value or nulls.
+    pub fn when_not_matched_insert(mut self) -> Self {
+        self.insert = true;
+        self
+    }
+
+    pub async fn execute(self) -> ILResult<MergeResult> {
+        let table = self.table;
+        let plan = &MergePlan::try_new(&self)?;
+        let result = TransactionHelper::run(&table.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                let snapshot_id = tx_helper.get_max_snapshot_id().await? + 1;
+                let result = process_merge(&mut tx_helper, table, plan, snapshot_id).await?;
+                commit_snapshot(&mut tx_helper, table, snapshot_id, SnapshotOperation::Merge)
+                    .await?;
+                tx_helper.commit().await?;
+                Ok(result)
+            })
+        })
+        .await?;
+
+        if result.inserted > 0 || result.updated > 0 {
+            table.try_spawn_dump_task(&plan.source).await?;
+        }
+        Ok(result)
+    }
+}
+
+/// Validated clauses of a merge.
+struct MergePlan {
+    source: RecordBatch,
+    /// Schema of target rows joined with their source row, source columns are prefixed.
+    joined_schema: SchemaRef,
+    target_keys: Vec<String>,
+    source_keys: Vec<String>,
+    update: Option<HashMap<String, Expr>>,
+    delete: Option<Expr>,
+    insert: bool,
+    check_constraints: Vec<CheckConstraint>,
+}
+
+impl MergePlan {
+    fn try_new(builder: &MergeBuilder) -> ILResult<Self> {
+        let table = builder.table;
+        let source = if builder.insert {
+            table.conform_record(&builder.source)?
+        } else {
+            builder.source.clone()
+        };
+        let source_schema = source.schema();
+        let mut joined_fields = table.schema.fields().iter().cloned().collect::<Vec<_>>();
+        for field in source_schema.fields() {
+            joined_fields.push(Arc::new(Field::new(
+                format!("{MERGE_SOURCE_PREFIX}{}", field.name()),
+                field.data_type().clone(),
+                field.is_nullable(),
+            )));
+        }
+        let joined_schema = Arc::new(Schema::new(joined_fields));
+
+        let on = builder
+            .on
+            .as_ref()
+            .ok_or_else(|| ILError::InvalidInput("Merge requires an on condition".to_string()))?;
+        let mut target_keys = Vec::new();
+        let mut source_keys = Vec::new();
+        for conjunct in split_conjunction_filters(vec![on.clone()]) {
+            let (target_key, source_key) = merge_key(&conjunct).ok_or_else(|| {
+                ILError::InvalidInput(format!(
+                    "Merge on condition must be equalities between target and source columns, got {conjunct}"
+                ))
+            })?;
+            let target_type = table.schema.field_with_name(&target_key)?.data_type();
+            let source_type = source_schema.field_with_name(&source_key)?.data_type();
+            if target_key == INTERNAL_ROW_ID_FIELD_NAME || target_type != source_type {
+                return Err(ILError::InvalidInput(format!(
+                    "Merge on condition {conjunct} compares {target_type} with {source_type}"
+                )));
+            }
+            target_keys.push(target_key);
+            source_keys.push(source_key);
+        }
+
+        if let Some(update) = &builder.update {
+            for (name, expr) in update {
+                let field = table.schema.field_with_name(name).ok();
+                let Some(field) = field.filter(|_| name != INTERNAL_ROW_ID_FIELD_NAME) else {
+                    return Err(ILError::InvalidInput(format!(
+                        "Merge updates unknown column {name}"
+                    )));
+                };
+                let data_type = expr.data_type(&joined_schema)?;
+                if &data_type != field.data_type() {
+                    return Err(ILError::InvalidInput(format!(
+                        "Merge sets column {name} of type {} to {data_type}",
+                        field.data_type()
+                    )));
+                }
+            }
+        }
+        if let Some(predicate) = &builder.delete {
+            let data_type = predicate.data_type(&joined_schema)?;
+            if data_type != DataType::Boolean {
+                return Err(ILError::InvalidInput(format!(
+                    "Merge delete predicate must be a boolean expression, but got {data_type}"
+                )));
+            }
+        }
+        Ok(Self {
+            source,
+            joined_schema,
+            target_keys,
+            source_keys,
+            update: builder.update.clone(),
+            delete: builder.delete.clone(),
+            insert: builder.insert,
+            check_constraints: table.config.check_constraints.clone(),
+        })
+    }
+
+    fn has_matched_clause(&self) -> bool {
+        self.update.is_some() || self.delete.is_some()
+    }
+}
+
+/// Returns the target and source column an equality compares.
+fn merge_key(conjunct: &Expr) -> Option<(String, String)> {
+    let Expr::BinaryExpr(binary) = conjunct else {
+        return None;
+    };
+    if binary.op != BinaryOp::Eq {
+        return None;
+    }
+    let (Expr::Column(left), Expr::Column(right)) = (binary.left.as_ref(), binary.right.as_ref())
+    else {
+        return None;
+    };
+    match (
+        left.strip_prefix(MERGE_SOURCE_PREFIX),
+        right.strip_prefix(MERGE_SOURCE_PREFIX),
+    ) {
+        (None, Some(source)) => Some((left.clone(), source.to_string())),
+        (Some(source), None) => Some((right.clone(), source.to_string())),
+        _ => None,
+    }
+}
+
+async fn process_merge(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    plan: &MergePlan,
+    snapshot_id: i64,
+) -> ILResult<MergeResult> {
+    let mut matcher = MergeMatcher::try_new(plan)?;
+
+    // Target rows are read from both the inline rows and the data files
+    let catalog_schema = Arc::new(CatalogSchema::from_arrow(&table.schema)?);
+    let rows = tx_helper
+        .scan_inline_rows(table.table_id, &catalog_schema)
+        .await?;
+    matcher.match_batch(&rows_to_record_batch(&table.schema, &rows)?)?;
+
+    let row_metadatas = tx_helper
+        .scan_row_metadata(table.table_id, &col("deleted").eq(lit(false)))
+        .await?;
+    let data_file_locations = row_metadatas
+        .into_iter()
+        .filter(|meta| matches!(meta.location, RowLocation::Parquet { .. }))
+        .map(|meta| (meta.row_id, meta.location))
+        .collect::<Vec<_>>();
+    if !data_file_locations.is_empty() {
+        let mut stream = read_parquet_files_by_locations(
+            table.storage.clone(),
+            table.schema.clone(),
+            None,
+            data_file_locations,
+            None,
+            &table.field_defaults,
+            &table.field_ids(),
+            None,
+        )
+        .await?;
+        while let Some(batch) = stream.next().await {
+            matcher.match_batch(&batch?)?;
+        }
+    }
+
+    let deleted =
+        process_delete_rows_by_row_ids(tx_helper, table, &matcher.deleted_row_ids, snapshot_id)
+            .await?;
+
+    // Updated rows get their new version inline, data file rows are pointed there
+    let updated_row_ids = matcher.updated_row_ids();
+    if !updated_row_ids.is_empty() {
+        let replaced = col(INTERNAL_ROW_ID_FIELD_NAME)
+            .in_list(updated_row_ids.iter().copied().map(lit).collect(), false);
+        record_replaced_rows(tx_helper, table, replaced, snapshot_id).await?;
+        tx_helper
+            .delete_inline_rows_by_row_ids(table.table_id, &updated_row_ids)
+            .await?;
+        for batch in matcher.updated_batches.iter() {
+            process_insert_into_inline_rows(
+                tx_helper,
+                table.table_id,
+                batch,
+                table.config.catalog_insert_batch_size,
+            )
+            .await?;
+        }
+        tx_helper
+            .update_row_location_as_inline(table.table_id, &updated_row_ids)
+            .await?;
+        if let Some(update) = &plan.update
+            && has_unique_column(table, update.keys())
+        {
+            replace_unique_keys(tx_helper, table, &matcher.updated_batches).await?;
+        }
+    }
+
+    let mut inserted = 0;
+    if plan.insert {
+        let unmatched = matcher
+            .source_matched
+            .iter()
+            .enumerate()
+            .filter(|(_, matched)| !**matched)
+            .map(|(i, _)| i as u32)
+            .collect::<Vec<_>>();
+        let record =
+            arrow::compute::take_record_batch(&plan.source, &UInt32Array::from(unmatched))?;
+        process_insert(tx_helper, table, &record).await?;
+        inserted = record.num_rows();
+    }
+
+    Ok(MergeResult {
+        updated: updated_row_ids.len() as u64,
+        deleted: deleted as u64,
+        inserted: inserted as u64,
+    })
+}
+
+/// Matches batches of target rows with the source rows, collecting the rows to delete and the
+/// new versions of the rows to update.
+struct MergeMatcher<'a> {
+    plan: &'a MergePlan,
+    converter: RowConverter,
+    /// Source row indices by row encoded key.
+    source_rows: HashMap<Box<[u8]>, Vec<u32>>,
+    source_matched: Vec<bool>,
+    deleted_row_ids: Vec<i64>,
+    updated_batches: Vec<RecordBatch>,
+}
+
+impl<'a> MergeMatcher<'a> {
+    fn try_new(plan: &'a MergePlan) -> ILResult<Self> {
+        let source_keys = key_arrays(&plan.source, &plan.source_keys)?;
+        let converter = RowConverter::new(
+            source_keys
+                .iter()
+                .map(|array| SortField::new(array.data_type().clone()))
+                .collect(),
+        )?;
+        let rows = converter.convert_columns(&source_keys)?;
+        let mut source_rows: HashMap<Box<[u8]>, Vec<u32>> = HashMap::new();
+        for i in 0..plan.source.num_rows() {
+            if !has_null_key(&source_keys, i) {
+                source_rows
+                    .entry(rows.row(i).as_ref().into())
+                    .or_default()
+                    .push(i as u32);
+            }
+        }
+        Ok(Self {
+            plan,
+            converter,
+            source_rows,
+            source_matched: vec![false; plan.source.num_rows()],
+            deleted_row_ids: Vec::new(),
+            updated_batches: Vec::new(),
+        })
+    }
+
+    fn match_batch(&mut self, batch: &RecordBatch) -> ILResult<()> {
+        let target_keys = key_arrays(batch, &self.plan.target_keys)?;
+        let rows = self.converter.convert_columns(&target_keys)?;
+        let row_ids = batch
+            .column_by_name(INTERNAL_ROW_ID_FIELD_NAME)
+            .and_then(|array| array.as_primitive_opt::<Int64Type>())
+            .ok_or_else(|| {
+                ILError::InternalError("row id array should be Int64Array".to_string())
+            })?;
+
+        let mut target_indices = Vec::new();
+        let mut source_indices = Vec::new();
+        for i in 0..batch.num_rows() {
+            if has_null_key(&target_keys, i) {
+                continue;
+            }
+            let Some(matched) = self.source_rows.get(rows.row(i).as_ref()) else {
+                continue;
+            };
+            for source_idx in matched {
+                self.source_matched[*source_idx as usize] = true;
+            }
+            if !self.plan.has_matched_clause() {
+                continue;
+            }
+            if matched.len() > 1 {
+                return Err(ILError::InvalidInput(format!(
+                    "Merge matches row {} with {} source rows",
+                    row_ids.value(i),
+                    matched.len()
+                )));
+            }
+            target_indices.push(i as u32);
+            source_indices.push(matched[0]);
+        }
+        if target_indices.is_empty() {
+            return Ok(());
+        }
+
+        let target = arrow::compute::take_record_batch(batch, &UInt32Array::from(target_indices))?;
+        let source = arrow::compute::take_record_batch(
+            &self.plan.source,
+            &UInt32Array::from(source_indices),
+        )?;
+        let mut joined_columns = target.columns().to_vec();
+        joined_columns.extend(source.columns().iter().cloned());
+        let joined = RecordBatch::try_new(self.plan.joined_schema.clone(), joined_columns)?;
+
+        let mut kept = joined;
+        if let Some(predicate) = &self.plan.delete {
+            let array = predicate.eval(&kept)?.into_array(kept.num_rows())?;
+            let deleted = array.as_boolean_opt().ok_or_else(|| {
+                ILError::InternalError(format!(
+                    "predicate should return BooleanArray, but got {:?}",
+                    array.data_type()
+                ))
+            })?;
+            let deleted_rows = arrow::compute::filter_record_batch(&kept, deleted)?;
+            let deleted_row_ids = deleted_rows.column(0).as_primitive::<Int64Type>();
+            self.deleted_row_ids
+                .extend(deleted_row_ids.values().iter().copied());
+            // Rows the predicate is null for are not deleted
+            let not_deleted = deleted
+                .iter()
+                .map(|deleted| Some(deleted != Some(true)))
+                .collect::<BooleanArray>();
+            kept = arrow::compute::filter_record_batch(&kept, &not_deleted)?;
+        }
+
+        if let Some(update) = &self.plan.update
+            && kept.num_rows() > 0
+        {
+            let mut columns = kept.columns()[..target.num_columns()].to_vec();
+            for (name, expr) in update {
+                let idx = target.schema().index_of(name)?;
+                let array = expr.eval(&kept)?.into_array(kept.num_rows())?;
+                enforce_not_null(target.schema().field(idx), &array, Some(&columns[0]))?;
+                columns[idx] = array;
+            }
+            let updated = RecordBatch::try_new(target.schema(), columns)?;
+            enforce_check_constraints(&self.plan.check_constraints, &updated)?;
+            self.updated_batches.push(updated);
+        }
+        Ok(())
+    }
+
+    fn updated_row_ids(&self) -> Vec<i64> {
+        self.updated_batches
+            .iter()
+            .flat_map(|batch| {
+                batch
+                    .column(0)
+                    .as_primitive::<Int64Type>()
+                    .values()
+                    .to_vec()
+            })
+            .collect()
+    }
+}
@@ -1,25 +1,83 @@
+mod add_files;
+mod alter;
+mod clone;
+mod clustering;
+mod column_default;
+mod column_stats;
+mod compact;
 mod config;
+mod constraint;
+mod count;
 mod create;
 mod delete;
 mod drop;
 mod dump;
+mod expire;
+mod export;
+mod flush;
+mod ingest;
 mod insert;
+mod list;
+mod merge;
+mod partition;
+mod presign;
+mod rebuild;
+mod relocate;
 mod scan;
+mod search;
+mod sink;
+mod snapshot;
+mod stats;
+mod sync;
+mod transaction;
 mod truncate;
+mod unique;
 mod update;
+mod upsert;
+mod vacuum;
+mod verify;
 
+pub use add_files::*;
+pub(crate) use alter::*;
+pub(crate) use clone::*;
+pub(crate) use clustering::*;
+pub use column_default::*;
+pub(crate) use column_stats::*;
+pub use compact::*;
 pub use config::*;
+pub use constraint::*;
+pub(crate) use count::*;
 pub use create::*;
 pub(crate) use delete::*;
 pub(crate) use drop::*;
 pub(crate) use dump::*;
+pub use expire::*;
+pub use export::*;
+pub use flush::*;
+pub use ingest::*;
 pub(crate) use insert::*;
+pub use list::*;
+pub use merge::*;
+pub use partition::*;
+pub use presign::*;
+pub(crate) use rebuild::*;
+pub(crate) use relocate::*;
 pub use scan::*;
-pub(crate) use truncate::*;
+pub(crate) use search::*;
+pub use sink::*;
+pub use snapshot::*;
+pub use stats::*;
+pub(crate) use sync::*;
+pub use transaction::*;
+pub use truncate::*;
+pub use unique::*;
 pub(crate) use update::*;
+pub(crate) use upsert::*;
+pub use vacuum::*;
+pub use verify::*;
 
 use crate::RecordBatchStream;
-use crate::catalog::{CatalogHelper, CatalogSchemaRef, Scalar};
+use crate::catalog::{CatalogHelper, INTERNAL_ROW_ID_FIELD_NAME, Scalar, check_writable};
 use crate::expr::Expr;
 use crate::index::{Index, IndexDefination, IndexDefinationRef, SearchQuery};
 use crate::utils::{has_duplicated_items, schema_with_row_id};
@@ -28,10 +86,13 @@ use crate::{
     catalog::{Catalog, RowStream, TransactionHelper},
     storage::Storage,
 };
-use arrow::array::RecordBatch;
-use arrow::datatypes::{DataType, FieldRef, SchemaRef};
+use arrow::array::{RecordBatch, RecordBatchOptions, make_array, new_null_array};
+use arrow::datatypes::{DataType, Field, FieldRef, Schema, SchemaRef};
+use parquet::arrow::PARQUET_FIELD_ID_META_KEY;
 use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::{Duration, SystemTime};
 
 #[derive(Debug, Clone)]
 pub struct Table {
@@ -41,11 +102,20 @@ pub struct Table {
     pub table_name: String,
     pub field_map: BTreeMap<i64, FieldRef>,
     pub schema: SchemaRef,
+    /// Default values of columns added after table creation, filled in for rows of data files
+    /// written before.
+    pub field_defaults: HashMap<String, Scalar>,
+    /// Defaults of columns applied to inserted rows, see [`ColumnDefault`].
+    pub column_defaults: HashMap<String, ColumnDefault>,
+    /// Number of schema changes of the table, bumped by every added or dropped column.
+    pub schema_version: i64,
     pub indexes: HashMap<String, IndexDefinationRef>,
     pub config: Arc<TableConfig>,
     pub catalog: Arc<dyn Catalog>,
     pub storage: Arc<Storage>,
     pub index_kinds: HashMap<String, Arc<dyn Index>>,
+    /// Snapshot the commits of the handle are based on, see [`Table::with_base_snapshot`].
+    pub(crate) base_snapshot: Option<Arc<AtomicI64>>,
 }
 
 impl Table {
@@ -53,31 +123,497 @@ impl Table {
         TransactionHelper::new(&self.catalog).await
     }
 
+    /// Directory holding the data files and index files of the table in the storage.
+    pub fn table_dir(&self) -> String {
+        self.config.table_dir(self.namespace_id, self.table_id)
+    }
+
+    /// Field ids of the columns in the data files by name, clones keep those of their source.
+    pub(crate) fn field_ids(&self) -> HashMap<String, i64> {
+        self.field_map
+            .iter()
+            .map(|(field_id, field)| {
+                let file_field_id = self.config.file_field_ids.get(field_id).unwrap_or(field_id);
+                (field.name().clone(), *file_field_id)
+            })
+            .collect()
+    }
+
+    /// Schema data files are written with, the fields carry their field id so that columns
+    /// still resolve once renamed.
+    pub(crate) fn data_file_schema(&self) -> SchemaRef {
+        let field_ids = self.field_ids();
+        let fields = self
+            .schema
+            .fields()
+            .iter()
+            .map(|field| match field_ids.get(field.name()) {
+                Some(field_id) => {
+                    let mut metadata = field.metadata().clone();
+                    metadata.insert(PARQUET_FIELD_ID_META_KEY.to_string(), field_id.to_string());
+                    Arc::new(field.as_ref().clone().with_metadata(metadata))
+                }
+                None => field.clone(),
+            })
+            .collect::<Vec<_>>();
+        Arc::new(Schema::new_with_metadata(
+            fields,
+            self.schema.metadata().clone(),
+        ))
+    }
+
     pub async fn create_index(&mut self, index_creation: IndexCreation) -> ILResult<()> {
+        check_writable(&self.catalog)?;
         let mut tx_helper = self.transaction_helper().await?;
         process_create_index(&mut tx_helper, self, index_creation).await?;
         tx_helper.commit().await?;
         Ok(())
     }
 
+    /// Rebuilds the index files of an index from the data files of the table, recomputing the
+    /// file metadata scans prune with. The catalog switches to the new index files in one
+    /// transaction, so scans see either the old or the new ones. The replaced index files are
+    /// kept in storage for scans still reading them, [`Table::vacuum`] deletes them.
+    pub async fn rebuild_index(&self, index_name: &str) -> ILResult<()> {
+        check_writable(&self.catalog)?;
+        TransactionHelper::run(&self.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                process_rebuild_index(&mut tx_helper, self, index_name).await?;
+                tx_helper.commit().await
+            })
+        })
+        .await
+    }
+
+    /// Builds the index files missing for data files written with
+    /// [`TableConfig::deferred_indexing`], returning the number of index files written. Scans
+    /// read data files without index files in full until then, see
+    /// [`IndexDescription::unindexed_data_file_count`].
+    pub async fn sync_indexes(&self) -> ILResult<usize> {
+        check_writable(&self.catalog)?;
+        TransactionHelper::run(&self.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                // Waits for or conflicts with a running dump task, like flushes do
+                tx_helper.insert_dump_task(self.table_id).await?;
+                let written = process_sync_indexes(&mut tx_helper, self).await?;
+                tx_helper.delete_dump_task(self.table_id).await?;
+                tx_helper.commit().await?;
+                Ok(written)
+            })
+        })
+        .await
+    }
+
+    /// Adds a nullable column. Data files are not rewritten, rows written before read as
+    /// `default` or null. A [`ColumnDefault`] declared on `field` applies to rows inserted
+    /// after.
+    pub async fn add_column(&mut self, field: Field, default: Option<Scalar>) -> ILResult<()> {
+        let table = &*self;
+        let field = &field;
+        let default = default.as_ref();
+        let (field_id, schema_version) = TransactionHelper::run(&self.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                let field_id = process_add_column(&mut tx_helper, table, field, default).await?;
+                let schema_version = tx_helper.get_table_schema_version(table.table_id).await?;
+                tx_helper.commit().await?;
+                Ok((field_id, schema_version))
+            })
+        })
+        .await?;
+
+        let (field, column_default) = split_column_default(field)?;
+        let field = Arc::new(field);
+        self.field_map.insert(field_id, field.clone());
+        let mut fields = self.schema.fields().to_vec();
+        fields.push(field.clone());
+        self.schema = Arc::new(Schema::new_with_metadata(
+            fields,
+            self.schema.metadata().clone(),
+        ));
+        if let Some(default) = default {
+            self.field_defaults
+                .insert(field.name().clone(), default.clone());
+        }
+        if let Some(column_default) = column_default {
+            self.column_defaults
+                .insert(field.name().clone(), column_default);
+        }
+        self.schema_version = schema_version;
+        Ok(())
+    }
+
+    /// Drops a column from the table schema. Data files are not rewritten, the column is only
+    /// no longer read from them. With `cascade`, indexes using the column are dropped as well,
+    /// otherwise such a column can not be dropped.
+    pub async fn drop_column(&mut self, field_name: &str, cascade: bool) -> ILResult<()> {
+        let table = &*self;
+        let (field_id, schema_version) = TransactionHelper::run(&self.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                let field_id =
+                    process_drop_column(&mut tx_helper, table, field_name, cascade).await?;
+                let schema_version = tx_helper.get_table_schema_version(table.table_id).await?;
+                tx_helper.commit().await?;
+                Ok((field_id, schema_version))
+            })
+        })
+        .await?;
+
+        let dropped_indexes = indexes_using_column(self, field_name)
+            .into_iter()
+            .map(|index| index.name.clone())
+            .collect::<Vec<_>>();
+        for index_name in dropped_indexes {
+            self.indexes.remove(&index_name);
+        }
+        self.field_map.remove(&field_id);
+        let fields = self
+            .schema
+            .fields()
+            .iter()
+            .filter(|field| field.name() != field_name)
+            .cloned()
+            .collect::<Vec<_>>();
+        self.schema = Arc::new(Schema::new_with_metadata(
+            fields,
+            self.schema.metadata().clone(),
+        ));
+        self.field_defaults.remove(field_name);
+        self.column_defaults.remove(field_name);
+        self.schema_version = schema_version;
+        Ok(())
+    }
+
+    /// Renames a column. Data files are not rewritten, their columns are matched by field id
+    /// so rows written before read under the new name. Columns used by indexes can not be
+    /// renamed, names of dropped columns can not be reused.
+    pub async fn rename_column(&mut self, old_name: &str, new_name: &str) -> ILResult<()> {
+        let table = &*self;
+        let (field_id, config, schema_version) =
+            TransactionHelper::run(&self.catalog, |mut tx_helper| {
+                Box::pin(async move {
+                    let (field_id, config) =
+                        process_rename_column(&mut tx_helper, table, old_name, new_name).await?;
+                    let schema_version = tx_helper.get_table_schema_version(table.table_id).await?;
+                    tx_helper.commit().await?;
+                    Ok((field_id, config, schema_version))
+                })
+            })
+            .await?;
+
+        let field = Arc::new(
+            self.field_map[&field_id]
+                .as_ref()
+                .clone()
+                .with_name(new_name),
+        );
+        self.field_map.insert(field_id, field.clone());
+        let fields = self
+            .schema
+            .fields()
+            .iter()
+            .map(|f| {
+                if f.name() == old_name {
+                    field.clone()
+                } else {
+                    f.clone()
+                }
+            })
+            .collect::<Vec<_>>();
+        self.schema = Arc::new(Schema::new_with_metadata(
+            fields,
+            self.schema.metadata().clone(),
+        ));
+        if let Some(default) = self.field_defaults.remove(old_name) {
+            self.field_defaults.insert(new_name.to_string(), default);
+        }
+        if let Some(column_default) = self.column_defaults.remove(old_name) {
+            self.column_defaults
+                .insert(new_name.to_string(), column_default);
+        }
+        self.config = Arc::new(config);
+        self.schema_version = schema_version;
+        Ok(())
+    }
+
+    /// Adds a check constraint, enforced on rows written afterwards through this table and tables
+    /// loaded after. With `validate`, the rows of the table are checked first and the constraint
+    /// is not added if one of them violates it, otherwise the constraint is marked
+    /// [`CheckConstraint::not_valid`].
+    pub async fn add_check_constraint(
+        &mut self,
+        mut constraint: CheckConstraint,
+        validate: bool,
+    ) -> ILResult<()> {
+        constraint.not_valid = !validate;
+        let mut config = self.config.as_ref().clone();
+        config.check_constraints.push(constraint.clone());
+        check_constraint_definitions(&self.schema, &config.check_constraints)?;
+        check_unique_constraint_definitions(
+            &self.schema,
+            &config.unique_constraints,
+            &config.check_constraints,
+        )?;
+        if validate {
+            validate_check_constraint(self, &constraint).await?;
+        }
+        self.update_config(config).await
+    }
+
+    /// Drops the check constraint `name`.
+    pub async fn drop_check_constraint(&mut self, name: &str) -> ILResult<()> {
+        let mut config = self.config.as_ref().clone();
+        let count = config.check_constraints.len();
+        config
+            .check_constraints
+            .retain(|constraint| constraint.name != name);
+        if config.check_constraints.len() == count {
+            return Err(ILError::InvalidInput(format!(
+                "Check constraint {name} not found in table {}",
+                self.table_name
+            )));
+        }
+        self.update_config(config).await
+    }
+
+    /// Sets or removes the TTL of the table, see [`Table::expire_rows`]. Only the table config
+    /// changes, no data file is rewritten.
+    pub async fn set_ttl(&mut self, ttl: Option<TtlPolicy>) -> ILResult<()> {
+        if let Some(ttl) = &ttl {
+            check_ttl_policy(&self.schema, ttl)?;
+        }
+        let mut config = self.config.as_ref().clone();
+        config.ttl = ttl;
+        self.update_config(config).await
+    }
+
+    async fn update_config(&mut self, config: TableConfig) -> ILResult<()> {
+        let (table_id, config_ref) = (self.table_id, &config);
+        TransactionHelper::run(&self.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                tx_helper.update_table_config(table_id, config_ref).await?;
+                tx_helper.commit().await
+            })
+        })
+        .await?;
+        self.config = Arc::new(config);
+        Ok(())
+    }
+
+    /// Inserts rows of `record`. Columns it lacks are filled with their [`ColumnDefault`],
+    /// nullable ones without one with their default value or nulls.
     pub async fn insert(&self, record: &RecordBatch) -> ILResult<()> {
-        let mut tx_helper = self.transaction_helper().await?;
-        let schema = schema_with_row_id(&record.schema());
-        if &schema != self.schema.as_ref() {
+        let record = &self.conform_record(record)?;
+        TransactionHelper::run(&self.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                let snapshot_id = tx_helper.get_max_snapshot_id().await? + 1;
+                process_insert(&mut tx_helper, self, record).await?;
+                commit_snapshot(&mut tx_helper, self, snapshot_id, SnapshotOperation::Insert)
+                    .await?;
+                tx_helper.commit().await
+            })
+        })
+        .await?;
+
+        self.try_spawn_dump_task(record).await
+    }
+
+    /// Inserts rows of all `batches` in one transaction and snapshot, so either all or none of
+    /// them become visible. Every batch is checked against the table schema before any row is
+    /// written, the rows are then written as one batch.
+    pub async fn insert_many(&self, batches: Vec<RecordBatch>) -> ILResult<()> {
+        let records = batches
+            .iter()
+            .map(|batch| self.conform_record(batch))
+            .collect::<ILResult<Vec<_>>>()?;
+        let Some(first) = records.first() else {
+            return Ok(());
+        };
+        let record = &arrow::compute::concat_batches(&first.schema(), &records)?;
+        self.insert(record).await
+    }
+
+    /// Opens a sink buffering written batches and committing them as data files of the table
+    /// once [`SinkOptions::max_buffered_rows`], [`SinkOptions::max_buffered_bytes`] or
+    /// [`SinkOptions::flush_interval`] is reached, instead of a commit per batch. Rows are
+    /// committed in data files directly rather than inline in the catalog, see [`InsertSink`].
+    pub fn insert_sink(&self, options: SinkOptions) -> ILResult<InsertSink> {
+        check_writable(&self.catalog)?;
+        InsertSink::try_new(self, options)
+    }
+
+    /// Inserts rows of `record`, replacing existing rows with the same primary key. When
+    /// several rows of `record` share a key, the last one wins.
+    pub async fn upsert(&self, record: &RecordBatch) -> ILResult<()> {
+        if self.config.primary_key.is_empty() {
+            return Err(ILError::InvalidInput(format!(
+                "Table {} has no primary key",
+                self.table_name
+            )));
+        }
+        let primary_key = self
+            .config
+            .primary_key
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        self.upsert_by_key(record, &primary_key).await
+    }
+
+    /// Inserts rows of `record` in one transaction, replacing existing rows with the same values
+    /// of `key_columns`. When several rows of `record` share a key, the last one wins. Rows with
+    /// a null key value never replace existing rows.
+    ///
+    /// Existing rows are looked up with an index on one of the key columns when there is one
+    /// supporting `IN` filters, e.g. a hash index, otherwise the key columns of all data files
+    /// are read. Upserts of disjoint keys only conflict on the catalog transaction, they are
+    /// retried like other transactions.
+    pub async fn upsert_by_key(&self, record: &RecordBatch, key_columns: &[&str]) -> ILResult<()> {
+        if key_columns.is_empty() {
+            return Err(ILError::InvalidInput(
+                "Upsert requires at least one key column".to_string(),
+            ));
+        }
+        if has_duplicated_items(key_columns.iter()) {
             return Err(ILError::InvalidInput(format!(
+                "Duplicated key columns {key_columns:?}"
+            )));
+        }
+        let record = &self.conform_record(record)?;
+        let key_columns = key_columns
+            .iter()
+            .map(|name| {
+                if *name == INTERNAL_ROW_ID_FIELD_NAME || self.schema.field_with_name(name).is_err()
+                {
+                    return Err(ILError::InvalidInput(format!(
+                        "Key column {name} not found in table {}",
+                        self.table_name
+                    )));
+                }
+                Ok(name.to_string())
+            })
+            .collect::<ILResult<Vec<_>>>()?;
+        let key_columns = &key_columns;
+        TransactionHelper::run(&self.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                let snapshot_id = tx_helper.get_max_snapshot_id().await? + 1;
+                process_upsert(&mut tx_helper, self, record, key_columns, snapshot_id).await?;
+                commit_snapshot(&mut tx_helper, self, snapshot_id, SnapshotOperation::Upsert)
+                    .await?;
+                tx_helper.commit().await
+            })
+        })
+        .await?;
+
+        self.try_spawn_dump_task(record).await
+    }
+
+    /// Conforms `record` to the table schema and checks its rows against the NOT NULL columns
+    /// and check constraints of the table.
+    fn conform_record(&self, record: &RecordBatch) -> ILResult<RecordBatch> {
+        let record = self.fill_record(record)?;
+        enforce_check_constraints(&self.config.check_constraints, &record)?;
+        Ok(record)
+    }
+
+    /// Checks `record` has the table schema, filling columns it lacks, e.g. ones added after it
+    /// was built, with their [`ColumnDefault`], or nullable ones with their default value or
+    /// nulls. Nulls of columns whose default applies to nulls are filled as well. Timestamps in
+    /// another time zone than their column are taken in the time zone of the column.
+    fn fill_record(&self, record: &RecordBatch) -> ILResult<RecordBatch> {
+        let schema = schema_with_row_id(&record.schema());
+        let fills_nulls = self
+            .column_defaults
+            .values()
+            .any(|column_default| column_default.mode == DefaultMode::OnNull);
+        if &schema == self.schema.as_ref() && !fills_nulls {
+            return Ok(record.clone());
+        }
+        let mismatch = || {
+            ILError::InvalidInput(format!(
                 "Schema mismatch: table schema {:?}, record batch schema {:?}",
                 self.schema, schema
-            )));
+            ))
+        };
+
+        let num_rows = record.num_rows();
+        let mut fields = Vec::new();
+        let mut present_fields = Vec::new();
+        let mut columns = Vec::new();
+        for field in self.schema.fields() {
+            if field.name() == INTERNAL_ROW_ID_FIELD_NAME {
+                continue;
+            }
+            let column = record.column_by_name(field.name());
+            if column.is_some() {
+                present_fields.push(field.clone());
+            }
+            let column = match (column, self.column_defaults.get(field.name())) {
+                (column, Some(column_default)) => {
+                    column_default.apply(column, field.data_type(), num_rows)?
+                }
+                (Some(column), None) if is_zone_change(column.data_type(), field.data_type()) => {
+                    make_array(
+                        column
+                            .to_data()
+                            .into_builder()
+                            .data_type(field.data_type().clone())
+                            .build()?,
+                    )
+                }
+                (Some(column), None) => column.clone(),
+                (None, None) if field.is_nullable() => {
+                    match self.field_defaults.get(field.name()) {
+                        Some(default) => default.to_array_of_size(num_rows)?,
+                        None => new_null_array(field.data_type(), num_rows),
+                    }
+                }
+                (None, None) => return Err(mismatch()),
+            };
+            fields.push(field.clone());
+            columns.push(column);
+        }
+        // present columns must keep the table order and field definitions, nullable ones are
+        // allowed for non-nullable columns as long as they hold no nulls once defaults apply
+        let record_schema = record.schema();
+        if record_schema.fields().len() != present_fields.len() {
+            return Err(mismatch());
+        }
+        for (record_field, field) in record_schema.fields().iter().zip(present_fields.iter()) {
+            let (mut record_field, _) = split_column_default(record_field)?;
+            if is_zone_change(record_field.data_type(), field.data_type()) {
+                record_field = record_field.with_data_type(field.data_type().clone());
+            }
+            if &record_field != field.as_ref()
+                && (field.is_nullable()
+                    || record_field.with_nullable(false) != field.as_ref().clone())
+            {
+                return Err(mismatch());
+            }
+        }
+        for (field, column) in fields.iter().zip(columns.iter()) {
+            enforce_not_null(field, column, None)?;
         }
-        process_insert(&mut tx_helper, self.table_id, record).await?;
-        tx_helper.commit().await?;
 
+        let schema = Schema::new_with_metadata(fields, self.schema.metadata().clone());
+        let options = RecordBatchOptions::new().with_row_count(Some(num_rows));
+        Ok(RecordBatch::try_new_with_options(
+            Arc::new(schema),
+            columns,
+            &options,
+        )?)
+    }
+
+    /// Dumps inline rows into a data file once they reach the row count or byte limit of the
+    /// table. Their size is estimated from the size per row of `inserted`.
+    async fn try_spawn_dump_task(&self, inserted: &RecordBatch) -> ILResult<()> {
         let catalog_helper = CatalogHelper::new(self.catalog.clone());
-        let inline_row_count = catalog_helper.count_inline_rows(self.table_id).await?;
-        if inline_row_count as usize >= self.config.inline_row_count_limit {
-            spawn_dump_task(self).await?;
+        let inline_row_count = catalog_helper.count_inline_rows(self.table_id, &[]).await? as usize;
+        if inline_row_count >= self.config.inline_row_count_limit {
+            spawn_dump_task(self, self.config.inline_row_count_limit).await?;
+        } else if inline_row_count * estimated_row_size(inserted)? >= self.config.inline_byte_limit
+        {
+            spawn_dump_task(self, 1).await?;
         }
-
         Ok(())
     }
 
@@ -90,60 +626,417 @@ impl Table {
             scan,
             self.storage.clone(),
             self,
+            false,
         )
         .await?;
         Ok(record_batch_stream)
     }
 
+    /// Scans like [`Table::scan`], but opens data files one at a time as the stream is polled
+    /// instead of reading all of them at once. Memory stays bounded by the batches of one row
+    /// group whatever the size of the table, and a consumer polling slowly slows down reading.
+    /// Projection and filters are applied while decoding, before batches are yielded.
+    pub async fn scan_stream(&self, scan: TableScan) -> ILResult<RecordBatchStream> {
+        let catalog_helper = CatalogHelper::new(self.catalog.clone());
+        process_scan(
+            &catalog_helper,
+            self.table_id,
+            &self.schema,
+            scan,
+            self.storage.clone(),
+            self,
+            true,
+        )
+        .await
+    }
+
+    /// Writes the rows of [`ExportOptions::scan`] to parquet files under `prefix` in `dest`,
+    /// with the schema of the scanned columns, and returns the files written. A new file is
+    /// started each time one reaches [`ExportOptions::target_file_size`]. The rows are read at
+    /// a single snapshot, which `{prefix}/_manifest.json` records along with the files, see
+    /// [`ExportManifest`].
+    pub async fn export_parquet(
+        &self,
+        options: ExportOptions,
+        dest: &Storage,
+        prefix: &str,
+    ) -> ILResult<Vec<ExportedFile>> {
+        process_export_parquet(self, &options, dest, prefix).await
+    }
+
+    /// Lists the snapshots of the table that have not expired, oldest first.
+    pub async fn snapshots(&self) -> ILResult<Vec<SnapshotMeta>> {
+        process_snapshots(self).await
+    }
+
+    /// Id of the latest snapshot of the table.
+    pub async fn current_snapshot_id(&self) -> ILResult<i64> {
+        let catalog_helper = CatalogHelper::new(self.catalog.clone());
+        let snapshots = catalog_helper.get_snapshots(self.table_id).await?;
+        Ok(snapshots
+            .last()
+            .map(|snapshot| snapshot.snapshot_id)
+            .unwrap_or(0))
+    }
+
+    /// Returns a handle of the table whose commits are based on the snapshot `snapshot_id`.
+    /// Commits recording a snapshot and compactions through the handle fail with
+    /// [`ILError::CommitConflict`] if the table has a later snapshot, committing nothing. The
+    /// check runs within the commit transaction, so of concurrent writers based on the same
+    /// snapshot only one commits. Each commit of the handle moves its base to the snapshot it
+    /// recorded, clones of the handle share the base.
+    ///
+    /// Read the table with [`TableScan::at_snapshot`] at the base snapshot, and on conflict
+    /// take a new handle at [`Table::current_snapshot_id`] and retry.
+    pub fn with_base_snapshot(&self, snapshot_id: i64) -> Table {
+        Table {
+            base_snapshot: Some(Arc::new(AtomicI64::new(snapshot_id))),
+            ..self.clone()
+        }
+    }
+
+    /// Snapshot the commits of the handle are based on, `None` if they are not checked.
+    pub fn base_snapshot_id(&self) -> Option<i64> {
+        self.base_snapshot
+            .as_ref()
+            .map(|base_snapshot| base_snapshot.load(Ordering::SeqCst))
+    }
+
+    /// Scans the rows as they were when the snapshot `snapshot_id` was committed, like
+    /// [`TableScan::at_snapshot`].
+    pub async fn scan_as_of(
+        &self,
+        snapshot_id: i64,
+        scan: TableScan,
+    ) -> ILResult<RecordBatchStream> {
+        self.scan(scan.at_snapshot(snapshot_id)).await
+    }
+
+    /// Scans the rows as they were at `timestamp`, like [`TableScan::at_timestamp`].
+    pub async fn scan_as_of_timestamp(
+        &self,
+        timestamp: SystemTime,
+        scan: TableScan,
+    ) -> ILResult<RecordBatchStream> {
+        self.scan(scan.at_timestamp(timestamp)).await
+    }
+
+    /// Returns the rows matching `query`, best ranked first, searched with the first index
+    /// (by name) whose kind supports the query. Inline rows are searched as well.
     pub async fn search(&self, query: Arc<dyn SearchQuery>) -> ILResult<RecordBatchStream> {
-        todo!()
+        process_search(self, query).await
     }
 
-    pub async fn update(&self, set_map: HashMap<String, Scalar>, condition: &Expr) -> ILResult<()> {
+    /// Starts a merge of `source` into the table, see [`MergeBuilder`].
+    pub fn merge(&self, source: RecordBatch) -> MergeBuilder<'_> {
+        MergeBuilder::new(self, source)
+    }
+
+    /// Updates the rows matching `condition`, setting each assigned column to its expression
+    /// evaluated on the current values of the row, and returns the number of rows updated.
+    /// Assignments are checked against the schema before any row is read. No snapshot is
+    /// committed when no row matches.
+    pub async fn update(
+        &self,
+        condition: &Expr,
+        assignments: Vec<(String, Expr)>,
+    ) -> ILResult<u64> {
         check_condition_data_type(condition, &self.schema)?;
-        let mut tx_helper = self.transaction_helper().await?;
-        process_update(
-            &mut tx_helper,
-            self.storage.clone(),
-            self.table_id,
-            &self.schema,
-            set_map,
-            condition,
-        )
-        .await?;
-        tx_helper.commit().await?;
-        Ok(())
+        check_update_assignments(self, &assignments)?;
+        let assignments = &assignments;
+        TransactionHelper::run(&self.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                let snapshot_id = tx_helper.get_max_snapshot_id().await? + 1;
+                let updated_count =
+                    process_update(&mut tx_helper, self, assignments, condition, snapshot_id)
+                        .await?;
+                if updated_count > 0 {
+                    commit_snapshot(&mut tx_helper, self, snapshot_id, SnapshotOperation::Update)
+                        .await?;
+                }
+                tx_helper.commit().await?;
+                Ok(updated_count as u64)
+            })
+        })
+        .await
     }
 
-    pub async fn delete(&self, condition: &Expr) -> ILResult<()> {
+    /// Deletes rows matching `condition` and returns the number of rows deleted. Inline rows are
+    /// removed from the catalog, rows in data files are marked deleted in their row metadata and
+    /// skipped by scans. Only the data files whose statistics may match are read, and only the
+    /// rows the indexes supporting the condition select. No snapshot is committed when no row
+    /// matches.
+    pub async fn delete(&self, condition: &Expr) -> ILResult<u64> {
         check_condition_data_type(condition, &self.schema)?;
-        let mut tx_helper = self.transaction_helper().await?;
-        process_delete(
-            &mut tx_helper,
-            self.storage.clone(),
+        TransactionHelper::run(&self.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                let snapshot_id = tx_helper.get_max_snapshot_id().await? + 1;
+                let deleted_count =
+                    process_delete(&mut tx_helper, self, condition, snapshot_id).await?;
+                if deleted_count > 0 {
+                    commit_snapshot(&mut tx_helper, self, snapshot_id, SnapshotOperation::Delete)
+                        .await?;
+                }
+                tx_helper.commit().await?;
+                Ok(deleted_count as u64)
+            })
+        })
+        .await
+    }
+
+    /// Deletes all rows of the table like [`Table::truncate`] and returns the number of rows
+    /// deleted. Data files are dropped from the catalog without being read, they stay in storage
+    /// for earlier snapshots until vacuumed.
+    pub async fn delete_all(&self) -> ILResult<u64> {
+        TransactionHelper::run(&self.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                let snapshot_id = tx_helper.get_max_snapshot_id().await? + 1;
+                let report = process_truncate(&mut tx_helper, self, snapshot_id).await?;
+                if report.removed_rows > 0 {
+                    commit_snapshot(&mut tx_helper, self, snapshot_id, SnapshotOperation::Delete)
+                        .await?;
+                }
+                tx_helper.commit().await?;
+                Ok(report.removed_rows)
+            })
+        })
+        .await
+    }
+
+    /// Registers existing parquet files in the storage as data files of the table, see
+    /// [`IngestOptions`]. Files whose schema does not match the table are rejected with the
+    /// differences, nothing is registered unless every file is.
+    pub async fn ingest_parquet(&self, paths: Vec<String>, options: IngestOptions) -> ILResult<()> {
+        let (paths, options) = (&paths, &options);
+        TransactionHelper::run(&self.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                let snapshot_id = tx_helper.get_max_snapshot_id().await? + 1;
+                process_ingest_parquet(&mut tx_helper, self, paths, options).await?;
+                commit_snapshot(
+                    &mut tx_helper,
+                    self,
+                    snapshot_id,
+                    SnapshotOperation::IngestParquet,
+                )
+                .await?;
+                tx_helper.commit().await
+            })
+        })
+        .await
+    }
+
+    /// Registers existing parquet files in the storage where they are as data files of the
+    /// table, without rewriting them, see [`AddFilesOptions`]. Files that can not be read or
+    /// whose schema does not match the table are listed in [`AddFilesReport::rejected_files`]
+    /// with the reason, the others are added in a single commit. Added files must not be
+    /// changed or deleted while the table refers to them.
+    pub async fn add_files(
+        &self,
+        paths: Vec<String>,
+        options: AddFilesOptions,
+    ) -> ILResult<AddFilesReport> {
+        let (paths, options) = (&paths, &options);
+        TransactionHelper::run(&self.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                let snapshot_id = tx_helper.get_max_snapshot_id().await? + 1;
+                let report = process_add_files(&mut tx_helper, self, paths, options).await?;
+                if !report.added_files.is_empty() {
+                    commit_snapshot(
+                        &mut tx_helper,
+                        self,
+                        snapshot_id,
+                        SnapshotOperation::AddFiles,
+                    )
+                    .await?;
+                }
+                tx_helper.commit().await?;
+                Ok(report)
+            })
+        })
+        .await
+    }
+
+    /// Writes the rows kept inline in the catalog into a data file right away, instead of waiting
+    /// for [`TableConfig::inline_row_count_limit`] or [`TableConfig::inline_byte_limit`]. Only
+    /// rows committed before the call are flushed, rows inserted meanwhile stay inline.
+    pub async fn flush(&self) -> ILResult<FlushReport> {
+        check_writable(&self.catalog)?;
+        let mut tx_helper = TransactionHelper::new(&self.catalog).await?;
+        let row_ids = tx_helper.scan_inline_row_ids(self.table_id).await?;
+        tx_helper.commit().await?;
+        if row_ids.is_empty() {
+            return Ok(FlushReport::default());
+        }
+
+        let row_ids = &row_ids;
+        TransactionHelper::run(&self.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                let report = process_flush(&mut tx_helper, self, row_ids).await?;
+                tx_helper.commit().await?;
+                Ok(report)
+            })
+        })
+        .await
+    }
+
+    /// Merges data files smaller than [`CompactOptions::target_file_size`] into larger ones,
+    /// leaving out deleted rows. The catalog switches to the merged files in one transaction,
+    /// so scans see either the replaced files or the merged ones, and the replaced files are
+    /// kept in storage for scans still reading them.
+    pub async fn compact(&self, options: CompactOptions) -> ILResult<CompactReport> {
+        if let Some(predicate) = &options.predicate {
+            check_condition_data_type(predicate, &self.schema)?;
+        }
+        let options = &options;
+        TransactionHelper::run(&self.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                let report = process_compact(&mut tx_helper, self, options).await?;
+                tx_helper.commit().await?;
+                Ok(report)
+            })
+        })
+        .await
+    }
+
+    /// Deletes files in the table directory of the storage that the catalog no longer refers
+    /// to, such as data files replaced by [`Table::compact`]. Only files last modified more than
+    /// `retention` ago are deleted, so scans still reading replaced files and inserts that have
+    /// not committed yet are not affected.
+    pub async fn vacuum(&self, retention: Duration) -> ILResult<VacuumReport> {
+        check_writable(&self.catalog)?;
+        process_vacuum(self, retention, false).await
+    }
+
+    /// Reports what [`Table::vacuum`] would delete and which snapshots it would expire, without
+    /// changing the catalog or the storage. Works on read-only catalogs.
+    pub async fn vacuum_dry_run(&self, retention: Duration) -> ILResult<VacuumReport> {
+        process_vacuum(self, retention, true).await
+    }
+
+    /// Reports the bytes the table occupies in the storage, including files waiting for
+    /// [`Table::vacuum`].
+    pub async fn storage_stats(&self) -> ILResult<StorageStats> {
+        let catalog_helper = CatalogHelper::new(self.catalog.clone());
+        process_storage_stats(
+            &catalog_helper,
+            &self.storage,
+            &self.table_dir(),
             self.table_id,
-            &self.schema,
-            condition,
         )
-        .await?;
-        tx_helper.commit().await?;
-        Ok(())
+        .await
     }
 
-    // Delete all rows in the table
-    pub async fn truncate(&self) -> ILResult<()> {
-        let mut tx_helper = self.transaction_helper().await?;
-        process_truncate(&mut tx_helper, self.table_id).await?;
-        tx_helper.commit().await?;
+    /// Counts the rows matching `predicate`, all rows without one. Data files are answered from
+    /// their row counts, partitions and column statistics in the catalog where those decide
+    /// the predicate, only the remaining ones are read with just the columns it refers to.
+    pub async fn count(&self, predicate: Option<Expr>) -> ILResult<u64> {
+        if let Some(predicate) = &predicate {
+            check_condition_data_type(predicate, &self.schema)?;
+        }
+        process_count(self, predicate.as_ref()).await
+    }
+
+    /// Reports row counts, sizes and per-column statistics of the table from the catalog,
+    /// without reading the data files.
+    pub async fn statistics(&self) -> ILResult<TableStatistics> {
+        process_table_statistics(self).await
+    }
+
+    /// Scans the table like `ANALYZE` for exact column bounds and null counts of the rows it
+    /// holds, and for estimated distinct counts of the columns. Nothing is kept in the catalog,
+    /// [`Table::statistics`] keeps reporting from the data file statistics.
+    pub async fn compute_statistics(&self) -> ILResult<TableStatistics> {
+        process_compute_statistics(self).await
+    }
+
+    /// Lists the partitions of a table partitioned by [`TableConfig::partition_by`] with their
+    /// data file and row counts, none for unpartitioned tables.
+    pub async fn partitions(&self) -> ILResult<Vec<PartitionInfo>> {
+        process_partitions(self).await
+    }
+
+    /// Reads every data file of the table and compares its content with the checksum recorded
+    /// when it was written. Mismatching files are reported rather than failing the check, so
+    /// one pass finds all of them.
+    pub async fn verify(&self) -> ILResult<VerifyReport> {
+        process_verify(self).await
+    }
+
+    /// Lists the data files of the table with URLs that clients can read them with directly
+    /// for `ttl`, see [`Storage::presign_read`]. Data files replaced by [`Table::compact`] but
+    /// not yet vacuumed are left out, as are inline rows that are not in any data file yet.
+    pub async fn data_file_urls(&self, ttl: Duration) -> ILResult<Vec<PresignedFile>> {
+        process_data_file_urls(self, ttl).await
+    }
+
+    /// Moves the data files and index files of the table under `new_prefix` in the storage.
+    /// Files are copied first, the catalog switches to the copies in one transaction and the
+    /// old files are deleted after it committed. Scans running meanwhile read the old files.
+    /// Files the catalog no longer refers to stay under the old prefix, vacuum the table
+    /// before relocating it to have them deleted.
+    ///
+    /// Other [`Table`] instances loaded before keep writing new files under the old prefix
+    /// until reloaded, the catalog refers to those files wherever they are.
+    pub async fn relocate(&mut self, new_prefix: &str) -> ILResult<()> {
+        check_writable(&self.catalog)?;
+        let config = process_relocate(self, new_prefix).await?;
+        self.config = Arc::new(config);
         Ok(())
     }
 
+    /// Expires the rows older than [`TableConfig::ttl`] in one snapshot. Data files whose
+    /// partition or column statistics show every row expired are dropped without being read, the
+    /// other expired rows are deleted like [`Table::delete`]. Earlier snapshots still read the
+    /// expired rows. No snapshot is committed when no row expired.
+    pub async fn expire_rows(&self) -> ILResult<ExpireReport> {
+        TransactionHelper::run(&self.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                let snapshot_id = tx_helper.get_max_snapshot_id().await? + 1;
+                let report = process_expire_rows(&mut tx_helper, self, snapshot_id).await?;
+                if report.removed_rows > 0 || report.removed_files > 0 {
+                    commit_snapshot(&mut tx_helper, self, snapshot_id, SnapshotOperation::Expire)
+                        .await?;
+                }
+                tx_helper.commit().await?;
+                Ok(report)
+            })
+        })
+        .await
+    }
+
+    /// Deletes all rows in the table in one transaction, keeping the table id, schema and index
+    /// definitions. No data file is read, the data files and index files are dropped from the
+    /// catalog and left in storage for earlier snapshots until vacuumed. A concurrent insert
+    /// either lands before the truncate and is removed, or after it and is kept.
+    pub async fn truncate(&self) -> ILResult<TruncateReport> {
+        TransactionHelper::run(&self.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                let snapshot_id = tx_helper.get_max_snapshot_id().await? + 1;
+                let report = process_truncate(&mut tx_helper, self, snapshot_id).await?;
+                commit_snapshot(
+                    &mut tx_helper,
+                    self,
+                    snapshot_id,
+                    SnapshotOperation::Truncate,
+                )
+                .await?;
+                tx_helper.commit().await?;
+                Ok(report)
+            })
+        })
+        .await
+    }
+
     // Drop the table
     pub async fn drop(self) -> ILResult<()> {
-        let mut tx_helper = self.transaction_helper().await?;
-        process_table_drop(&mut tx_helper, self.table_id).await?;
-        tx_helper.commit().await?;
-        Ok(())
+        let (table_id, config) = (self.table_id, self.config.as_ref());
+        TransactionHelper::run(&self.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                process_table_drop(&mut tx_helper, table_id, config).await?;
+                tx_helper.commit().await
+            })
+        })
+        .await
     }
 }
 
@@ -156,3 +1049,15 @@ fn check_condition_data_type(condition: &Expr, schema: &SchemaRef) -> ILResult<(
     }
     Ok(())
 }
+
+/// Whether timestamps of type `from` only differ from `to` in their time zone, so that their
+/// UTC values hold the same instants in `to`. Timestamps without a time zone are local times
+/// and do not qualify.
+fn is_zone_change(from: &DataType, to: &DataType) -> bool {
+    match (from, to) {
+        (DataType::Timestamp(u1, Some(tz1)), DataType::Timestamp(u2, Some(tz2))) => {
+            u1 == u2 && tz1 != tz2
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,571 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use arrow::array::{Array, AsArray, RecordBatch, UInt32Array};
+use arrow::compute::take_record_batch;
+use arrow::datatypes::{DataType, Schema, SchemaRef};
+
+use crate::catalog::{
+    CatalogHelper, CatalogSchema, ColumnStats, DataFileRecord, Row, RowLocation, Scalar,
+};
+use crate::expr::{BinaryOp, Expr, col, lit, visited_columns};
+use crate::table::{
+    DateUnit, PartitionTransform, Table, TableConfig, may_match_stats, must_match_stats,
+    prune_data_files_by_stats,
+};
+use crate::utils::has_duplicated_items;
+use crate::{ILError, ILResult};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartitionInfo {
+    /// Values identifying the partition: the partition column values for identity partitions,
+    /// the bucket number for hash partitions and the start timestamps for date partitions.
+    /// Rows with null partition column values have partitions of their own.
+    pub values: Vec<Scalar>,
+    /// Number of data files holding rows of the partition.
+    pub data_file_count: usize,
+    /// Number of rows of the partition not deleted, inline rows included.
+    pub row_count: u64,
+}
+
+pub(crate) fn check_partition_columns(schema: &SchemaRef, config: &TableConfig) -> ILResult<()> {
+    if has_duplicated_items(config.partition_by.iter()) {
+        return Err(ILError::InvalidInput(format!(
+            "Duplicated columns in partition columns {:?}",
+            config.partition_by
+        )));
+    }
+    for name in &config.partition_by {
+        schema.field_with_name(name).map_err(|_| {
+            ILError::InvalidInput(format!("Partition column {name} not found in table schema"))
+        })?;
+    }
+    if let PartitionTransform::Hash { buckets } = config.partition_transform
+        && buckets == 0
+    {
+        return Err(ILError::InvalidInput(
+            "Partition hash buckets must be greater than 0".to_string(),
+        ));
+    }
+    if let PartitionTransform::DateTrunc { .. } = config.partition_transform {
+        for name in &config.partition_by {
+            let data_type = schema.field_with_name(name)?.data_type();
+            if data_type != &DataType::Int64 {
+                return Err(ILError::InvalidInput(format!(
+                    "Date partition column {name} must be Int64 timestamps in milliseconds, got {data_type}"
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the partition of `row`, `None` for unpartitioned tables. Identity partitions are
+/// identified by the values of the partition columns, hash partitions by their bucket number.
+pub(crate) fn row_partition_values(
+    table_schema: &SchemaRef,
+    config: &TableConfig,
+    row: &Row,
+) -> ILResult<Option<Vec<Scalar>>> {
+    if config.partition_by.is_empty() {
+        return Ok(None);
+    }
+    let mut values = Vec::with_capacity(config.partition_by.len());
+    for name in &config.partition_by {
+        let idx = row.schema.index_of(name).ok_or_else(|| {
+            ILError::InternalError(format!("Partition column {name} not found in row"))
+        })?;
+        let field = table_schema.field_with_name(name)?;
+        values.push(cast_scalar(&row.values[idx], field.data_type())?);
+    }
+    Ok(Some(match config.partition_transform {
+        PartitionTransform::Identity => values,
+        PartitionTransform::Hash { buckets } => {
+            vec![Scalar::Int64(Some(hash_bucket(&values, buckets)?))]
+        }
+        PartitionTransform::DateTrunc { unit } => values
+            .into_iter()
+            .map(|value| match value {
+                Scalar::Int64(timestamp) => Ok(Scalar::Int64(
+                    timestamp.map(|timestamp| truncate_timestamp(timestamp, unit)),
+                )),
+                _ => Err(ILError::InternalError(format!(
+                    "Date partition value must be Int64, got {value:?}"
+                ))),
+            })
+            .collect::<ILResult<Vec<_>>>()?,
+    }))
+}
+
+const MILLIS_PER_HOUR: i64 = 3_600_000;
+const MILLIS_PER_DAY: i64 = 24 * MILLIS_PER_HOUR;
+
+/// Start of the UTC hour, day, month or year holding the timestamp in milliseconds.
+fn truncate_timestamp(timestamp: i64, unit: DateUnit) -> i64 {
+    let days = timestamp.div_euclid(MILLIS_PER_DAY);
+    match unit {
+        DateUnit::Hour => timestamp - timestamp.rem_euclid(MILLIS_PER_HOUR),
+        DateUnit::Day => days * MILLIS_PER_DAY,
+        DateUnit::Month => {
+            let (year, month, _) = civil_from_days(days);
+            days_from_civil(year, month, 1) * MILLIS_PER_DAY
+        }
+        DateUnit::Year => {
+            let (year, _, _) = civil_from_days(days);
+            days_from_civil(year, 1, 1) * MILLIS_PER_DAY
+        }
+    }
+}
+
+/// Start of the date partition following the one starting at `start`.
+fn next_partition_start(start: i64, unit: DateUnit) -> i64 {
+    let (year, month, _) = civil_from_days(start.div_euclid(MILLIS_PER_DAY));
+    match unit {
+        DateUnit::Hour => start + MILLIS_PER_HOUR,
+        DateUnit::Day => start + MILLIS_PER_DAY,
+        DateUnit::Month if month == 12 => days_from_civil(year + 1, 1, 1) * MILLIS_PER_DAY,
+        DateUnit::Month => days_from_civil(year, month + 1, 1) * MILLIS_PER_DAY,
+        DateUnit::Year => days_from_civil(year + 1, 1, 1) * MILLIS_PER_DAY,
+    }
+}
+
+// Days since 1970-01-01 of a proleptic Gregorian date, from Howard Hinnant's date algorithms
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+// Catalog rows may come back with a wider type than the column, partition values are kept in
+// the column type so they hash and compare the same whatever produced them
+fn cast_scalar(scalar: &Scalar, data_type: &DataType) -> ILResult<Scalar> {
+    let array = arrow::compute::cast(&scalar.to_array_of_size(1)?, data_type)?;
+    Scalar::try_from_array(array.as_ref(), 0)
+}
+
+// FNV-1a over the serialized values, stable across processes and releases
+fn hash_bucket(values: &[Scalar], buckets: u32) -> ILResult<i64> {
+    let bytes = serde_json::to_vec(values).map_err(|e| {
+        ILError::InternalError(format!("Failed to serialize partition values: {e:?}"))
+    })?;
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    Ok((hash % buckets as u64) as i64)
+}
+
+pub(crate) type PartitionRows = (Option<Vec<Scalar>>, Vec<Row>);
+
+/// Groups rows by partition, keeping the order of rows within each partition and the order of
+/// first appearance of the partitions.
+pub(crate) fn group_rows_by_partition(
+    table_schema: &SchemaRef,
+    config: &TableConfig,
+    rows: Vec<Row>,
+) -> ILResult<Vec<PartitionRows>> {
+    let mut groups: Vec<PartitionRows> = Vec::new();
+    let mut group_indexes = HashMap::new();
+    for row in rows {
+        let partition_values = row_partition_values(table_schema, config, &row)?;
+        let key = serde_json::to_string(&partition_values).map_err(|e| {
+            ILError::InternalError(format!("Failed to serialize partition values: {e:?}"))
+        })?;
+        let idx = *group_indexes.entry(key).or_insert_with(|| {
+            groups.push((partition_values, Vec::new()));
+            groups.len() - 1
+        });
+        groups[idx].1.push(row);
+    }
+    Ok(groups)
+}
+
+/// Splits `record` of the table schema into a batch per partition like
+/// [`group_rows_by_partition`] groups rows, the whole batch for unpartitioned tables.
+pub(crate) fn group_batch_by_partition(
+    table: &Table,
+    record: &RecordBatch,
+) -> ILResult<Vec<(Option<Vec<Scalar>>, RecordBatch)>> {
+    if table.config.partition_by.is_empty() {
+        return Ok(vec![(None, record.clone())]);
+    }
+    let projection = table
+        .config
+        .partition_by
+        .iter()
+        .map(|name| record.schema().index_of(name))
+        .collect::<Result<Vec<_>, _>>()?;
+    let partition_record = record.project(&projection)?;
+    let partition_schema = Arc::new(CatalogSchema::from_arrow(&partition_record.schema())?);
+
+    let mut groups: Vec<(Option<Vec<Scalar>>, Vec<u32>)> = Vec::new();
+    let mut group_indexes = HashMap::new();
+    for row_idx in 0..record.num_rows() {
+        let values = partition_record
+            .columns()
+            .iter()
+            .map(|column| Scalar::try_from_array(column.as_ref(), row_idx))
+            .collect::<ILResult<Vec<_>>>()?;
+        let row = Row::new(partition_schema.clone(), values);
+        let partition_values = row_partition_values(&table.schema, &table.config, &row)?;
+        let key = serde_json::to_string(&partition_values).map_err(|e| {
+            ILError::InternalError(format!("Failed to serialize partition values: {e:?}"))
+        })?;
+        let idx = *group_indexes.entry(key).or_insert_with(|| {
+            groups.push((partition_values, Vec::new()));
+            groups.len() - 1
+        });
+        groups[idx].1.push(row_idx as u32);
+    }
+    groups
+        .into_iter()
+        .map(|(partition_values, indices)| {
+            let batch = take_record_batch(record, &UInt32Array::from(indices))?;
+            Ok((partition_values, batch))
+        })
+        .collect()
+}
+
+/// Returns the relative paths of the data files whose partition or column statistics show they
+/// can not hold rows matching all `filters`. Filters that can not be evaluated on the partition
+/// values or statistics prune nothing.
+pub(crate) fn prune_data_files(
+    table_schema: &SchemaRef,
+    config: &TableConfig,
+    filters: &[Expr],
+    data_files: &[DataFileRecord],
+) -> ILResult<HashSet<String>> {
+    let mut pruned = prune_data_files_by_stats(filters, data_files);
+    if config.partition_by.is_empty() {
+        return Ok(pruned);
+    }
+    match config.partition_transform {
+        PartitionTransform::Identity => {
+            let partition_filters = filters
+                .iter()
+                .filter(|filter| on_partition_columns(config, filter))
+                .collect::<Vec<_>>();
+            if partition_filters.is_empty() {
+                return Ok(pruned);
+            }
+            let partition_schema = partition_schema(table_schema, config)?;
+            for data_file in data_files {
+                let Some(values) = &data_file.partition_values else {
+                    continue;
+                };
+                let batch = partition_batch(&partition_schema, values)?;
+                if partition_filters
+                    .iter()
+                    .any(|filter| !may_match(filter, &batch))
+                {
+                    pruned.insert(data_file.relative_path.clone());
+                }
+            }
+        }
+        PartitionTransform::Hash { buckets } => {
+            let Some(values) = equality_values(table_schema, &config.partition_by, filters) else {
+                return Ok(pruned);
+            };
+            let bucket = hash_bucket(&values, buckets)?;
+            for data_file in data_files {
+                if let Some(partition_values) = &data_file.partition_values
+                    && !matches!(partition_values.as_slice(), [Scalar::Int64(Some(b))] if *b == bucket)
+                {
+                    pruned.insert(data_file.relative_path.clone());
+                }
+            }
+        }
+        PartitionTransform::DateTrunc { unit } => {
+            // A date partition holds the timestamps from its start to the start of the next
+            // one, filters are checked against that range like against column statistics
+            for data_file in data_files {
+                let Some(values) = &data_file.partition_values else {
+                    continue;
+                };
+                let columns = date_partition_stats(config, values, unit);
+                if filters
+                    .iter()
+                    .any(|filter| !may_match_stats(&columns, 1, filter))
+                {
+                    pruned.insert(data_file.relative_path.clone());
+                }
+            }
+        }
+    }
+    Ok(pruned)
+}
+
+/// Returns the relative paths of the data files whose partition or column statistics show
+/// every row of them matches all `filters`, so their undeleted rows match without reading them.
+/// Hash partitions prove nothing as a bucket holds many values.
+pub(crate) fn fully_matching_data_files(
+    table_schema: &SchemaRef,
+    config: &TableConfig,
+    filters: &[Expr],
+    data_files: &[DataFileRecord],
+) -> ILResult<HashSet<String>> {
+    let partition_schema = match config.partition_transform {
+        PartitionTransform::Identity if !config.partition_by.is_empty() => {
+            Some(partition_schema(table_schema, config)?)
+        }
+        _ => None,
+    };
+    let mut matching = HashSet::new();
+    for data_file in data_files {
+        let record_count = data_file.record_count as u64;
+        let partition = match (&data_file.partition_values, config.partition_transform) {
+            (Some(values), PartitionTransform::Identity) => match &partition_schema {
+                Some(schema) => Some(partition_batch(schema, values)?),
+                None => None,
+            },
+            _ => None,
+        };
+        let date_stats = match (&data_file.partition_values, config.partition_transform) {
+            (Some(values), PartitionTransform::DateTrunc { unit }) => {
+                Some(date_partition_stats(config, values, unit))
+            }
+            _ => None,
+        };
+        let all_match = filters.iter().all(|filter| {
+            let by_stats = data_file
+                .column_stats
+                .as_ref()
+                .is_some_and(|columns| must_match_stats(columns, record_count, filter));
+            let by_partition = partition.as_ref().is_some_and(|batch| {
+                on_partition_columns(config, filter) && may_match(filter, batch)
+            });
+            let by_date = date_stats
+                .as_ref()
+                .is_some_and(|columns| must_match_stats(columns, 1, filter));
+            by_stats || by_partition || by_date
+        });
+        if all_match {
+            matching.insert(data_file.relative_path.clone());
+        }
+    }
+    Ok(matching)
+}
+
+fn on_partition_columns(config: &TableConfig, filter: &Expr) -> bool {
+    let columns = visited_columns(filter);
+    !columns.is_empty() && columns.iter().all(|c| config.partition_by.contains(c))
+}
+
+fn partition_schema(table_schema: &SchemaRef, config: &TableConfig) -> ILResult<SchemaRef> {
+    let fields = config
+        .partition_by
+        .iter()
+        .map(|name| Ok(table_schema.field_with_name(name)?.clone()))
+        .collect::<ILResult<Vec<_>>>()?;
+    Ok(Arc::new(Schema::new(fields)))
+}
+
+// Single row batch of the values of an identity partition
+fn partition_batch(partition_schema: &SchemaRef, values: &[Scalar]) -> ILResult<RecordBatch> {
+    let columns = values
+        .iter()
+        .map(|value| value.to_array_of_size(1))
+        .collect::<ILResult<Vec<_>>>()?;
+    Ok(RecordBatch::try_new(partition_schema.clone(), columns)?)
+}
+
+// A date partition holds the timestamps from its start to the start of the next one
+fn date_partition_stats(
+    config: &TableConfig,
+    values: &[Scalar],
+    unit: DateUnit,
+) -> BTreeMap<String, ColumnStats> {
+    let mut columns = BTreeMap::new();
+    for (name, value) in config.partition_by.iter().zip(values) {
+        let stats = match value {
+            Scalar::Int64(Some(start)) => ColumnStats {
+                min: Some(Scalar::Int64(Some(*start))),
+                max: Some(Scalar::Int64(Some(next_partition_start(*start, unit) - 1))),
+                null_count: 0,
+            },
+            _ => ColumnStats {
+                min: None,
+                max: None,
+                null_count: 1,
+            },
+        };
+        columns.insert(name.clone(), stats);
+    }
+    columns
+}
+
+/// Lists the partitions of the table with their data files and rows, ordered by their values.
+/// Inline rows count towards the partitions they will be dumped into.
+pub(crate) async fn process_partitions(table: &Table) -> ILResult<Vec<PartitionInfo>> {
+    if table.config.partition_by.is_empty() {
+        return Ok(Vec::new());
+    }
+    let catalog_helper = CatalogHelper::new(table.catalog.clone());
+    let data_files = catalog_helper.get_data_files(table.table_id).await?;
+    let non_inline = col("location").neq(lit(RowLocation::Inline.to_string()));
+    let deleted = col("deleted").eq(lit(true));
+    let deleted_rows = catalog_helper
+        .scan_row_metadata(table.table_id, &non_inline.and(deleted), None)
+        .await?;
+    let mut deleted_row_counts = HashMap::new();
+    for row in deleted_rows {
+        if let RowLocation::Parquet { relative_path, .. } = row.location {
+            *deleted_row_counts.entry(relative_path).or_insert(0u64) += 1;
+        }
+    }
+
+    let mut partitions: BTreeMap<String, PartitionInfo> = BTreeMap::new();
+    for data_file in data_files {
+        let Some(values) = data_file.partition_values else {
+            continue;
+        };
+        let deleted_count = deleted_row_counts
+            .get(&data_file.relative_path)
+            .copied()
+            .unwrap_or_default();
+        let partition = partition_entry(&mut partitions, values)?;
+        partition.data_file_count += 1;
+        partition.row_count += (data_file.record_count as u64).saturating_sub(deleted_count);
+    }
+
+    let projection = table
+        .config
+        .partition_by
+        .iter()
+        .map(|name| table.schema.index_of(name))
+        .collect::<Result<Vec<_>, _>>()?;
+    let partition_schema = table.schema.project(&projection)?;
+    let catalog_schema = Arc::new(CatalogSchema::from_arrow(&partition_schema)?);
+    let inline_rows = catalog_helper
+        .scan_inline_rows(table.table_id, &catalog_schema, &[], None)
+        .await?;
+    for row in inline_rows {
+        if let Some(values) = row_partition_values(&table.schema, &table.config, &row)? {
+            partition_entry(&mut partitions, values)?.row_count += 1;
+        }
+    }
+
+    let mut partitions = partitions.into_values().collect::<Vec<_>>();
+    partitions.sort_by(|a, b| {
+        a.values
+            .partial_cmp(&b.values)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    Ok(partitions)
+}
+
+fn partition_entry(
+    partitions: &mut BTreeMap<String, PartitionInfo>,
+    values: Vec<Scalar>,
+) -> ILResult<&mut PartitionInfo> {
+    let key = serde_json::to_string(&values).map_err(|e| {
+        ILError::InternalError(format!("Failed to serialize partition values: {e:?}"))
+    })?;
+    Ok(partitions.entry(key).or_insert_with(|| PartitionInfo {
+        values,
+        data_file_count: 0,
+        row_count: 0,
+    }))
+}
+
+fn may_match(filter: &Expr, batch: &RecordBatch) -> bool {
+    let Ok(array) = filter
+        .eval(batch)
+        .and_then(|value| value.into_array(batch.num_rows()))
+    else {
+        return true;
+    };
+    match array.as_boolean_opt() {
+        Some(array) => array.is_valid(0) && array.value(0),
+        None => true,
+    }
+}
+
+// Values of `column = literal` filters covering every partition column
+fn equality_values(
+    table_schema: &SchemaRef,
+    partition_by: &[String],
+    filters: &[Expr],
+) -> Option<Vec<Scalar>> {
+    let mut equalities = HashMap::new();
+    for filter in filters {
+        let Expr::BinaryExpr(binary) = filter else {
+            continue;
+        };
+        if binary.op != BinaryOp::Eq {
+            continue;
+        }
+        match (binary.left.as_ref(), binary.right.as_ref()) {
+            (Expr::Column(name), Expr::Literal(value))
+            | (Expr::Literal(value), Expr::Column(name)) => {
+                equalities.insert(name.as_str(), value);
+            }
+            _ => {}
+        }
+    }
+    partition_by
+        .iter()
+        .map(|name| {
+            let value = equalities.get(name.as_str())?;
+            let field = table_schema.field_with_name(name).ok()?;
+            cast_scalar(value, field.data_type()).ok()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_timestamp() {
+        // 2024-02-29T12:30:00Z
+        let timestamp = 1709209800000;
+        assert_eq!(truncate_timestamp(timestamp, DateUnit::Hour), 1709208000000);
+        assert_eq!(truncate_timestamp(timestamp, DateUnit::Day), 1709164800000);
+        assert_eq!(
+            truncate_timestamp(timestamp, DateUnit::Month),
+            1706745600000
+        );
+        assert_eq!(truncate_timestamp(timestamp, DateUnit::Year), 1704067200000);
+        // 1969-12-31T23:59:59.999Z
+        assert_eq!(truncate_timestamp(-1, DateUnit::Day), -86400000);
+        assert_eq!(truncate_timestamp(-1, DateUnit::Month), -2678400000);
+        assert_eq!(truncate_timestamp(-1, DateUnit::Year), -31536000000);
+    }
+
+    #[test]
+    fn test_next_partition_start() {
+        assert_eq!(
+            next_partition_start(1706745600000, DateUnit::Month),
+            1709251200000
+        );
+        assert_eq!(next_partition_start(-2678400000, DateUnit::Month), 0);
+        assert_eq!(
+            next_partition_start(1704067200000, DateUnit::Year),
+            1735689600000
+        );
+        assert_eq!(
+            next_partition_start(1709164800000, DateUnit::Day),
+            1709251200000
+        );
+    }
+}
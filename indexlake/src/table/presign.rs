@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+use arrow::datatypes::SchemaRef;
+
+use crate::ILResult;
+use crate::catalog::{RowLocation, TransactionHelper};
+use crate::expr::{col, lit};
+use crate::table::Table;
+
+/// A data file of the table with a URL to read it directly from the storage.
+#[derive(Debug, Clone)]
+pub struct PresignedFile {
+    pub relative_path: String,
+    /// URL to read the file with, see [`Storage::presign_read`](crate::storage::Storage::presign_read).
+    pub url: String,
+    pub file_size_bytes: u64,
+    /// Schema of the table, including the row id column. Files written before columns were
+    /// added or dropped have other columns.
+    pub schema: SchemaRef,
+    /// Sorted ranges of the ids of the live rows of the file. Other rows of the file were
+    /// deleted or updated since, readers skip them by their row id column.
+    pub row_id_ranges: Vec<RangeInclusive<i64>>,
+}
+
+pub(crate) async fn process_data_file_urls(
+    table: &Table,
+    ttl: Duration,
+) -> ILResult<Vec<PresignedFile>> {
+    // Data files and row metadata are read in one transaction, so a concurrent compaction is
+    // seen either before or after
+    let mut tx_helper = TransactionHelper::new(&table.catalog).await?;
+    let data_files = tx_helper.get_data_files(table.table_id).await?;
+    let non_inline = col("location").neq(lit(RowLocation::Inline.to_string()));
+    let undeleted = col("deleted").eq(lit(false));
+    let row_metadatas = tx_helper
+        .scan_row_metadata(table.table_id, &non_inline.and(undeleted))
+        .await?;
+    tx_helper.commit().await?;
+
+    let mut live_row_ids: HashMap<String, Vec<i64>> = HashMap::new();
+    for row_metadata in row_metadatas {
+        if let RowLocation::Parquet { relative_path, .. } = row_metadata.location {
+            live_row_ids
+                .entry(relative_path)
+                .or_default()
+                .push(row_metadata.row_id);
+        }
+    }
+
+    let mut files = Vec::new();
+    for data_file in data_files {
+        // Files whose rows are all deleted are left out
+        let Some(mut row_ids) = live_row_ids.remove(&data_file.relative_path) else {
+            continue;
+        };
+        row_ids.sort_unstable();
+        let url = table
+            .storage
+            .presign_read(&data_file.relative_path, ttl)
+            .await?;
+        files.push(PresignedFile {
+            relative_path: data_file.relative_path,
+            url,
+            file_size_bytes: data_file.file_size_bytes as u64,
+            schema: table.schema.clone(),
+            row_id_ranges: row_id_ranges(&row_ids),
+        });
+    }
+    Ok(files)
+}
+
+/// Merges sorted row ids into ranges of consecutive ids.
+fn row_id_ranges(row_ids: &[i64]) -> Vec<RangeInclusive<i64>> {
+    let mut ranges: Vec<RangeInclusive<i64>> = Vec::new();
+    for &row_id in row_ids {
+        match ranges.last_mut() {
+            Some(range) if *range.end() + 1 == row_id => *range = *range.start()..=row_id,
+            _ => ranges.push(row_id..=row_id),
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_row_id_ranges() {
+        assert!(row_id_ranges(&[]).is_empty());
+        assert_eq!(
+            row_id_ranges(&[1, 2, 3, 5, 7, 8]),
+            vec![1..=3, 5..=5, 7..=8]
+        );
+    }
+}
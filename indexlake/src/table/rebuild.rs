@@ -0,0 +1,33 @@
+use crate::catalog::TransactionHelper;
+use crate::table::{Table, build_index_files};
+use crate::{ILError, ILResult};
+
+/// Replaces the index file records of the index with ones of index files built anew. The new
+/// files get new paths, the replaced files are not overwritten. Inline rows are not indexed,
+/// scans evaluate filters on them directly.
+pub(crate) async fn process_rebuild_index(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    index_name: &str,
+) -> ILResult<()> {
+    let index_def = table
+        .indexes
+        .get(index_name)
+        .ok_or_else(|| ILError::InvalidInput(format!("Index {index_name} not found")))?;
+    let index = table
+        .index_kinds
+        .get(&index_def.kind)
+        .ok_or_else(|| ILError::InvalidInput(format!("Index kind {} not found", index_def.kind)))?;
+
+    let replaced_ids = tx_helper
+        .get_index_files(table.table_id)
+        .await?
+        .into_iter()
+        .filter(|index_file| index_file.index_id == index_def.index_id)
+        .map(|index_file| index_file.index_file_id)
+        .collect::<Vec<_>>();
+    let data_files = tx_helper.get_data_files(table.table_id).await?;
+    build_index_files(tx_helper, table, index_def, index.as_ref(), data_files).await?;
+    tx_helper.delete_index_files_by_ids(&replaced_ids).await?;
+    Ok(())
+}
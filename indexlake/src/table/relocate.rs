@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use log::{debug, warn};
+
+use crate::catalog::TransactionHelper;
+use crate::table::{Table, TableConfig, check_storage_prefix, referenced_files};
+use crate::{ILError, ILResult};
+
+/// Rounds of copying files written by concurrent commits before giving up.
+const MAX_RELOCATE_ROUNDS: usize = 3;
+
+/// Copies the files of the table under `new_prefix`, then points the catalog to the copies and
+/// sets the storage prefix of the table config in one transaction. The old files are deleted
+/// once the transaction committed, scans that started before keep reading them until then.
+/// Old files clones of the table refer to are kept.
+///
+/// Files committed while copying are copied in a further round. Files outside the table
+/// directory, as ingested in place, stay where they are. Returns the new table config.
+pub(crate) async fn process_relocate(table: &Table, new_prefix: &str) -> ILResult<TableConfig> {
+    check_storage_prefix(new_prefix)?;
+    let mut config = table.config.as_ref().clone();
+    config.storage_prefix = Some(new_prefix.to_string());
+
+    // Old paths to the paths of their copies
+    let mut copies = HashMap::new();
+    if let Err(e) = relocate_files(table, new_prefix, &config, &mut copies).await {
+        warn!(
+            "Failed to relocate table {} to {new_prefix}, deleting copied files: {e}",
+            table.table_id
+        );
+        for new_path in copies.values() {
+            table.storage.delete(new_path).await?;
+        }
+        return Err(e);
+    }
+
+    // Clones keep referring to the old files of the table
+    let mut tx_helper = TransactionHelper::new(&table.catalog).await?;
+    let shared = tx_helper.get_referenced_paths(&table.table_dir()).await?;
+    tx_helper.commit().await?;
+    for (old_path, new_path) in copies {
+        if shared.contains(&old_path) {
+            continue;
+        }
+        debug!(
+            "Relocate table {} deletes {old_path} moved to {new_path}",
+            table.table_id
+        );
+        table.storage.delete(&old_path).await?;
+    }
+    Ok(config)
+}
+
+async fn relocate_files(
+    table: &Table,
+    new_prefix: &str,
+    config: &TableConfig,
+    copies: &mut HashMap<String, String>,
+) -> ILResult<()> {
+    let table_dir = &table.table_dir();
+    for _ in 0..MAX_RELOCATE_ROUNDS {
+        let mut tx_helper = TransactionHelper::new(&table.catalog).await?;
+        let referenced = referenced_files(&mut tx_helper, table.table_id).await?;
+        tx_helper.commit().await?;
+        for old_path in referenced {
+            let new_path = relocated_path(&old_path, new_prefix);
+            if copies.contains_key(&old_path)
+                || new_path == old_path
+                || !in_table_dir(&old_path, table_dir)
+            {
+                continue;
+            }
+            table.storage.copy_file(&old_path, &new_path).await?;
+            copies.insert(old_path, new_path);
+        }
+
+        let copies = &*copies;
+        let relocated = TransactionHelper::run(&table.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                let referenced = referenced_files(&mut tx_helper, table.table_id).await?;
+                if referenced.iter().any(|old_path| {
+                    !copies.contains_key(old_path)
+                        && relocated_path(old_path, new_prefix) != *old_path
+                        && in_table_dir(old_path, table_dir)
+                }) {
+                    tx_helper.rollback().await?;
+                    return Ok(false);
+                }
+                for (old_path, new_path) in copies {
+                    tx_helper
+                        .update_file_path(table.table_id, old_path, new_path)
+                        .await?;
+                }
+                tx_helper
+                    .update_table_config(table.table_id, config)
+                    .await?;
+                tx_helper.commit().await?;
+                Ok(true)
+            })
+        })
+        .await?;
+        if relocated {
+            return Ok(());
+        }
+    }
+    Err(ILError::CatalogConflict(format!(
+        "Files of table {} kept changing while relocating it to {new_prefix}",
+        table.table_id
+    )))
+}
+
+fn in_table_dir(path: &str, table_dir: &str) -> bool {
+    path.strip_prefix(table_dir)
+        .is_some_and(|rest| rest.starts_with('/'))
+}
+
+fn relocated_path(path: &str, new_prefix: &str) -> String {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    format!("{new_prefix}/{file_name}")
+}
@@ -1,41 +1,133 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::SystemTime,
+};
 
-use arrow::datatypes::{Schema, SchemaRef};
+use arrow::{
+    array::RecordBatch,
+    datatypes::{Schema, SchemaRef},
+};
+use futures::StreamExt;
 
 use crate::{
     ILError, ILResult, RecordBatchStream,
-    catalog::{CatalogHelper, CatalogSchema, RowLocation, rows_to_record_batch},
-    expr::{Expr, merge_filters, split_conjunction_filters},
+    catalog::{
+        CatalogHelper, CatalogSchema, DataFileRecord, IndexFileRecord, RowLocation,
+        RowMetadataRecord, Scalar, rows_to_record_batch,
+    },
+    expr::{Expr, filters_imply, merge_filters, split_conjunction_filters, visited_columns},
     index::{Index, IndexDefinationRef},
-    storage::{Storage, read_parquet_files_by_locations},
-    table::Table,
-    utils::project_schema,
+    storage::{Storage, read_parquet_files_by_locations, stream_parquet_files_by_locations},
+    table::{
+        Table, TableConfig, find_snapshot, find_snapshot_at, limit_stream, process_scan_as_of,
+        prune_data_files, verify_scanned_files,
+    },
+    utils::{has_duplicated_items, project_schema},
 };
 
 #[derive(Debug, Clone, derive_with::With)]
 pub struct TableScan {
     pub projection: Option<Vec<usize>>,
+    /// Names of the columns to read, batches hold them in this order. Only their column chunks
+    /// are fetched from data files. Can not be set along with `projection`.
+    pub columns: Option<Vec<String>>,
     pub filters: Vec<Expr>,
+    /// Rows returned at most, any matching rows as scans have no order. Data files are then
+    /// read lazily, one after another or `concurrency` at once, and the scan stops reading them
+    /// once the limit is reached.
     pub limit: Option<usize>,
+    /// Checks the data files read against their checksums before reading them, failing with
+    /// [`ILError::ChecksumMismatch`] on corrupted files. Every data file read is read twice.
+    pub verify_checksums: bool,
+    /// Rows per batch read from data files, the default of the parquet reader if `None`.
+    /// Batches of inline rows are split to this size as well.
+    pub batch_size: Option<usize>,
+    /// Data files read at once, each fetched and decoded in a task of its own. With `None`
+    /// they are read in the task polling the scan, all at once by [`Table::scan`] and one
+    /// after another by [`Table::scan_stream`].
+    ///
+    /// Batches come in no particular order either way, sort them by the row id column where
+    /// the order matters. A failure reading any file ends the scan with that error, the tasks
+    /// still reading other files are aborted once the stream is dropped.
+    pub concurrency: Option<usize>,
+    /// Snapshot to read the table at, the current state of the table if `None`. See
+    /// [`TableScan::at_snapshot`] and [`TableScan::at_timestamp`].
+    pub as_of: Option<ScanAsOf>,
+}
+
+/// Earlier state of the table a scan reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanAsOf {
+    Snapshot(i64),
+    /// The latest snapshot committed at or before the timestamp.
+    Timestamp(SystemTime),
 }
 
 impl TableScan {
+    /// Reads the rows as they were when the snapshot `snapshot_id` was committed, see
+    /// [`Table::snapshots`]. Index filters are not used, all filters are evaluated on the rows
+    /// read. Fails with [`ILError::SnapshotExpired`] once the snapshot has expired.
+    pub fn at_snapshot(self, snapshot_id: i64) -> Self {
+        self.with_as_of(Some(ScanAsOf::Snapshot(snapshot_id)))
+    }
+
+    /// Reads the rows as they were at `timestamp`, that is at the latest snapshot committed at
+    /// or before it, like [`TableScan::at_snapshot`].
+    pub fn at_timestamp(self, timestamp: SystemTime) -> Self {
+        self.with_as_of(Some(ScanAsOf::Timestamp(timestamp)))
+    }
+
     pub fn projected_schema(&self, table_schema: &SchemaRef) -> ILResult<SchemaRef> {
-        if let Some(projection) = &self.projection {
-            let projected_schema = table_schema.project(projection)?;
+        if let Some(projection) = self.resolve_projection(table_schema)? {
+            let projected_schema = table_schema.project(&projection)?;
             Ok(Arc::new(projected_schema))
         } else {
             Ok(table_schema.clone())
         }
     }
+
+    /// Indices of the scanned columns in the table schema, `None` to scan every column.
+    pub(crate) fn resolve_projection(
+        &self,
+        table_schema: &SchemaRef,
+    ) -> ILResult<Option<Vec<usize>>> {
+        let Some(columns) = &self.columns else {
+            return Ok(self.projection.clone());
+        };
+        if self.projection.is_some() {
+            return Err(ILError::InvalidInput(
+                "Scan projection and columns can not be set together".to_string(),
+            ));
+        }
+        if has_duplicated_items(columns.iter()) {
+            return Err(ILError::InvalidInput(format!(
+                "Duplicated columns in scan columns {columns:?}"
+            )));
+        }
+        let projection = columns
+            .iter()
+            .map(|name| {
+                table_schema.index_of(name).map_err(|_| {
+                    ILError::InvalidInput(format!("Scan column {name} not found in table"))
+                })
+            })
+            .collect::<ILResult<Vec<_>>>()?;
+        Ok(Some(projection))
+    }
 }
 
 impl Default for TableScan {
     fn default() -> Self {
         Self {
             projection: None,
+            columns: None,
             filters: vec![],
             limit: None,
+            verify_checksums: false,
+            batch_size: None,
+            concurrency: None,
+            as_of: None,
         }
     }
 }
@@ -47,22 +139,57 @@ pub(crate) async fn process_scan(
     scan: TableScan,
     storage: Arc<Storage>,
     table: &Table,
+    streaming: bool,
 ) -> ILResult<RecordBatchStream> {
+    if scan.batch_size == Some(0) {
+        return Err(ILError::InvalidInput(
+            "Scan batch size must be greater than 0".to_string(),
+        ));
+    }
+    if scan.concurrency == Some(0) {
+        return Err(ILError::InvalidInput(
+            "Scan concurrency must be greater than 0".to_string(),
+        ));
+    }
+    match scan.as_of {
+        Some(ScanAsOf::Snapshot(snapshot_id)) => {
+            let snapshot = find_snapshot(table, snapshot_id).await?;
+            return process_scan_as_of(table, &snapshot, scan).await;
+        }
+        Some(ScanAsOf::Timestamp(timestamp)) => {
+            let snapshot = find_snapshot_at(table, timestamp).await?;
+            return process_scan_as_of(table, &snapshot, scan).await;
+        }
+        None => {}
+    }
+    // Unknown columns fail the scan before anything is read
+    let projection = scan.resolve_projection(table_schema)?;
+    // Columns the filters read but the projection leaves out are read and dropped afterwards
+    let (projection, projected_len) =
+        extend_projection_with_filter_columns(table_schema, projection, &scan.filters)?;
+    let read_options = DataFileReadOptions {
+        batch_size: scan.batch_size,
+        concurrency: scan.concurrency,
+        streaming: streaming || scan.limit.is_some(),
+    };
     let filters = split_conjunction_filters(scan.filters.clone());
 
     let index_filter_assignment =
         assign_index_filters(&table.indexes, &table.index_kinds, &filters)?;
 
-    if index_filter_assignment
+    let stream = if index_filter_assignment
         .values()
         .any(|filters| filters.len() > 0)
     {
         process_index_scan(
             catalog_helper,
             table,
-            scan.projection,
-            &filters,
+            projection,
+            filters,
+            scan.limit,
+            scan.verify_checksums,
             index_filter_assignment,
+            read_options,
         )
         .await
     } else {
@@ -71,11 +198,51 @@ pub(crate) async fn process_scan(
             &storage,
             table_id,
             table_schema,
-            scan.projection,
+            &table.config,
+            &table.field_defaults,
+            &table.field_ids(),
+            projection,
             filters,
             scan.limit,
+            scan.verify_checksums,
+            read_options,
         )
         .await
+    }?;
+    match projected_len {
+        Some(projected_len) => {
+            let indices = (0..projected_len).collect::<Vec<_>>();
+            Ok(Box::pin(
+                stream.map(move |batch| Ok(batch?.project(&indices)?)),
+            ))
+        }
+        None => Ok(stream),
+    }
+}
+
+/// Appends the columns the filters read to the projection when it leaves them out, returning
+/// the extended projection and the number of projected columns to keep from the batches read.
+fn extend_projection_with_filter_columns(
+    table_schema: &SchemaRef,
+    projection: Option<Vec<usize>>,
+    filters: &[Expr],
+) -> ILResult<(Option<Vec<usize>>, Option<usize>)> {
+    let Some(mut projection) = projection else {
+        return Ok((None, None));
+    };
+    let projected_len = projection.len();
+    for filter in filters {
+        for name in visited_columns(filter) {
+            let idx = table_schema.index_of(&name)?;
+            if !projection.contains(&idx) {
+                projection.push(idx);
+            }
+        }
+    }
+    if projection.len() == projected_len {
+        Ok((Some(projection), None))
+    } else {
+        Ok((Some(projection), Some(projected_len)))
     }
 }
 
@@ -84,9 +251,14 @@ async fn process_table_scan(
     storage: &Arc<Storage>,
     table_id: i64,
     table_schema: &SchemaRef,
+    table_config: &TableConfig,
+    field_defaults: &HashMap<String, Scalar>,
+    field_ids: &HashMap<String, i64>,
     projection: Option<Vec<usize>>,
     filters: Vec<Expr>,
     limit: Option<usize>,
+    verify_checksums: bool,
+    read_options: DataFileReadOptions,
 ) -> ILResult<RecordBatchStream> {
     // Scan inline rows
     let projected_schema = Arc::new(project_schema(table_schema, projection.as_ref())?);
@@ -96,8 +268,7 @@ async fn process_table_scan(
         .await?;
     let inline_row_count = rows.len();
     let batch = rows_to_record_batch(&projected_schema, &rows)?;
-    let batch_stream =
-        Box::pin(futures::stream::once(futures::future::ready(Ok(batch)))) as RecordBatchStream;
+    let batch_stream = inline_batch_stream(batch, read_options.batch_size);
     if let Some(limit) = limit
         && inline_row_count == limit
     {
@@ -106,23 +277,46 @@ async fn process_table_scan(
 
     let left_limit = limit.map(|l| l - inline_row_count);
 
+    // Skip data files whose partition or column statistics can not match the filters
+    let pruned_files = if filters.is_empty() {
+        HashSet::new()
+    } else {
+        let data_files = catalog_helper.get_data_files(table_id).await?;
+        prune_data_files(table_schema, table_config, &filters, &data_files)?
+    };
+    // Rows filtered out must not use up the limit
+    let metadata_limit = if filters.is_empty() { left_limit } else { None };
+
     // Scan data files
     let row_metadatas = catalog_helper
-        .scan_undeleted_non_inline_row_metadata(table_id, left_limit)
+        .scan_undeleted_non_inline_row_metadata(table_id, metadata_limit)
         .await?;
     let data_file_locations = row_metadatas
         .into_iter()
-        .filter(|meta| matches!(meta.location, RowLocation::Parquet { .. }))
-        .map(|meta| meta.location)
+        .filter(|meta| match &meta.location {
+            RowLocation::Parquet { relative_path, .. } => !pruned_files.contains(relative_path),
+            _ => false,
+        })
+        .map(|meta| (meta.row_id, meta.location))
         .collect::<Vec<_>>();
-    let stream = read_parquet_files_by_locations(
+    if verify_checksums {
+        verify_scanned_files(catalog_helper, storage, table_id, &data_file_locations).await?;
+    }
+    let stream = read_data_files(
         storage.clone(),
         table_schema.clone(),
         projection.clone(),
         data_file_locations,
         merge_filters(filters),
+        field_defaults,
+        field_ids,
+        read_options,
     )
     .await?;
+    let stream = match left_limit {
+        Some(left_limit) => limit_stream(stream, left_limit),
+        None => stream,
+    };
 
     Ok(Box::pin(futures::stream::select_all(vec![
         batch_stream,
@@ -134,13 +328,226 @@ async fn process_index_scan(
     catalog_helper: &CatalogHelper,
     table: &Table,
     projection: Option<Vec<usize>>,
-    filters: &[Expr],
+    filters: Vec<Expr>,
+    limit: Option<usize>,
+    verify_checksums: bool,
     index_filter_assignment: HashMap<String, Vec<usize>>,
+    read_options: DataFileReadOptions,
 ) -> ILResult<RecordBatchStream> {
-    todo!()
+    // Scan inline rows, they are not indexed
+    let projected_schema = Arc::new(project_schema(&table.schema, projection.as_ref())?);
+    let catalog_schema = Arc::new(CatalogSchema::from_arrow(&projected_schema)?);
+    let rows = catalog_helper
+        .scan_inline_rows(table.table_id, &catalog_schema, &filters, limit)
+        .await?;
+    let inline_row_count = rows.len();
+    let batch = rows_to_record_batch(&projected_schema, &rows)?;
+    let batch_stream = inline_batch_stream(batch, read_options.batch_size);
+    if let Some(limit) = limit
+        && inline_row_count == limit
+    {
+        return Ok(batch_stream);
+    }
+
+    let data_files = catalog_helper.get_data_files(table.table_id).await?;
+    let index_files = catalog_helper.get_index_files(table.table_id).await?;
+    let file_row_ids = select_data_file_rows(
+        table,
+        data_files,
+        index_files,
+        &filters,
+        &index_filter_assignment,
+    )
+    .await?;
+
+    // Index entries of deleted or moved rows are left out by the row metadata
+    let row_metadatas = catalog_helper
+        .scan_undeleted_non_inline_row_metadata(table.table_id, None)
+        .await?;
+    let data_file_locations = selected_row_locations(row_metadatas, &file_row_ids);
+    if verify_checksums {
+        verify_scanned_files(
+            catalog_helper,
+            &table.storage,
+            table.table_id,
+            &data_file_locations,
+        )
+        .await?;
+    }
+    // Indexes may return rows not matching the filters, so all filters are applied again
+    let stream = read_data_files(
+        table.storage.clone(),
+        table.schema.clone(),
+        projection,
+        data_file_locations,
+        merge_filters(filters),
+        &table.field_defaults,
+        &table.field_ids(),
+        read_options,
+    )
+    .await?;
+    let stream = match limit {
+        Some(limit) => limit_stream(stream, limit - inline_row_count),
+        None => stream,
+    };
+
+    Ok(Box::pin(futures::stream::select_all(vec![
+        batch_stream,
+        stream,
+    ])))
 }
 
-fn assign_index_filters(
+/// Asks the indexes which rows of each data file may match the filters, `None` for all of its
+/// rows. Data files pruned by their statistics or without any row an index selects are left
+/// out.
+pub(crate) async fn select_data_file_rows(
+    table: &Table,
+    data_files: Vec<DataFileRecord>,
+    index_files: Vec<IndexFileRecord>,
+    filters: &[Expr],
+    index_filter_assignment: &HashMap<String, Vec<usize>>,
+) -> ILResult<HashMap<String, Option<HashSet<i64>>>> {
+    let pruned_files = prune_data_files(&table.schema, &table.config, filters, &data_files)?;
+    let index_files = index_files
+        .into_iter()
+        .map(|index_file| ((index_file.data_file_id, index_file.index_id), index_file))
+        .collect::<HashMap<_, _>>();
+    let mut file_row_ids: HashMap<String, Option<HashSet<i64>>> = HashMap::new();
+    'data_files: for data_file in data_files {
+        if pruned_files.contains(&data_file.relative_path) {
+            continue;
+        }
+        let mut selected: Option<HashSet<i64>> = None;
+        for (index_name, filter_indexes) in index_filter_assignment {
+            if filter_indexes.is_empty() {
+                continue;
+            }
+            let index_def = table
+                .indexes
+                .get(index_name)
+                .ok_or_else(|| ILError::InternalError(format!("Index {index_name} not found")))?;
+            // Data files written before the index was created are not indexed
+            let Some(index_file) = index_files.get(&(data_file.data_file_id, index_def.index_id))
+            else {
+                continue;
+            };
+            let index = table.index_kinds.get(&index_def.kind).ok_or_else(|| {
+                ILError::InternalError(format!("Index kind {} not found", index_def.kind))
+            })?;
+            let index_filters = filter_indexes
+                .iter()
+                .map(|idx| filters[*idx].clone())
+                .collect::<Vec<_>>();
+            if let Some(metadata) = &index_file.metadata {
+                match index.filter_file_metadata(index_def, metadata, &index_filters)? {
+                    Some(false) => continue 'data_files,
+                    Some(true) => continue,
+                    None => {}
+                }
+            }
+            let index_file = table.storage.open_file(&index_file.relative_path).await?;
+            let entries = index.filter(index_def, index_file, &index_filters).await?;
+            let row_ids = entries
+                .row_ids
+                .values()
+                .iter()
+                .copied()
+                .collect::<HashSet<_>>();
+            selected = Some(match selected {
+                Some(selected) => selected.intersection(&row_ids).copied().collect(),
+                None => row_ids,
+            });
+        }
+        if selected.as_ref().is_some_and(|row_ids| row_ids.is_empty()) {
+            continue;
+        }
+        file_row_ids.insert(data_file.relative_path, selected);
+    }
+    Ok(file_row_ids)
+}
+
+/// Locations of the rows in data files selected by [`select_data_file_rows`].
+pub(crate) fn selected_row_locations(
+    row_metadatas: Vec<RowMetadataRecord>,
+    file_row_ids: &HashMap<String, Option<HashSet<i64>>>,
+) -> Vec<(i64, RowLocation)> {
+    row_metadatas
+        .into_iter()
+        .filter(|meta| match &meta.location {
+            RowLocation::Parquet { relative_path, .. } => match file_row_ids.get(relative_path) {
+                Some(Some(row_ids)) => row_ids.contains(&meta.row_id),
+                Some(None) => true,
+                None => false,
+            },
+            _ => false,
+        })
+        .map(|meta| (meta.row_id, meta.location))
+        .collect()
+}
+
+/// Inline rows as one batch, or batches of `batch_size` rows.
+pub(crate) fn inline_batch_stream(
+    batch: RecordBatch,
+    batch_size: Option<usize>,
+) -> RecordBatchStream {
+    let batches = match batch_size {
+        Some(batch_size) if batch.num_rows() > batch_size => (0..batch.num_rows())
+            .step_by(batch_size)
+            .map(|offset| Ok(batch.slice(offset, batch_size.min(batch.num_rows() - offset))))
+            .collect::<Vec<_>>(),
+        _ => vec![Ok(batch)],
+    };
+    Box::pin(futures::stream::iter(batches))
+}
+
+#[derive(Debug, Clone, Copy)]
+struct DataFileReadOptions {
+    batch_size: Option<usize>,
+    concurrency: Option<usize>,
+    streaming: bool,
+}
+
+/// Reads the data files in tasks of their own with a concurrency, otherwise one at a time for
+/// streaming scans and all at once for the others.
+#[allow(clippy::too_many_arguments)]
+async fn read_data_files(
+    storage: Arc<Storage>,
+    table_schema: SchemaRef,
+    projection: Option<Vec<usize>>,
+    data_file_locations: Vec<(i64, RowLocation)>,
+    predicate: Option<Expr>,
+    field_defaults: &HashMap<String, Scalar>,
+    field_ids: &HashMap<String, i64>,
+    read_options: DataFileReadOptions,
+) -> ILResult<RecordBatchStream> {
+    if read_options.streaming || read_options.concurrency.is_some() {
+        stream_parquet_files_by_locations(
+            storage,
+            table_schema,
+            projection,
+            data_file_locations,
+            predicate,
+            field_defaults.clone(),
+            field_ids.clone(),
+            read_options.batch_size,
+            read_options.concurrency,
+        )
+    } else {
+        read_parquet_files_by_locations(
+            storage,
+            table_schema,
+            projection,
+            data_file_locations,
+            predicate,
+            field_defaults,
+            field_ids,
+            read_options.batch_size,
+        )
+        .await
+    }
+}
+
+pub(crate) fn assign_index_filters(
     indexes: &HashMap<String, IndexDefinationRef>,
     index_kinds: &HashMap<String, Arc<dyn Index>>,
     filters: &[Expr],
@@ -148,6 +555,12 @@ fn assign_index_filters(
     let mut index_filter_assignment: HashMap<String, Vec<usize>> = HashMap::new();
     for (filter_idx, filter) in filters.iter().enumerate() {
         for (index_name, index_def) in indexes.iter() {
+            // Partial indexes miss the rows not matching their predicate
+            if let Some(predicate) = &index_def.where_predicate
+                && !filters_imply(filters, predicate)
+            {
+                continue;
+            }
             let index_kind = &index_def.kind;
             let index = index_kinds.get(index_kind).ok_or_else(|| {
                 ILError::InternalError(format!("Index kind {index_kind} not found"))
@@ -161,5 +574,16 @@ fn assign_index_filters(
             }
         }
     }
+    for (index_name, filter_indexes) in index_filter_assignment.iter_mut() {
+        let index_def = &indexes[index_name];
+        let index = &index_kinds[&index_def.kind];
+        let index_filters = filter_indexes
+            .iter()
+            .map(|idx| filters[*idx].clone())
+            .collect::<Vec<_>>();
+        if !index.supports_filters(index_def, &index_filters)? {
+            filter_indexes.clear();
+        }
+    }
     Ok(index_filter_assignment)
 }
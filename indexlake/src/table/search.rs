@@ -0,0 +1,217 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use arrow::{
+    array::{RecordBatch, UInt64Array},
+    compute::{concat_batches, take_record_batch},
+};
+use futures::TryStreamExt;
+
+use crate::{
+    ILError, ILResult, RecordBatchStream,
+    catalog::{CatalogHelper, CatalogSchema, RowLocation, rows_to_record_batch},
+    index::{Index, IndexDefinationRef, SearchIndexEntries, SearchQuery},
+    storage::{Storage, read_parquet_files_by_locations},
+    table::Table,
+    utils::extract_row_id_array_from_record_batch,
+};
+
+/// Where a search hit was found, hits of rows since moved elsewhere are stale.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum HitSource {
+    Inline,
+    DataFile(String),
+}
+
+pub(crate) async fn process_search(
+    table: &Table,
+    query: Arc<dyn SearchQuery>,
+) -> ILResult<RecordBatchStream> {
+    let (index_def, index) = find_search_index(table, query.as_ref())?;
+    let catalog_helper = CatalogHelper::new(table.catalog.clone());
+
+    // Inline rows are not indexed, they are searched with an index built on the fly
+    let catalog_schema = Arc::new(CatalogSchema::from_arrow(&table.schema)?);
+    let rows = catalog_helper
+        .scan_inline_rows(table.table_id, &catalog_schema, &[], None)
+        .await?;
+    let inline_batch = rows_to_record_batch(&table.schema, &rows)?;
+    let mut hits = Vec::new();
+    if inline_batch.num_rows() > 0 {
+        let entries =
+            search_batch(index.as_ref(), &index_def, &inline_batch, query.as_ref()).await?;
+        collect_hits(&mut hits, &entries, HitSource::Inline);
+    }
+
+    let row_metadatas = catalog_helper
+        .scan_undeleted_non_inline_row_metadata(table.table_id, None)
+        .await?;
+    let index_file_paths = catalog_helper
+        .get_index_files(table.table_id)
+        .await?
+        .into_iter()
+        .filter(|index_file| index_file.index_id == index_def.index_id)
+        .map(|index_file| (index_file.data_file_id, index_file.relative_path))
+        .collect::<HashMap<_, _>>();
+    let mut score_higher_is_better = true;
+    for data_file in catalog_helper.get_data_files(table.table_id).await? {
+        let entries = match index_file_paths.get(&data_file.data_file_id) {
+            Some(relative_path) => {
+                let index_file = table.storage.open_file(relative_path).await?;
+                index.search(&index_def, index_file, query.as_ref()).await?
+            }
+            // Data files written before the index was created are not indexed
+            None => {
+                let locations = row_metadatas
+                    .iter()
+                    .filter(|meta| in_data_file(&meta.location, &data_file.relative_path))
+                    .map(|meta| (meta.row_id, meta.location.clone()))
+                    .collect::<Vec<_>>();
+                let batch = read_locations(table, locations).await?;
+                search_batch(index.as_ref(), &index_def, &batch, query.as_ref()).await?
+            }
+        };
+        score_higher_is_better = entries.score_higher_is_better;
+        collect_hits(
+            &mut hits,
+            &entries,
+            HitSource::DataFile(data_file.relative_path),
+        );
+    }
+
+    // Hits of deleted rows and of rows moved to another file since are left out
+    let inline_row_ids = extract_row_id_array_from_record_batch(&inline_batch)?
+        .values()
+        .iter()
+        .copied()
+        .collect::<HashSet<_>>();
+    let locations = row_metadatas
+        .into_iter()
+        .map(|meta| (meta.row_id, meta.location))
+        .collect::<HashMap<_, _>>();
+    hits.retain(|(row_id, _, source)| match source {
+        HitSource::Inline => inline_row_ids.contains(row_id),
+        HitSource::DataFile(relative_path) => locations
+            .get(row_id)
+            .is_some_and(|location| in_data_file(location, relative_path)),
+    });
+    hits.sort_by(|(a_row_id, a_score, _), (b_row_id, b_score, _)| {
+        let ordering = if score_higher_is_better {
+            b_score.total_cmp(a_score)
+        } else {
+            a_score.total_cmp(b_score)
+        };
+        ordering.then(a_row_id.cmp(b_row_id))
+    });
+    if let Some(limit) = query.limit() {
+        hits.truncate(limit);
+    }
+
+    // Read the rows of the hits and return them in ranked order
+    let file_locations = hits
+        .iter()
+        .filter(|(_, _, source)| matches!(source, HitSource::DataFile(_)))
+        .filter_map(|(row_id, _, _)| {
+            locations
+                .get(row_id)
+                .map(|location| (*row_id, location.clone()))
+        })
+        .collect::<Vec<_>>();
+    let file_batch = read_locations(table, file_locations).await?;
+    let batch = concat_batches(&table.schema, [&inline_batch, &file_batch])?;
+    let offsets = extract_row_id_array_from_record_batch(&batch)?
+        .values()
+        .iter()
+        .enumerate()
+        .map(|(offset, row_id)| (*row_id, offset as u64))
+        .collect::<HashMap<_, _>>();
+    let indices = hits
+        .iter()
+        .filter_map(|(row_id, _, _)| offsets.get(row_id).copied())
+        .collect::<Vec<_>>();
+    let batch = take_record_batch(&batch, &UInt64Array::from(indices))?;
+
+    Ok(Box::pin(futures::stream::once(futures::future::ready(Ok(
+        batch,
+    )))))
+}
+
+fn find_search_index(
+    table: &Table,
+    query: &dyn SearchQuery,
+) -> ILResult<(IndexDefinationRef, Arc<dyn Index>)> {
+    let mut index_names = table.indexes.keys().collect::<Vec<_>>();
+    index_names.sort();
+    for index_name in index_names {
+        let index_def = &table.indexes[index_name];
+        let index = table.index_kinds.get(&index_def.kind).ok_or_else(|| {
+            ILError::InternalError(format!("Index kind {} not found", index_def.kind))
+        })?;
+        if index.supports_search(index_def, query)? {
+            return Ok((index_def.clone(), index.clone()));
+        }
+    }
+    Err(ILError::InvalidInput(format!(
+        "No index of table {} supports search query {query:?}",
+        table.table_name
+    )))
+}
+
+/// Searches rows with an index built in memory.
+async fn search_batch(
+    index: &dyn Index,
+    index_def: &IndexDefinationRef,
+    batch: &RecordBatch,
+    query: &dyn SearchQuery,
+) -> ILResult<SearchIndexEntries> {
+    let storage = Storage::new_memory();
+    let relative_path = "search.index";
+    let mut builder = index.builder(index_def)?;
+    builder.update(&index_def.indexed_rows(batch)?)?;
+    builder
+        .write(storage.create_file(relative_path).await?)
+        .await?;
+    index
+        .search(index_def, storage.open_file(relative_path).await?, query)
+        .await
+}
+
+fn collect_hits(
+    hits: &mut Vec<(i64, f64, HitSource)>,
+    entries: &SearchIndexEntries,
+    source: HitSource,
+) {
+    for (row_id, score) in entries
+        .row_ids
+        .values()
+        .iter()
+        .zip(entries.scores.values().iter())
+    {
+        hits.push((*row_id, *score, source.clone()));
+    }
+}
+
+fn in_data_file(location: &RowLocation, data_file_path: &str) -> bool {
+    matches!(location, RowLocation::Parquet { relative_path, .. } if relative_path == data_file_path)
+}
+
+async fn read_locations(
+    table: &Table,
+    locations: Vec<(i64, RowLocation)>,
+) -> ILResult<RecordBatch> {
+    let stream = read_parquet_files_by_locations(
+        table.storage.clone(),
+        table.schema.clone(),
+        None,
+        locations,
+        None,
+        &table.field_defaults,
+        &table.field_ids(),
+        None,
+    )
+    .await?;
+    let batches = stream.try_collect::<Vec<_>>().await?;
+    Ok(concat_batches(&table.schema, &batches)?)
+}
@@ -0,0 +1,257 @@
+use std::time::Duration;
+
+use arrow::array::RecordBatch;
+use arrow::compute::concat_batches;
+use log::debug;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use crate::catalog::TransactionHelper;
+use crate::table::{SnapshotOperation, Table, commit_snapshot, process_flush, process_insert};
+use crate::{ILError, ILResult};
+
+#[derive(Debug, Clone, derive_with::With)]
+pub struct SinkOptions {
+    /// Buffered rows are committed once there are at least this many of them.
+    pub max_buffered_rows: usize,
+    /// Buffered rows are committed once their batches take at least this many bytes of memory.
+    pub max_buffered_bytes: usize,
+    /// Buffered rows are committed at the latest this long after the first of them was written,
+    /// `None` commits them on size only.
+    pub flush_interval: Option<Duration>,
+}
+
+impl Default for SinkOptions {
+    fn default() -> Self {
+        Self {
+            max_buffered_rows: 100_000,
+            max_buffered_bytes: 64 * 1024 * 1024,
+            flush_interval: Some(Duration::from_secs(10)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SinkReport {
+    /// Rows committed by the sink.
+    pub written_rows: u64,
+    /// Data files written by the commits, in commit order.
+    pub data_file_paths: Vec<String>,
+}
+
+enum SinkMessage {
+    Write(RecordBatch),
+    Flush(oneshot::Sender<u64>),
+    Close,
+}
+
+/// Handle of [`Table::insert_sink`] buffering written batches in a background task, which
+/// commits them into data files of the table whenever a threshold of [`SinkOptions`] trips.
+/// Each commit writes the buffered rows as one data file, one per partition in partitioned
+/// tables, and one snapshot.
+///
+/// Dropping the handle without [`InsertSink::close`] discards the buffered rows, nothing of them
+/// is written or registered. Commits already started complete or fail as a whole.
+pub struct InsertSink {
+    table: Table,
+    sender: mpsc::Sender<SinkMessage>,
+    worker: Option<JoinHandle<ILResult<SinkReport>>>,
+}
+
+impl InsertSink {
+    pub(crate) fn try_new(table: &Table, options: SinkOptions) -> ILResult<Self> {
+        if options.max_buffered_rows == 0 || options.max_buffered_bytes == 0 {
+            return Err(ILError::InvalidInput(
+                "Sink max buffered rows and bytes must be greater than 0".to_string(),
+            ));
+        }
+        if options.flush_interval == Some(Duration::ZERO) {
+            return Err(ILError::InvalidInput(
+                "Sink flush interval must be greater than 0".to_string(),
+            ));
+        }
+        // A single batch in flight, writers wait while the buffer is committed
+        let (sender, receiver) = mpsc::channel(1);
+        let worker = SinkWorker {
+            table: table.clone(),
+            options,
+            receiver,
+            buffer: Vec::new(),
+            buffered_rows: 0,
+            buffered_bytes: 0,
+            deadline: None,
+            report: SinkReport::default(),
+        };
+        Ok(Self {
+            table: table.clone(),
+            sender,
+            worker: Some(tokio::spawn(worker.run())),
+        })
+    }
+
+    /// Buffers the rows of `batch`, filled and checked like [`Table::insert`] does. Waits while
+    /// the buffer is full and being committed. Fails with the error of a failed commit, which
+    /// ends the sink.
+    pub async fn write(&mut self, batch: &RecordBatch) -> ILResult<()> {
+        let batch = self.table.conform_record(batch)?;
+        if batch.num_rows() == 0 {
+            return Ok(());
+        }
+        self.send(SinkMessage::Write(batch)).await
+    }
+
+    /// Commits the buffered rows, returning how many there were.
+    pub async fn flush(&mut self) -> ILResult<u64> {
+        let (reply, flushed) = oneshot::channel();
+        self.send(SinkMessage::Flush(reply)).await?;
+        match flushed.await {
+            Ok(flushed_rows) => Ok(flushed_rows),
+            Err(_) => Err(self.worker_error().await),
+        }
+    }
+
+    /// Commits the buffered rows and ends the sink, returning the rows committed over its
+    /// lifetime.
+    pub async fn close(mut self) -> ILResult<SinkReport> {
+        self.send(SinkMessage::Close).await?;
+        match self.worker.take() {
+            Some(worker) => join_worker(worker).await,
+            None => Err(closed_error()),
+        }
+    }
+
+    async fn send(&mut self, message: SinkMessage) -> ILResult<()> {
+        if self.sender.send(message).await.is_err() {
+            return Err(self.worker_error().await);
+        }
+        Ok(())
+    }
+
+    /// Error that ended the background task.
+    async fn worker_error(&mut self) -> ILError {
+        match self.worker.take() {
+            Some(worker) => match join_worker(worker).await {
+                Ok(_) => closed_error(),
+                Err(e) => e,
+            },
+            None => closed_error(),
+        }
+    }
+}
+
+async fn join_worker(worker: JoinHandle<ILResult<SinkReport>>) -> ILResult<SinkReport> {
+    worker
+        .await
+        .map_err(|e| ILError::InternalError(format!("Insert sink task failed: {e:?}")))?
+}
+
+fn closed_error() -> ILError {
+    ILError::InvalidInput("Insert sink is closed after a failed commit".to_string())
+}
+
+struct SinkWorker {
+    table: Table,
+    options: SinkOptions,
+    receiver: mpsc::Receiver<SinkMessage>,
+    buffer: Vec<RecordBatch>,
+    buffered_rows: usize,
+    buffered_bytes: usize,
+    /// When the buffered rows are due for the flush interval.
+    deadline: Option<Instant>,
+    report: SinkReport,
+}
+
+impl SinkWorker {
+    async fn run(mut self) -> ILResult<SinkReport> {
+        loop {
+            let message = match self.deadline {
+                Some(deadline) => tokio::select! {
+                    message = self.receiver.recv() => message,
+                    _ = tokio::time::sleep_until(deadline) => {
+                        self.commit().await?;
+                        continue;
+                    }
+                },
+                None => self.receiver.recv().await,
+            };
+            match message {
+                Some(SinkMessage::Write(batch)) => {
+                    if self.buffer.is_empty() {
+                        self.deadline = self.options.flush_interval.map(|i| Instant::now() + i);
+                    }
+                    self.buffered_rows += batch.num_rows();
+                    self.buffered_bytes += batch.get_array_memory_size();
+                    self.buffer.push(batch);
+                    if self.buffered_rows >= self.options.max_buffered_rows
+                        || self.buffered_bytes >= self.options.max_buffered_bytes
+                    {
+                        self.commit().await?;
+                    }
+                }
+                Some(SinkMessage::Flush(reply)) => {
+                    let flushed_rows = self.commit().await?;
+                    let _ = reply.send(flushed_rows);
+                }
+                Some(SinkMessage::Close) => {
+                    self.commit().await?;
+                    return Ok(self.report);
+                }
+                None => {
+                    debug!(
+                        "Insert sink of table {} dropped, discard {} buffered rows",
+                        self.table.table_id, self.buffered_rows
+                    );
+                    return Ok(self.report);
+                }
+            }
+        }
+    }
+
+    /// Commits the buffered rows, returning how many there were.
+    async fn commit(&mut self) -> ILResult<u64> {
+        self.deadline = None;
+        let Some(first) = self.buffer.first() else {
+            return Ok(0);
+        };
+        let record = concat_batches(&first.schema(), &self.buffer)?;
+        self.buffer.clear();
+        self.buffered_rows = 0;
+        self.buffered_bytes = 0;
+
+        let data_file_paths = commit_sink_batch(&self.table, &record).await?;
+        debug!(
+            "Insert sink committed {} rows into table {} as data files {data_file_paths:?}",
+            record.num_rows(),
+            self.table.table_id
+        );
+        self.report.written_rows += record.num_rows() as u64;
+        self.report.data_file_paths.extend(data_file_paths);
+        Ok(record.num_rows() as u64)
+    }
+}
+
+/// Inserts the rows of `record` and moves them into data files in one transaction, returning
+/// the paths of the files.
+async fn commit_sink_batch(table: &Table, record: &RecordBatch) -> ILResult<Vec<String>> {
+    TransactionHelper::run(&table.catalog, |mut tx_helper| {
+        Box::pin(async move {
+            let snapshot_id = tx_helper.get_max_snapshot_id().await? + 1;
+            let first_row_id = tx_helper.get_max_row_id(table.table_id).await? + 1;
+            process_insert(&mut tx_helper, table, record).await?;
+            let row_ids =
+                (first_row_id..first_row_id + record.num_rows() as i64).collect::<Vec<_>>();
+            let report = process_flush(&mut tx_helper, table, &row_ids).await?;
+            commit_snapshot(
+                &mut tx_helper,
+                table,
+                snapshot_id,
+                SnapshotOperation::Insert,
+            )
+            .await?;
+            tx_helper.commit().await?;
+            Ok(report.data_file_paths)
+        })
+    })
+    .await
+}
@@ -0,0 +1,601 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use arrow::array::{AsArray, RecordBatch};
+use arrow::compute::filter_record_batch;
+use futures::{StreamExt, TryStreamExt};
+
+use crate::catalog::{
+    CatalogHelper, CatalogSchema, INTERNAL_ROW_ID_FIELD_NAME, Row, RowHistoryRecord, RowLocation,
+    Scalar, SnapshotRecord, TransactionHelper, rows_to_record_batch,
+};
+use crate::expr::{Expr, col, lit, merge_filters, split_conjunction_filters};
+use crate::storage::{read_parquet_files_by_locations, stream_parquet_files_by_locations};
+use crate::table::{Table, TableScan, inline_batch_stream, verify_scanned_files};
+use crate::{CommitConflict, ILError, ILResult, RecordBatchStream};
+
+/// A committed state of a table. Every commit changing the rows or data files of a table
+/// records a snapshot, which [`TableScan::at_snapshot`] can read until it expires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotMeta {
+    /// Increases with every commit.
+    pub snapshot_id: i64,
+    pub timestamp: SystemTime,
+    /// `None` for snapshots committed before operations were recorded.
+    pub operation: Option<SnapshotOperation>,
+    /// Rows inserted by the commit, upserts insert a new version of the rows they replace.
+    pub added_rows: u64,
+    /// Rows updated in place, keeping their row ids.
+    pub updated_rows: u64,
+    /// Rows deleted by the commit, including the rows upserts replace.
+    pub removed_rows: u64,
+}
+
+impl From<&SnapshotRecord> for SnapshotMeta {
+    fn from(record: &SnapshotRecord) -> Self {
+        Self {
+            snapshot_id: record.snapshot_id,
+            timestamp: UNIX_EPOCH + Duration::from_millis(record.timestamp_ms as u64),
+            operation: record
+                .operation
+                .as_deref()
+                .and_then(|operation| operation.parse().ok()),
+            added_rows: record.added_rows as u64,
+            updated_rows: record.updated_rows as u64,
+            removed_rows: record.removed_rows as u64,
+        }
+    }
+}
+
+/// Kind of commit that recorded a snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotOperation {
+    CreateTable,
+    Insert,
+    Upsert,
+    Update,
+    Delete,
+    Merge,
+    Truncate,
+    IngestParquet,
+    AddFiles,
+    Expire,
+    Clone,
+}
+
+impl SnapshotOperation {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SnapshotOperation::CreateTable => "create_table",
+            SnapshotOperation::Insert => "insert",
+            SnapshotOperation::Upsert => "upsert",
+            SnapshotOperation::Update => "update",
+            SnapshotOperation::Delete => "delete",
+            SnapshotOperation::Merge => "merge",
+            SnapshotOperation::Truncate => "truncate",
+            SnapshotOperation::IngestParquet => "ingest_parquet",
+            SnapshotOperation::AddFiles => "add_files",
+            SnapshotOperation::Expire => "expire",
+            SnapshotOperation::Clone => "clone",
+        }
+    }
+}
+
+impl std::fmt::Display for SnapshotOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for SnapshotOperation {
+    type Err = ILError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "create_table" => Ok(SnapshotOperation::CreateTable),
+            "insert" => Ok(SnapshotOperation::Insert),
+            "upsert" => Ok(SnapshotOperation::Upsert),
+            "update" => Ok(SnapshotOperation::Update),
+            "delete" => Ok(SnapshotOperation::Delete),
+            "merge" => Ok(SnapshotOperation::Merge),
+            "truncate" => Ok(SnapshotOperation::Truncate),
+            "ingest_parquet" => Ok(SnapshotOperation::IngestParquet),
+            "add_files" => Ok(SnapshotOperation::AddFiles),
+            "expire" => Ok(SnapshotOperation::Expire),
+            "clone" => Ok(SnapshotOperation::Clone),
+            _ => Err(ILError::InvalidInput(format!(
+                "Invalid snapshot operation: {s}"
+            ))),
+        }
+    }
+}
+
+fn timestamp_ms(timestamp: SystemTime) -> i64 {
+    timestamp
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+/// Records the snapshot `snapshot_id` of the table, to be called after the other changes of
+/// the transaction as it captures the greatest row id allocated so far and counts the rows the
+/// transaction inserted and replaced.
+pub(crate) async fn record_snapshot(
+    tx_helper: &mut TransactionHelper,
+    table_id: i64,
+    snapshot_id: i64,
+    operation: SnapshotOperation,
+) -> ILResult<()> {
+    let max_row_id = tx_helper.get_max_row_id(table_id).await?;
+    let previous_max_row_id = tx_helper
+        .get_snapshots(table_id)
+        .await?
+        .last()
+        .map(|snapshot| snapshot.max_row_id)
+        .unwrap_or(0);
+    let (replaced_rows, updated_rows) =
+        tx_helper.count_replaced_rows(table_id, snapshot_id).await?;
+    tx_helper
+        .insert_snapshot(&SnapshotRecord {
+            snapshot_id,
+            table_id,
+            timestamp_ms: timestamp_ms(SystemTime::now()),
+            max_row_id,
+            operation: Some(operation.to_string()),
+            added_rows: max_row_id - previous_max_row_id,
+            updated_rows,
+            removed_rows: replaced_rows - updated_rows,
+            expired: false,
+        })
+        .await?;
+    Ok(())
+}
+
+/// Fails with [`ILError::CommitConflict`] if the table has a snapshot after the base snapshot
+/// of the handle, and returns the base snapshot id. The current snapshot is read within the
+/// transaction, so of two transactions committing on the same base only one can commit.
+pub(crate) async fn check_base_snapshot(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+) -> ILResult<Option<i64>> {
+    let Some(base_snapshot) = &table.base_snapshot else {
+        return Ok(None);
+    };
+    let base_snapshot_id = base_snapshot.load(Ordering::SeqCst);
+    let current_snapshot_id = tx_helper.get_current_snapshot_id(table.table_id).await?;
+    if current_snapshot_id != base_snapshot_id {
+        return Err(commit_conflict(
+            table,
+            base_snapshot_id,
+            current_snapshot_id,
+        ));
+    }
+    Ok(Some(base_snapshot_id))
+}
+
+fn commit_conflict(table: &Table, base_snapshot_id: i64, current_snapshot_id: i64) -> ILError {
+    ILError::CommitConflict(CommitConflict {
+        table_name: table.table_name.clone(),
+        base_snapshot_id,
+        current_snapshot_id,
+    })
+}
+
+/// Records the snapshot `snapshot_id` like [`record_snapshot`], then expires the snapshots
+/// committed before the snapshot retention of the table. Handles with a base snapshot check it
+/// first and move it to `snapshot_id` once the transaction commits.
+pub(crate) async fn commit_snapshot(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    snapshot_id: i64,
+    operation: SnapshotOperation,
+) -> ILResult<()> {
+    if let Some(base_snapshot_id) = check_base_snapshot(tx_helper, table).await?
+        && let Some(base_snapshot) = table.base_snapshot.clone()
+    {
+        tx_helper.after_commit(move || {
+            // Left alone if the handle was rebased meanwhile
+            let _ = base_snapshot.compare_exchange(
+                base_snapshot_id,
+                snapshot_id,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            );
+        });
+    }
+    record_snapshot(tx_helper, table.table_id, snapshot_id, operation).await?;
+    if let Some(retention) = table.config.snapshot_retention {
+        let expire_before = SystemTime::now()
+            .checked_sub(retention)
+            .unwrap_or(UNIX_EPOCH);
+        expire_snapshots(tx_helper, table.table_id, expire_before).await?;
+    }
+    Ok(())
+}
+
+/// Keeps the current version of the undeleted rows matching `row_metadata_condition` as row
+/// history ending at `snapshot_id` and returns their number. Must be called before the
+/// transaction updates or deletes the rows.
+pub(crate) async fn record_replaced_rows(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    row_metadata_condition: Expr,
+    snapshot_id: i64,
+) -> ILResult<usize> {
+    let undeleted = col("deleted").eq(lit(false));
+    let row_metadatas = tx_helper
+        .scan_row_metadata(table.table_id, &undeleted.and(row_metadata_condition))
+        .await?;
+    if row_metadatas.is_empty() {
+        return Ok(0);
+    }
+
+    let inline_row_ids = row_metadatas
+        .iter()
+        .filter(|meta| matches!(meta.location, RowLocation::Inline))
+        .map(|meta| meta.row_id)
+        .collect::<Vec<_>>();
+    let mut inline_values = HashMap::new();
+    if !inline_row_ids.is_empty() {
+        let catalog_schema = Arc::new(CatalogSchema::from_arrow(&table.schema)?);
+        let rows = tx_helper
+            .scan_inline_rows_by_row_ids(table.table_id, &catalog_schema, &inline_row_ids)
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+        for row in rows {
+            let row_id = row.get_row_id()?.expect("row_id is not null");
+            inline_values.insert(row_id, encode_row_values(&row)?);
+        }
+    }
+
+    let row_histories = row_metadatas
+        .into_iter()
+        .map(|meta| RowHistoryRecord {
+            table_id: table.table_id,
+            row_id: meta.row_id,
+            end_snapshot_id: snapshot_id,
+            row_values: inline_values.remove(&meta.row_id),
+            location: meta.location,
+        })
+        .collect::<Vec<_>>();
+    tx_helper
+        .insert_row_histories(&row_histories, table.config.catalog_insert_batch_size)
+        .await?;
+    Ok(row_histories.len())
+}
+
+fn encode_row_values(row: &Row) -> ILResult<Vec<u8>> {
+    let values = row
+        .schema
+        .columns
+        .iter()
+        .zip(row.values.iter())
+        .filter(|(column, _)| column.name != INTERNAL_ROW_ID_FIELD_NAME)
+        .map(|(column, value)| (column.name.as_str(), value))
+        .collect::<BTreeMap<_, _>>();
+    serde_json::to_vec(&values)
+        .map_err(|e| ILError::InternalError(format!("Failed to serialize row values: {e:?}")))
+}
+
+/// Decodes the values of a replaced inline row into a row of the current table schema.
+/// Columns added since are filled with their default value or null.
+fn decode_row_values(table: &Table, row_id: i64, row_values: &[u8]) -> ILResult<Row> {
+    let mut values: HashMap<String, Scalar> = serde_json::from_slice(row_values)
+        .map_err(|e| ILError::InternalError(format!("Failed to deserialize row values: {e:?}")))?;
+    let catalog_schema = Arc::new(CatalogSchema::from_arrow(&table.schema)?);
+    let mut row = Vec::with_capacity(table.schema.fields().len());
+    for field in table.schema.fields() {
+        let value = if field.name() == INTERNAL_ROW_ID_FIELD_NAME {
+            Scalar::Int64(Some(row_id))
+        } else if let Some(value) = values.remove(field.name()) {
+            value
+        } else if let Some(default) = table.field_defaults.get(field.name()) {
+            default.clone()
+        } else {
+            Scalar::try_new_null(field.data_type())?
+        };
+        row.push(value);
+    }
+    Ok(Row::new(catalog_schema, row))
+}
+
+pub(crate) async fn process_snapshots(table: &Table) -> ILResult<Vec<SnapshotMeta>> {
+    let catalog_helper = CatalogHelper::new(table.catalog.clone());
+    let snapshots = catalog_helper.get_snapshots(table.table_id).await?;
+    Ok(snapshots
+        .iter()
+        .filter(|snapshot| !snapshot.expired)
+        .map(SnapshotMeta::from)
+        .collect())
+}
+
+/// Returns the snapshot `snapshot_id` of the table. Expired snapshots are all older than the
+/// latest one, which is kept marked expired, so ids up to it fail with
+/// [`ILError::SnapshotExpired`].
+pub(crate) async fn find_snapshot(table: &Table, snapshot_id: i64) -> ILResult<SnapshotRecord> {
+    let catalog_helper = CatalogHelper::new(table.catalog.clone());
+    let snapshots = catalog_helper.get_snapshots(table.table_id).await?;
+    if let Some(snapshot) = snapshots
+        .iter()
+        .find(|snapshot| snapshot.snapshot_id == snapshot_id && !snapshot.expired)
+    {
+        return Ok(snapshot.clone());
+    }
+    if snapshots
+        .iter()
+        .any(|snapshot| snapshot.expired && snapshot.snapshot_id >= snapshot_id)
+    {
+        return Err(ILError::SnapshotExpired(format!(
+            "Snapshot {snapshot_id} of table {} has expired",
+            table.table_name
+        )));
+    }
+    Err(ILError::InvalidInput(format!(
+        "Snapshot {snapshot_id} of table {} not found",
+        table.table_name
+    )))
+}
+
+/// Returns the latest snapshot committed at or before `timestamp`. Fails with
+/// [`ILError::SnapshotExpired`] if that snapshot has expired.
+pub(crate) async fn find_snapshot_at(
+    table: &Table,
+    timestamp: SystemTime,
+) -> ILResult<SnapshotRecord> {
+    let catalog_helper = CatalogHelper::new(table.catalog.clone());
+    let timestamp_ms = timestamp_ms(timestamp);
+    let snapshots = catalog_helper.get_snapshots(table.table_id).await?;
+    let has_expired = snapshots.iter().any(|snapshot| snapshot.expired);
+    match snapshots
+        .into_iter()
+        .filter(|snapshot| snapshot.timestamp_ms <= timestamp_ms)
+        .next_back()
+    {
+        Some(snapshot) if !snapshot.expired => Ok(snapshot),
+        // Expired snapshots other than the latest are gone, the timestamp may fall before it
+        _ if has_expired => Err(ILError::SnapshotExpired(format!(
+            "Snapshot of table {} at {timestamp:?} has expired",
+            table.table_name
+        ))),
+        _ => Err(ILError::InvalidInput(format!(
+            "Table {} has no snapshot at or before {timestamp:?}",
+            table.table_name
+        ))),
+    }
+}
+
+/// Scans the rows that were live at `snapshot`. A row reads as its first version in the row
+/// history that ended after the snapshot, or as its current version if it has not been
+/// replaced since. Rows with an id above the greatest row id of the snapshot were inserted
+/// later.
+pub(crate) async fn process_scan_as_of(
+    table: &Table,
+    snapshot: &SnapshotRecord,
+    scan: TableScan,
+) -> ILResult<RecordBatchStream> {
+    let projection = scan.resolve_projection(&table.schema)?;
+    let catalog_helper = CatalogHelper::new(table.catalog.clone());
+    let filters = split_conjunction_filters(scan.filters.clone());
+
+    let mut replaced = HashMap::new();
+    for row_history in catalog_helper.scan_row_histories_at(snapshot).await? {
+        replaced.entry(row_history.row_id).or_insert(row_history);
+    }
+
+    let condition = col(INTERNAL_ROW_ID_FIELD_NAME)
+        .lt_eq(lit(snapshot.max_row_id))
+        .and(col("deleted").eq(lit(false)));
+    let row_metadatas = catalog_helper
+        .scan_row_metadata(table.table_id, &condition, None)
+        .await?;
+
+    let mut inline_row_ids = HashSet::new();
+    let mut locations = Vec::new();
+    for meta in row_metadatas {
+        if replaced.contains_key(&meta.row_id) {
+            continue;
+        }
+        match meta.location {
+            RowLocation::Inline => {
+                inline_row_ids.insert(meta.row_id);
+            }
+            location => locations.push((meta.row_id, location)),
+        }
+    }
+
+    let mut rows = Vec::new();
+    for row_history in replaced.into_values() {
+        match (&row_history.location, &row_history.row_values) {
+            (RowLocation::Inline, Some(row_values)) => {
+                rows.push(decode_row_values(table, row_history.row_id, row_values)?);
+            }
+            (RowLocation::Inline, None) => {
+                return Err(ILError::InternalError(format!(
+                    "Row history of inline row {} has no values",
+                    row_history.row_id
+                )));
+            }
+            (location, _) => locations.push((row_history.row_id, location.clone())),
+        }
+    }
+    if !inline_row_ids.is_empty() {
+        let catalog_schema = Arc::new(CatalogSchema::from_arrow(&table.schema)?);
+        for row in catalog_helper
+            .scan_inline_rows(table.table_id, &catalog_schema, &[], None)
+            .await?
+        {
+            if inline_row_ids.contains(&row.get_row_id()?.expect("row_id is not null")) {
+                rows.push(row);
+            }
+        }
+    }
+    rows.sort_by_key(|row| row.get_row_id().ok().flatten());
+
+    let mut batch = rows_to_record_batch(&table.schema, &rows)?;
+    for filter in &filters {
+        batch = filter_batch(&batch, filter)?;
+    }
+    if let Some(projection) = &projection {
+        batch = batch.project(projection)?;
+    }
+    if let Some(limit) = scan.limit {
+        batch = batch.slice(0, batch.num_rows().min(limit));
+    }
+    let inline_row_count = batch.num_rows();
+    let batch_stream = inline_batch_stream(batch, scan.batch_size);
+    if let Some(limit) = scan.limit
+        && inline_row_count == limit
+    {
+        return Ok(batch_stream);
+    }
+
+    if scan.verify_checksums {
+        verify_scanned_files(&catalog_helper, &table.storage, table.table_id, &locations).await?;
+    }
+    let stream = match scan.limit {
+        // Files are read lazily so that the scan stops reading them once the limit is reached
+        Some(limit) => limit_stream(
+            stream_parquet_files_by_locations(
+                table.storage.clone(),
+                table.schema.clone(),
+                projection,
+                locations,
+                merge_filters(filters),
+                table.field_defaults.clone(),
+                table.field_ids(),
+                scan.batch_size,
+                scan.concurrency,
+            )?,
+            limit - inline_row_count,
+        ),
+        None => {
+            read_parquet_files_by_locations(
+                table.storage.clone(),
+                table.schema.clone(),
+                projection,
+                locations,
+                merge_filters(filters),
+                &table.field_defaults,
+                &table.field_ids(),
+                scan.batch_size,
+            )
+            .await?
+        }
+    };
+
+    Ok(Box::pin(futures::stream::select_all(vec![
+        batch_stream,
+        stream,
+    ])))
+}
+
+fn filter_batch(batch: &RecordBatch, filter: &Expr) -> ILResult<RecordBatch> {
+    let array = filter.eval(batch)?.into_array(batch.num_rows())?;
+    let bool_array = array.as_boolean_opt().ok_or_else(|| {
+        ILError::InternalError(format!(
+            "filter should return BooleanArray, but got {:?}",
+            array.data_type()
+        ))
+    })?;
+    Ok(filter_record_batch(batch, bool_array)?)
+}
+
+/// Ends the stream after `limit` rows. The inner stream is dropped as soon as the limit is
+/// reached, so the files it has yet to read are never opened and reads in flight are aborted.
+pub(crate) fn limit_stream(stream: RecordBatchStream, limit: usize) -> RecordBatchStream {
+    Box::pin(futures::stream::unfold(
+        ((limit > 0).then_some(stream), limit),
+        |(stream, left)| async move {
+            let mut stream = stream?;
+            let (item, left) = match stream.next().await? {
+                Ok(batch) => {
+                    let num_rows = batch.num_rows().min(left);
+                    (Ok(batch.slice(0, num_rows)), left - num_rows)
+                }
+                Err(e) => (Err(e), left),
+            };
+            Some((item, ((left > 0).then_some(stream), left)))
+        },
+    ))
+}
+
+/// Snapshots [`expire_snapshots`] expires, see [`plan_snapshot_expiry`].
+pub(crate) struct SnapshotExpiry {
+    pub(crate) expired_ids: Vec<i64>,
+    /// Expired snapshots deleted, all but the latest expired one.
+    deleted_ids: Vec<i64>,
+    latest_expired_id: i64,
+    /// Row history ended by this snapshot or an earlier one is only read by expired snapshots.
+    pub(crate) oldest_retained_id: i64,
+}
+
+/// Plans the expiry of the snapshots of the table committed before `expire_before` without
+/// changing the catalog, `None` if no snapshot expires. The latest snapshot is never expired.
+pub(crate) async fn plan_snapshot_expiry(
+    tx_helper: &mut TransactionHelper,
+    table_id: i64,
+    expire_before: SystemTime,
+) -> ILResult<Option<SnapshotExpiry>> {
+    let (previously_expired, snapshots): (Vec<_>, Vec<_>) = tx_helper
+        .get_snapshots(table_id)
+        .await?
+        .into_iter()
+        .partition(|snapshot| snapshot.expired);
+    let Some((latest, earlier)) = snapshots.split_last() else {
+        return Ok(None);
+    };
+    let expire_before_ms = timestamp_ms(expire_before);
+    let (expired, retained): (Vec<_>, Vec<_>) = earlier
+        .iter()
+        .partition(|snapshot| snapshot.timestamp_ms < expire_before_ms);
+    let Some((latest_expired, _)) = expired.split_last() else {
+        return Ok(None);
+    };
+    let oldest_retained = retained.first().copied().unwrap_or(latest);
+
+    let expired_ids = expired
+        .iter()
+        .map(|snapshot| snapshot.snapshot_id)
+        .collect::<Vec<_>>();
+    let deleted_ids = previously_expired
+        .iter()
+        .chain(expired.iter().copied())
+        .map(|snapshot| snapshot.snapshot_id)
+        .filter(|snapshot_id| *snapshot_id != latest_expired.snapshot_id)
+        .collect::<Vec<_>>();
+    Ok(Some(SnapshotExpiry {
+        expired_ids,
+        deleted_ids,
+        latest_expired_id: latest_expired.snapshot_id,
+        oldest_retained_id: oldest_retained.snapshot_id,
+    }))
+}
+
+/// Expires the snapshots of the table committed before `expire_before` along with the row
+/// history only they could read, see [`plan_snapshot_expiry`]. The latest expired snapshot is
+/// kept marked expired so that reads of expired snapshots fail with
+/// [`ILError::SnapshotExpired`], the others are deleted. Returns the ids of the expired
+/// snapshots.
+pub(crate) async fn expire_snapshots(
+    tx_helper: &mut TransactionHelper,
+    table_id: i64,
+    expire_before: SystemTime,
+) -> ILResult<Vec<i64>> {
+    let Some(expiry) = plan_snapshot_expiry(tx_helper, table_id, expire_before).await? else {
+        return Ok(Vec::new());
+    };
+    tx_helper
+        .delete_snapshots_by_ids(&expiry.deleted_ids)
+        .await?;
+    tx_helper
+        .mark_snapshot_expired(expiry.latest_expired_id)
+        .await?;
+    tx_helper
+        .delete_row_histories_ended_by(table_id, expiry.oldest_retained_id)
+        .await?;
+    Ok(expiry.expired_ids)
+}
@@ -0,0 +1,341 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::{DefaultHasher, Hasher};
+use std::sync::Arc;
+
+use arrow::array::{Array, ArrayRef};
+use arrow::row::{RowConverter, SortField};
+use futures::TryStreamExt;
+
+use crate::ILResult;
+use crate::catalog::{
+    CatalogHelper, CatalogSchema, ColumnStats, INTERNAL_ROW_ID_FIELD_NAME, RowLocation, Scalar,
+    rows_to_record_batch,
+};
+use crate::expr::{col, lit};
+use crate::storage::Storage;
+use crate::table::{ColumnStatsBuilder, Table, TableScan};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StorageStats {
+    /// Size of every file under the table directory. Inline rows live in the catalog and are
+    /// not counted.
+    pub total_bytes: u64,
+    /// Number of data files holding the rows of the table.
+    pub data_file_count: usize,
+    /// Total size of the data files.
+    pub data_file_bytes: u64,
+    /// Total size of the index files of the data files.
+    pub index_file_bytes: u64,
+    /// `data_file_bytes` divided by `data_file_count`, 0 without data files.
+    pub average_data_file_bytes: u64,
+    /// Bytes a vacuum or compaction could give back: the share of data files taken by deleted
+    /// rows, estimated from their row count, and the files no data file or index file refers
+    /// to anymore.
+    pub deleted_row_bytes: u64,
+}
+
+impl StorageStats {
+    /// Adds up the stats of several tables.
+    pub(crate) fn merge(stats: impl IntoIterator<Item = StorageStats>) -> StorageStats {
+        let mut merged = StorageStats::default();
+        for stats in stats {
+            merged.total_bytes += stats.total_bytes;
+            merged.data_file_count += stats.data_file_count;
+            merged.data_file_bytes += stats.data_file_bytes;
+            merged.index_file_bytes += stats.index_file_bytes;
+            merged.deleted_row_bytes += stats.deleted_row_bytes;
+        }
+        merged.average_data_file_bytes =
+            average_file_bytes(merged.data_file_bytes, merged.data_file_count);
+        merged
+    }
+}
+
+/// Data file sizes come from the catalog. Index files and files left behind by deletes,
+/// updates and compactions have no size in the catalog, they are taken from a listing of the
+/// table directory.
+pub(crate) async fn process_storage_stats(
+    catalog_helper: &CatalogHelper,
+    storage: &Storage,
+    table_dir: &str,
+    table_id: i64,
+) -> ILResult<StorageStats> {
+    let data_files = catalog_helper.get_data_files(table_id).await?;
+    let index_files = catalog_helper.get_index_files(table_id).await?;
+    let non_inline = col("location").neq(lit(RowLocation::Inline.to_string()));
+    let deleted = col("deleted").eq(lit(true));
+    let deleted_rows = catalog_helper
+        .scan_row_metadata(table_id, &non_inline.and(deleted), None)
+        .await?;
+
+    let listed_sizes = storage
+        .list_files(table_dir)
+        .await?
+        .into_iter()
+        .map(|file| (file.relative_path, file.size_bytes))
+        .collect::<HashMap<_, _>>();
+
+    let mut deleted_row_counts = HashMap::new();
+    for row in deleted_rows {
+        if let RowLocation::Parquet { relative_path, .. } = row.location {
+            *deleted_row_counts.entry(relative_path).or_insert(0u64) += 1;
+        }
+    }
+
+    let mut stats = StorageStats {
+        total_bytes: listed_sizes.values().sum(),
+        data_file_count: data_files.len(),
+        ..Default::default()
+    };
+    let mut referenced = HashSet::new();
+    for data_file in data_files.iter() {
+        let file_size_bytes = data_file.file_size_bytes as u64;
+        stats.data_file_bytes += file_size_bytes;
+        if let Some(deleted_count) = deleted_row_counts.get(&data_file.relative_path)
+            && data_file.record_count > 0
+        {
+            stats.deleted_row_bytes +=
+                file_size_bytes * deleted_count / data_file.record_count as u64;
+        }
+        referenced.insert(data_file.relative_path.as_str());
+    }
+    for index_file in index_files.iter() {
+        stats.index_file_bytes += listed_sizes
+            .get(&index_file.relative_path)
+            .copied()
+            .unwrap_or_default();
+        referenced.insert(index_file.relative_path.as_str());
+    }
+    stats.deleted_row_bytes += listed_sizes
+        .iter()
+        .filter(|(relative_path, _)| !referenced.contains(relative_path.as_str()))
+        .map(|(_, size_bytes)| size_bytes)
+        .sum::<u64>();
+    stats.average_data_file_bytes =
+        average_file_bytes(stats.data_file_bytes, stats.data_file_count);
+    Ok(stats)
+}
+
+fn average_file_bytes(total_bytes: u64, file_count: usize) -> u64 {
+    if file_count == 0 {
+        0
+    } else {
+        total_bytes / file_count as u64
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TableStatistics {
+    /// Number of rows not deleted, inline rows included.
+    pub row_count: u64,
+    /// Total size of the data files. Inline rows live in the catalog and are not counted.
+    pub total_bytes: u64,
+    /// Number of data files holding the rows of the table.
+    pub data_file_count: usize,
+    /// Number of rows kept inline in the catalog, not yet dumped into a data file.
+    pub inline_row_count: u64,
+    /// Statistics of every column of the table by column name.
+    pub columns: BTreeMap<String, ColumnStatistics>,
+}
+
+/// Statistics of a column over the data files and inline rows of a table. Deleted rows still
+/// held by data files are counted until the files are compacted, so `min` and `max` bound the
+/// values without being exact.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColumnStatistics {
+    /// Smallest non-null value, `None` when unknown or when all values are null.
+    pub min: Option<Scalar>,
+    /// Largest non-null value, `None` when unknown or when all values are null.
+    pub max: Option<Scalar>,
+    /// Number of nulls, `None` when a data file has no statistics of the column, e.g. one
+    /// written before the column was added.
+    pub null_count: Option<u64>,
+    /// Estimated number of distinct non-null values, only computed by
+    /// [`Table::compute_statistics`].
+    pub distinct_count: Option<u64>,
+}
+
+/// Aggregates the column stats recorded in the catalog for every data file, the data files are
+/// not read. Inline rows are read from the catalog.
+pub(crate) async fn process_table_statistics(table: &Table) -> ILResult<TableStatistics> {
+    let catalog_helper = CatalogHelper::new(table.catalog.clone());
+    let data_files = catalog_helper.get_data_files(table.table_id).await?;
+    let catalog_schema = Arc::new(CatalogSchema::from_arrow(&table.schema)?);
+    let inline_rows = catalog_helper
+        .scan_inline_rows(table.table_id, &catalog_schema, &[], None)
+        .await?;
+    let row_count = catalog_helper.count_undeleted_rows(table.table_id).await? as u64;
+
+    let mut column_stats_builder = ColumnStatsBuilder::new();
+    column_stats_builder.update(&rows_to_record_batch(&table.schema, &inline_rows)?);
+    let inline_stats = column_stats_builder.finish();
+
+    let mut columns = BTreeMap::new();
+    for field in table.schema.fields() {
+        if field.name() == INTERNAL_ROW_ID_FIELD_NAME {
+            continue;
+        }
+        let mut aggregator = ColumnStatsAggregator::default();
+        for data_file in data_files.iter() {
+            let stats = data_file
+                .column_stats
+                .as_ref()
+                .and_then(|column_stats| column_stats.get(field.name()));
+            aggregator.add(stats, data_file.record_count as u64);
+        }
+        if !inline_rows.is_empty() {
+            aggregator.add(inline_stats.get(field.name()), inline_rows.len() as u64);
+        }
+        columns.insert(field.name().clone(), aggregator.finish());
+    }
+
+    Ok(TableStatistics {
+        row_count,
+        total_bytes: data_files
+            .iter()
+            .map(|data_file| data_file.file_size_bytes as u64)
+            .sum(),
+        data_file_count: data_files.len(),
+        inline_row_count: inline_rows.len() as u64,
+        columns,
+    })
+}
+
+/// Scans the rows of the table for exact `min`, `max` and null counts of the columns and for
+/// their distinct counts, estimated with a HyperLogLog sketch per column.
+pub(crate) async fn process_compute_statistics(table: &Table) -> ILResult<TableStatistics> {
+    let mut statistics = process_table_statistics(table).await?;
+
+    let mut stats_builder = ColumnStatsBuilder::new();
+    let mut sketches = BTreeMap::new();
+    let mut row_count = 0;
+    let mut stream = table.scan(TableScan::default()).await?;
+    while let Some(batch) = stream.try_next().await? {
+        row_count += batch.num_rows() as u64;
+        stats_builder.update(&batch);
+        for (field, array) in batch.schema().fields().iter().zip(batch.columns()) {
+            if field.name() == INTERNAL_ROW_ID_FIELD_NAME {
+                continue;
+            }
+            sketches
+                .entry(field.name().clone())
+                .or_insert_with(HyperLogLog::new)
+                .update(array)?;
+        }
+    }
+
+    let scanned_stats = stats_builder.finish();
+    for (name, column) in statistics.columns.iter_mut() {
+        let stats = scanned_stats.get(name);
+        let all_null = stats.is_some_and(|stats| stats.null_count >= row_count);
+        *column = ColumnStatistics {
+            min: stats.and_then(|stats| stats.min.clone()),
+            max: stats.and_then(|stats| stats.max.clone()),
+            null_count: Some(stats.map_or(0, |stats| stats.null_count)),
+            distinct_count: Some(match sketches.get(name) {
+                Some(sketch) if !all_null => sketch.estimate(),
+                _ => 0,
+            }),
+        };
+    }
+    statistics.row_count = row_count;
+    Ok(statistics)
+}
+
+const HLL_PRECISION: u32 = 14;
+const HLL_REGISTERS: usize = 1 << HLL_PRECISION;
+
+/// HyperLogLog sketch of the distinct non-null values of a column, with a standard error
+/// around 0.8%.
+struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    fn new() -> Self {
+        Self {
+            registers: vec![0; HLL_REGISTERS],
+        }
+    }
+
+    fn update(&mut self, array: &ArrayRef) -> ILResult<()> {
+        // The row format encodes values of any type as bytes that are equal for equal values
+        let converter = RowConverter::new(vec![SortField::new(array.data_type().clone())])?;
+        let rows = converter.convert_columns(std::slice::from_ref(array))?;
+        for (idx, row) in rows.iter().enumerate() {
+            if array.is_null(idx) {
+                continue;
+            }
+            let mut hasher = DefaultHasher::new();
+            hasher.write(row.as_ref());
+            let hash = hasher.finish();
+            let register = (hash >> (64 - HLL_PRECISION)) as usize;
+            let rank = ((hash << HLL_PRECISION) | (1 << (HLL_PRECISION - 1))).leading_zeros() + 1;
+            self.registers[register] = self.registers[register].max(rank as u8);
+        }
+        Ok(())
+    }
+
+    fn estimate(&self) -> u64 {
+        let m = HLL_REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum = self
+            .registers
+            .iter()
+            .map(|register| 2f64.powi(-(*register as i32)))
+            .sum::<f64>();
+        let estimate = alpha * m * m / sum;
+        let empty_registers = self.registers.iter().filter(|r| **r == 0).count();
+        // Linear counting is more accurate for small cardinalities
+        if estimate <= 2.5 * m && empty_registers > 0 {
+            (m * (m / empty_registers as f64).ln()).round() as u64
+        } else {
+            estimate.round() as u64
+        }
+    }
+}
+
+#[derive(Default)]
+struct ColumnStatsAggregator {
+    stats: ColumnStatistics,
+    null_count: u64,
+    /// Whether some non-null values are not bound by `min` and `max`.
+    unbounded: bool,
+    /// Whether some data file had no statistics of the column.
+    incomplete: bool,
+}
+
+impl ColumnStatsAggregator {
+    /// Adds the stats of `record_count` rows, `None` if they are unknown.
+    fn add(&mut self, stats: Option<&ColumnStats>, record_count: u64) {
+        let Some(stats) = stats else {
+            self.incomplete = true;
+            self.unbounded = true;
+            return;
+        };
+        self.null_count += stats.null_count;
+        if stats.null_count >= record_count || self.unbounded {
+            return;
+        }
+        match (&stats.min, &stats.max) {
+            (Some(min), Some(max)) => {
+                if self.stats.min.as_ref().is_none_or(|current| min < current) {
+                    self.stats.min = Some(min.clone());
+                }
+                if self.stats.max.as_ref().is_none_or(|current| max > current) {
+                    self.stats.max = Some(max.clone());
+                }
+            }
+            _ => self.unbounded = true,
+        }
+    }
+
+    fn finish(mut self) -> ColumnStatistics {
+        if self.unbounded {
+            self.stats.min = None;
+            self.stats.max = None;
+        }
+        self.stats.null_count = (!self.incomplete).then_some(self.null_count);
+        self.stats
+    }
+}
@@ -0,0 +1,61 @@
+use std::collections::HashSet;
+
+use log::debug;
+
+use crate::catalog::TransactionHelper;
+use crate::table::{Table, build_index_files};
+use crate::{ILError, ILResult};
+
+/// Builds an index file for every data file and index of the table lacking one, like data
+/// files written with [`TableConfig::deferred_indexing`](crate::table::TableConfig::deferred_indexing),
+/// within the transaction of `tx_helper`. Returns the number of index files written.
+pub(crate) async fn process_sync_indexes(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+) -> ILResult<usize> {
+    let data_files = tx_helper.get_data_files(table.table_id).await?;
+    let indexed = tx_helper
+        .get_index_files(table.table_id)
+        .await?
+        .into_iter()
+        .map(|index_file| (index_file.data_file_id, index_file.index_id))
+        .collect::<HashSet<_>>();
+
+    let mut written = 0;
+    for index_def in table.indexes.values() {
+        let index = table.index_kinds.get(&index_def.kind).ok_or_else(|| {
+            ILError::InternalError(format!("Index kind {} not found", index_def.kind))
+        })?;
+        let unindexed = data_files
+            .iter()
+            .filter(|data_file| !indexed.contains(&(data_file.data_file_id, index_def.index_id)))
+            .cloned()
+            .collect::<Vec<_>>();
+        if !unindexed.is_empty() {
+            written +=
+                build_index_files(tx_helper, table, index_def, index.as_ref(), unindexed).await?;
+        }
+    }
+    Ok(written)
+}
+
+/// Syncs the indexes of the table in the background after a dump. Skipped while another dump
+/// or sync of the table is running, the data files are left to the next sync.
+pub(crate) async fn run_index_sync_task(table: &Table) -> ILResult<()> {
+    let mut tx_helper = TransactionHelper::new(&table.catalog).await?;
+    if !tx_helper.try_insert_dump_task(table.table_id).await? {
+        debug!(
+            "Table {} already has a dump task, skip index sync",
+            table.table_id
+        );
+        return Ok(());
+    }
+    let written = process_sync_indexes(&mut tx_helper, table).await?;
+    tx_helper.delete_dump_task(table.table_id).await?;
+    tx_helper.commit().await?;
+    debug!(
+        "Synced indexes of table {}: {written} index files",
+        table.table_id
+    );
+    Ok(())
+}
@@ -0,0 +1,492 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use arrow::array::{Int64Array, RecordBatch};
+use arrow::datatypes::Schema;
+use log::{debug, error, warn};
+
+use crate::catalog::{
+    Catalog, ColumnStats, DataFileRecord, INTERNAL_ROW_ID_FIELD_NAME, IndexFileRecord, RetryPolicy,
+    Scalar, TransactionHelper, check_writable,
+};
+use crate::storage::{Storage, write_parquet_file};
+use crate::table::{
+    ColumnStatsBuilder, SnapshotOperation, Table, commit_snapshot, group_batch_by_partition,
+    insert_unique_keys, parquet_row_metadatas, table_index_builders, unique_keys,
+};
+use crate::utils::record_batch_with_row_id;
+use crate::{ILError, ILResult};
+
+/// Write transaction over several tables of a client, started by [`crate::LakeClient::begin`].
+///
+/// Inserted rows are written right away into staged data files under the directories of their
+/// tables, a file per insert and partition. The staged files are only registered, with their
+/// rows, index files and a snapshot per table, by [`LakeTransaction::commit`] in a single
+/// catalog transaction, so either all tables get their rows or none does.
+///
+/// [`LakeTransaction::rollback`] and dropping the transaction without committing leave nothing
+/// in the catalog and delete the staged files, files whose deletion fails are left to vacuum.
+pub struct LakeTransaction {
+    catalog: Arc<dyn Catalog>,
+    options: TransactionOptions,
+    tables: Vec<StagedTable>,
+    finished: bool,
+}
+
+/// Options of a [`LakeTransaction`], see [`crate::LakeClient::begin_with_options`].
+#[derive(Debug, Clone, derive_with::With)]
+pub struct TransactionOptions {
+    /// Attempts of the commit when it is aborted by a concurrent catalog transaction, including
+    /// the first one, and the delays between them.
+    pub commit_retry: RetryPolicy,
+}
+
+impl Default for TransactionOptions {
+    fn default() -> Self {
+        Self {
+            commit_retry: RetryPolicy::new(10, Duration::from_millis(10), Duration::from_secs(1)),
+        }
+    }
+}
+
+struct StagedTable {
+    table: Table,
+    /// Snapshot of the table when the transaction first wrote to it, the commit is rebased on
+    /// later snapshots.
+    base_snapshot_id: i64,
+    files: Vec<StagedFile>,
+    /// Index files of the staged files, written ahead of the catalog transaction of the commit.
+    index_files: Option<StagedIndexFiles>,
+}
+
+/// Index files of the staged files of a table, a list per staged file, built for the row ids
+/// the commit gives from `first_row_id` on.
+struct StagedIndexFiles {
+    first_row_id: i64,
+    files: Vec<Vec<StagedIndexFile>>,
+}
+
+struct StagedIndexFile {
+    index_id: i64,
+    relative_path: String,
+    metadata: Option<Vec<u8>>,
+}
+
+struct StagedFile {
+    relative_path: String,
+    partition_values: Option<Vec<Scalar>>,
+    file_size_bytes: usize,
+    checksum: u32,
+    record_count: usize,
+    row_group_num_rows: Vec<usize>,
+    column_stats: BTreeMap<String, ColumnStats>,
+    /// Rows of the file, kept for tables whose unique keys and indexes need the row ids given
+    /// at commit.
+    record: Option<RecordBatch>,
+}
+
+impl LakeTransaction {
+    pub(crate) fn new(catalog: Arc<dyn Catalog>, options: TransactionOptions) -> Self {
+        Self {
+            catalog,
+            options,
+            tables: Vec::new(),
+            finished: false,
+        }
+    }
+
+    /// Checks the rows of `record` like [`Table::insert`] does and writes them into staged data
+    /// files of the table.
+    pub async fn insert(&mut self, table: &Table, record: &RecordBatch) -> ILResult<()> {
+        if !Arc::ptr_eq(&table.catalog, &self.catalog) {
+            return Err(ILError::InvalidInput(format!(
+                "Table {} belongs to another catalog than the transaction",
+                table.table_name
+            )));
+        }
+        check_writable(&table.catalog)?;
+        let record = table.conform_record(record)?;
+        if record.num_rows() == 0 {
+            return Ok(());
+        }
+
+        let idx = match self
+            .tables
+            .iter()
+            .position(|staged| staged.table.table_id == table.table_id)
+        {
+            Some(idx) => idx,
+            None => {
+                let base_snapshot_id = table.current_snapshot_id().await?;
+                self.tables.push(StagedTable {
+                    table: table.clone(),
+                    base_snapshot_id,
+                    files: Vec::new(),
+                    index_files: None,
+                });
+                self.tables.len() - 1
+            }
+        };
+        for (partition_values, batch) in group_batch_by_partition(table, &record)? {
+            let staged_file = write_staged_file(table, partition_values, batch).await?;
+            debug!(
+                "Staged {} rows of table {} in {}",
+                staged_file.record_count, table.table_id, staged_file.relative_path
+            );
+            self.tables[idx].files.push(staged_file);
+        }
+        Ok(())
+    }
+
+    /// Registers the staged files of all tables in one catalog transaction, recording a snapshot
+    /// per table. The staged files are immutable, so commits of other writers since the
+    /// transaction first wrote to a table do not fail the commit, the rows are appended on top of
+    /// the current snapshot. Transactions aborted by a concurrent catalog transaction are re-run
+    /// per [`TransactionOptions::commit_retry`], re-reading the state of the tables.
+    ///
+    /// Real conflicts still fail the commit, committing nothing: [`ILError::SchemaConflict`] if
+    /// the schema of a table changed since its handle was loaded, [`ILError::ConstraintViolation`]
+    /// if a concurrent writer took a unique key, and [`ILError::CommitConflict`] for handles with
+    /// a [base snapshot](Table::with_base_snapshot). The staged files of a failed commit are
+    /// deleted.
+    ///
+    /// Index files are written before the catalog transaction for the row ids the commit is
+    /// expected to give, and only rebuilt when a concurrent writer took these row ids.
+    pub async fn commit(mut self) -> ILResult<()> {
+        self.finished = true;
+        let result = self.commit_with_retry().await;
+        if result.is_err() {
+            delete_staged_files(self.staged_files()).await;
+        }
+        result
+    }
+
+    async fn commit_with_retry(&mut self) -> ILResult<()> {
+        check_writable(&self.catalog)?;
+        let policy = self.options.commit_retry.clone();
+        let mut attempt = 1;
+        loop {
+            let result = self.commit_once().await;
+            match result {
+                Err(e) if e.is_conflict() && attempt < policy.max_attempts => {
+                    let delay = policy.delay(attempt);
+                    warn!(
+                        "transaction commit failed on attempt {attempt}/{}, retrying in {delay:?}: {e}",
+                        policy.max_attempts
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn commit_once(&mut self) -> ILResult<()> {
+        for staged in self.tables.iter_mut() {
+            prepare_index_files(&self.catalog, staged).await?;
+        }
+        let mut tx_helper = TransactionHelper::new(&self.catalog).await?;
+        commit_staged_tables(&mut tx_helper, &self.tables).await
+    }
+
+    /// Discards the transaction, deleting its staged files.
+    pub async fn rollback(mut self) {
+        self.finished = true;
+        delete_staged_files(self.staged_files()).await;
+    }
+
+    fn staged_files(&self) -> Vec<(Arc<Storage>, String)> {
+        self.tables
+            .iter()
+            .flat_map(|staged| {
+                let index_paths = staged
+                    .index_files
+                    .iter()
+                    .flat_map(|index_files| index_files.files.iter().flatten())
+                    .map(|file| &file.relative_path);
+                staged
+                    .files
+                    .iter()
+                    .map(|file| &file.relative_path)
+                    .chain(index_paths)
+                    .map(|relative_path| (staged.table.storage.clone(), relative_path.clone()))
+            })
+            .collect()
+    }
+}
+
+impl Drop for LakeTransaction {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        let staged_files = self.staged_files();
+        if staged_files.is_empty() {
+            return;
+        }
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(delete_staged_files(staged_files));
+            }
+            Err(_) => debug!(
+                "Transaction dropped outside of a runtime, {} staged files are left to vacuum",
+                staged_files.len()
+            ),
+        }
+    }
+}
+
+async fn delete_staged_files(staged_files: Vec<(Arc<Storage>, String)>) {
+    for (storage, relative_path) in staged_files {
+        if let Err(e) = storage.delete(&relative_path).await {
+            error!("Failed to delete staged file {relative_path}: {e:?}");
+        }
+    }
+}
+
+/// Writes the rows of `record`, all of one partition, into a new staged data file. The file
+/// lacks the row id column, the ids given at commit are kept by the catalog.
+async fn write_staged_file(
+    table: &Table,
+    partition_values: Option<Vec<Scalar>>,
+    record: RecordBatch,
+) -> ILResult<StagedFile> {
+    let relative_path = format!(
+        "{}/staged-{}.parquet",
+        table.table_dir(),
+        uuid::Uuid::new_v4()
+    );
+    let data_file_schema = table.data_file_schema();
+    let fields = data_file_schema
+        .fields()
+        .iter()
+        .filter(|field| field.name() != INTERNAL_ROW_ID_FIELD_NAME)
+        .cloned()
+        .collect::<Vec<_>>();
+    let file_schema = Arc::new(Schema::new_with_metadata(
+        fields,
+        data_file_schema.metadata().clone(),
+    ));
+
+    let ((), file_size_bytes, checksum) = write_parquet_file(
+        &table.storage,
+        &table.config,
+        &relative_path,
+        file_schema,
+        async |arrow_writer| Ok(arrow_writer.write(&record).await?),
+    )
+    .await?;
+
+    let mut column_stats_builder = ColumnStatsBuilder::new();
+    column_stats_builder.update(&record);
+    let row_group_size = table.config.parquet_row_group_size;
+    let record_count = record.num_rows();
+    let row_group_num_rows = (0..record_count.div_ceil(row_group_size))
+        .map(|i| row_group_size.min(record_count - i * row_group_size))
+        .collect();
+    // With deferred indexing the data file is indexed when the indexes are synced
+    let keeps_record = !table.config.unique_constraints.is_empty()
+        || (!table.indexes.is_empty() && !table.config.deferred_indexing);
+    Ok(StagedFile {
+        relative_path,
+        partition_values,
+        file_size_bytes,
+        checksum,
+        record_count,
+        row_group_num_rows,
+        column_stats: column_stats_builder.finish(),
+        record: keeps_record.then_some(record),
+    })
+}
+
+/// Registers the staged files of all tables and commits the catalog transaction. Tables whose
+/// schema changed since their handle was loaded fail the commit.
+async fn commit_staged_tables(
+    tx_helper: &mut TransactionHelper,
+    tables: &[StagedTable],
+) -> ILResult<()> {
+    for staged in tables {
+        let table = &staged.table;
+        let schema_version = tx_helper.get_table_schema_version(table.table_id).await?;
+        if schema_version != table.schema_version {
+            return Err(ILError::SchemaConflict(format!(
+                "table {} is at schema version {schema_version}, its rows were written at schema version {}",
+                table.table_name, table.schema_version
+            )));
+        }
+        let current_snapshot_id = tx_helper.get_current_snapshot_id(table.table_id).await?;
+        if current_snapshot_id != staged.base_snapshot_id {
+            debug!(
+                "Rebase staged files of table {} from snapshot {} onto snapshot {current_snapshot_id}",
+                table.table_id, staged.base_snapshot_id
+            );
+        }
+    }
+    for staged in tables {
+        register_staged_files(tx_helper, staged).await?;
+    }
+    tx_helper.commit().await
+}
+
+/// Registers the staged files of a table as data files with new row ids, their unique keys and
+/// index files, then commits a snapshot of the table.
+async fn register_staged_files(
+    tx_helper: &mut TransactionHelper,
+    staged: &StagedTable,
+) -> ILResult<()> {
+    let table = &staged.table;
+    let snapshot_id = tx_helper.get_max_snapshot_id().await? + 1;
+    let mut next_row_id = tx_helper.get_max_row_id(table.table_id).await? + 1;
+    if let Some(index_files) = &staged.index_files
+        && index_files.first_row_id != next_row_id
+    {
+        return Err(ILError::CatalogConflict(format!(
+            "rows of table {} were inserted concurrently, its index files are built for row ids from {} on, the commit gives row ids from {next_row_id} on",
+            table.table_name, index_files.first_row_id
+        )));
+    }
+    for (file_idx, file) in staged.files.iter().enumerate() {
+        let row_ids = (next_row_id..next_row_id + file.record_count as i64).collect::<Vec<_>>();
+        next_row_id += file.record_count as i64;
+        let data_file_id = tx_helper.get_max_data_file_id().await? + 1;
+
+        if let Some(record) = &file.record {
+            let keys = unique_keys(table, record, &row_ids, false)?;
+            insert_unique_keys(tx_helper, table, &keys).await?;
+        }
+
+        let row_metadatas = parquet_row_metadatas(
+            &file.relative_path,
+            &row_ids,
+            file.row_group_num_rows.clone(),
+        );
+        tx_helper
+            .insert_data_files(
+                &[DataFileRecord {
+                    data_file_id,
+                    table_id: table.table_id,
+                    relative_path: file.relative_path.clone(),
+                    file_size_bytes: file.file_size_bytes as i64,
+                    record_count: file.record_count as i64,
+                    row_ids,
+                    partition_values: file.partition_values.clone(),
+                    checksum: Some(file.checksum),
+                    column_stats: Some(file.column_stats.clone()),
+                }],
+                table.config.catalog_insert_batch_size,
+            )
+            .await?;
+        if let Some(index_files) = &staged.index_files {
+            let mut index_file_id = tx_helper.get_max_index_file_id().await? + 1;
+            let mut index_file_records = Vec::new();
+            for index_file in &index_files.files[file_idx] {
+                index_file_records.push(IndexFileRecord {
+                    index_file_id,
+                    index_id: index_file.index_id,
+                    data_file_id,
+                    relative_path: index_file.relative_path.clone(),
+                    metadata: index_file.metadata.clone(),
+                });
+                index_file_id += 1;
+            }
+            tx_helper.insert_index_files(&index_file_records).await?;
+        }
+        tx_helper
+            .insert_row_metadatas(
+                table.table_id,
+                &row_metadatas,
+                table.config.catalog_insert_batch_size,
+            )
+            .await?;
+    }
+    commit_snapshot(tx_helper, table, snapshot_id, SnapshotOperation::Insert).await
+}
+
+/// Writes the index files of the staged files of the table under new paths, unless the index
+/// files written for an earlier attempt are built for the row ids the commit gives.
+async fn prepare_index_files(catalog: &Arc<dyn Catalog>, staged: &mut StagedTable) -> ILResult<()> {
+    let table = &staged.table;
+    if table_index_builders(table)?.is_empty() {
+        return Ok(());
+    }
+    let mut tx_helper = TransactionHelper::new(catalog).await?;
+    let first_row_id = tx_helper.get_max_row_id(table.table_id).await? + 1;
+    tx_helper.commit().await?;
+    if let Some(index_files) = staged.index_files.take() {
+        if index_files.first_row_id == first_row_id {
+            staged.index_files = Some(index_files);
+            return Ok(());
+        }
+        debug!(
+            "Rebuild index files of table {} for row ids from {first_row_id} on",
+            table.table_id
+        );
+        delete_staged_files(index_file_paths(table, index_files.files)).await;
+    }
+
+    let mut files = Vec::with_capacity(staged.files.len());
+    let mut next_row_id = first_row_id;
+    for file in &staged.files {
+        let row_ids = (next_row_id..next_row_id + file.record_count as i64).collect::<Vec<_>>();
+        next_row_id += file.record_count as i64;
+        let mut file_index_files = Vec::new();
+        let result = write_staged_index_files(table, file, row_ids, &mut file_index_files).await;
+        files.push(file_index_files);
+        if let Err(e) = result {
+            delete_staged_files(index_file_paths(table, files)).await;
+            return Err(e);
+        }
+    }
+    staged.index_files = Some(StagedIndexFiles {
+        first_row_id,
+        files,
+    });
+    Ok(())
+}
+
+/// Writes the index files of a staged file whose rows get `row_ids` into `index_files`, which
+/// also lists the files written before a failure.
+async fn write_staged_index_files(
+    table: &Table,
+    file: &StagedFile,
+    row_ids: Vec<i64>,
+    index_files: &mut Vec<StagedIndexFile>,
+) -> ILResult<()> {
+    let Some(record) = &file.record else {
+        return Ok(());
+    };
+    let record = record_batch_with_row_id(record, Int64Array::from(row_ids))?;
+    for (index_name, mut index_builder) in table_index_builders(table)? {
+        let index_def = &table.indexes[&index_name];
+        index_builder.update(&index_def.indexed_rows(&record)?)?;
+        let relative_path = format!(
+            "{}/staged-{}.index",
+            table.table_dir(),
+            uuid::Uuid::new_v4()
+        );
+        let output_file = table.storage.create_file(&relative_path).await?;
+        index_files.push(StagedIndexFile {
+            index_id: index_def.index_id,
+            relative_path,
+            metadata: None,
+        });
+        index_builder.write(output_file).await?;
+        if let Some(index_file) = index_files.last_mut() {
+            index_file.metadata = index_builder.file_metadata()?;
+        }
+    }
+    Ok(())
+}
+
+fn index_file_paths(
+    table: &Table,
+    index_files: Vec<Vec<StagedIndexFile>>,
+) -> Vec<(Arc<Storage>, String)> {
+    index_files
+        .into_iter()
+        .flatten()
+        .map(|file| (table.storage.clone(), file.relative_path))
+        .collect()
+}
@@ -1,16 +1,44 @@
 use crate::ILResult;
 use crate::catalog::TransactionHelper;
+use crate::expr::lit;
+use crate::table::{Table, record_replaced_rows};
 
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TruncateReport {
+    pub removed_rows: u64,
+    /// Size of the data files dropped from the table. They stay in storage for earlier
+    /// snapshots until vacuumed.
+    pub removed_bytes: u64,
+}
+
+/// Deletes all rows of the table by dropping its row metadata, inline rows, data files and index
+/// files from the catalog, without reading the files. Index definitions are kept.
 pub(crate) async fn process_truncate(
     tx_helper: &mut TransactionHelper,
-    table_id: i64,
-) -> ILResult<()> {
+    table: &Table,
+    snapshot_id: i64,
+) -> ILResult<TruncateReport> {
+    let table_id = table.table_id;
+    // Earlier snapshots keep reading the rows from the row history
+    let removed_rows = record_replaced_rows(tx_helper, table, lit(true), snapshot_id).await?;
+    let removed_bytes = tx_helper
+        .get_data_files(table_id)
+        .await?
+        .iter()
+        .map(|data_file| data_file.file_size_bytes as u64)
+        .sum();
+
     tx_helper.truncate_row_metadata_table(table_id).await?;
     tx_helper.truncate_inline_row_table(table_id).await?;
+    if !table.config.unique_constraints.is_empty() {
+        tx_helper.truncate_unique_key_table(table_id).await?;
+    }
 
+    tx_helper.delete_all_index_files(table_id).await?;
     tx_helper.delete_all_data_files(table_id).await?;
 
-    // TODO clean up index data
-
-    Ok(())
+    Ok(TruncateReport {
+        removed_rows: removed_rows as u64,
+        removed_bytes,
+    })
 }
@@ -0,0 +1,249 @@
+use std::collections::HashSet;
+
+use arrow::array::RecordBatch;
+use arrow::datatypes::Schema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    ConstraintViolation, ILError, ILResult,
+    catalog::{INTERNAL_ROW_ID_FIELD_NAME, Scalar, TransactionHelper},
+    table::{
+        CheckConstraint, NOT_NULL_CONSTRAINT, Table, has_null_key, key_arrays, row_ids_of_batch,
+    },
+    utils::has_duplicated_items,
+};
+
+/// Columns no two rows of the table may hold the same values of. Declared in
+/// [`TableConfig::unique_constraints`](crate::table::TableConfig::unique_constraints) at creation.
+///
+/// The keys of the rows are kept in a catalog table whose primary key rejects a key held by
+/// another row within the writing transaction, so of two concurrent writes of the same key only
+/// one commits. Rows with a null in any of the columns never conflict, as in SQL.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UniqueConstraint {
+    pub name: String,
+    pub columns: Vec<String>,
+}
+
+impl UniqueConstraint {
+    pub fn new(name: impl Into<String>, columns: Vec<String>) -> Self {
+        Self {
+            name: name.into(),
+            columns,
+        }
+    }
+}
+
+/// Checks the unique constraints have names unique among all constraints of the table and
+/// refer to columns of `schema` at most once.
+pub(crate) fn check_unique_constraint_definitions(
+    schema: &Schema,
+    constraints: &[UniqueConstraint],
+    check_constraints: &[CheckConstraint],
+) -> ILResult<()> {
+    let names = constraints
+        .iter()
+        .map(|constraint| &constraint.name)
+        .chain(check_constraints.iter().map(|constraint| &constraint.name));
+    if has_duplicated_items(names) {
+        return Err(ILError::InvalidInput(
+            "Duplicated constraint names".to_string(),
+        ));
+    }
+    for constraint in constraints {
+        if constraint.name.is_empty() || constraint.name == NOT_NULL_CONSTRAINT {
+            return Err(ILError::InvalidInput(format!(
+                "Invalid unique constraint name {:?}",
+                constraint.name
+            )));
+        }
+        if constraint.columns.is_empty() {
+            return Err(ILError::InvalidInput(format!(
+                "Unique constraint {} has no columns",
+                constraint.name
+            )));
+        }
+        if has_duplicated_items(constraint.columns.iter()) {
+            return Err(ILError::InvalidInput(format!(
+                "Duplicated columns in unique constraint {}",
+                constraint.name
+            )));
+        }
+        for column in constraint.columns.iter() {
+            let field = schema
+                .field_with_name(column)
+                .ok()
+                .filter(|_| column != INTERNAL_ROW_ID_FIELD_NAME)
+                .ok_or_else(|| {
+                    ILError::InvalidInput(format!(
+                        "Unique constraint {} refers to unknown column {column}",
+                        constraint.name
+                    ))
+                })?;
+            Scalar::try_new_null(field.data_type()).map_err(|_| {
+                ILError::InvalidInput(format!(
+                    "Unique constraint {} does not support column {column} of type {}",
+                    constraint.name,
+                    field.data_type()
+                ))
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Key of a unique constraint held by a written row.
+pub(crate) struct UniqueKey {
+    constraint: usize,
+    /// Hex encoded SHA-256 of the constraint name and the key values, the key of the row in the
+    /// catalog.
+    hash: String,
+    values: Vec<Scalar>,
+    row_index: usize,
+    row_id: i64,
+    /// Whether the row had its id before the write, the id is reported in violations then.
+    existing_row: bool,
+}
+
+/// Keys of the rows of `record` for all unique constraints of the table. Rows with a null key
+/// value have none for the constraint.
+pub(crate) fn unique_keys(
+    table: &Table,
+    record: &RecordBatch,
+    row_ids: &[i64],
+    existing_rows: bool,
+) -> ILResult<Vec<UniqueKey>> {
+    let mut keys = Vec::new();
+    for (constraint_idx, constraint) in table.config.unique_constraints.iter().enumerate() {
+        let key_arrays = key_arrays(record, &constraint.columns)?;
+        for (row_index, row_id) in row_ids.iter().enumerate() {
+            if has_null_key(&key_arrays, row_index) {
+                continue;
+            }
+            let values = key_arrays
+                .iter()
+                .map(|array| Scalar::try_from_array(array, row_index))
+                .collect::<ILResult<Vec<_>>>()?;
+            let encoded = serde_json::to_vec(&(&constraint.name, &values)).map_err(|e| {
+                ILError::InternalError(format!("Failed to serialize unique key: {e:?}"))
+            })?;
+            keys.push(UniqueKey {
+                constraint: constraint_idx,
+                hash: hex::encode(Sha256::digest(&encoded)),
+                values,
+                row_index,
+                row_id: *row_id,
+                existing_row: existing_rows,
+            });
+        }
+    }
+    Ok(keys)
+}
+
+/// Records the keys in the catalog, failing on the first key held by another row, written
+/// along or committed before.
+pub(crate) async fn insert_unique_keys(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    keys: &[UniqueKey],
+) -> ILResult<()> {
+    if keys.is_empty() {
+        return Ok(());
+    }
+    let mut written = HashSet::with_capacity(keys.len());
+    for key in keys {
+        if !written.insert(key.hash.as_str()) {
+            return Err(violation(table, key));
+        }
+    }
+
+    let entries = keys
+        .iter()
+        .map(|key| (key.hash.clone(), key.row_id))
+        .collect::<Vec<_>>();
+    let inserted = tx_helper
+        .insert_unique_keys(
+            table.table_id,
+            &entries,
+            table.config.catalog_insert_batch_size,
+        )
+        .await?;
+    if inserted == keys.len() {
+        return Ok(());
+    }
+
+    let hashes = keys.iter().map(|key| key.hash.as_str()).collect::<Vec<_>>();
+    let holders = tx_helper
+        .get_unique_key_row_ids(table.table_id, &hashes)
+        .await?;
+    match keys.iter().find(|key| {
+        holders
+            .get(&key.hash)
+            .is_some_and(|row_id| *row_id != key.row_id)
+    }) {
+        Some(key) => Err(violation(table, key)),
+        // The row holding the key was deleted after the insert skipped it
+        None => Err(ILError::CatalogConflict(format!(
+            "Unique keys of table {} changed by a concurrent transaction",
+            table.table_name
+        ))),
+    }
+}
+
+/// Removes the keys of the rows from the catalog.
+pub(crate) async fn delete_unique_keys(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    row_ids: &[i64],
+) -> ILResult<()> {
+    if !table.config.unique_constraints.is_empty() {
+        tx_helper
+            .delete_unique_keys_by_row_ids(table.table_id, row_ids)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Whether assigning `columns` may change keys of the unique constraints.
+pub(crate) fn has_unique_column<'a>(
+    table: &Table,
+    mut columns: impl Iterator<Item = &'a String>,
+) -> bool {
+    columns.any(|name| {
+        table
+            .config
+            .unique_constraints
+            .iter()
+            .any(|constraint| constraint.columns.contains(name))
+    })
+}
+
+/// Replaces the keys of updated rows with the keys of their new versions in `batches`, which
+/// hold the row ids in their first column.
+pub(crate) async fn replace_unique_keys(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    batches: &[RecordBatch],
+) -> ILResult<()> {
+    let mut row_ids = Vec::new();
+    let mut keys = Vec::new();
+    for batch in batches {
+        let batch_row_ids = row_ids_of_batch(batch)?;
+        keys.extend(unique_keys(table, batch, &batch_row_ids, true)?);
+        row_ids.extend(batch_row_ids);
+    }
+    delete_unique_keys(tx_helper, table, &row_ids).await?;
+    insert_unique_keys(tx_helper, table, &keys).await
+}
+
+fn violation(table: &Table, key: &UniqueKey) -> ILError {
+    let constraint = &table.config.unique_constraints[key.constraint];
+    ILError::ConstraintViolation(ConstraintViolation {
+        constraint: constraint.name.clone(),
+        columns: constraint.columns.clone(),
+        row_index: key.row_index,
+        row_id: key.existing_row.then_some(key.row_id),
+        values: key.values.clone(),
+    })
+}
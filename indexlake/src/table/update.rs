@@ -1,115 +1,139 @@
-use std::{collections::HashMap, sync::Arc};
-
-use arrow::{
-    array::{AsArray, Int64Array, RecordBatch, RecordBatchOptions},
-    datatypes::{Int64Type, SchemaRef},
-};
+use arrow::array::{RecordBatch, RecordBatchOptions};
 use futures::StreamExt;
 
 use crate::{
     ILError, ILResult,
-    catalog::{INTERNAL_ROW_ID_FIELD_NAME, RowLocation, Scalar, TransactionHelper},
-    expr::{Expr, visited_columns},
-    storage::{Storage, read_parquet_files_by_locations},
-    table::process_insert_into_inline_rows,
+    catalog::{INTERNAL_ROW_ID_FIELD_NAME, TransactionHelper},
+    expr::{Expr, col, lit},
+    table::{
+        Table, enforce_check_constraints, enforce_not_null, find_matched_inline_rows,
+        has_unique_column, process_insert_into_inline_rows, read_matched_data_file_rows,
+        record_replaced_rows, replace_unique_keys, row_ids_of_batch,
+    },
+    utils::has_duplicated_items,
 };
 
-pub(crate) async fn process_update(
-    tx_helper: &mut TransactionHelper,
-    storage: Arc<Storage>,
-    table_id: i64,
-    table_schema: &SchemaRef,
-    set_map: HashMap<String, Scalar>,
-    condition: &Expr,
+/// Checks that the assigned columns exist, are assigned once and get values of their type.
+pub(crate) fn check_update_assignments(
+    table: &Table,
+    assignments: &[(String, Expr)],
 ) -> ILResult<()> {
-    let mut row_metadata_condition =
-        Expr::Column("deleted".to_string()).eq(Expr::Literal(Scalar::Boolean(Some(false))));
-    if visited_columns(condition) == vec![INTERNAL_ROW_ID_FIELD_NAME] {
-        row_metadata_condition = row_metadata_condition.and(condition.clone());
+    if has_duplicated_items(assignments.iter().map(|(name, _)| name)) {
+        return Err(ILError::InvalidInput(format!(
+            "Duplicated columns in update assignments {:?}",
+            assignments
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>()
+        )));
     }
+    for (name, expr) in assignments {
+        if name == INTERNAL_ROW_ID_FIELD_NAME {
+            return Err(ILError::InvalidInput(format!(
+                "Column {name} can not be updated"
+            )));
+        }
+        let field = table.schema.field_with_name(name).map_err(|_| {
+            ILError::InvalidInput(format!("Column {name} not found in table schema"))
+        })?;
+        let data_type = expr.data_type(&table.schema)?;
+        if &data_type != field.data_type() {
+            return Err(ILError::InvalidInput(format!(
+                "Assignment to column {name} of type {} has type {data_type}",
+                field.data_type()
+            )));
+        }
+    }
+    Ok(())
+}
 
-    let row_metadatas = tx_helper
-        .scan_row_metadata(table_id, &row_metadata_condition)
-        .await?;
-    let data_file_locations = row_metadatas
-        .into_iter()
-        .filter(|meta| matches!(meta.location, RowLocation::Parquet { .. }))
-        .map(|meta| meta.location)
-        .collect::<Vec<_>>();
+/// Updates the rows matching `condition` with the values of `assignments` evaluated on their
+/// current values, keeping their current version as row history ending at `snapshot_id`. The
+/// updated rows become inline rows, index entries of rows moved out of data files are ignored
+/// through their row metadata. Returns the number of rows updated.
+pub(crate) async fn process_update(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    assignments: &[(String, Expr)],
+    condition: &Expr,
+    snapshot_id: i64,
+) -> ILResult<usize> {
+    let table_id = table.table_id;
+    let inline_batch =
+        find_matched_inline_rows(tx_helper, table_id, &table.schema, condition).await?;
+    let inline_row_ids = row_ids_of_batch(&inline_batch)?;
+    let updated_inline_batch = update_record_batch(table, &inline_batch, assignments)?;
+    // New versions of the rows are kept to replace their unique keys only if those may change
+    let replaces_unique_keys = has_unique_column(table, assignments.iter().map(|(name, _)| name));
+    let mut updated_batches = vec![updated_inline_batch.clone()];
 
-    let mut updated_row_ids = Vec::new();
-    let mut stream = read_parquet_files_by_locations(
-        storage,
-        table_schema.clone(),
-        None,
-        data_file_locations,
-        Some(condition.clone()),
-    )
-    .await?;
+    let mut moved_row_ids = Vec::new();
+    let mut stream = read_matched_data_file_rows(tx_helper, table, condition).await?;
     while let Some(batch) = stream.next().await {
         let batch = batch?;
-        let array = condition.eval(&batch)?.into_array(batch.num_rows())?;
-        let bool_array = array.as_boolean_opt().ok_or_else(|| {
-            ILError::InternalError(format!(
-                "condition should return BooleanArray, but got {:?}",
-                array.data_type()
-            ))
-        })?;
-
-        let row_id_array = batch
-            .column(0)
-            .as_primitive_opt::<Int64Type>()
-            .ok_or_else(|| {
-                ILError::InternalError(format!(
-                    "row id array should be Int64Array, but got {:?}",
-                    batch.column(0).data_type()
-                ))
-            })?;
-
-        let mut selected_indices = Vec::new();
-        for (i, v) in bool_array.iter().enumerate() {
-            if let Some(v) = v
-                && v
-            {
-                selected_indices.push(i as i64);
-                updated_row_ids.push(row_id_array.value(i));
-            }
-        }
-
-        let indices = Arc::new(Int64Array::from(selected_indices));
-        let selected_batch = arrow::compute::take_record_batch(&batch, indices.as_ref())?;
-        if selected_batch.num_rows() == 0 {
+        if batch.num_rows() == 0 {
             continue;
         }
-        let updated_batch = update_record_batch(&selected_batch, &set_map)?;
-        process_insert_into_inline_rows(tx_helper, table_id, &updated_batch).await?;
+        moved_row_ids.extend(row_ids_of_batch(&batch)?);
+        let updated_batch = update_record_batch(table, &batch, assignments)?;
+        process_insert_into_inline_rows(
+            tx_helper,
+            table_id,
+            &updated_batch,
+            table.config.catalog_insert_batch_size,
+        )
+        .await?;
+        if replaces_unique_keys {
+            updated_batches.push(updated_batch);
+        }
+    }
+
+    // The moved rows still point at their old locations and the inline rows hold their old
+    // values here
+    let replaced_row_ids = [inline_row_ids.clone(), moved_row_ids.clone()].concat();
+    if replaced_row_ids.is_empty() {
+        return Ok(0);
     }
+    let replaced = col(INTERNAL_ROW_ID_FIELD_NAME)
+        .in_list(replaced_row_ids.iter().copied().map(lit).collect(), false);
+    record_replaced_rows(tx_helper, table, replaced, snapshot_id).await?;
 
     tx_helper
-        .update_row_location_as_inline(table_id, &updated_row_ids)
+        .delete_inline_rows_by_row_ids(table_id, &inline_row_ids)
         .await?;
+    process_insert_into_inline_rows(
+        tx_helper,
+        table_id,
+        &updated_inline_batch,
+        table.config.catalog_insert_batch_size,
+    )
+    .await?;
 
     tx_helper
-        .update_inline_rows(table_id, &set_map, condition)
+        .update_row_location_as_inline(table_id, &moved_row_ids)
         .await?;
+    if replaces_unique_keys {
+        replace_unique_keys(tx_helper, table, &updated_batches).await?;
+    }
 
-    Ok(())
+    Ok(replaced_row_ids.len())
 }
 
 fn update_record_batch(
+    table: &Table,
     batch: &RecordBatch,
-    set_map: &HashMap<String, Scalar>,
+    assignments: &[(String, Expr)],
 ) -> ILResult<RecordBatch> {
     let mut columns = batch.columns().to_vec();
-    for (name, value) in set_map {
-        let idx = batch.schema().index_of(&name)?;
-        let array = value.to_array_of_size(batch.num_rows())?;
-        columns[idx] = Arc::new(array);
+    let row_ids = batch.column_by_name(INTERNAL_ROW_ID_FIELD_NAME);
+    for (name, expr) in assignments {
+        let idx = batch.schema().index_of(name)?;
+        let array = expr.eval(batch)?.into_array(batch.num_rows())?;
+        enforce_not_null(table.schema.field(idx), &array, row_ids)?;
+        columns[idx] = array;
     }
     let options = RecordBatchOptions::default().with_row_count(Some(batch.num_rows()));
-    Ok(RecordBatch::try_new_with_options(
-        batch.schema(),
-        columns,
-        &options,
-    )?)
+    let updated = RecordBatch::try_new_with_options(batch.schema(), columns, &options)?;
+    enforce_check_constraints(&table.config.check_constraints, &updated)?;
+    Ok(updated)
 }
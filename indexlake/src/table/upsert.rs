@@ -0,0 +1,297 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use arrow::array::{Array, ArrayRef, AsArray, RecordBatch, UInt32Array};
+use arrow::datatypes::Int64Type;
+use arrow::row::{RowConverter, SortField};
+use futures::StreamExt;
+
+use crate::catalog::{
+    CatalogSchema, INTERNAL_ROW_ID_FIELD_NAME, RowLocation, Scalar, TransactionHelper,
+    rows_to_record_batch,
+};
+use crate::expr::{Expr, col, lit};
+use crate::index::{Index, IndexDefination};
+use crate::storage::read_parquet_files_by_locations;
+use crate::table::{Table, process_delete_rows_by_row_ids, process_insert};
+use crate::{ILError, ILResult};
+
+/// Replaces rows whose `key_columns` values match a row of `record` and inserts the others. Rows
+/// of `record` sharing a key are reduced to the last one, rows with a null key value never match
+/// existing rows.
+pub(crate) async fn process_upsert(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    record: &RecordBatch,
+    key_columns: &[String],
+    snapshot_id: i64,
+) -> ILResult<()> {
+    let record = dedup_by_key(record, key_columns)?;
+    if record.num_rows() == 0 {
+        return Ok(());
+    }
+
+    // Matching rows are found in both the inline rows and the data files
+    let keys = KeySet::try_new(&record, key_columns)?;
+    let inline_row_ids = find_inline_row_ids_by_keys(tx_helper, table, &keys).await?;
+    let data_file_row_ids =
+        find_data_file_row_ids_by_keys(tx_helper, table, &record, &keys).await?;
+    let row_ids = [inline_row_ids, data_file_row_ids].concat();
+    process_delete_rows_by_row_ids(tx_helper, table, &row_ids, snapshot_id).await?;
+
+    process_insert(tx_helper, table, &record).await
+}
+
+pub(crate) fn key_arrays(batch: &RecordBatch, key_columns: &[String]) -> ILResult<Vec<ArrayRef>> {
+    key_columns
+        .iter()
+        .map(|name| {
+            batch.column_by_name(name).cloned().ok_or_else(|| {
+                ILError::InvalidInput(format!("Key column {name} not found in record"))
+            })
+        })
+        .collect()
+}
+
+fn key_converter(key_arrays: &[ArrayRef]) -> ILResult<RowConverter> {
+    Ok(RowConverter::new(
+        key_arrays
+            .iter()
+            .map(|array| SortField::new(array.data_type().clone()))
+            .collect(),
+    )?)
+}
+
+fn dedup_by_key(record: &RecordBatch, key_columns: &[String]) -> ILResult<RecordBatch> {
+    let key_arrays = key_arrays(record, key_columns)?;
+    let converter = key_converter(&key_arrays)?;
+    let rows = converter.convert_columns(&key_arrays)?;
+
+    let mut last_indices = HashMap::new();
+    for (i, row) in rows.iter().enumerate() {
+        last_indices.insert(row, i as u32);
+    }
+    if last_indices.len() == record.num_rows() {
+        return Ok(record.clone());
+    }
+
+    let mut indices = last_indices.into_values().collect::<Vec<_>>();
+    indices.sort();
+    Ok(arrow::compute::take_record_batch(
+        record,
+        &UInt32Array::from(indices),
+    )?)
+}
+
+/// Row encoded keys of the upserted rows, existing rows are matched by looking up their keys.
+struct KeySet {
+    key_columns: Vec<String>,
+    converter: RowConverter,
+    keys: HashSet<Box<[u8]>>,
+}
+
+impl KeySet {
+    fn try_new(record: &RecordBatch, key_columns: &[String]) -> ILResult<Self> {
+        let key_arrays = key_arrays(record, key_columns)?;
+        let converter = key_converter(&key_arrays)?;
+        let rows = converter.convert_columns(&key_arrays)?;
+        let keys = (0..record.num_rows())
+            .filter(|i| !has_null_key(&key_arrays, *i))
+            .map(|i| rows.row(i).as_ref().into())
+            .collect();
+        Ok(Self {
+            key_columns: key_columns.to_vec(),
+            converter,
+            keys,
+        })
+    }
+
+    /// Indices of the rows of `batch` whose key is in the set.
+    fn matched_indices(&self, batch: &RecordBatch) -> ILResult<Vec<usize>> {
+        let key_arrays = key_arrays(batch, &self.key_columns)?;
+        let rows = self.converter.convert_columns(&key_arrays)?;
+        Ok((0..batch.num_rows())
+            .filter(|i| !has_null_key(&key_arrays, *i) && self.keys.contains(rows.row(*i).as_ref()))
+            .collect())
+    }
+
+    /// Ids of the rows of `batch` whose key is in the set, the batch must hold the row ids.
+    fn matched_row_ids(&self, batch: &RecordBatch) -> ILResult<Vec<i64>> {
+        let row_id_array = batch
+            .column_by_name(INTERNAL_ROW_ID_FIELD_NAME)
+            .and_then(|array| array.as_primitive_opt::<Int64Type>())
+            .ok_or_else(|| {
+                ILError::InternalError("row id array should be Int64Array".to_string())
+            })?;
+        Ok(self
+            .matched_indices(batch)?
+            .into_iter()
+            .map(|i| row_id_array.value(i))
+            .collect())
+    }
+}
+
+pub(crate) fn has_null_key(key_arrays: &[ArrayRef], index: usize) -> bool {
+    key_arrays.iter().any(|array| array.is_null(index))
+}
+
+async fn find_inline_row_ids_by_keys(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    keys: &KeySet,
+) -> ILResult<Vec<i64>> {
+    let catalog_schema = Arc::new(CatalogSchema::from_arrow(&table.schema)?);
+    let rows = tx_helper
+        .scan_inline_rows(table.table_id, &catalog_schema)
+        .await?;
+    let batch = rows_to_record_batch(&table.schema, &rows)?;
+    keys.matched_row_ids(&batch)
+}
+
+async fn find_data_file_row_ids_by_keys(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    record: &RecordBatch,
+    keys: &KeySet,
+) -> ILResult<Vec<i64>> {
+    let candidates = find_candidate_row_ids_by_index(tx_helper, table, record, keys).await?;
+    let row_metadatas = tx_helper
+        .scan_row_metadata(table.table_id, &col("deleted").eq(lit(false)))
+        .await?;
+    let data_file_locations = row_metadatas
+        .into_iter()
+        .filter(|meta| match &meta.location {
+            RowLocation::Parquet { relative_path, .. } => match &candidates {
+                Some(candidates) => candidates
+                    .get(relative_path)
+                    .is_none_or(|row_ids| row_ids.contains(&meta.row_id)),
+                None => true,
+            },
+            RowLocation::Inline => false,
+        })
+        .map(|meta| (meta.row_id, meta.location))
+        .collect::<Vec<_>>();
+    if data_file_locations.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Only the row ids and keys are read, keys are matched against the set
+    let mut projection = vec![table.schema.index_of(INTERNAL_ROW_ID_FIELD_NAME)?];
+    for name in keys.key_columns.iter() {
+        projection.push(table.schema.index_of(name)?);
+    }
+    let mut stream = read_parquet_files_by_locations(
+        table.storage.clone(),
+        table.schema.clone(),
+        Some(projection),
+        data_file_locations,
+        None,
+        &table.field_defaults,
+        &table.field_ids(),
+        None,
+    )
+    .await?;
+
+    let mut row_ids = Vec::new();
+    while let Some(batch) = stream.next().await {
+        row_ids.extend(keys.matched_row_ids(&batch?)?);
+    }
+    Ok(row_ids)
+}
+
+/// Asks an index on one of the key columns which rows of each data file may hold the keys.
+/// Returns the candidate row ids by data file path, data files missing from the map are not
+/// indexed and all their rows are candidates. Returns `None` when no key column has a usable
+/// index.
+async fn find_candidate_row_ids_by_index(
+    tx_helper: &mut TransactionHelper,
+    table: &Table,
+    record: &RecordBatch,
+    keys: &KeySet,
+) -> ILResult<Option<HashMap<String, HashSet<i64>>>> {
+    let Some((index_def, index, filter)) = find_key_index(table, record, keys)? else {
+        return Ok(None);
+    };
+
+    let data_file_paths = tx_helper
+        .get_data_files(table.table_id)
+        .await?
+        .into_iter()
+        .map(|data_file| (data_file.data_file_id, data_file.relative_path))
+        .collect::<HashMap<_, _>>();
+    let index_files = tx_helper
+        .get_index_files(table.table_id)
+        .await?
+        .into_iter()
+        .filter(|index_file| index_file.index_id == index_def.index_id);
+
+    let filters = [filter];
+    let mut candidates = HashMap::new();
+    for index_file in index_files {
+        let Some(relative_path) = data_file_paths.get(&index_file.data_file_id) else {
+            continue;
+        };
+        if let Some(metadata) = &index_file.metadata {
+            match index.filter_file_metadata(index_def, metadata, &filters)? {
+                Some(false) => {
+                    candidates.insert(relative_path.clone(), HashSet::new());
+                    continue;
+                }
+                Some(true) => continue,
+                None => {}
+            }
+        }
+        let input_file = table.storage.open_file(&index_file.relative_path).await?;
+        let entries = index.filter(index_def, input_file, &filters).await?;
+        candidates.insert(
+            relative_path.clone(),
+            entries.row_ids.values().iter().copied().collect(),
+        );
+    }
+    Ok(Some(candidates))
+}
+
+/// Finds a full index on a single key column that supports filtering the column by the keys of
+/// `record`, along with that filter.
+fn find_key_index<'a>(
+    table: &'a Table,
+    record: &RecordBatch,
+    keys: &KeySet,
+) -> ILResult<Option<(&'a IndexDefination, &'a dyn Index, Expr)>> {
+    for name in keys.key_columns.iter() {
+        let mut index_defs = table
+            .indexes
+            .values()
+            .filter(|index_def| {
+                index_def.key_columns == [name.clone()] && index_def.where_predicate.is_none()
+            })
+            .collect::<Vec<_>>();
+        if index_defs.is_empty() {
+            continue;
+        }
+        // Indexes are tried in a stable order
+        index_defs.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let column = record.column_by_name(name).ok_or_else(|| {
+            ILError::InvalidInput(format!("Key column {name} not found in record"))
+        })?;
+        let mut list = Vec::with_capacity(column.len());
+        for i in 0..column.len() {
+            let value = Scalar::try_from_array(column, i)?;
+            if !value.is_null() {
+                list.push(lit(value));
+            }
+        }
+        let filter = col(name).in_list(list, false);
+
+        for index_def in index_defs {
+            let index = table.index_kinds.get(&index_def.kind).ok_or_else(|| {
+                ILError::InternalError(format!("Index kind {} not found", index_def.kind))
+            })?;
+            if index.supports_filter(index_def, &filter)? {
+                return Ok(Some((index_def.as_ref(), index.as_ref(), filter)));
+            }
+        }
+    }
+    Ok(None)
+}
@@ -0,0 +1,193 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use log::debug;
+
+use crate::ILResult;
+use crate::catalog::{Catalog, RowLocation, TransactionHelper};
+use crate::storage::{Storage, StorageFile};
+use crate::table::{Table, expire_snapshots, plan_snapshot_expiry};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VacuumReport {
+    /// Relative paths of the deleted files, of the files that would be deleted in a dry run.
+    pub deleted_files: Vec<String>,
+    /// Total size of the deleted files.
+    pub reclaimed_bytes: u64,
+    /// Ids of the snapshots expired before looking for unreferenced files, of the snapshots that
+    /// would expire in a dry run.
+    pub expired_snapshots: Vec<i64>,
+}
+
+impl VacuumReport {
+    fn merge(&mut self, other: VacuumReport) {
+        self.deleted_files.extend(other.deleted_files);
+        self.reclaimed_bytes += other.reclaimed_bytes;
+        self.expired_snapshots.extend(other.expired_snapshots);
+    }
+}
+
+/// Expires the snapshots committed more than `retention` ago, then deletes files under the table
+/// directory that no data file, index file or row history of the catalog refers to and that
+/// were last modified more than `retention` ago. Files of the table its clones still refer to
+/// are kept. A dry run only reads the catalog and the storage, so it works on read-only
+/// catalogs.
+pub(crate) async fn process_vacuum(
+    table: &Table,
+    retention: Duration,
+    dry_run: bool,
+) -> ILResult<VacuumReport> {
+    let now = SystemTime::now();
+    let expire_before = now.checked_sub(retention).unwrap_or(SystemTime::UNIX_EPOCH);
+    let (expired_snapshots, referenced) = if dry_run {
+        let mut tx_helper = TransactionHelper::new(&table.catalog).await?;
+        let expiry = plan_snapshot_expiry(&mut tx_helper, table.table_id, expire_before).await?;
+        let referenced = match &expiry {
+            Some(expiry) => {
+                tx_helper
+                    .get_referenced_paths_after_expiry(
+                        &table.table_dir(),
+                        table.table_id,
+                        expiry.oldest_retained_id,
+                    )
+                    .await?
+            }
+            None => tx_helper.get_referenced_paths(&table.table_dir()).await?,
+        };
+        tx_helper.rollback().await?;
+        let expired = expiry.map(|expiry| expiry.expired_ids).unwrap_or_default();
+        (expired, referenced)
+    } else {
+        let expired = TransactionHelper::run(&table.catalog, |mut tx_helper| {
+            Box::pin(async move {
+                let expired =
+                    expire_snapshots(&mut tx_helper, table.table_id, expire_before).await?;
+                tx_helper.commit().await?;
+                Ok(expired)
+            })
+        })
+        .await?;
+
+        // Files written by inserts that commit after this point are not in the referenced set,
+        // the retention window keeps them from being deleted
+        let mut tx_helper = TransactionHelper::new(&table.catalog).await?;
+        let referenced = tx_helper.get_referenced_paths(&table.table_dir()).await?;
+        tx_helper.commit().await?;
+        (expired, referenced)
+    };
+
+    let files = table.storage.list_files(&table.table_dir()).await?;
+    let mut report = delete_old_files(
+        &table.storage,
+        files
+            .into_iter()
+            .filter(|file| !referenced.contains(&file.relative_path)),
+        now,
+        retention,
+        dry_run,
+    )
+    .await?;
+    report.expired_snapshots = expired_snapshots;
+    Ok(report)
+}
+
+/// Vacuums every table of the namespace like [`process_vacuum`], then deletes files under the
+/// namespace directory outside the directories of its tables, such as the files of dropped
+/// tables, that no table of any namespace refers to and that were last modified more than
+/// `retention` ago.
+pub(crate) async fn process_vacuum_namespace(
+    catalog: &Arc<dyn Catalog>,
+    storage: &Storage,
+    namespace_id: i64,
+    tables: &[Table],
+    retention: Duration,
+    dry_run: bool,
+) -> ILResult<VacuumReport> {
+    let mut report = VacuumReport::default();
+    for table in tables {
+        report.merge(process_vacuum(table, retention, dry_run).await?);
+    }
+
+    // Tables created after the tables were listed write files within the retention window
+    let now = SystemTime::now();
+    let table_dirs = tables
+        .iter()
+        .map(|table| format!("{}/", table.table_dir()))
+        .collect::<Vec<_>>();
+    let namespace_dir = namespace_id.to_string();
+    let mut tx_helper = TransactionHelper::new(catalog).await?;
+    let referenced = tx_helper.get_referenced_paths(&namespace_dir).await?;
+    tx_helper.commit().await?;
+
+    let files = storage.list_files(&namespace_dir).await?;
+    report.merge(
+        delete_old_files(
+            storage,
+            files.into_iter().filter(|file| {
+                !referenced.contains(&file.relative_path)
+                    && !table_dirs
+                        .iter()
+                        .any(|dir| file.relative_path.starts_with(dir))
+            }),
+            now,
+            retention,
+            dry_run,
+        )
+        .await?,
+    );
+    report.deleted_files.sort();
+    Ok(report)
+}
+
+/// Deletes the `files` last modified more than `retention` before `now`, only reports them in
+/// a dry run.
+async fn delete_old_files(
+    storage: &Storage,
+    files: impl Iterator<Item = StorageFile>,
+    now: SystemTime,
+    retention: Duration,
+    dry_run: bool,
+) -> ILResult<VacuumReport> {
+    let mut report = VacuumReport::default();
+    for file in files {
+        // Files of unknown age may belong to an insert still in progress
+        let Some(last_modified) = file.last_modified else {
+            continue;
+        };
+        let age = now.duration_since(last_modified).unwrap_or_default();
+        if age <= retention {
+            continue;
+        }
+        if !dry_run {
+            debug!("Vacuum deletes unreferenced file {}", file.relative_path);
+            storage.delete(&file.relative_path).await?;
+        }
+        report.reclaimed_bytes += file.size_bytes;
+        report.deleted_files.push(file.relative_path);
+    }
+    report.deleted_files.sort();
+    Ok(report)
+}
+
+/// Relative paths of the files that the data files, index files and row histories of the table
+/// refer to.
+pub(crate) async fn referenced_files(
+    tx_helper: &mut TransactionHelper,
+    table_id: i64,
+) -> ILResult<HashSet<String>> {
+    let mut referenced = HashSet::new();
+    for data_file in tx_helper.get_data_files(table_id).await? {
+        referenced.insert(data_file.relative_path);
+    }
+    for index_file in tx_helper.get_index_files(table_id).await? {
+        referenced.insert(index_file.relative_path);
+    }
+    // Retained snapshots may still read rows from replaced data files
+    for row_history in tx_helper.get_row_histories(table_id).await? {
+        if let RowLocation::Parquet { relative_path, .. } = row_history.location {
+            referenced.insert(relative_path);
+        }
+    }
+    Ok(referenced)
+}
@@ -0,0 +1,75 @@
+use std::collections::HashSet;
+
+use crate::catalog::{CatalogHelper, DataFileRecord, RowLocation};
+use crate::storage::Storage;
+use crate::table::Table;
+use crate::{ChecksumMismatch, ILError, ILResult};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// Number of data files whose content was checked against their checksum.
+    pub verified_file_count: usize,
+    /// Number of data files written before checksums were kept or added by
+    /// [`Table::add_files`](crate::table::Table::add_files) without being read, they are not
+    /// checked.
+    pub unchecked_file_count: usize,
+    /// Data files whose content does not match their checksum.
+    pub mismatches: Vec<ChecksumMismatch>,
+}
+
+pub(crate) async fn process_verify(table: &Table) -> ILResult<VerifyReport> {
+    let catalog_helper = CatalogHelper::new(table.catalog.clone());
+    let mut report = VerifyReport::default();
+    for data_file in catalog_helper.get_data_files(table.table_id).await? {
+        if data_file.checksum.is_none() {
+            report.unchecked_file_count += 1;
+            continue;
+        }
+        report.verified_file_count += 1;
+        if let Some(mismatch) = verify_data_file(&table.storage, &data_file).await? {
+            report.mismatches.push(mismatch);
+        }
+    }
+    Ok(report)
+}
+
+/// Checks the data files a scan reads at `locations`, failing on the first mismatch. Files no
+/// longer in the catalog, read by time travel scans, have no checksum to check against.
+pub(crate) async fn verify_scanned_files(
+    catalog_helper: &CatalogHelper,
+    storage: &Storage,
+    table_id: i64,
+    locations: &[(i64, RowLocation)],
+) -> ILResult<()> {
+    let relative_paths = locations
+        .iter()
+        .filter_map(|(_, location)| match location {
+            RowLocation::Parquet { relative_path, .. } => Some(relative_path.as_str()),
+            _ => None,
+        })
+        .collect::<HashSet<_>>();
+    for data_file in catalog_helper.get_data_files(table_id).await? {
+        if !relative_paths.contains(data_file.relative_path.as_str()) {
+            continue;
+        }
+        if let Some(mismatch) = verify_data_file(storage, &data_file).await? {
+            return Err(ILError::ChecksumMismatch(mismatch));
+        }
+    }
+    Ok(())
+}
+
+async fn verify_data_file(
+    storage: &Storage,
+    data_file: &DataFileRecord,
+) -> ILResult<Option<ChecksumMismatch>> {
+    let Some(expected) = data_file.checksum else {
+        return Ok(None);
+    };
+    let actual = storage.checksum_file(&data_file.relative_path).await?;
+    Ok((actual != expected).then(|| ChecksumMismatch {
+        relative_path: data_file.relative_path.clone(),
+        expected,
+        actual,
+    }))
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use arrow::{
@@ -6,9 +7,26 @@ use arrow::{
 };
 use indexlake::{
     ILResult, LakeClient,
+    storage::Storage,
     table::{Table, TableConfig, TableCreation},
 };
 
+/// In-memory storage that records which paths get opened.
+pub fn counted_storage() -> Arc<Storage> {
+    Arc::new(Storage::with_read_counter(Storage::new_memory()))
+}
+
+/// Number of parquet data files opened on `storage` so far.
+pub fn data_files_opened(storage: &Storage) -> usize {
+    storage
+        .read_stats()
+        .unwrap()
+        .opened_paths
+        .iter()
+        .filter(|path| path.ends_with(".parquet"))
+        .count()
+}
+
 pub async fn prepare_testing_table(client: &LakeClient, table_name: &str) -> ILResult<Table> {
     let namespace_name = "test_namespace";
     create_namespace_if_not_exists(client, namespace_name).await?;
@@ -19,6 +37,7 @@ pub async fn prepare_testing_table(client: &LakeClient, table_name: &str) -> ILR
     let table_config = TableConfig {
         inline_row_count_limit: 3,
         parquet_row_group_size: 2,
+        ..Default::default()
     };
     let table_creation = TableCreation {
         namespace_name: namespace_name.to_string(),
@@ -50,5 +69,25 @@ pub async fn create_namespace_if_not_exists(
     if let Some(namespace_id) = client.get_namespace_id(namespace_name).await? {
         return Ok(namespace_id);
     }
-    client.create_namespace(namespace_name).await
+    client
+        .create_namespace(namespace_name, HashMap::new())
+        .await
+}
+
+/// Inserts ids `0..file_count * inline_row_count_limit` in batches of the inline row count
+/// limit of the table, so each batch is dumped into its own file. `file_batch` builds the rows
+/// of the table from the ids of a batch.
+pub async fn insert_files(
+    table: &Table,
+    file_count: i32,
+    file_batch: impl Fn(Vec<i32>) -> ILResult<RecordBatch>,
+) -> ILResult<()> {
+    let rows_per_file = table.config.inline_row_count_limit as i32;
+    for i in 0..file_count {
+        let ids = (i * rows_per_file..(i + 1) * rows_per_file).collect::<Vec<_>>();
+        table.insert(&file_batch(ids)?).await?;
+        // wait for dump task to finish
+        tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+    }
+    Ok(())
 }
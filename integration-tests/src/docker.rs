@@ -125,4 +125,19 @@ impl DockerCompose {
             ),
         )
     }
+
+    pub fn restart(&self) {
+        let mut cmd = Command::new("docker");
+        cmd.current_dir(&self.docker_compose_dir);
+
+        cmd.args(vec!["compose", "-p", self.project_name.as_str(), "restart"]);
+
+        run_command(
+            cmd,
+            format!(
+                "Restarting docker compose in {}, project name: {}",
+                self.docker_compose_dir, self.project_name
+            ),
+        )
+    }
 }
@@ -1,16 +1,29 @@
 pub mod data;
 mod docker;
+mod oss;
 pub mod utils;
 
+pub use oss::MockOss;
+
 use std::{
     path::PathBuf,
     sync::{Arc, OnceLock},
 };
 
-use indexlake::{catalog::Catalog, storage::Storage};
+use indexlake::{
+    catalog::{CATALOG_VERSION, Catalog, CatalogDatabase, catalog_migrations},
+    storage::Storage,
+};
+#[cfg(feature = "duckdb")]
+use indexlake_catalog_duckdb::DuckDbCatalog;
+use indexlake_catalog_memory::MemoryCatalog;
+use indexlake_catalog_mysql::MySqlCatalog;
 use indexlake_catalog_postgres::PostgresCatalog;
 use indexlake_catalog_sqlite::SqliteCatalog;
-use opendal::services::S3Config;
+use opendal::{
+    raw::HttpClient,
+    services::{AzblobConfig, GcsConfig, OssConfig, S3Config},
+};
 
 use crate::docker::DockerCompose;
 
@@ -20,7 +33,7 @@ pub fn init_env_logger() {
     unsafe {
         std::env::set_var(
             "RUST_LOG",
-            "info,indexlake=debug,indexlake_catalog_postgres=debug,indexlake_catalog_sqlite=debug,indexlake_index_rstar=debug",
+            "info,indexlake=debug,indexlake_catalog_duckdb=debug,indexlake_catalog_memory=debug,indexlake_catalog_mysql=debug,indexlake_catalog_postgres=debug,indexlake_catalog_sqlite=debug,indexlake_index_rstar=debug",
         );
     }
     ENV_LOGGER.get_or_init(|| {
@@ -37,8 +50,25 @@ pub fn setup_sqlite_db() -> String {
     );
     std::fs::create_dir_all(PathBuf::from(&db_path).parent().unwrap()).unwrap();
     let conn = rusqlite::Connection::open(&db_path).unwrap();
-    conn.execute_batch(include_str!("../testdata/sqlite/init_catalog.sql"))
-        .unwrap();
+    for migration in catalog_migrations(CatalogDatabase::Sqlite) {
+        conn.execute_batch(migration.sql).unwrap();
+    }
+    conn.execute_batch(&format!(
+        "CREATE TABLE indexlake_catalog_version (version BIGINT NOT NULL);
+        INSERT INTO indexlake_catalog_version (version) VALUES ({CATALOG_VERSION})"
+    ))
+    .unwrap();
+    db_path
+}
+
+#[cfg(feature = "duckdb")]
+pub fn setup_duckdb_db() -> String {
+    let db_path = format!(
+        "{}/tmp/duckdb/{}.db",
+        env!("CARGO_MANIFEST_DIR"),
+        uuid::Uuid::new_v4()
+    );
+    std::fs::create_dir_all(PathBuf::from(&db_path).parent().unwrap()).unwrap();
     db_path
 }
 
@@ -53,6 +83,39 @@ pub async fn setup_postgres_db() -> DockerCompose {
     docker_compose
 }
 
+pub async fn setup_postgres_tls_db() -> DockerCompose {
+    let docker_compose = DockerCompose::new(
+        "postgres-tls",
+        format!("{}/testdata/postgres-tls", env!("CARGO_MANIFEST_DIR")),
+    );
+    docker_compose.down();
+    docker_compose.up();
+    tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+    docker_compose
+}
+
+pub async fn setup_mysql_db() -> DockerCompose {
+    let docker_compose = DockerCompose::new(
+        "mysql",
+        format!("{}/testdata/mysql", env!("CARGO_MANIFEST_DIR")),
+    );
+    docker_compose.down();
+    docker_compose.up();
+    tokio::time::sleep(std::time::Duration::from_secs(15)).await;
+    docker_compose
+}
+
+pub async fn setup_mariadb_db() -> DockerCompose {
+    let docker_compose = DockerCompose::new(
+        "mariadb",
+        format!("{}/testdata/mariadb", env!("CARGO_MANIFEST_DIR")),
+    );
+    docker_compose.down();
+    docker_compose.up();
+    tokio::time::sleep(std::time::Duration::from_secs(15)).await;
+    docker_compose
+}
+
 pub fn setup_minio() -> DockerCompose {
     let docker_compose = DockerCompose::new(
         "minio",
@@ -63,34 +126,201 @@ pub fn setup_minio() -> DockerCompose {
     docker_compose
 }
 
+pub fn setup_fake_gcs() -> DockerCompose {
+    let docker_compose = DockerCompose::new(
+        "fake-gcs",
+        format!("{}/testdata/fake-gcs", env!("CARGO_MANIFEST_DIR")),
+    );
+    docker_compose.down();
+    docker_compose.up();
+    docker_compose
+}
+
+pub fn setup_azurite() -> DockerCompose {
+    let docker_compose = DockerCompose::new(
+        "azurite",
+        format!("{}/testdata/azurite", env!("CARGO_MANIFEST_DIR")),
+    );
+    docker_compose.down();
+    docker_compose.up();
+    docker_compose
+}
+
 pub fn catalog_sqlite() -> Arc<dyn Catalog> {
     let db_path = setup_sqlite_db();
     Arc::new(SqliteCatalog::try_new(db_path).unwrap())
 }
 
+#[cfg(feature = "duckdb")]
+pub async fn catalog_duckdb() -> Arc<dyn Catalog> {
+    let catalog = DuckDbCatalog::try_new(setup_duckdb_db()).unwrap();
+    catalog.migrate().await.unwrap();
+    Arc::new(catalog)
+}
+
+pub fn catalog_memory() -> Arc<dyn Catalog> {
+    Arc::new(MemoryCatalog::new())
+}
+
 pub async fn catalog_postgres() -> Arc<dyn Catalog> {
     let _ = setup_postgres_db().await;
-    Arc::new(
+    let catalog =
         PostgresCatalog::try_new("localhost", 5432, "postgres", "password", Some("postgres"))
             .await
-            .unwrap(),
-    )
+            .unwrap();
+    catalog.migrate().await.unwrap();
+    Arc::new(catalog)
+}
+
+pub async fn catalog_mysql() -> Arc<dyn Catalog> {
+    let _ = setup_mysql_db().await;
+    let catalog = MySqlCatalog::try_new("localhost", 3306, "root", "password", Some("indexlake"))
+        .await
+        .unwrap();
+    catalog.migrate().await.unwrap();
+    Arc::new(catalog)
+}
+
+pub async fn catalog_mariadb() -> Arc<dyn Catalog> {
+    let _ = setup_mariadb_db().await;
+    let catalog = MySqlCatalog::try_new("localhost", 3307, "root", "password", Some("indexlake"))
+        .await
+        .unwrap();
+    catalog.migrate().await.unwrap();
+    Arc::new(catalog)
 }
 
+/// File system storage under a directory of its own, fresh catalogs all write the same paths.
 pub fn storage_fs() -> Arc<Storage> {
-    let home = format!("{}/{}", env!("CARGO_MANIFEST_DIR"), "tmp/fs_storage");
+    let home = format!(
+        "{}/tmp/fs_storage/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        uuid::Uuid::new_v4()
+    );
     Arc::new(Storage::new_fs(home))
 }
 
+pub fn storage_memory() -> Arc<Storage> {
+    Arc::new(Storage::new_memory())
+}
+
+/// MinIO server started with docker compose, serving the `indexlake` bucket.
+pub struct MinioTestContext {
+    _docker_compose: DockerCompose,
+    config: S3Config,
+    bucket: String,
+}
+
+impl MinioTestContext {
+    pub fn setup() -> Self {
+        let docker_compose = setup_minio();
+        std::thread::sleep(std::time::Duration::from_secs(5));
+        let mut config = S3Config::default();
+        config.endpoint = Some("http://127.0.0.1:9000".to_string());
+        config.access_key_id = Some("admin".to_string());
+        config.secret_access_key = Some("password".to_string());
+        config.region = Some("us-east-1".to_string());
+        config.disable_config_load = true;
+        config.disable_ec2_metadata = true;
+        Self {
+            _docker_compose: docker_compose,
+            config,
+            bucket: "indexlake".to_string(),
+        }
+    }
+
+    pub fn config(&self) -> S3Config {
+        self.config.clone()
+    }
+
+    pub fn bucket(&self) -> &str {
+        &self.bucket
+    }
+
+    pub fn storage(&self) -> Arc<Storage> {
+        Arc::new(Storage::new_s3(self.config(), self.bucket()))
+    }
+}
+
 pub fn storage_s3() -> Arc<Storage> {
-    let _ = setup_minio();
-    std::thread::sleep(std::time::Duration::from_secs(5));
-    let mut config = S3Config::default();
-    config.endpoint = Some("http://127.0.0.1:9000".to_string());
-    config.access_key_id = Some("admin".to_string());
-    config.secret_access_key = Some("password".to_string());
-    config.region = Some("us-east-1".to_string());
-    config.disable_config_load = true;
-    config.disable_ec2_metadata = true;
-    Arc::new(Storage::new_s3(config, "indexlake"))
+    MinioTestContext::setup().storage()
+}
+
+/// R2 storage against MinIO, with the config shape used for Cloudflare R2: an endpoint
+/// override and no region, which R2 defaults to `auto`. MinIO accepts any region.
+pub fn storage_r2() -> Arc<Storage> {
+    let context = MinioTestContext::setup();
+    let mut config = context.config();
+    config.region = None;
+    Arc::new(Storage::new_r2(config, context.bucket()).unwrap())
+}
+
+/// OSS storage whose requests are answered by a [`MockOss`] bucket.
+pub fn storage_oss() -> Arc<Storage> {
+    let mut config = OssConfig::default();
+    config.endpoint = Some("http://127.0.0.1:9100".to_string());
+    config.access_key_id = Some("access_key_id".to_string());
+    config.access_key_secret = Some("access_key_secret".to_string());
+    let mock = MockOss::default();
+    let storage = Storage::new_oss(config, "indexlake").unwrap();
+    Arc::new(Storage::with_layer(storage, move |op| {
+        op.update_http_client(|_| HttpClient::with(mock.clone()));
+        op
+    }))
+}
+
+/// Fake GCS server started with docker compose, serving the `indexlake` bucket anonymously.
+pub struct GcsTestContext {
+    _docker_compose: DockerCompose,
+    config: GcsConfig,
+    bucket: String,
+}
+
+impl GcsTestContext {
+    pub fn setup() -> Self {
+        let docker_compose = setup_fake_gcs();
+        std::thread::sleep(std::time::Duration::from_secs(5));
+        let mut config = GcsConfig::default();
+        config.endpoint = Some("http://127.0.0.1:4443".to_string());
+        config.allow_anonymous = true;
+        config.disable_config_load = true;
+        config.disable_vm_metadata = true;
+        Self {
+            _docker_compose: docker_compose,
+            config,
+            bucket: "indexlake".to_string(),
+        }
+    }
+
+    pub fn config(&self) -> GcsConfig {
+        self.config.clone()
+    }
+
+    pub fn bucket(&self) -> &str {
+        &self.bucket
+    }
+
+    pub fn storage(&self) -> Arc<Storage> {
+        Arc::new(Storage::new_gcs(self.config(), self.bucket()).unwrap())
+    }
+}
+
+pub fn storage_gcs() -> Arc<Storage> {
+    GcsTestContext::setup().storage()
+}
+
+pub fn storage_azblob() -> Arc<Storage> {
+    let _ = setup_azurite();
+    std::thread::sleep(std::time::Duration::from_secs(10));
+    let config = AzblobConfig {
+        endpoint: Some("http://127.0.0.1:10000/devstoreaccount1".to_string()),
+        account_name: Some("devstoreaccount1".to_string()),
+        // Well-known Azurite development account key
+        account_key: Some(
+            "Eby8vdM02xNOcqFlqUwJPLlmEtlCDXJ1OUzFT50uSRZ6IFsuFq2UVErCz4I6tq/K1SZFPTOtr/KBHBeksoGMGw=="
+                .to_string(),
+        ),
+        ..Default::default()
+    };
+    Arc::new(Storage::new_azblob(config, "indexlake"))
 }
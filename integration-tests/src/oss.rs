@@ -0,0 +1,269 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bytes::Bytes;
+use http::{Method, Request, Response, StatusCode};
+use opendal::{
+    Buffer,
+    raw::{
+        HttpBody, HttpFetch, format_datetime_into_http_date, parse_datetime_from_from_timestamp,
+    },
+};
+
+struct MockObject {
+    content: Bytes,
+    etag: String,
+    last_modified: i64,
+}
+
+#[derive(Default)]
+struct MockOssState {
+    objects: BTreeMap<String, MockObject>,
+    // Upload id to the parts uploaded so far
+    uploads: HashMap<String, BTreeMap<usize, Bytes>>,
+    next_id: u64,
+}
+
+/// In-process stand-in for an OSS bucket, answering the requests opendal sends for the
+/// operations indexlake uses: put, multipart upload, ranged get, head, delete, batch delete and
+/// list v2. Installed as the HTTP client of the operator, so no server or DNS is involved.
+#[derive(Clone, Default)]
+pub struct MockOss {
+    state: Arc<Mutex<MockOssState>>,
+}
+
+impl HttpFetch for MockOss {
+    async fn fetch(&self, req: Request<Buffer>) -> opendal::Result<Response<HttpBody>> {
+        let (status, headers, body) = self.handle(req);
+        let mut builder = Response::builder().status(status);
+        for (name, value) in headers {
+            builder = builder.header(name, value);
+        }
+        let size = body.len() as u64;
+        let stream = futures::stream::iter(vec![Ok(Buffer::from(body))]);
+        Ok(builder
+            .body(HttpBody::new(stream, Some(size)))
+            .expect("mock OSS response must be valid"))
+    }
+}
+
+type MockResponse = (StatusCode, Vec<(&'static str, String)>, Bytes);
+
+impl MockOss {
+    fn handle(&self, req: Request<Buffer>) -> MockResponse {
+        let key = percent_decode(req.uri().path().trim_start_matches('/'));
+        let query = parse_query(req.uri().query().unwrap_or_default());
+        let mut state = self.state.lock().unwrap();
+        match (req.method().clone(), key.is_empty()) {
+            (Method::GET, true) => list_objects(&state, &query),
+            (Method::POST, true) if query.contains_key("delete") => {
+                let body = String::from_utf8_lossy(&req.body().to_bytes()).to_string();
+                let mut deleted = String::new();
+                for key in xml_values(&body, "Key") {
+                    state.objects.remove(&key);
+                    deleted.push_str(&format!("<Deleted><Key>{key}</Key></Deleted>"));
+                }
+                xml_response(format!("<DeleteResult>{deleted}</DeleteResult>"))
+            }
+            (Method::POST, false) if query.contains_key("uploads") => {
+                state.next_id += 1;
+                let upload_id = format!("upload-{}", state.next_id);
+                state.uploads.insert(upload_id.clone(), BTreeMap::new());
+                xml_response(format!(
+                    "<InitiateMultipartUploadResult><Bucket>indexlake</Bucket><Key>{key}</Key>\
+                     <UploadId>{upload_id}</UploadId></InitiateMultipartUploadResult>"
+                ))
+            }
+            (Method::PUT, false) if query.contains_key("uploadId") => {
+                let part_number = query["partNumber"].parse::<usize>().unwrap();
+                let Some(parts) = state.uploads.get_mut(&query["uploadId"]) else {
+                    return empty_response(StatusCode::NOT_FOUND);
+                };
+                parts.insert(part_number, req.body().to_bytes());
+                let etag = format!("\"part-{part_number}\"");
+                (StatusCode::OK, vec![("etag", etag)], Bytes::new())
+            }
+            (Method::POST, false) if query.contains_key("uploadId") => {
+                let Some(parts) = state.uploads.remove(&query["uploadId"]) else {
+                    return empty_response(StatusCode::NOT_FOUND);
+                };
+                let content = parts.into_values().flatten().collect::<Vec<_>>();
+                let etag = put_object(&mut state, key, Bytes::from(content));
+                xml_response(format!(
+                    "<CompleteMultipartUploadResult><ETag>{etag}</ETag>\
+                     </CompleteMultipartUploadResult>"
+                ))
+            }
+            (Method::DELETE, false) if query.contains_key("uploadId") => {
+                state.uploads.remove(&query["uploadId"]);
+                empty_response(StatusCode::NO_CONTENT)
+            }
+            (Method::PUT, false) => {
+                let etag = put_object(&mut state, key, req.body().to_bytes());
+                (StatusCode::OK, vec![("etag", etag)], Bytes::new())
+            }
+            (Method::GET, false) | (Method::HEAD, false) => {
+                let Some(object) = state.objects.get(&key) else {
+                    return empty_response(StatusCode::NOT_FOUND);
+                };
+                let last_modified = format_datetime_into_http_date(
+                    parse_datetime_from_from_timestamp(object.last_modified).unwrap(),
+                );
+                let mut headers = vec![
+                    ("etag", object.etag.clone()),
+                    ("last-modified", last_modified),
+                ];
+                if req.method() == Method::HEAD {
+                    headers.push(("content-length", object.content.len().to_string()));
+                    return (StatusCode::OK, headers, Bytes::new());
+                }
+                let range = req
+                    .headers()
+                    .get(http::header::RANGE)
+                    .and_then(|range| range.to_str().ok())
+                    .and_then(|range| parse_range(range, object.content.len()));
+                match range {
+                    Some((start, end)) => {
+                        let total = object.content.len();
+                        headers.push(("content-range", format!("bytes {start}-{end}/{total}")));
+                        let content = object.content.slice(start..end + 1);
+                        (StatusCode::PARTIAL_CONTENT, headers, content)
+                    }
+                    None => (StatusCode::OK, headers, object.content.clone()),
+                }
+            }
+            (Method::DELETE, false) => {
+                state.objects.remove(&key);
+                empty_response(StatusCode::NO_CONTENT)
+            }
+            _ => empty_response(StatusCode::NOT_IMPLEMENTED),
+        }
+    }
+}
+
+fn put_object(state: &mut MockOssState, key: String, content: Bytes) -> String {
+    state.next_id += 1;
+    let etag = format!("\"etag-{}\"", state.next_id);
+    let last_modified = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    state.objects.insert(
+        key,
+        MockObject {
+            content,
+            etag: etag.clone(),
+            last_modified,
+        },
+    );
+    etag
+}
+
+/// Lists every object under the prefix in one page, grouping keys by the delimiter.
+fn list_objects(state: &MockOssState, query: &HashMap<String, String>) -> MockResponse {
+    let prefix = query.get("prefix").cloned().unwrap_or_default();
+    let delimiter = query.get("delimiter").cloned().unwrap_or_default();
+    let mut contents = String::new();
+    let mut common_prefixes = Vec::<String>::new();
+    for (key, object) in state.objects.range(prefix.clone()..) {
+        let Some(rest) = key.strip_prefix(&prefix) else {
+            break;
+        };
+        if let Some(idx) = (!delimiter.is_empty())
+            .then(|| rest.find(&delimiter))
+            .flatten()
+        {
+            let common_prefix = format!("{prefix}{}", &rest[..idx + delimiter.len()]);
+            if !common_prefixes.contains(&common_prefix) {
+                common_prefixes.push(common_prefix);
+            }
+            continue;
+        }
+        let last_modified = parse_datetime_from_from_timestamp(object.last_modified)
+            .unwrap()
+            .to_rfc3339();
+        contents.push_str(&format!(
+            "<Contents><Key>{key}</Key><LastModified>{last_modified}</LastModified>\
+             <ETag>{}</ETag><Size>{}</Size></Contents>",
+            object.etag,
+            object.content.len()
+        ));
+    }
+    let common_prefixes = common_prefixes
+        .iter()
+        .map(|prefix| format!("<CommonPrefixes><Prefix>{prefix}</Prefix></CommonPrefixes>"))
+        .collect::<String>();
+    xml_response(format!(
+        "<ListBucketResult><Prefix>{prefix}</Prefix><IsTruncated>false</IsTruncated>\
+         {contents}{common_prefixes}</ListBucketResult>"
+    ))
+}
+
+fn xml_response(body: String) -> MockResponse {
+    (
+        StatusCode::OK,
+        vec![("content-type", "application/xml".to_string())],
+        Bytes::from(body),
+    )
+}
+
+fn empty_response(status: StatusCode) -> MockResponse {
+    (status, Vec::new(), Bytes::new())
+}
+
+fn xml_values(xml: &str, tag: &str) -> Vec<String> {
+    let open = format!("<{tag}>");
+    let close = format!("</{tag}>");
+    xml.split(&open)
+        .skip(1)
+        .filter_map(|rest| rest.split_once(&close).map(|(value, _)| value.to_string()))
+        .collect()
+}
+
+/// Parses `bytes=start-end` and `bytes=start-` into an inclusive range.
+fn parse_range(range: &str, len: usize) -> Option<(usize, usize)> {
+    let (start, end) = range.strip_prefix("bytes=")?.split_once('-')?;
+    let start = start.parse::<usize>().ok()?;
+    let end = match end {
+        "" => len - 1,
+        end => end.parse::<usize>().ok()?.min(len - 1),
+    };
+    Some((start, end))
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((name, value)) => (name.to_string(), percent_decode(value)),
+            None => (pair.to_string(), String::new()),
+        })
+        .collect()
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
@@ -45,3 +45,15 @@ pub async fn table_scan(table: &Table, scan: TableScan) -> ILResult<String> {
     let table_str = pretty_format_batches(&[sorted_batch])?.to_string();
     Ok(table_str)
 }
+
+pub async fn table_scan_as_of(
+    table: &Table,
+    snapshot_id: i64,
+    scan: TableScan,
+) -> ILResult<String> {
+    let stream = table.scan_as_of(snapshot_id, scan).await?;
+    let batches = stream.try_collect::<Vec<_>>().await?;
+    let sorted_batch = sort_record_batches(&batches, INTERNAL_ROW_ID_FIELD_NAME)?;
+    let table_str = pretty_format_batches(&[sorted_batch])?.to_string();
+    Ok(table_str)
+}
@@ -0,0 +1,50 @@
+CREATE TABLE indexlake_namespace (
+    namespace_id BIGINT PRIMARY KEY,
+    namespace_name TEXT NOT NULL
+);
+
+CREATE TABLE indexlake_table (
+    table_id BIGINT PRIMARY KEY,
+    table_name TEXT NOT NULL,
+    namespace_id BIGINT NOT NULL,
+    config TEXT NOT NULL
+);
+
+CREATE TABLE indexlake_field (
+    field_id BIGINT PRIMARY KEY,
+    table_id BIGINT NOT NULL,
+    field_name TEXT NOT NULL,
+    data_type TEXT NOT NULL,
+    nullable BOOLEAN NOT NULL,
+    metadata TEXT NOT NULL
+);
+
+CREATE TABLE indexlake_dump_task (
+    table_id BIGINT PRIMARY KEY
+);
+
+CREATE TABLE indexlake_data_file (
+    data_file_id BIGINT PRIMARY KEY,
+    table_id BIGINT NOT NULL,
+    relative_path TEXT NOT NULL,
+    file_size_bytes BIGINT NOT NULL,
+    record_count BIGINT NOT NULL,
+    row_ids LONGBLOB NOT NULL
+);
+
+CREATE TABLE indexlake_index (
+    index_id BIGINT PRIMARY KEY,
+    table_id BIGINT NOT NULL,
+    index_name TEXT NOT NULL,
+    index_kind TEXT NOT NULL,
+    key_field_ids TEXT NOT NULL,
+    include_field_ids TEXT NOT NULL,
+    params TEXT NOT NULL
+);
+
+CREATE TABLE indexlake_index_file (
+    index_file_id BIGINT PRIMARY KEY,
+    index_id BIGINT NOT NULL,
+    data_file_id BIGINT NOT NULL,
+    relative_path TEXT NOT NULL
+);
\ No newline at end of file
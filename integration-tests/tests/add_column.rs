@@ -0,0 +1,265 @@
+use arrow::array::{Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use indexlake::catalog::Scalar;
+use indexlake::expr::{col, lit};
+use indexlake::table::TableScan;
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::prepare_testing_table;
+use indexlake_integration_tests::utils::{full_table_scan, table_scan};
+use indexlake_integration_tests::{
+    catalog_mariadb, catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite,
+    init_env_logger, storage_fs, storage_memory, storage_s3,
+};
+use std::sync::Arc;
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn add_column_with_default(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let mut table = prepare_testing_table(&client, "add_column_with_default").await?;
+    // Stays inline
+    let old_batch = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("name", DataType::Utf8, false),
+            Field::new("age", DataType::Int32, false),
+        ])),
+        vec![
+            Arc::new(StringArray::from(vec!["Eve"])),
+            Arc::new(Int32Array::from(vec![24])),
+        ],
+    )?;
+    table.insert(&old_batch).await?;
+
+    table
+        .add_column(
+            Field::new("email", DataType::Utf8, true),
+            Some(Scalar::Utf8(Some("unknown".to_string()))),
+        )
+        .await?;
+    assert_eq!(table.schema.fields().len(), 4);
+    assert_eq!(table.schema.field(3).name(), "email");
+
+    let new_batch = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("name", DataType::Utf8, false),
+            Field::new("age", DataType::Int32, false),
+            Field::new("email", DataType::Utf8, true),
+        ])),
+        vec![
+            Arc::new(StringArray::from(vec!["Frank", "Grace"])),
+            Arc::new(Int32Array::from(vec![25, 26])),
+            Arc::new(StringArray::from(vec![Some("frank@example.com"), None])),
+        ],
+    )?;
+    table.insert(&new_batch).await?;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+
+    let expected = r#"+-------------------+---------+-----+-------------------+
+| _indexlake_row_id | name    | age | email             |
++-------------------+---------+-----+-------------------+
+| 1                 | Alice   | 20  | unknown           |
+| 2                 | Bob     | 21  | unknown           |
+| 3                 | Charlie | 22  | unknown           |
+| 4                 | David   | 23  | unknown           |
+| 5                 | Eve     | 24  | unknown           |
+| 6                 | Frank   | 25  | frank@example.com |
+| 7                 | Grace   | 26  |                   |
++-------------------+---------+-----+-------------------+"#;
+    assert_eq!(full_table_scan(&table).await?, expected);
+
+    // Tables loaded afterwards see the column as well
+    let loaded_table = client
+        .load_table("test_namespace", "add_column_with_default")
+        .await?;
+    assert_eq!(loaded_table.schema, table.schema);
+    assert_eq!(full_table_scan(&loaded_table).await?, expected);
+
+    let scan = TableScan::default().with_filters(vec![col("email").eq(lit("unknown".to_string()))]);
+    let table_str = table_scan(&table, scan).await?;
+    assert_eq!(table_str.matches("unknown").count(), 5);
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn add_column_without_default(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let mut table = prepare_testing_table(&client, "add_column_without_default").await?;
+
+    table
+        .add_column(Field::new("score", DataType::Int32, true), None)
+        .await?;
+
+    let table_str = full_table_scan(&table).await?;
+    assert_eq!(
+        table_str,
+        r#"+-------------------+---------+-----+-------+
+| _indexlake_row_id | name    | age | score |
++-------------------+---------+-----+-------+
+| 1                 | Alice   | 20  |       |
+| 2                 | Bob     | 21  |       |
+| 3                 | Charlie | 22  |       |
+| 4                 | David   | 23  |       |
++-------------------+---------+-----+-------+"#
+    );
+
+    let result = table
+        .add_column(Field::new("age", DataType::Int32, true), None)
+        .await;
+    assert!(result.unwrap_err().to_string().contains("already exists"));
+    let result = table
+        .add_column(Field::new("required", DataType::Int32, false), None)
+        .await;
+    assert!(result.is_err());
+    let result = table
+        .add_column(
+            Field::new("mismatch", DataType::Int32, true),
+            Some(Scalar::Utf8(Some("a".to_string()))),
+        )
+        .await;
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_memory())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_memory())]
+#[case(async { catalog_mariadb().await }, storage_memory())]
+#[case(async { catalog_memory() }, storage_memory())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_memory()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn scan_files_of_three_schema_versions(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    // Rows 1-4 are dumped to a file of version 0
+    let mut table = prepare_testing_table(&client, "scan_files_of_three_schema_versions").await?;
+    assert_eq!(table.schema_version, 0);
+
+    table
+        .add_column(
+            Field::new("email", DataType::Utf8, true),
+            Some(Scalar::Utf8(Some("unknown".to_string()))),
+        )
+        .await?;
+    assert_eq!(table.schema_version, 1);
+    let v1_batch = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("name", DataType::Utf8, false),
+            Field::new("age", DataType::Int32, false),
+            Field::new("email", DataType::Utf8, true),
+        ])),
+        vec![
+            Arc::new(StringArray::from(vec!["Eve", "Frank", "Grace"])),
+            Arc::new(Int32Array::from(vec![24, 25, 26])),
+            Arc::new(StringArray::from(vec![Some("eve@example.com"), None, None])),
+        ],
+    )?;
+    table.insert(&v1_batch).await?;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+
+    table
+        .add_column(Field::new("score", DataType::Int32, true), None)
+        .await?;
+    assert_eq!(table.schema_version, 2);
+    // Batches still lacking the nullable score column are accepted
+    table.insert(&v1_batch.slice(0, 1)).await?;
+    let v2_batch = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("name", DataType::Utf8, false),
+            Field::new("age", DataType::Int32, false),
+            Field::new("score", DataType::Int32, true),
+        ])),
+        vec![
+            Arc::new(StringArray::from(vec!["Heidi", "Ivan"])),
+            Arc::new(Int32Array::from(vec![27, 28])),
+            Arc::new(Int32Array::from(vec![Some(90), None])),
+        ],
+    )?;
+    table.insert(&v2_batch).await?;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+
+    let expected = r#"+-------------------+---------+-----+-----------------+-------+
+| _indexlake_row_id | name    | age | email           | score |
++-------------------+---------+-----+-----------------+-------+
+| 1                 | Alice   | 20  | unknown         |       |
+| 2                 | Bob     | 21  | unknown         |       |
+| 3                 | Charlie | 22  | unknown         |       |
+| 4                 | David   | 23  | unknown         |       |
+| 5                 | Eve     | 24  | eve@example.com |       |
+| 6                 | Frank   | 25  |                 |       |
+| 7                 | Grace   | 26  |                 |       |
+| 8                 | Eve     | 24  | eve@example.com |       |
+| 9                 | Heidi   | 27  | unknown         | 90    |
+| 10                | Ivan    | 28  | unknown         |       |
++-------------------+---------+-----+-----------------+-------+"#;
+    assert_eq!(full_table_scan(&table).await?, expected);
+
+    let loaded_table = client
+        .load_table("test_namespace", "scan_files_of_three_schema_versions")
+        .await?;
+    assert_eq!(loaded_table.schema_version, 2);
+    assert_eq!(full_table_scan(&loaded_table).await?, expected);
+
+    // Non-nullable columns can't be left out, present columns must keep the table order
+    let missing_name = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![Field::new("age", DataType::Int32, false)])),
+        vec![Arc::new(Int32Array::from(vec![29]))],
+    )?;
+    assert!(table.insert(&missing_name).await.is_err());
+    let reordered = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("age", DataType::Int32, false),
+            Field::new("name", DataType::Utf8, false),
+        ])),
+        vec![
+            Arc::new(Int32Array::from(vec![29])),
+            Arc::new(StringArray::from(vec!["Judy"])),
+        ],
+    )?;
+    assert!(table.insert(&reordered).await.is_err());
+
+    table.drop_column("score", false).await?;
+    assert_eq!(table.schema_version, 3);
+
+    Ok(())
+}
@@ -0,0 +1,334 @@
+use arrow::array::{Int32Array, Int64Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use indexlake::expr::{col, lit};
+use indexlake::index::Index;
+use indexlake::table::{
+    AddFilesOptions, CompactOptions, IndexCreation, SchemaMatching, SnapshotOperation, Table,
+    TableConfig, TableCreation, TableScan,
+};
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+use indexlake_index_hash::{HashIndex, HashIndexParams};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::{
+    counted_storage, create_namespace_if_not_exists, data_files_opened,
+};
+use indexlake_integration_tests::utils::table_scan;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger,
+};
+use parquet::arrow::AsyncArrowWriter;
+use std::sync::Arc;
+
+async fn create_table(
+    client: &LakeClient,
+    table_name: &str,
+) -> Result<Table, Box<dyn std::error::Error>> {
+    create_namespace_if_not_exists(client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: Arc::new(Schema::new(vec![
+                Field::new("id", DataType::Int64, false),
+                Field::new("name", DataType::Utf8, true),
+            ])),
+            config: TableConfig::default(),
+        })
+        .await?;
+    Ok(client.load_table("test_namespace", table_name).await?)
+}
+
+/// Writes a parquet file outside the table directory, in row groups of three rows.
+async fn write_parquet(
+    storage: &Storage,
+    path: &str,
+    batch: &RecordBatch,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let properties = parquet::file::properties::WriterProperties::builder()
+        .set_max_row_group_size(3)
+        .build();
+    let output_file = storage.create_file(path).await?;
+    let mut writer = AsyncArrowWriter::try_new(output_file, batch.schema(), Some(properties))?;
+    writer.write(batch).await?;
+    writer.close().await?;
+    Ok(())
+}
+
+/// Batch of ids `ids` named after them, with ids of type `id_type`.
+fn named_ids(
+    ids: std::ops::Range<i64>,
+    id_type: DataType,
+) -> Result<RecordBatch, Box<dyn std::error::Error>> {
+    let names = ids
+        .clone()
+        .map(|id| format!("name{id}"))
+        .collect::<Vec<_>>();
+    let id_array = arrow::compute::cast(&Int64Array::from_iter_values(ids), &id_type)?;
+    let schema: SchemaRef = Arc::new(Schema::new(vec![
+        Field::new("id", id_type, false),
+        Field::new("name", DataType::Utf8, true),
+    ]));
+    Ok(RecordBatch::try_new(
+        schema,
+        vec![id_array, Arc::new(StringArray::from(names))],
+    )?)
+}
+
+fn paths(paths: &[&str]) -> Vec<String> {
+    paths.iter().map(|path| path.to_string()).collect()
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn add_files(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage.clone());
+    client.register_index(Arc::new(HashIndex))?;
+    let mut table = create_table(&client, "add_files").await?;
+
+    let day1 = "warehouse/day=2024-01-01/part-0.parquet";
+    let day2 = "warehouse/day=2024-01-02/part-0.parquet";
+    let strings = "warehouse/day=2024-01-03/part-0.parquet";
+    let nulls = "warehouse/day=2024-01-04/part-0.parquet";
+    let missing = "warehouse/day=2024-01-05/part-0.parquet";
+    write_parquet(&storage, day1, &named_ids(0..5, DataType::Int64)?).await?;
+    write_parquet(&storage, day2, &named_ids(5..10, DataType::Int32)?).await?;
+    write_parquet(&storage, strings, &named_ids(10..15, DataType::Utf8)?).await?;
+    let null_ids = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, true)])),
+        vec![Arc::new(Int64Array::from(vec![Some(15), None]))],
+    )?;
+    write_parquet(&storage, nulls, &null_ids).await?;
+
+    // files that do not match strictly are reported, the others are added
+    let report = table
+        .add_files(
+            paths(&[day1, day2, strings, missing]),
+            AddFilesOptions::default(),
+        )
+        .await?;
+    let added = report
+        .added_files
+        .iter()
+        .map(|file| (file.path.as_str(), file.row_count))
+        .collect::<Vec<_>>();
+    assert_eq!(added, vec![(day1, 5)]);
+    let rejected = report
+        .rejected_files
+        .iter()
+        .map(|file| file.path.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(rejected, vec![day2, strings, missing]);
+    assert!(
+        report.rejected_files[0]
+            .reason
+            .contains("column id has type Int32")
+    );
+    assert_eq!(
+        table.snapshots().await?.last().unwrap().operation,
+        Some(SnapshotOperation::AddFiles)
+    );
+
+    // narrower types are cast, files already added and nulls in required columns are not
+    let report = table
+        .add_files(
+            paths(&[day2, day1, strings, nulls]),
+            AddFilesOptions::default().with_schema_matching(SchemaMatching::SafeCast),
+        )
+        .await?;
+    assert_eq!(report.added_files.len(), 1);
+    assert_eq!(report.added_files[0].path, day2);
+    let rejected = report
+        .rejected_files
+        .iter()
+        .map(|file| file.path.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(rejected, vec![day1, strings, nulls]);
+    assert!(
+        report.rejected_files[2]
+            .reason
+            .contains("nulls in column id")
+    );
+
+    // nothing added commits no snapshot
+    let latest = table.snapshots().await?.last().unwrap().snapshot_id;
+    let report = table
+        .add_files(paths(&[missing]), AddFilesOptions::default())
+        .await?;
+    assert!(report.added_files.is_empty());
+    assert_eq!(table.snapshots().await?.last().unwrap().snapshot_id, latest);
+
+    // footer statistics prune the file without matching ids
+    storage.reset_read_stats();
+    let scan = TableScan::default().with_filters(vec![col("id").gt_eq(lit(8i64))]);
+    assert_eq!(
+        table_scan(&table, scan).await?,
+        r#"+-------------------+----+-------+
+| _indexlake_row_id | id | name  |
++-------------------+----+-------+
+| 9                 | 8  | name8 |
+| 10                | 9  | name9 |
++-------------------+----+-------+"#,
+    );
+    assert_eq!(data_files_opened(&storage), 1);
+
+    // added files are indexed, deleted from and compacted like written ones
+    table
+        .create_index(IndexCreation {
+            name: "name_index".to_string(),
+            kind: HashIndex.kind().to_string(),
+            key_columns: vec!["name".to_string()],
+            include_columns: vec![],
+            params: Arc::new(HashIndexParams::default()),
+            where_predicate: None,
+        })
+        .await?;
+    table.delete(&col("id").lt(lit(3i64))).await?;
+    let scan = TableScan::default().with_filters(vec![col("name").eq(lit("name6".to_string()))]);
+    assert_eq!(
+        table_scan(&table, scan.clone()).await?,
+        r#"+-------------------+----+-------+
+| _indexlake_row_id | id | name  |
++-------------------+----+-------+
+| 7                 | 6  | name6 |
++-------------------+----+-------+"#,
+    );
+    let before_compaction = table_scan(&table, TableScan::default()).await?;
+    assert_eq!(before_compaction.lines().count(), 11);
+    let compacted = table.compact(CompactOptions::default()).await?;
+    assert_eq!(compacted.rewritten_files, 2);
+    assert_eq!(
+        table_scan(&table, TableScan::default()).await?,
+        before_compaction
+    );
+    assert!(table_scan(&table, scan).await?.contains("name6"));
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[tokio::test(flavor = "multi_thread")]
+async fn add_files_to_indexed_table(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage.clone());
+    client.register_index(Arc::new(HashIndex))?;
+    let mut table = create_table(&client, "add_files_to_indexed_table").await?;
+    table
+        .create_index(IndexCreation {
+            name: "name_index".to_string(),
+            kind: HashIndex.kind().to_string(),
+            key_columns: vec!["name".to_string()],
+            include_columns: vec![],
+            params: Arc::new(HashIndexParams::default()),
+            where_predicate: None,
+        })
+        .await?;
+
+    let batch = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![Field::new("id", DataType::Int32, false)])),
+        vec![Arc::new(Int32Array::from(vec![1, 2]))],
+    )?;
+    write_parquet(&storage, "external/ids.parquet", &batch).await?;
+    write_parquet(
+        &storage,
+        "external/named.parquet",
+        &named_ids(3..6, DataType::Int64)?,
+    )
+    .await?;
+    // files read through for their index entries are rejected the same way
+    let report = table
+        .add_files(
+            paths(&["external/ids.parquet", "external/named.parquet"]),
+            AddFilesOptions::default(),
+        )
+        .await?;
+    assert_eq!(report.added_files.len(), 1);
+    assert_eq!(report.rejected_files.len(), 1);
+    assert_eq!(report.rejected_files[0].path, "external/ids.parquet");
+
+    storage.reset_read_stats();
+    let scan = TableScan::default().with_filters(vec![col("name").eq(lit("name4".to_string()))]);
+    assert!(table_scan(&table, scan).await?.contains("name4"));
+    assert_eq!(data_files_opened(&storage), 1);
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[tokio::test(flavor = "multi_thread")]
+async fn add_files_concurrently_to_indexed_table(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage.clone());
+    client.register_index(Arc::new(HashIndex))?;
+    let mut table = create_table(&client, "add_files_concurrently_to_indexed_table").await?;
+    table
+        .create_index(IndexCreation {
+            name: "name_index".to_string(),
+            kind: HashIndex.kind().to_string(),
+            key_columns: vec!["name".to_string()],
+            include_columns: vec![],
+            params: Arc::new(HashIndexParams::default()),
+            where_predicate: None,
+        })
+        .await?;
+
+    // concurrent writers never overwrite each other's index files, including the files of
+    // catalog transactions re-run after a conflict
+    let mut handles = Vec::new();
+    for writer in 0..4i64 {
+        let path = format!("external/concurrent_{writer}.parquet");
+        write_parquet(
+            &storage,
+            &path,
+            &named_ids(writer * 10..writer * 10 + 10, DataType::Int64)?,
+        )
+        .await?;
+        let table = table.clone();
+        handles.push(tokio::spawn(async move {
+            table
+                .add_files(vec![path], AddFilesOptions::default())
+                .await
+        }));
+    }
+    for handle in handles {
+        assert_eq!(handle.await??.added_files.len(), 1);
+    }
+
+    for id in [0, 15, 27, 39] {
+        let scan =
+            TableScan::default().with_filters(vec![col("name").eq(lit(format!("name{id}")))]);
+        let result = table_scan(&table, scan).await?;
+        assert!(result.contains(&format!("| name{id} ")), "{result}");
+    }
+
+    Ok(())
+}
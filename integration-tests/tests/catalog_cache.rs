@@ -0,0 +1,114 @@
+use indexlake::catalog::{
+    CachedCatalog, Catalog, CatalogDatabase, CatalogSchemaRef, IsolationLevel, RowStream,
+    Transaction,
+};
+use indexlake::{ILResult, LakeClient};
+use indexlake_integration_tests::data::prepare_testing_table;
+use indexlake_integration_tests::{catalog_sqlite, init_env_logger, storage_fs};
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+use std::time::Duration;
+
+/// Counts queries reaching the wrapped catalog outside of transactions.
+#[derive(Debug)]
+struct CountingCatalog {
+    inner: Arc<dyn Catalog>,
+    queries: AtomicUsize,
+}
+
+#[async_trait::async_trait]
+impl Catalog for CountingCatalog {
+    fn database(&self) -> CatalogDatabase {
+        self.inner.database()
+    }
+
+    async fn query(&self, sql: &str, schema: CatalogSchemaRef) -> ILResult<RowStream<'static>> {
+        self.queries.fetch_add(1, Ordering::SeqCst);
+        self.inner.query(sql, schema).await
+    }
+
+    async fn transaction(&self) -> ILResult<Box<dyn Transaction>> {
+        self.inner.transaction().await
+    }
+
+    async fn begin_transaction(&self, level: IsolationLevel) -> ILResult<Box<dyn Transaction>> {
+        self.inner.begin_transaction(level).await
+    }
+}
+
+fn counting_catalog() -> Arc<CountingCatalog> {
+    Arc::new(CountingCatalog {
+        inner: catalog_sqlite(),
+        queries: AtomicUsize::new(0),
+    })
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cached_table_loads() -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let counting = counting_catalog();
+    let catalog = Arc::new(CachedCatalog::new(
+        counting.clone(),
+        Duration::from_secs(600),
+    ));
+    let client = LakeClient::new(catalog, storage_fs());
+    prepare_testing_table(&client, "cached_table_loads").await?;
+
+    client
+        .load_table("test_namespace", "cached_table_loads")
+        .await?;
+    let queries = counting.queries.load(Ordering::SeqCst);
+    for _ in 0..1000 {
+        client
+            .load_table("test_namespace", "cached_table_loads")
+            .await?;
+    }
+    assert_eq!(counting.queries.load(Ordering::SeqCst), queries);
+
+    // DDL through the same client invalidates the cache
+    client
+        .rename_table("test_namespace", "cached_table_loads", "renamed_table")
+        .await?;
+    assert!(
+        client
+            .load_table("test_namespace", "cached_table_loads")
+            .await
+            .is_err()
+    );
+    client.load_table("test_namespace", "renamed_table").await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cached_entries_expire() -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let counting = counting_catalog();
+    let catalog = Arc::new(CachedCatalog::new(
+        counting.clone(),
+        Duration::from_millis(200),
+    ));
+    let client = LakeClient::new(catalog, storage_fs());
+    prepare_testing_table(&client, "cached_entries_expire").await?;
+
+    client
+        .load_table("test_namespace", "cached_entries_expire")
+        .await?;
+    let queries = counting.queries.load(Ordering::SeqCst);
+    client
+        .load_table("test_namespace", "cached_entries_expire")
+        .await?;
+    assert_eq!(counting.queries.load(Ordering::SeqCst), queries);
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    client
+        .load_table("test_namespace", "cached_entries_expire")
+        .await?;
+    assert!(counting.queries.load(Ordering::SeqCst) > queries);
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use indexlake::catalog::CatalogDump;
+use indexlake::expr::{col, lit};
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger, storage_fs,
+};
+use indexlake_integration_tests::{data::prepare_testing_table, utils::full_table_scan};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, async { catalog_postgres().await }, storage_fs())]
+#[case(async { catalog_postgres().await }, async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_sqlite() }, async { catalog_mysql().await }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_sqlite() }, async { catalog_duckdb().await }, storage_fs()))]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, async { catalog_memory() }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn dump_and_restore_catalog(
+    #[future(awt)]
+    #[case]
+    source: Arc<dyn Catalog>,
+    #[future(awt)]
+    #[case]
+    target: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    source.migrate().await?;
+    let source_client = LakeClient::new(source.clone(), storage.clone());
+    let table = prepare_testing_table(&source_client, "dump_and_restore_catalog").await?;
+    // one flushed and one inline row are deleted
+    table.delete(&col("age").lt_eq(lit(20))).await?;
+    table.delete(&col("age").eq(lit(23))).await?;
+    let table_str_before = full_table_scan(&table).await?;
+
+    let dump = CatalogDump::from_json(&source.export_dump().await?.to_json()?)?;
+    target.import_dump(&dump).await?;
+
+    let target_client = LakeClient::new(target, storage);
+    let table = target_client
+        .load_table("test_namespace", "dump_and_restore_catalog")
+        .await?;
+    let table_str_after = full_table_scan(&table).await?;
+    assert_eq!(table_str_before, table_str_after);
+
+    // ids continue after the restored ones
+    let namespace_id = target_client
+        .create_namespace("another_namespace", HashMap::new())
+        .await?;
+    assert_eq!(namespace_id, 2);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn restore_into_non_empty_catalog() -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let source = catalog_memory();
+    let source_client = LakeClient::new(source.clone(), storage_fs());
+    prepare_testing_table(&source_client, "restore_into_non_empty_catalog").await?;
+    let dump = source.export_dump().await?;
+
+    let target = catalog_memory();
+    target.import_dump(&dump).await?;
+    let result = target.import_dump(&dump).await;
+    assert!(result.unwrap_err().to_string().contains("empty catalog"));
+
+    Ok(())
+}
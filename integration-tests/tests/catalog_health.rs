@@ -0,0 +1,54 @@
+use indexlake::{LakeClient, catalog::Catalog};
+use indexlake_catalog_postgres::PostgresCatalog;
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::{
+    catalog_mariadb, catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite,
+    init_env_logger, setup_postgres_db, storage_fs,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() })]
+#[case(async { catalog_postgres().await })]
+#[case(async { catalog_mysql().await })]
+#[case(async { catalog_mariadb().await })]
+#[case(async { catalog_memory() })]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }))]
+#[tokio::test(flavor = "multi_thread")]
+async fn health_check(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let health = catalog.health_check().await?;
+    assert!(!health.version.is_empty());
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn postgres_reconnect() -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let docker_compose = setup_postgres_db().await;
+    let catalog = Arc::new(
+        PostgresCatalog::try_new("localhost", 5432, "postgres", "password", Some("postgres"))
+            .await?,
+    );
+    let client = LakeClient::new(catalog.clone(), storage_fs());
+    client
+        .create_namespace("before_restart", HashMap::new())
+        .await?;
+
+    docker_compose.restart();
+    tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+
+    catalog.health_check().await?;
+    client
+        .create_namespace("after_restart", HashMap::new())
+        .await?;
+    assert!(client.get_namespace_id("before_restart").await?.is_some());
+
+    Ok(())
+}
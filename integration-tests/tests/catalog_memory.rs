@@ -0,0 +1,97 @@
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+use indexlake_catalog_memory::MemoryCatalog;
+use indexlake_integration_tests::{
+    data::prepare_testing_table, storage_fs, utils::full_table_scan,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn memory_catalog_snapshot() -> Result<(), Box<dyn std::error::Error>> {
+    let catalog = MemoryCatalog::new();
+    let client = LakeClient::new(Arc::new(catalog.clone()), storage_fs());
+    let namespace_id = client
+        .create_namespace("test_namespace", HashMap::new())
+        .await?;
+
+    let snapshot = catalog.snapshot().await?;
+    client
+        .create_namespace("another_namespace", HashMap::new())
+        .await?;
+
+    let snapshot_client = LakeClient::new(Arc::new(snapshot), storage_fs());
+    assert_eq!(
+        snapshot_client.get_namespace_id("test_namespace").await?,
+        Some(namespace_id)
+    );
+    assert_eq!(
+        snapshot_client
+            .get_namespace_id("another_namespace")
+            .await?,
+        None
+    );
+    assert!(
+        client
+            .get_namespace_id("another_namespace")
+            .await?
+            .is_some()
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn memory_catalog_rollback() -> Result<(), Box<dyn std::error::Error>> {
+    let catalog = MemoryCatalog::new();
+
+    let mut transaction = catalog.transaction().await?;
+    transaction
+        .execute("INSERT INTO indexlake_namespace (namespace_id, namespace_name) VALUES (1, 'ns')")
+        .await?;
+    transaction.rollback().await?;
+    drop(transaction);
+
+    let mut transaction = catalog.transaction().await?;
+    transaction
+        .execute("INSERT INTO indexlake_namespace (namespace_id, namespace_name) VALUES (2, 'ns')")
+        .await?;
+    drop(transaction);
+
+    let client = LakeClient::new(Arc::new(catalog), storage_fs());
+    assert_eq!(client.get_namespace_id("ns").await?, None);
+
+    Ok(())
+}
+
+// Ephemeral table: both the catalog and the data files live in memory, nothing is left behind
+#[tokio::test(flavor = "multi_thread")]
+async fn ephemeral_table() -> Result<(), Box<dyn std::error::Error>> {
+    let storage = Arc::new(Storage::new_memory());
+    let client = LakeClient::new(Arc::new(MemoryCatalog::new()), storage.clone());
+    let table = prepare_testing_table(&client, "ephemeral_table").await?;
+
+    let table_str = full_table_scan(&table).await?;
+    assert_eq!(
+        table_str,
+        r#"+-------------------+---------+-----+
+| _indexlake_row_id | name    | age |
++-------------------+---------+-----+
+| 1                 | Alice   | 20  |
+| 2                 | Bob     | 21  |
+| 3                 | Charlie | 22  |
+| 4                 | David   | 23  |
++-------------------+---------+-----+"#,
+    );
+    let table_dir = format!("{}/{}", table.namespace_id, table.table_id);
+    assert!(!storage.list_files(&table_dir).await?.is_empty());
+
+    table.drop().await?;
+    assert!(
+        client
+            .load_table("test_namespace", "ephemeral_table")
+            .await
+            .is_err()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use indexlake::{
+    ILError,
+    catalog::{Catalog, PoolStatus},
+};
+use indexlake_catalog_postgres::PostgresCatalog;
+use indexlake_integration_tests::{catalog_postgres, catalog_sqlite};
+use std::{sync::Arc, time::Duration};
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, false)]
+#[case(async { catalog_postgres().await }, true)]
+#[tokio::test(flavor = "multi_thread")]
+async fn pool_status(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] pooled: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !pooled {
+        assert_eq!(catalog.pool_status(), None);
+        return Ok(());
+    }
+
+    let mut transaction = catalog.transaction().await?;
+    let PoolStatus {
+        active_connections, ..
+    } = catalog.pool_status().unwrap();
+    assert_eq!(active_connections, 1);
+
+    transaction.rollback().await?;
+    drop(transaction);
+    let PoolStatus {
+        active_connections,
+        idle_connections,
+    } = catalog.pool_status().unwrap();
+    assert_eq!(active_connections, 0);
+    assert!(idle_connections >= 1);
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, false)]
+#[case(async { catalog_postgres().await }, true)]
+#[tokio::test(flavor = "multi_thread")]
+async fn pool_exhausted(
+    #[future(awt)]
+    #[case]
+    _catalog: Arc<dyn Catalog>,
+    #[case] pooled: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !pooled {
+        return Ok(());
+    }
+
+    let catalog = PostgresCatalog::builder()
+        .host("localhost")
+        .port(5432)
+        .user("postgres")
+        .password("password")
+        .dbname("postgres")
+        .max_connections(1)
+        .connect_timeout(Duration::from_secs(1))
+        .build()
+        .await?;
+
+    let _transaction = catalog.transaction().await?;
+    let result = catalog.transaction().await;
+    assert!(matches!(result, Err(ILError::CatalogPoolExhausted(_))));
+
+    Ok(())
+}
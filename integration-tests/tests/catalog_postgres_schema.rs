@@ -0,0 +1,105 @@
+use arrow::array::{Int64Array, RecordBatch};
+use arrow::datatypes::{DataType, Field, Schema};
+use indexlake::{
+    LakeClient,
+    catalog::Catalog,
+    storage::Storage,
+    table::{TableConfig, TableCreation},
+};
+use indexlake_catalog_postgres::PostgresCatalog;
+use indexlake_integration_tests::{
+    init_env_logger, setup_postgres_db, storage_fs, utils::full_table_scan,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+async fn schema_catalog(schema: &str) -> Result<Arc<dyn Catalog>, Box<dyn std::error::Error>> {
+    let catalog = PostgresCatalog::builder()
+        .host("localhost")
+        .port(5432)
+        .user("postgres")
+        .password("password")
+        .dbname("postgres")
+        .schema(schema)
+        .build()
+        .await?;
+    catalog.migrate().await?;
+    Ok(Arc::new(catalog))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn postgres_schema_isolation() -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+    let _docker_compose = setup_postgres_db().await;
+
+    // Data file paths are derived from catalog ids, so each catalog needs its own storage root
+    let storage_root = format!(
+        "{}/tmp/postgres_schema/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        uuid::Uuid::new_v4()
+    );
+    let client_a = LakeClient::new(
+        schema_catalog("catalog_a").await?,
+        Arc::new(Storage::new_fs(format!("{storage_root}/a"))),
+    );
+    let client_b = LakeClient::new(
+        schema_catalog("catalog_b").await?,
+        Arc::new(Storage::new_fs(format!("{storage_root}/b"))),
+    );
+
+    let table_schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, false)]));
+    for client in [&client_a, &client_b] {
+        client
+            .create_namespace("test_namespace", HashMap::new())
+            .await?;
+        client
+            .create_table(TableCreation {
+                namespace_name: "test_namespace".to_string(),
+                table_name: "postgres_schema_isolation".to_string(),
+                schema: table_schema.clone(),
+                config: TableConfig::default(),
+            })
+            .await?;
+    }
+
+    let table_a = client_a
+        .load_table("test_namespace", "postgres_schema_isolation")
+        .await?;
+    let record_batch = RecordBatch::try_new(
+        table_schema.clone(),
+        vec![Arc::new(Int64Array::from(vec![1, 2, 3]))],
+    )?;
+    table_a.insert(&record_batch).await?;
+
+    let table_b = client_b
+        .load_table("test_namespace", "postgres_schema_isolation")
+        .await?;
+    assert!(full_table_scan(&table_a).await?.contains("| 3"));
+    assert!(!full_table_scan(&table_b).await?.contains("| 3"));
+
+    // The default schema is left untouched
+    let client_public = LakeClient::new(
+        Arc::new(
+            PostgresCatalog::try_new("localhost", 5432, "postgres", "password", Some("postgres"))
+                .await?,
+        ),
+        storage_fs(),
+    );
+    assert_eq!(
+        client_public.get_namespace_id("test_namespace").await?,
+        None
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn postgres_schema_invalid_name() -> Result<(), Box<dyn std::error::Error>> {
+    let result = PostgresCatalog::builder()
+        .schema("catalog; DROP SCHEMA public")
+        .build()
+        .await;
+    assert!(result.is_err());
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use indexlake::LakeClient;
+use indexlake_catalog_postgres::{PostgresCatalog, PostgresCatalogBuilder, PostgresSslMode};
+use indexlake_integration_tests::{setup_postgres_tls_db, storage_fs};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn tls_catalog_builder(host: &str) -> PostgresCatalogBuilder {
+    PostgresCatalog::builder()
+        .host(host)
+        .port(5433)
+        .user("postgres")
+        .password("password")
+        .dbname("postgres")
+}
+
+fn root_cert_path() -> String {
+    format!(
+        "{}/testdata/postgres-tls/certs/ca.crt",
+        env!("CARGO_MANIFEST_DIR")
+    )
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn postgres_tls_modes() -> Result<(), Box<dyn std::error::Error>> {
+    let _docker_compose = setup_postgres_tls_db().await;
+
+    for ssl_mode in [
+        PostgresSslMode::Require,
+        PostgresSslMode::VerifyCa,
+        PostgresSslMode::VerifyFull,
+    ] {
+        let catalog = tls_catalog_builder("localhost")
+            .ssl_mode(ssl_mode)
+            .ssl_root_cert(root_cert_path())
+            .build()
+            .await?;
+        let client = LakeClient::new(Arc::new(catalog), storage_fs());
+        client
+            .create_namespace(&format!("{ssl_mode:?}"), HashMap::new())
+            .await?;
+    }
+
+    // Without the self-signed CA the server certificate can not be verified
+    let catalog = tls_catalog_builder("localhost")
+        .ssl_mode(PostgresSslMode::VerifyCa)
+        .build()
+        .await?;
+    let client = LakeClient::new(Arc::new(catalog), storage_fs());
+    assert!(client.get_namespace_id("Require").await.is_err());
+
+    // The certificate is issued for localhost, hostname verification must reject other names
+    let catalog = tls_catalog_builder("127.0.0.1")
+        .ssl_mode(PostgresSslMode::VerifyFull)
+        .ssl_root_cert(root_cert_path())
+        .build()
+        .await?;
+    let client = LakeClient::new(Arc::new(catalog), storage_fs());
+    assert!(client.get_namespace_id("Require").await.is_err());
+
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use indexlake::catalog::{
+    Catalog, CatalogDatabase, CatalogSchemaRef, IsolationLevel, RetryPolicy, RetryingCatalog,
+    RowStream, Transaction,
+};
+use indexlake::{ILError, ILResult, LakeClient};
+use indexlake_integration_tests::{catalog_memory, init_env_logger, storage_fs};
+use std::collections::HashMap;
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+use std::time::Duration;
+
+/// Fails the first `failures` transactions with the error built by `error`.
+#[derive(Debug)]
+struct FlakyCatalog {
+    inner: Arc<dyn Catalog>,
+    failures: usize,
+    error: fn() -> ILError,
+    attempts: AtomicUsize,
+}
+
+impl FlakyCatalog {
+    fn new(failures: usize, error: fn() -> ILError) -> Self {
+        Self {
+            inner: catalog_memory(),
+            failures,
+            error,
+            attempts: AtomicUsize::new(0),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Catalog for FlakyCatalog {
+    fn database(&self) -> CatalogDatabase {
+        self.inner.database()
+    }
+
+    async fn query(&self, sql: &str, schema: CatalogSchemaRef) -> ILResult<RowStream<'static>> {
+        self.inner.query(sql, schema).await
+    }
+
+    async fn transaction(&self) -> ILResult<Box<dyn Transaction>> {
+        if self.attempts.fetch_add(1, Ordering::SeqCst) < self.failures {
+            return Err((self.error)());
+        }
+        self.inner.transaction().await
+    }
+
+    async fn begin_transaction(&self, level: IsolationLevel) -> ILResult<Box<dyn Transaction>> {
+        self.inner.begin_transaction(level).await
+    }
+}
+
+fn retry_policy() -> RetryPolicy {
+    RetryPolicy::new(3, Duration::from_millis(10), Duration::from_millis(50))
+}
+
+fn connection_reset() -> ILError {
+    ILError::CatalogTransient("connection reset by peer".to_string())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn retry_transient_failure() -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let flaky = Arc::new(FlakyCatalog::new(2, connection_reset));
+    let catalog = Arc::new(RetryingCatalog::new(flaky.clone(), retry_policy()));
+    let client = LakeClient::new(catalog, storage_fs());
+
+    let namespace_id = client
+        .create_namespace("test_namespace", HashMap::new())
+        .await?;
+    assert_eq!(flaky.attempts.load(Ordering::SeqCst), 3);
+    assert_eq!(
+        client.get_namespace_id("test_namespace").await?,
+        Some(namespace_id)
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn retry_gives_up_after_max_attempts() -> Result<(), Box<dyn std::error::Error>> {
+    let flaky = Arc::new(FlakyCatalog::new(5, connection_reset));
+    let catalog = Arc::new(RetryingCatalog::new(flaky.clone(), retry_policy()));
+    let client = LakeClient::new(catalog, storage_fs());
+
+    let result = client
+        .create_namespace("test_namespace", HashMap::new())
+        .await;
+    assert!(matches!(result, Err(ILError::CatalogTransient(_))));
+    assert_eq!(flaky.attempts.load(Ordering::SeqCst), 3);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn retry_skips_non_transient_failure() -> Result<(), Box<dyn std::error::Error>> {
+    let flaky = Arc::new(FlakyCatalog::new(1, || {
+        ILError::CatalogError("password authentication failed".to_string())
+    }));
+    let catalog = Arc::new(RetryingCatalog::new(flaky.clone(), retry_policy()));
+    let client = LakeClient::new(catalog, storage_fs());
+
+    let result = client
+        .create_namespace("test_namespace", HashMap::new())
+        .await;
+    assert!(matches!(result, Err(ILError::CatalogError(_))));
+    assert_eq!(flaky.attempts.load(Ordering::SeqCst), 1);
+
+    Ok(())
+}
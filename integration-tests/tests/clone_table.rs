@@ -0,0 +1,173 @@
+use arrow::array::{Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use indexlake::expr::{col, lit};
+use indexlake::index::Index;
+use indexlake::table::{
+    CompactOptions, IndexCreation, SnapshotOperation, Table, TableConfig, TableCreation,
+    TableIdent, TableScan,
+};
+use indexlake::{ILError, LakeClient, catalog::Catalog, storage::Storage};
+use indexlake_index_hash::{HashIndex, HashIndexParams};
+use indexlake_integration_tests::data::create_namespace_if_not_exists;
+use indexlake_integration_tests::utils::{full_table_scan, table_scan};
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger, storage_fs,
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn users_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("user", DataType::Utf8, true),
+    ]))
+}
+
+fn users_batch(ids: Vec<i32>) -> Result<RecordBatch, Box<dyn std::error::Error>> {
+    let users = ids.iter().map(|id| format!("user{id}")).collect::<Vec<_>>();
+    Ok(RecordBatch::try_new(
+        users_schema(),
+        vec![
+            Arc::new(Int32Array::from(ids)),
+            Arc::new(StringArray::from(users)),
+        ],
+    )?)
+}
+
+async fn file_paths(
+    storage: &Storage,
+    prefix: &str,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut paths = storage
+        .list_files(prefix)
+        .await?
+        .into_iter()
+        .map(|file| file.relative_path)
+        .collect::<Vec<_>>();
+    paths.sort();
+    Ok(paths)
+}
+
+async fn user_lookup(table: &Table, user: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let scan = TableScan::default().with_filters(vec![col("user").eq(lit(user.to_string()))]);
+    Ok(table_scan(table, scan).await?)
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_fs())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[tokio::test(flavor = "multi_thread")]
+async fn clone_table(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage.clone());
+    client.register_index(Arc::new(HashIndex))?;
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    create_namespace_if_not_exists(&client, "clone_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "clone_source".to_string(),
+            schema: users_schema(),
+            config: TableConfig {
+                inline_row_count_limit: 3,
+                ..Default::default()
+            },
+        })
+        .await?;
+    let mut source = client.load_table("test_namespace", "clone_source").await?;
+    source
+        .create_index(IndexCreation {
+            name: "user_index".to_string(),
+            kind: HashIndex.kind().to_string(),
+            key_columns: vec!["user".to_string()],
+            include_columns: vec![],
+            params: Arc::new(HashIndexParams::default()),
+            where_predicate: None,
+        })
+        .await?;
+    // two data files and an inline row
+    for ids in [vec![1, 2, 3, 4], vec![5, 6, 7, 8]] {
+        source.insert(&users_batch(ids)?).await?;
+        // wait for dump task to finish
+        tokio::time::sleep(Duration::from_secs(2)).await;
+    }
+    source.insert(&users_batch(vec![9])?).await?;
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    let source_dir = source.table_dir();
+    let source_files = file_paths(&storage, &source_dir).await?;
+    let source_str = full_table_scan(&source).await?;
+
+    let source_ident = TableIdent::new("test_namespace", "clone_source");
+    let clone_ident = TableIdent::new("clone_namespace", "clone_target");
+    client.clone_table(&source_ident, &clone_ident).await?;
+    let mut clone = client.load_table("clone_namespace", "clone_target").await?;
+    let clone_dir = clone.table_dir();
+
+    // the clone reads the files of the source, nothing is copied
+    assert_eq!(full_table_scan(&clone).await?, source_str);
+    assert_eq!(file_paths(&storage, &source_dir).await?, source_files);
+    assert!(file_paths(&storage, &clone_dir).await?.is_empty());
+    let snapshots = clone.snapshots().await?;
+    assert_eq!(snapshots.len(), 1);
+    assert_eq!(snapshots[0].operation, Some(SnapshotOperation::Clone));
+    assert_eq!(
+        user_lookup(&clone, "user6").await?,
+        user_lookup(&source, "user6").await?
+    );
+
+    // a clone can not replace an existing table
+    assert!(matches!(
+        client.clone_table(&source_ident, &clone_ident).await,
+        Err(ILError::InvalidInput(_))
+    ));
+
+    // writes to either table are not seen by the other
+    source.insert(&users_batch(vec![10])?).await?;
+    source.delete(&col("id").eq(lit(1))).await?;
+    clone.delete(&col("id").eq(lit(2))).await?;
+    clone.insert(&users_batch(vec![20, 21, 22, 23])?).await?;
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    let source_str = full_table_scan(&source).await?;
+    let clone_str = full_table_scan(&clone).await?;
+    assert!(source_str.contains("user10") && !source_str.contains("user1 "));
+    assert!(source_str.contains("user2 ") && !source_str.contains("user20"));
+    assert!(clone_str.contains("user1 ") && !clone_str.contains("user2 "));
+    assert!(clone_str.contains("user20") && !clone_str.contains("user10"));
+    assert!(!user_lookup(&clone, "user10").await?.contains("user10"));
+    assert!(user_lookup(&clone, "user21").await?.contains("user21"));
+    // the clone writes into its own directory
+    assert!(!file_paths(&storage, &clone_dir).await?.is_empty());
+
+    // files of the source still read by the clone survive compaction and vacuum of the source
+    source.compact(CompactOptions::default()).await?;
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    let report = source.vacuum(Duration::from_secs(1)).await?;
+    assert!(
+        report
+            .deleted_files
+            .iter()
+            .all(|path| !source_files.contains(path))
+    );
+    assert_eq!(full_table_scan(&clone).await?, clone_str);
+
+    // renaming a column of the clone leaves the source alone
+    clone.rename_column("id", "user_id").await?;
+    let clone = client.load_table("clone_namespace", "clone_target").await?;
+    let clone_str = full_table_scan(&clone).await?;
+    assert!(clone_str.contains("| user_id ") && clone_str.contains("user1 "));
+    assert_eq!(full_table_scan(&source).await?, source_str);
+
+    // purging the source keeps the files the clone refers to
+    client.drop_table(&source_ident, true).await?;
+    assert_eq!(full_table_scan(&clone).await?, clone_str);
+
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use arrow::array::{Int64Array, RecordBatch};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use futures::TryStreamExt;
+use indexlake::expr::{Expr, col, lit};
+use indexlake::table::{Clustering, CompactOptions, Table, TableConfig, TableCreation, TableScan};
+use indexlake::{ILError, LakeClient, catalog::Catalog, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::{
+    counted_storage, create_namespace_if_not_exists, data_files_opened,
+};
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger,
+};
+use std::sync::Arc;
+
+fn table_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("x", DataType::Int64, false),
+        Field::new("y", DataType::Int64, false),
+    ]))
+}
+
+/// Flushes eight data files of 64 points each, every file spreading over the whole 64 x 64
+/// grid, then compacts them into four files.
+async fn prepare_table(
+    client: &LakeClient,
+    table_name: &str,
+    clustering: Clustering,
+) -> Result<Table, Box<dyn std::error::Error>> {
+    create_namespace_if_not_exists(client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: table_schema(),
+            config: TableConfig {
+                inline_row_count_limit: 1000,
+                parquet_row_group_size: 16,
+                clustering,
+                ..Default::default()
+            },
+        })
+        .await?;
+    let table = client.load_table("test_namespace", table_name).await?;
+
+    for file in 0..8i64 {
+        let (xs, ys): (Vec<i64>, Vec<i64>) = (0..64i64)
+            .map(|i| (i % 8 * 8 + file, i / 8 * 8 + (file + i) % 8))
+            .unzip();
+        table
+            .insert(&RecordBatch::try_new(
+                table_schema(),
+                vec![
+                    Arc::new(Int64Array::from(xs)),
+                    Arc::new(Int64Array::from(ys)),
+                ],
+            )?)
+            .await?;
+        table.flush().await?;
+    }
+
+    // the clustered table splits its rows into four files of the target size, the unclustered
+    // one merges pairs of files into four files as well
+    let data_file_bytes = table.storage_stats().await?.data_file_bytes;
+    let mut options = CompactOptions::default().with_target_file_size(data_file_bytes / 4 + 1);
+    if table.config.clustering == Clustering::None {
+        options = options.with_max_files_per_group(2usize);
+    }
+    let report = table.compact(options).await?;
+    assert_eq!(report.rewritten_files, 8);
+    assert_eq!(report.written_files, 4);
+    Ok(table)
+}
+
+/// Scans the rows matching `filter`, returning their number and the data files opened.
+async fn scan_count(
+    table: &Table,
+    storage: &Storage,
+    filter: Expr,
+) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+    storage.reset_read_stats();
+    let scan = TableScan::default().with_filters(vec![filter]);
+    let batches = table.scan(scan).await?.try_collect::<Vec<_>>().await?;
+    let rows = batches.iter().map(|batch| batch.num_rows()).sum();
+    Ok((rows, data_files_opened(storage)))
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn zorder_clustering_prunes_files(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage.clone());
+    let unclustered = prepare_table(&client, "zorder_unclustered", Clustering::None).await?;
+    let clustering = Clustering::ZOrder(vec!["x".to_string(), "y".to_string()]);
+    let clustered = prepare_table(&client, "zorder_clustered", clustering.clone()).await?;
+
+    // the clustering is persisted with the table
+    let reloaded = client
+        .load_table("test_namespace", "zorder_clustered")
+        .await?;
+    assert_eq!(reloaded.config.clustering, clustering);
+
+    let filters = [
+        col("x").lt(lit(16i64)).and(col("y").lt(lit(16i64))),
+        col("x").gt_eq(lit(48i64)),
+        col("y").gt_eq(lit(48i64)),
+    ];
+    for filter in filters {
+        let (unclustered_rows, unclustered_opened) =
+            scan_count(&unclustered, &storage, filter.clone()).await?;
+        let (clustered_rows, clustered_opened) =
+            scan_count(&clustered, &storage, filter.clone()).await?;
+        assert_eq!(clustered_rows, unclustered_rows, "{filter}");
+        // every unclustered file spreads over the whole grid, the clustered files over a
+        // quadrant each
+        assert_eq!(unclustered_opened, 4, "{filter}");
+        assert!(clustered_opened <= 2, "{filter}");
+    }
+    let filter = col("x").lt(lit(16i64)).and(col("y").lt(lit(16i64)));
+    assert_eq!(scan_count(&clustered, &storage, filter).await?, (32, 1));
+
+    // clustering columns can not be dropped
+    let mut clustered = clustered;
+    assert!(matches!(
+        clustered.drop_column("y", false).await,
+        Err(ILError::InvalidInput(_))
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,311 @@
+use arrow::array::{AsArray, Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Int64Type, Schema};
+use futures::TryStreamExt;
+use indexlake::catalog::Scalar;
+use indexlake::table::{ColumnDefault, DefaultMode, Table, TableConfig, TableCreation, TableScan};
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::create_namespace_if_not_exists;
+use indexlake_integration_tests::utils::table_scan;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger, storage_fs,
+    storage_s3,
+};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+async fn create_table(
+    client: &LakeClient,
+    table_name: &str,
+    fields: Vec<Field>,
+) -> Result<Table, Box<dyn std::error::Error>> {
+    create_namespace_if_not_exists(client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: Arc::new(Schema::new(fields)),
+            config: TableConfig::default(),
+        })
+        .await?;
+    Ok(client.load_table("test_namespace", table_name).await?)
+}
+
+fn ids_batch(ids: Vec<i32>) -> Result<RecordBatch, arrow::error::ArrowError> {
+    RecordBatch::try_new(
+        Arc::new(Schema::new(vec![Field::new("id", DataType::Int32, false)])),
+        vec![Arc::new(Int32Array::from(ids))],
+    )
+}
+
+fn millis_since_epoch() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn column_defaults_of_every_type(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let literal = |name: &str, data_type: DataType, value: Scalar| {
+        ColumnDefault::literal(value).declare_on(Field::new(name, data_type, false))
+    };
+    let fields = vec![
+        Field::new("id", DataType::Int32, false),
+        literal("boolean", DataType::Boolean, Scalar::Boolean(Some(true)))?,
+        literal("int16", DataType::Int16, Scalar::Int16(Some(16)))?,
+        literal("int32", DataType::Int32, Scalar::Int32(Some(32)))?,
+        literal("int64", DataType::Int64, Scalar::Int64(Some(64)))?,
+        literal("float32", DataType::Float32, Scalar::Float32(Some(3.5)))?,
+        literal("float64", DataType::Float64, Scalar::Float64(Some(6.25)))?,
+        literal(
+            "utf8",
+            DataType::Utf8,
+            Scalar::Utf8(Some("text".to_string())),
+        )?,
+        literal("binary", DataType::Binary, Scalar::Binary(Some(vec![1, 2])))?,
+        ColumnDefault::now().declare_on(Field::new("created", DataType::Int64, false))?,
+        ColumnDefault::uuid().declare_on(Field::new("uuid_utf8", DataType::Utf8, false))?,
+        ColumnDefault::uuid().declare_on(Field::new("uuid_binary", DataType::Binary, false))?,
+    ];
+    let table = create_table(&client, "column_defaults_of_every_type", fields).await?;
+    // the defaults are kept in the catalog, not in the schema
+    assert_eq!(table.column_defaults.len(), 11);
+    assert!(
+        table
+            .schema
+            .fields()
+            .iter()
+            .all(|field| field.metadata().is_empty())
+    );
+
+    let before = millis_since_epoch();
+    table.insert(&ids_batch(vec![1, 2])?).await?;
+    let after = millis_since_epoch();
+
+    let scan = TableScan::default().with_columns(Some(
+        [
+            "_indexlake_row_id",
+            "id",
+            "boolean",
+            "int16",
+            "int32",
+            "int64",
+            "float32",
+            "float64",
+            "utf8",
+            "binary",
+        ]
+        .iter()
+        .map(|name| name.to_string())
+        .collect(),
+    ));
+    assert_eq!(
+        table_scan(&table, scan).await?,
+        r#"+-------------------+----+---------+-------+-------+-------+---------+---------+------+--------+
+| _indexlake_row_id | id | boolean | int16 | int32 | int64 | float32 | float64 | utf8 | binary |
++-------------------+----+---------+-------+-------+-------+---------+---------+------+--------+
+| 1                 | 1  | true    | 16    | 32    | 64    | 3.5     | 6.25    | text | 0102   |
+| 2                 | 2  | true    | 16    | 32    | 64    | 3.5     | 6.25    | text | 0102   |
++-------------------+----+---------+-------+-------+-------+---------+---------+------+--------+"#,
+    );
+
+    let scan = TableScan::default().with_columns(Some(vec![
+        "created".to_string(),
+        "uuid_utf8".to_string(),
+        "uuid_binary".to_string(),
+    ]));
+    let batches = table.scan(scan).await?.try_collect::<Vec<_>>().await?;
+    let mut uuids = Vec::new();
+    for batch in batches {
+        for created in batch.column(0).as_primitive::<Int64Type>().values() {
+            assert!(*created >= before && *created <= after);
+        }
+        for uuid in batch.column(1).as_string::<i32>().iter() {
+            uuids.push(uuid::Uuid::parse_str(uuid.unwrap())?);
+        }
+        for uuid in batch.column(2).as_binary::<i32>().iter() {
+            uuids.push(uuid::Uuid::from_slice(uuid.unwrap())?);
+        }
+    }
+    uuids.sort();
+    uuids.dedup();
+    assert_eq!(uuids.len(), 4);
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn column_defaults_on_null(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let fields = vec![
+        Field::new("id", DataType::Int32, false),
+        ColumnDefault::literal(Scalar::Utf8(Some("missing".to_string())))
+            .declare_on(Field::new("on_missing", DataType::Utf8, true))?,
+        ColumnDefault::literal(Scalar::Utf8(Some("null".to_string())))
+            .with_mode(DefaultMode::OnNull)
+            .declare_on(Field::new("on_null", DataType::Utf8, true))?,
+        ColumnDefault::literal(Scalar::Int32(Some(0)))
+            .with_mode(DefaultMode::OnNull)
+            .declare_on(Field::new("required", DataType::Int32, false))?,
+    ];
+    let table = create_table(&client, "column_defaults_on_null", fields).await?;
+
+    // nulls are only replaced in columns defaulting on nulls, also in non-nullable ones
+    let batch = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("on_missing", DataType::Utf8, true),
+            Field::new("on_null", DataType::Utf8, true),
+            Field::new("required", DataType::Int32, true),
+        ])),
+        vec![
+            Arc::new(Int32Array::from(vec![1, 2])),
+            Arc::new(StringArray::from(vec![None, Some("a")])),
+            Arc::new(StringArray::from(vec![None, Some("b")])),
+            Arc::new(Int32Array::from(vec![None, Some(5)])),
+        ],
+    )?;
+    table.insert(&batch).await?;
+    table.insert(&ids_batch(vec![3])?).await?;
+
+    // a table loaded again applies the defaults as well
+    let table = client
+        .load_table("test_namespace", "column_defaults_on_null")
+        .await?;
+    table.insert(&ids_batch(vec![4])?).await?;
+
+    assert_eq!(
+        table_scan(&table, TableScan::default()).await?,
+        r#"+-------------------+----+------------+---------+----------+
+| _indexlake_row_id | id | on_missing | on_null | required |
++-------------------+----+------------+---------+----------+
+| 1                 | 1  |            | null    | 0        |
+| 2                 | 2  | a          | b       | 5        |
+| 3                 | 3  | missing    | null    | 0        |
+| 4                 | 4  | missing    | null    | 0        |
++-------------------+----+------------+---------+----------+"#,
+    );
+
+    // nulls in a non-nullable column without such a default are rejected
+    let batch = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![Field::new("id", DataType::Int32, true)])),
+        vec![Arc::new(Int32Array::from(vec![None]))],
+    )?;
+    assert!(table.insert(&batch).await.is_err());
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn column_default_type_mismatch(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let int_default = ColumnDefault::literal(Scalar::Int32(Some(1)));
+    assert!(
+        int_default
+            .declare_on(Field::new("name", DataType::Utf8, true))
+            .is_err()
+    );
+    assert!(
+        ColumnDefault::now()
+            .declare_on(Field::new("created", DataType::Utf8, true))
+            .is_err()
+    );
+    assert!(
+        ColumnDefault::uuid()
+            .declare_on(Field::new("uuid", DataType::Int64, true))
+            .is_err()
+    );
+    assert!(
+        ColumnDefault::literal(Scalar::Int32(None))
+            .declare_on(Field::new("id", DataType::Int32, false))
+            .is_err()
+    );
+
+    // a default declared on a field of another type is rejected at creation and when added
+    let int_field = int_default.declare_on(Field::new("name", DataType::Int32, true))?;
+    let mismatched =
+        Field::new("name", DataType::Utf8, true).with_metadata(int_field.metadata().clone());
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    let result = client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "column_default_type_mismatch".to_string(),
+            schema: Arc::new(Schema::new(vec![
+                Field::new("id", DataType::Int32, false),
+                mismatched.clone(),
+            ])),
+            config: TableConfig::default(),
+        })
+        .await;
+    assert!(result.is_err());
+
+    let mut table = create_table(
+        &client,
+        "column_default_type_mismatch",
+        vec![Field::new("id", DataType::Int32, false)],
+    )
+    .await?;
+    assert!(table.add_column(mismatched, None).await.is_err());
+
+    // a default declared on an added column fills it for rows inserted after
+    table
+        .add_column(
+            ColumnDefault::literal(Scalar::Utf8(Some("new".to_string())))
+                .declare_on(Field::new("name", DataType::Utf8, true))?,
+            None,
+        )
+        .await?;
+    table.insert(&ids_batch(vec![1])?).await?;
+    assert_eq!(
+        table_scan(&table, TableScan::default()).await?,
+        r#"+-------------------+----+------+
+| _indexlake_row_id | id | name |
++-------------------+----+------+
+| 1                 | 1  | new  |
++-------------------+----+------+"#,
+    );
+
+    Ok(())
+}
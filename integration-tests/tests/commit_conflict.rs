@@ -0,0 +1,154 @@
+use arrow::array::{Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use indexlake::expr::{col, lit};
+use indexlake::table::{CompactOptions, Table};
+use indexlake::{ILError, LakeClient, catalog::Catalog, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::prepare_testing_table;
+use indexlake_integration_tests::utils::full_table_scan;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger, storage_fs,
+    storage_s3,
+};
+use std::sync::Arc;
+use tokio::sync::Barrier;
+
+fn person_batch(name: &str, age: i32) -> Result<RecordBatch, ILError> {
+    Ok(RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("name", DataType::Utf8, false),
+            Field::new("age", DataType::Int32, false),
+        ])),
+        vec![
+            Arc::new(StringArray::from(vec![name])),
+            Arc::new(Int32Array::from(vec![age])),
+        ],
+    )?)
+}
+
+/// Inserts a row on top of the current snapshot, retrying on commit conflicts. The first
+/// attempt waits for the other writer to take the same base snapshot. Returns the number of
+/// conflicts.
+async fn insert_with_retry(
+    table: Table,
+    barrier: Arc<Barrier>,
+    name: &str,
+    age: i32,
+) -> Result<usize, ILError> {
+    let mut conflicts = 0;
+    loop {
+        let base_snapshot_id = table.current_snapshot_id().await?;
+        let writer = table.with_base_snapshot(base_snapshot_id);
+        if conflicts == 0 {
+            barrier.wait().await;
+        }
+        match writer.insert(&person_batch(name, age)?).await {
+            Ok(()) => return Ok(conflicts),
+            Err(ILError::CommitConflict(conflict)) => {
+                assert_eq!(conflict.base_snapshot_id, base_snapshot_id);
+                assert!(conflict.current_snapshot_id > base_snapshot_id);
+                conflicts += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn concurrent_writers_conflict(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let table = prepare_testing_table(&client, "concurrent_writers_conflict").await?;
+
+    let barrier = Arc::new(Barrier::new(2));
+    let first = tokio::spawn(insert_with_retry(
+        client
+            .load_table("test_namespace", "concurrent_writers_conflict")
+            .await?,
+        barrier.clone(),
+        "Eve",
+        24,
+    ));
+    let second = tokio::spawn(insert_with_retry(
+        client
+            .load_table("test_namespace", "concurrent_writers_conflict")
+            .await?,
+        barrier,
+        "Frank",
+        25,
+    ));
+    let mut conflicts = vec![first.await??, second.await??];
+    conflicts.sort();
+    // both writers took the same base, exactly one of them committed on top of it
+    assert_eq!(conflicts, vec![0, 1]);
+    let table_str = full_table_scan(&table).await?;
+    assert!(table_str.contains("Eve") && table_str.contains("Frank"));
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn stale_base_snapshot(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let table = prepare_testing_table(&client, "stale_base_snapshot").await?;
+    assert_eq!(table.base_snapshot_id(), None);
+
+    // commits of a handle move its base along
+    let base_snapshot_id = table.current_snapshot_id().await?;
+    let writer = table.with_base_snapshot(base_snapshot_id);
+    let stale = table.with_base_snapshot(base_snapshot_id);
+    writer.insert(&person_batch("Eve", 24)?).await?;
+    writer
+        .delete(&col("name").eq(lit("Alice".to_string())))
+        .await?;
+    assert_eq!(
+        writer.base_snapshot_id(),
+        Some(table.current_snapshot_id().await?)
+    );
+    assert!(writer.base_snapshot_id() > Some(base_snapshot_id));
+
+    // a handle left behind commits nothing
+    let table_str = full_table_scan(&table).await?;
+    assert!(matches!(
+        stale.delete(&col("name").eq(lit("Bob".to_string()))).await,
+        Err(ILError::CommitConflict(_))
+    ));
+    assert!(matches!(
+        stale.compact(CompactOptions::default()).await,
+        Err(ILError::CommitConflict(_))
+    ));
+    assert_eq!(stale.base_snapshot_id(), Some(base_snapshot_id));
+    assert_eq!(full_table_scan(&table).await?, table_str);
+
+    // handles without a base are not checked
+    table.insert(&person_batch("Frank", 25)?).await?;
+    assert!(full_table_scan(&table).await?.contains("Frank"));
+
+    Ok(())
+}
@@ -0,0 +1,263 @@
+use arrow::array::{AsArray, Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::Int32Type;
+use arrow::datatypes::{DataType, Field, Schema};
+use futures::TryStreamExt;
+use indexlake::catalog::{CatalogDataType, CatalogSchema, Column};
+use indexlake::expr::{col, lit};
+use indexlake::table::TableScan;
+use indexlake::table::{CompactOptions, CompactReport, Table, TableConfig, TableCreation};
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::create_namespace_if_not_exists;
+use indexlake_integration_tests::utils::full_table_scan;
+use indexlake_integration_tests::{
+    catalog_mariadb, catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite,
+    init_env_logger, storage_fs, storage_s3,
+};
+use std::sync::Arc;
+
+async fn data_file_count(table: &Table) -> Result<i64, Box<dyn std::error::Error>> {
+    let schema = Arc::new(CatalogSchema::new(vec![Column::new(
+        "count",
+        CatalogDataType::Int64,
+        false,
+    )]));
+    let rows = table
+        .catalog
+        .query(
+            &format!(
+                "SELECT COUNT(1) FROM indexlake_data_file WHERE table_id = {}",
+                table.table_id
+            ),
+            schema,
+        )
+        .await?;
+    let rows = rows.try_collect::<Vec<_>>().await?;
+    Ok(rows[0].int64(0)?.unwrap())
+}
+
+/// Inserts `batch_count` batches of three rows, each batch is dumped into its own data file.
+async fn prepare_small_files_table(
+    client: &LakeClient,
+    table_name: &str,
+    batch_count: i32,
+) -> Result<Table, Box<dyn std::error::Error>> {
+    create_namespace_if_not_exists(client, "test_namespace").await?;
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("name", DataType::Utf8, false),
+        Field::new("age", DataType::Int32, false),
+    ]));
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: schema.clone(),
+            config: TableConfig {
+                inline_row_count_limit: 3,
+                parquet_row_group_size: 2,
+                ..Default::default()
+            },
+        })
+        .await?;
+    let table = client.load_table("test_namespace", table_name).await?;
+
+    for i in 0..batch_count {
+        let ages = (0..3).map(|j| i * 10 + j).collect::<Vec<_>>();
+        let names = ages.iter().map(|age| format!("n{age}")).collect::<Vec<_>>();
+        table
+            .insert(&RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(StringArray::from(names)),
+                    Arc::new(Int32Array::from(ages)),
+                ],
+            )?)
+            .await?;
+        // wait for dump task to finish
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    }
+    Ok(table)
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn compact_small_files(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let table = prepare_small_files_table(&client, "compact_small_files", 4).await?;
+    assert_eq!(data_file_count(&table).await?, 4);
+
+    // deleted rows are left out of the merged file
+    table.delete(&col("age").eq(lit(11))).await?;
+    let table_str_before = full_table_scan(&table).await?;
+
+    // not enough small files
+    let report = table
+        .compact(CompactOptions::default().with_min_input_files(5usize))
+        .await?;
+    assert_eq!(report, CompactReport::default());
+    assert_eq!(data_file_count(&table).await?, 4);
+
+    let report = table.compact(CompactOptions::default()).await?;
+    assert_eq!(report.rewritten_files, 4);
+    assert_eq!(report.written_files, 1);
+    assert_eq!(report.dropped_rows, 1);
+    assert!(report.bytes_before > 0 && report.bytes_after > 0);
+    assert_eq!(data_file_count(&table).await?, 1);
+    assert_eq!(full_table_scan(&table).await?, table_str_before);
+    assert_eq!(table.delete(&col("age").eq(lit(11))).await?, 0);
+
+    // rows of the merged file can be deleted as before
+    assert_eq!(table.delete(&col("age").eq(lit(21))).await?, 1);
+    assert_eq!(
+        full_table_scan(&table).await?,
+        r#"+-------------------+------+-----+
+| _indexlake_row_id | name | age |
++-------------------+------+-----+
+| 1                 | n0   | 0   |
+| 2                 | n1   | 1   |
+| 3                 | n2   | 2   |
+| 4                 | n10  | 10  |
+| 6                 | n12  | 12  |
+| 7                 | n20  | 20  |
+| 9                 | n22  | 22  |
+| 10                | n30  | 30  |
+| 11                | n31  | 31  |
+| 12                | n32  | 32  |
++-------------------+------+-----+"#
+    );
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[tokio::test(flavor = "multi_thread")]
+async fn compact_files_matching_predicate(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let table = prepare_small_files_table(&client, "compact_files_matching_predicate", 4).await?;
+    let table_str_before = full_table_scan(&table).await?;
+
+    // only the first three files have rows with age below 30
+    table
+        .compact(CompactOptions::default().with_predicate(Some(col("age").lt(lit(30)))))
+        .await?;
+    assert_eq!(data_file_count(&table).await?, 2);
+    assert_eq!(full_table_scan(&table).await?, table_str_before);
+
+    // files at the target size are left alone
+    table
+        .compact(CompactOptions::default().with_target_file_size(1u64))
+        .await?;
+    assert_eq!(data_file_count(&table).await?, 2);
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[tokio::test(flavor = "multi_thread")]
+async fn compact_during_inserts(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let table = prepare_small_files_table(&client, "compact_during_inserts", 4).await?;
+
+    let result = table
+        .compact(CompactOptions::default().with_max_files_per_group(1usize))
+        .await;
+    assert!(result.is_err());
+
+    // each insert is dumped into a new data file while files are being merged
+    let inserting_table = client
+        .load_table("test_namespace", "compact_during_inserts")
+        .await?;
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("name", DataType::Utf8, false),
+        Field::new("age", DataType::Int32, false),
+    ]));
+    let handle = tokio::spawn(async move {
+        for i in 0..6 {
+            let ages = (0..3).map(|j| 100 + i * 10 + j).collect::<Vec<_>>();
+            let names = ages.iter().map(|age| format!("n{age}")).collect::<Vec<_>>();
+            let batch = RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(StringArray::from(names)),
+                    Arc::new(Int32Array::from(ages)),
+                ],
+            )?;
+            inserting_table.insert(&batch).await?;
+            tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        }
+        Ok::<_, Box<dyn std::error::Error + Send + Sync>>(())
+    });
+    let mut rewritten_files = 0;
+    for _ in 0..6 {
+        let report = table
+            .compact(CompactOptions::default().with_max_files_per_group(2usize))
+            .await?;
+        assert!(report.written_files * 2 <= report.rewritten_files);
+        rewritten_files += report.rewritten_files;
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    }
+    handle.await?.map_err(|e| e.to_string())?;
+    assert!(rewritten_files >= 4);
+
+    // wait for dump tasks to finish
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    table.compact(CompactOptions::default()).await?;
+    assert_eq!(data_file_count(&table).await?, 1);
+
+    let batches = table
+        .scan(TableScan::default().with_columns(Some(vec!["age".to_string()])))
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    let mut ages = batches
+        .iter()
+        .flat_map(|batch| {
+            batch
+                .column(0)
+                .as_primitive::<Int32Type>()
+                .values()
+                .to_vec()
+        })
+        .collect::<Vec<_>>();
+    ages.sort();
+    let expected = (0..4)
+        .flat_map(|i| (0..3).map(move |j| i * 10 + j))
+        .chain((0..6).flat_map(|i| (0..3).map(move |j| 100 + i * 10 + j)))
+        .collect::<Vec<_>>();
+    assert_eq!(ages, expected);
+
+    Ok(())
+}
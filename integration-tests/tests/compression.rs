@@ -0,0 +1,141 @@
+use arrow::array::{Int64Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use indexlake::{
+    LakeClient,
+    storage::Storage,
+    table::{Compression, TableConfig, TableCreation},
+};
+use indexlake_integration_tests::{
+    catalog_sqlite, init_env_logger, storage_fs, utils::full_table_scan,
+};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+fn parquet_files_size(dir: &Path) -> u64 {
+    let mut size = 0;
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            size += parquet_files_size(&path);
+        } else if path.extension().is_some_and(|ext| ext == "parquet") {
+            size += std::fs::metadata(&path).unwrap().len();
+        }
+    }
+    size
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn compression_codecs() -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let storage_root = format!(
+        "{}/tmp/compression/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        uuid::Uuid::new_v4()
+    );
+    let client = LakeClient::new(
+        catalog_sqlite(),
+        Arc::new(Storage::new_fs(storage_root.clone())),
+    );
+    let namespace_id = client
+        .create_namespace("test_namespace", HashMap::new())
+        .await?;
+
+    let table_schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, false),
+    ]));
+    let ids = (0..1000).collect::<Vec<i64>>();
+    let names = ids
+        .iter()
+        .map(|id| format!("{id:0>64}"))
+        .collect::<Vec<_>>();
+    let record_batch = RecordBatch::try_new(
+        table_schema.clone(),
+        vec![
+            Arc::new(Int64Array::from(ids)),
+            Arc::new(StringArray::from(names)),
+        ],
+    )?;
+
+    let codecs = [
+        Compression::Uncompressed,
+        Compression::Snappy,
+        Compression::Zstd { level: 3 },
+        Compression::Lz4,
+    ];
+    let mut file_sizes = Vec::new();
+    let mut table_strs = Vec::new();
+    for (idx, compression) in codecs.into_iter().enumerate() {
+        let table_name = format!("compression_{idx}");
+        client
+            .create_table(TableCreation {
+                namespace_name: "test_namespace".to_string(),
+                table_name: table_name.clone(),
+                schema: table_schema.clone(),
+                config: TableConfig {
+                    inline_row_count_limit: 500,
+                    compression,
+                    ..Default::default()
+                },
+            })
+            .await?;
+        let table = client.load_table("test_namespace", &table_name).await?;
+        assert_eq!(table.config.compression, compression);
+
+        table.insert(&record_batch).await?;
+        // wait for dump task to finish
+        tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+
+        let table_dir = Path::new(&storage_root)
+            .join(namespace_id.to_string())
+            .join(table.table_id.to_string());
+        file_sizes.push(parquet_files_size(&table_dir));
+        table_strs.push(full_table_scan(&table).await?);
+    }
+
+    assert!(file_sizes[0] > 0);
+    for idx in 1..codecs.len() {
+        assert_eq!(table_strs[idx], table_strs[0]);
+        assert!(
+            file_sizes[idx] < file_sizes[0],
+            "{:?} file size {} not smaller than uncompressed {}",
+            codecs[idx],
+            file_sizes[idx],
+            file_sizes[0]
+        );
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn invalid_zstd_level() -> Result<(), Box<dyn std::error::Error>> {
+    let client = LakeClient::new(catalog_sqlite(), storage_fs());
+    client
+        .create_namespace("test_namespace", HashMap::new())
+        .await?;
+
+    for level in [0, 23] {
+        let result = client
+            .create_table(TableCreation {
+                namespace_name: "test_namespace".to_string(),
+                table_name: "invalid_zstd_level".to_string(),
+                schema: Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, false)])),
+                config: TableConfig {
+                    compression: Compression::Zstd { level },
+                    ..Default::default()
+                },
+            })
+            .await;
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("must be between 1 and 22")
+        );
+    }
+
+    Ok(())
+}
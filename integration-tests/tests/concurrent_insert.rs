@@ -0,0 +1,142 @@
+use arrow::array::{Int64Array, RecordBatch};
+use arrow::datatypes::{DataType, Field, Schema};
+use futures::TryStreamExt;
+use indexlake::{
+    LakeClient,
+    catalog::Catalog,
+    storage::Storage,
+    table::{TableConfig, TableCreation, TableScan},
+};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_postgres, catalog_sqlite, init_env_logger, storage_fs, storage_s3,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[rstest::rstest]
+#[case(async { catalog_memory() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn concurrent_insert(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let namespace_name = "test_namespace";
+    client
+        .create_namespace(namespace_name, HashMap::new())
+        .await?;
+
+    let table_schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, false)]));
+    let table_name = "concurrent_insert";
+    let table_creation = TableCreation {
+        namespace_name: namespace_name.to_string(),
+        table_name: table_name.to_string(),
+        schema: table_schema.clone(),
+        config: TableConfig {
+            inline_row_count_limit: 10000,
+            ..Default::default()
+        },
+    };
+    client.create_table(table_creation).await?;
+
+    let mut handles = Vec::new();
+    for task in 0..2 {
+        let table = client.load_table(namespace_name, table_name).await?;
+        let table_schema = table_schema.clone();
+        handles.push(tokio::spawn(async move {
+            for i in 0..20 {
+                let start = task * 1000 + i * 5;
+                let record_batch = RecordBatch::try_new(
+                    table_schema.clone(),
+                    vec![Arc::new(Int64Array::from_iter_values(start..start + 5))],
+                )?;
+                table.insert(&record_batch).await?;
+            }
+            Ok::<_, indexlake::ILError>(())
+        }));
+    }
+    for handle in handles {
+        handle.await??;
+    }
+
+    let table = client.load_table(namespace_name, table_name).await?;
+    let batches = table
+        .scan(TableScan::default())
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    let row_count: usize = batches.iter().map(|batch| batch.num_rows()).sum();
+    assert_eq!(row_count, 200);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_insert_distinct_tables_sqlite() -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = Arc::new(LakeClient::new(catalog_sqlite(), storage_fs()));
+    let namespace_name = "test_namespace";
+    client
+        .create_namespace(namespace_name, HashMap::new())
+        .await?;
+
+    let table_schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, false)]));
+    let mut handles = Vec::new();
+    for task in 0..16 {
+        let client = client.clone();
+        let table_schema = table_schema.clone();
+        handles.push(tokio::spawn(async move {
+            let table_name = format!("concurrent_insert_{task}");
+            client
+                .create_table(TableCreation {
+                    namespace_name: namespace_name.to_string(),
+                    table_name: table_name.clone(),
+                    schema: table_schema.clone(),
+                    config: TableConfig {
+                        inline_row_count_limit: 20,
+                        ..Default::default()
+                    },
+                })
+                .await?;
+            let table = client.load_table(namespace_name, &table_name).await?;
+            for i in 0..10 {
+                let start = i * 5;
+                let record_batch = RecordBatch::try_new(
+                    table_schema.clone(),
+                    vec![Arc::new(Int64Array::from_iter_values(start..start + 5))],
+                )?;
+                table.insert(&record_batch).await?;
+            }
+            Ok::<_, indexlake::ILError>(())
+        }));
+    }
+    for handle in handles {
+        handle.await??;
+    }
+    // wait for dump tasks to finish
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+
+    for task in 0..16 {
+        let table = client
+            .load_table(namespace_name, &format!("concurrent_insert_{task}"))
+            .await?;
+        let batches = table
+            .scan(TableScan::default())
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+        let row_count: usize = batches.iter().map(|batch| batch.num_rows()).sum();
+        assert_eq!(row_count, 50);
+    }
+
+    Ok(())
+}
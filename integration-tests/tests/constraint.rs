@@ -0,0 +1,228 @@
+use arrow::array::{Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use indexlake::expr::{col, lit};
+use indexlake::table::{CheckConstraint, NOT_NULL_CONSTRAINT, Table, TableConfig, TableCreation};
+use indexlake::{ConstraintViolation, ILError, ILResult};
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::{create_namespace_if_not_exists, prepare_testing_table};
+use indexlake_integration_tests::utils::full_table_scan;
+use indexlake_integration_tests::{
+    catalog_mariadb, catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite,
+    init_env_logger, storage_memory, storage_s3,
+};
+use std::sync::Arc;
+
+fn people_batch(names: Vec<Option<&str>>, ages: Vec<i32>) -> ILResult<RecordBatch> {
+    Ok(RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("name", DataType::Utf8, true),
+            Field::new("age", DataType::Int32, false),
+        ])),
+        vec![
+            Arc::new(StringArray::from(names)),
+            Arc::new(Int32Array::from(ages)),
+        ],
+    )?)
+}
+
+fn violation<T: std::fmt::Debug>(result: ILResult<T>) -> ConstraintViolation {
+    match result {
+        Err(ILError::ConstraintViolation(violation)) => violation,
+        other => panic!("expected a constraint violation, got {other:?}"),
+    }
+}
+
+async fn create_people_table(client: &LakeClient, table_name: &str) -> ILResult<Table> {
+    create_namespace_if_not_exists(client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: Arc::new(Schema::new(vec![
+                Field::new("name", DataType::Utf8, false),
+                Field::new("age", DataType::Int32, false),
+            ])),
+            config: TableConfig {
+                inline_row_count_limit: 3,
+                primary_key: vec!["name".to_string()],
+                check_constraints: vec![CheckConstraint::new("adult", col("age").gt_eq(lit(18)))],
+                ..Default::default()
+            },
+        })
+        .await?;
+    client.load_table("test_namespace", table_name).await
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_memory())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_memory())]
+#[case(async { catalog_mariadb().await }, storage_memory())]
+#[case(async { catalog_memory() }, storage_memory())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_memory()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn constraints_on_writes(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let table = create_people_table(&client, "constraints_on_writes").await?;
+    table
+        .insert(&people_batch(
+            vec![Some("Alice"), Some("Bob"), Some("Charlie"), Some("David")],
+            vec![20, 21, 22, 23],
+        )?)
+        .await?;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    let expected = full_table_scan(&table).await?;
+
+    // nulls of a non-nullable column are rejected with the first offending row
+    let result = table
+        .insert(&people_batch(
+            vec![Some("Eve"), None, None],
+            vec![30, 31, 32],
+        )?)
+        .await;
+    assert_eq!(
+        violation(result),
+        ConstraintViolation {
+            constraint: NOT_NULL_CONSTRAINT.to_string(),
+            columns: vec!["name".to_string()],
+            row_index: 1,
+            row_id: None,
+            values: vec![],
+        }
+    );
+
+    let result = table
+        .insert(&people_batch(
+            vec![Some("Eve"), Some("Frank"), Some("Grace")],
+            vec![30, 31, 17],
+        )?)
+        .await;
+    assert_eq!(
+        violation(result),
+        ConstraintViolation {
+            constraint: "adult".to_string(),
+            columns: vec!["age".to_string()],
+            row_index: 2,
+            row_id: None,
+            values: vec![],
+        }
+    );
+
+    // a violating batch fails the whole insert
+    let result = table
+        .insert_many(vec![
+            people_batch(vec![Some("Eve")], vec![30])?,
+            people_batch(vec![Some("Frank")], vec![10])?,
+        ])
+        .await;
+    assert_eq!(violation(result).row_index, 0);
+
+    let result = table
+        .upsert(&people_batch(
+            vec![Some("Alice"), Some("Eve")],
+            vec![15, 30],
+        )?)
+        .await;
+    assert_eq!(violation(result).row_index, 0);
+
+    // updated rows are reported with their row id, inline and dumped ones alike
+    let result = table
+        .update(
+            &col("name").eq(lit("Bob".to_string())),
+            vec![("age".to_string(), col("age").minus(lit(10)))],
+        )
+        .await;
+    assert_eq!(violation(result).row_id, Some(2));
+    let result = table
+        .update(
+            &col("name").eq(lit("David".to_string())),
+            vec![("age".to_string(), col("age").minus(lit(10)))],
+        )
+        .await;
+    assert_eq!(violation(result).row_id, Some(4));
+
+    // none of the failed writes left rows behind
+    assert_eq!(full_table_scan(&table).await?, expected);
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_memory())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_memory())]
+#[case(async { catalog_mariadb().await }, storage_memory())]
+#[case(async { catalog_memory() }, storage_memory())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_memory()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn add_check_constraint(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let mut table = prepare_testing_table(&client, "add_check_constraint").await?;
+
+    // validating the rows of the table finds the violating one
+    let under_23 = CheckConstraint::new("under_23", col("age").lt(lit(23)));
+    let result = table.add_check_constraint(under_23.clone(), true).await;
+    let violation = violation(result);
+    assert_eq!(violation.constraint, "under_23");
+    assert_eq!(violation.row_id, Some(4));
+    assert!(table.config.check_constraints.is_empty());
+
+    // a constraint not validated is added for rows written afterwards
+    table.add_check_constraint(under_23, false).await?;
+    assert!(table.config.check_constraints[0].not_valid);
+    let record = people_batch(vec![Some("Eve")], vec![30])?;
+    assert!(matches!(
+        table.insert(&record).await,
+        Err(ILError::ConstraintViolation(_))
+    ));
+
+    // the constraint is kept in the catalog, a string literal with a quote included
+    let named = CheckConstraint::new("not_o_brien", col("name").neq(lit("O'Brien".to_string())));
+    table.add_check_constraint(named, true).await?;
+    let mut table = client
+        .load_table("test_namespace", "add_check_constraint")
+        .await?;
+    assert_eq!(table.config.check_constraints.len(), 2);
+    assert!(table.insert(&record).await.is_err());
+
+    // columns used by a constraint can not be dropped or renamed
+    assert!(table.drop_column("age", true).await.is_err());
+    assert!(table.rename_column("age", "years").await.is_err());
+
+    // invalid constraints are rejected
+    let invalid = [
+        CheckConstraint::new("under_23", col("age").lt(lit(20))),
+        CheckConstraint::new("unknown", col("height").gt(lit(0))),
+        CheckConstraint::new("not_boolean", col("age").plus(lit(1))),
+        CheckConstraint::new(NOT_NULL_CONSTRAINT, col("age").gt(lit(0))),
+    ];
+    for constraint in invalid {
+        assert!(matches!(
+            table.add_check_constraint(constraint, false).await,
+            Err(ILError::InvalidInput(_))
+        ));
+    }
+
+    table.drop_check_constraint("under_23").await?;
+    table.insert(&record).await?;
+    assert!(table.drop_check_constraint("under_23").await.is_err());
+
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use arrow::array::{Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use futures::TryStreamExt;
+use indexlake::expr::{Expr, col, lit};
+use indexlake::table::{PartitionTransform, Table, TableConfig, TableCreation, TableScan};
+use indexlake::{ILError, ILResult, LakeClient, catalog::Catalog, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::{counted_storage, create_namespace_if_not_exists};
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger,
+};
+use std::sync::Arc;
+
+fn scores_batch(regions: Vec<&str>, scores: Vec<Option<i32>>) -> ILResult<RecordBatch> {
+    Ok(RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("region", DataType::Utf8, false),
+            Field::new("score", DataType::Int32, true),
+        ])),
+        vec![
+            Arc::new(StringArray::from(regions)),
+            Arc::new(Int32Array::from(scores)),
+        ],
+    )?)
+}
+
+async fn scanned_row_count(table: &Table, predicate: &Expr) -> ILResult<u64> {
+    let batches = table
+        .scan(TableScan::default().with_filters(vec![predicate.clone()]))
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    Ok(batches.iter().map(|batch| batch.num_rows() as u64).sum())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn count_rows(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage.clone());
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "count_rows".to_string(),
+            schema: Arc::new(Schema::new(vec![
+                Field::new("region", DataType::Utf8, false),
+                Field::new("score", DataType::Int32, true),
+            ])),
+            config: TableConfig {
+                inline_row_count_limit: 6,
+                partition_by: vec!["region".to_string()],
+                partition_transform: PartitionTransform::Identity,
+                ..Default::default()
+            },
+        })
+        .await?;
+    let table = client.load_table("test_namespace", "count_rows").await?;
+
+    // one data file per region, two inline rows and a deleted row in the us file
+    table
+        .insert(&scores_batch(
+            vec!["eu", "us", "ap", "eu", "us", "ap"],
+            vec![Some(10), Some(60), None, Some(70), Some(20), Some(80)],
+        )?)
+        .await?;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+    table
+        .insert(&scores_batch(vec!["eu", "us"], vec![Some(90), None])?)
+        .await?;
+    table.delete(&col("score").eq(lit(20))).await?;
+
+    // counts decided by row counts, partitions and column stats read no data file
+    storage.reset_read_stats();
+    assert_eq!(table.count(None).await?, 7);
+    assert_eq!(
+        table
+            .count(Some(col("region").eq(lit("eu".to_string()))))
+            .await?,
+        3
+    );
+    assert_eq!(
+        table
+            .count(Some(col("region").eq(lit("us".to_string()))))
+            .await?,
+        2
+    );
+    let eu_scored = col("region")
+        .eq(lit("eu".to_string()))
+        .and(col("score").gt_eq(lit(10)));
+    assert_eq!(table.count(Some(eu_scored)).await?, 3);
+    assert_eq!(table.count(Some(col("score").gt(lit(100)))).await?, 0);
+    assert!(storage.read_stats().unwrap().opened_paths.is_empty());
+
+    // other predicates read the undecided data files and agree with a scan
+    let predicates = [
+        col("score").gt(lit(50)),
+        col("score").is_null(),
+        col("region")
+            .eq(lit("ap".to_string()))
+            .or(col("score").lt(lit(15))),
+        col("region").neq(lit("eu".to_string())),
+        col("score").in_list(vec![lit(60), lit(90)], false),
+    ];
+    for predicate in predicates {
+        assert_eq!(
+            table.count(Some(predicate.clone())).await?,
+            scanned_row_count(&table, &predicate).await?,
+            "{predicate:?}"
+        );
+    }
+
+    assert!(matches!(
+        table.count(Some(col("score").plus(lit(1)))).await,
+        Err(ILError::InvalidInput(_))
+    ));
+
+    Ok(())
+}
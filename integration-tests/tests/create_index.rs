@@ -1,19 +1,26 @@
 use indexlake::{LakeClient, catalog::Catalog, index::Index, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
 use indexlake_integration_tests::{
-    catalog_postgres, catalog_sqlite, data::prepare_testing_table, init_env_logger, storage_fs,
-    storage_s3,
+    catalog_mariadb, catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite,
+    data::prepare_testing_table, init_env_logger, storage_fs, storage_s3,
 };
 use std::sync::Arc;
 
 use std::collections::HashMap;
 
 use indexlake::table::IndexCreation;
+use indexlake_index_btree::{BTreeIndex, BTreeIndexParams};
 use indexlake_index_hash::{HashIndex, HashIndexParams};
 use indexlake_index_rstar::RStarIndex;
 
 #[rstest::rstest]
 #[case(async { catalog_sqlite() }, storage_fs())]
 #[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
 #[tokio::test(flavor = "multi_thread")]
 async fn duplicated_index_name(
     #[future(awt)]
@@ -24,16 +31,17 @@ async fn duplicated_index_name(
     init_env_logger();
 
     let mut client = LakeClient::new(catalog, storage);
-    client.register_index(Arc::new(HashIndex))?;
+    client.register_index(Arc::new(BTreeIndex))?;
 
     let mut table = prepare_testing_table(&client, "duplicated_index_name").await?;
 
     let index_creation = IndexCreation {
         name: "test_index".to_string(),
-        kind: HashIndex.kind().to_string(),
+        kind: BTreeIndex.kind().to_string(),
         key_columns: vec!["name".to_string()],
         include_columns: vec!["age".to_string()],
-        params: Arc::new(HashIndexParams),
+        params: Arc::new(BTreeIndexParams::default()),
+        where_predicate: None,
     };
 
     table.create_index(index_creation.clone()).await?;
@@ -47,6 +55,10 @@ async fn duplicated_index_name(
 #[rstest::rstest]
 #[case(async { catalog_sqlite() }, storage_fs())]
 #[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
 #[tokio::test(flavor = "multi_thread")]
 async fn unsupported_index_kind(
     #[future(awt)]
@@ -66,7 +78,8 @@ async fn unsupported_index_kind(
         kind: "unsupported_index_kind".to_string(),
         key_columns: vec!["name".to_string()],
         include_columns: vec!["age".to_string()],
-        params: Arc::new(HashIndexParams),
+        params: Arc::new(HashIndexParams::default()),
+        where_predicate: None,
     };
 
     let result = table.create_index(index_creation).await;
@@ -1,10 +1,22 @@
-use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
-use indexlake_integration_tests::{catalog_postgres, catalog_sqlite, storage_fs, storage_s3};
+use arrow::datatypes::{DataType, Field, Schema};
+use indexlake::table::{TableConfig, TableCreation};
+use indexlake::{ILError, LakeClient, catalog::Catalog, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::{
+    catalog_mariadb, catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, storage_fs,
+    storage_s3,
+};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 #[rstest::rstest]
 #[case(async { catalog_sqlite() }, storage_fs())]
 #[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
 #[tokio::test(flavor = "multi_thread")]
 async fn create_namespace(
     #[future(awt)]
@@ -14,7 +26,9 @@ async fn create_namespace(
 ) -> Result<(), Box<dyn std::error::Error>> {
     let client = LakeClient::new(catalog, storage);
 
-    let expected_namespace_id = client.create_namespace("test_namespace").await?;
+    let expected_namespace_id = client
+        .create_namespace("test_namespace", HashMap::new())
+        .await?;
 
     let namespace_id = client.get_namespace_id("test_namespace").await?;
     assert_eq!(namespace_id, Some(expected_namespace_id));
@@ -28,6 +42,10 @@ async fn create_namespace(
 #[rstest::rstest]
 #[case(async { catalog_sqlite() }, storage_fs())]
 #[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
 #[tokio::test(flavor = "multi_thread")]
 async fn duplicated_namespace_name(
     #[future(awt)]
@@ -38,9 +56,112 @@ async fn duplicated_namespace_name(
     let client = LakeClient::new(catalog, storage);
 
     let namespace_name = "test_namespace";
-    client.create_namespace(namespace_name).await?;
-    let result = client.create_namespace(namespace_name).await;
+    client
+        .create_namespace(namespace_name, HashMap::new())
+        .await?;
+    let result = client
+        .create_namespace(namespace_name, HashMap::new())
+        .await;
     assert!(result.is_err());
 
     Ok(())
 }
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn namespace_management(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let client = LakeClient::new(catalog, storage);
+
+    let properties = HashMap::from([
+        ("owner".to_string(), "analytics".to_string()),
+        ("comment".to_string(), "O'Brien's tables".to_string()),
+    ]);
+    let sales_id = client.create_namespace("sales", properties.clone()).await?;
+    client.create_namespace("archive", HashMap::new()).await?;
+    assert!(client.namespace_exists("sales").await?);
+    assert!(!client.namespace_exists("not_exists").await?);
+
+    // namespaces are listed by name with their properties
+    let namespaces = client.list_namespaces().await?;
+    let names = namespaces
+        .iter()
+        .map(|namespace| namespace.namespace_name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["archive", "sales"]);
+    assert_eq!(namespaces[1].namespace_id, sales_id);
+    assert_eq!(namespaces[1].properties, properties);
+    assert!(namespaces[0].properties.is_empty());
+
+    client.drop_namespace("archive", false).await?;
+    assert!(!client.namespace_exists("archive").await?);
+    assert!(client.drop_namespace("archive", false).await.is_err());
+    assert_eq!(client.list_namespaces().await?.len(), 1);
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn drop_namespace_cascade(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let client = LakeClient::new(catalog, storage);
+
+    client.create_namespace("sales", HashMap::new()).await?;
+    for table_name in ["orders", "customers"] {
+        client
+            .create_table(TableCreation {
+                namespace_name: "sales".to_string(),
+                table_name: table_name.to_string(),
+                schema: Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, false)])),
+                config: TableConfig::default(),
+            })
+            .await?;
+    }
+
+    // a namespace holding tables is only dropped with cascade, the error names the tables
+    match client.drop_namespace("sales", false).await {
+        Err(ILError::InvalidInput(message)) => {
+            assert!(message.contains("customers, orders"), "{message}");
+        }
+        other => panic!("expected an invalid input error, got {other:?}"),
+    }
+    assert!(client.load_table("sales", "orders").await.is_ok());
+
+    client.drop_namespace("sales", true).await?;
+    assert!(!client.namespace_exists("sales").await?);
+    assert!(client.load_table("sales", "orders").await.is_err());
+
+    // the names are free again
+    client.create_namespace("sales", HashMap::new()).await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "sales".to_string(),
+            table_name: "orders".to_string(),
+            schema: Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, false)])),
+            config: TableConfig::default(),
+        })
+        .await?;
+
+    Ok(())
+}
@@ -8,10 +8,14 @@ use indexlake::{
     storage::Storage,
     table::{TableConfig, TableCreation},
 };
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
 use indexlake_integration_tests::utils::full_table_scan;
 use indexlake_integration_tests::{
-    catalog_postgres, catalog_sqlite, init_env_logger, storage_fs, storage_s3,
+    catalog_mariadb, catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite,
+    init_env_logger, storage_fs, storage_s3,
 };
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use arrow::array::{
@@ -22,6 +26,10 @@ use arrow::array::{
 #[rstest::rstest]
 #[case(async { catalog_sqlite() }, storage_fs())]
 #[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
 #[tokio::test(flavor = "multi_thread")]
 async fn create_table(
     #[future(awt)]
@@ -34,7 +42,9 @@ async fn create_table(
     let client = LakeClient::new(catalog, storage);
 
     let namespace_name = "test_namespace";
-    let expected_namespace_id = client.create_namespace(namespace_name).await?;
+    let expected_namespace_id = client
+        .create_namespace(namespace_name, HashMap::new())
+        .await?;
 
     let expected_schema = Arc::new(Schema::new(vec![
         Field::new("id", DataType::Int64, false),
@@ -70,6 +80,10 @@ async fn create_table(
 #[rstest::rstest]
 #[case(async { catalog_sqlite() }, storage_fs())]
 #[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
 #[tokio::test(flavor = "multi_thread")]
 async fn table_data_types(
     #[future(awt)]
@@ -82,7 +96,9 @@ async fn table_data_types(
     let client = LakeClient::new(catalog, storage);
 
     let namespace_name = "test_namespace";
-    client.create_namespace(namespace_name).await?;
+    client
+        .create_namespace(namespace_name, HashMap::new())
+        .await?;
 
     let table_schema = Arc::new(Schema::new(vec![
         Field::new("boolean_col", DataType::Boolean, true),
@@ -139,6 +155,10 @@ async fn table_data_types(
 #[rstest::rstest]
 #[case(async { catalog_sqlite() }, storage_fs())]
 #[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
 #[tokio::test(flavor = "multi_thread")]
 async fn duplicated_table_name(
     #[future(awt)]
@@ -151,7 +171,9 @@ async fn duplicated_table_name(
     let client = LakeClient::new(catalog, storage);
 
     let namespace_name = "test_namespace";
-    client.create_namespace(namespace_name).await?;
+    client
+        .create_namespace(namespace_name, HashMap::new())
+        .await?;
 
     let expected_schema = Arc::new(Schema::new(vec![
         Field::new("id", DataType::Int64, false),
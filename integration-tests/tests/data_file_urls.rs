@@ -0,0 +1,183 @@
+use arrow::array::{Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use indexlake::expr::{col, lit};
+use indexlake::table::{CompactOptions, PresignedFile, Table, TableConfig, TableCreation};
+use indexlake::{ILError, LakeClient, catalog::Catalog, storage::Storage};
+use indexlake_integration_tests::data::create_namespace_if_not_exists;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger, storage_fs,
+    storage_memory,
+};
+use opendal::services::S3Config;
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn table_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("name", DataType::Utf8, false),
+        Field::new("age", DataType::Int32, false),
+    ]))
+}
+
+async fn insert_and_dump(
+    table: &Table,
+    names: Vec<&str>,
+    ages: Vec<i32>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    table
+        .insert(&RecordBatch::try_new(
+            table_schema(),
+            vec![
+                Arc::new(StringArray::from(names)),
+                Arc::new(Int32Array::from(ages)),
+            ],
+        )?)
+        .await?;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+    Ok(())
+}
+
+async fn create_table(
+    client: &LakeClient,
+    table_name: &str,
+    storage_prefix: Option<String>,
+) -> Result<Table, Box<dyn std::error::Error>> {
+    create_namespace_if_not_exists(client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: table_schema(),
+            config: TableConfig {
+                inline_row_count_limit: 2,
+                storage_prefix,
+                ..Default::default()
+            },
+        })
+        .await?;
+    Ok(client.load_table("test_namespace", table_name).await?)
+}
+
+/// Row count of the parquet file behind a `file://` URL.
+fn file_row_count(file: &PresignedFile) -> Result<i64, Box<dyn std::error::Error>> {
+    let path = file.url.strip_prefix("file://").expect("fs storage URL");
+    let reader = SerializedFileReader::new(std::fs::File::open(path)?)?;
+    Ok(reader.metadata().file_metadata().num_rows())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_fs())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[tokio::test(flavor = "multi_thread")]
+async fn data_file_urls_of_live_files(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    // Cases share the fs storage, each table gets its own directory
+    let storage_prefix = format!("data_file_urls/{}", uuid::Uuid::new_v4());
+    let table = create_table(
+        &client,
+        "data_file_urls_of_live_files",
+        Some(storage_prefix),
+    )
+    .await?;
+    insert_and_dump(&table, vec!["Alice", "Bob"], vec![20, 21]).await?;
+    insert_and_dump(&table, vec!["Charlie", "David"], vec![22, 23]).await?;
+    insert_and_dump(&table, vec!["Eve", "Frank"], vec![24, 25]).await?;
+    table.delete(&col("age").eq(lit(22))).await?;
+
+    let mut files = table.data_file_urls(Duration::from_secs(3600)).await?;
+    files.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+    let row_id_ranges = files
+        .iter()
+        .map(|file| file.row_id_ranges.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(row_id_ranges, vec![vec![1..=2], vec![4..=4], vec![5..=6]]);
+    for file in files.iter() {
+        assert!(file.url.starts_with("file://"));
+        assert_eq!(file.schema, table.schema);
+        assert_eq!(
+            std::fs::metadata(file.url.strip_prefix("file://").unwrap())?.len(),
+            file.file_size_bytes
+        );
+        // The deleted row is still in its file, readers skip it by row id
+        assert_eq!(file_row_count(file)?, 2);
+    }
+
+    // Replaced files stay in storage until vacuumed, but are not listed
+    table.compact(CompactOptions::default()).await?;
+    let compacted = table.data_file_urls(Duration::from_secs(3600)).await?;
+    assert_eq!(compacted.len(), 1);
+    assert!(
+        files
+            .iter()
+            .all(|file| file.relative_path != compacted[0].relative_path)
+    );
+    assert_eq!(compacted[0].row_id_ranges, vec![1..=2, 4..=6]);
+    assert_eq!(file_row_count(&compacted[0])?, 5);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn data_file_urls_unsupported_storage() -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog_sqlite(), storage_memory());
+    let table = create_table(&client, "data_file_urls_unsupported_storage", None).await?;
+    // Inline rows are not listed
+    table
+        .insert(&RecordBatch::try_new(
+            table_schema(),
+            vec![
+                Arc::new(StringArray::from(vec!["Alice"])),
+                Arc::new(Int32Array::from(vec![20])),
+            ],
+        )?)
+        .await?;
+    assert!(
+        table
+            .data_file_urls(Duration::from_secs(60))
+            .await?
+            .is_empty()
+    );
+
+    insert_and_dump(&table, vec!["Bob", "Charlie"], vec![21, 22]).await?;
+    assert!(matches!(
+        table.data_file_urls(Duration::from_secs(60)).await,
+        Err(ILError::NotSupported(_))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn presign_s3_read() -> Result<(), Box<dyn std::error::Error>> {
+    let mut config = S3Config::default();
+    config.endpoint = Some("http://127.0.0.1:9000".to_string());
+    config.access_key_id = Some("admin".to_string());
+    config.secret_access_key = Some("password".to_string());
+    config.region = Some("us-east-1".to_string());
+    config.disable_config_load = true;
+    config.disable_ec2_metadata = true;
+    let storage = Storage::new_s3(config, "indexlake");
+
+    // Presigning is local, no request is sent
+    let url = storage
+        .presign_read("1/1/1.parquet", Duration::from_secs(600))
+        .await?;
+    assert!(url.starts_with("http://127.0.0.1:9000/indexlake/1/1/1.parquet?"));
+    assert!(url.contains("X-Amz-Expires=600"));
+    assert!(url.contains("X-Amz-Signature="));
+
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use arrow::util::pretty::pretty_format_batches;
+use datafusion::physical_plan::displayable;
+use datafusion::prelude::SessionContext;
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+use indexlake_datafusion::IndexLakeTable;
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::prepare_testing_table;
+use indexlake_integration_tests::utils::sort_record_batches;
+use indexlake_integration_tests::{
+    catalog_mariadb, catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite,
+    init_env_logger, storage_fs, storage_s3,
+};
+use std::sync::Arc;
+
+async fn query(
+    ctx: &SessionContext,
+    sql: &str,
+    sort_col: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let batches = ctx.sql(sql).await?.collect().await?;
+    let sorted_batch = sort_record_batches(&batches, sort_col)?;
+    Ok(pretty_format_batches(&[sorted_batch])?.to_string())
+}
+
+async fn physical_plan(
+    ctx: &SessionContext,
+    sql: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let plan = ctx.sql(sql).await?.create_physical_plan().await?;
+    Ok(displayable(plan.as_ref()).indent(false).to_string())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn datafusion_query(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    // Rows 1-3 are in a data file, row 4 is inline
+    let table = prepare_testing_table(&client, "datafusion_query").await?;
+
+    let ctx = SessionContext::new();
+    ctx.register_table("people", Arc::new(IndexLakeTable::new(Arc::new(table))))?;
+
+    // The filter and limit are both pushed down into the scan
+    let sql = "SELECT name, age FROM people WHERE age > 20 AND age < 23 LIMIT 2";
+    let plan = physical_plan(&ctx, sql).await?;
+    assert!(!plan.contains("FilterExec"), "{plan}");
+    assert!(
+        plan.contains("IndexLakeScanExec: table=test_namespace.datafusion_query, projection=Some([1, 2]), filters=[(age > 20), (age < 23)], limit=Some(2)"),
+        "{plan}"
+    );
+    assert_eq!(
+        query(&ctx, sql, "age").await?,
+        r#"+---------+-----+
+| name    | age |
++---------+-----+
+| Bob     | 21  |
+| Charlie | 22  |
++---------+-----+"#
+    );
+
+    // String filters are pushed down and checked again by DataFusion
+    let sql = "SELECT age FROM people WHERE name = 'David' OR name LIKE 'Al%'";
+    let plan = physical_plan(&ctx, sql).await?;
+    assert!(plan.contains("FilterExec"), "{plan}");
+    assert!(
+        plan.contains("filters=[((name = David) OR name LIKE Al%)]"),
+        "{plan}"
+    );
+    assert_eq!(
+        query(&ctx, sql, "age").await?,
+        r#"+-----+
+| age |
++-----+
+| 20  |
+| 23  |
++-----+"#
+    );
+
+    // Filters indexlake can not evaluate are left to DataFusion
+    let sql = "SELECT name FROM people WHERE abs(age - 22) = 1";
+    assert_eq!(
+        query(&ctx, sql, "name").await?,
+        r#"+-------+
+| name  |
++-------+
+| Bob   |
+| David |
++-------+"#
+    );
+
+    assert_eq!(
+        query(
+            &ctx,
+            "SELECT COUNT(*) AS count FROM people WHERE age >= 21",
+            "count"
+        )
+        .await?,
+        r#"+-------+
+| count |
++-------+
+| 3     |
++-------+"#
+    );
+
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use arrow::array::{Decimal128Array, Decimal256Array, Int64Array, RecordBatch};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef, i256};
+use futures::TryStreamExt;
+use indexlake::catalog::Scalar;
+use indexlake::expr::{col, lit};
+use indexlake::table::{Table, TableConfig, TableCreation, TableScan};
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::{
+    counted_storage, create_namespace_if_not_exists, data_files_opened,
+};
+use indexlake_integration_tests::utils::sort_record_batches;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger,
+};
+use std::sync::Arc;
+
+fn table_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("price", DataType::Decimal128(10, 2), false),
+        Field::new("rate", DataType::Decimal128(38, 10), true),
+        Field::new("total", DataType::Decimal256(50, 0), true),
+    ]))
+}
+
+/// Rows of ids `ids` with prices `prices` in cents, and rates and totals at the limits of
+/// their precisions.
+fn decimal_batch(
+    ids: Vec<i64>,
+    prices: Vec<i128>,
+) -> Result<RecordBatch, Box<dyn std::error::Error>> {
+    let rates = ids
+        .iter()
+        .map(|id| match id % 3 {
+            0 => None,
+            1 => Some(i128::MAX / 10 - *id as i128),
+            _ => Some(-(i128::MAX / 10) + *id as i128),
+        })
+        .collect::<Decimal128Array>()
+        .with_precision_and_scale(38, 10)?;
+    let totals = ids
+        .iter()
+        .map(|id| match id % 2 {
+            0 => i256::from_string(&format!("-{}{id}", "9".repeat(45))),
+            _ => i256::from_string(&format!("{}{id}", "9".repeat(45))),
+        })
+        .collect::<Decimal256Array>()
+        .with_precision_and_scale(50, 0)?;
+    Ok(RecordBatch::try_new(
+        table_schema(),
+        vec![
+            Arc::new(Int64Array::from(ids)),
+            Arc::new(Decimal128Array::from(prices).with_precision_and_scale(10, 2)?),
+            Arc::new(rates),
+            Arc::new(totals),
+        ],
+    )?)
+}
+
+async fn scan_sorted(
+    table: &Table,
+    scan: TableScan,
+) -> Result<RecordBatch, Box<dyn std::error::Error>> {
+    let batches = table.scan(scan).await?.try_collect::<Vec<_>>().await?;
+    Ok(sort_record_batches(&batches, "id")?)
+}
+
+async fn scan_ids(table: &Table, scan: TableScan) -> Result<Vec<i64>, Box<dyn std::error::Error>> {
+    let scan = scan.with_columns(Some(vec!["id".to_string()]));
+    let batch = scan_sorted(table, scan).await?;
+    Ok(batch
+        .column(0)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap()
+        .values()
+        .to_vec())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn decimal_columns(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage.clone());
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "decimal_columns".to_string(),
+            schema: table_schema(),
+            config: TableConfig::default(),
+        })
+        .await?;
+    let table = client
+        .load_table("test_namespace", "decimal_columns")
+        .await?;
+    // precisions and scales are kept by the catalog
+    for field in table_schema().fields() {
+        assert_eq!(table.schema.field_with_name(field.name())?, field.as_ref());
+    }
+
+    let columns = Some(vec![
+        "id".to_string(),
+        "price".to_string(),
+        "rate".to_string(),
+        "total".to_string(),
+    ]);
+    let scan = TableScan::default().with_columns(columns);
+    let cheap = decimal_batch(vec![1, 2, 3, 4], vec![-1050, 0, 1999, 4999])?;
+    table.insert(&cheap).await?;
+    // inline rows are read back bit-exact and filtered by the numbers, whatever the scale of
+    // the literal
+    assert_eq!(scan_sorted(&table, scan.clone()).await?, cheap);
+    let over_19_98 = col("price").gt(lit(Scalar::Decimal128(Some(19_980), 7, 3)));
+    let scan_over = TableScan::default().with_filters(vec![over_19_98.clone()]);
+    assert_eq!(scan_ids(&table, scan_over.clone()).await?, vec![3, 4]);
+    let under_zero = col("rate").lt(lit(Scalar::Decimal128(Some(0), 1, 0)));
+    assert_eq!(
+        scan_ids(&table, TableScan::default().with_filters(vec![under_zero])).await?,
+        vec![2]
+    );
+
+    // rows flushed into a data file
+    table.flush().await?;
+    assert_eq!(scan_sorted(&table, scan.clone()).await?, cheap);
+    assert_eq!(scan_ids(&table, scan_over).await?, vec![3, 4]);
+
+    let expensive = decimal_batch(vec![5, 6, 7], vec![50_000, 75_025, 99_999_999])?;
+    table.insert(&expensive).await?;
+    table.flush().await?;
+
+    // the range of prices prunes the data file of cheap rows by its statistics
+    storage.reset_read_stats();
+    let range = col("price")
+        .gt_eq(lit(Scalar::Decimal128(Some(500), 3, 0)))
+        .and(col("price").lt(lit(Scalar::Decimal128(Some(7_502_500), 10, 4))));
+    let scan_range = TableScan::default().with_filters(vec![range]);
+    assert_eq!(scan_ids(&table, scan_range).await?, vec![5]);
+    assert_eq!(data_files_opened(&storage), 1);
+
+    let total = i256::from_string(&format!("{}7", "9".repeat(45))).unwrap();
+    let scan_total =
+        TableScan::default().with_filters(vec![col("total").eq(lit(Scalar::Decimal256(
+            Some(total),
+            50,
+            0,
+        )))]);
+    assert_eq!(scan_ids(&table, scan_total).await?, vec![7]);
+
+    Ok(())
+}
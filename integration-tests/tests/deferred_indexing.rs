@@ -0,0 +1,180 @@
+use arrow::array::{Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use indexlake::expr::{col, lit};
+use indexlake::index::Index;
+use indexlake::table::{
+    IndexCreation, IndexDescription, Table, TableConfig, TableCreation, TableIdent, TableScan,
+};
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+use indexlake_index_btree::{BTreeIndex, BTreeIndexParams};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::{
+    counted_storage, create_namespace_if_not_exists, data_files_opened,
+};
+use indexlake_integration_tests::utils::table_scan;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger,
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn table_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("value", DataType::Utf8, false),
+    ]))
+}
+
+async fn create_table(
+    client: &LakeClient,
+    table_name: &str,
+    inline_row_count_limit: usize,
+) -> Result<Table, Box<dyn std::error::Error>> {
+    create_namespace_if_not_exists(client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: table_schema(),
+            config: TableConfig {
+                inline_row_count_limit,
+                parquet_row_group_size: 4,
+                deferred_indexing: true,
+                ..Default::default()
+            },
+        })
+        .await?;
+    let mut table = client.load_table("test_namespace", table_name).await?;
+    table
+        .create_index(IndexCreation {
+            name: "id_index".to_string(),
+            kind: BTreeIndex.kind().to_string(),
+            key_columns: vec!["id".to_string()],
+            include_columns: vec![],
+            params: Arc::new(BTreeIndexParams { leaf_size: 4 }),
+            where_predicate: None,
+        })
+        .await?;
+    Ok(table)
+}
+
+/// Ten rows of file `file` out of `file_count`, ids interleave over the files so column stats
+/// prune none of them.
+fn file_batch(file: i32, file_count: i32) -> Result<RecordBatch, Box<dyn std::error::Error>> {
+    let ids = (0..10).map(|i| i * file_count + file).collect::<Vec<_>>();
+    let values = ids.iter().map(|id| format!("v{id}")).collect::<Vec<_>>();
+    Ok(RecordBatch::try_new(
+        table_schema(),
+        vec![
+            Arc::new(Int32Array::from(ids)),
+            Arc::new(StringArray::from(values)),
+        ],
+    )?)
+}
+
+async fn id_index(
+    client: &LakeClient,
+    table_name: &str,
+) -> Result<IndexDescription, Box<dyn std::error::Error>> {
+    let description = client
+        .describe_table(&TableIdent::new("test_namespace", table_name))
+        .await?;
+    Ok(description.indexes[0].clone())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn deferred_indexing_sync_indexes(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage.clone());
+    client.register_index(Arc::new(BTreeIndex))?;
+    let table = create_table(&client, "deferred_indexing_sync_indexes", 1000).await?;
+    for file in 0..3 {
+        table.insert(&file_batch(file, 3)?).await?;
+        table.flush().await?;
+    }
+
+    let index = id_index(&client, "deferred_indexing_sync_indexes").await?;
+    assert_eq!(index.unindexed_data_file_count, 3);
+    assert!(!index.is_synced());
+
+    // unindexed data files are read in full
+    let scan = TableScan::default().with_filters(vec![col("id").eq(lit(4))]);
+    let expected = r#"+-------------------+----+-------+
+| _indexlake_row_id | id | value |
++-------------------+----+-------+
+| 12                | 4  | v4    |
++-------------------+----+-------+"#;
+    storage.reset_read_stats();
+    assert_eq!(table_scan(&table, scan.clone()).await?, expected);
+    assert_eq!(data_files_opened(&storage), 3);
+
+    assert_eq!(table.sync_indexes().await?, 3);
+    let index = id_index(&client, "deferred_indexing_sync_indexes").await?;
+    assert_eq!(index.unindexed_data_file_count, 0);
+    assert!(index.is_synced());
+
+    // the index rules out the files without the id
+    storage.reset_read_stats();
+    assert_eq!(table_scan(&table, scan).await?, expected);
+    assert_eq!(data_files_opened(&storage), 1);
+
+    // nothing left to index
+    assert_eq!(table.sync_indexes().await?, 0);
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[tokio::test(flavor = "multi_thread")]
+async fn deferred_indexing_background_sync(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage.clone());
+    client.register_index(Arc::new(BTreeIndex))?;
+    let table = create_table(&client, "deferred_indexing_background_sync", 10).await?;
+    for file in 0..3 {
+        table.insert(&file_batch(file, 3)?).await?;
+        // wait for dump task to finish
+        tokio::time::sleep(Duration::from_secs(2)).await;
+    }
+
+    // the dump tasks index their data files after writing them
+    let mut index = id_index(&client, "deferred_indexing_background_sync").await?;
+    for _ in 0..50 {
+        if index.is_synced() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        index = id_index(&client, "deferred_indexing_background_sync").await?;
+    }
+    assert!(index.is_synced());
+    assert_eq!(table.storage_stats().await?.data_file_count, 3);
+
+    storage.reset_read_stats();
+    let scan = TableScan::default().with_filters(vec![col("id").eq(lit(4))]);
+    assert!(table_scan(&table, scan).await?.contains("v4"));
+    assert_eq!(data_files_opened(&storage), 1);
+
+    Ok(())
+}
@@ -1,15 +1,30 @@
+use arrow::array::{Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
 use indexlake::catalog::INTERNAL_ROW_ID_FIELD_NAME;
-use indexlake::expr::Expr;
+use indexlake::expr::{Expr, col, lit};
+use indexlake::index::Index;
+use indexlake::table::{IndexCreation, SnapshotOperation, Table, TableConfig, TableCreation};
 use indexlake::{LakeClient, catalog::Catalog, catalog::Scalar, storage::Storage};
+use indexlake_index_hash::{HashIndex, HashIndexParams};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::{
+    counted_storage, create_namespace_if_not_exists, data_files_opened, prepare_testing_table,
+};
+use indexlake_integration_tests::utils::full_table_scan;
 use indexlake_integration_tests::{
-    catalog_postgres, catalog_sqlite, init_env_logger, storage_fs, storage_s3,
+    catalog_mariadb, catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite,
+    init_env_logger, storage_fs, storage_s3,
 };
-use indexlake_integration_tests::{data::prepare_testing_table, utils::full_table_scan};
 use std::sync::Arc;
 
 #[rstest::rstest]
 #[case(async { catalog_sqlite() }, storage_fs())]
 #[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
 #[tokio::test(flavor = "multi_thread")]
 async fn delete_table_by_condition(
     #[future(awt)]
@@ -23,7 +38,7 @@ async fn delete_table_by_condition(
     let table = prepare_testing_table(&client, "delete_table_by_condition").await?;
 
     let condition = Expr::Column("age".to_string()).gt(Expr::Literal(Scalar::Int32(Some(21))));
-    table.delete(&condition).await?;
+    assert_eq!(table.delete(&condition).await?, 2);
 
     let table_str = full_table_scan(&table).await?;
     println!("{}", table_str);
@@ -43,6 +58,10 @@ async fn delete_table_by_condition(
 #[rstest::rstest]
 #[case(async { catalog_sqlite() }, storage_fs())]
 #[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
 #[tokio::test(flavor = "multi_thread")]
 async fn delete_table_by_row_id(
     #[future(awt)]
@@ -57,7 +76,9 @@ async fn delete_table_by_row_id(
 
     let condition = Expr::Column(INTERNAL_ROW_ID_FIELD_NAME.to_string())
         .eq(Expr::Literal(Scalar::Int64(Some(1))));
-    table.delete(&condition).await?;
+    assert_eq!(table.delete(&condition).await?, 1);
+    // Already deleted rows are not counted again
+    assert_eq!(table.delete(&condition).await?, 0);
 
     let table_str = full_table_scan(&table).await?;
     println!("{}", table_str);
@@ -74,3 +95,220 @@ async fn delete_table_by_row_id(
 
     Ok(())
 }
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn delete_table_by_range(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    // Alice, Bob and Charlie are dumped into one data file, David stays inline
+    let table = prepare_testing_table(&client, "delete_table_by_range").await?;
+
+    let condition = col("age").gt_eq(lit(21)).and(col("age").lt(lit(22)));
+    assert_eq!(table.delete(&condition).await?, 1);
+    assert_eq!(
+        full_table_scan(&table).await?,
+        r#"+-------------------+---------+-----+
+| _indexlake_row_id | name    | age |
++-------------------+---------+-----+
+| 1                 | Alice   | 20  |
+| 3                 | Charlie | 22  |
+| 4                 | David   | 23  |
++-------------------+---------+-----+"#,
+    );
+
+    // Deletes the remaining rows of the data file and the inline row
+    assert_eq!(table.delete(&col("age").gt(lit(0))).await?, 3);
+    assert_eq!(
+        full_table_scan(&table).await?,
+        r#"+-------------------+------+-----+
+| _indexlake_row_id | name | age |
++-------------------+------+-----+
++-------------------+------+-----+"#,
+    );
+    assert_eq!(table.delete(&col("age").gt(lit(0))).await?, 0);
+
+    Ok(())
+}
+
+/// Table with a hash index on `name` and three data files of three rows, ages 0 to 2, 10 to 12
+/// and 20 to 22, and one inline row of age 30.
+async fn prepare_indexed_table(
+    client: &LakeClient,
+    table_name: &str,
+) -> Result<Table, Box<dyn std::error::Error>> {
+    create_namespace_if_not_exists(client, "test_namespace").await?;
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("name", DataType::Utf8, false),
+        Field::new("age", DataType::Int32, false),
+    ]));
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: schema.clone(),
+            config: TableConfig {
+                inline_row_count_limit: 3,
+                ..Default::default()
+            },
+        })
+        .await?;
+    let mut table = client.load_table("test_namespace", table_name).await?;
+    table
+        .create_index(IndexCreation {
+            name: "name_index".to_string(),
+            kind: HashIndex.kind().to_string(),
+            key_columns: vec!["name".to_string()],
+            include_columns: vec![],
+            params: Arc::new(HashIndexParams::default()),
+            where_predicate: None,
+        })
+        .await?;
+
+    for ages in [vec![0, 1, 2], vec![10, 11, 12], vec![20, 21, 22], vec![30]] {
+        let names = ages.iter().map(|age| format!("n{age}")).collect::<Vec<_>>();
+        table
+            .insert(&RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(StringArray::from(names)),
+                    Arc::new(Int32Array::from(ages)),
+                ],
+            )?)
+            .await?;
+        // wait for dump task to finish
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    }
+    Ok(table)
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn delete_table_with_index(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage.clone());
+    client.register_index(Arc::new(HashIndex))?;
+    let table = prepare_indexed_table(&client, "delete_table_with_index").await?;
+    let snapshot_count = table.snapshots().await?.len();
+
+    // the index selects the row to delete, only its data file is read
+    storage.reset_read_stats();
+    assert_eq!(
+        table
+            .delete(&col("name").eq(lit("n11".to_string())))
+            .await?,
+        1
+    );
+    assert_eq!(data_files_opened(&storage), 1);
+
+    // column statistics prune the other data files
+    storage.reset_read_stats();
+    assert_eq!(table.delete(&col("age").gt_eq(lit(21))).await?, 3);
+    assert_eq!(data_files_opened(&storage), 1);
+    assert_eq!(table.snapshots().await?.len(), snapshot_count + 2);
+
+    // no data file is read and no snapshot committed when nothing matches
+    storage.reset_read_stats();
+    assert_eq!(
+        table
+            .delete(&col("name").eq(lit("n99".to_string())))
+            .await?,
+        0
+    );
+    assert_eq!(table.delete(&col("age").gt(lit(100))).await?, 0);
+    assert_eq!(data_files_opened(&storage), 0);
+    assert_eq!(table.snapshots().await?.len(), snapshot_count + 2);
+
+    assert_eq!(
+        full_table_scan(&table).await?,
+        r#"+-------------------+------+-----+
+| _indexlake_row_id | name | age |
++-------------------+------+-----+
+| 1                 | n0   | 0   |
+| 2                 | n1   | 1   |
+| 3                 | n2   | 2   |
+| 4                 | n10  | 10  |
+| 6                 | n12  | 12  |
+| 7                 | n20  | 20  |
++-------------------+------+-----+"#,
+    );
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn delete_all_rows(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage.clone());
+    client.register_index(Arc::new(HashIndex))?;
+    let table = prepare_indexed_table(&client, "delete_all_rows").await?;
+    table.delete(&col("age").eq(lit(1))).await?;
+
+    // a failed delete leaves every row in place
+    let data_file = storage
+        .list_files(&table.table_dir())
+        .await?
+        .into_iter()
+        .find(|file| file.relative_path.ends_with(".parquet"))
+        .unwrap();
+    storage.delete(&data_file.relative_path).await?;
+    let snapshot_count = table.snapshots().await?.len();
+    assert!(table.delete(&col("age").gt_eq(lit(0))).await.is_err());
+    assert_eq!(table.snapshots().await?.len(), snapshot_count);
+
+    // rows are deleted without reading the data files
+    storage.reset_read_stats();
+    assert_eq!(table.delete_all().await?, 9);
+    assert_eq!(data_files_opened(&storage), 0);
+    let snapshots = table.snapshots().await?;
+    let snapshot = snapshots.last().unwrap();
+    assert_eq!(snapshot.operation, Some(SnapshotOperation::Delete));
+    assert_eq!(snapshot.removed_rows, 9);
+    assert_eq!(
+        full_table_scan(&table).await?,
+        r#"+-------------------+------+-----+
+| _indexlake_row_id | name | age |
++-------------------+------+-----+
++-------------------+------+-----+"#,
+    );
+
+    assert_eq!(table.delete_all().await?, 0);
+    assert_eq!(table.snapshots().await?.len(), snapshots.len());
+
+    Ok(())
+}
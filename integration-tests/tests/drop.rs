@@ -5,17 +5,26 @@ use indexlake::{
     LakeClient,
     catalog::Catalog,
     storage::Storage,
-    table::{TableConfig, TableCreation},
+    table::{TableConfig, TableCreation, TableIdent},
 };
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::prepare_testing_table;
 use indexlake_integration_tests::utils::full_table_scan;
 use indexlake_integration_tests::{
-    catalog_postgres, catalog_sqlite, init_env_logger, storage_fs, storage_s3,
+    catalog_mariadb, catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite,
+    init_env_logger, storage_fs, storage_s3,
 };
+use std::collections::HashMap;
 use std::sync::Arc;
 
 #[rstest::rstest]
 #[case(async { catalog_sqlite() }, storage_fs())]
 #[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
 #[tokio::test(flavor = "multi_thread")]
 async fn drop_table(
     #[future(awt)]
@@ -28,7 +37,9 @@ async fn drop_table(
     let client = LakeClient::new(catalog, storage);
 
     let namespace_name = "test_namespace";
-    client.create_namespace(namespace_name).await?;
+    client
+        .create_namespace(namespace_name, HashMap::new())
+        .await?;
 
     let table_schema = Arc::new(Schema::new(vec![
         Field::new("id", DataType::Int64, false),
@@ -75,3 +86,67 @@ async fn drop_table(
 
     Ok(())
 }
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, Arc::new(Storage::new_memory()))]
+#[case(async { catalog_postgres().await }, Arc::new(Storage::new_memory()))]
+#[case(async { catalog_mysql().await }, Arc::new(Storage::new_memory()))]
+#[case(async { catalog_memory() }, Arc::new(Storage::new_memory()))]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, Arc::new(Storage::new_memory())))]
+#[tokio::test(flavor = "multi_thread")]
+async fn drop_table_purge(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage.clone());
+    let kept_files = prepare_testing_table(&client, "drop_kept_files").await?;
+    let purged = prepare_testing_table(&client, "drop_purged").await?;
+    let kept_dir = kept_files.table_dir();
+    let purged_dir = purged.table_dir();
+    let files_before = storage.list_files(&kept_dir).await?;
+    assert!(!files_before.is_empty());
+    assert!(!storage.list_files(&purged_dir).await?.is_empty());
+
+    // without purge only the catalog forgets the table, its files stay for recovery
+    client
+        .drop_table(&TableIdent::new("test_namespace", "drop_kept_files"), false)
+        .await?;
+    assert!(
+        client
+            .load_table("test_namespace", "drop_kept_files")
+            .await
+            .is_err()
+    );
+    assert_eq!(storage.list_files(&kept_dir).await?, files_before);
+
+    client
+        .drop_table(&TableIdent::new("test_namespace", "drop_purged"), true)
+        .await?;
+    assert!(
+        client
+            .load_table("test_namespace", "drop_purged")
+            .await
+            .is_err()
+    );
+    assert!(storage.list_files(&purged_dir).await?.is_empty());
+    assert_eq!(storage.list_files(&kept_dir).await?, files_before);
+
+    assert!(
+        client
+            .drop_table(&TableIdent::new("test_namespace", "drop_purged"), true)
+            .await
+            .is_err()
+    );
+    assert!(
+        client
+            .drop_table(&TableIdent::new("not_exists", "drop_purged"), false)
+            .await
+            .is_err()
+    );
+
+    Ok(())
+}
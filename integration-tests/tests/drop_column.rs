@@ -0,0 +1,225 @@
+use arrow::array::{Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use indexlake::expr::{col, lit};
+use indexlake::table::{IndexCreation, TableScan};
+use indexlake::{LakeClient, catalog::Catalog, index::Index, storage::Storage};
+use indexlake_index_btree::{BTreeIndex, BTreeIndexParams};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::prepare_testing_table;
+use indexlake_integration_tests::utils::{full_table_scan, table_scan};
+use indexlake_integration_tests::{
+    catalog_mariadb, catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite,
+    init_env_logger, storage_fs, storage_s3,
+};
+use std::sync::Arc;
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn drop_column(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let mut table = prepare_testing_table(&client, "drop_column").await?;
+
+    let table_str = full_table_scan(&table).await?;
+    assert_eq!(
+        table_str,
+        r#"+-------------------+---------+-----+
+| _indexlake_row_id | name    | age |
++-------------------+---------+-----+
+| 1                 | Alice   | 20  |
+| 2                 | Bob     | 21  |
+| 3                 | Charlie | 22  |
+| 4                 | David   | 23  |
++-------------------+---------+-----+"#
+    );
+
+    table.drop_column("name", false).await?;
+    assert_eq!(table.schema.fields().len(), 2);
+    assert_eq!(table.schema.field(1).name(), "age");
+
+    let expected = r#"+-------------------+-----+
+| _indexlake_row_id | age |
++-------------------+-----+
+| 1                 | 20  |
+| 2                 | 21  |
+| 3                 | 22  |
+| 4                 | 23  |
++-------------------+-----+"#;
+    assert_eq!(full_table_scan(&table).await?, expected);
+    let loaded_table = client.load_table("test_namespace", "drop_column").await?;
+    assert_eq!(loaded_table.schema, table.schema);
+    assert_eq!(full_table_scan(&loaded_table).await?, expected);
+
+    let scan = TableScan::default().with_filters(vec![col("age").gt(lit(21))]);
+    assert_eq!(
+        table_scan(&table, scan).await?,
+        r#"+-------------------+-----+
+| _indexlake_row_id | age |
++-------------------+-----+
+| 3                 | 22  |
+| 4                 | 23  |
++-------------------+-----+"#
+    );
+
+    // Inserts with the dropped column are rejected
+    let old_batch = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("name", DataType::Utf8, false),
+            Field::new("age", DataType::Int32, false),
+        ])),
+        vec![
+            Arc::new(StringArray::from(vec!["Eve"])),
+            Arc::new(Int32Array::from(vec![24])),
+        ],
+    )?;
+    assert!(table.insert(&old_batch).await.is_err());
+
+    let new_batch = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![Field::new("age", DataType::Int32, false)])),
+        vec![Arc::new(Int32Array::from(vec![24, 25, 26]))],
+    )?;
+    table.insert(&new_batch).await?;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+
+    assert_eq!(
+        full_table_scan(&table).await?,
+        r#"+-------------------+-----+
+| _indexlake_row_id | age |
++-------------------+-----+
+| 1                 | 20  |
+| 2                 | 21  |
+| 3                 | 22  |
+| 4                 | 23  |
+| 5                 | 24  |
+| 6                 | 25  |
+| 7                 | 26  |
++-------------------+-----+"#
+    );
+
+    let result = table.drop_column("name", false).await;
+    assert!(result.unwrap_err().to_string().contains("not found"));
+    let result = table
+        .add_column(Field::new("name", DataType::Utf8, true), None)
+        .await;
+    assert!(result.unwrap_err().to_string().contains("was dropped"));
+    let result = table.drop_column("age", false).await;
+    assert!(result.unwrap_err().to_string().contains("last column"));
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn drop_indexed_column(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage);
+    client.register_index(Arc::new(BTreeIndex))?;
+    let mut table = prepare_testing_table(&client, "drop_indexed_column").await?;
+
+    table
+        .create_index(IndexCreation {
+            name: "name_index".to_string(),
+            kind: BTreeIndex.kind().to_string(),
+            key_columns: vec!["name".to_string()],
+            include_columns: vec!["age".to_string()],
+            params: Arc::new(BTreeIndexParams::default()),
+            where_predicate: None,
+        })
+        .await?;
+
+    for column in ["name", "age"] {
+        let result = table.drop_column(column, false).await;
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("is used by index name_index")
+        );
+    }
+    assert_eq!(table.schema.fields().len(), 3);
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn drop_indexed_column_cascade(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage);
+    client.register_index(Arc::new(BTreeIndex))?;
+    let mut table = prepare_testing_table(&client, "drop_indexed_column_cascade").await?;
+
+    for (index_name, key_column) in [("name_index", "name"), ("age_index", "age")] {
+        table
+            .create_index(IndexCreation {
+                name: index_name.to_string(),
+                kind: BTreeIndex.kind().to_string(),
+                key_columns: vec![key_column.to_string()],
+                include_columns: vec![],
+                params: Arc::new(BTreeIndexParams::default()),
+                where_predicate: None,
+            })
+            .await?;
+    }
+
+    table.drop_column("name", true).await?;
+    assert_eq!(table.indexes.keys().collect::<Vec<_>>(), vec!["age_index"]);
+    let loaded_table = client
+        .load_table("test_namespace", "drop_indexed_column_cascade")
+        .await?;
+    assert_eq!(
+        loaded_table.indexes.keys().collect::<Vec<_>>(),
+        vec!["age_index"]
+    );
+
+    let scan = TableScan::default().with_filters(vec![col("age").gt(lit(21))]);
+    assert_eq!(
+        table_scan(&loaded_table, scan).await?,
+        r#"+-------------------+-----+
+| _indexlake_row_id | age |
++-------------------+-----+
+| 3                 | 22  |
+| 4                 | 23  |
++-------------------+-----+"#
+    );
+
+    Ok(())
+}
@@ -0,0 +1,310 @@
+use arrow::array::{Int64Array, RecordBatch};
+use arrow::datatypes::{DataType, Field, Schema};
+use futures::TryStreamExt;
+use indexlake::table::{
+    ExpireReport, SnapshotOperation, Table, TableConfig, TableCreation, TableScan, TtlPolicy,
+};
+use indexlake::{ILError, ILResult, LakeClient, catalog::Catalog, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::{counted_storage, create_namespace_if_not_exists};
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger,
+};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
+
+fn events_batch(ids: Vec<i64>, created_at: Vec<Option<i64>>) -> ILResult<RecordBatch> {
+    Ok(RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("created_at", DataType::Int64, true),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(ids)),
+            Arc::new(Int64Array::from(created_at)),
+        ],
+    )?)
+}
+
+async fn scanned_ids(table: &Table, scan: TableScan) -> ILResult<Vec<i64>> {
+    let batches = table.scan(scan).await?.try_collect::<Vec<_>>().await?;
+    let mut ids = batches
+        .iter()
+        .flat_map(|batch| {
+            batch
+                .column_by_name("id")
+                .unwrap()
+                .as_any()
+                .downcast_ref::<Int64Array>()
+                .unwrap()
+                .values()
+                .to_vec()
+        })
+        .collect::<Vec<_>>();
+    ids.sort();
+    Ok(ids)
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn expire_rows(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage.clone());
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("created_at", DataType::Int64, true),
+    ]));
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "expire_rows".to_string(),
+            schema: schema.clone(),
+            config: TableConfig {
+                inline_row_count_limit: 3,
+                ttl: Some(TtlPolicy::new("created_at", Duration::from_secs(3600))),
+                ..Default::default()
+            },
+        })
+        .await?;
+    let mut table = client.load_table("test_namespace", "expire_rows").await?;
+
+    let now = now_ms();
+    // a data file of expired rows, a data file with a fresh row and a null timestamp, and
+    // inline rows
+    table
+        .insert(&events_batch(
+            vec![1, 2, 3],
+            vec![
+                Some(now - 3 * DAY_MS),
+                Some(now - 2 * DAY_MS),
+                Some(now - DAY_MS),
+            ],
+        )?)
+        .await?;
+    // wait for dump task to finish
+    tokio::time::sleep(Duration::from_secs(3)).await;
+    table
+        .insert(&events_batch(
+            vec![4, 5, 6],
+            vec![Some(now - 2 * DAY_MS), Some(now), None],
+        )?)
+        .await?;
+    tokio::time::sleep(Duration::from_secs(3)).await;
+    table
+        .insert(&events_batch(
+            vec![7, 8],
+            vec![Some(now - DAY_MS), Some(now)],
+        )?)
+        .await?;
+    let before_expire = table.snapshots().await?.last().unwrap().snapshot_id;
+
+    // the data file of expired rows is dropped without being read
+    storage.reset_read_stats();
+    let report = table.expire_rows().await?;
+    assert_eq!(report.removed_rows, 5);
+    assert_eq!(report.removed_files, 1);
+    assert!(report.removed_bytes > 0);
+    let opened_paths = storage.read_stats().unwrap().opened_paths;
+    assert_eq!(opened_paths.len(), 1);
+    assert_eq!(
+        table.snapshots().await?.last().unwrap().operation,
+        Some(SnapshotOperation::Expire)
+    );
+    assert_eq!(
+        scanned_ids(&table, TableScan::default()).await?,
+        vec![5, 6, 8]
+    );
+    // earlier snapshots still read the expired rows
+    assert_eq!(
+        scanned_ids(&table, TableScan::default().at_snapshot(before_expire)).await?,
+        vec![1, 2, 3, 4, 5, 6, 7, 8]
+    );
+
+    // nothing left to expire commits no snapshot
+    let latest = table.snapshots().await?.last().unwrap().snapshot_id;
+    assert_eq!(table.expire_rows().await?, ExpireReport::default());
+    assert_eq!(table.snapshots().await?.last().unwrap().snapshot_id, latest);
+
+    // the TTL column can not be dropped while the TTL uses it
+    assert!(matches!(
+        table.drop_column("created_at", false).await,
+        Err(ILError::InvalidInput(_))
+    ));
+    // the TTL follows a renamed column
+    table.rename_column("created_at", "inserted_at").await?;
+    let table = client.load_table("test_namespace", "expire_rows").await?;
+    assert_eq!(
+        table.config.ttl,
+        Some(TtlPolicy::new("inserted_at", Duration::from_secs(3600)))
+    );
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, Arc::new(Storage::new_memory()))]
+#[case(async { catalog_postgres().await }, Arc::new(Storage::new_memory()))]
+#[case(async { catalog_mysql().await }, Arc::new(Storage::new_memory()))]
+#[case(async { catalog_memory() }, Arc::new(Storage::new_memory()))]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, Arc::new(Storage::new_memory())))]
+#[tokio::test(flavor = "multi_thread")]
+async fn change_ttl(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("created_at", DataType::Int64, true),
+    ]));
+
+    // TTL columns must be columns of the schema
+    for ttl in [
+        TtlPolicy::new("id_", Duration::from_secs(60)),
+        TtlPolicy::new("_indexlake_row_id", Duration::from_secs(60)),
+    ] {
+        let result = client
+            .create_table(TableCreation {
+                namespace_name: "test_namespace".to_string(),
+                table_name: "change_ttl".to_string(),
+                schema: schema.clone(),
+                config: TableConfig {
+                    ttl: Some(ttl),
+                    ..Default::default()
+                },
+            })
+            .await;
+        assert!(matches!(result, Err(ILError::InvalidInput(_))));
+    }
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "change_ttl".to_string(),
+            schema: schema.clone(),
+            config: TableConfig::default(),
+        })
+        .await?;
+    let mut table = client.load_table("test_namespace", "change_ttl").await?;
+
+    let now = now_ms();
+    table
+        .insert(&events_batch(
+            vec![1, 2, 3],
+            vec![Some(now - 10 * DAY_MS), Some(now - 2 * DAY_MS), Some(now)],
+        )?)
+        .await?;
+    assert!(matches!(
+        table.expire_rows().await,
+        Err(ILError::InvalidInput(_))
+    ));
+
+    table
+        .set_ttl(Some(TtlPolicy::new(
+            "created_at",
+            Duration::from_secs(7 * 24 * 3600),
+        )))
+        .await?;
+    assert_eq!(client.expire_rows().await?.removed_rows, 1);
+    assert_eq!(scanned_ids(&table, TableScan::default()).await?, vec![2, 3]);
+
+    // a shorter retention applies to the next expiration, no data is rewritten
+    table
+        .set_ttl(Some(TtlPolicy::new(
+            "created_at",
+            Duration::from_secs(3600),
+        )))
+        .await?;
+    let table = client.load_table("test_namespace", "change_ttl").await?;
+    assert_eq!(table.expire_rows().await?.removed_rows, 1);
+    assert_eq!(scanned_ids(&table, TableScan::default()).await?, vec![3]);
+
+    let mut table = table;
+    table.set_ttl(None).await?;
+    let table = client.load_table("test_namespace", "change_ttl").await?;
+    assert!(table.config.ttl.is_none());
+    assert_eq!(client.expire_rows().await?, ExpireReport::default());
+
+    assert!(matches!(
+        table
+            .clone()
+            .set_ttl(Some(TtlPolicy::new("id_", Duration::from_secs(60))))
+            .await,
+        Err(ILError::InvalidInput(_))
+    ));
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, Arc::new(Storage::new_memory()))]
+#[case(async { catalog_memory() }, Arc::new(Storage::new_memory()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn background_row_expiration(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "background_row_expiration".to_string(),
+            schema: Arc::new(Schema::new(vec![
+                Field::new("id", DataType::Int64, false),
+                Field::new("created_at", DataType::Int64, true),
+            ])),
+            config: TableConfig {
+                ttl: Some(TtlPolicy::new("created_at", Duration::from_secs(3600))),
+                ..Default::default()
+            },
+        })
+        .await?;
+    let table = client
+        .load_table("test_namespace", "background_row_expiration")
+        .await?;
+
+    let handle = client.spawn_row_expiration(Duration::from_millis(200));
+    let now = now_ms();
+    table
+        .insert(&events_batch(
+            vec![1, 2],
+            vec![Some(now - DAY_MS), Some(now)],
+        )?)
+        .await?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    handle.abort();
+    assert_eq!(scanned_ids(&table, TableScan::default()).await?, vec![2]);
+
+    Ok(())
+}
@@ -0,0 +1,331 @@
+use arrow::array::{AsArray, Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Int32Type, Schema};
+use futures::TryStreamExt;
+use indexlake::catalog::Scalar;
+use indexlake::expr::{col, lit};
+use indexlake::table::{
+    EXPORT_NULL_PARTITION, ExportManifest, ExportOptions, Table, TableConfig, TableCreation,
+    TableScan,
+};
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::create_namespace_if_not_exists;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_postgres, catalog_sqlite, init_env_logger, storage_fs, storage_s3,
+};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+async fn prepare_table(
+    client: &LakeClient,
+    table_name: &str,
+) -> Result<Table, Box<dyn std::error::Error>> {
+    create_namespace_if_not_exists(client, "test_namespace").await?;
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("name", DataType::Utf8, true),
+    ]));
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: schema.clone(),
+            config: TableConfig {
+                inline_row_count_limit: 60,
+                ..Default::default()
+            },
+        })
+        .await?;
+    let table = client.load_table("test_namespace", table_name).await?;
+
+    for start in [0, 50] {
+        let ids = (start..start + 50).collect::<Vec<_>>();
+        let names = ids.iter().map(|id| format!("name{id}")).collect::<Vec<_>>();
+        table
+            .insert(&RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(Int32Array::from(ids)),
+                    Arc::new(StringArray::from(names)),
+                ],
+            )?)
+            .await?;
+    }
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    Ok(table)
+}
+
+/// Reads an exported file back with the parquet reader of arrow.
+fn read_parquet(path: &Path) -> Result<Vec<RecordBatch>, Box<dyn std::error::Error>> {
+    let file = std::fs::File::open(path)?;
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)?.build()?;
+    Ok(reader.collect::<Result<Vec<_>, _>>()?)
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn export_parquet_files(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let table = prepare_table(&client, "export_parquet_files").await?;
+
+    let dest_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tmp/export")
+        .join(uuid::Uuid::new_v4().to_string());
+    let dest = Storage::new_fs(&dest_root);
+
+    // rows are read from the data file and the inline rows, each batch ends up in a file of its
+    // own
+    let scan = TableScan::default()
+        .with_columns(Some(vec!["name".to_string(), "id".to_string()]))
+        .with_filters(vec![col("id").gt_eq(lit(10i32))])
+        .with_batch_size(Some(20));
+    let options = ExportOptions::default()
+        .with_scan(scan.clone())
+        .with_target_file_size(1u64);
+    let exported_files = table.export_parquet(options, &dest, "out/small").await?;
+    assert!(exported_files.len() >= 5);
+    let row_counts = exported_files
+        .iter()
+        .map(|file| file.row_count)
+        .collect::<Vec<_>>();
+    assert_eq!(row_counts.iter().sum::<usize>(), 90);
+    assert!(row_counts.iter().all(|row_count| *row_count <= 20));
+
+    let expected_schema = scan.projected_schema(&table.schema)?;
+    let mut ids = Vec::new();
+    for (i, exported_file) in exported_files.iter().enumerate() {
+        assert_eq!(
+            exported_file.relative_path,
+            format!("out/small/part-{i:05}.parquet")
+        );
+        let path = dest_root.join(&exported_file.relative_path);
+        assert_eq!(
+            exported_file.file_size_bytes,
+            std::fs::metadata(&path)?.len()
+        );
+        let batches = read_parquet(&path)?;
+        let mut row_count = 0;
+        for batch in batches {
+            assert_eq!(batch.schema().fields(), expected_schema.fields());
+            for (id, name) in batch
+                .column(1)
+                .as_primitive::<Int32Type>()
+                .values()
+                .iter()
+                .zip(batch.column(0).as_string::<i32>().iter())
+            {
+                assert_eq!(name, Some(format!("name{id}").as_str()));
+                ids.push(*id);
+            }
+            row_count += batch.num_rows();
+        }
+        assert_eq!(row_count, exported_file.row_count);
+    }
+    ids.sort();
+    assert_eq!(ids, (10..100).collect::<Vec<_>>());
+
+    // all rows and columns in one file
+    let exported_files = table
+        .export_parquet(ExportOptions::default(), &dest, "out/all")
+        .await?;
+    assert_eq!(exported_files.len(), 1);
+    assert_eq!(exported_files[0].row_count, 100);
+    let batches = read_parquet(&dest_root.join(&exported_files[0].relative_path))?;
+    assert_eq!(batches[0].schema().fields(), table.schema.fields());
+    // the manifest lists the files along with the snapshot read
+    let manifest: ExportManifest =
+        serde_json::from_slice(&std::fs::read(dest_root.join("out/all/_manifest.json"))?)?;
+    assert_eq!(manifest.files, exported_files);
+    assert_eq!(
+        manifest.snapshot_id,
+        Some(table.current_snapshot_id().await?)
+    );
+
+    // no file without matching rows
+    let scan = TableScan::default().with_filters(vec![col("id").gt_eq(lit(100i32))]);
+    let exported_files = table
+        .export_parquet(ExportOptions::default().with_scan(scan), &dest, "out/none")
+        .await?;
+    assert!(exported_files.is_empty());
+    assert!(!dest_root.join("out/none").exists());
+
+    let result = table
+        .export_parquet(ExportOptions::default(), &dest, "../out")
+        .await;
+    assert!(result.is_err());
+
+    std::fs::remove_dir_all(&dest_root)?;
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn export_parquet_partitioned(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let table = prepare_table(&client, "export_parquet_partitioned").await?;
+    table
+        .update(
+            &col("id").gt_eq(lit(90i32)),
+            vec![("name".to_string(), lit(Scalar::Utf8(None)))],
+        )
+        .await?;
+    table
+        .update(
+            &col("id").lt(lit(10i32)),
+            vec![("name".to_string(), lit("a/b".to_string()))],
+        )
+        .await?;
+
+    let dest_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tmp/export")
+        .join(uuid::Uuid::new_v4().to_string());
+    let dest = Storage::new_fs(&dest_root);
+
+    // rows of each name go to a directory of their own, without the name or row id column
+    let scan = TableScan::default().with_filters(vec![
+        col("id")
+            .lt(lit(10i32))
+            .or(col("id").gt_eq(lit(90i32)))
+            .or(col("id").eq(lit(42i32))),
+    ]);
+    let options = ExportOptions::default()
+        .with_scan(scan)
+        .with_partition_by(vec!["name".to_string()])
+        .with_include_row_id(false);
+    let exported_files = table.export_parquet(options, &dest, "out").await?;
+    let files = exported_files
+        .iter()
+        .map(|file| (file.relative_path.as_str(), file.row_count))
+        .collect::<Vec<_>>();
+    let null_path = format!("out/name={EXPORT_NULL_PARTITION}/part-00000.parquet");
+    assert_eq!(
+        files,
+        vec![
+            (null_path.as_str(), 10),
+            ("out/name=a%2Fb/part-00000.parquet", 10),
+            ("out/name=name42/part-00000.parquet", 1),
+        ]
+    );
+    let batches = read_parquet(&dest_root.join("out/name=name42/part-00000.parquet"))?;
+    let field_names = batches[0]
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| field.name().clone())
+        .collect::<Vec<_>>();
+    assert_eq!(field_names, vec!["id"]);
+    assert_eq!(
+        batches[0].column(0).as_primitive::<Int32Type>().values(),
+        &[42]
+    );
+    assert!(dest_root.join("out/_manifest.json").exists());
+
+    // partition columns must be scanned and something must be left to write
+    let scan = TableScan::default().with_columns(Some(vec!["id".to_string()]));
+    let options = ExportOptions::default()
+        .with_scan(scan.clone())
+        .with_partition_by(vec!["name".to_string()]);
+    assert!(table.export_parquet(options, &dest, "bad").await.is_err());
+    let options = ExportOptions::default()
+        .with_scan(scan)
+        .with_partition_by(vec!["id".to_string()]);
+    assert!(table.export_parquet(options, &dest, "bad").await.is_err());
+
+    std::fs::remove_dir_all(&dest_root)?;
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn export_parquet_single_snapshot(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let table = prepare_table(&client, "export_parquet_single_snapshot").await?;
+
+    let dest_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tmp/export")
+        .join(uuid::Uuid::new_v4().to_string());
+    let dest = Storage::new_fs(&dest_root);
+
+    // rows inserted and deleted during the export are all in or all out
+    let writer = table.clone();
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("name", DataType::Utf8, true),
+    ]));
+    let writes = tokio::spawn(async move {
+        for id in 100..110 {
+            let batch = RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(Int32Array::from(vec![id])),
+                    Arc::new(StringArray::from(vec![format!("name{id}")])),
+                ],
+            )?;
+            writer.insert(&batch).await?;
+            writer.delete(&col("id").eq(lit(id - 100))).await?;
+        }
+        Ok::<_, indexlake::ILError>(())
+    });
+    let exported_files = table
+        .export_parquet(ExportOptions::default(), &dest, "out")
+        .await?;
+    writes.await??;
+
+    let manifest: ExportManifest =
+        serde_json::from_slice(&std::fs::read(dest_root.join("out/_manifest.json"))?)?;
+    let snapshot_id = manifest.snapshot_id.unwrap();
+    let mut exported_ids: Vec<i32> = Vec::new();
+    for exported_file in &exported_files {
+        for batch in read_parquet(&dest_root.join(&exported_file.relative_path))? {
+            exported_ids.extend(batch.column(1).as_primitive::<Int32Type>().values().iter());
+        }
+    }
+    exported_ids.sort();
+    let mut snapshot_ids: Vec<i32> = Vec::new();
+    let scan = TableScan::default().at_snapshot(snapshot_id);
+    for batch in table.scan(scan).await?.try_collect::<Vec<_>>().await? {
+        snapshot_ids.extend(batch.column(1).as_primitive::<Int32Type>().values().iter());
+    }
+    snapshot_ids.sort();
+    assert_eq!(exported_ids, snapshot_ids);
+    assert_eq!(exported_ids.len(), 100);
+
+    std::fs::remove_dir_all(&dest_root)?;
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use arrow::util::pretty::pretty_format_batches;
+use arrow_flight::FlightClient;
+use arrow_flight::error::FlightError;
+use futures::TryStreamExt;
+use indexlake::catalog::INTERNAL_ROW_ID_FIELD_NAME;
+use indexlake::expr::{col, lit};
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+use indexlake_flight::{IndexLakeFlightService, ScanTicket};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::prepare_testing_table;
+use indexlake_integration_tests::utils::{sort_record_batches, table_scan};
+use indexlake_integration_tests::{
+    catalog_memory, catalog_postgres, catalog_sqlite, init_env_logger, storage_fs, storage_s3,
+};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tonic::Code;
+use tonic::transport::Channel;
+
+fn status_code(error: FlightError) -> Option<Code> {
+    match error {
+        FlightError::Tonic(status) => Some(status.code()),
+        _ => None,
+    }
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn flight_scan(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    // Alice, Bob and Charlie are dumped into one data file, David stays inline
+    let table = prepare_testing_table(&client, "flight_scan").await?;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let service = IndexLakeFlightService::new(client).with_bearer_token("secret");
+    let server = tokio::spawn(service.serve(listener));
+
+    let channel = Channel::from_shared(format!("http://{addr}"))?
+        .connect()
+        .await?;
+    let mut flight_client = FlightClient::new(channel);
+    let ticket = ScanTicket::new("test_namespace", "flight_scan")
+        .with_columns(Some(vec![
+            INTERNAL_ROW_ID_FIELD_NAME.to_string(),
+            "name".to_string(),
+        ]))
+        .with_predicate(Some(col("age").gt(lit(20))));
+
+    // requests without the token are rejected
+    let err = flight_client
+        .get_flight_info(ticket.to_descriptor()?)
+        .await
+        .unwrap_err();
+    assert_eq!(status_code(err), Some(Code::Unauthenticated));
+    let err = flight_client.do_get(ticket.to_ticket()?).await.unwrap_err();
+    assert_eq!(status_code(err), Some(Code::Unauthenticated));
+
+    flight_client.add_header("authorization", "Bearer secret")?;
+    let info = flight_client
+        .get_flight_info(ticket.to_descriptor()?)
+        .await?;
+    let scan = ticket.to_scan();
+    assert_eq!(
+        Arc::new(info.clone().try_decode_schema()?),
+        scan.projected_schema(&table.schema)?
+    );
+
+    // the batches streamed from the endpoint are those of a local scan
+    let endpoint_ticket = info.endpoint[0].ticket.clone().unwrap();
+    let batches = flight_client
+        .do_get(endpoint_ticket)
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    let sorted_batch = sort_record_batches(&batches, INTERNAL_ROW_ID_FIELD_NAME)?;
+    let flight_str = pretty_format_batches(&[sorted_batch])?.to_string();
+    assert_eq!(flight_str, table_scan(&table, scan).await?);
+    assert_eq!(
+        flight_str,
+        r#"+-------------------+---------+
+| _indexlake_row_id | name    |
++-------------------+---------+
+| 2                 | Bob     |
+| 3                 | Charlie |
+| 4                 | David   |
++-------------------+---------+"#,
+    );
+
+    // tickets built by clients that do not link the crate
+    let json_ticket = r#"{"namespace": "test_namespace", "table": "flight_scan"}"#;
+    let batches = flight_client
+        .do_get(arrow_flight::Ticket::new(json_ticket))
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    let row_count = batches.iter().map(|batch| batch.num_rows()).sum::<usize>();
+    assert_eq!(row_count, 4);
+
+    let err = flight_client
+        .do_get(arrow_flight::Ticket::new("not a ticket"))
+        .await
+        .unwrap_err();
+    assert_eq!(status_code(err), Some(Code::InvalidArgument));
+
+    server.abort();
+    Ok(())
+}
@@ -0,0 +1,323 @@
+use arrow::array::{AsArray, RecordBatch};
+use arrow::datatypes::{DataType, Field, Schema};
+use futures::TryStreamExt;
+use indexlake::expr::{col, lit};
+use indexlake::table::{Table, TableConfig, TableCreation, TableScan};
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::create_namespace_if_not_exists;
+use indexlake_integration_tests::utils::{full_table_scan, table_scan};
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger, storage_fs,
+    storage_s3,
+};
+use indexlake_io_csv::{
+    CsvImportOptions, CsvRowError, OnMalformedRow, import_csv, import_csv_or_create,
+};
+use std::sync::Arc;
+
+async fn create_table(
+    client: &LakeClient,
+    table_name: &str,
+) -> Result<Table, Box<dyn std::error::Error>> {
+    create_namespace_if_not_exists(client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: Arc::new(Schema::new(vec![
+                Field::new("id", DataType::Int32, false),
+                Field::new("name", DataType::Utf8, true),
+                Field::new("score", DataType::Float64, true),
+            ])),
+            config: TableConfig::default(),
+        })
+        .await?;
+    Ok(client.load_table("test_namespace", table_name).await?)
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn import_csv_quoted_fields(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let table = create_table(&client, "import_csv_quoted_fields").await?;
+
+    // columns in another order than the table, empty fields are nulls
+    let csv =
+        "name,id,score\n\"Doe, John\",1,1.5\n\"say \"\"hi\"\"\",2,\n\"multi\nline\",3,2.25\n,4,3\n";
+    let report = import_csv(&table, csv.as_bytes(), &CsvImportOptions::default()).await?;
+    assert_eq!(report.imported_rows, 4);
+    assert!(report.skipped_rows.is_empty());
+
+    let scan = TableScan::default().with_filters(vec![col("id").neq(lit(3i32))]);
+    assert_eq!(
+        table_scan(&table, scan).await?,
+        r#"+-------------------+----+-----------+-------+
+| _indexlake_row_id | id | name      | score |
++-------------------+----+-----------+-------+
+| 1                 | 1  | Doe, John | 1.5   |
+| 2                 | 2  | say "hi"  |       |
+| 4                 | 4  |           | 3.0   |
++-------------------+----+-----------+-------+"#,
+    );
+
+    let scan = TableScan::default()
+        .with_columns(Some(vec!["name".to_string()]))
+        .with_filters(vec![col("id").eq(lit(3i32))]);
+    let batches = table
+        .scan(scan)
+        .await?
+        .try_collect::<Vec<RecordBatch>>()
+        .await?;
+    let names = batches
+        .iter()
+        .flat_map(|batch| {
+            batch
+                .column(0)
+                .as_string::<i32>()
+                .iter()
+                .map(|name| name.map(|name| name.to_string()))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec![Some("multi\nline".to_string())]);
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn import_csv_malformed_rows(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let table = create_table(&client, "import_csv_malformed_rows").await?;
+
+    // the first row spans two lines
+    let csv = "1;\"first\nrow\";1.0\n2;second;abc\nx;third;2.0\n4;fourth\n5;;5.5\n;sixth;6.0\n";
+    let options = CsvImportOptions::default()
+        .with_delimiter(b';')
+        .with_has_header(false);
+
+    let err = import_csv(&table, csv.as_bytes(), &options)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("line 3, column score: invalid Float64 value \"abc\""),
+        "{err}"
+    );
+    // nothing is imported when the import fails
+    assert_eq!(full_table_scan(&table).await?.lines().count(), 4);
+
+    let options = options.with_on_malformed_row(OnMalformedRow::Skip);
+    let report = import_csv(&table, csv.as_bytes(), &options).await?;
+    assert_eq!(report.imported_rows, 2);
+    let row_error = |line: u64, column: Option<&str>, message: &str| CsvRowError {
+        line,
+        column: column.map(|column| column.to_string()),
+        message: message.to_string(),
+    };
+    assert_eq!(
+        report.skipped_rows,
+        vec![
+            row_error(3, Some("score"), "invalid Float64 value \"abc\""),
+            row_error(4, Some("id"), "invalid Int32 value \"x\""),
+            row_error(5, None, "expected 3 fields, found 2"),
+            row_error(7, Some("id"), "null value in non-nullable column"),
+        ]
+    );
+    let scan = TableScan::default().with_filters(vec![col("id").eq(lit(5i32))]);
+    assert_eq!(
+        table_scan(&table, scan).await?,
+        r#"+-------------------+----+------+-------+
+| _indexlake_row_id | id | name | score |
++-------------------+----+------+-------+
+| 2                 | 5  |      | 5.5   |
++-------------------+----+------+-------+"#,
+    );
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn import_csv_infer_schema(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+
+    let csv = "id,price,active,label\n1,1.5,true,a\n2,2,FALSE,\n3,,true,c\n";
+    let (table, report) = import_csv_or_create(
+        &client,
+        "test_namespace",
+        "import_csv_infer_schema",
+        TableConfig::default(),
+        csv.as_bytes(),
+        &CsvImportOptions::default(),
+    )
+    .await?;
+    assert_eq!(report.imported_rows, 3);
+    let data_types = table
+        .schema
+        .fields()
+        .iter()
+        .skip(1)
+        .map(|field| (field.name().as_str(), field.data_type().clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        data_types,
+        vec![
+            ("id", DataType::Int64),
+            ("price", DataType::Float64),
+            ("active", DataType::Boolean),
+            ("label", DataType::Utf8),
+        ]
+    );
+
+    // rows are imported into the existing table
+    let (table, report) = import_csv_or_create(
+        &client,
+        "test_namespace",
+        "import_csv_infer_schema",
+        TableConfig::default(),
+        "label,id\nd,4\n".as_bytes(),
+        &CsvImportOptions::default(),
+    )
+    .await?;
+    assert_eq!(report.imported_rows, 1);
+    assert_eq!(
+        full_table_scan(&table).await?,
+        r#"+-------------------+----+-------+--------+-------+
+| _indexlake_row_id | id | price | active | label |
++-------------------+----+-------+--------+-------+
+| 1                 | 1  | 1.5   | true   | a     |
+| 2                 | 2  | 2.0   | false  |       |
+| 3                 | 3  |       | true   | c     |
+| 4                 | 4  |       |        | d     |
++-------------------+----+-------+--------+-------+"#,
+    );
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn import_csv_chunked_lossy(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "import_csv_chunked_lossy".to_string(),
+            schema: Arc::new(Schema::new(vec![
+                Field::new("id", DataType::Int32, false),
+                Field::new("score", DataType::Float64, true),
+                Field::new("passed", DataType::Boolean, true),
+            ])),
+            config: TableConfig::default(),
+        })
+        .await?;
+    let table = client
+        .load_table("test_namespace", "import_csv_chunked_lossy")
+        .await?;
+
+    let csv = "id,score,passed\n1,1.5,true\n2,abc,false\nx,2.0,\n4,4.5,maybe\n5,NA,true\n";
+    let (rejects, received) = std::sync::mpsc::channel();
+    let options = CsvImportOptions::default()
+        .with_null_token("NA".to_string())
+        .with_lossy_casts(true)
+        .with_commit_rows(Some(2))
+        .with_on_malformed_row(OnMalformedRow::Skip)
+        .with_rejects(Some(rejects));
+    let snapshot_count = table.snapshots().await?.len();
+    let report = import_csv(&table, csv.as_bytes(), &options).await?;
+    drop(options);
+    assert_eq!(report.imported_rows, 4);
+    assert_eq!(report.skipped_row_count, 1);
+    assert_eq!(report.bytes_read, csv.len() as u64);
+    // rejected rows go to the sink instead of the report
+    assert!(report.skipped_rows.is_empty());
+    assert_eq!(
+        received.iter().collect::<Vec<_>>(),
+        vec![CsvRowError {
+            line: 4,
+            column: Some("id".to_string()),
+            message: "invalid Int32 value \"x\"".to_string(),
+        }]
+    );
+    // values that do not parse in nullable columns are nulls
+    assert_eq!(
+        full_table_scan(&table).await?,
+        r#"+-------------------+----+-------+--------+
+| _indexlake_row_id | id | score | passed |
++-------------------+----+-------+--------+
+| 1                 | 1  | 1.5   | true   |
+| 2                 | 2  |       | false  |
+| 3                 | 4  | 4.5   |        |
+| 4                 | 5  |       | true   |
++-------------------+----+-------+--------+"#,
+    );
+    // rows are committed two at a time
+    assert_eq!(table.snapshots().await?.len(), snapshot_count + 2);
+
+    let err = import_csv(
+        &table,
+        csv.as_bytes(),
+        &CsvImportOptions::default().with_commit_rows(Some(0)),
+    )
+    .await
+    .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("commit rows must be greater than 0")
+    );
+
+    Ok(())
+}
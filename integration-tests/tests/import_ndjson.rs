@@ -0,0 +1,176 @@
+use arrow::array::{Int32Array, Int64Builder, ListBuilder};
+use arrow::datatypes::{DataType, Field, Schema};
+use futures::TryStreamExt;
+use indexlake::table::{Table, TableConfig, TableCreation, TableScan};
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::create_namespace_if_not_exists;
+use indexlake_integration_tests::utils::{full_table_scan, sort_record_batches};
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger, storage_fs,
+    storage_s3,
+};
+use indexlake_io_json::{JsonRowError, NdjsonImportOptions, OnMalformedRow, import_ndjson};
+use std::sync::Arc;
+
+async fn create_table(
+    client: &LakeClient,
+    table_name: &str,
+) -> Result<Table, Box<dyn std::error::Error>> {
+    create_namespace_if_not_exists(client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: Arc::new(Schema::new(vec![
+                Field::new("id", DataType::Int32, false),
+                Field::new("name", DataType::Utf8, true),
+                Field::new("score", DataType::Float64, true),
+                Field::new(
+                    "scores",
+                    DataType::List(Arc::new(Field::new_list_field(DataType::Int64, true))),
+                    true,
+                ),
+            ])),
+            config: TableConfig::default(),
+        })
+        .await?;
+    Ok(client.load_table("test_namespace", table_name).await?)
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn import_ndjson_rows(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let table = create_table(&client, "import_ndjson_rows").await?;
+
+    // keys in any order, missing keys and JSON nulls are nulls, numbers convert to strings
+    let ndjson = r#"{"id": 1, "name": "a", "score": 1.5, "scores": [1, 2]}
+{"name": 2, "id": "2", "scores": null}
+
+{"id": 3, "name": null, "scores": [null, 5]}
+"#;
+    let report = import_ndjson(&table, ndjson.as_bytes(), &NdjsonImportOptions::default()).await?;
+    assert_eq!(report.imported_rows, 3);
+    assert_eq!(report.skipped_row_count, 0);
+    assert_eq!(report.bytes_read, ndjson.len() as u64);
+    assert_eq!(
+        full_table_scan(&table).await?,
+        r#"+-------------------+----+------+-------+--------+
+| _indexlake_row_id | id | name | score | scores |
++-------------------+----+------+-------+--------+
+| 1                 | 1  | a    | 1.5   | [1, 2] |
+| 2                 | 2  | 2    |       |        |
+| 3                 | 3  |      |       | [, 5]  |
++-------------------+----+------+-------+--------+"#,
+    );
+
+    let scan =
+        TableScan::default().with_columns(Some(vec!["id".to_string(), "scores".to_string()]));
+    let batches = table.scan(scan).await?.try_collect::<Vec<_>>().await?;
+    let batch = sort_record_batches(&batches, "id")?;
+    assert_eq!(batch.column(0).as_ref(), &Int32Array::from(vec![1, 2, 3]));
+    let mut scores = ListBuilder::new(Int64Builder::new());
+    scores.append_value([Some(1), Some(2)]);
+    scores.append_null();
+    scores.append_value([None, Some(5)]);
+    assert_eq!(batch.column(1).as_ref(), &scores.finish());
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn import_ndjson_malformed_rows(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let table = create_table(&client, "import_ndjson_malformed_rows").await?;
+
+    let ndjson = r#"{"id": 1, "score": "2.5"}
+{"id": 2, "score": "high"}
+[3]
+{"id": 4, "age": 40}
+{"name": "five"}
+{"id": 6, "scores": {"a": 1}}
+{"id": 7, "name": "N/A", "scores": []}
+{"id": "x"}
+"#;
+    let options = NdjsonImportOptions::default().with_null_token(Some("N/A".to_string()));
+
+    let err = import_ndjson(&table, ndjson.as_bytes(), &options)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("line 2, column score: invalid Float64 value \"high\""),
+        "{err}"
+    );
+    // nothing is imported when the import fails
+    assert_eq!(full_table_scan(&table).await?.lines().count(), 4);
+
+    let options = options
+        .with_on_malformed_row(OnMalformedRow::Skip)
+        .with_lossy_casts(true);
+    let report = import_ndjson(&table, ndjson.as_bytes(), &options).await?;
+    assert_eq!(report.imported_rows, 3);
+    assert_eq!(report.skipped_row_count, 5);
+    let lines_columns = report
+        .skipped_rows
+        .iter()
+        .map(|row_error| (row_error.line, row_error.column.as_deref()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lines_columns,
+        vec![
+            (3, None),
+            (4, None),
+            (5, Some("id")),
+            (6, Some("scores")),
+            (8, Some("id")),
+        ]
+    );
+    assert_eq!(
+        report.skipped_rows[1],
+        JsonRowError {
+            line: 4,
+            column: None,
+            message: "unknown column age".to_string(),
+        }
+    );
+    assert_eq!(
+        full_table_scan(&table).await?,
+        r#"+-------------------+----+------+-------+--------+
+| _indexlake_row_id | id | name | score | scores |
++-------------------+----+------+-------+--------+
+| 1                 | 1  |      | 2.5   |        |
+| 2                 | 2  |      |       |        |
+| 3                 | 7  |      |       | []     |
++-------------------+----+------+-------+--------+"#,
+    );
+
+    Ok(())
+}
@@ -0,0 +1,193 @@
+use arrow::array::{Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use indexlake::expr::{col, lit};
+use indexlake::index::Index;
+use indexlake::table::{IndexCreation, Table, TableConfig, TableCreation, TableScan};
+use indexlake::{ILResult, LakeClient, catalog::Catalog, storage::Storage};
+use indexlake_index_bloom::{BloomIndex, BloomIndexParams};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::{
+    counted_storage, create_namespace_if_not_exists, insert_files,
+};
+use indexlake_integration_tests::utils::table_scan;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger,
+};
+use std::sync::Arc;
+
+fn files_opened(storage: &Storage, extension: &str) -> usize {
+    storage
+        .read_stats()
+        .unwrap()
+        .opened_paths
+        .iter()
+        .filter(|path| path.ends_with(extension))
+        .count()
+}
+
+fn table_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("user", DataType::Utf8, true),
+    ]))
+}
+
+fn bloom_index_creation(params: BloomIndexParams) -> IndexCreation {
+    IndexCreation {
+        name: "bloom_index".to_string(),
+        kind: BloomIndex.kind().to_string(),
+        key_columns: vec!["id".to_string(), "user".to_string()],
+        include_columns: vec![],
+        params: Arc::new(params),
+        where_predicate: None,
+    }
+}
+
+async fn create_table(
+    client: &LakeClient,
+    table_name: &str,
+) -> Result<Table, Box<dyn std::error::Error>> {
+    create_namespace_if_not_exists(client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: table_schema(),
+            config: TableConfig {
+                inline_row_count_limit: 10,
+                ..Default::default()
+            },
+        })
+        .await?;
+    Ok(client.load_table("test_namespace", table_name).await?)
+}
+
+/// Rows of the ids with users named after them, except every tenth user which is null.
+fn file_batch(ids: Vec<i32>) -> ILResult<RecordBatch> {
+    let users = ids
+        .iter()
+        .map(|id| (id % 10 != 0).then(|| format!("user{id}")))
+        .collect::<Vec<_>>();
+    Ok(RecordBatch::try_new(
+        table_schema(),
+        vec![
+            Arc::new(Int32Array::from(ids)),
+            Arc::new(StringArray::from(users)),
+        ],
+    )?)
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn bloom_skips_files(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage.clone());
+    client.register_index(Arc::new(BloomIndex))?;
+    let mut table = create_table(&client, "bloom_skips_files").await?;
+    let false_positive_rate = 0.05;
+    table
+        .create_index(bloom_index_creation(BloomIndexParams {
+            false_positive_rate,
+        }))
+        .await?;
+    let file_count = 10;
+    insert_files(&table, file_count, file_batch).await?;
+
+    storage.reset_read_stats();
+    let scan = TableScan::default().with_filters(vec![col("user").eq(lit("user23".to_string()))]);
+    assert_eq!(
+        table_scan(&table, scan.clone()).await?,
+        r#"+-------------------+----+--------+
+| _indexlake_row_id | id | user   |
++-------------------+----+--------+
+| 24                | 23 | user23 |
++-------------------+----+--------+"#,
+    );
+    // the filters are read from the catalog, index files are left alone
+    assert_eq!(files_opened(&storage, ".index"), 0);
+    assert!(files_opened(&storage, ".parquet") <= 2);
+
+    // every key column has its own filter
+    storage.reset_read_stats();
+    let scan_id = TableScan::default().with_filters(vec![col("id").eq(lit(57))]);
+    assert_eq!(
+        table_scan(&table, scan_id).await?,
+        r#"+-------------------+----+--------+
+| _indexlake_row_id | id | user   |
++-------------------+----+--------+
+| 58                | 57 | user57 |
++-------------------+----+--------+"#,
+    );
+    assert!(files_opened(&storage, ".parquet") <= 2);
+
+    // files read for values no file holds are false positives
+    let lookups = 50;
+    let mut false_positives = 0;
+    for i in 0..lookups {
+        storage.reset_read_stats();
+        let missing =
+            TableScan::default().with_filters(vec![col("user").eq(lit(format!("nobody{i}")))]);
+        table_scan(&table, missing).await?;
+        false_positives += files_opened(&storage, ".parquet");
+    }
+    let rate = false_positives as f64 / (lookups * file_count) as f64;
+    assert!(
+        rate <= false_positive_rate * 2.0,
+        "false positive rate {rate} exceeds {false_positive_rate}"
+    );
+
+    // null users are not indexed, null filters are answered by the scan
+    let scan_nulls = TableScan::default().with_filters(vec![col("user").is_null()]);
+    assert_eq!(
+        table_scan(&table, scan_nulls).await?.lines().count(),
+        4 + file_count as usize
+    );
+
+    // deleted rows are not returned from files that may hold them
+    table.delete(&col("id").eq(lit(23))).await?;
+    assert_eq!(
+        table_scan(&table, scan).await?,
+        r#"+-------------------+----+------+
+| _indexlake_row_id | id | user |
++-------------------+----+------+
++-------------------+----+------+"#,
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bloom_validates_false_positive_rate() -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog_sqlite(), counted_storage());
+    client.register_index(Arc::new(BloomIndex))?;
+    let mut table = create_table(&client, "bloom_validates_false_positive_rate").await?;
+    for false_positive_rate in [0.0, 1.0, -0.5, 2.0] {
+        assert!(
+            table
+                .create_index(bloom_index_creation(BloomIndexParams {
+                    false_positive_rate,
+                }))
+                .await
+                .is_err()
+        );
+    }
+    table
+        .create_index(bloom_index_creation(BloomIndexParams::default()))
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,307 @@
+use arrow::array::{Int32Array, Int64Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use indexlake::expr::{col, lit};
+use indexlake::index::Index;
+use indexlake::table::{
+    CompactOptions, IndexCreation, Table, TableConfig, TableCreation, TableScan,
+};
+use indexlake::{ILResult, LakeClient, catalog::Catalog, storage::Storage};
+use indexlake_index_btree::{BTreeIndex, BTreeIndexParams};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::{
+    counted_storage, create_namespace_if_not_exists, data_files_opened, insert_files,
+};
+use indexlake_integration_tests::utils::table_scan;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger,
+};
+use std::sync::Arc;
+
+fn table_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("value", DataType::Utf8, false),
+    ]))
+}
+
+fn id_index_creation() -> IndexCreation {
+    IndexCreation {
+        name: "id_index".to_string(),
+        kind: BTreeIndex.kind().to_string(),
+        key_columns: vec!["id".to_string()],
+        include_columns: vec![],
+        params: Arc::new(BTreeIndexParams { leaf_size: 4 }),
+        where_predicate: None,
+    }
+}
+
+async fn create_table(
+    client: &LakeClient,
+    table_name: &str,
+) -> Result<Table, Box<dyn std::error::Error>> {
+    create_namespace_if_not_exists(client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: table_schema(),
+            config: TableConfig {
+                inline_row_count_limit: 10,
+                parquet_row_group_size: 4,
+                ..Default::default()
+            },
+        })
+        .await?;
+    Ok(client.load_table("test_namespace", table_name).await?)
+}
+
+/// Rows of the ids with values named after them.
+fn file_batch(ids: Vec<i32>) -> ILResult<RecordBatch> {
+    let values = ids.iter().map(|id| format!("v{id}")).collect::<Vec<_>>();
+    Ok(RecordBatch::try_new(
+        table_schema(),
+        vec![
+            Arc::new(Int32Array::from(ids)),
+            Arc::new(StringArray::from(values)),
+        ],
+    )?)
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn btree_range_scan(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage.clone());
+    client.register_index(Arc::new(BTreeIndex))?;
+    let mut table = create_table(&client, "btree_range_scan").await?;
+    table.create_index(id_index_creation()).await?;
+    insert_files(&table, 5, file_batch).await?;
+
+    storage.reset_read_stats();
+    table_scan(&table, TableScan::default()).await?;
+    assert_eq!(data_files_opened(&storage), 5);
+
+    let range_scan =
+        TableScan::default().with_filters(vec![col("id").gt_eq(lit(22)), col("id").lt(lit(25))]);
+    storage.reset_read_stats();
+    assert_eq!(
+        table_scan(&table, range_scan.clone()).await?,
+        r#"+-------------------+----+-------+
+| _indexlake_row_id | id | value |
++-------------------+----+-------+
+| 23                | 22 | v22   |
+| 24                | 23 | v23   |
+| 25                | 24 | v24   |
++-------------------+----+-------+"#,
+    );
+    assert_eq!(data_files_opened(&storage), 1);
+
+    // index entries of deleted rows are not returned
+    table.delete(&col("id").eq(lit(23))).await?;
+    let expected = r#"+-------------------+----+-------+
+| _indexlake_row_id | id | value |
++-------------------+----+-------+
+| 23                | 22 | v22   |
+| 25                | 24 | v24   |
++-------------------+----+-------+"#;
+    assert_eq!(table_scan(&table, range_scan.clone()).await?, expected);
+
+    // the merged file is indexed as well
+    table.compact(CompactOptions::default()).await?;
+    storage.reset_read_stats();
+    assert_eq!(table_scan(&table, range_scan).await?, expected);
+    assert_eq!(data_files_opened(&storage), 1);
+    let scan = TableScan::default().with_filters(vec![col("id").eq(lit(41))]);
+    assert_eq!(
+        table_scan(&table, scan).await?,
+        r#"+-------------------+----+-------+
+| _indexlake_row_id | id | value |
++-------------------+----+-------+
+| 42                | 41 | v41   |
++-------------------+----+-------+"#,
+    );
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn btree_index_existing_data(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage.clone());
+    client.register_index(Arc::new(BTreeIndex))?;
+    let mut table = create_table(&client, "btree_index_existing_data").await?;
+    insert_files(&table, 3, file_batch).await?;
+    table.create_index(id_index_creation()).await?;
+
+    storage.reset_read_stats();
+    let scan = TableScan::default().with_filters(vec![col("id").eq(lit(17))]);
+    assert_eq!(
+        table_scan(&table, scan).await?,
+        r#"+-------------------+----+-------+
+| _indexlake_row_id | id | value |
++-------------------+----+-------+
+| 18                | 17 | v17   |
++-------------------+----+-------+"#,
+    );
+    assert_eq!(data_files_opened(&storage), 1);
+
+    // no leaf holds the key, no data file is read
+    storage.reset_read_stats();
+    let scan = TableScan::default().with_filters(vec![col("id").gt(lit(1000))]);
+    table_scan(&table, scan).await?;
+    assert_eq!(data_files_opened(&storage), 0);
+
+    Ok(())
+}
+
+fn events_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("tenant_id", DataType::Int32, false),
+        Field::new("created_at", DataType::Int64, false),
+    ]))
+}
+
+/// Inserts events `0..file_count * 10` in batches of ten, each batch is dumped into its own
+/// file. Events are created at their number, odd events belong to tenant 1, even ones to 0.
+async fn insert_events(table: &Table, file_count: i64) -> Result<(), Box<dyn std::error::Error>> {
+    for i in 0..file_count {
+        let created_ats = (i * 10..i * 10 + 10).collect::<Vec<_>>();
+        let tenant_ids = created_ats
+            .iter()
+            .map(|created_at| (created_at % 2) as i32)
+            .collect::<Vec<_>>();
+        table
+            .insert(&RecordBatch::try_new(
+                events_schema(),
+                vec![
+                    Arc::new(Int32Array::from(tenant_ids)),
+                    Arc::new(Int64Array::from(created_ats)),
+                ],
+            )?)
+            .await?;
+        // wait for dump task to finish
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    }
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn btree_composite_prefix(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage.clone());
+    client.register_index(Arc::new(BTreeIndex))?;
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "btree_composite_prefix".to_string(),
+            schema: events_schema(),
+            config: TableConfig {
+                inline_row_count_limit: 10,
+                parquet_row_group_size: 4,
+                ..Default::default()
+            },
+        })
+        .await?;
+    let mut table = client
+        .load_table("test_namespace", "btree_composite_prefix")
+        .await?;
+    table
+        .create_index(IndexCreation {
+            name: "tenant_created_at_index".to_string(),
+            kind: BTreeIndex.kind().to_string(),
+            key_columns: vec!["tenant_id".to_string(), "created_at".to_string()],
+            include_columns: vec![],
+            params: Arc::new(BTreeIndexParams { leaf_size: 2 }),
+            where_predicate: None,
+        })
+        .await?;
+    let file_count = 5;
+    insert_events(&table, file_count).await?;
+
+    // both columns narrow the leaves down, only the file of the range is read
+    let both = TableScan::default().with_filters(vec![
+        col("tenant_id").eq(lit(1)),
+        col("created_at").gt_eq(lit(22i64)),
+        col("created_at").lt(lit(26i64)),
+    ]);
+    storage.reset_read_stats();
+    assert_eq!(
+        table_scan(&table, both).await?,
+        r#"+-------------------+-----------+------------+
+| _indexlake_row_id | tenant_id | created_at |
++-------------------+-----------+------------+
+| 24                | 1         | 23         |
+| 26                | 1         | 25         |
++-------------------+-----------+------------+"#,
+    );
+    assert_eq!(data_files_opened(&storage), 1);
+
+    // the leading column alone uses the index
+    storage.reset_read_stats();
+    let leading = TableScan::default().with_filters(vec![col("tenant_id").eq(lit(7))]);
+    table_scan(&table, leading).await?;
+    assert_eq!(data_files_opened(&storage), 0);
+    let leading = TableScan::default().with_filters(vec![col("tenant_id").eq(lit(1))]);
+    assert_eq!(
+        table_scan(&table, leading).await?.lines().count(),
+        4 + file_count as usize * 5
+    );
+
+    // the second column alone does not, data files are only skipped by their column stats
+    let second = TableScan::default().with_filters(vec![
+        col("created_at").gt_eq(lit(22i64)),
+        col("created_at").lt(lit(26i64)),
+    ]);
+    storage.reset_read_stats();
+    assert_eq!(
+        table_scan(&table, second).await?,
+        r#"+-------------------+-----------+------------+
+| _indexlake_row_id | tenant_id | created_at |
++-------------------+-----------+------------+
+| 23                | 0         | 22         |
+| 24                | 1         | 23         |
+| 25                | 0         | 24         |
+| 26                | 1         | 25         |
++-------------------+-----------+------------+"#,
+    );
+    assert_eq!(data_files_opened(&storage), 1);
+
+    Ok(())
+}
@@ -0,0 +1,204 @@
+use arrow::array::{Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use indexlake::expr::{col, lit};
+use indexlake::index::Index;
+use indexlake::table::{IndexCreation, Table, TableConfig, TableCreation, TableScan};
+use indexlake::{ILResult, LakeClient, catalog::Catalog, storage::Storage};
+use indexlake_index_hash::{HashIndex, HashIndexParams};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::{
+    counted_storage, create_namespace_if_not_exists, data_files_opened, insert_files,
+};
+use indexlake_integration_tests::utils::table_scan;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger,
+};
+use std::sync::Arc;
+
+fn table_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("user", DataType::Utf8, true),
+    ]))
+}
+
+fn user_index_creation(params: HashIndexParams) -> IndexCreation {
+    IndexCreation {
+        name: "user_index".to_string(),
+        kind: HashIndex.kind().to_string(),
+        key_columns: vec!["user".to_string()],
+        include_columns: vec![],
+        params: Arc::new(params),
+        where_predicate: None,
+    }
+}
+
+async fn create_table(
+    client: &LakeClient,
+    table_name: &str,
+) -> Result<Table, Box<dyn std::error::Error>> {
+    create_namespace_if_not_exists(client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: table_schema(),
+            config: TableConfig {
+                inline_row_count_limit: 10,
+                parquet_row_group_size: 4,
+                ..Default::default()
+            },
+        })
+        .await?;
+    Ok(client.load_table("test_namespace", table_name).await?)
+}
+
+/// Rows of the ids with users named after them, except every tenth user which is null.
+fn file_batch(ids: Vec<i32>) -> ILResult<RecordBatch> {
+    let users = ids
+        .iter()
+        .map(|id| (id % 10 != 0).then(|| format!("user{id}")))
+        .collect::<Vec<_>>();
+    Ok(RecordBatch::try_new(
+        table_schema(),
+        vec![
+            Arc::new(Int32Array::from(ids)),
+            Arc::new(StringArray::from(users)),
+        ],
+    )?)
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn hash_equality_lookup(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage.clone());
+    client.register_index(Arc::new(HashIndex))?;
+    let mut table = create_table(&client, "hash_equality_lookup").await?;
+    table
+        .create_index(user_index_creation(HashIndexParams::default()))
+        .await?;
+    insert_files(&table, 5, file_batch).await?;
+
+    storage.reset_read_stats();
+    let scan = TableScan::default().with_filters(vec![col("user").eq(lit("user23".to_string()))]);
+    assert_eq!(
+        table_scan(&table, scan.clone()).await?,
+        r#"+-------------------+----+--------+
+| _indexlake_row_id | id | user   |
++-------------------+----+--------+
+| 24                | 23 | user23 |
++-------------------+----+--------+"#,
+    );
+    assert_eq!(data_files_opened(&storage), 1);
+
+    // no file holds the user
+    storage.reset_read_stats();
+    let missing =
+        TableScan::default().with_filters(vec![col("user").eq(lit("nobody".to_string()))]);
+    table_scan(&table, missing).await?;
+    assert_eq!(data_files_opened(&storage), 0);
+
+    // in lists read the files holding any of their users
+    storage.reset_read_stats();
+    let scan_list = TableScan::default().with_filters(vec![col("user").in_list(
+        vec![
+            lit("user23".to_string()),
+            lit("user41".to_string()),
+            lit("nobody".to_string()),
+        ],
+        false,
+    )]);
+    assert_eq!(
+        table_scan(&table, scan_list).await?,
+        r#"+-------------------+----+--------+
+| _indexlake_row_id | id | user   |
++-------------------+----+--------+
+| 24                | 23 | user23 |
+| 42                | 41 | user41 |
++-------------------+----+--------+"#,
+    );
+    assert_eq!(data_files_opened(&storage), 2);
+
+    // null users are not indexed, null filters are answered by the scan
+    let scan_nulls = TableScan::default().with_filters(vec![col("user").is_null()]);
+    assert_eq!(
+        table_scan(&table, scan_nulls).await?,
+        r#"+-------------------+----+------+
+| _indexlake_row_id | id | user |
++-------------------+----+------+
+| 1                 | 0  |      |
+| 11                | 10 |      |
+| 21                | 20 |      |
+| 31                | 30 |      |
+| 41                | 40 |      |
++-------------------+----+------+"#,
+    );
+
+    // index entries of deleted rows are not returned
+    table.delete(&col("id").eq(lit(23))).await?;
+    assert_eq!(
+        table_scan(&table, scan).await?,
+        r#"+-------------------+----+------+
+| _indexlake_row_id | id | user |
++-------------------+----+------+
++-------------------+----+------+"#,
+    );
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn hash_collisions(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage.clone());
+    client.register_index(Arc::new(HashIndex))?;
+    let mut table = create_table(&client, "hash_collisions").await?;
+    // all users collide into a single bucket
+    table
+        .create_index(user_index_creation(HashIndexParams {
+            num_buckets: 1,
+            group_size: 4,
+        }))
+        .await?;
+    insert_files(&table, 3, file_batch).await?;
+
+    storage.reset_read_stats();
+    let scan = TableScan::default().with_filters(vec![col("user").eq(lit("user17".to_string()))]);
+    assert_eq!(
+        table_scan(&table, scan).await?,
+        r#"+-------------------+----+--------+
+| _indexlake_row_id | id | user   |
++-------------------+----+--------+
+| 18                | 17 | user17 |
++-------------------+----+--------+"#,
+    );
+    // keys are verified within the index files, so other files are still skipped
+    assert_eq!(data_files_opened(&storage), 1);
+
+    Ok(())
+}
@@ -0,0 +1,244 @@
+use arrow::array::{AsArray, FixedSizeListArray, Float32Array, Int32Array, RecordBatch};
+use arrow::datatypes::{DataType, Field, Int32Type, Schema, SchemaRef};
+use futures::TryStreamExt;
+use indexlake::index::Index;
+use indexlake::table::{IndexCreation, Table, TableConfig, TableCreation};
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+use indexlake_index_hnsw::{DistanceMetric, HnswIndex, HnswIndexParams, VectorQuery};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::create_namespace_if_not_exists;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger,
+    storage_memory,
+};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+const DIMENSION: usize = 8;
+
+fn table_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new(
+            "embedding",
+            DataType::FixedSizeList(
+                Arc::new(Field::new_list_field(DataType::Float32, true)),
+                DIMENSION as i32,
+            ),
+            true,
+        ),
+    ]))
+}
+
+/// Pseudo-random vectors with elements between -0.5 and 0.5.
+fn vectors(count: usize, seed: u64) -> Vec<Vec<f32>> {
+    let mut state = seed;
+    (0..count)
+        .map(|_| {
+            (0..DIMENSION)
+                .map(|_| {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    (state >> 40) as f32 / (1u64 << 24) as f32 - 0.5
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn embedding_index_creation(name: &str, params: HnswIndexParams) -> IndexCreation {
+    IndexCreation {
+        name: name.to_string(),
+        kind: HnswIndex.kind().to_string(),
+        key_columns: vec!["embedding".to_string()],
+        include_columns: vec![],
+        params: Arc::new(params),
+        where_predicate: None,
+    }
+}
+
+async fn insert_vectors(
+    table: &Table,
+    ids: Vec<i32>,
+    vectors: Vec<Option<Vec<f32>>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let embeddings = FixedSizeListArray::from_iter_primitive::<arrow::datatypes::Float32Type, _, _>(
+        vectors
+            .into_iter()
+            .map(|vector| vector.map(|vector| vector.into_iter().map(Some))),
+        DIMENSION as i32,
+    );
+    table
+        .insert(&RecordBatch::try_new(
+            table_schema(),
+            vec![Arc::new(Int32Array::from(ids)), Arc::new(embeddings)],
+        )?)
+        .await?;
+    Ok(())
+}
+
+/// Returns the ids of the rows nearest to the query vector, nearest first.
+async fn search(table: &Table, query: VectorQuery) -> Result<Vec<i32>, Box<dyn std::error::Error>> {
+    let batches = table
+        .search(Arc::new(query))
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    Ok(batches
+        .iter()
+        .flat_map(|batch| {
+            batch
+                .column_by_name("id")
+                .unwrap()
+                .as_primitive::<Int32Type>()
+                .values()
+                .to_vec()
+        })
+        .collect())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_memory())]
+#[case(async { catalog_postgres().await }, storage_memory())]
+#[case(async { catalog_mysql().await }, storage_memory())]
+#[case(async { catalog_memory() }, storage_memory())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_memory()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn hnsw_recall(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage);
+    client.register_index(Arc::new(HnswIndex))?;
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "hnsw_recall".to_string(),
+            schema: table_schema(),
+            config: TableConfig {
+                inline_row_count_limit: 100,
+                ..Default::default()
+            },
+        })
+        .await?;
+    let mut table = client.load_table("test_namespace", "hnsw_recall").await?;
+    for metric in [DistanceMetric::Cosine, DistanceMetric::L2] {
+        let mut params = HnswIndexParams::new(DIMENSION, metric);
+        params.m = 8;
+        table
+            .create_index(embedding_index_creation(
+                &format!("{metric:?}_index").to_lowercase(),
+                params,
+            ))
+            .await?;
+    }
+
+    // Most rows are dumped into data files, the last ones stay inline
+    let data = vectors(450, 1);
+    for start in (0..data.len()).step_by(100) {
+        let end = (start + 100).min(data.len());
+        insert_vectors(
+            &table,
+            (start as i32..end as i32).collect(),
+            data[start..end].iter().cloned().map(Some).collect(),
+        )
+        .await?;
+        // wait for dump task to finish
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    }
+    insert_vectors(&table, vec![-1], vec![None]).await?;
+
+    let k = 10;
+    let queries = vectors(10, 2);
+    for metric in [DistanceMetric::Cosine, DistanceMetric::L2] {
+        let mut found = 0;
+        for query in queries.iter() {
+            let mut exact = data
+                .iter()
+                .enumerate()
+                .map(|(id, vector)| (id as i32, metric.distance(query, vector)))
+                .collect::<Vec<_>>();
+            exact.sort_by(|a, b| a.1.total_cmp(&b.1));
+            let exact = exact[..k].iter().map(|(id, _)| *id).collect::<HashSet<_>>();
+
+            let ids = search(&table, VectorQuery::new(query.clone(), k, metric)).await?;
+            assert_eq!(ids.len(), k);
+            let distances = ids
+                .iter()
+                .map(|id| metric.distance(query, &data[*id as usize]))
+                .collect::<Vec<_>>();
+            assert!(distances.windows(2).all(|pair| pair[0] <= pair[1]));
+            found += ids.iter().filter(|id| exact.contains(id)).count();
+        }
+        let recall = found as f64 / (queries.len() * k) as f64;
+        assert!(recall >= 0.9, "recall {recall} of {metric:?} is too low");
+    }
+
+    // No index answers queries of another dimension
+    assert!(
+        search(
+            &table,
+            VectorQuery::new(vec![0.0; DIMENSION + 1], k, DistanceMetric::L2)
+        )
+        .await
+        .is_err()
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn hnsw_validates_key_column() -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog_sqlite(), storage_memory());
+    client.register_index(Arc::new(HnswIndex))?;
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "hnsw_validates_key_column".to_string(),
+            schema: table_schema(),
+            config: TableConfig::default(),
+        })
+        .await?;
+    let mut table = client
+        .load_table("test_namespace", "hnsw_validates_key_column")
+        .await?;
+
+    let params = HnswIndexParams::new(DIMENSION + 1, DistanceMetric::L2);
+    assert!(
+        table
+            .create_index(embedding_index_creation("wrong_dimension", params))
+            .await
+            .is_err()
+    );
+
+    let params = HnswIndexParams::new(DIMENSION, DistanceMetric::L2);
+    let mut creation = embedding_index_creation("wrong_type", params);
+    creation.key_columns = vec!["id".to_string()];
+    assert!(table.create_index(creation).await.is_err());
+
+    // Vectors round trip through inline rows
+    let vector = Float32Array::from(vec![0.5; DIMENSION]);
+    insert_vectors(&table, vec![1], vec![Some(vector.values().to_vec())]).await?;
+    let batches = table
+        .scan(Default::default())
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    let embeddings = batches[0]
+        .column_by_name("embedding")
+        .unwrap()
+        .as_fixed_size_list();
+    assert_eq!(embeddings.value(0).as_primitive(), &vector);
+
+    Ok(())
+}
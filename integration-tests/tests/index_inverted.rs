@@ -0,0 +1,183 @@
+use arrow::array::{AsArray, Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Int32Type, Schema, SchemaRef};
+use futures::TryStreamExt;
+use indexlake::expr::{col, lit};
+use indexlake::index::Index;
+use indexlake::table::{IndexCreation, Table, TableConfig, TableCreation};
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+use indexlake_index_inverted::{InvertedIndex, InvertedIndexParams, MatchMode, MatchQuery};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::create_namespace_if_not_exists;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger,
+    storage_memory,
+};
+use std::sync::Arc;
+
+fn table_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("text", DataType::Utf8, true),
+    ]))
+}
+
+async fn insert_docs(
+    table: &Table,
+    ids: Vec<i32>,
+    texts: Vec<Option<&str>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    table
+        .insert(&RecordBatch::try_new(
+            table_schema(),
+            vec![
+                Arc::new(Int32Array::from(ids)),
+                Arc::new(StringArray::from(texts)),
+            ],
+        )?)
+        .await?;
+    Ok(())
+}
+
+/// Returns the ids of the matching documents, best ranked first.
+async fn search(
+    table: &Table,
+    terms: &[&str],
+    mode: MatchMode,
+    limit: Option<usize>,
+) -> Result<Vec<i32>, Box<dyn std::error::Error>> {
+    let mut query = MatchQuery::new(terms.iter().map(|term| term.to_string()).collect(), mode);
+    query.limit = limit;
+    let batches = table
+        .search(Arc::new(query))
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    Ok(batches
+        .iter()
+        .flat_map(|batch| {
+            batch
+                .column_by_name("id")
+                .unwrap()
+                .as_primitive::<Int32Type>()
+                .values()
+                .to_vec()
+        })
+        .collect())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_memory())]
+#[case(async { catalog_postgres().await }, storage_memory())]
+#[case(async { catalog_mysql().await }, storage_memory())]
+#[case(async { catalog_memory() }, storage_memory())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_memory()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn inverted_index_match_query(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage);
+    client.register_index(Arc::new(InvertedIndex::new()))?;
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "inverted_index_match_query".to_string(),
+            schema: table_schema(),
+            config: TableConfig {
+                inline_row_count_limit: 3,
+                ..Default::default()
+            },
+        })
+        .await?;
+    let mut table = client
+        .load_table("test_namespace", "inverted_index_match_query")
+        .await?;
+    table
+        .create_index(IndexCreation {
+            name: "text_index".to_string(),
+            kind: InvertedIndex::new().kind().to_string(),
+            key_columns: vec!["text".to_string()],
+            include_columns: vec![],
+            params: Arc::new(InvertedIndexParams {
+                group_size: 4,
+                ..Default::default()
+            }),
+            where_predicate: None,
+        })
+        .await?;
+
+    // the first documents are dumped into a data file, the last ones stay inline
+    insert_docs(
+        &table,
+        vec![1, 2, 3, 4],
+        vec![
+            Some("The quick brown fox"),
+            Some("the lazy dog"),
+            Some("A quick dog and a quick cat."),
+            Some("The fox and the dog"),
+        ],
+    )
+    .await?;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+    insert_docs(
+        &table,
+        vec![5, 6],
+        vec![Some("of the people, by the people, for the people"), None],
+    )
+    .await?;
+
+    assert_eq!(
+        search(&table, &["quick", "dog"], MatchMode::AllOf, None).await?,
+        vec![3]
+    );
+    assert_eq!(
+        search(&table, &["fox", "cat"], MatchMode::AnyOf, None).await?,
+        vec![1, 3, 4]
+    );
+    // ranked by term frequency
+    assert_eq!(
+        search(&table, &["Quick"], MatchMode::AnyOf, None).await?,
+        vec![3, 1]
+    );
+    assert!(
+        search(&table, &["quick", "people"], MatchMode::AllOf, None)
+            .await?
+            .is_empty()
+    );
+
+    // stop words are indexed like any other term
+    assert_eq!(
+        search(
+            &table,
+            &["the", "of", "the", "people"],
+            MatchMode::AllOf,
+            None
+        )
+        .await?,
+        vec![5]
+    );
+    assert_eq!(
+        search(&table, &["the", "a"], MatchMode::AnyOf, None).await?,
+        vec![5, 3, 4, 1, 2]
+    );
+    assert_eq!(
+        search(&table, &["the", "a"], MatchMode::AnyOf, Some(2)).await?,
+        vec![5, 3]
+    );
+
+    table.delete(&col("id").eq(lit(3))).await?;
+    assert!(
+        search(&table, &["quick", "dog"], MatchMode::AllOf, None)
+            .await?
+            .is_empty()
+    );
+
+    Ok(())
+}
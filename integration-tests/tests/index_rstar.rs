@@ -47,6 +47,7 @@ async fn create_rstar_index(
     let table_config = TableConfig {
         inline_row_count_limit: 3,
         parquet_row_group_size: 2,
+        ..Default::default()
     };
     let table_name = "create_rstar_index";
     let table_creation = TableCreation {
@@ -66,6 +67,7 @@ async fn create_rstar_index(
         params: Arc::new(RStarIndexParams {
             wkb_dialect: WkbDialect::Wkb,
         }),
+        where_predicate: None,
     };
     table.create_index(index_creation.clone()).await?;
 
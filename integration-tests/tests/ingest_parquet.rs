@@ -0,0 +1,247 @@
+use arrow::array::{ArrayRef, Int32Array, Int64Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use indexlake::expr::{col, lit};
+use indexlake::index::Index;
+use indexlake::table::{
+    IndexCreation, IngestOptions, Table, TableConfig, TableCreation, TableScan,
+};
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+use indexlake_index_hash::{HashIndex, HashIndexParams};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::{
+    counted_storage, create_namespace_if_not_exists, data_files_opened,
+};
+use indexlake_integration_tests::utils::table_scan;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger,
+};
+use parquet::arrow::AsyncArrowWriter;
+use std::sync::Arc;
+
+fn table_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("name", DataType::Utf8, true),
+    ]))
+}
+
+async fn create_table(
+    client: &LakeClient,
+    table_name: &str,
+) -> Result<Table, Box<dyn std::error::Error>> {
+    create_namespace_if_not_exists(client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: table_schema(),
+            config: TableConfig {
+                parquet_row_group_size: 4,
+                ..Default::default()
+            },
+        })
+        .await?;
+    Ok(client.load_table("test_namespace", table_name).await?)
+}
+
+/// Writes a parquet file outside the table directory, in row groups of three rows.
+async fn write_parquet(
+    storage: &Storage,
+    path: &str,
+    batch: &RecordBatch,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let properties = parquet::file::properties::WriterProperties::builder()
+        .set_max_row_group_size(3)
+        .build();
+    let output_file = storage.create_file(path).await?;
+    let mut writer = AsyncArrowWriter::try_new(output_file, batch.schema(), Some(properties))?;
+    writer.write(batch).await?;
+    writer.close().await?;
+    Ok(())
+}
+
+/// Batch of ids `ids` named after them, with the columns in the reverse order of the table.
+fn named_ids(ids: std::ops::Range<i32>) -> Result<RecordBatch, Box<dyn std::error::Error>> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("name", DataType::Utf8, true),
+        Field::new("id", DataType::Int32, false),
+    ]));
+    let names = ids
+        .clone()
+        .map(|id| format!("name{id}"))
+        .collect::<Vec<_>>();
+    Ok(RecordBatch::try_new(
+        schema,
+        vec![
+            Arc::new(StringArray::from(names)),
+            Arc::new(Int32Array::from_iter_values(ids)),
+        ],
+    )?)
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn ingest_parquet_files(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage.clone());
+    client.register_index(Arc::new(HashIndex))?;
+    let mut table = create_table(&client, "ingest_parquet_files").await?;
+    table
+        .create_index(IndexCreation {
+            name: "name_index".to_string(),
+            kind: HashIndex.kind().to_string(),
+            key_columns: vec!["name".to_string()],
+            include_columns: vec![],
+            params: Arc::new(HashIndexParams::default()),
+            where_predicate: None,
+        })
+        .await?;
+
+    write_parquet(&storage, "external/copied.parquet", &named_ids(0..10)?).await?;
+    write_parquet(&storage, "external/in_place.parquet", &named_ids(10..20)?).await?;
+    table
+        .ingest_parquet(
+            vec!["external/copied.parquet".to_string()],
+            IngestOptions::default(),
+        )
+        .await?;
+    table
+        .ingest_parquet(
+            vec!["external/in_place.parquet".to_string()],
+            IngestOptions::default().with_in_place(true),
+        )
+        .await?;
+
+    let scanned = table_scan(&table, TableScan::default()).await?;
+    assert_eq!(scanned.lines().count(), 24);
+    // the file registered in place is checked against its checksum too
+    let report = table.verify().await?;
+    assert_eq!(report.verified_file_count, 2);
+    assert!(report.mismatches.is_empty());
+
+    // column statistics prune the file without matching ids
+    storage.reset_read_stats();
+    let scan = TableScan::default().with_filters(vec![col("id").gt_eq(lit(17))]);
+    assert_eq!(
+        table_scan(&table, scan).await?,
+        r#"+-------------------+----+--------+
+| _indexlake_row_id | id | name   |
++-------------------+----+--------+
+| 18                | 17 | name17 |
+| 19                | 18 | name18 |
+| 20                | 19 | name19 |
++-------------------+----+--------+"#,
+    );
+    assert_eq!(data_files_opened(&storage), 1);
+
+    // both files are indexed
+    storage.reset_read_stats();
+    let scan = TableScan::default().with_filters(vec![col("name").eq(lit("name4".to_string()))]);
+    assert_eq!(
+        table_scan(&table, scan).await?,
+        r#"+-------------------+----+-------+
+| _indexlake_row_id | id | name  |
++-------------------+----+-------+
+| 5                 | 4  | name4 |
++-------------------+----+-------+"#,
+    );
+    assert_eq!(data_files_opened(&storage), 1);
+
+    // rows of the file registered in place are deleted through their row metadata
+    table.delete(&col("id").lt(lit(12))).await?;
+    let scan = TableScan::default().with_filters(vec![col("id").lt(lit(14))]);
+    assert_eq!(
+        table_scan(&table, scan).await?,
+        r#"+-------------------+----+--------+
+| _indexlake_row_id | id | name   |
++-------------------+----+--------+
+| 13                | 12 | name12 |
+| 14                | 13 | name13 |
++-------------------+----+--------+"#,
+    );
+
+    // the same file can not be registered twice
+    let result = table
+        .ingest_parquet(
+            vec!["external/in_place.parquet".to_string()],
+            IngestOptions::default().with_in_place(true),
+        )
+        .await;
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn ingest_parquet_schema_mismatch(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage.clone());
+    let table = create_table(&client, "ingest_parquet_schema_mismatch").await?;
+
+    write_parquet(&storage, "external/valid.parquet", &named_ids(0..5)?).await?;
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("age", DataType::Int32, true),
+    ]));
+    let batch = RecordBatch::try_new(
+        schema,
+        vec![
+            Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef,
+            Arc::new(Int32Array::from(vec![20, 21])),
+        ],
+    )?;
+    write_parquet(&storage, "external/invalid.parquet", &batch).await?;
+
+    let err = table
+        .ingest_parquet(
+            vec![
+                "external/valid.parquet".to_string(),
+                "external/invalid.parquet".to_string(),
+            ],
+            IngestOptions::default(),
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains(
+            "Schema of parquet file external/invalid.parquet does not match table ingest_parquet_schema_mismatch"
+        ),
+        "{err}"
+    );
+    assert!(
+        err.contains("column id has type Int64 in the file, Int32 in the table"),
+        "{err}"
+    );
+    assert!(err.contains("unexpected column age: Int32"), "{err}");
+
+    // nothing is registered unless every file is
+    let scanned = table_scan(&table, TableScan::default()).await?;
+    assert_eq!(scanned.lines().count(), 4);
+
+    Ok(())
+}
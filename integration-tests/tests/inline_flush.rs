@@ -0,0 +1,326 @@
+use arrow::array::{AsArray, Int64Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Int64Type, Schema};
+use futures::TryStreamExt;
+use indexlake::catalog::INTERNAL_ROW_ID_FIELD_NAME;
+use indexlake::expr::{col, lit};
+use indexlake::index::Index;
+use indexlake::table::{FlushReport, IndexCreation, Table, TableConfig, TableCreation, TableScan};
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+use indexlake_index_hash::{HashIndex, HashIndexParams};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::create_namespace_if_not_exists;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger, storage_fs,
+    storage_s3,
+};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+fn table_schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, false),
+    ]))
+}
+
+async fn create_table(
+    client: &LakeClient,
+    table_name: &str,
+    config: TableConfig,
+) -> Result<Table, Box<dyn std::error::Error>> {
+    create_namespace_if_not_exists(client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: table_schema(),
+            config,
+        })
+        .await?;
+    Ok(client.load_table("test_namespace", table_name).await?)
+}
+
+/// Inserts rows of `ids` with names of 100 bytes, about 110 bytes per row.
+async fn insert_rows(table: &Table, ids: Vec<i64>) -> Result<(), Box<dyn std::error::Error>> {
+    let names = ids
+        .iter()
+        .map(|id| format!("{id:0>100}"))
+        .collect::<Vec<_>>();
+    table
+        .insert(&RecordBatch::try_new(
+            table_schema(),
+            vec![
+                Arc::new(Int64Array::from(ids)),
+                Arc::new(StringArray::from(names)),
+            ],
+        )?)
+        .await?;
+    Ok(())
+}
+
+/// Inserts six batches of five rows and returns the number of data files after each insert.
+async fn insert_batches(table: &Table) -> Result<Vec<usize>, Box<dyn std::error::Error>> {
+    let mut data_file_counts = Vec::new();
+    for i in 0..6 {
+        insert_rows(table, (i * 5..i * 5 + 5).collect()).await?;
+        // wait for dump task to finish
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        data_file_counts.push(table.storage_stats().await?.data_file_count);
+    }
+    Ok(data_file_counts)
+}
+
+async fn scanned_row_ids(table: &Table) -> Result<Vec<i64>, indexlake::ILError> {
+    let batches = table
+        .scan(TableScan::default().with_columns(Some(vec![INTERNAL_ROW_ID_FIELD_NAME.to_string()])))
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    let mut row_ids = batches
+        .iter()
+        .flat_map(|batch| {
+            batch
+                .column(0)
+                .as_primitive::<Int64Type>()
+                .values()
+                .to_vec()
+        })
+        .collect::<Vec<_>>();
+    row_ids.sort();
+    Ok(row_ids)
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn flush_by_row_count(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let config = TableConfig {
+        inline_row_count_limit: 10,
+        ..Default::default()
+    };
+    let table = create_table(&client, "flush_by_row_count", config).await?;
+    assert_eq!(insert_batches(&table).await?, vec![0, 1, 1, 2, 2, 3]);
+    assert_eq!(scanned_row_ids(&table).await?, (1..=30).collect::<Vec<_>>());
+
+    // a write-heavy table flushes less often
+    let config = TableConfig {
+        inline_row_count_limit: 20,
+        ..Default::default()
+    };
+    let table = create_table(&client, "flush_by_row_count_write_heavy", config).await?;
+    assert_eq!(insert_batches(&table).await?, vec![0, 0, 0, 1, 1, 1]);
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn flush_by_bytes(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    // ten rows exceed the byte limit long before the row count limit
+    let config = TableConfig {
+        inline_row_count_limit: 1000,
+        inline_byte_limit: 1000,
+        ..Default::default()
+    };
+    let table = create_table(&client, "flush_by_bytes", config).await?;
+    assert_eq!(insert_batches(&table).await?, vec![0, 1, 1, 2, 2, 3]);
+    assert_eq!(scanned_row_ids(&table).await?, (1..=30).collect::<Vec<_>>());
+
+    let config = TableConfig {
+        inline_row_count_limit: 1000,
+        inline_byte_limit: 3000,
+        ..Default::default()
+    };
+    let table = create_table(&client, "flush_by_bytes_larger_limit", config).await?;
+    assert_eq!(insert_batches(&table).await?, vec![0, 0, 0, 0, 0, 1]);
+
+    let config = TableConfig {
+        inline_byte_limit: 0,
+        ..Default::default()
+    };
+    assert!(
+        create_table(&client, "flush_by_bytes_zero_limit", config)
+            .await
+            .is_err()
+    );
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn scans_during_flushes(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let config = TableConfig {
+        inline_row_count_limit: 1000,
+        inline_byte_limit: 500,
+        ..Default::default()
+    };
+    let table = create_table(&client, "scans_during_flushes", config).await?;
+
+    // rows moving from the catalog into data files are never seen twice
+    let done = Arc::new(AtomicBool::new(false));
+    let reader = {
+        let table = table.clone();
+        let done = done.clone();
+        tokio::spawn(async move {
+            while !done.load(Ordering::SeqCst) {
+                let row_ids = scanned_row_ids(&table).await?;
+                let mut deduped = row_ids.clone();
+                deduped.dedup();
+                assert_eq!(row_ids, deduped);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            Ok::<_, indexlake::ILError>(())
+        })
+    };
+    let data_file_counts = insert_batches(&table).await;
+    done.store(true, Ordering::SeqCst);
+    reader.await??;
+
+    assert_eq!(data_file_counts?.last(), Some(&6));
+    assert_eq!(scanned_row_ids(&table).await?, (1..=30).collect::<Vec<_>>());
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn manual_flush(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage.clone());
+    client.register_index(Arc::new(HashIndex))?;
+    let mut table = create_table(&client, "manual_flush", TableConfig::default()).await?;
+    table
+        .create_index(IndexCreation {
+            name: "name_index".to_string(),
+            kind: HashIndex.kind().to_string(),
+            key_columns: vec!["name".to_string()],
+            include_columns: vec![],
+            params: Arc::new(HashIndexParams::default()),
+            where_predicate: None,
+        })
+        .await?;
+
+    // nothing to flush is not an error
+    assert_eq!(table.flush().await?, FlushReport::default());
+
+    // rows below the limits are only written to a data file when flushed
+    insert_rows(&table, (0..5).collect()).await?;
+    assert_eq!(table.storage_stats().await?.data_file_count, 0);
+    let report = table.flush().await?;
+    assert_eq!(report.flushed_rows, 5);
+    assert_eq!(report.data_file_paths.len(), 1);
+    assert!(storage.exists(&report.data_file_paths[0]).await?);
+    let stats = table.storage_stats().await?;
+    assert_eq!(stats.data_file_count, 1);
+    assert!(stats.index_file_bytes > 0);
+    assert_eq!(table.flush().await?, FlushReport::default());
+
+    // the flushed rows are filtered with the index built for their data file
+    let batches = table
+        .scan(TableScan::default().with_filters(vec![col("name").eq(lit(format!("{:0>100}", 3)))]))
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 1);
+    assert_eq!(scanned_row_ids(&table).await?, (1..=5).collect::<Vec<_>>());
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn flush_during_inserts(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let table = create_table(&client, "flush_during_inserts", TableConfig::default()).await?;
+    insert_rows(&table, (0..5).collect()).await?;
+
+    let writer = {
+        let table = table.clone();
+        tokio::spawn(async move {
+            for i in 1..6 {
+                insert_rows(&table, (i * 5..i * 5 + 5).collect())
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+            Ok::<_, String>(())
+        })
+    };
+    let mut flushed_rows = 0;
+    for _ in 0..3 {
+        let report = table.flush().await?;
+        flushed_rows += report.flushed_rows;
+    }
+    writer.await??;
+    flushed_rows += table.flush().await?.flushed_rows;
+
+    // every row is flushed exactly once
+    assert_eq!(flushed_rows, 30);
+    assert_eq!(scanned_row_ids(&table).await?, (1..=30).collect::<Vec<_>>());
+    assert_eq!(table.flush().await?, FlushReport::default());
+
+    Ok(())
+}
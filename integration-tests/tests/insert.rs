@@ -3,20 +3,33 @@ use arrow::datatypes::{DataType, Field, Schema};
 use arrow::util::pretty::pretty_format_batches;
 use futures::TryStreamExt;
 use indexlake::{
-    LakeClient,
-    catalog::Catalog,
+    ILResult, LakeClient,
+    catalog::{
+        Catalog, CatalogDatabase, CatalogSchemaRef, IsolationLevel, RowStream, Scalar, Transaction,
+    },
     storage::Storage,
-    table::{TableConfig, TableCreation},
+    table::{TableConfig, TableCreation, TableScan},
 };
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
 use indexlake_integration_tests::utils::full_table_scan;
 use indexlake_integration_tests::{
-    catalog_postgres, catalog_sqlite, init_env_logger, storage_fs, storage_s3,
+    catalog_mariadb, catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite,
+    init_env_logger, storage_fs, storage_s3,
+};
+use std::collections::HashMap;
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, AtomicUsize, Ordering},
 };
-use std::sync::Arc;
 
 #[rstest::rstest]
 #[case(async { catalog_sqlite() }, storage_fs())]
 #[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
 #[tokio::test(flavor = "multi_thread")]
 async fn insert_table(
     #[future(awt)]
@@ -29,7 +42,9 @@ async fn insert_table(
     let client = LakeClient::new(catalog, storage);
 
     let namespace_name = "test_namespace";
-    client.create_namespace(namespace_name).await?;
+    client
+        .create_namespace(namespace_name, HashMap::new())
+        .await?;
 
     let table_schema = Arc::new(Schema::new(vec![
         Field::new("id", DataType::Int64, false),
@@ -71,3 +86,214 @@ async fn insert_table(
 
     Ok(())
 }
+
+/// Counts transactions and the statements run in them against the wrapped catalog.
+#[derive(Debug)]
+struct CountingCatalog {
+    inner: Arc<dyn Catalog>,
+    counts: Arc<Counts>,
+}
+
+#[derive(Debug, Default)]
+struct Counts {
+    transactions: AtomicUsize,
+    statements: AtomicUsize,
+}
+
+impl Counts {
+    fn get(&self) -> (usize, usize) {
+        (
+            self.transactions.load(Ordering::SeqCst),
+            self.statements.load(Ordering::SeqCst),
+        )
+    }
+}
+
+#[async_trait::async_trait]
+impl Catalog for CountingCatalog {
+    fn database(&self) -> CatalogDatabase {
+        self.inner.database()
+    }
+
+    async fn query(&self, sql: &str, schema: CatalogSchemaRef) -> ILResult<RowStream<'static>> {
+        self.inner.query(sql, schema).await
+    }
+
+    async fn transaction(&self) -> ILResult<Box<dyn Transaction>> {
+        self.counts.transactions.fetch_add(1, Ordering::SeqCst);
+        Ok(Box::new(CountingTransaction {
+            inner: self.inner.transaction().await?,
+            counts: self.counts.clone(),
+        }))
+    }
+
+    async fn begin_transaction(&self, level: IsolationLevel) -> ILResult<Box<dyn Transaction>> {
+        self.counts.transactions.fetch_add(1, Ordering::SeqCst);
+        Ok(Box::new(CountingTransaction {
+            inner: self.inner.begin_transaction(level).await?,
+            counts: self.counts.clone(),
+        }))
+    }
+}
+
+#[derive(Debug)]
+struct CountingTransaction {
+    inner: Box<dyn Transaction>,
+    counts: Arc<Counts>,
+}
+
+#[async_trait::async_trait]
+impl Transaction for CountingTransaction {
+    async fn query<'a>(
+        &'a mut self,
+        sql: &str,
+        schema: CatalogSchemaRef,
+    ) -> ILResult<RowStream<'a>> {
+        self.counts.statements.fetch_add(1, Ordering::SeqCst);
+        self.inner.query(sql, schema).await
+    }
+
+    async fn execute(&mut self, sql: &str) -> ILResult<usize> {
+        self.counts.statements.fetch_add(1, Ordering::SeqCst);
+        self.inner.execute(sql).await
+    }
+
+    async fn execute_batch(&mut self, sqls: &[String]) -> ILResult<()> {
+        self.counts.statements.fetch_add(1, Ordering::SeqCst);
+        self.inner.execute_batch(sqls).await
+    }
+
+    async fn insert_rows(
+        &mut self,
+        database: CatalogDatabase,
+        table_name: &str,
+        columns: &[String],
+        rows: &[Vec<Scalar>],
+        batch_size: usize,
+    ) -> ILResult<usize> {
+        self.counts.statements.fetch_add(1, Ordering::SeqCst);
+        self.inner
+            .insert_rows(database, table_name, columns, rows, batch_size)
+            .await
+    }
+
+    async fn commit(&mut self) -> ILResult<()> {
+        self.inner.commit().await
+    }
+
+    async fn rollback(&mut self) -> ILResult<()> {
+        self.inner.rollback().await
+    }
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn insert_many_batches(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let counts = Arc::new(Counts::default());
+    let counting = Arc::new(CountingCatalog {
+        inner: catalog.clone(),
+        counts: counts.clone(),
+    });
+    let client = LakeClient::new(counting, storage.clone());
+    client
+        .create_namespace("test_namespace", HashMap::new())
+        .await?;
+    let table_schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, false),
+    ]));
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "insert_many_batches".to_string(),
+            schema: table_schema.clone(),
+            config: TableConfig::default(),
+        })
+        .await?;
+    let table = client
+        .load_table("test_namespace", "insert_many_batches")
+        .await?;
+
+    let batch = |start: i64| {
+        RecordBatch::try_new(
+            table_schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(vec![start, start + 1])),
+                Arc::new(StringArray::from(vec!["a", "b"])),
+            ],
+        )
+    };
+    let batches = (0..50)
+        .map(|i| batch(i * 2))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // a batch of another schema fails the insert before anything is written
+    let invalid_batch = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, false)])),
+        vec![Arc::new(Int64Array::from(vec![1000]))],
+    )?;
+    let before = counts.get();
+    let invalid_batches = [batches.clone(), vec![invalid_batch]].concat();
+    assert!(table.insert_many(invalid_batches).await.is_err());
+    assert_eq!(counts.get(), before);
+
+    // catalog round trips of one single batch insert
+    let before = counts.get();
+    table.insert(&batch(100)?).await?;
+    let after = counts.get();
+    let single_statements = after.1 - before.1;
+    assert_eq!(after.0 - before.0, 1);
+
+    // scans, not counted, see either none or all of the inserted rows
+    let done = Arc::new(AtomicBool::new(false));
+    let reader = {
+        let table = LakeClient::new(catalog, storage)
+            .load_table("test_namespace", "insert_many_batches")
+            .await?;
+        let done = done.clone();
+        tokio::spawn(async move {
+            let mut row_counts = Vec::new();
+            while !done.load(Ordering::SeqCst) {
+                let batches = table
+                    .scan(TableScan::default())
+                    .await?
+                    .try_collect::<Vec<_>>()
+                    .await?;
+                row_counts.push(batches.iter().map(|b| b.num_rows()).sum::<usize>());
+            }
+            Ok::<_, indexlake::ILError>(row_counts)
+        })
+    };
+
+    let before = counts.get();
+    table.insert_many(batches).await?;
+    let after = counts.get();
+    done.store(true, Ordering::SeqCst);
+    let row_counts = reader.await??;
+    assert!(row_counts.iter().all(|count| *count == 2 || *count == 102));
+
+    // one transaction with the statements of a single insert
+    assert_eq!(after.0 - before.0, 1);
+    assert_eq!(after.1 - before.1, single_statements);
+
+    let batches = table
+        .scan(TableScan::default())
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 102);
+
+    Ok(())
+}
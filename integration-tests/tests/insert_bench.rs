@@ -0,0 +1,78 @@
+use arrow::array::{Int64Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use indexlake::{
+    LakeClient,
+    catalog::Catalog,
+    storage::Storage,
+    table::{TableConfig, TableCreation},
+};
+use indexlake_integration_tests::{
+    catalog_postgres, catalog_sqlite, init_env_logger, storage_fs, storage_s3,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+const ROW_COUNT: usize = 20000;
+
+// Compares row throughput of one catalog statement per row against batched multi-row inserts.
+// Run with `cargo test --release --test insert_bench -- --ignored --nocapture`.
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[tokio::test(flavor = "multi_thread")]
+#[ignore]
+async fn insert_throughput(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let namespace_name = "test_namespace";
+    client
+        .create_namespace(namespace_name, HashMap::new())
+        .await?;
+
+    let table_schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, false),
+    ]));
+    let record_batch = RecordBatch::try_new(
+        table_schema.clone(),
+        vec![
+            Arc::new(Int64Array::from_iter_values(0..ROW_COUNT as i64)),
+            Arc::new(StringArray::from_iter_values(
+                (0..ROW_COUNT).map(|i| format!("name_{i}")),
+            )),
+        ],
+    )?;
+
+    for batch_size in [1, 100, 1000, 5000] {
+        let table_name = format!("insert_throughput_{batch_size}");
+        let table_creation = TableCreation {
+            namespace_name: namespace_name.to_string(),
+            table_name: table_name.clone(),
+            schema: table_schema.clone(),
+            config: TableConfig {
+                inline_row_count_limit: ROW_COUNT * 2,
+                catalog_insert_batch_size: batch_size,
+                ..Default::default()
+            },
+        };
+        client.create_table(table_creation).await?;
+        let table = client.load_table(namespace_name, &table_name).await?;
+
+        let start = Instant::now();
+        table.insert(&record_batch).await?;
+        let elapsed = start.elapsed();
+        println!(
+            "batch size {batch_size:>5}: {ROW_COUNT} rows in {elapsed:?}, {:.0} rows/s",
+            ROW_COUNT as f64 / elapsed.as_secs_f64()
+        );
+    }
+
+    Ok(())
+}
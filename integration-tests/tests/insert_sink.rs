@@ -0,0 +1,182 @@
+use arrow::array::{Int64Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use indexlake::table::{SinkOptions, SnapshotOperation, Table, TableConfig, TableCreation};
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::create_namespace_if_not_exists;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger, storage_fs,
+    storage_s3,
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn table_schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, false),
+    ]))
+}
+
+async fn create_table(
+    client: &LakeClient,
+    table_name: &str,
+) -> Result<Table, Box<dyn std::error::Error>> {
+    create_namespace_if_not_exists(client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: table_schema(),
+            config: TableConfig::default(),
+        })
+        .await?;
+    Ok(client.load_table("test_namespace", table_name).await?)
+}
+
+fn batch(ids: std::ops::Range<i64>) -> Result<RecordBatch, Box<dyn std::error::Error>> {
+    let names = ids
+        .clone()
+        .map(|id| format!("name{id}"))
+        .collect::<Vec<_>>();
+    Ok(RecordBatch::try_new(
+        table_schema(),
+        vec![
+            Arc::new(Int64Array::from_iter_values(ids)),
+            Arc::new(StringArray::from(names)),
+        ],
+    )?)
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn insert_sink(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let table = create_table(&client, "insert_sink").await?;
+    let snapshot_count = table.snapshots().await?.len();
+
+    // batches are committed together once 10 rows are buffered
+    let mut sink = table.insert_sink(
+        SinkOptions::default()
+            .with_max_buffered_rows(10usize)
+            .with_flush_interval(None),
+    )?;
+    for start in (0..25).step_by(5) {
+        sink.write(&batch(start..start + 5)?).await?;
+    }
+    assert_eq!(sink.flush().await?, 5);
+    assert_eq!(sink.flush().await?, 0);
+    assert_eq!(table.count(None).await?, 25);
+    sink.write(&batch(25..28)?).await?;
+    let report = sink.close().await?;
+    assert_eq!(report.written_rows, 28);
+    assert_eq!(report.data_file_paths.len(), 4);
+
+    // rows are committed into data files, a snapshot per commit
+    assert_eq!(table.count(None).await?, 28);
+    assert_eq!(table.storage_stats().await?.data_file_count, 4);
+    let snapshots = table.snapshots().await?;
+    assert_eq!(snapshots.len(), snapshot_count + 4);
+    assert_eq!(
+        snapshots.last().unwrap().operation,
+        Some(SnapshotOperation::Insert)
+    );
+
+    // invalid batches are rejected on write and leave the sink usable
+    let mut sink = table.insert_sink(SinkOptions::default())?;
+    let wrong_schema = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![Field::new("id", DataType::Utf8, false)])),
+        vec![Arc::new(StringArray::from(vec!["a"]))],
+    )?;
+    assert!(sink.write(&wrong_schema).await.is_err());
+    sink.write(&batch(28..30)?).await?;
+    assert_eq!(sink.close().await?.written_rows, 2);
+    assert_eq!(table.count(None).await?, 30);
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[tokio::test(flavor = "multi_thread")]
+async fn insert_sink_flush_interval(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let table = create_table(&client, "insert_sink_flush_interval").await?;
+
+    let mut sink = table.insert_sink(
+        SinkOptions::default().with_flush_interval(Some(Duration::from_millis(200))),
+    )?;
+    sink.write(&batch(0..3)?).await?;
+    assert_eq!(table.count(None).await?, 0);
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert_eq!(table.count(None).await?, 3);
+    assert_eq!(table.storage_stats().await?.data_file_count, 1);
+
+    // dropping the sink discards the buffered rows
+    sink.write(&batch(3..6)?).await?;
+    drop(sink);
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(table.count(None).await?, 3);
+    assert_eq!(table.storage_stats().await?.data_file_count, 1);
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[tokio::test(flavor = "multi_thread")]
+async fn insert_sink_byte_threshold(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let table = create_table(&client, "insert_sink_byte_threshold").await?;
+    assert!(
+        table
+            .insert_sink(SinkOptions::default().with_max_buffered_rows(0usize))
+            .is_err()
+    );
+
+    // every batch fills the buffer, writes wait for the previous commit
+    let mut sink = table.insert_sink(
+        SinkOptions::default()
+            .with_max_buffered_bytes(1usize)
+            .with_flush_interval(None),
+    )?;
+    for start in (0..30).step_by(10) {
+        sink.write(&batch(start..start + 10)?).await?;
+    }
+    assert_eq!(sink.flush().await?, 0);
+    assert_eq!(table.count(None).await?, 30);
+    let report = sink.close().await?;
+    assert_eq!(report.written_rows, 30);
+    assert_eq!(report.data_file_paths.len(), 3);
+
+    Ok(())
+}
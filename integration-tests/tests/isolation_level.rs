@@ -0,0 +1,48 @@
+use indexlake::catalog::{Catalog, CatalogDatabase, IsolationLevel};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::{
+    catalog_mariadb, catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite,
+};
+use std::sync::Arc;
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() })]
+#[case(async { catalog_postgres().await })]
+#[case(async { catalog_mysql().await })]
+#[case(async { catalog_mariadb().await })]
+#[case(async { catalog_memory() })]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }))]
+#[tokio::test(flavor = "multi_thread")]
+async fn begin_transaction_with_isolation_level(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let single_writer = matches!(
+        catalog.database(),
+        CatalogDatabase::Sqlite | CatalogDatabase::DuckDb
+    );
+
+    for level in [
+        IsolationLevel::ReadCommitted,
+        IsolationLevel::RepeatableRead,
+    ] {
+        let result = catalog.begin_transaction(level).await;
+        if single_writer {
+            assert!(result.is_err());
+        } else {
+            result?.commit().await?;
+        }
+    }
+
+    let mut transaction = catalog
+        .begin_transaction(IsolationLevel::Serializable)
+        .await?;
+    transaction
+        .execute("INSERT INTO indexlake_namespace (namespace_id, namespace_name) VALUES (1, 'ns')")
+        .await?;
+    transaction.commit().await?;
+
+    Ok(())
+}
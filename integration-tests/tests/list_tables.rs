@@ -0,0 +1,267 @@
+use arrow::datatypes::{DataType, Field, Schema};
+use indexlake::expr::{col, lit};
+use indexlake::index::Index;
+use indexlake::table::{
+    DateUnit, IndexCreation, IndexDescription, ListOptions, PartitionTransform, TableConfig,
+    TableCreation, TableIdent,
+};
+use indexlake::{ILError, LakeClient, catalog::Catalog, storage::Storage};
+use indexlake_index_hash::{HashIndex, HashIndexParams};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::{create_namespace_if_not_exists, prepare_testing_table};
+use indexlake_integration_tests::{
+    catalog_mariadb, catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite,
+    init_env_logger, storage_fs, storage_s3,
+};
+use std::sync::Arc;
+
+async fn list_all_pages(
+    client: &LakeClient,
+    mut options: ListOptions,
+) -> Result<Vec<Vec<String>>, Box<dyn std::error::Error>> {
+    let mut pages = Vec::new();
+    loop {
+        let page = client
+            .list_tables("test_namespace", options.clone())
+            .await?;
+        pages.push(
+            page.tables
+                .into_iter()
+                .map(|table| table.table_name)
+                .collect::<Vec<_>>(),
+        );
+        match page.next_page_token {
+            Some(token) => options.page_token = Some(token),
+            None => return Ok(pages),
+        }
+    }
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn list_tables(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    prepare_testing_table(&client, "list_a").await?;
+
+    let table_schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, false)]));
+    for table_name in ["list_b", "list_c", "list_d", "other"] {
+        client
+            .create_table(TableCreation {
+                namespace_name: "test_namespace".to_string(),
+                table_name: table_name.to_string(),
+                schema: table_schema.clone(),
+                config: TableConfig::default(),
+            })
+            .await?;
+    }
+
+    let page = client
+        .list_tables("test_namespace", ListOptions::default())
+        .await?;
+    assert_eq!(page.tables.len(), 5);
+    assert!(page.next_page_token.is_none());
+    let summary = &page.tables[0];
+    assert_eq!(summary.table_name, "list_a");
+    assert_eq!(summary.row_count, 4);
+    assert_eq!(summary.schema.fields().len(), 2);
+    assert_eq!(summary.schema.field(0).name(), "name");
+    assert_eq!(page.tables[1].row_count, 0);
+
+    // Page size exactly divides the table count, no empty trailing page
+    let options = ListOptions {
+        prefix: Some("list_".to_string()),
+        limit: Some(2),
+        ..Default::default()
+    };
+    let pages = list_all_pages(&client, options).await?;
+    assert_eq!(
+        pages,
+        vec![vec!["list_a", "list_b"], vec!["list_c", "list_d"]]
+    );
+
+    let options = ListOptions {
+        limit: Some(3),
+        ..Default::default()
+    };
+    let pages = list_all_pages(&client, options).await?;
+    assert_eq!(
+        pages,
+        vec![vec!["list_a", "list_b", "list_c"], vec!["list_d", "other"]]
+    );
+
+    let options = ListOptions {
+        page_token: Some("not a token".to_string()),
+        ..Default::default()
+    };
+    assert!(client.list_tables("test_namespace", options).await.is_err());
+    assert!(
+        client
+            .list_tables("not_exists", ListOptions::default())
+            .await
+            .is_err()
+    );
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn describe_tables(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage);
+    client.register_index(Arc::new(HashIndex))?;
+    let basic = prepare_testing_table(&client, "describe_basic").await?;
+
+    let events_schema = Arc::new(Schema::new(vec![
+        Field::new("user", DataType::Utf8, false),
+        Field::new("ts", DataType::Int64, false),
+        Field::new("score", DataType::Float64, true),
+    ]));
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "describe_events".to_string(),
+            schema: events_schema.clone(),
+            config: TableConfig {
+                partition_by: vec!["ts".to_string()],
+                partition_transform: PartitionTransform::DateTrunc {
+                    unit: DateUnit::Day,
+                },
+                storage_prefix: Some("custom/describe_events".to_string()),
+                ..Default::default()
+            },
+        })
+        .await?;
+    let mut events = client
+        .load_table("test_namespace", "describe_events")
+        .await?;
+    events
+        .create_index(IndexCreation {
+            name: "user_index".to_string(),
+            kind: HashIndex.kind().to_string(),
+            key_columns: vec!["user".to_string()],
+            include_columns: vec!["score".to_string()],
+            params: Arc::new(HashIndexParams::default()),
+            where_predicate: Some(col("score").is_not_null()),
+        })
+        .await?;
+
+    create_namespace_if_not_exists(&client, "describe_empty").await?;
+    assert!(client.list_table_idents("describe_empty").await?.is_empty());
+    let idents = client.list_table_idents("test_namespace").await?;
+    assert_eq!(
+        idents,
+        vec![
+            TableIdent::new("test_namespace", "describe_basic"),
+            TableIdent::new("test_namespace", "describe_events"),
+        ]
+    );
+
+    let description = client.describe_table(&idents[0]).await?;
+    assert_eq!(description.ident, idents[0]);
+    assert_eq!(description.table_id, basic.table_id);
+    assert_eq!(
+        description.schema.as_ref(),
+        &Schema::new(vec![
+            Field::new("name", DataType::Utf8, false),
+            Field::new("age", DataType::Int32, false),
+        ])
+    );
+    assert!(description.partition_by.is_empty());
+    assert!(description.indexes.is_empty());
+    assert_eq!(
+        description.current_snapshot_id,
+        basic.snapshots().await?.last().map(|s| s.snapshot_id)
+    );
+    assert_eq!(description.row_count, 4);
+    assert_eq!(description.storage_prefix, basic.table_dir());
+
+    let description = client.describe_table(&idents[1]).await?;
+    assert_eq!(description.table_id, events.table_id);
+    assert_eq!(
+        description
+            .schema
+            .fields()
+            .iter()
+            .map(|field| (field.name().as_str(), field.data_type().clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("user", DataType::Utf8),
+            ("ts", DataType::Int64),
+            ("score", DataType::Float64),
+        ]
+    );
+    assert_eq!(description.partition_by, vec!["ts".to_string()]);
+    assert_eq!(
+        description.partition_transform,
+        PartitionTransform::DateTrunc {
+            unit: DateUnit::Day
+        }
+    );
+    assert_eq!(
+        description.indexes,
+        vec![IndexDescription {
+            name: "user_index".to_string(),
+            kind: HashIndex.kind().to_string(),
+            key_columns: vec!["user".to_string()],
+            include_columns: vec!["score".to_string()],
+            where_predicate: Some(col("score").is_not_null()),
+            unindexed_data_file_count: 0,
+        }]
+    );
+    assert_eq!(description.row_count, 0);
+    assert_eq!(description.storage_prefix, "custom/describe_events");
+
+    // descriptions follow the catalog, not loaded tables
+    basic.delete(&col("age").gt(lit(21))).await?;
+    let description = client.describe_table(&idents[0]).await?;
+    assert_eq!(description.row_count, 2);
+    assert_eq!(
+        description.current_snapshot_id,
+        basic.snapshots().await?.last().map(|s| s.snapshot_id)
+    );
+
+    assert!(matches!(
+        client.list_table_idents("not_exists").await,
+        Err(ILError::CatalogError(_))
+    ));
+    assert!(matches!(
+        client
+            .describe_table(&TableIdent::new("test_namespace", "not_exists"))
+            .await,
+        Err(ILError::CatalogError(_))
+    ));
+    assert!(matches!(
+        client
+            .describe_table(&TableIdent::new("not_exists", "describe_basic"))
+            .await,
+        Err(ILError::CatalogError(_))
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,250 @@
+use arrow::array::{Int64Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use indexlake::expr::{col, lit};
+use indexlake::table::{MergeResult, Table, TableConfig, TableCreation, source_col};
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::create_namespace_if_not_exists;
+use indexlake_integration_tests::utils::full_table_scan;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_postgres, catalog_sqlite, init_env_logger, storage_fs, storage_s3,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn table_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, false),
+        Field::new("qty", DataType::Int64, false),
+    ]))
+}
+
+fn batch(
+    ids: Vec<i64>,
+    names: Vec<&str>,
+    qtys: Vec<i64>,
+) -> Result<RecordBatch, Box<dyn std::error::Error>> {
+    Ok(RecordBatch::try_new(
+        table_schema(),
+        vec![
+            Arc::new(Int64Array::from(ids)),
+            Arc::new(StringArray::from(names)),
+            Arc::new(Int64Array::from(qtys)),
+        ],
+    )?)
+}
+
+async fn create_table(
+    client: &LakeClient,
+    table_name: &str,
+) -> Result<Table, Box<dyn std::error::Error>> {
+    create_namespace_if_not_exists(client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: table_schema(),
+            config: TableConfig {
+                inline_row_count_limit: 3,
+                parquet_row_group_size: 2,
+                ..Default::default()
+            },
+        })
+        .await?;
+    Ok(client.load_table("test_namespace", table_name).await?)
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn merge_all_clauses(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let table = create_table(&client, "merge_all_clauses").await?;
+    table
+        .insert(&batch(
+            vec![1, 2, 3, 4, 5],
+            vec!["a", "b", "c", "d", "e"],
+            vec![10, 20, 30, 40, 50],
+        )?)
+        .await?;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+    table.insert(&batch(vec![6], vec!["f"], vec![60])?).await?;
+
+    // Ids 2 and 6 are updated, 4 is deleted, 7 and 8 are inserted
+    let source = batch(
+        vec![2, 4, 6, 7, 8],
+        vec!["b2", "d2", "f2", "g", "h"],
+        vec![5, 0, 6, 70, 80],
+    )?;
+    let result = table
+        .merge(source)
+        .on(col("id").eq(source_col("id")))
+        .when_matched_delete(source_col("qty").eq(lit(0i64)))
+        .when_matched_update(HashMap::from([
+            ("name".to_string(), source_col("name")),
+            ("qty".to_string(), col("qty").plus(source_col("qty"))),
+        ]))
+        .when_not_matched_insert()
+        .execute()
+        .await?;
+    assert_eq!(
+        result,
+        MergeResult {
+            updated: 2,
+            deleted: 1,
+            inserted: 2,
+        }
+    );
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+
+    assert_eq!(
+        full_table_scan(&table).await?,
+        r#"+-------------------+----+------+-----+
+| _indexlake_row_id | id | name | qty |
++-------------------+----+------+-----+
+| 1                 | 1  | a    | 10  |
+| 2                 | 2  | b2   | 25  |
+| 3                 | 3  | c    | 30  |
+| 5                 | 5  | e    | 50  |
+| 6                 | 6  | f2   | 66  |
+| 7                 | 7  | g    | 70  |
+| 8                 | 8  | h    | 80  |
++-------------------+----+------+-----+"#
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn merge_optional_clauses() -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog_sqlite(), storage_fs());
+    let table = create_table(&client, "merge_optional_clauses").await?;
+    table
+        .insert(&batch(vec![1, 2], vec!["a", "b"], vec![10, 20])?)
+        .await?;
+
+    // Only unmatched rows are inserted, matched rows are left as they are
+    let result = table
+        .merge(batch(vec![2, 3], vec!["b2", "c"], vec![0, 30])?)
+        .on(source_col("id").eq(col("id")))
+        .when_not_matched_insert()
+        .execute()
+        .await?;
+    assert_eq!(
+        result,
+        MergeResult {
+            inserted: 1,
+            ..Default::default()
+        }
+    );
+
+    // Matched rows not meeting the delete predicate are kept without an update clause
+    let result = table
+        .merge(batch(vec![1, 2, 9], vec!["", "", ""], vec![0, 1, 0])?)
+        .on(col("id").eq(source_col("id")))
+        .when_matched_delete(source_col("qty").eq(lit(0i64)))
+        .execute()
+        .await?;
+    assert_eq!(
+        result,
+        MergeResult {
+            deleted: 1,
+            ..Default::default()
+        }
+    );
+
+    assert_eq!(
+        full_table_scan(&table).await?,
+        r#"+-------------------+----+------+-----+
+| _indexlake_row_id | id | name | qty |
++-------------------+----+------+-----+
+| 2                 | 2  | b    | 20  |
+| 3                 | 3  | c    | 30  |
++-------------------+----+------+-----+"#
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn merge_invalid() -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog_sqlite(), storage_fs());
+    let table = create_table(&client, "merge_invalid").await?;
+    table
+        .insert(&batch(vec![1, 2], vec!["a", "b"], vec![10, 20])?)
+        .await?;
+    let source = batch(vec![1, 1], vec!["x", "y"], vec![1, 2])?;
+    let update = HashMap::from([("qty".to_string(), source_col("qty"))]);
+
+    // a target row matched by several source rows
+    let result = table
+        .merge(source.clone())
+        .on(col("id").eq(source_col("id")))
+        .when_matched_update(update.clone())
+        .execute()
+        .await;
+    assert!(
+        result
+            .unwrap_err()
+            .to_string()
+            .contains("with 2 source rows")
+    );
+
+    for (on, message) in [
+        (col("id").gt(source_col("id")), "must be equalities"),
+        (col("id").eq(col("qty")), "must be equalities"),
+        (col("name").eq(source_col("id")), "compares Utf8 with Int64"),
+    ] {
+        let result = table
+            .merge(source.clone())
+            .on(on)
+            .when_matched_update(update.clone())
+            .execute()
+            .await;
+        assert!(result.unwrap_err().to_string().contains(message));
+    }
+
+    let result = table
+        .merge(source.clone())
+        .on(col("id").eq(source_col("id")))
+        .when_matched_update(HashMap::from([("qty".to_string(), source_col("name"))]))
+        .execute()
+        .await;
+    assert!(
+        result
+            .unwrap_err()
+            .to_string()
+            .contains("of type Int64 to Utf8")
+    );
+
+    // failed merges change nothing
+    assert_eq!(
+        full_table_scan(&table).await?,
+        r#"+-------------------+----+------+-----+
+| _indexlake_row_id | id | name | qty |
++-------------------+----+------+-----+
+| 1                 | 1  | a    | 10  |
+| 2                 | 2  | b    | 20  |
++-------------------+----+------+-----+"#
+    );
+
+    Ok(())
+}
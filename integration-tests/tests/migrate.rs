@@ -0,0 +1,122 @@
+use indexlake::catalog::CATALOG_VERSION;
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+use indexlake_catalog_sqlite::SqliteCatalog;
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::{
+    catalog_mariadb, catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite,
+    init_env_logger, storage_fs, storage_s3,
+};
+use indexlake_integration_tests::{data::prepare_testing_table, utils::full_table_scan};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn migrate_existing_catalog(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog.clone(), storage);
+    let table = prepare_testing_table(&client, "migrate_existing_catalog").await?;
+    let table_str_before = full_table_scan(&table).await?;
+
+    catalog.migrate().await?;
+    assert_eq!(catalog.catalog_version().await?, CATALOG_VERSION);
+    // migrating again is a no-op
+    catalog.migrate().await?;
+
+    let table = client
+        .load_table("test_namespace", "migrate_existing_catalog")
+        .await?;
+    let table_str_after = full_table_scan(&table).await?;
+    assert_eq!(table_str_before, table_str_after);
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() })]
+#[case(async { catalog_postgres().await })]
+#[case(async { catalog_mysql().await })]
+#[case(async { catalog_mariadb().await })]
+#[case(async { catalog_memory() })]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }))]
+#[tokio::test(flavor = "multi_thread")]
+async fn reject_newer_catalog_version(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    catalog.migrate().await?;
+
+    let mut transaction = catalog.transaction().await?;
+    transaction
+        .execute(&format!(
+            "UPDATE indexlake_catalog_version SET version = {}",
+            CATALOG_VERSION + 1
+        ))
+        .await?;
+    transaction.commit().await?;
+
+    let result = catalog.migrate().await;
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn migrate_empty_sqlite_catalog() -> Result<(), Box<dyn std::error::Error>> {
+    let db_path = format!(
+        "{}/tmp/sqlite/{}.db",
+        env!("CARGO_MANIFEST_DIR"),
+        uuid::Uuid::new_v4()
+    );
+    std::fs::create_dir_all(std::path::Path::new(&db_path).parent().unwrap())?;
+    rusqlite::Connection::open(&db_path)?;
+
+    let catalog = SqliteCatalog::try_new(db_path)?;
+    assert_eq!(catalog.catalog_version().await?, 0);
+    catalog.migrate().await?;
+    assert_eq!(catalog.catalog_version().await?, CATALOG_VERSION);
+
+    let client = LakeClient::new(Arc::new(catalog), storage_fs());
+    client
+        .create_namespace("test_namespace", HashMap::new())
+        .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn migrate_v1_sqlite_catalog() -> Result<(), Box<dyn std::error::Error>> {
+    let db_path = format!(
+        "{}/tmp/sqlite/{}.db",
+        env!("CARGO_MANIFEST_DIR"),
+        uuid::Uuid::new_v4()
+    );
+    std::fs::create_dir_all(std::path::Path::new(&db_path).parent().unwrap())?;
+    let conn = rusqlite::Connection::open(&db_path)?;
+    conn.execute_batch(include_str!("../testdata/sqlite/init_catalog.sql"))?;
+
+    let catalog = SqliteCatalog::try_new(db_path)?;
+    assert_eq!(catalog.catalog_version().await?, 1);
+    catalog.migrate().await?;
+    assert_eq!(catalog.catalog_version().await?, CATALOG_VERSION);
+
+    let client = LakeClient::new(Arc::new(catalog), storage_fs());
+    let table = prepare_testing_table(&client, "migrate_v1_sqlite_catalog").await?;
+    assert_eq!(table.snapshots().await?.len(), 2);
+
+    Ok(())
+}
@@ -0,0 +1,394 @@
+use arrow::array::{Int64Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use futures::TryStreamExt;
+use indexlake::catalog::RetryPolicy;
+use indexlake::expr::{col, lit};
+use indexlake::index::Index;
+use indexlake::table::{
+    IndexCreation, SnapshotOperation, Table, TableConfig, TableCreation, TableScan,
+    TransactionOptions, UniqueConstraint,
+};
+use indexlake::{ILError, LakeClient, catalog::Catalog, storage::Storage};
+use indexlake_index_btree::{BTreeIndex, BTreeIndexParams};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::create_namespace_if_not_exists;
+use indexlake_integration_tests::utils::sort_record_batches;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger, storage_s3,
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+// Each case lists the files of its own tables
+fn memory_storage() -> Arc<Storage> {
+    Arc::new(Storage::new_memory())
+}
+
+fn orders_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("order_id", DataType::Int64, false),
+        Field::new("customer", DataType::Utf8, false),
+    ]))
+}
+
+fn items_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("order_id", DataType::Int64, false),
+        Field::new("product", DataType::Utf8, false),
+    ]))
+}
+
+async fn create_tables(
+    client: &LakeClient,
+    prefix: &str,
+) -> Result<(Table, Table), Box<dyn std::error::Error>> {
+    create_namespace_if_not_exists(client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: format!("{prefix}_orders"),
+            schema: orders_schema(),
+            config: TableConfig {
+                unique_constraints: vec![UniqueConstraint::new(
+                    "unique_order_id",
+                    vec!["order_id".to_string()],
+                )],
+                ..Default::default()
+            },
+        })
+        .await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: format!("{prefix}_items"),
+            schema: items_schema(),
+            config: TableConfig {
+                partition_by: vec!["order_id".to_string()],
+                ..Default::default()
+            },
+        })
+        .await?;
+    let orders = client
+        .load_table("test_namespace", &format!("{prefix}_orders"))
+        .await?;
+    let items = client
+        .load_table("test_namespace", &format!("{prefix}_items"))
+        .await?;
+    Ok((orders, items))
+}
+
+fn orders_batch(
+    order_ids: Vec<i64>,
+    customers: Vec<&str>,
+) -> Result<RecordBatch, Box<dyn std::error::Error>> {
+    Ok(RecordBatch::try_new(
+        orders_schema(),
+        vec![
+            Arc::new(Int64Array::from(order_ids)),
+            Arc::new(StringArray::from(customers)),
+        ],
+    )?)
+}
+
+fn items_batch(
+    order_ids: Vec<i64>,
+    products: Vec<&str>,
+) -> Result<RecordBatch, Box<dyn std::error::Error>> {
+    Ok(RecordBatch::try_new(
+        items_schema(),
+        vec![
+            Arc::new(Int64Array::from(order_ids)),
+            Arc::new(StringArray::from(products)),
+        ],
+    )?)
+}
+
+async fn scan_orders(orders: &Table) -> Result<RecordBatch, Box<dyn std::error::Error>> {
+    let columns = vec!["order_id".to_string(), "customer".to_string()];
+    let scan = TableScan::default().with_columns(Some(columns));
+    let batches = orders.scan(scan).await?.try_collect::<Vec<_>>().await?;
+    Ok(sort_record_batches(&batches, "order_id")?)
+}
+
+async fn stored_file_count(table: &Table) -> Result<usize, Box<dyn std::error::Error>> {
+    Ok(table.storage.list_files(&table.table_dir()).await?.len())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, memory_storage())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, memory_storage())]
+#[case(async { catalog_memory() }, memory_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn multi_table_transaction(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let (orders, items) = create_tables(&client, "multi_table_transaction").await?;
+    let orders_snapshots = orders.snapshots().await?.len();
+
+    // nothing is visible before the commit
+    let mut tx = client.begin();
+    let order_batch = orders_batch(vec![1, 2], vec!["alice", "bob"])?;
+    let item_batch = items_batch(vec![1, 1, 2], vec!["apple", "pear", "plum"])?;
+    tx.insert(&orders, &order_batch).await?;
+    tx.insert(&items, &item_batch).await?;
+    assert_eq!(orders.count(None).await?, 0);
+    assert_eq!(items.count(None).await?, 0);
+    tx.commit().await?;
+
+    // both tables get their rows as data files, items a file per partition
+    assert_eq!(scan_orders(&orders).await?, order_batch);
+    assert_eq!(items.count(None).await?, 3);
+    assert_eq!(orders.storage_stats().await?.data_file_count, 1);
+    assert_eq!(items.storage_stats().await?.data_file_count, 2);
+    let snapshots = orders.snapshots().await?;
+    assert_eq!(snapshots.len(), orders_snapshots + 1);
+    assert_eq!(
+        snapshots.last().unwrap().operation,
+        Some(SnapshotOperation::Insert)
+    );
+
+    // committed rows are updated and deleted like any other
+    assert_eq!(
+        items
+            .delete(&col("product").eq(lit("pear".to_string())))
+            .await?,
+        1
+    );
+    assert_eq!(items.count(None).await?, 2);
+
+    // a unique key taken by a committed row fails the commit of both tables
+    let orders_files = stored_file_count(&orders).await?;
+    let items_files = stored_file_count(&items).await?;
+    let mut tx = client.begin();
+    tx.insert(&items, &items_batch(vec![3], vec!["fig"])?)
+        .await?;
+    tx.insert(&orders, &orders_batch(vec![2], vec!["carol"])?)
+        .await?;
+    assert!(matches!(
+        tx.commit().await,
+        Err(ILError::ConstraintViolation(_))
+    ));
+    assert_eq!(orders.count(None).await?, 2);
+    assert_eq!(items.count(None).await?, 2);
+    assert_eq!(stored_file_count(&orders).await?, orders_files);
+    assert_eq!(stored_file_count(&items).await?, items_files);
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, memory_storage())]
+#[case(async { catalog_memory() }, memory_storage())]
+#[tokio::test(flavor = "multi_thread")]
+async fn multi_table_transaction_rollback(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let (orders, items) = create_tables(&client, "multi_table_transaction_rollback").await?;
+    let orders_snapshots = orders.snapshots().await?.len();
+
+    // staged files are written on insert and deleted on rollback
+    let mut tx = client.begin();
+    tx.insert(&orders, &orders_batch(vec![1], vec!["alice"])?)
+        .await?;
+    tx.insert(&items, &items_batch(vec![1, 2], vec!["apple", "plum"])?)
+        .await?;
+    assert_eq!(stored_file_count(&orders).await?, 1);
+    assert_eq!(stored_file_count(&items).await?, 2);
+    tx.rollback().await;
+    assert_eq!(stored_file_count(&orders).await?, 0);
+    assert_eq!(stored_file_count(&items).await?, 0);
+    assert_eq!(orders.count(None).await?, 0);
+    assert_eq!(orders.snapshots().await?.len(), orders_snapshots);
+    assert_eq!(items.storage_stats().await?.data_file_count, 0);
+
+    // dropping the transaction rolls it back as well
+    let mut tx = client.begin();
+    tx.insert(&orders, &orders_batch(vec![1], vec!["alice"])?)
+        .await?;
+    drop(tx);
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(stored_file_count(&orders).await?, 0);
+    assert_eq!(orders.count(None).await?, 0);
+
+    // tables of another client can not join the transaction
+    let other_client = LakeClient::new(catalog_memory(), memory_storage());
+    let mut tx = other_client.begin();
+    assert!(
+        tx.insert(&orders, &orders_batch(vec![1], vec!["alice"])?)
+            .await
+            .is_err()
+    );
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, memory_storage())]
+#[case(async { catalog_memory() }, memory_storage())]
+#[tokio::test(flavor = "multi_thread")]
+async fn multi_table_transaction_conflict(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let (orders, items) = create_tables(&client, "multi_table_transaction_conflict").await?;
+
+    // a single table write committed meanwhile does not overlap the staged rows
+    let mut tx = client.begin();
+    tx.insert(&orders, &orders_batch(vec![1], vec!["alice"])?)
+        .await?;
+    tx.insert(&items, &items_batch(vec![1], vec!["apple"])?)
+        .await?;
+    orders.insert(&orders_batch(vec![2], vec!["bob"])?).await?;
+    tx.commit().await?;
+    assert_eq!(orders.count(None).await?, 2);
+    assert_eq!(items.count(None).await?, 1);
+
+    // a column added meanwhile is a real conflict, nothing is committed
+    let items_files = stored_file_count(&items).await?;
+    let mut tx = client.begin();
+    tx.insert(&orders, &orders_batch(vec![3], vec!["carol"])?)
+        .await?;
+    tx.insert(&items, &items_batch(vec![3], vec!["fig"])?)
+        .await?;
+    let mut altered_items = client
+        .load_table("test_namespace", &items.table_name)
+        .await?;
+    altered_items
+        .add_column(Field::new("quantity", DataType::Int64, true), None)
+        .await?;
+    assert!(matches!(tx.commit().await, Err(ILError::SchemaConflict(_))));
+    assert_eq!(orders.count(None).await?, 2);
+    assert_eq!(altered_items.count(None).await?, 1);
+    assert_eq!(stored_file_count(&items).await?, items_files);
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, memory_storage())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_memory() }, memory_storage())]
+#[tokio::test(flavor = "multi_thread")]
+async fn multi_table_transaction_concurrent_commits(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage);
+    client.register_index(Arc::new(BTreeIndex))?;
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    for table_name in [
+        "multi_table_transaction_concurrent_commits",
+        "multi_table_transaction_concurrent_commits_indexed",
+    ] {
+        client
+            .create_table(TableCreation {
+                namespace_name: "test_namespace".to_string(),
+                table_name: table_name.to_string(),
+                schema: items_schema(),
+                config: TableConfig::default(),
+            })
+            .await?;
+    }
+    let table = client
+        .load_table(
+            "test_namespace",
+            "multi_table_transaction_concurrent_commits",
+        )
+        .await?;
+    let mut indexed = client
+        .load_table(
+            "test_namespace",
+            "multi_table_transaction_concurrent_commits_indexed",
+        )
+        .await?;
+    indexed
+        .create_index(IndexCreation {
+            name: "order_id_index".to_string(),
+            kind: BTreeIndex.kind().to_string(),
+            key_columns: vec!["order_id".to_string()],
+            include_columns: vec![],
+            params: Arc::new(BTreeIndexParams { leaf_size: 4 }),
+            where_predicate: None,
+        })
+        .await?;
+
+    // every commit of every writer lands, aborted catalog transactions are retried
+    let options = TransactionOptions::default().with_commit_retry(RetryPolicy::new(
+        100,
+        Duration::from_millis(5),
+        Duration::from_millis(200),
+    ));
+    let mut handles = Vec::new();
+    for writer in 0..8i64 {
+        let client = client.clone();
+        let table = table.clone();
+        let indexed = indexed.clone();
+        let options = options.clone();
+        handles.push(tokio::spawn(async move {
+            for commit in 0..50i64 {
+                let mut tx = client.begin_with_options(options.clone());
+                let batch = items_batch(vec![writer * 50 + commit], vec!["apple"]).unwrap();
+                tx.insert(&table, &batch).await?;
+                tx.insert(&indexed, &batch).await?;
+                tx.commit().await?;
+            }
+            Ok::<_, ILError>(())
+        }));
+    }
+    for handle in handles {
+        handle.await??;
+    }
+
+    assert_eq!(table.storage_stats().await?.data_file_count, 400);
+    assert_eq!(table.count(None).await?, 400);
+
+    // every data file has its index file, index files of retried commits are not left behind
+    // and index the row ids their commit gave
+    assert_eq!(indexed.storage_stats().await?.data_file_count, 400);
+    assert_eq!(stored_file_count(&indexed).await?, 800);
+    for order_id in [0i64, 57, 199, 399] {
+        let scan = TableScan::default()
+            .with_columns(Some(vec!["order_id".to_string()]))
+            .with_filters(vec![col("order_id").eq(lit(order_id))]);
+        let batches = indexed.scan(scan).await?.try_collect::<Vec<_>>().await?;
+        let order_ids = batches
+            .iter()
+            .flat_map(|batch| {
+                batch
+                    .column(0)
+                    .as_any()
+                    .downcast_ref::<Int64Array>()
+                    .unwrap()
+                    .values()
+                    .to_vec()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(order_ids, vec![order_id]);
+    }
+
+    Ok(())
+}
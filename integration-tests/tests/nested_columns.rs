@@ -0,0 +1,152 @@
+use arrow::array::{
+    ArrayRef, Int32Array, Int64Array, Int64Builder, ListBuilder, RecordBatch, StringArray,
+    StructArray,
+};
+use arrow::buffer::NullBuffer;
+use arrow::compute::concat;
+use arrow::datatypes::{DataType, Field, Fields, Schema, SchemaRef};
+use futures::TryStreamExt;
+use indexlake::expr::{col, lit};
+use indexlake::table::{Table, TableConfig, TableCreation, TableScan};
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::create_namespace_if_not_exists;
+use indexlake_integration_tests::utils::sort_record_batches;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger, storage_fs,
+    storage_s3,
+};
+use std::sync::Arc;
+
+fn address_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("zip", DataType::Int32, false),
+        Field::new("city", DataType::Utf8, true),
+    ])
+}
+
+fn table_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("address", DataType::Struct(address_fields()), true),
+        Field::new(
+            "scores",
+            DataType::List(Arc::new(Field::new_list_field(DataType::Int64, true))),
+            true,
+        ),
+    ]))
+}
+
+/// Rows with nulls at every level: a null struct, a null struct field, a null list, an empty
+/// list and null list items.
+fn nested_batch() -> Result<RecordBatch, Box<dyn std::error::Error>> {
+    let address = StructArray::try_new(
+        address_fields(),
+        vec![
+            Arc::new(Int32Array::from(vec![10115, 0, 80331, 20095])) as ArrayRef,
+            Arc::new(StringArray::from(vec![
+                Some("Berlin"),
+                None,
+                None,
+                Some("Hamburg"),
+            ])),
+        ],
+        Some(NullBuffer::from(vec![true, false, true, true])),
+    )?;
+    let mut scores = ListBuilder::new(Int64Builder::new());
+    scores.append_value([Some(1), Some(2), Some(3)]);
+    scores.append_null();
+    scores.append_value(Vec::<Option<i64>>::new());
+    scores.append_value([None, Some(5)]);
+    Ok(RecordBatch::try_new(
+        table_schema(),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 2, 3, 4])),
+            Arc::new(address),
+            Arc::new(scores.finish()),
+        ],
+    )?)
+}
+
+async fn scan_sorted(
+    table: &Table,
+    scan: TableScan,
+) -> Result<RecordBatch, Box<dyn std::error::Error>> {
+    let batches = table.scan(scan).await?.try_collect::<Vec<_>>().await?;
+    Ok(sort_record_batches(&batches, "id")?)
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn nested_columns(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "nested_columns".to_string(),
+            schema: table_schema(),
+            config: TableConfig::default(),
+        })
+        .await?;
+    let table = client
+        .load_table("test_namespace", "nested_columns")
+        .await?;
+    // the nested types are stored exactly, names and nullability of children included
+    for field in table_schema().fields() {
+        assert_eq!(table.schema.field_with_name(field.name())?, field.as_ref());
+    }
+
+    let batch = nested_batch()?;
+    table.insert(&batch).await?;
+    let columns = Some(vec![
+        "id".to_string(),
+        "address".to_string(),
+        "scores".to_string(),
+    ]);
+    let scan = TableScan::default().with_columns(columns);
+
+    // inline rows
+    assert_eq!(scan_sorted(&table, scan.clone()).await?, batch);
+
+    // rows flushed into a data file
+    let report = table.flush().await?;
+    assert_eq!(report.flushed_rows, 4);
+    assert_eq!(scan_sorted(&table, scan.clone()).await?, batch);
+
+    // projections of nested columns and filters on other columns, inline and in data files
+    let more = nested_batch()?;
+    let more = RecordBatch::try_new(
+        more.schema(),
+        vec![
+            Arc::new(Int64Array::from(vec![5, 6, 7, 8])),
+            more.column(1).clone(),
+            more.column(2).clone(),
+        ],
+    )?;
+    table.insert(&more).await?;
+    let scan = TableScan::default()
+        .with_columns(Some(vec!["id".to_string(), "scores".to_string()]))
+        .with_filters(vec![col("id").eq(lit(4i64)).or(col("id").eq(lit(8i64)))]);
+    let scanned = scan_sorted(&table, scan).await?;
+    let scores = batch.column(2).slice(3, 1);
+    assert_eq!(
+        scanned.column(1),
+        &concat(&[scores.as_ref(), scores.as_ref()])?
+    );
+
+    Ok(())
+}
@@ -0,0 +1,202 @@
+use arrow::array::{Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use indexlake::expr::{Expr, col, lit};
+use indexlake::index::Index;
+use indexlake::table::{IndexCreation, TableConfig, TableCreation, TableScan};
+use indexlake::{ILResult, LakeClient, catalog::Catalog, storage::Storage};
+use indexlake_index_hash::{HashIndex, HashIndexParams};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::{
+    counted_storage, create_namespace_if_not_exists, insert_files,
+};
+use indexlake_integration_tests::utils::table_scan;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger,
+};
+use std::sync::Arc;
+
+fn files_opened(storage: &Storage, extension: &str) -> usize {
+    storage
+        .read_stats()
+        .unwrap()
+        .opened_paths
+        .iter()
+        .filter(|path| path.ends_with(extension))
+        .count()
+}
+
+fn table_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("name", DataType::Utf8, false),
+        Field::new("status", DataType::Utf8, false),
+    ]))
+}
+
+fn active() -> Expr {
+    col("status").eq(lit("active".to_string()))
+}
+
+fn name_index_creation(where_predicate: Option<Expr>) -> IndexCreation {
+    IndexCreation {
+        name: "active_name_index".to_string(),
+        kind: HashIndex.kind().to_string(),
+        key_columns: vec!["name".to_string()],
+        include_columns: vec![],
+        params: Arc::new(HashIndexParams::default()),
+        where_predicate,
+    }
+}
+
+/// Rows of the ids with users named after them, users of even ids are active and the others
+/// archived.
+fn file_batch(ids: Vec<i32>) -> ILResult<RecordBatch> {
+    let names = ids.iter().map(|id| format!("user{id}")).collect::<Vec<_>>();
+    let statuses = ids
+        .iter()
+        .map(|id| if id % 2 == 0 { "active" } else { "archived" })
+        .collect::<Vec<_>>();
+    Ok(RecordBatch::try_new(
+        table_schema(),
+        vec![
+            Arc::new(Int32Array::from(ids)),
+            Arc::new(StringArray::from(names)),
+            Arc::new(StringArray::from(statuses)),
+        ],
+    )?)
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn partial_index_scan(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage.clone());
+    client.register_index(Arc::new(HashIndex))?;
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "partial_index_scan".to_string(),
+            schema: table_schema(),
+            config: TableConfig {
+                inline_row_count_limit: 10,
+                parquet_row_group_size: 4,
+                ..Default::default()
+            },
+        })
+        .await?;
+    let mut table = client
+        .load_table("test_namespace", "partial_index_scan")
+        .await?;
+    table
+        .create_index(name_index_creation(Some(active())))
+        .await?;
+    let file_count = 3;
+    insert_files(&table, file_count, file_batch).await?;
+
+    // the filters imply the predicate, the index narrows the scan down to one file
+    let active_scan = TableScan::default()
+        .with_filters(vec![col("name").eq(lit("user14".to_string())), active()]);
+    storage.reset_read_stats();
+    assert_eq!(
+        table_scan(&table, active_scan).await?,
+        r#"+-------------------+----+--------+--------+
+| _indexlake_row_id | id | name   | status |
++-------------------+----+--------+--------+
+| 15                | 14 | user14 | active |
++-------------------+----+--------+--------+"#,
+    );
+    // the file of users 20 to 29 is skipped by its column stats before its index is read
+    assert_eq!(files_opened(&storage, ".index"), file_count as usize - 1);
+    assert_eq!(files_opened(&storage, ".parquet"), 1);
+
+    // archived rows are not indexed
+    let archived_scan = TableScan::default().with_filters(vec![
+        col("name").eq(lit("user13".to_string())).and(active()),
+    ]);
+    storage.reset_read_stats();
+    assert_eq!(table_scan(&table, archived_scan).await?.lines().count(), 4);
+    assert_eq!(files_opened(&storage, ".parquet"), 0);
+
+    // scans of all rows do not use the index, archived rows are found as well
+    let all_rows_scan =
+        TableScan::default().with_filters(vec![col("name").eq(lit("user13".to_string()))]);
+    storage.reset_read_stats();
+    assert_eq!(
+        table_scan(&table, all_rows_scan).await?,
+        r#"+-------------------+----+--------+----------+
+| _indexlake_row_id | id | name   | status   |
++-------------------+----+--------+----------+
+| 14                | 13 | user13 | archived |
++-------------------+----+--------+----------+"#,
+    );
+    assert_eq!(files_opened(&storage, ".index"), 0);
+    assert_eq!(files_opened(&storage, ".parquet"), file_count as usize - 1);
+
+    // the predicate is kept in the catalog
+    let table = client
+        .load_table("test_namespace", "partial_index_scan")
+        .await?;
+    let active_scan = TableScan::default()
+        .with_filters(vec![col("name").eq(lit("user24".to_string())), active()]);
+    storage.reset_read_stats();
+    assert_eq!(table_scan(&table, active_scan).await?.lines().count(), 5);
+    assert_eq!(files_opened(&storage, ".parquet"), 1);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn partial_index_invalid_predicate() -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog_sqlite(), counted_storage());
+    client.register_index(Arc::new(HashIndex))?;
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "partial_index_invalid_predicate".to_string(),
+            schema: table_schema(),
+            config: TableConfig::default(),
+        })
+        .await?;
+    let mut table = client
+        .load_table("test_namespace", "partial_index_invalid_predicate")
+        .await?;
+
+    let missing_column = col("state").eq(lit("active".to_string()));
+    assert!(
+        table
+            .create_index(name_index_creation(Some(missing_column)))
+            .await
+            .is_err()
+    );
+    assert!(
+        table
+            .create_index(name_index_creation(Some(col("status"))))
+            .await
+            .is_err()
+    );
+    assert!(table.indexes.is_empty());
+
+    // columns of the predicate can not be dropped
+    table
+        .create_index(name_index_creation(Some(active())))
+        .await?;
+    assert!(table.drop_column("status", false).await.is_err());
+
+    Ok(())
+}
@@ -0,0 +1,300 @@
+use arrow::array::{Int32Array, Int64Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use indexlake::catalog::Scalar;
+use indexlake::expr::{col, lit};
+use indexlake::table::{
+    DateUnit, PartitionInfo, PartitionTransform, Table, TableConfig, TableCreation, TableScan,
+};
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::{counted_storage, create_namespace_if_not_exists};
+use indexlake_integration_tests::utils::table_scan;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger,
+};
+use std::sync::Arc;
+
+/// Inserts six rows, two per region, which are dumped together into per-partition files.
+async fn prepare_partitioned_table(
+    client: &LakeClient,
+    table_name: &str,
+    partition_by: Vec<String>,
+    partition_transform: PartitionTransform,
+) -> Result<Table, Box<dyn std::error::Error>> {
+    create_namespace_if_not_exists(client, "test_namespace").await?;
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("region", DataType::Utf8, false),
+        Field::new("age", DataType::Int32, false),
+    ]));
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: schema.clone(),
+            config: TableConfig {
+                inline_row_count_limit: 6,
+                partition_by,
+                partition_transform,
+                ..Default::default()
+            },
+        })
+        .await?;
+    let table = client.load_table("test_namespace", table_name).await?;
+
+    table
+        .insert(&RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec!["eu", "us", "ap", "eu", "us", "ap"])),
+                Arc::new(Int32Array::from(vec![20, 21, 22, 23, 24, 25])),
+            ],
+        )?)
+        .await?;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+    Ok(table)
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn identity_partition_pruning(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage.clone());
+    let table = prepare_partitioned_table(
+        &client,
+        "identity_partition_pruning",
+        vec!["region".to_string()],
+        PartitionTransform::Identity,
+    )
+    .await?;
+
+    // one data file per region
+    storage.reset_read_stats();
+    table_scan(&table, TableScan::default()).await?;
+    assert_eq!(storage.read_stats().unwrap().opened_paths.len(), 3);
+
+    storage.reset_read_stats();
+    let scan = TableScan::default().with_filters(vec![col("region").eq(lit("eu".to_string()))]);
+    assert_eq!(
+        table_scan(&table, scan).await?,
+        r#"+-------------------+--------+-----+
+| _indexlake_row_id | region | age |
++-------------------+--------+-----+
+| 1                 | eu     | 20  |
+| 4                 | eu     | 23  |
++-------------------+--------+-----+"#,
+    );
+    assert_eq!(storage.read_stats().unwrap().opened_paths.len(), 1);
+
+    // filters on other columns only prune by the column stats, no eu row is older than 23
+    storage.reset_read_stats();
+    let scan = TableScan::default().with_filters(vec![col("age").gt(lit(23))]);
+    table_scan(&table, scan).await?;
+    assert_eq!(storage.read_stats().unwrap().opened_paths.len(), 2);
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn hash_partition_pruning(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage.clone());
+    let table = prepare_partitioned_table(
+        &client,
+        "hash_partition_pruning",
+        vec!["age".to_string()],
+        PartitionTransform::Hash { buckets: 4 },
+    )
+    .await?;
+
+    storage.reset_read_stats();
+    table_scan(&table, TableScan::default()).await?;
+    let file_count = storage.read_stats().unwrap().opened_paths.len();
+    assert!(file_count > 1);
+
+    storage.reset_read_stats();
+    let scan = TableScan::default().with_filters(vec![col("age").eq(lit(22))]);
+    assert_eq!(
+        table_scan(&table, scan).await?,
+        r#"+-------------------+--------+-----+
+| _indexlake_row_id | region | age |
++-------------------+--------+-----+
+| 3                 | ap     | 22  |
++-------------------+--------+-----+"#,
+    );
+    assert_eq!(storage.read_stats().unwrap().opened_paths.len(), 1);
+
+    // only equality filters select a bucket
+    storage.reset_read_stats();
+    let scan = TableScan::default().with_filters(vec![col("age").gt(lit(19))]);
+    table_scan(&table, scan).await?;
+    assert_eq!(storage.read_stats().unwrap().opened_paths.len(), file_count);
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn date_partition_pruning(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    // 2024-02-28, 2024-02-29 and 2024-03-01 at midnight UTC
+    let (day1, day2, day3) = (1709078400000i64, 1709164800000i64, 1709251200000i64);
+
+    let client = LakeClient::new(catalog, storage.clone());
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("event", DataType::Utf8, false),
+        Field::new("ts", DataType::Int64, true),
+    ]));
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "date_partition_pruning".to_string(),
+            schema: schema.clone(),
+            config: TableConfig {
+                inline_row_count_limit: 6,
+                partition_by: vec!["ts".to_string()],
+                partition_transform: PartitionTransform::DateTrunc {
+                    unit: DateUnit::Day,
+                },
+                ..Default::default()
+            },
+        })
+        .await?;
+    let table = client
+        .load_table("test_namespace", "date_partition_pruning")
+        .await?;
+
+    // one batch spanning three days and rows without timestamp
+    table
+        .insert(&RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec![
+                    "click", "view", "click", "view", "click", "view",
+                ])),
+                Arc::new(Int64Array::from(vec![
+                    Some(day1 + 36000000),
+                    Some(day2 - 60000),
+                    Some(day2),
+                    Some(day2 + 45000000),
+                    Some(day3 + 28800000),
+                    None,
+                ])),
+            ],
+        )?)
+        .await?;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+
+    // one data file per day and one for null timestamps
+    storage.reset_read_stats();
+    table_scan(&table, TableScan::default()).await?;
+    assert_eq!(storage.read_stats().unwrap().opened_paths.len(), 4);
+
+    storage.reset_read_stats();
+    let scan = TableScan::default().with_filters(vec![
+        col("ts").gt_eq(lit(day2)).and(col("ts").lt(lit(day3))),
+    ]);
+    assert_eq!(
+        table_scan(&table, scan).await?,
+        r#"+-------------------+-------+---------------+
+| _indexlake_row_id | event | ts            |
++-------------------+-------+---------------+
+| 3                 | click | 1709164800000 |
+| 4                 | view  | 1709209800000 |
++-------------------+-------+---------------+"#,
+    );
+    assert_eq!(storage.read_stats().unwrap().opened_paths.len(), 1);
+
+    // filters on other columns are applied within the partitions left
+    storage.reset_read_stats();
+    let scan = TableScan::default().with_filters(vec![
+        col("ts").lt(lit(day2)),
+        col("event").eq(lit("view".to_string())),
+    ]);
+    assert_eq!(
+        table_scan(&table, scan).await?,
+        r#"+-------------------+-------+---------------+
+| _indexlake_row_id | event | ts            |
++-------------------+-------+---------------+
+| 2                 | view  | 1709164740000 |
++-------------------+-------+---------------+"#,
+    );
+    assert_eq!(storage.read_stats().unwrap().opened_paths.len(), 1);
+
+    storage.reset_read_stats();
+    let scan = TableScan::default().with_filters(vec![col("ts").is_null()]);
+    assert_eq!(
+        table_scan(&table, scan).await?,
+        r#"+-------------------+-------+----+
+| _indexlake_row_id | event | ts |
++-------------------+-------+----+
+| 6                 | view  |    |
++-------------------+-------+----+"#,
+    );
+    assert_eq!(storage.read_stats().unwrap().opened_paths.len(), 1);
+
+    // inline rows count towards their partition, deleted rows do not
+    table
+        .insert(&RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec!["view"])),
+                Arc::new(Int64Array::from(vec![day3 + 72000000])),
+            ],
+        )?)
+        .await?;
+    table.delete(&col("ts").eq(lit(day2 - 60000))).await?;
+    let partition = |start: Option<i64>, data_file_count, row_count| PartitionInfo {
+        values: vec![Scalar::Int64(start)],
+        data_file_count,
+        row_count,
+    };
+    assert_eq!(
+        table.partitions().await?,
+        vec![
+            partition(None, 1, 1),
+            partition(Some(day1), 1, 1),
+            partition(Some(day2), 1, 2),
+            partition(Some(day3), 1, 2),
+        ]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use arrow::array::{Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use indexlake::expr::{col, lit};
+use indexlake::table::{ListOptions, TableCreation};
+use indexlake::{ILError, LakeClient, catalog::Catalog, storage::Storage};
+use indexlake_catalog_sqlite::SqliteCatalog;
+use indexlake_integration_tests::data::prepare_testing_table;
+use indexlake_integration_tests::utils::full_table_scan;
+use indexlake_integration_tests::{init_env_logger, setup_sqlite_db};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn list_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            files.extend(list_files(&path));
+        } else {
+            files.push(path);
+        }
+    }
+    files.sort();
+    files
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn read_only_sqlite_catalog() -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let db_path = setup_sqlite_db();
+    let storage_root = format!(
+        "{}/tmp/read_only/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        uuid::Uuid::new_v4()
+    );
+    let storage = Arc::new(Storage::new_fs(storage_root.clone()));
+
+    let client = LakeClient::new(
+        Arc::new(SqliteCatalog::try_new(db_path.clone())?),
+        storage.clone(),
+    );
+    prepare_testing_table(&client, "read_only_sqlite_catalog").await?;
+    let files = list_files(Path::new(&storage_root));
+    assert!(!files.is_empty());
+
+    let read_only_catalog = Arc::new(SqliteCatalog::try_new_read_only(db_path)?);
+    assert!(read_only_catalog.read_only());
+    let client = LakeClient::new(read_only_catalog, storage);
+
+    let table = client
+        .load_table("test_namespace", "read_only_sqlite_catalog")
+        .await?;
+    let table_str = full_table_scan(&table).await?;
+    assert_eq!(
+        table_str,
+        r#"+-------------------+---------+-----+
+| _indexlake_row_id | name    | age |
++-------------------+---------+-----+
+| 1                 | Alice   | 20  |
+| 2                 | Bob     | 21  |
+| 3                 | Charlie | 22  |
+| 4                 | David   | 23  |
++-------------------+---------+-----+"#,
+    );
+    let page = client
+        .list_tables("test_namespace", ListOptions::default())
+        .await?;
+    assert_eq!(page.tables.len(), 1);
+
+    // Enough rows to trigger a dump if the insert went through
+    let record_batch = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("name", DataType::Utf8, false),
+            Field::new("age", DataType::Int32, false),
+        ])),
+        vec![
+            Arc::new(StringArray::from(vec!["Eve", "Frank", "Grace", "Heidi"])),
+            Arc::new(Int32Array::from(vec![24, 25, 26, 27])),
+        ],
+    )?;
+    let result = table.insert(&record_batch).await;
+    assert!(matches!(result, Err(ILError::CatalogReadOnly(_))));
+
+    let result = table.delete(&col("age").eq(lit(20))).await;
+    assert!(matches!(result, Err(ILError::CatalogReadOnly(_))));
+
+    let result = client
+        .create_namespace("read_only_namespace", HashMap::new())
+        .await;
+    assert!(matches!(result, Err(ILError::CatalogReadOnly(_))));
+
+    let result = client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "read_only_table".to_string(),
+            schema: record_batch.schema(),
+            config: Default::default(),
+        })
+        .await;
+    assert!(matches!(result, Err(ILError::CatalogReadOnly(_))));
+
+    // a dry run only lists what a vacuum would delete
+    let report = table.vacuum_dry_run(Duration::ZERO).await?;
+    assert!(report.deleted_files.is_empty());
+    client
+        .vacuum_namespace_dry_run("test_namespace", Duration::ZERO)
+        .await?;
+    let result = table.vacuum(Duration::ZERO).await;
+    assert!(matches!(result, Err(ILError::CatalogReadOnly(_))));
+
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(list_files(Path::new(&storage_root)), files);
+    assert_eq!(full_table_scan(&table).await?, table_str);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn read_only_sqlite_rejects_writes() -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let catalog = SqliteCatalog::try_new_read_only(setup_sqlite_db())?;
+    let mut transaction = catalog.transaction().await?;
+    let result = transaction.execute("DELETE FROM indexlake_namespace").await;
+    assert!(matches!(result, Err(ILError::CatalogReadOnly(_))));
+
+    Ok(())
+}
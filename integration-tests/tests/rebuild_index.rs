@@ -0,0 +1,132 @@
+use arrow::array::{Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use indexlake::expr::{col, lit};
+use indexlake::index::Index;
+use indexlake::table::{IndexCreation, Table, TableConfig, TableCreation, TableScan};
+use indexlake::{ILResult, LakeClient, catalog::Catalog, storage::Storage};
+use indexlake_index_hash::{HashIndex, HashIndexParams};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::{
+    counted_storage, create_namespace_if_not_exists, insert_files,
+};
+use indexlake_integration_tests::utils::table_scan;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger,
+};
+use std::sync::Arc;
+
+fn table_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("name", DataType::Utf8, false),
+    ]))
+}
+
+/// Rows of the ids with users named after them.
+fn file_batch(ids: Vec<i32>) -> ILResult<RecordBatch> {
+    let names = ids.iter().map(|id| format!("user{id}")).collect::<Vec<_>>();
+    Ok(RecordBatch::try_new(
+        table_schema(),
+        vec![
+            Arc::new(Int32Array::from(ids)),
+            Arc::new(StringArray::from(names)),
+        ],
+    )?)
+}
+
+async fn index_files(
+    storage: &Storage,
+    table: &Table,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut index_files = storage
+        .list_files(&table.table_dir())
+        .await?
+        .into_iter()
+        .map(|file| file.relative_path)
+        .filter(|path| path.ends_with(".index"))
+        .collect::<Vec<_>>();
+    index_files.sort();
+    Ok(index_files)
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn rebuild_desynced_index(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage.clone());
+    client.register_index(Arc::new(HashIndex))?;
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "rebuild_desynced_index".to_string(),
+            schema: table_schema(),
+            config: TableConfig {
+                inline_row_count_limit: 10,
+                ..Default::default()
+            },
+        })
+        .await?;
+    let mut table = client
+        .load_table("test_namespace", "rebuild_desynced_index")
+        .await?;
+    table
+        .create_index(IndexCreation {
+            name: "name_index".to_string(),
+            kind: HashIndex.kind().to_string(),
+            key_columns: vec!["name".to_string()],
+            include_columns: vec![],
+            params: Arc::new(HashIndexParams::default()),
+            where_predicate: None,
+        })
+        .await?;
+    insert_files(&table, 2, file_batch).await?;
+
+    let scan = TableScan::default().with_filters(vec![col("name").eq(lit("user14".to_string()))]);
+    let expected = r#"+-------------------+----+--------+
+| _indexlake_row_id | id | name   |
++-------------------+----+--------+
+| 15                | 14 | user14 |
++-------------------+----+--------+"#;
+    assert_eq!(table_scan(&table, scan.clone()).await?, expected);
+
+    // Overwrite the index file of the second data file with the one of the first
+    let old_index_files = index_files(&storage, &table).await?;
+    assert_eq!(old_index_files.len(), 2);
+    storage
+        .copy_file(&old_index_files[0], &old_index_files[1])
+        .await?;
+    assert_ne!(
+        table_scan(&table, scan.clone()).await.ok().as_deref(),
+        Some(expected)
+    );
+
+    table.rebuild_index("name_index").await?;
+    assert_eq!(table_scan(&table, scan.clone()).await?, expected);
+
+    // The catalog refers to new index files, the replaced ones are left for vacuum
+    let index_files = index_files(&storage, &table).await?;
+    assert_eq!(index_files.len(), 4);
+    storage.reset_read_stats();
+    table_scan(&table, scan).await?;
+    let opened_paths = storage.read_stats().unwrap().opened_paths;
+    for path in old_index_files {
+        assert!(!opened_paths.contains(&path));
+    }
+
+    assert!(table.rebuild_index("missing_index").await.is_err());
+
+    Ok(())
+}
@@ -0,0 +1,189 @@
+use arrow::array::{Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use indexlake::expr::{col, lit};
+use indexlake::index::Index;
+use indexlake::table::{IndexCreation, Table, TableConfig, TableCreation, TableScan};
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+use indexlake_index_hash::{HashIndex, HashIndexParams};
+use indexlake_integration_tests::data::create_namespace_if_not_exists;
+use indexlake_integration_tests::utils::{full_table_scan, table_scan, table_scan_as_of};
+use indexlake_integration_tests::{
+    catalog_memory, catalog_postgres, catalog_sqlite, init_env_logger, storage_fs, storage_memory,
+    storage_s3,
+};
+use std::sync::Arc;
+
+fn table_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("name", DataType::Utf8, false),
+        Field::new("age", DataType::Int32, false),
+    ]))
+}
+
+async fn create_table(
+    client: &LakeClient,
+    table_name: &str,
+    storage_prefix: Option<String>,
+) -> Result<Table, Box<dyn std::error::Error>> {
+    create_namespace_if_not_exists(client, "relocate_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "relocate_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: table_schema(),
+            config: TableConfig {
+                inline_row_count_limit: 2,
+                storage_prefix,
+                ..Default::default()
+            },
+        })
+        .await?;
+    Ok(client.load_table("relocate_namespace", table_name).await?)
+}
+
+async fn insert_and_dump(
+    table: &Table,
+    names: Vec<&str>,
+    ages: Vec<i32>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    table
+        .insert(&RecordBatch::try_new(
+            table_schema(),
+            vec![
+                Arc::new(StringArray::from(names)),
+                Arc::new(Int32Array::from(ages)),
+            ],
+        )?)
+        .await?;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+    Ok(())
+}
+
+async fn list_paths(
+    storage: &Storage,
+    prefix: &str,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut paths = storage
+        .list_files(prefix)
+        .await?
+        .into_iter()
+        .map(|file| file.relative_path)
+        .collect::<Vec<_>>();
+    paths.sort();
+    Ok(paths)
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_memory() }, storage_memory())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[tokio::test(flavor = "multi_thread")]
+async fn relocate_table(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage.clone());
+    client.register_index(Arc::new(HashIndex))?;
+    let team_prefix = format!("teams/{}/events", uuid::Uuid::new_v4());
+    let mut table = create_table(&client, "relocate_table", Some(team_prefix.clone())).await?;
+    assert_eq!(table.table_dir(), team_prefix);
+    table
+        .create_index(IndexCreation {
+            name: "name_index".to_string(),
+            kind: HashIndex.kind().to_string(),
+            key_columns: vec!["name".to_string()],
+            include_columns: vec![],
+            params: Arc::new(HashIndexParams::default()),
+            where_predicate: None,
+        })
+        .await?;
+
+    insert_and_dump(&table, vec!["Alice", "Bob"], vec![20, 21]).await?;
+    let before_delete = table.snapshots().await?.last().unwrap().snapshot_id;
+    table.delete(&col("age").eq(lit(20))).await?;
+    insert_and_dump(&table, vec!["Charlie", "David"], vec![22, 23]).await?;
+    table.compact(Default::default()).await?;
+
+    // data files and index files land under the prefix
+    let old_paths = list_paths(&storage, &team_prefix).await?;
+    assert!(old_paths.iter().any(|path| path.ends_with(".parquet")));
+    assert!(old_paths.iter().any(|path| path.ends_with(".index")));
+
+    let table_str = full_table_scan(&table).await?;
+    let lookup = TableScan::default().with_filters(vec![col("name").eq(lit("Bob".to_string()))]);
+    let lookup_str = table_scan(&table, lookup.clone()).await?;
+    let as_of_str = table_scan_as_of(&table, before_delete, TableScan::default()).await?;
+    assert!(as_of_str.contains("Alice"));
+
+    let archive_prefix = format!("archive/{}/events", uuid::Uuid::new_v4());
+    table.relocate(&archive_prefix).await?;
+    assert_eq!(table.table_dir(), archive_prefix);
+
+    // referenced files are moved, files only left for vacuum stay behind
+    let file_name = |path: &String| path.rsplit('/').next().unwrap().to_string();
+    let new_paths = list_paths(&storage, &archive_prefix).await?;
+    let left_paths = list_paths(&storage, &team_prefix).await?;
+    assert!(!new_paths.is_empty());
+    assert_eq!(new_paths.len() + left_paths.len(), old_paths.len());
+    let new_names = new_paths.iter().map(file_name).collect::<Vec<_>>();
+    assert!(
+        left_paths
+            .iter()
+            .all(|path| !new_names.contains(&file_name(path)))
+    );
+
+    assert_eq!(full_table_scan(&table).await?, table_str);
+    assert_eq!(table_scan(&table, lookup).await?, lookup_str);
+    assert_eq!(
+        table_scan_as_of(&table, before_delete, TableScan::default()).await?,
+        as_of_str
+    );
+
+    // the prefix is kept in the catalog, reloaded tables write under it
+    let table = client
+        .load_table("relocate_namespace", "relocate_table")
+        .await?;
+    assert_eq!(table.table_dir(), archive_prefix);
+    insert_and_dump(&table, vec!["Eve", "Frank"], vec![24, 25]).await?;
+    assert!(list_paths(&storage, &archive_prefix).await?.len() > new_paths.len());
+    assert_eq!(list_paths(&storage, &team_prefix).await?, left_paths);
+    assert_eq!(table.storage_stats().await?.data_file_count, 2);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn relocate_validates_prefix() -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog_sqlite(), storage_memory());
+    for prefix in ["", "/abs", "trailing/", "a//b", "a/../b", "a:b", "it's"] {
+        assert!(
+            create_table(
+                &client,
+                "relocate_validates_prefix",
+                Some(prefix.to_string())
+            )
+            .await
+            .is_err()
+        );
+    }
+
+    let mut table = create_table(&client, "relocate_validates_prefix", None).await?;
+    assert_eq!(
+        table.table_dir(),
+        format!("{}/{}", table.namespace_id, table.table_id)
+    );
+    assert!(table.relocate("../escape").await.is_err());
+    assert_eq!(
+        table.table_dir(),
+        format!("{}/{}", table.namespace_id, table.table_id)
+    );
+
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::{create_namespace_if_not_exists, prepare_testing_table};
+use indexlake_integration_tests::utils::full_table_scan;
+use indexlake_integration_tests::{
+    catalog_mariadb, catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite,
+    init_env_logger, storage_fs, storage_s3,
+};
+use std::sync::Arc;
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn rename_table(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let namespace_name = "test_namespace";
+    let table = prepare_testing_table(&client, "rename_table").await?;
+    let table_str = full_table_scan(&table).await?;
+
+    client
+        .rename_table(namespace_name, "rename_table", "renamed_table")
+        .await?;
+    assert!(
+        client
+            .load_table(namespace_name, "rename_table")
+            .await
+            .is_err()
+    );
+
+    let renamed_table = client.load_table(namespace_name, "renamed_table").await?;
+    assert_eq!(renamed_table.table_id, table.table_id);
+    assert_eq!(full_table_scan(&renamed_table).await?, table_str);
+    // Handles loaded before the rename keep working by id
+    assert_eq!(full_table_scan(&table).await?, table_str);
+
+    prepare_testing_table(&client, "other_table").await?;
+    let result = client
+        .rename_table(namespace_name, "renamed_table", "other_table")
+        .await;
+    assert!(result.unwrap_err().to_string().contains("already exists"));
+    // the failed rename changed neither table
+    let other_table = client.load_table(namespace_name, "other_table").await?;
+    assert_ne!(other_table.table_id, table.table_id);
+    assert_eq!(
+        client
+            .load_table(namespace_name, "renamed_table")
+            .await?
+            .table_id,
+        table.table_id
+    );
+
+    let result = client
+        .rename_table(namespace_name, "not_exists", "another_table")
+        .await;
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn rename_namespace(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let table = prepare_testing_table(&client, "rename_namespace").await?;
+    let table_str = full_table_scan(&table).await?;
+    let namespace_id = client.get_namespace_id("test_namespace").await?;
+
+    client
+        .rename_namespace("test_namespace", "renamed_namespace")
+        .await?;
+    assert_eq!(client.get_namespace_id("test_namespace").await?, None);
+    assert_eq!(
+        client.get_namespace_id("renamed_namespace").await?,
+        namespace_id
+    );
+
+    let renamed_table = client
+        .load_table("renamed_namespace", "rename_namespace")
+        .await?;
+    assert_eq!(full_table_scan(&renamed_table).await?, table_str);
+    assert_eq!(full_table_scan(&table).await?, table_str);
+
+    create_namespace_if_not_exists(&client, "other_namespace").await?;
+    let result = client
+        .rename_namespace("renamed_namespace", "other_namespace")
+        .await;
+    assert!(result.unwrap_err().to_string().contains("already exists"));
+
+    Ok(())
+}
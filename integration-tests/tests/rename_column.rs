@@ -0,0 +1,128 @@
+use arrow::array::{Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use indexlake::expr::{col, lit};
+use indexlake::table::TableScan;
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::prepare_testing_table;
+use indexlake_integration_tests::utils::{full_table_scan, table_scan};
+use indexlake_integration_tests::{
+    catalog_mariadb, catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite,
+    init_env_logger, storage_fs, storage_s3,
+};
+use std::sync::Arc;
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn rename_column(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    // Rows 1-3 are in a data file, row 4 is inline
+    let mut table = prepare_testing_table(&client, "rename_column").await?;
+
+    table.rename_column("name", "first_name").await?;
+    let batch = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("first_name", DataType::Utf8, false),
+            Field::new("age", DataType::Int32, false),
+        ])),
+        vec![
+            Arc::new(StringArray::from(vec!["Eve", "Frank"])),
+            Arc::new(Int32Array::from(vec![24, 25])),
+        ],
+    )?;
+    table.insert(&batch).await?;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+
+    // Files written under both names hold the column by its field id
+    table.rename_column("first_name", "full_name").await?;
+    table.rename_column("age", "name").await?;
+    assert_eq!(table.schema_version, 3);
+    let batch = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("full_name", DataType::Utf8, false),
+            Field::new("name", DataType::Int32, false),
+        ])),
+        vec![
+            Arc::new(StringArray::from(vec!["Grace"])),
+            Arc::new(Int32Array::from(vec![26])),
+        ],
+    )?;
+    table.insert(&batch).await?;
+
+    let expected = r#"+-------------------+-----------+------+
+| _indexlake_row_id | full_name | name |
++-------------------+-----------+------+
+| 1                 | Alice     | 20   |
+| 2                 | Bob       | 21   |
+| 3                 | Charlie   | 22   |
+| 4                 | David     | 23   |
+| 5                 | Eve       | 24   |
+| 6                 | Frank     | 25   |
+| 7                 | Grace     | 26   |
++-------------------+-----------+------+"#;
+    assert_eq!(full_table_scan(&table).await?, expected);
+
+    let loaded_table = client.load_table("test_namespace", "rename_column").await?;
+    assert_eq!(loaded_table.schema, table.schema);
+    assert_eq!(loaded_table.schema_version, 3);
+    assert_eq!(full_table_scan(&loaded_table).await?, expected);
+
+    // Column stats of data files follow the renames
+    let scan = TableScan::default().with_filters(vec![col("name").gt(lit(23))]);
+    assert_eq!(
+        table_scan(&table, scan).await?,
+        r#"+-------------------+-----------+------+
+| _indexlake_row_id | full_name | name |
++-------------------+-----------+------+
+| 5                 | Eve       | 24   |
+| 6                 | Frank     | 25   |
+| 7                 | Grace     | 26   |
++-------------------+-----------+------+"#
+    );
+    let scan = TableScan::default().with_filters(vec![col("full_name").eq(lit("Bob".to_string()))]);
+    assert_eq!(
+        table_scan(&table, scan).await?,
+        r#"+-------------------+-----------+------+
+| _indexlake_row_id | full_name | name |
++-------------------+-----------+------+
+| 2                 | Bob       | 21   |
++-------------------+-----------+------+"#
+    );
+
+    // Inserts with the old names are rejected
+    let old_batch = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("first_name", DataType::Utf8, false),
+            Field::new("age", DataType::Int32, false),
+        ])),
+        vec![
+            Arc::new(StringArray::from(vec!["Heidi"])),
+            Arc::new(Int32Array::from(vec![27])),
+        ],
+    )?;
+    assert!(table.insert(&old_batch).await.is_err());
+
+    let result = table.rename_column("full_name", "name").await;
+    assert!(result.unwrap_err().to_string().contains("already exists"));
+    let result = table.rename_column("age", "years").await;
+    assert!(result.unwrap_err().to_string().contains("not found"));
+    let result = table.rename_column("_indexlake_row_id", "id").await;
+    assert!(result.unwrap_err().to_string().contains("internal"));
+
+    Ok(())
+}
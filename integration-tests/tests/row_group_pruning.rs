@@ -0,0 +1,124 @@
+use arrow::array::{Int64Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use futures::TryStreamExt;
+use indexlake::expr::{Expr, col, lit};
+use indexlake::table::{Table, TableConfig, TableCreation, TableScan};
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::{counted_storage, create_namespace_if_not_exists};
+use indexlake_integration_tests::utils::sort_record_batches;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger,
+};
+use std::sync::Arc;
+
+fn row_groups_read(storage: &Storage) -> u64 {
+    storage.read_stats().unwrap().row_groups_read
+}
+
+fn table_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, true),
+    ]))
+}
+
+async fn scan_ids(table: &Table, filter: Expr) -> Result<Vec<i64>, Box<dyn std::error::Error>> {
+    let scan = TableScan::default()
+        .with_columns(Some(vec!["id".to_string()]))
+        .with_filters(vec![filter]);
+    let batches = table.scan(scan).await?.try_collect::<Vec<_>>().await?;
+    let batch = sort_record_batches(&batches, "id")?;
+    Ok(batch
+        .column(0)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap()
+        .values()
+        .to_vec())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn row_group_pruning(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage.clone());
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "row_group_pruning".to_string(),
+            schema: table_schema(),
+            config: TableConfig {
+                parquet_row_group_size: 10,
+                ..Default::default()
+            },
+        })
+        .await?;
+    let table = client
+        .load_table("test_namespace", "row_group_pruning")
+        .await?;
+
+    // one data file of 4 row groups sorted by id, names only in the last row group
+    let names = (0..40)
+        .map(|id| (id >= 30).then(|| format!("name{id}")))
+        .collect::<Vec<_>>();
+    table
+        .insert(&RecordBatch::try_new(
+            table_schema(),
+            vec![
+                Arc::new(Int64Array::from_iter_values(0..40)),
+                Arc::new(StringArray::from(names)),
+            ],
+        )?)
+        .await?;
+    table.flush().await?;
+    assert_eq!(table.storage_stats().await?.data_file_count, 1);
+
+    // a range within one row group decodes only that row group
+    storage.reset_read_stats();
+    let filter = col("id").gt_eq(lit(12i64)).and(col("id").lt(lit(15i64)));
+    assert_eq!(scan_ids(&table, filter).await?, vec![12, 13, 14]);
+    assert_eq!(row_groups_read(&storage), 1);
+
+    storage.reset_read_stats();
+    let filter = col("id").eq(lit(5i64)).or(col("id").eq(lit(35i64)));
+    assert_eq!(scan_ids(&table, filter).await?, vec![5, 35]);
+    assert_eq!(row_groups_read(&storage), 2);
+
+    // null counts prune row groups without non-null values
+    storage.reset_read_stats();
+    let ids = scan_ids(&table, col("name").is_not_null()).await?;
+    assert_eq!(ids, (30..40).collect::<Vec<_>>());
+    assert_eq!(row_groups_read(&storage), 1);
+
+    // filters the statistics can not decide decode every row group
+    storage.reset_read_stats();
+    let filter = col("id").gt_eq(lit(0i64));
+    assert_eq!(scan_ids(&table, filter).await?.len(), 40);
+    assert_eq!(row_groups_read(&storage), 4);
+
+    // deleted rows are not read either way, pruning keeps the right row ids
+    table.delete(&col("id").eq(lit(13i64))).await?;
+    storage.reset_read_stats();
+    let filter = col("id").gt_eq(lit(12i64)).and(col("id").lt(lit(22i64)));
+    assert_eq!(
+        scan_ids(&table, filter).await?,
+        vec![12, 14, 15, 16, 17, 18, 19, 20, 21]
+    );
+    assert_eq!(row_groups_read(&storage), 2);
+
+    Ok(())
+}
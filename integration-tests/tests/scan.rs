@@ -1,16 +1,29 @@
+use arrow::array::{ArrayRef, AsArray, Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::util::pretty::pretty_format_batches;
+use futures::TryStreamExt;
 use indexlake::expr::{col, lit};
-use indexlake::table::TableScan;
+use indexlake::table::{TableConfig, TableCreation, TableScan};
 use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
-use indexlake_integration_tests::data::prepare_testing_table;
-use indexlake_integration_tests::utils::table_scan;
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::{
+    counted_storage, create_namespace_if_not_exists, prepare_testing_table,
+};
+use indexlake_integration_tests::utils::{sort_record_batches, table_scan};
 use indexlake_integration_tests::{
-    catalog_postgres, catalog_sqlite, init_env_logger, storage_fs, storage_s3,
+    catalog_mariadb, catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite,
+    init_env_logger, storage_fs, storage_s3,
 };
 use std::sync::Arc;
 
 #[rstest::rstest]
 #[case(async { catalog_sqlite() }, storage_fs())]
 #[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
 #[tokio::test(flavor = "multi_thread")]
 async fn scan_with_projection(
     #[future(awt)]
@@ -44,6 +57,10 @@ async fn scan_with_projection(
 #[rstest::rstest]
 #[case(async { catalog_sqlite() }, storage_fs())]
 #[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
 #[tokio::test(flavor = "multi_thread")]
 async fn scan_with_filters(
     #[future(awt)]
@@ -78,6 +95,10 @@ async fn scan_with_filters(
 #[rstest::rstest]
 #[case(async { catalog_sqlite() }, storage_fs())]
 #[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
 #[tokio::test(flavor = "multi_thread")]
 async fn scan_with_limit(
     #[future(awt)]
@@ -120,3 +141,272 @@ async fn scan_with_limit(
 
     Ok(())
 }
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn scan_with_columns(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let table = prepare_testing_table(&client, "scan_with_columns").await?;
+
+    // batches of inline rows and data files both hold the columns in the requested order
+    let scan = TableScan::default().with_columns(Some(vec![
+        "age".to_string(),
+        "name".to_string(),
+        "_indexlake_row_id".to_string(),
+    ]));
+    assert_eq!(
+        table_scan(&table, scan).await?,
+        r#"+-----+---------+-------------------+
+| age | name    | _indexlake_row_id |
++-----+---------+-------------------+
+| 20  | Alice   | 1                 |
+| 21  | Bob     | 2                 |
+| 22  | Charlie | 3                 |
+| 23  | David   | 4                 |
++-----+---------+-------------------+"#,
+    );
+
+    // filters may read columns left out of the scanned ones
+    let scan = TableScan::default()
+        .with_columns(Some(vec![
+            "_indexlake_row_id".to_string(),
+            "name".to_string(),
+        ]))
+        .with_filters(vec![col("age").gt(lit(20))]);
+    let expected = r#"+-------------------+---------+
+| _indexlake_row_id | name    |
++-------------------+---------+
+| 2                 | Bob     |
+| 3                 | Charlie |
+| 4                 | David   |
++-------------------+---------+"#;
+    assert_eq!(table_scan(&table, scan.clone()).await?, expected);
+    let batches = table
+        .scan_stream(scan)
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    let sorted_batch = sort_record_batches(&batches, "_indexlake_row_id")?;
+    assert_eq!(
+        pretty_format_batches(&[sorted_batch])?.to_string(),
+        expected
+    );
+
+    let scan = TableScan::default().with_columns(Some(vec!["height".to_string()]));
+    let err = table.scan(scan).await.err().unwrap();
+    assert!(err.to_string().contains("Scan column height not found"));
+
+    let scan = TableScan::default()
+        .with_projection(Some(vec![0]))
+        .with_columns(Some(vec!["age".to_string()]));
+    assert!(table.scan(scan).await.is_err());
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn scan_columns_of_wide_table(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage.clone());
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    let column_count = 20;
+    let row_count = 200;
+    let schema = Arc::new(Schema::new(
+        (0..column_count)
+            .map(|i| Field::new(format!("c{i}"), DataType::Utf8, false))
+            .collect::<Vec<_>>(),
+    ));
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "scan_columns_of_wide_table".to_string(),
+            schema: schema.clone(),
+            config: TableConfig {
+                inline_row_count_limit: row_count,
+                ..Default::default()
+            },
+        })
+        .await?;
+    let table = client
+        .load_table("test_namespace", "scan_columns_of_wide_table")
+        .await?;
+    let columns = (0..column_count)
+        .map(|i| {
+            let values = (0..row_count)
+                .map(|row| format!("column {i} row {row} {}", "x".repeat(100)))
+                .collect::<Vec<_>>();
+            Arc::new(StringArray::from(values)) as ArrayRef
+        })
+        .collect::<Vec<_>>();
+    table
+        .insert(&RecordBatch::try_new(schema, columns)?)
+        .await?;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+
+    storage.reset_read_stats();
+    let batches = table
+        .scan(TableScan::default())
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    assert_eq!(
+        batches.iter().map(|b| b.num_rows()).sum::<usize>(),
+        row_count
+    );
+    let all_bytes = storage.read_stats().unwrap().bytes_read;
+
+    storage.reset_read_stats();
+    let scan = TableScan::default().with_columns(Some(vec!["c7".to_string(), "c2".to_string()]));
+    let batches = table.scan(scan).await?.try_collect::<Vec<_>>().await?;
+    assert_eq!(
+        batches.iter().map(|b| b.num_rows()).sum::<usize>(),
+        row_count
+    );
+    for batch in batches.iter() {
+        let names = batch
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["c7", "c2"]);
+        assert!(
+            batch
+                .column(0)
+                .as_string::<i32>()
+                .iter()
+                .all(|value| value.unwrap().starts_with("column 7 "))
+        );
+    }
+    let projected_bytes = storage.read_stats().unwrap().bytes_read;
+    assert!(
+        projected_bytes * 4 < all_bytes,
+        "{projected_bytes} bytes read for 2 columns, {all_bytes} bytes for all"
+    );
+
+    // unknown columns fail before any file is read
+    storage.reset_read_stats();
+    let scan = TableScan::default().with_columns(Some(vec!["c99".to_string()]));
+    assert!(table.scan(scan).await.is_err());
+    assert_eq!(storage.read_stats().unwrap().files_opened, 0);
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn scan_with_limit_stops_reading_files(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage.clone());
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int32, false)]));
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "scan_with_limit_stops_reading_files".to_string(),
+            schema: schema.clone(),
+            config: TableConfig {
+                inline_row_count_limit: 10,
+                ..Default::default()
+            },
+        })
+        .await?;
+    let table = client
+        .load_table("test_namespace", "scan_with_limit_stops_reading_files")
+        .await?;
+    let file_count = 8;
+    for i in 0..file_count {
+        let ids = Int32Array::from((i * 10..i * 10 + 10).collect::<Vec<_>>());
+        table
+            .insert(&RecordBatch::try_new(schema.clone(), vec![Arc::new(ids)])?)
+            .await?;
+        // wait for dump task to finish
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    }
+    let row_count = |batches: &[RecordBatch]| batches.iter().map(|b| b.num_rows()).sum::<usize>();
+    // matches rows of every file, so no file is pruned by its statistics
+    let filter = col("id").gt_eq(lit(0));
+
+    storage.reset_read_stats();
+    let scan = TableScan::default().with_filters(vec![filter.clone()]);
+    let batches = table.scan(scan).await?.try_collect::<Vec<_>>().await?;
+    assert_eq!(row_count(&batches), 80);
+    assert_eq!(
+        storage.read_stats().unwrap().files_opened,
+        file_count as u64
+    );
+
+    storage.reset_read_stats();
+    let scan = TableScan::default()
+        .with_filters(vec![filter.clone()])
+        .with_limit(Some(5));
+    let batches = table.scan(scan).await?.try_collect::<Vec<_>>().await?;
+    assert_eq!(row_count(&batches), 5);
+    assert_eq!(storage.read_stats().unwrap().files_opened, 1);
+
+    // rows filtered out do not use up the limit, no file is pruned either
+    let scan = TableScan::default()
+        .with_filters(vec![col("id").gt(lit(5))])
+        .with_limit(Some(3));
+    let batches = table.scan(scan).await?.try_collect::<Vec<_>>().await?;
+    assert_eq!(row_count(&batches), 3);
+
+    storage.reset_read_stats();
+    let scan = TableScan::default()
+        .with_filters(vec![filter.clone()])
+        .with_limit(Some(15));
+    let batches = table
+        .scan_stream(scan)
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    assert_eq!(row_count(&batches), 15);
+    assert_eq!(storage.read_stats().unwrap().files_opened, 2);
+
+    // reads in flight are dropped once the limit is reached
+    storage.reset_read_stats();
+    let scan = TableScan::default()
+        .with_filters(vec![filter])
+        .with_limit(Some(5))
+        .with_concurrency(Some(2));
+    let batches = table.scan(scan).await?.try_collect::<Vec<_>>().await?;
+    assert_eq!(row_count(&batches), 5);
+    assert!(storage.read_stats().unwrap().files_opened <= 3);
+
+    Ok(())
+}
@@ -0,0 +1,219 @@
+use arrow::array::{AsArray, Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Int32Type, Schema, SchemaRef};
+use futures::TryStreamExt;
+use indexlake::LakeClient;
+use indexlake::storage::Storage;
+use indexlake::table::{Table, TableConfig, TableCreation, TableScan};
+use indexlake_integration_tests::data::create_namespace_if_not_exists;
+use indexlake_integration_tests::{catalog_sqlite, init_env_logger};
+use opendal::raw::*;
+use opendal::{Operator, Result};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const FILE_COUNT: i32 = 12;
+const READ_LATENCY: Duration = Duration::from_millis(20);
+
+/// Delays reads and stats like the round trips of an object store.
+struct LatencyLayer;
+
+impl<A: Access> Layer<A> for LatencyLayer {
+    type LayeredAccess = LatencyAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccess {
+        LatencyAccessor { inner }
+    }
+}
+
+#[derive(Debug)]
+struct LatencyAccessor<A> {
+    inner: A,
+}
+
+impl<A: Access> LayeredAccess for LatencyAccessor<A> {
+    type Inner = A;
+    type Reader = A::Reader;
+    type BlockingReader = A::BlockingReader;
+    type Writer = A::Writer;
+    type BlockingWriter = A::BlockingWriter;
+    type Lister = A::Lister;
+    type BlockingLister = A::BlockingLister;
+    type Deleter = A::Deleter;
+    type BlockingDeleter = A::BlockingDeleter;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        tokio::time::sleep(READ_LATENCY).await;
+        self.inner.read(path, args).await
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.inner.write(path, args).await
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        tokio::time::sleep(READ_LATENCY).await;
+        self.inner.stat(path, args).await
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Lister)> {
+        self.inner.list(path, args).await
+    }
+
+    async fn delete(&self) -> Result<(RpDelete, Self::Deleter)> {
+        self.inner.delete().await
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.inner.blocking_read(path, args)
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.inner.blocking_write(path, args)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingLister)> {
+        self.inner.blocking_list(path, args)
+    }
+
+    fn blocking_delete(&self) -> Result<(RpDelete, Self::BlockingDeleter)> {
+        self.inner.blocking_delete()
+    }
+}
+
+fn table_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("name", DataType::Utf8, false),
+    ]))
+}
+
+/// Inserts ids `0..FILE_COUNT * 10` in batches of ten, each batch is dumped into its own file.
+async fn prepare_table(
+    storage: Arc<Storage>,
+    table_name: &str,
+) -> std::result::Result<Table, Box<dyn std::error::Error>> {
+    let client = LakeClient::new(catalog_sqlite(), storage);
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: table_schema(),
+            config: TableConfig {
+                inline_row_count_limit: 10,
+                ..Default::default()
+            },
+        })
+        .await?;
+    let table = client.load_table("test_namespace", table_name).await?;
+    for i in 0..FILE_COUNT {
+        let ids = (i * 10..i * 10 + 10).collect::<Vec<_>>();
+        let names = ids.iter().map(|id| format!("user{id}")).collect::<Vec<_>>();
+        table
+            .insert(&RecordBatch::try_new(
+                table_schema(),
+                vec![
+                    Arc::new(Int32Array::from(ids)),
+                    Arc::new(StringArray::from(names)),
+                ],
+            )?)
+            .await?;
+        // wait for dump task to finish
+        tokio::time::sleep(Duration::from_secs(2)).await;
+    }
+    Ok(table)
+}
+
+/// Scans the table, returning the sorted ids read and the time taken.
+async fn timed_scan(
+    table: &Table,
+    concurrency: Option<usize>,
+) -> std::result::Result<(Vec<i32>, Duration), Box<dyn std::error::Error>> {
+    let start = Instant::now();
+    let batches = table
+        .scan(TableScan::default().with_concurrency(concurrency))
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    let elapsed = start.elapsed();
+    let mut ids = batches
+        .iter()
+        .flat_map(|batch| {
+            let ids = batch
+                .column_by_name("id")
+                .unwrap()
+                .as_primitive::<Int32Type>();
+            ids.values().to_vec()
+        })
+        .collect::<Vec<_>>();
+    ids.sort();
+    Ok((ids, elapsed))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn concurrent_scan_speeds_up_with_concurrency()
+-> std::result::Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let storage = Arc::new(Storage::with_layer(
+        Storage::new_memory(),
+        |op: Operator| op.layer(LatencyLayer),
+    ));
+    let table = prepare_table(storage, "concurrent_scan_speeds_up").await?;
+
+    let (sequential_ids, sequential) = timed_scan(&table, Some(1)).await?;
+    assert_eq!(sequential_ids, (0..FILE_COUNT * 10).collect::<Vec<_>>());
+    let (concurrent_ids, concurrent) = timed_scan(&table, Some(4)).await?;
+    assert_eq!(concurrent_ids, sequential_ids);
+    let speedup = sequential.as_secs_f64() / concurrent.as_secs_f64();
+    assert!(
+        speedup > 2.5,
+        "concurrency 4 took {concurrent:?}, concurrency 1 took {sequential:?}"
+    );
+
+    assert!(
+        table
+            .scan(TableScan::default().with_concurrency(Some(0)))
+            .await
+            .is_err()
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn concurrent_scan_fails_on_missing_file()
+-> std::result::Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let storage = Arc::new(Storage::with_layer(
+        Storage::new_memory(),
+        |op: Operator| op.layer(LatencyLayer),
+    ));
+    let table = prepare_table(storage.clone(), "concurrent_scan_fails").await?;
+    let data_files = storage
+        .list_files(&table.table_dir())
+        .await?
+        .into_iter()
+        .filter(|file| file.relative_path.ends_with(".parquet"))
+        .collect::<Vec<_>>();
+    storage.delete(&data_files[0].relative_path).await?;
+
+    let storage_refs = Arc::strong_count(&storage);
+    let result = table
+        .scan(TableScan::default().with_concurrency(Some(4)))
+        .await?
+        .try_collect::<Vec<_>>()
+        .await;
+    assert!(result.is_err());
+
+    // The tasks reading the other files are aborted along with the scan
+    tokio::time::sleep(READ_LATENCY * 5).await;
+    assert_eq!(Arc::strong_count(&storage), storage_refs);
+
+    Ok(())
+}
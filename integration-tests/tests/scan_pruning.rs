@@ -0,0 +1,143 @@
+use arrow::array::{Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use indexlake::expr::{col, lit};
+use indexlake::table::{Table, TableConfig, TableCreation, TableScan};
+use indexlake::{ILResult, LakeClient, catalog::Catalog, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::{
+    counted_storage, create_namespace_if_not_exists, data_files_opened, insert_files,
+};
+use indexlake_integration_tests::utils::table_scan;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger,
+};
+use std::sync::Arc;
+
+fn table_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("name", DataType::Utf8, true),
+    ]))
+}
+
+async fn create_table(
+    client: &LakeClient,
+    table_name: &str,
+) -> Result<Table, Box<dyn std::error::Error>> {
+    create_namespace_if_not_exists(client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: table_schema(),
+            config: TableConfig {
+                inline_row_count_limit: 10,
+                parquet_row_group_size: 4,
+                ..Default::default()
+            },
+        })
+        .await?;
+    Ok(client.load_table("test_namespace", table_name).await?)
+}
+
+/// Rows of the ids, files of ten rows are named apple for ids 0-9, banana for ids 10-19 and
+/// have no names for ids 20-29.
+fn file_batch(ids: Vec<i32>) -> ILResult<RecordBatch> {
+    let fruit = [Some("apple"), Some("banana"), None][ids[0] as usize / 10];
+    let names = ids
+        .iter()
+        .map(|id| fruit.map(|fruit| format!("{fruit}{id}")))
+        .collect::<Vec<_>>();
+    Ok(RecordBatch::try_new(
+        table_schema(),
+        vec![
+            Arc::new(Int32Array::from(ids)),
+            Arc::new(StringArray::from(names)),
+        ],
+    )?)
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn scan_prunes_files_by_column_stats(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage.clone());
+    let table = create_table(&client, "scan_prunes_files_by_column_stats").await?;
+    insert_files(&table, 3, file_batch).await?;
+
+    storage.reset_read_stats();
+    let scan =
+        TableScan::default().with_filters(vec![col("id").in_list(vec![lit(3), lit(25)], false)]);
+    assert_eq!(
+        table_scan(&table, scan).await?,
+        r#"+-------------------+----+--------+
+| _indexlake_row_id | id | name   |
++-------------------+----+--------+
+| 4                 | 3  | apple3 |
+| 26                | 25 |        |
++-------------------+----+--------+"#,
+    );
+    assert_eq!(data_files_opened(&storage), 2);
+
+    // no file holds the ids
+    storage.reset_read_stats();
+    let missing = col("id").in_list(vec![lit(100), lit(200)], false);
+    let scan = TableScan::default().with_filters(vec![missing]);
+    table_scan(&table, scan).await?;
+    assert_eq!(data_files_opened(&storage), 0);
+
+    storage.reset_read_stats();
+    let scan = TableScan::default().with_filters(vec![col("id").gt(lit(25))]);
+    assert_eq!(
+        table_scan(&table, scan).await?,
+        r#"+-------------------+----+------+
+| _indexlake_row_id | id | name |
++-------------------+----+------+
+| 27                | 26 |      |
+| 28                | 27 |      |
+| 29                | 28 |      |
+| 30                | 29 |      |
++-------------------+----+------+"#,
+    );
+    assert_eq!(data_files_opened(&storage), 1);
+
+    // only the file without names holds nulls
+    storage.reset_read_stats();
+    let scan = TableScan::default().with_filters(vec![col("name").is_null()]);
+    let scanned = table_scan(&table, scan).await?;
+    assert_eq!(scanned.lines().count(), 14);
+    assert_eq!(data_files_opened(&storage), 1);
+
+    storage.reset_read_stats();
+    let scan = TableScan::default().with_filters(vec![col("name").is_not_null()]);
+    let scanned = table_scan(&table, scan).await?;
+    assert_eq!(scanned.lines().count(), 24);
+    assert_eq!(data_files_opened(&storage), 2);
+
+    storage.reset_read_stats();
+    let scan = TableScan::default().with_filters(vec![col("name").like(lit("ban%".to_string()))]);
+    let scanned = table_scan(&table, scan).await?;
+    assert_eq!(scanned.lines().count(), 14);
+    assert!(scanned.contains("banana10") && !scanned.contains("apple"));
+    assert_eq!(data_files_opened(&storage), 1);
+
+    storage.reset_read_stats();
+    let scan =
+        TableScan::default().with_filters(vec![col("name").like(lit("cherry%".to_string()))]);
+    table_scan(&table, scan).await?;
+    assert_eq!(data_files_opened(&storage), 0);
+
+    Ok(())
+}
@@ -0,0 +1,175 @@
+use arrow::array::{AsArray, Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Int32Type, Schema, SchemaRef};
+use futures::{StreamExt, TryStreamExt};
+use indexlake::expr::{col, lit};
+use indexlake::table::{TableConfig, TableCreation, TableScan};
+use indexlake::{ILError, ILResult, LakeClient};
+use indexlake_integration_tests::data::{create_namespace_if_not_exists, insert_files};
+use indexlake_integration_tests::{catalog_sqlite, init_env_logger, storage_memory};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Tracks the bytes allocated by the test process and the most allocated at once.
+struct PeakAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+fn record_alloc(size: usize) {
+    let allocated = ALLOCATED.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(allocated, Ordering::Relaxed);
+}
+
+/// Starts measuring the peak from the bytes allocated now.
+fn reset_peak() -> usize {
+    let allocated = ALLOCATED.load(Ordering::Relaxed);
+    PEAK.store(allocated, Ordering::Relaxed);
+    allocated
+}
+
+unsafe impl GlobalAlloc for PeakAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record_alloc(layout.size());
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record_alloc(new_size);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: PeakAllocator = PeakAllocator;
+
+const FILE_COUNT: i32 = 6;
+const ROWS_PER_FILE: i32 = 2000;
+const PAYLOAD_LEN: usize = 2000;
+
+fn table_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("payload", DataType::Utf8, false),
+    ]))
+}
+
+/// Payloads of pseudo-random letters, so data files do not compress well.
+fn payload(id: i32) -> String {
+    let mut state = id as u64 + 1;
+    (0..PAYLOAD_LEN)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (b'a' + (state >> 59) as u8 % 26) as char
+        })
+        .collect()
+}
+
+fn file_batch(ids: Vec<i32>) -> ILResult<RecordBatch> {
+    let payloads = ids.iter().map(|id| payload(*id)).collect::<Vec<_>>();
+    Ok(RecordBatch::try_new(
+        table_schema(),
+        vec![
+            Arc::new(Int32Array::from(ids)),
+            Arc::new(StringArray::from(payloads)),
+        ],
+    )?)
+}
+
+/// Row count and sum of the ids of the batches.
+fn summarize(batches: &[RecordBatch]) -> (usize, i64) {
+    batches.iter().fold((0, 0), |(rows, sum), batch| {
+        let ids = batch
+            .column_by_name("id")
+            .unwrap()
+            .as_primitive::<Int32Type>();
+        let batch_sum = ids.values().iter().map(|id| *id as i64).sum::<i64>();
+        (rows + batch.num_rows(), sum + batch_sum)
+    })
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn scan_stream_bounds_memory() -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog_sqlite(), storage_memory());
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "scan_stream_bounds_memory".to_string(),
+            schema: table_schema(),
+            config: TableConfig {
+                inline_row_count_limit: ROWS_PER_FILE as usize,
+                parquet_row_group_size: 250,
+                ..Default::default()
+            },
+        })
+        .await?;
+    let table = client
+        .load_table("test_namespace", "scan_stream_bounds_memory")
+        .await?;
+    insert_files(&table, FILE_COUNT, file_batch).await?;
+
+    let full_scan = table
+        .scan(TableScan::default())
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    let expected = summarize(&full_scan);
+    assert_eq!(expected.0, (FILE_COUNT * ROWS_PER_FILE) as usize);
+    drop(full_scan);
+
+    // Batches are dropped as they are consumed, only the data of the batch being decoded is
+    // held at once
+    let baseline = reset_peak();
+    let mut stream = table
+        .scan_stream(TableScan::default().with_batch_size(Some(100)))
+        .await?;
+    let mut summary = (0, 0);
+    while let Some(batch) = stream.next().await {
+        let batch = batch?;
+        assert!(batch.num_rows() <= 100);
+        let (rows, sum) = summarize(&[batch]);
+        summary = (summary.0 + rows, summary.1 + sum);
+    }
+    drop(stream);
+    let peak = PEAK.load(Ordering::Relaxed) - baseline;
+    assert_eq!(summary, expected);
+    let data_bytes = FILE_COUNT as usize * ROWS_PER_FILE as usize * PAYLOAD_LEN;
+    assert!(
+        peak < data_bytes / 4,
+        "scan stream peaked at {peak} bytes for {data_bytes} bytes of data"
+    );
+
+    // Projection and filters are applied before batches are yielded
+    let filtered = table
+        .scan_stream(
+            TableScan::default()
+                .with_projection(Some(vec![1]))
+                .with_filters(vec![col("id").lt(lit(150))])
+                .with_batch_size(Some(100)),
+        )
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    assert!(filtered.iter().all(|batch| batch.num_columns() == 1));
+    assert_eq!(summarize(&filtered), (150, (0..150).sum::<i64>()));
+
+    assert!(matches!(
+        table
+            .scan_stream(TableScan::default().with_batch_size(Some(0)))
+            .await,
+        Err(ILError::InvalidInput(_))
+    ));
+
+    Ok(())
+}
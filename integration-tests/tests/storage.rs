@@ -1,10 +1,22 @@
+use indexlake::LakeClient;
 use indexlake::storage::Storage;
-use indexlake_integration_tests::{storage_fs, storage_s3};
+use indexlake_integration_tests::{
+    GcsTestContext, catalog_sqlite, data::prepare_testing_table, init_env_logger, storage_azblob,
+    storage_fs, storage_gcs, storage_memory, storage_oss, storage_r2, storage_s3,
+    utils::full_table_scan,
+};
+use opendal::services::{GcsConfig, OssConfig, S3Config};
+use parquet::arrow::async_writer::AsyncFileWriter;
 use std::sync::Arc;
 
 #[rstest::rstest]
 #[case(storage_fs())]
 #[case(storage_s3())]
+#[case(storage_gcs())]
+#[case(storage_azblob())]
+#[case(storage_oss())]
+#[case(storage_r2())]
+#[case(storage_memory())]
 #[tokio::test(flavor = "multi_thread")]
 async fn file_operations(#[case] storage: Arc<Storage>) -> Result<(), Box<dyn std::error::Error>> {
     let file_path = "test/test.txt";
@@ -23,3 +35,230 @@ async fn file_operations(#[case] storage: Arc<Storage>) -> Result<(), Box<dyn st
 
     Ok(())
 }
+
+#[rstest::rstest]
+#[case(storage_fs())]
+#[case(storage_s3())]
+#[case(storage_gcs())]
+#[case(storage_azblob())]
+#[case(storage_oss())]
+#[case(storage_r2())]
+#[case(storage_memory())]
+#[tokio::test(flavor = "multi_thread")]
+async fn list_files(#[case] storage: Arc<Storage>) -> Result<(), Box<dyn std::error::Error>> {
+    let file_paths = ["list_dir/1/a.txt", "list_dir/1/b.txt", "list_dir/2/c.txt"];
+    for file_path in file_paths {
+        let output_file = storage.create_file(file_path).await?;
+        output_file.write(bytes::Bytes::from(file_path)).await?;
+    }
+
+    let mut files = storage.list_files("list_dir").await?;
+    files.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+    assert_eq!(
+        files
+            .iter()
+            .map(|file| file.relative_path.as_str())
+            .collect::<Vec<_>>(),
+        file_paths
+    );
+    for file in &files {
+        assert_eq!(file.size_bytes, file.relative_path.len() as u64);
+        assert!(file.last_modified.is_some());
+    }
+    assert!(storage.list_files("list_dir/missing").await?.is_empty());
+
+    storage.remove_dir_all("list_dir").await?;
+    assert!(storage.list_files("list_dir").await?.is_empty());
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(storage_fs())]
+#[case(storage_s3())]
+#[case(storage_gcs())]
+#[case(storage_azblob())]
+#[case(storage_oss())]
+#[case(storage_r2())]
+#[case(storage_memory())]
+#[tokio::test(flavor = "multi_thread")]
+async fn remove_dir_all(#[case] storage: Arc<Storage>) -> Result<(), Box<dyn std::error::Error>> {
+    let file_paths = ["test_dir/1/a.txt", "test_dir/1/b.txt", "test_dir/2/c.txt"];
+    for file_path in file_paths {
+        let output_file = storage.create_file(file_path).await?;
+        output_file.write(bytes::Bytes::from(file_path)).await?;
+    }
+
+    storage.remove_dir_all("test_dir/1").await?;
+    assert!(!storage.exists(file_paths[0]).await?);
+    assert!(!storage.exists(file_paths[1]).await?);
+    assert!(storage.exists(file_paths[2]).await?);
+
+    storage.remove_dir_all("test_dir").await?;
+    assert!(!storage.exists(file_paths[2]).await?);
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(storage_fs())]
+#[case(storage_s3())]
+#[case(storage_gcs())]
+#[case(storage_azblob())]
+#[case(storage_oss())]
+#[case(storage_r2())]
+#[case(storage_memory())]
+#[tokio::test(flavor = "multi_thread")]
+async fn parquet_scan(#[case] storage: Arc<Storage>) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog_sqlite(), storage);
+    let table = prepare_testing_table(&client, "storage_parquet_scan").await?;
+
+    let table_str = full_table_scan(&table).await?;
+    assert_eq!(
+        table_str,
+        r#"+-------------------+---------+-----+
+| _indexlake_row_id | name    | age |
++-------------------+---------+-----+
+| 1                 | Alice   | 20  |
+| 2                 | Bob     | 21  |
+| 3                 | Charlie | 22  |
+| 4                 | David   | 23  |
++-------------------+---------+-----+"#,
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn gcs_missing_credentials() -> Result<(), Box<dyn std::error::Error>> {
+    let mut config = GcsConfig::default();
+    config.endpoint = Some("http://127.0.0.1:4443".to_string());
+    config.disable_config_load = true;
+    config.disable_vm_metadata = true;
+    let err = Storage::new_gcs(config, "indexlake").unwrap_err();
+    assert!(err.to_string().contains("no credentials"));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn gcs_range_read() -> Result<(), Box<dyn std::error::Error>> {
+    let context = GcsTestContext::setup();
+    let storage = context.storage();
+
+    let file_path = "gcs_range_read/test.txt";
+    let output_file = storage.create_file(file_path).await?;
+    output_file
+        .write(bytes::Bytes::from("Hello, world!"))
+        .await?;
+
+    let input_file = storage.open_file(file_path).await?;
+    assert_eq!(input_file.file_size_bytes().await?, 13);
+    assert_eq!(
+        input_file.read_range(7..12).await?,
+        bytes::Bytes::from("world")
+    );
+
+    storage.delete(file_path).await?;
+    assert!(!storage.exists(file_path).await?);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn memory_range_read() -> Result<(), Box<dyn std::error::Error>> {
+    let storage = storage_memory();
+
+    let file_path = "memory_range_read/test.txt";
+    let output_file = storage.create_file(file_path).await?;
+    output_file
+        .write(bytes::Bytes::from("Hello, world!"))
+        .await?;
+
+    let input_file = storage.open_file(file_path).await?;
+    assert_eq!(input_file.file_size_bytes().await?, 13);
+    assert_eq!(
+        input_file.read_range(7..12).await?,
+        bytes::Bytes::from("world")
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn memory_storages_are_isolated() -> Result<(), Box<dyn std::error::Error>> {
+    let storage = Storage::new_memory();
+    let other = Storage::new_memory();
+
+    let file_path = "isolated/test.txt";
+    storage
+        .create_file(file_path)
+        .await?
+        .write(bytes::Bytes::from("Hello, world!"))
+        .await?;
+    assert!(storage.exists(file_path).await?);
+    assert!(!other.exists(file_path).await?);
+    assert!(other.list_files("isolated").await?.is_empty());
+
+    // Clones share the files
+    assert!(storage.clone().exists(file_path).await?);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn oss_multipart_upload() -> Result<(), Box<dyn std::error::Error>> {
+    let storage = storage_oss();
+
+    // Parts of 5 bytes make a multipart upload of three parts
+    let file_path = "oss_multipart_upload/complete.txt";
+    let mut output_file = storage.create_file_with_part_size(file_path, 5).await?;
+    for chunk in ["Hello", ", wor", "ld!"] {
+        AsyncFileWriter::write(&mut output_file, bytes::Bytes::from(chunk)).await?;
+    }
+    AsyncFileWriter::complete(&mut output_file).await?;
+
+    let input_file = storage.open_file(file_path).await?;
+    assert_eq!(input_file.file_size_bytes().await?, 13);
+    assert_eq!(
+        input_file.read_range(7..12).await?,
+        bytes::Bytes::from("world")
+    );
+
+    let file_path = "oss_multipart_upload/aborted.txt";
+    let mut output_file = storage.create_file_with_part_size(file_path, 5).await?;
+    for chunk in ["Hello", ", wor"] {
+        AsyncFileWriter::write(&mut output_file, bytes::Bytes::from(chunk)).await?;
+    }
+    output_file.abort().await?;
+    assert!(!storage.exists(file_path).await?);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn oss_invalid_config() -> Result<(), Box<dyn std::error::Error>> {
+    let err = Storage::new_oss(OssConfig::default(), "indexlake").unwrap_err();
+    assert!(err.to_string().contains("no endpoint"));
+
+    let mut config = OssConfig::default();
+    config.endpoint = Some("https://oss-cn-hangzhou.aliyuncs.com".to_string());
+    assert!(Storage::new_oss(config, "Index_Lake").is_err());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn r2_unsupported_config() -> Result<(), Box<dyn std::error::Error>> {
+    let err = Storage::new_r2(S3Config::default(), "indexlake").unwrap_err();
+    assert!(err.to_string().contains("no endpoint"));
+
+    let mut config = S3Config::default();
+    config.endpoint = Some("https://account.r2.cloudflarestorage.com".to_string());
+    config.server_side_encryption = Some("aws:kms".to_string());
+    assert!(Storage::new_r2(config, "indexlake").is_err());
+
+    Ok(())
+}
@@ -0,0 +1,139 @@
+use indexlake::LakeClient;
+use indexlake::storage::{CacheConfig, Storage};
+use indexlake_integration_tests::{
+    MinioTestContext, catalog_sqlite, data::prepare_testing_table, init_env_logger,
+    utils::full_table_scan,
+};
+use std::sync::Arc;
+
+fn cache_dir() -> String {
+    format!(
+        "{}/tmp/disk_cache/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        uuid::Uuid::new_v4()
+    )
+}
+
+fn storage_fs_cached() -> Arc<Storage> {
+    let home = format!(
+        "{}/tmp/fs_storage/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        uuid::Uuid::new_v4()
+    );
+    let cache_dir = cache_dir();
+    let storage = Storage::with_disk_cache(Storage::new_fs(home), cache_dir, 64 * 1024 * 1024)
+        .expect("failed to create disk cache");
+    Arc::new(storage)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn disk_cache_serves_repeated_scans() -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let storage = storage_fs_cached();
+    let client = LakeClient::new(catalog_sqlite(), storage.clone());
+    let table = prepare_testing_table(&client, "disk_cache_serves_repeated_scans").await?;
+
+    let table_str = full_table_scan(&table).await?;
+    let stats = storage.cache_stats().unwrap();
+    assert!(stats.misses > 0);
+    assert!(stats.entries > 0);
+
+    // Concurrent readers share the cache
+    let table = Arc::new(table);
+    let handles = (0..2)
+        .map(|_| {
+            let table = table.clone();
+            tokio::spawn(async move { full_table_scan(&table).await.unwrap() })
+        })
+        .collect::<Vec<_>>();
+    for handle in handles {
+        assert_eq!(handle.await?, table_str);
+    }
+
+    let cached_stats = storage.cache_stats().unwrap();
+    assert_eq!(cached_stats.misses, stats.misses);
+    assert!(cached_stats.hits > stats.hits);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn disk_cache_invalidated_on_delete() -> Result<(), Box<dyn std::error::Error>> {
+    let storage = storage_fs_cached();
+    let file_path = "disk_cache/test.txt";
+
+    let output_file = storage.create_file(file_path).await?;
+    output_file
+        .write(bytes::Bytes::from("Hello, world!"))
+        .await?;
+
+    let input_file = storage.open_file(file_path).await?;
+    assert_eq!(input_file.read_range(0..5).await?, "Hello");
+    assert_eq!(input_file.read_range(0..5).await?, "Hello");
+    assert_eq!(storage.cache_stats().unwrap().hits, 1);
+
+    storage.delete(file_path).await?;
+    let stats = storage.cache_stats().unwrap();
+    assert_eq!(stats.entries, 0);
+    assert_eq!(stats.used_bytes, 0);
+
+    let output_file = storage.create_file(file_path).await?;
+    output_file.write(bytes::Bytes::from("Goodbye!")).await?;
+    let input_file = storage.open_file(file_path).await?;
+    assert_eq!(input_file.read_range(0..5).await?, "Goodb");
+
+    storage.delete(file_path).await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn disk_cache_reloads_overwritten_file() -> Result<(), Box<dyn std::error::Error>> {
+    let storage = storage_fs_cached();
+    let file_path = "disk_cache/overwritten.txt";
+
+    let output_file = storage.create_file(file_path).await?;
+    output_file
+        .write(bytes::Bytes::from("Hello, world!"))
+        .await?;
+    let input_file = storage.open_file(file_path).await?;
+    assert_eq!(input_file.read_range(0..5).await?, "Hello");
+
+    // Overwriting the file drops its cached ranges
+    let output_file = storage.create_file(file_path).await?;
+    output_file.write(bytes::Bytes::from("Goodbye!")).await?;
+    let input_file = storage.open_file(file_path).await?;
+    assert_eq!(input_file.read_range(0..5).await?, "Goodb");
+    assert_eq!(storage.cache_stats().unwrap().hits, 0);
+
+    storage.delete(file_path).await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn s3_cache_serves_repeated_scans() -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let context = MinioTestContext::setup();
+    let storage = Arc::new(Storage::with_read_counter(Storage::new_s3_with_cache(
+        context.config(),
+        context.bucket(),
+        CacheConfig {
+            dir: cache_dir().into(),
+            max_bytes: 64 * 1024 * 1024,
+        },
+    )?));
+    let client = LakeClient::new(catalog_sqlite(), storage.clone());
+    let table = prepare_testing_table(&client, "s3_cache_serves_repeated_scans").await?;
+
+    storage.reset_read_stats();
+    let table_str = full_table_scan(&table).await?;
+    assert!(storage.read_stats().unwrap().read_requests > 0);
+
+    // The second scan only checks the etags of the files
+    storage.reset_read_stats();
+    assert_eq!(full_table_scan(&table).await?, table_str);
+    assert_eq!(storage.read_stats().unwrap().read_requests, 0);
+
+    Ok(())
+}
@@ -0,0 +1,260 @@
+use indexlake::LakeClient;
+use indexlake::storage::{Storage, StorageOptions};
+use indexlake_integration_tests::{
+    catalog_sqlite, data::prepare_testing_table, init_env_logger, utils::full_table_scan,
+};
+use opendal::raw::*;
+use opendal::{Buffer, Metadata, Operator, Result};
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+use std::time::Duration;
+
+/// Requests of one kind in flight below the concurrency limits, and the most seen at once.
+#[derive(Debug, Default)]
+struct InFlightCounter {
+    current: AtomicUsize,
+    max: AtomicUsize,
+}
+
+impl InFlightCounter {
+    fn max(&self) -> usize {
+        self.max.load(Ordering::SeqCst)
+    }
+}
+
+/// Records the requests in flight of the operators of a storage wrapped with its layer. Reads
+/// and stats are delayed, so requests of concurrent scans overlap.
+#[derive(Debug, Default)]
+struct InFlightRecorder {
+    reads: InFlightCounter,
+    writes: InFlightCounter,
+    requests: InFlightCounter,
+}
+
+impl InFlightRecorder {
+    fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    fn layer(self: &Arc<Self>) -> impl Fn(Operator) -> Operator + Send + Sync + 'static {
+        let recorder = self.clone();
+        move |op| {
+            op.layer(InFlightLayer {
+                recorder: recorder.clone(),
+            })
+        }
+    }
+
+    fn start(self: &Arc<Self>, write: bool) -> InFlight {
+        let kind = if write { &self.writes } else { &self.reads };
+        for counter in [kind, &self.requests] {
+            let current = counter.current.fetch_add(1, Ordering::SeqCst) + 1;
+            counter.max.fetch_max(current, Ordering::SeqCst);
+        }
+        InFlight {
+            recorder: self.clone(),
+            write,
+        }
+    }
+}
+
+/// A request in flight, finished on drop.
+struct InFlight {
+    recorder: Arc<InFlightRecorder>,
+    write: bool,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        let kind = if self.write {
+            &self.recorder.writes
+        } else {
+            &self.recorder.reads
+        };
+        for counter in [kind, &self.recorder.requests] {
+            counter.current.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+struct InFlightLayer {
+    recorder: Arc<InFlightRecorder>,
+}
+
+impl<A: Access> Layer<A> for InFlightLayer {
+    type LayeredAccess = InFlightAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccess {
+        InFlightAccessor {
+            inner,
+            recorder: self.recorder.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct InFlightAccessor<A> {
+    inner: A,
+    recorder: Arc<InFlightRecorder>,
+}
+
+impl<A: Access> LayeredAccess for InFlightAccessor<A> {
+    type Inner = A;
+    type Reader = InFlightWrapper<A::Reader>;
+    type BlockingReader = A::BlockingReader;
+    type Writer = InFlightWrapper<A::Writer>;
+    type BlockingWriter = A::BlockingWriter;
+    type Lister = A::Lister;
+    type BlockingLister = A::BlockingLister;
+    type Deleter = A::Deleter;
+    type BlockingDeleter = A::BlockingDeleter;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let in_flight = self.recorder.start(false);
+        tokio::time::sleep(Duration::from_millis(2)).await;
+        let (rp, inner) = self.inner.read(path, args).await?;
+        Ok((
+            rp,
+            InFlightWrapper {
+                inner,
+                recorder: self.recorder.clone(),
+                in_flight: Some(in_flight),
+            },
+        ))
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let (rp, inner) = self.inner.write(path, args).await?;
+        Ok((
+            rp,
+            InFlightWrapper {
+                inner,
+                recorder: self.recorder.clone(),
+                in_flight: None,
+            },
+        ))
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let _in_flight = self.recorder.start(false);
+        tokio::time::sleep(Duration::from_millis(2)).await;
+        self.inner.stat(path, args).await
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Lister)> {
+        self.inner.list(path, args).await
+    }
+
+    async fn delete(&self) -> Result<(RpDelete, Self::Deleter)> {
+        self.inner.delete().await
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.inner.blocking_read(path, args)
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.inner.blocking_write(path, args)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingLister)> {
+        self.inner.blocking_list(path, args)
+    }
+
+    fn blocking_delete(&self) -> Result<(RpDelete, Self::BlockingDeleter)> {
+        self.inner.blocking_delete()
+    }
+}
+
+/// Reader in flight until dropped, or writer in flight during each call.
+struct InFlightWrapper<R> {
+    inner: R,
+    recorder: Arc<InFlightRecorder>,
+    in_flight: Option<InFlight>,
+}
+
+impl<R: oio::Read> oio::Read for InFlightWrapper<R> {
+    async fn read(&mut self) -> Result<Buffer> {
+        let buffer = self.inner.read().await?;
+        if buffer.is_empty() {
+            self.in_flight = None;
+        }
+        Ok(buffer)
+    }
+}
+
+impl<W: oio::Write> oio::Write for InFlightWrapper<W> {
+    async fn write(&mut self, bs: Buffer) -> Result<()> {
+        let _in_flight = self.recorder.start(true);
+        self.inner.write(bs).await
+    }
+
+    async fn close(&mut self) -> Result<Metadata> {
+        let _in_flight = self.recorder.start(true);
+        self.inner.close().await
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        let _in_flight = self.recorder.start(true);
+        self.inner.abort().await
+    }
+}
+
+/// Runs 64 full scans at once, spread over two tables of the storage.
+async fn run_concurrent_scans(
+    storage: Storage,
+    table_prefix: &str,
+) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let client = LakeClient::new(catalog_sqlite(), Arc::new(storage));
+    let mut tables = Vec::new();
+    for suffix in ["a", "b"] {
+        let table = prepare_testing_table(&client, &format!("{table_prefix}_{suffix}")).await?;
+        tables.push(Arc::new(table));
+    }
+    let table_str = full_table_scan(&tables[0]).await?;
+
+    let handles = (0..64)
+        .map(|i| {
+            let table = tables[i % tables.len()].clone();
+            tokio::spawn(async move { full_table_scan(&table).await.unwrap() })
+        })
+        .collect::<Vec<_>>();
+    for handle in handles {
+        assert_eq!(handle.await?, table_str);
+    }
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn concurrency_limits_bound_requests_in_flight()
+-> std::result::Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    // Without limits, the scans overlap well beyond the limits below
+    let unlimited = InFlightRecorder::new();
+    let storage = Storage::with_layer(Storage::new_memory(), unlimited.layer());
+    run_concurrent_scans(storage, "concurrency_unlimited").await?;
+    assert!(unlimited.reads.max() > 4);
+
+    let recorder = InFlightRecorder::new();
+    let storage = Storage::with_concurrency_limits(
+        Storage::with_layer(Storage::new_memory(), recorder.layer()),
+        StorageOptions {
+            max_concurrent_requests: Some(5),
+            max_concurrent_reads: Some(4),
+            max_concurrent_writes: Some(1),
+        },
+    )?;
+    run_concurrent_scans(storage, "concurrency_limited").await?;
+    assert!(recorder.reads.max() > 0 && recorder.reads.max() <= 4);
+    assert!(recorder.writes.max() > 0 && recorder.writes.max() <= 1);
+    assert!(recorder.requests.max() <= 5);
+
+    Ok(())
+}
@@ -0,0 +1,215 @@
+use indexlake::storage::{Storage, StorageRetryPolicy};
+use indexlake::{ILError, LakeClient};
+use indexlake_integration_tests::{
+    catalog_sqlite, data::prepare_testing_table, init_env_logger, utils::full_table_scan,
+};
+use opendal::raw::*;
+use opendal::{Error, ErrorKind, Operator, Result};
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicUsize, Ordering},
+};
+use std::time::Duration;
+
+/// Fails the next storage calls armed with `fail_next`, shared by the operators of a storage
+/// wrapped with its layer.
+#[derive(Debug, Default)]
+pub struct FaultInjector {
+    remaining: AtomicUsize,
+    error: Mutex<Option<(ErrorKind, bool)>>,
+    calls: AtomicUsize,
+}
+
+impl FaultInjector {
+    fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Fails the next `count` calls with an error of `kind`, retried by the storage if
+    /// `temporary`, like a 503 or a connection reset.
+    fn fail_next(&self, count: usize, kind: ErrorKind, temporary: bool) {
+        *self.error.lock().unwrap() = Some((kind, temporary));
+        self.remaining.store(count, Ordering::SeqCst);
+        self.calls.store(0, Ordering::SeqCst);
+    }
+
+    /// Number of calls since the failures were armed, including the failed ones.
+    fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+
+    fn layer(self: &Arc<Self>) -> impl Fn(Operator) -> Operator + Send + Sync + 'static {
+        let injector = self.clone();
+        move |op| {
+            op.layer(FaultInjectionLayer {
+                injector: injector.clone(),
+            })
+        }
+    }
+
+    fn check(&self, operation: &str) -> Result<()> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        let failing = self
+            .remaining
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |remaining| {
+                remaining.checked_sub(1)
+            })
+            .is_ok();
+        if !failing {
+            return Ok(());
+        }
+        let (kind, temporary) = self
+            .error
+            .lock()
+            .unwrap()
+            .unwrap_or((ErrorKind::Unexpected, true));
+        let err = Error::new(kind, format!("injected {operation} failure"));
+        Err(if temporary { err.set_temporary() } else { err })
+    }
+}
+
+struct FaultInjectionLayer {
+    injector: Arc<FaultInjector>,
+}
+
+impl<A: Access> Layer<A> for FaultInjectionLayer {
+    type LayeredAccess = FaultInjectionAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccess {
+        FaultInjectionAccessor {
+            inner,
+            injector: self.injector.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct FaultInjectionAccessor<A> {
+    inner: A,
+    injector: Arc<FaultInjector>,
+}
+
+impl<A: Access> LayeredAccess for FaultInjectionAccessor<A> {
+    type Inner = A;
+    type Reader = A::Reader;
+    type BlockingReader = A::BlockingReader;
+    type Writer = A::Writer;
+    type BlockingWriter = A::BlockingWriter;
+    type Lister = A::Lister;
+    type BlockingLister = A::BlockingLister;
+    type Deleter = A::Deleter;
+    type BlockingDeleter = A::BlockingDeleter;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.injector.check("read")?;
+        self.inner.read(path, args).await
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.injector.check("write")?;
+        self.inner.write(path, args).await
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.injector.check("stat")?;
+        self.inner.stat(path, args).await
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Lister)> {
+        self.injector.check("list")?;
+        self.inner.list(path, args).await
+    }
+
+    async fn delete(&self) -> Result<(RpDelete, Self::Deleter)> {
+        self.injector.check("delete")?;
+        self.inner.delete().await
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.inner.blocking_read(path, args)
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.inner.blocking_write(path, args)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingLister)> {
+        self.inner.blocking_list(path, args)
+    }
+
+    fn blocking_delete(&self) -> Result<(RpDelete, Self::BlockingDeleter)> {
+        self.inner.blocking_delete()
+    }
+}
+
+fn retry_policy() -> StorageRetryPolicy {
+    StorageRetryPolicy::new(3, Duration::from_millis(10), Duration::from_millis(50))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn retry_transient_storage_failure() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let injector = FaultInjector::new();
+    let storage = Storage::with_retry_policy(
+        Storage::with_layer(Storage::new_memory(), injector.layer()),
+        retry_policy(),
+    );
+    let client = LakeClient::new(catalog_sqlite(), Arc::new(storage));
+    let table = prepare_testing_table(&client, "retry_transient_storage_failure").await?;
+    let table_str = full_table_scan(&table).await?;
+
+    injector.fail_next(2, ErrorKind::Unexpected, true);
+    assert_eq!(full_table_scan(&table).await?, table_str);
+    assert!(injector.calls() > 2);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn retry_skips_permission_denied() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let injector = FaultInjector::new();
+    let storage = Arc::new(Storage::with_retry_policy(
+        Storage::with_layer(Storage::new_memory(), injector.layer()),
+        retry_policy(),
+    ));
+    let client = LakeClient::new(catalog_sqlite(), storage.clone());
+    let table = prepare_testing_table(&client, "retry_skips_permission_denied").await?;
+
+    injector.fail_next(usize::MAX, ErrorKind::PermissionDenied, false);
+    assert!(matches!(
+        full_table_scan(&table).await,
+        Err(ILError::StorageError(_))
+    ));
+
+    injector.fail_next(usize::MAX, ErrorKind::PermissionDenied, false);
+    assert!(
+        storage
+            .exists("retry_skips_permission_denied")
+            .await
+            .is_err()
+    );
+    assert_eq!(injector.calls(), 1);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn retry_disabled() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let injector = FaultInjector::new();
+    let storage = Storage::with_retry_policy(
+        Storage::with_layer(Storage::new_memory(), injector.layer()),
+        StorageRetryPolicy::disabled(),
+    );
+
+    injector.fail_next(1, ErrorKind::Unexpected, true);
+    assert!(storage.exists("retry_disabled").await.is_err());
+    assert!(!storage.exists("retry_disabled").await?);
+    assert_eq!(injector.calls(), 2);
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use indexlake::storage::{S3ServerSideEncryption, Storage};
+use opendal::services::S3Config;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+type RecordedRequests = Arc<Mutex<Vec<(String, HashMap<String, String>)>>>;
+
+/// Minimal S3 endpoint accepting every request and recording request lines and headers.
+async fn start_recording_endpoint() -> (String, RecordedRequests) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    let requests = RecordedRequests::default();
+    let recorded = requests.clone();
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            tokio::spawn(serve_connection(stream, recorded.clone()));
+        }
+    });
+    (endpoint, requests)
+}
+
+async fn serve_connection(mut stream: TcpStream, requests: RecordedRequests) {
+    let mut buf = Vec::new();
+    loop {
+        let header_end = loop {
+            if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+            let mut chunk = [0u8; 4096];
+            match stream.read(&mut chunk).await {
+                Ok(0) | Err(_) => return,
+                Ok(n) => buf.extend_from_slice(&chunk[..n]),
+            }
+        };
+        let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+        let mut lines = head.split("\r\n");
+        let request_line = lines.next().unwrap().to_string();
+        let headers = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+            .collect::<HashMap<_, _>>();
+        let content_length = headers
+            .get("content-length")
+            .map(|len| len.parse::<usize>().unwrap())
+            .unwrap_or(0);
+        while buf.len() < header_end + content_length {
+            let mut chunk = [0u8; 4096];
+            match stream.read(&mut chunk).await {
+                Ok(0) | Err(_) => return,
+                Ok(n) => buf.extend_from_slice(&chunk[..n]),
+            }
+        }
+        buf.drain(..header_end + content_length);
+        requests.lock().unwrap().push((request_line, headers));
+
+        let response = "HTTP/1.1 200 OK\r\nContent-Length: 0\r\nETag: \"etag\"\r\n\r\n";
+        if stream.write_all(response.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+fn s3_config(endpoint: String) -> S3Config {
+    let mut config = S3Config::default();
+    config.endpoint = Some(endpoint);
+    config.access_key_id = Some("admin".to_string());
+    config.secret_access_key = Some("password".to_string());
+    config.region = Some("us-east-1".to_string());
+    config.disable_config_load = true;
+    config.disable_ec2_metadata = true;
+    config
+}
+
+async fn put_headers(storage: &Storage, requests: &RecordedRequests) -> HashMap<String, String> {
+    let output_file = storage.create_file("test/test.parquet").await.unwrap();
+    output_file
+        .write(bytes::Bytes::from("Hello, world!"))
+        .await
+        .unwrap();
+    let requests = requests.lock().unwrap();
+    let (_, headers) = requests
+        .iter()
+        .rev()
+        .find(|(request_line, _)| request_line.starts_with("PUT "))
+        .expect("no PUT request sent");
+    headers.clone()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn s3_sse_kms_headers() -> Result<(), Box<dyn std::error::Error>> {
+    let (endpoint, requests) = start_recording_endpoint().await;
+    let storage = Storage::new_s3_with_encryption(
+        s3_config(endpoint),
+        "indexlake",
+        S3ServerSideEncryption::Kms {
+            key_id: Some("test-key".to_string()),
+        },
+    );
+
+    let headers = put_headers(&storage, &requests).await;
+    assert_eq!(
+        headers.get("x-amz-server-side-encryption").unwrap(),
+        "aws:kms"
+    );
+    assert_eq!(
+        headers
+            .get("x-amz-server-side-encryption-aws-kms-key-id")
+            .unwrap(),
+        "test-key"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn s3_sse_aes256_headers() -> Result<(), Box<dyn std::error::Error>> {
+    let (endpoint, requests) = start_recording_endpoint().await;
+    let storage = Storage::new_s3_with_encryption(
+        s3_config(endpoint),
+        "indexlake",
+        S3ServerSideEncryption::Aes256,
+    );
+
+    let headers = put_headers(&storage, &requests).await;
+    assert_eq!(
+        headers.get("x-amz-server-side-encryption").unwrap(),
+        "AES256"
+    );
+    assert!(!headers.contains_key("x-amz-server-side-encryption-aws-kms-key-id"));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn s3_without_sse_headers() -> Result<(), Box<dyn std::error::Error>> {
+    let (endpoint, requests) = start_recording_endpoint().await;
+    let storage = Storage::new_s3(s3_config(endpoint), "indexlake");
+
+    let headers = put_headers(&storage, &requests).await;
+    assert!(!headers.contains_key("x-amz-server-side-encryption"));
+
+    Ok(())
+}
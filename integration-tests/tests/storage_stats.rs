@@ -0,0 +1,196 @@
+use arrow::array::{Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use indexlake::expr::{col, lit};
+use indexlake::table::{CompactOptions, StorageStats, Table, TableConfig, TableCreation};
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+use indexlake_integration_tests::data::create_namespace_if_not_exists;
+use indexlake_integration_tests::{
+    catalog_postgres, catalog_sqlite, init_env_logger, storage_fs, storage_memory, storage_s3,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn table_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("name", DataType::Utf8, false),
+        Field::new("age", DataType::Int32, false),
+    ]))
+}
+
+async fn create_table(
+    client: &LakeClient,
+    table_name: &str,
+) -> Result<Table, Box<dyn std::error::Error>> {
+    create_namespace_if_not_exists(client, "stats_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "stats_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: table_schema(),
+            config: TableConfig {
+                inline_row_count_limit: 3,
+                parquet_row_group_size: 2,
+                ..Default::default()
+            },
+        })
+        .await?;
+    Ok(client.load_table("stats_namespace", table_name).await?)
+}
+
+async fn insert_and_dump(
+    table: &Table,
+    names: Vec<&str>,
+    ages: Vec<i32>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    table
+        .insert(&RecordBatch::try_new(
+            table_schema(),
+            vec![
+                Arc::new(StringArray::from(names)),
+                Arc::new(Int32Array::from(ages)),
+            ],
+        )?)
+        .await?;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+    Ok(())
+}
+
+/// Sizes of the files of the table directory, each stat-ed on its own.
+async fn stat_files(
+    storage: &Storage,
+    table: &Table,
+) -> Result<HashMap<String, u64>, Box<dyn std::error::Error>> {
+    let table_dir = format!("{}/{}", table.namespace_id, table.table_id);
+    let mut sizes = HashMap::new();
+    for file in storage.list_files(&table_dir).await? {
+        let input_file = storage.open_file(&file.relative_path).await?;
+        sizes.insert(file.relative_path, input_file.file_size_bytes().await?);
+    }
+    Ok(sizes)
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_sqlite() }, storage_memory())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[tokio::test(flavor = "multi_thread")]
+async fn table_storage_stats(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage.clone());
+    let table = create_table(&client, "table_storage_stats").await?;
+    assert_eq!(table.storage_stats().await?, StorageStats::default());
+
+    insert_and_dump(
+        &table,
+        vec!["Alice", "Bob", "Charlie", "David"],
+        vec![20, 21, 22, 23],
+    )
+    .await?;
+    let sizes = stat_files(&storage, &table).await?;
+    assert_eq!(sizes.len(), 1);
+    let first_file_bytes = sizes.values().sum::<u64>();
+    let stats = table.storage_stats().await?;
+    assert_eq!(
+        stats,
+        StorageStats {
+            total_bytes: first_file_bytes,
+            data_file_count: 1,
+            data_file_bytes: first_file_bytes,
+            index_file_bytes: 0,
+            average_data_file_bytes: first_file_bytes,
+            deleted_row_bytes: 0,
+        }
+    );
+
+    // the dump wrote three of the rows, one of them is deleted
+    assert_eq!(table.delete(&col("age").eq(lit(20))).await?, 1);
+    let stats = table.storage_stats().await?;
+    assert_eq!(stats.total_bytes, first_file_bytes);
+    assert_eq!(stats.deleted_row_bytes, first_file_bytes / 3);
+
+    insert_and_dump(
+        &table,
+        vec!["Eve", "Frank", "Grace", "Heidi"],
+        vec![24, 25, 26, 27],
+    )
+    .await?;
+    let sizes = stat_files(&storage, &table).await?;
+    assert_eq!(sizes.len(), 2);
+    let stats = table.storage_stats().await?;
+    assert_eq!(stats.total_bytes, sizes.values().sum::<u64>());
+    assert_eq!(stats.data_file_count, 2);
+    assert_eq!(stats.data_file_bytes, stats.total_bytes);
+    assert_eq!(stats.average_data_file_bytes, stats.total_bytes / 2);
+    assert_eq!(stats.deleted_row_bytes, first_file_bytes / 3);
+
+    // the replaced files wait for a vacuum
+    table.compact(CompactOptions::default()).await?;
+    let sizes_after_compact = stat_files(&storage, &table).await?;
+    assert_eq!(sizes_after_compact.len(), 3);
+    let stats = table.storage_stats().await?;
+    assert_eq!(stats.total_bytes, sizes_after_compact.values().sum::<u64>());
+    assert_eq!(stats.data_file_count, 1);
+    assert_eq!(stats.deleted_row_bytes, sizes.values().sum::<u64>());
+    assert_eq!(
+        stats.data_file_bytes,
+        stats.total_bytes - stats.deleted_row_bytes
+    );
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_sqlite() }, storage_memory())]
+#[tokio::test(flavor = "multi_thread")]
+async fn namespace_storage_stats(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage.clone());
+    let first = create_table(&client, "namespace_storage_stats_1").await?;
+    let second = create_table(&client, "namespace_storage_stats_2").await?;
+    insert_and_dump(
+        &first,
+        vec!["Alice", "Bob", "Charlie", "David"],
+        vec![20, 21, 22, 23],
+    )
+    .await?;
+    insert_and_dump(
+        &second,
+        vec!["Eve", "Frank", "Grace", "Heidi", "Ivan", "Judy"],
+        vec![24, 25, 26, 27, 28, 29],
+    )
+    .await?;
+    second.delete(&col("age").eq(lit(24))).await?;
+
+    let first_stats = first.storage_stats().await?;
+    let second_stats = second.storage_stats().await?;
+    let stats = client.namespace_storage_stats("stats_namespace").await?;
+    assert_eq!(
+        stats.total_bytes,
+        first_stats.total_bytes + second_stats.total_bytes
+    );
+    assert_eq!(stats.data_file_count, 2);
+    assert_eq!(
+        stats.average_data_file_bytes,
+        (first_stats.data_file_bytes + second_stats.data_file_bytes) / 2
+    );
+    assert_eq!(stats.deleted_row_bytes, second_stats.deleted_row_bytes);
+    assert!(stats.deleted_row_bytes > 0);
+
+    assert!(client.namespace_storage_stats("missing").await.is_err());
+
+    Ok(())
+}
@@ -0,0 +1,313 @@
+use arrow::array::{Int64Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use indexlake::storage::Storage;
+use indexlake::table::{Table, TableConfig, TableCreation};
+use indexlake::{ILResult, LakeClient};
+use indexlake_integration_tests::{
+    MinioTestContext, catalog_sqlite, init_env_logger, utils::full_table_scan,
+};
+use opendal::raw::*;
+use opendal::{Buffer, Error, ErrorKind, Metadata, Result};
+use parquet::arrow::async_writer::AsyncFileWriter;
+use std::collections::HashMap;
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicUsize, Ordering},
+};
+
+/// Records the parts written to the service for each data file, and fails the writes of data
+/// files past a number of parts.
+#[derive(Debug)]
+struct PartRecorder {
+    parts: Mutex<Vec<(String, usize)>>,
+    aborted: Mutex<Vec<String>>,
+    fail_after_parts: AtomicUsize,
+}
+
+impl PartRecorder {
+    fn new() -> Arc<Self> {
+        Arc::new(Self {
+            parts: Mutex::new(Vec::new()),
+            aborted: Mutex::new(Vec::new()),
+            fail_after_parts: AtomicUsize::new(usize::MAX),
+        })
+    }
+
+    fn layer(
+        self: &Arc<Self>,
+    ) -> impl Fn(opendal::Operator) -> opendal::Operator + Send + Sync + 'static {
+        let recorder = self.clone();
+        move |op| {
+            op.layer(PartRecorderLayer {
+                recorder: recorder.clone(),
+            })
+        }
+    }
+
+    /// Sizes of the parts written to data files.
+    fn data_file_parts(&self) -> Vec<usize> {
+        self.parts
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(path, _)| path.ends_with(".parquet"))
+            .map(|(_, size)| *size)
+            .collect()
+    }
+
+    fn aborted(&self) -> Vec<String> {
+        self.aborted.lock().unwrap().clone()
+    }
+}
+
+struct PartRecorderLayer {
+    recorder: Arc<PartRecorder>,
+}
+
+impl<A: Access> Layer<A> for PartRecorderLayer {
+    type LayeredAccess = PartRecorderAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccess {
+        PartRecorderAccessor {
+            inner,
+            recorder: self.recorder.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PartRecorderAccessor<A> {
+    inner: A,
+    recorder: Arc<PartRecorder>,
+}
+
+impl<A: Access> LayeredAccess for PartRecorderAccessor<A> {
+    type Inner = A;
+    type Reader = A::Reader;
+    type BlockingReader = A::BlockingReader;
+    type Writer = PartRecorderWriter<A::Writer>;
+    type BlockingWriter = A::BlockingWriter;
+    type Lister = A::Lister;
+    type BlockingLister = A::BlockingLister;
+    type Deleter = A::Deleter;
+    type BlockingDeleter = A::BlockingDeleter;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.inner.read(path, args).await
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let (rp, writer) = self.inner.write(path, args).await?;
+        Ok((
+            rp,
+            PartRecorderWriter {
+                inner: writer,
+                path: path.to_string(),
+                parts: 0,
+                recorder: self.recorder.clone(),
+            },
+        ))
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Lister)> {
+        self.inner.list(path, args).await
+    }
+
+    async fn delete(&self) -> Result<(RpDelete, Self::Deleter)> {
+        self.inner.delete().await
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.inner.blocking_read(path, args)
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.inner.blocking_write(path, args)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingLister)> {
+        self.inner.blocking_list(path, args)
+    }
+
+    fn blocking_delete(&self) -> Result<(RpDelete, Self::BlockingDeleter)> {
+        self.inner.blocking_delete()
+    }
+}
+
+struct PartRecorderWriter<W> {
+    inner: W,
+    path: String,
+    parts: usize,
+    recorder: Arc<PartRecorder>,
+}
+
+impl<W: oio::Write> oio::Write for PartRecorderWriter<W> {
+    async fn write(&mut self, bs: Buffer) -> Result<()> {
+        if self.path.ends_with(".parquet")
+            && self.parts >= self.recorder.fail_after_parts.load(Ordering::SeqCst)
+        {
+            return Err(Error::new(
+                ErrorKind::PermissionDenied,
+                "injected part upload failure",
+            ));
+        }
+        self.parts += 1;
+        self.recorder
+            .parts
+            .lock()
+            .unwrap()
+            .push((self.path.clone(), bs.len()));
+        self.inner.write(bs).await
+    }
+
+    async fn close(&mut self) -> Result<Metadata> {
+        self.inner.close().await
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        self.recorder
+            .aborted
+            .lock()
+            .unwrap()
+            .push(self.path.clone());
+        self.inner.abort().await
+    }
+}
+
+async fn create_table(client: &LakeClient, table_name: &str) -> ILResult<Table> {
+    client
+        .create_namespace("test_namespace", HashMap::new())
+        .await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: table_schema(),
+            config: TableConfig {
+                inline_row_count_limit: 1000,
+                parquet_row_group_size: 100,
+                data_file_part_size: 4096,
+                ..Default::default()
+            },
+        })
+        .await?;
+    client.load_table("test_namespace", table_name).await
+}
+
+fn table_schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, false),
+    ]))
+}
+
+fn record_batch() -> ILResult<RecordBatch> {
+    let ids = (0..1000).collect::<Vec<i64>>();
+    let names = ids
+        .iter()
+        .map(|id| format!("{id:0>64}"))
+        .collect::<Vec<_>>();
+    Ok(RecordBatch::try_new(
+        table_schema(),
+        vec![
+            Arc::new(Int64Array::from(ids)),
+            Arc::new(StringArray::from(names)),
+        ],
+    )?)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn data_file_written_in_parts() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let recorder = PartRecorder::new();
+    let storage = Storage::with_layer(Storage::new_memory(), recorder.layer());
+    let client = LakeClient::new(catalog_sqlite(), Arc::new(storage));
+    let table = create_table(&client, "data_file_written_in_parts").await?;
+
+    table.insert(&record_batch()?).await?;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+
+    // Every part but the last one is exactly the part size
+    let parts = recorder.data_file_parts();
+    assert!(parts.len() > 10);
+    let (last, full) = parts.split_last().unwrap();
+    assert!(full.iter().all(|size| *size == 4096));
+    assert!(*last <= 4096);
+
+    let table_str = full_table_scan(&table).await?;
+    assert_eq!(table_str.lines().count(), 1000 + 4);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn aborted_data_file_is_cleaned_up() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    // File systems write in place, the partial file must be removed on abort
+    let storage_root = format!(
+        "{}/tmp/storage_write/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        uuid::Uuid::new_v4()
+    );
+    let recorder = PartRecorder::new();
+    recorder.fail_after_parts.store(2, Ordering::SeqCst);
+    let storage = Arc::new(Storage::with_layer(
+        Storage::new_fs(storage_root),
+        recorder.layer(),
+    ));
+    let client = LakeClient::new(catalog_sqlite(), storage.clone());
+    let table = create_table(&client, "aborted_data_file_is_cleaned_up").await?;
+
+    table.insert(&record_batch()?).await?;
+    // wait for dump task to fail
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+
+    assert_eq!(recorder.data_file_parts().len(), 2);
+    assert_eq!(recorder.aborted().len(), 1);
+    assert!(storage.list_files("").await?.is_empty());
+
+    // The rows stay inline
+    let table_str = full_table_scan(&table).await?;
+    assert_eq!(table_str.lines().count(), 1000 + 4);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn s3_multipart_upload() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let minio = MinioTestContext::setup();
+    let storage = minio.storage();
+    let part_size = 5 * 1024 * 1024;
+    let chunk = bytes::Bytes::from(vec![7u8; 1024 * 1024]);
+
+    let mut output_file = storage
+        .create_file_with_part_size("multipart/complete.bin", part_size)
+        .await?;
+    for _ in 0..12 {
+        AsyncFileWriter::write(&mut output_file, chunk.clone()).await?;
+    }
+    AsyncFileWriter::complete(&mut output_file).await?;
+    let input_file = storage.open_file("multipart/complete.bin").await?;
+    assert_eq!(input_file.file_size_bytes().await?, 12 * 1024 * 1024);
+
+    // Parts already uploaded are dropped along with the upload
+    let mut output_file = storage
+        .create_file_with_part_size("multipart/aborted.bin", part_size)
+        .await?;
+    for _ in 0..7 {
+        AsyncFileWriter::write(&mut output_file, chunk.clone()).await?;
+    }
+    output_file.abort().await?;
+    assert!(!storage.exists("multipart/aborted.bin").await?);
+
+    Ok(())
+}
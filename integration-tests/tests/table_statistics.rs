@@ -0,0 +1,159 @@
+use arrow::array::{Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use indexlake::catalog::Scalar;
+use indexlake::expr::{col, lit};
+use indexlake::table::{ColumnStatistics, CompactOptions};
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::{counted_storage, prepare_testing_table};
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger,
+};
+use std::sync::Arc;
+
+fn age_statistics(min: i32, max: i32) -> ColumnStatistics {
+    ColumnStatistics {
+        min: Some(Scalar::Int32(Some(min))),
+        max: Some(Scalar::Int32(Some(max))),
+        null_count: Some(0),
+        distinct_count: None,
+    }
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn table_statistics(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage.clone());
+    // Alice, Bob and Charlie aged 20 to 22 in a data file, David aged 23 inline
+    let table = prepare_testing_table(&client, "table_statistics").await?;
+
+    storage.reset_read_stats();
+    let stats = table.statistics().await?;
+    assert!(storage.read_stats().unwrap().opened_paths.is_empty());
+    assert_eq!(stats.row_count, 4);
+    assert_eq!(stats.data_file_count, 1);
+    assert_eq!(stats.inline_row_count, 1);
+    assert_eq!(
+        stats.total_bytes,
+        table.storage_stats().await?.data_file_bytes
+    );
+    assert_eq!(stats.columns["age"], age_statistics(20, 23));
+    assert_eq!(
+        stats.columns["name"],
+        ColumnStatistics {
+            min: Some(Scalar::Utf8(Some("Alice".to_string()))),
+            max: Some(Scalar::Utf8(Some("David".to_string()))),
+            null_count: Some(0),
+            distinct_count: None,
+        }
+    );
+    assert_eq!(stats.columns.len(), 2);
+
+    // a second data file of David, Eve and Frank
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("name", DataType::Utf8, false),
+        Field::new("age", DataType::Int32, false),
+    ]));
+    table
+        .insert(&RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from(vec!["Eve", "Frank"])),
+                Arc::new(Int32Array::from(vec![24, 25])),
+            ],
+        )?)
+        .await?;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+
+    // deleted rows leave the row count, the bounds keep them until compacted
+    table
+        .delete(&col("name").eq(lit("Alice".to_string())))
+        .await?;
+    let stats = table.statistics().await?;
+    assert_eq!(stats.row_count, 5);
+    assert_eq!(stats.data_file_count, 2);
+    assert_eq!(stats.inline_row_count, 0);
+    assert_eq!(stats.columns["age"], age_statistics(20, 25));
+
+    let report = table.compact(CompactOptions::default()).await?;
+    assert_eq!(report.written_files, 1);
+    let stats = table.statistics().await?;
+    assert_eq!(stats.row_count, 5);
+    assert_eq!(stats.data_file_count, 1);
+    assert_eq!(stats.total_bytes, report.bytes_after);
+    assert_eq!(stats.columns["age"], age_statistics(21, 25));
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn compute_table_statistics(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let table = prepare_testing_table(&client, "compute_table_statistics").await?;
+    // 500 names and 50 ages aged 30 to 79 repeated over 2000 rows
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("name", DataType::Utf8, false),
+        Field::new("age", DataType::Int32, false),
+    ]));
+    table
+        .insert(&RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from_iter_values(
+                    (0..2000).map(|i| format!("name_{}", i % 500)),
+                )),
+                Arc::new(Int32Array::from_iter_values((0..2000).map(|i| i % 50 + 30))),
+            ],
+        )?)
+        .await?;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    table
+        .delete(&col("name").eq(lit("Alice".to_string())))
+        .await?;
+
+    // the scanned statistics leave out deleted rows and estimate the distinct counts
+    let stats = table.compute_statistics().await?;
+    assert_eq!(stats.row_count, 2003);
+    let age = &stats.columns["age"];
+    assert_eq!(age.min, Some(Scalar::Int32(Some(21))));
+    assert_eq!(age.max, Some(Scalar::Int32(Some(79))));
+    assert_eq!(age.null_count, Some(0));
+    let age_distinct = age.distinct_count.unwrap();
+    assert!((51..=55).contains(&age_distinct), "{age_distinct}");
+    let name_distinct = stats.columns["name"].distinct_count.unwrap();
+    assert!((478..=528).contains(&name_distinct), "{name_distinct}");
+
+    // the catalog statistics are left as they were
+    let stats = table.statistics().await?;
+    assert_eq!(stats.columns["age"], age_statistics(20, 79));
+
+    Ok(())
+}
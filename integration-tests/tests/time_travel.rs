@@ -0,0 +1,349 @@
+use arrow::array::{Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use indexlake::expr::{col, lit};
+use indexlake::table::{CompactOptions, SnapshotOperation, TableConfig, TableCreation, TableScan};
+use indexlake::{ILError, LakeClient, catalog::Catalog, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::{
+    catalog_mariadb, catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite,
+    init_env_logger, storage_fs, storage_s3,
+};
+use indexlake_integration_tests::{
+    data::{create_namespace_if_not_exists, prepare_testing_table},
+    utils::{full_table_scan, table_scan, table_scan_as_of},
+};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn scan_as_of_snapshot(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let table = prepare_testing_table(&client, "scan_as_of_snapshot").await?;
+    let snapshots = table.snapshots().await?;
+    // table creation and the insert
+    assert_eq!(snapshots.len(), 2);
+    let inserted = snapshots[1].snapshot_id;
+    let inserted_str = full_table_scan(&table).await?;
+
+    table
+        .update(
+            &col("name").eq(lit("Alice".to_string())),
+            vec![("age".to_string(), lit(30i32))],
+        )
+        .await?;
+    table
+        .delete(&col("name").eq(lit("David".to_string())))
+        .await?;
+    table
+        .insert(&RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new("name", DataType::Utf8, false),
+                Field::new("age", DataType::Int32, false),
+            ])),
+            vec![
+                Arc::new(StringArray::from(vec!["Eve"])),
+                Arc::new(Int32Array::from(vec![24])),
+            ],
+        )?)
+        .await?;
+    let snapshots = table.snapshots().await?;
+    assert_eq!(snapshots.len(), 5);
+    let updated = snapshots[2].snapshot_id;
+    let current_str = full_table_scan(&table).await?;
+
+    assert_eq!(
+        table_scan_as_of(&table, inserted, TableScan::default()).await?,
+        inserted_str
+    );
+    let table_str = table_scan_as_of(&table, updated, TableScan::default()).await?;
+    println!("{}", table_str);
+    assert_eq!(
+        table_str,
+        r#"+-------------------+---------+-----+
+| _indexlake_row_id | name    | age |
++-------------------+---------+-----+
+| 1                 | Alice   | 30  |
+| 2                 | Bob     | 21  |
+| 3                 | Charlie | 22  |
+| 4                 | David   | 23  |
++-------------------+---------+-----+"#,
+    );
+    let table_str = table_scan_as_of(
+        &table,
+        inserted,
+        TableScan::default().with_filters(vec![col("age").gt(lit(21i32))]),
+    )
+    .await?;
+    assert_eq!(
+        table_str,
+        r#"+-------------------+---------+-----+
+| _indexlake_row_id | name    | age |
++-------------------+---------+-----+
+| 3                 | Charlie | 22  |
+| 4                 | David   | 23  |
++-------------------+---------+-----+"#,
+    );
+
+    // The latest snapshot reads as the table
+    let stream = table
+        .scan_as_of_timestamp(SystemTime::now(), TableScan::default())
+        .await?;
+    assert_eq!(
+        futures::TryStreamExt::try_collect::<Vec<_>>(stream)
+            .await?
+            .iter()
+            .map(|batch| batch.num_rows())
+            .sum::<usize>(),
+        4
+    );
+
+    table.compact(CompactOptions::default()).await?;
+    table.truncate().await?;
+    assert_eq!(
+        table_scan_as_of(&table, inserted, TableScan::default()).await?,
+        inserted_str
+    );
+    let latest = table.snapshots().await?.last().unwrap().snapshot_id;
+    assert_eq!(
+        table_scan_as_of(&table, latest - 1, TableScan::default()).await?,
+        current_str
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn vacuum_expires_snapshots() -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let home = format!(
+        "{}/tmp/time_travel_storage/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        uuid::Uuid::new_v4()
+    );
+    let client = LakeClient::new(catalog_sqlite(), Arc::new(Storage::new_fs(home)));
+    let table = prepare_testing_table(&client, "vacuum_expires_snapshots").await?;
+    table.delete(&col("age").lt(lit(22i32))).await?;
+    let snapshots = table.snapshots().await?;
+    assert_eq!(snapshots.len(), 3);
+
+    // rows deleted from data files are still read by the earlier snapshots
+    table.compact(CompactOptions::default()).await?;
+    let report = table.vacuum(Duration::from_secs(3600)).await?;
+    assert!(report.expired_snapshots.is_empty());
+    let table_str =
+        table_scan_as_of(&table, snapshots[1].snapshot_id, TableScan::default()).await?;
+    assert!(table_str.contains("Alice"));
+
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    let report = table.vacuum(Duration::from_secs(1)).await?;
+    assert_eq!(
+        report.expired_snapshots,
+        vec![snapshots[0].snapshot_id, snapshots[1].snapshot_id]
+    );
+    assert_eq!(table.snapshots().await?, snapshots[2..].to_vec());
+
+    let result = table
+        .scan_as_of(snapshots[1].snapshot_id, TableScan::default())
+        .await;
+    assert!(matches!(result, Err(ILError::SnapshotExpired(_))));
+    let result = table
+        .scan_as_of(snapshots[0].snapshot_id, TableScan::default())
+        .await;
+    assert!(matches!(result, Err(ILError::SnapshotExpired(_))));
+    let result = table
+        .scan_as_of_timestamp(snapshots[1].timestamp, TableScan::default())
+        .await;
+    assert!(matches!(result, Err(ILError::SnapshotExpired(_))));
+    let result = table
+        .scan_as_of(snapshots[2].snapshot_id + 100, TableScan::default())
+        .await;
+    assert!(matches!(result, Err(ILError::InvalidInput(_))));
+    assert_eq!(
+        table_scan_as_of(&table, snapshots[2].snapshot_id, TableScan::default()).await?,
+        full_table_scan(&table).await?
+    );
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn snapshot_operations(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let table = prepare_testing_table(&client, "snapshot_operations").await?;
+    let inserted_str = full_table_scan(&table).await?;
+    table
+        .update(
+            &col("age").lt(lit(22i32)),
+            vec![("age".to_string(), lit(30i32))],
+        )
+        .await?;
+    table
+        .delete(&col("name").eq(lit("David".to_string())))
+        .await?;
+    table.truncate().await?;
+
+    let snapshots = table.snapshots().await?;
+    let history = snapshots
+        .iter()
+        .map(|snapshot| {
+            (
+                snapshot.operation,
+                snapshot.added_rows,
+                snapshot.updated_rows,
+                snapshot.removed_rows,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        history,
+        vec![
+            (Some(SnapshotOperation::CreateTable), 0, 0, 0),
+            (Some(SnapshotOperation::Insert), 4, 0, 0),
+            (Some(SnapshotOperation::Update), 0, 2, 0),
+            (Some(SnapshotOperation::Delete), 0, 0, 1),
+            (Some(SnapshotOperation::Truncate), 0, 0, 3),
+        ]
+    );
+
+    // scans read earlier snapshots by id or by timestamp
+    let scan = TableScan::default()
+        .with_filters(vec![col("age").gt(lit(21i32))])
+        .at_snapshot(snapshots[2].snapshot_id);
+    assert_eq!(
+        table_scan(&table, scan).await?,
+        r#"+-------------------+---------+-----+
+| _indexlake_row_id | name    | age |
++-------------------+---------+-----+
+| 1                 | Alice   | 30  |
+| 2                 | Bob     | 30  |
+| 3                 | Charlie | 22  |
+| 4                 | David   | 23  |
++-------------------+---------+-----+"#,
+    );
+    let scan = TableScan::default().at_timestamp(snapshots[1].timestamp);
+    assert_eq!(table_scan(&table, scan).await?, inserted_str);
+    let scan = TableScan::default().at_timestamp(SystemTime::now());
+    assert_eq!(table_scan(&table, scan).await?.lines().count(), 4);
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn snapshot_retention(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("name", DataType::Utf8, false),
+        Field::new("age", DataType::Int32, false),
+    ]));
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "snapshot_retention".to_string(),
+            schema: schema.clone(),
+            config: TableConfig {
+                snapshot_retention: Some(Duration::from_secs(2)),
+                ..Default::default()
+            },
+        })
+        .await?;
+    let table = client
+        .load_table("test_namespace", "snapshot_retention")
+        .await?;
+    let insert = |name: &str, age: i32| {
+        RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec![name.to_string()])),
+                Arc::new(Int32Array::from(vec![age])),
+            ],
+        )
+    };
+    table.insert(&insert("Alice", 20)?).await?;
+    table
+        .update(
+            &col("name").eq(lit("Alice".to_string())),
+            vec![("age".to_string(), lit(30i32))],
+        )
+        .await?;
+    let snapshots = table.snapshots().await?;
+    assert_eq!(snapshots.len(), 3);
+
+    // the next commits expire the snapshots committed before the retention
+    tokio::time::sleep(Duration::from_secs(3)).await;
+    table.insert(&insert("Bob", 21)?).await?;
+    table
+        .update(
+            &col("name").eq(lit("Bob".to_string())),
+            vec![("age".to_string(), lit(31i32))],
+        )
+        .await?;
+    let retained = table.snapshots().await?;
+    assert_eq!(retained.len(), 2);
+    assert!(retained[0].snapshot_id > snapshots[2].snapshot_id);
+
+    for snapshot in &snapshots {
+        let scan = TableScan::default().at_snapshot(snapshot.snapshot_id);
+        let result = table.scan(scan).await;
+        assert!(matches!(result, Err(ILError::SnapshotExpired(_))));
+    }
+    let scan = TableScan::default().at_timestamp(snapshots[1].timestamp);
+    assert!(matches!(
+        table.scan(scan).await,
+        Err(ILError::SnapshotExpired(_))
+    ));
+    let scan = TableScan::default().at_snapshot(retained[0].snapshot_id);
+    assert_eq!(
+        table_scan(&table, scan).await?,
+        r#"+-------------------+-------+-----+
+| _indexlake_row_id | name  | age |
++-------------------+-------+-----+
+| 1                 | Alice | 30  |
+| 2                 | Bob   | 21  |
++-------------------+-------+-----+"#,
+    );
+
+    Ok(())
+}
@@ -0,0 +1,192 @@
+use arrow::array::{Array, Int64Array, RecordBatch, TimestampMicrosecondArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use futures::TryStreamExt;
+use indexlake::catalog::Scalar;
+use indexlake::expr::{Expr, col, lit};
+use indexlake::table::{Table, TableConfig, TableCreation, TableScan};
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::{
+    counted_storage, create_namespace_if_not_exists, data_files_opened,
+};
+use indexlake_integration_tests::utils::sort_record_batches;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger,
+};
+use std::sync::Arc;
+
+/// 2024-01-01T00:00:00Z in seconds.
+const NEW_YEAR: i64 = 1_704_067_200;
+const HOUR: i64 = 3600;
+
+fn event_time_type(tz: &str) -> DataType {
+    DataType::Timestamp(TimeUnit::Microsecond, Some(tz.into()))
+}
+
+fn table_schema(tz: &str) -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("event_time", event_time_type(tz), true),
+    ]))
+}
+
+/// Rows of ids `ids` with events `hours` after new year in UTC, in the time zone `tz`.
+fn event_batch(
+    ids: Vec<i64>,
+    hours: Vec<i64>,
+    tz: &str,
+) -> Result<RecordBatch, Box<dyn std::error::Error>> {
+    let event_times = hours
+        .iter()
+        .map(|hour| (NEW_YEAR + hour * HOUR) * 1_000_000)
+        .collect::<Vec<_>>();
+    Ok(RecordBatch::try_new(
+        table_schema(tz),
+        vec![
+            Arc::new(Int64Array::from(ids)),
+            Arc::new(TimestampMicrosecondArray::from(event_times).with_timezone(tz)),
+        ],
+    )?)
+}
+
+/// Events from `from` hours after new year in UTC until before `to` hours, with literals in
+/// seconds and nanoseconds.
+fn utc_range(from: i64, to: i64) -> Expr {
+    let start = Scalar::Timestamp(
+        Some(NEW_YEAR + from * HOUR),
+        TimeUnit::Second,
+        Some("UTC".to_string()),
+    );
+    let end = Scalar::Timestamp(
+        Some((NEW_YEAR + to * HOUR) * 1_000_000_000),
+        TimeUnit::Nanosecond,
+        Some("UTC".to_string()),
+    );
+    col("event_time")
+        .gt_eq(lit(start))
+        .and(col("event_time").lt(lit(end)))
+}
+
+async fn scan_sorted(
+    table: &Table,
+    scan: TableScan,
+) -> Result<RecordBatch, Box<dyn std::error::Error>> {
+    let batches = table.scan(scan).await?.try_collect::<Vec<_>>().await?;
+    Ok(sort_record_batches(&batches, "id")?)
+}
+
+async fn scan_ids(table: &Table, filter: Expr) -> Result<Vec<i64>, Box<dyn std::error::Error>> {
+    let scan = TableScan::default()
+        .with_columns(Some(vec!["id".to_string()]))
+        .with_filters(vec![filter]);
+    let batch = scan_sorted(table, scan).await?;
+    Ok(batch
+        .column(0)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap()
+        .values()
+        .to_vec())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn timestamp_columns(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage.clone());
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "timestamp_columns".to_string(),
+            schema: table_schema("+02:00"),
+            config: TableConfig::default(),
+        })
+        .await?;
+    let table = client
+        .load_table("test_namespace", "timestamp_columns")
+        .await?;
+    // the time zone is kept by the catalog
+    assert_eq!(
+        table.schema.field_with_name("event_time")?.data_type(),
+        &event_time_type("+02:00")
+    );
+
+    let columns = Some(vec!["id".to_string(), "event_time".to_string()]);
+    let scan = TableScan::default().with_columns(columns);
+    let early = event_batch(vec![1, 2, 3, 4], vec![0, 1, 2, 3], "+02:00")?;
+    table.insert(&early).await?;
+    // inline rows keep their time zone and are filtered and counted by UTC ranges
+    assert_eq!(scan_sorted(&table, scan.clone()).await?, early);
+    assert_eq!(scan_ids(&table, utc_range(1, 3)).await?, vec![2, 3]);
+    assert_eq!(table.count(Some(utc_range(1, 3))).await?, 2);
+
+    // rows flushed into a data file
+    table.flush().await?;
+    assert_eq!(scan_sorted(&table, scan.clone()).await?, early);
+    assert_eq!(scan_ids(&table, utc_range(1, 3)).await?, vec![2, 3]);
+
+    // rows in another time zone are taken in the time zone of the column
+    let late = event_batch(vec![5, 6, 7], vec![10, 11, 12], "America/New_York")?;
+    table.insert(&late).await?;
+    table.flush().await?;
+    let scanned = scan_sorted(&table, scan).await?;
+    assert_eq!(scanned.column(1).data_type(), &event_time_type("+02:00"));
+    let late_times = late
+        .column(1)
+        .as_any()
+        .downcast_ref::<TimestampMicrosecondArray>();
+    let scanned_times = scanned
+        .column(1)
+        .as_any()
+        .downcast_ref::<TimestampMicrosecondArray>();
+    assert_eq!(
+        scanned_times.unwrap().values()[4..],
+        late_times.unwrap().values()[..]
+    );
+
+    // ranges in UTC prune the data file of the other rows by its statistics
+    storage.reset_read_stats();
+    assert_eq!(scan_ids(&table, utc_range(10, 12)).await?, vec![5, 6]);
+    assert_eq!(data_files_opened(&storage), 1);
+    storage.reset_read_stats();
+    assert_eq!(scan_ids(&table, utc_range(3, 11)).await?, vec![4, 5]);
+    assert_eq!(data_files_opened(&storage), 2);
+
+    // literals in a third time zone compare as the same instants
+    storage.reset_read_stats();
+    let noon_in_kolkata = Scalar::Timestamp(
+        Some((NEW_YEAR + 11 * HOUR) * 1000),
+        TimeUnit::Millisecond,
+        Some("+05:30".to_string()),
+    );
+    assert_eq!(
+        scan_ids(&table, col("event_time").eq(lit(noon_in_kolkata))).await?,
+        vec![6]
+    );
+    assert_eq!(data_files_opened(&storage), 1);
+    assert_eq!(
+        table
+            .count(Some(col("event_time").gt(lit(Scalar::Timestamp(
+                Some(NEW_YEAR + 2 * HOUR),
+                TimeUnit::Second,
+                None,
+            )))))
+            .await?,
+        4
+    );
+
+    Ok(())
+}
@@ -1,20 +1,33 @@
 use arrow::array::{Int64Array, RecordBatch, StringArray};
 use arrow::datatypes::{DataType, Field, Schema};
+use futures::TryStreamExt;
+use indexlake::expr::{col, lit};
+use indexlake::index::Index;
 use indexlake::{
     LakeClient,
     catalog::Catalog,
     storage::Storage,
-    table::{TableConfig, TableCreation},
+    table::{IndexCreation, Table, TableConfig, TableCreation, TableScan, TruncateReport},
 };
-use indexlake_integration_tests::utils::full_table_scan;
+use indexlake_index_hash::{HashIndex, HashIndexParams};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::{counted_storage, create_namespace_if_not_exists};
+use indexlake_integration_tests::utils::{full_table_scan, table_scan};
 use indexlake_integration_tests::{
-    catalog_postgres, catalog_sqlite, init_env_logger, storage_fs, storage_s3,
+    catalog_mariadb, catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite,
+    init_env_logger, storage_fs, storage_s3,
 };
+use std::collections::HashMap;
 use std::sync::Arc;
 
 #[rstest::rstest]
 #[case(async { catalog_sqlite() }, storage_fs())]
 #[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
 #[tokio::test(flavor = "multi_thread")]
 async fn truncate_table(
     #[future(awt)]
@@ -27,7 +40,9 @@ async fn truncate_table(
     let client = LakeClient::new(catalog, storage);
 
     let namespace_name = "test_namespace";
-    client.create_namespace(namespace_name).await?;
+    client
+        .create_namespace(namespace_name, HashMap::new())
+        .await?;
 
     let table_schema = Arc::new(Schema::new(vec![
         Field::new("id", DataType::Int64, false),
@@ -54,7 +69,14 @@ async fn truncate_table(
 
     table.insert(&record_batch).await?;
 
-    table.truncate().await?;
+    let report = table.truncate().await?;
+    assert_eq!(
+        report,
+        TruncateReport {
+            removed_rows: 2,
+            removed_bytes: 0,
+        }
+    );
 
     let table_str = full_table_scan(&table).await?;
     println!("{}", table_str);
@@ -68,3 +90,189 @@ async fn truncate_table(
 
     Ok(())
 }
+
+fn batch(schema: &Arc<Schema>, ids: Vec<i64>) -> Result<RecordBatch, arrow::error::ArrowError> {
+    let names = ids.iter().map(|id| format!("n{id}")).collect::<Vec<_>>();
+    RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(Int64Array::from(ids)),
+            Arc::new(StringArray::from(names)),
+        ],
+    )
+}
+
+async fn row_count(table: &Table) -> Result<usize, Box<dyn std::error::Error>> {
+    let batches = table
+        .scan(TableScan::default())
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    Ok(batches.iter().map(|batch| batch.num_rows()).sum())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, counted_storage())]
+#[case(async { catalog_postgres().await }, counted_storage())]
+#[case(async { catalog_mysql().await }, counted_storage())]
+#[case(async { catalog_memory() }, counted_storage())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, counted_storage()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn truncate_table_with_index(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage.clone());
+    client.register_index(Arc::new(HashIndex))?;
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, false),
+    ]));
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "truncate_table_with_index".to_string(),
+            schema: schema.clone(),
+            config: TableConfig {
+                inline_row_count_limit: 3,
+                ..Default::default()
+            },
+        })
+        .await?;
+    let mut table = client
+        .load_table("test_namespace", "truncate_table_with_index")
+        .await?;
+    table
+        .create_index(IndexCreation {
+            name: "name_index".to_string(),
+            kind: HashIndex.kind().to_string(),
+            key_columns: vec!["name".to_string()],
+            include_columns: vec![],
+            params: Arc::new(HashIndexParams::default()),
+            where_predicate: None,
+        })
+        .await?;
+    for ids in [vec![1, 2, 3], vec![4, 5, 6], vec![7]] {
+        table.insert(&batch(&schema, ids)?).await?;
+        // wait for dump task to finish
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    }
+    let data_file_bytes = storage
+        .list_files(&table.table_dir())
+        .await?
+        .iter()
+        .filter(|file| file.relative_path.ends_with(".parquet"))
+        .map(|file| file.size_bytes)
+        .sum::<u64>();
+    assert!(data_file_bytes > 0);
+
+    // no data file is read
+    storage.reset_read_stats();
+    let report = table.truncate().await?;
+    assert_eq!(
+        report,
+        TruncateReport {
+            removed_rows: 7,
+            removed_bytes: data_file_bytes,
+        }
+    );
+    assert!(
+        storage
+            .read_stats()
+            .unwrap()
+            .opened_paths
+            .iter()
+            .all(|path| !path.ends_with(".parquet"))
+    );
+    assert_eq!(row_count(&table).await?, 0);
+
+    // the table keeps its id and its emptied index, which indexes new rows
+    let reloaded = client
+        .load_table("test_namespace", "truncate_table_with_index")
+        .await?;
+    assert_eq!(reloaded.table_id, table.table_id);
+    assert!(reloaded.indexes.contains_key("name_index"));
+    for ids in [vec![11, 12, 13], vec![14]] {
+        reloaded.insert(&batch(&schema, ids)?).await?;
+        // wait for dump task to finish
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    }
+    let scan = TableScan::default().with_filters(vec![col("name").eq(lit("n12".to_string()))]);
+    let table_str = table_scan(&reloaded, scan).await?;
+    assert_eq!(table_str.lines().count(), 5);
+    assert!(table_str.contains("| 12 | n12  |"));
+    let scan = TableScan::default().with_filters(vec![col("name").eq(lit("n2".to_string()))]);
+    assert_eq!(table_scan(&reloaded, scan).await?.lines().count(), 4);
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn truncate_during_inserts(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, false),
+    ]));
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "truncate_during_inserts".to_string(),
+            schema: schema.clone(),
+            config: TableConfig {
+                inline_row_count_limit: 10,
+                ..Default::default()
+            },
+        })
+        .await?;
+    let table = client
+        .load_table("test_namespace", "truncate_during_inserts")
+        .await?;
+
+    let inserter = {
+        let table = table.clone();
+        let schema = schema.clone();
+        tokio::spawn(async move {
+            for i in 0..20 {
+                let ids = (i * 5..i * 5 + 5).collect::<Vec<_>>();
+                table.insert(&batch(&schema, ids).unwrap()).await?;
+            }
+            Ok::<_, indexlake::ILError>(())
+        })
+    };
+    let mut removed_rows = 0;
+    for _ in 0..5 {
+        removed_rows += table.truncate().await?.removed_rows;
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    inserter.await??;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+
+    // every insert is removed or kept as a whole
+    let remaining = row_count(&table).await? as u64;
+    assert_eq!(remaining % 5, 0);
+    assert_eq!(removed_rows % 5, 0);
+    assert_eq!(removed_rows + remaining, 100);
+
+    Ok(())
+}
@@ -0,0 +1,286 @@
+use arrow::array::{AsArray, Int64Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use futures::TryStreamExt;
+use indexlake::catalog::Scalar;
+use indexlake::expr::{col, lit};
+use indexlake::table::{Table, TableConfig, TableCreation, TableScan, UniqueConstraint};
+use indexlake::{ConstraintViolation, ILError, ILResult};
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::create_namespace_if_not_exists;
+use indexlake_integration_tests::{
+    catalog_mariadb, catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite,
+    init_env_logger, storage_fs, storage_s3,
+};
+use std::sync::Arc;
+
+fn users_batch(ids: Vec<i64>, emails: Vec<Option<&str>>) -> ILResult<RecordBatch> {
+    Ok(RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("email", DataType::Utf8, true),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(ids)),
+            Arc::new(StringArray::from(emails)),
+        ],
+    )?)
+}
+
+fn violation<T: std::fmt::Debug>(result: ILResult<T>) -> ConstraintViolation {
+    match result {
+        Err(ILError::ConstraintViolation(violation)) => violation,
+        other => panic!("expected a constraint violation, got {other:?}"),
+    }
+}
+
+fn email(value: &str) -> Vec<Scalar> {
+    vec![Scalar::Utf8(Some(value.to_string()))]
+}
+
+async fn create_users_table(client: &LakeClient, table_name: &str) -> ILResult<Table> {
+    create_namespace_if_not_exists(client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: Arc::new(Schema::new(vec![
+                Field::new("id", DataType::Int64, false),
+                Field::new("email", DataType::Utf8, true),
+            ])),
+            config: TableConfig {
+                inline_row_count_limit: 3,
+                primary_key: vec!["id".to_string()],
+                unique_constraints: vec![UniqueConstraint::new(
+                    "unique_email",
+                    vec!["email".to_string()],
+                )],
+                ..Default::default()
+            },
+        })
+        .await?;
+    client.load_table("test_namespace", table_name).await
+}
+
+async fn scanned_emails(table: &Table) -> ILResult<Vec<Option<String>>> {
+    let batches = table
+        .scan(TableScan::default().with_columns(Some(vec!["email".to_string()])))
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    let mut emails = batches
+        .iter()
+        .flat_map(|batch| {
+            batch
+                .column(0)
+                .as_string::<i32>()
+                .iter()
+                .map(|email| email.map(str::to_string))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    emails.sort();
+    Ok(emails)
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn unique_constraint_on_writes(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let mut table = create_users_table(&client, "unique_constraint_on_writes").await?;
+    table
+        .insert(&users_batch(
+            vec![1, 2, 3, 4],
+            vec![Some("a@x"), Some("b@x"), Some("c@x"), None],
+        )?)
+        .await?;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+
+    // keys shared within the written rows or with existing rows are reported with their values
+    let result = table
+        .insert(&users_batch(vec![5, 6], vec![Some("d@x"), Some("d@x")])?)
+        .await;
+    assert_eq!(
+        violation(result),
+        ConstraintViolation {
+            constraint: "unique_email".to_string(),
+            columns: vec!["email".to_string()],
+            row_index: 1,
+            row_id: None,
+            values: email("d@x"),
+        }
+    );
+    let result = table
+        .insert(&users_batch(vec![5, 6], vec![Some("d@x"), Some("a@x")])?)
+        .await;
+    let violation_on_existing = violation(result);
+    assert_eq!(violation_on_existing.row_index, 1);
+    assert_eq!(violation_on_existing.values, email("a@x"));
+
+    // nulls never conflict
+    table
+        .insert(&users_batch(vec![5, 6], vec![None, None])?)
+        .await?;
+
+    // upserts replacing the row holding a key keep it, other rows can not take it
+    table
+        .upsert(&users_batch(vec![1], vec![Some("a@x")])?)
+        .await?;
+    let result = table
+        .upsert(&users_batch(vec![7], vec![Some("b@x")])?)
+        .await;
+    assert_eq!(violation(result).values, email("b@x"));
+
+    // updated rows are reported with their row id, inline and dumped ones alike
+    let result = table
+        .update(
+            &col("id").eq(lit(2i64)),
+            vec![("email".to_string(), lit("c@x".to_string()))],
+        )
+        .await;
+    assert_eq!(violation(result).row_id, Some(2));
+    let result = table
+        .update(
+            &col("id").eq(lit(5i64)),
+            vec![("email".to_string(), lit("c@x".to_string()))],
+        )
+        .await;
+    assert_eq!(violation(result).row_id, Some(5));
+
+    // keys changed by updates or removed by deletes are free to take
+    table
+        .update(
+            &col("id").eq(lit(2i64)),
+            vec![("email".to_string(), lit("e@x".to_string()))],
+        )
+        .await?;
+    table.delete(&col("id").eq(lit(3i64))).await?;
+    table
+        .insert(&users_batch(vec![8, 9], vec![Some("b@x"), Some("c@x")])?)
+        .await?;
+    assert_eq!(
+        scanned_emails(&table).await?,
+        vec![
+            None,
+            None,
+            None,
+            Some("a@x".to_string()),
+            Some("b@x".to_string()),
+            Some("c@x".to_string()),
+            Some("e@x".to_string()),
+        ]
+    );
+
+    // key columns may be renamed but not dropped
+    table.rename_column("email", "mail").await?;
+    let result = table
+        .insert(&RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new("id", DataType::Int64, false),
+                Field::new("mail", DataType::Utf8, true),
+            ])),
+            vec![
+                Arc::new(Int64Array::from(vec![10])),
+                Arc::new(StringArray::from(vec!["a@x"])),
+            ],
+        )?)
+        .await;
+    assert_eq!(violation(result).columns, vec!["mail".to_string()]);
+    assert!(table.drop_column("mail", true).await.is_err());
+
+    table.truncate().await?;
+    table
+        .upsert(&RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new("id", DataType::Int64, false),
+                Field::new("mail", DataType::Utf8, true),
+            ])),
+            vec![
+                Arc::new(Int64Array::from(vec![1])),
+                Arc::new(StringArray::from(vec!["a@x"])),
+            ],
+        )?)
+        .await?;
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn concurrent_unique_inserts(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let table = create_users_table(&client, "concurrent_unique_inserts").await?;
+
+    // of the writers of the same key only one commits
+    let mut handles = Vec::new();
+    for id in 0..4 {
+        let table = table.clone();
+        handles.push(tokio::spawn(async move {
+            table
+                .insert(&users_batch(vec![id], vec![Some("same@x")])?)
+                .await
+        }));
+    }
+    let mut succeeded = 0;
+    for handle in handles {
+        if handle.await?.is_ok() {
+            succeeded += 1;
+        }
+    }
+    assert_eq!(succeeded, 1);
+    assert_eq!(
+        scanned_emails(&table).await?,
+        vec![Some("same@x".to_string())]
+    );
+
+    // invalid constraints are rejected at creation
+    let invalid = [
+        UniqueConstraint::new("no_columns", vec![]),
+        UniqueConstraint::new("unknown", vec!["name".to_string()]),
+        UniqueConstraint::new("twice", vec!["id".to_string(), "id".to_string()]),
+    ];
+    for constraint in invalid {
+        let result = client
+            .create_table(TableCreation {
+                namespace_name: "test_namespace".to_string(),
+                table_name: "concurrent_unique_inserts_invalid".to_string(),
+                schema: Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, false)])),
+                config: TableConfig {
+                    unique_constraints: vec![constraint],
+                    ..Default::default()
+                },
+            })
+            .await;
+        assert!(matches!(result, Err(ILError::InvalidInput(_))));
+    }
+
+    Ok(())
+}
@@ -1,16 +1,28 @@
-use indexlake::expr::Expr;
+use arrow::array::{Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use indexlake::catalog::INTERNAL_ROW_ID_FIELD_NAME;
+use indexlake::expr::{Expr, col, lit};
+use indexlake::index::Index;
+use indexlake::table::{IndexCreation, Table, TableConfig, TableCreation, TableScan};
 use indexlake::{LakeClient, catalog::Catalog, catalog::Scalar, storage::Storage};
-use indexlake_integration_tests::data::prepare_testing_table;
-use indexlake_integration_tests::utils::full_table_scan;
+use indexlake_index_hash::{HashIndex, HashIndexParams};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::{create_namespace_if_not_exists, prepare_testing_table};
+use indexlake_integration_tests::utils::{full_table_scan, table_scan};
 use indexlake_integration_tests::{
-    catalog_postgres, catalog_sqlite, init_env_logger, storage_fs, storage_s3,
+    catalog_mariadb, catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite,
+    init_env_logger, storage_fs, storage_s3,
 };
-use std::collections::HashMap;
 use std::sync::Arc;
 
 #[rstest::rstest]
 #[case(async { catalog_sqlite() }, storage_fs())]
 #[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
 #[tokio::test(flavor = "multi_thread")]
 async fn update_table(
     #[future(awt)]
@@ -23,10 +35,15 @@ async fn update_table(
     let client = LakeClient::new(catalog, storage);
     let table = prepare_testing_table(&client, "update_table").await?;
 
-    let set_map = HashMap::from([("age".to_string(), Scalar::Int32(Some(30)))]);
     let condition =
         Expr::Column("name".to_string()).eq(Expr::Literal(Scalar::Utf8(Some("Alice".to_string()))));
-    table.update(set_map, &condition).await?;
+    let updated_count = table
+        .update(
+            &condition,
+            vec![("age".to_string(), Expr::Literal(Scalar::Int32(Some(30))))],
+        )
+        .await?;
+    assert_eq!(updated_count, 1);
 
     let table_str = full_table_scan(&table).await?;
     println!("{}", table_str);
@@ -44,3 +61,222 @@ async fn update_table(
 
     Ok(())
 }
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn update_table_with_expressions(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let table = prepare_testing_table(&client, "update_table_with_expressions").await?;
+    let snapshot_count = table.snapshots().await?.len();
+
+    // the filtered column is updated, rows of data files and inline rows are updated once
+    let updated_count = table
+        .update(
+            &col("age").gt_eq(lit(21i32)),
+            vec![(
+                "age".to_string(),
+                col("age").multiply(lit(2i32)).minus(lit(1i32)),
+            )],
+        )
+        .await?;
+    assert_eq!(updated_count, 3);
+    assert_eq!(
+        full_table_scan(&table).await?,
+        r#"+-------------------+---------+-----+
+| _indexlake_row_id | name    | age |
++-------------------+---------+-----+
+| 1                 | Alice   | 20  |
+| 2                 | Bob     | 41  |
+| 3                 | Charlie | 43  |
+| 4                 | David   | 45  |
++-------------------+---------+-----+"#,
+    );
+    assert_eq!(table.snapshots().await?.len(), snapshot_count + 1);
+
+    // no snapshot is committed when nothing matches
+    let updated_count = table
+        .update(
+            &col("age").gt(lit(100i32)),
+            vec![("age".to_string(), lit(0i32))],
+        )
+        .await?;
+    assert_eq!(updated_count, 0);
+    assert_eq!(table.snapshots().await?.len(), snapshot_count + 1);
+
+    // assignments are checked against the schema
+    let invalid_assignments = vec![
+        vec![("age".to_string(), lit("old".to_string()))],
+        vec![("age".to_string(), col("age").plus(lit(1i64)))],
+        vec![("email".to_string(), lit("a@b.c".to_string()))],
+        vec![
+            ("age".to_string(), lit(1i32)),
+            ("age".to_string(), lit(2i32)),
+        ],
+        vec![(INTERNAL_ROW_ID_FIELD_NAME.to_string(), lit(10i64))],
+    ];
+    for assignments in invalid_assignments {
+        let result = table.update(&col("age").gt(lit(0i32)), assignments).await;
+        assert!(result.is_err());
+    }
+
+    // nulls in a non-nullable column fail the update and leave every row in place
+    let result = table
+        .update(
+            &col("age").gt(lit(0i32)),
+            vec![("age".to_string(), Expr::Literal(Scalar::Int32(None)))],
+        )
+        .await;
+    assert!(result.is_err());
+    assert_eq!(table.snapshots().await?.len(), snapshot_count + 1);
+    assert_eq!(full_table_scan(&table).await?.lines().count(), 8);
+    assert_eq!(
+        table_scan(
+            &table,
+            TableScan::default().with_filters(vec![col("age").gt(lit(40i32))])
+        )
+        .await?
+        .lines()
+        .count(),
+        7
+    );
+
+    Ok(())
+}
+
+/// Table with a hash index on `name`, a data file of rows n0 to n2 and an inline row n3.
+async fn prepare_indexed_table(
+    client: &LakeClient,
+    table_name: &str,
+) -> Result<Table, Box<dyn std::error::Error>> {
+    create_namespace_if_not_exists(client, "test_namespace").await?;
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("name", DataType::Utf8, false),
+        Field::new("age", DataType::Int32, false),
+    ]));
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: schema.clone(),
+            config: TableConfig {
+                inline_row_count_limit: 3,
+                ..Default::default()
+            },
+        })
+        .await?;
+    let mut table = client.load_table("test_namespace", table_name).await?;
+    table
+        .create_index(IndexCreation {
+            name: "name_index".to_string(),
+            kind: HashIndex.kind().to_string(),
+            key_columns: vec!["name".to_string()],
+            include_columns: vec![],
+            params: Arc::new(HashIndexParams::default()),
+            where_predicate: None,
+        })
+        .await?;
+
+    for ages in [vec![0, 1, 2], vec![3]] {
+        let names = ages.iter().map(|age| format!("n{age}")).collect::<Vec<_>>();
+        table
+            .insert(&RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(StringArray::from(names)),
+                    Arc::new(Int32Array::from(ages)),
+                ],
+            )?)
+            .await?;
+        // wait for dump task to finish
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    }
+    Ok(table)
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn update_table_with_index(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage);
+    client.register_index(Arc::new(HashIndex))?;
+    let table = prepare_indexed_table(&client, "update_table_with_index").await?;
+
+    // the indexed column of a row in the data file and of the inline row is updated
+    let updated_count = table
+        .update(
+            &col("name")
+                .eq(lit("n1".to_string()))
+                .or(col("name").eq(lit("n3".to_string()))),
+            vec![
+                ("name".to_string(), lit("m".to_string())),
+                ("age".to_string(), col("age").plus(lit(10i32))),
+            ],
+        )
+        .await?;
+    assert_eq!(updated_count, 2);
+
+    let scan_name =
+        |name: &str| TableScan::default().with_filters(vec![col("name").eq(lit(name.to_string()))]);
+    // the index entry of the old version is ignored
+    assert_eq!(
+        table_scan(&table, scan_name("n1")).await?.lines().count(),
+        4
+    );
+    assert_eq!(
+        table_scan(&table, scan_name("n3")).await?.lines().count(),
+        4
+    );
+    let expected = r#"+-------------------+------+-----+
+| _indexlake_row_id | name | age |
++-------------------+------+-----+
+| 2                 | m    | 11  |
+| 4                 | m    | 13  |
++-------------------+------+-----+"#;
+    assert_eq!(table_scan(&table, scan_name("m")).await?, expected);
+
+    // the updated rows are indexed once dumped into a data file
+    table
+        .insert(&RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new("name", DataType::Utf8, false),
+                Field::new("age", DataType::Int32, false),
+            ])),
+            vec![
+                Arc::new(StringArray::from(vec!["n5"])),
+                Arc::new(Int32Array::from(vec![5])),
+            ],
+        )?)
+        .await?;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    assert_eq!(table_scan(&table, scan_name("m")).await?, expected);
+    assert_eq!(
+        table_scan(&table, scan_name("n1")).await?.lines().count(),
+        4
+    );
+
+    Ok(())
+}
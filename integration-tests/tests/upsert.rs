@@ -0,0 +1,463 @@
+use arrow::array::{AsArray, Int32Array, Int64Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Int64Type, Schema, SchemaRef};
+use futures::TryStreamExt;
+use indexlake::index::Index;
+use indexlake::table::{IndexCreation, Table, TableConfig, TableCreation, TableScan};
+use indexlake::{LakeClient, catalog::Catalog, storage::Storage};
+use indexlake_index_hash::{HashIndex, HashIndexParams};
+#[cfg(feature = "duckdb")]
+use indexlake_integration_tests::catalog_duckdb;
+use indexlake_integration_tests::data::{create_namespace_if_not_exists, prepare_testing_table};
+use indexlake_integration_tests::utils::full_table_scan;
+use indexlake_integration_tests::{
+    catalog_mariadb, catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite,
+    init_env_logger, storage_fs, storage_s3,
+};
+use std::sync::Arc;
+
+async fn create_upsert_table(
+    client: &LakeClient,
+    table_name: &str,
+    schema: SchemaRef,
+    primary_key: &[&str],
+) -> Result<Table, Box<dyn std::error::Error>> {
+    create_namespace_if_not_exists(client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema,
+            config: TableConfig {
+                inline_row_count_limit: 3,
+                parquet_row_group_size: 2,
+                primary_key: primary_key.iter().map(|name| name.to_string()).collect(),
+                ..Default::default()
+            },
+        })
+        .await?;
+    Ok(client.load_table("test_namespace", table_name).await?)
+}
+
+fn id_name_batch(
+    schema: &SchemaRef,
+    ids: Vec<i64>,
+    names: Vec<&str>,
+) -> Result<RecordBatch, Box<dyn std::error::Error>> {
+    Ok(RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(Int64Array::from(ids)),
+            Arc::new(StringArray::from(names)),
+        ],
+    )?)
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_mariadb().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[cfg_attr(feature = "duckdb", case(async { catalog_duckdb().await }, storage_fs()))]
+#[tokio::test(flavor = "multi_thread")]
+async fn upsert_by_primary_key(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, false),
+    ]));
+    let mut table =
+        create_upsert_table(&client, "upsert_by_primary_key", schema.clone(), &["id"]).await?;
+
+    table
+        .insert(&id_name_batch(
+            &schema,
+            vec![1, 2, 3, 4],
+            vec!["a", "b", "c", "d"],
+        )?)
+        .await?;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+
+    // Replaces a flushed row and inserts a new one, both stay inline
+    table
+        .upsert(&id_name_batch(&schema, vec![2, 5], vec!["b2", "e"])?)
+        .await?;
+    assert_eq!(
+        full_table_scan(&table).await?,
+        r#"+-------------------+----+------+
+| _indexlake_row_id | id | name |
++-------------------+----+------+
+| 1                 | 1  | a    |
+| 3                 | 3  | c    |
+| 4                 | 4  | d    |
+| 5                 | 2  | b2   |
+| 6                 | 5  | e    |
++-------------------+----+------+"#
+    );
+
+    // Replaces an inline row and a flushed row, the last duplicate of key 5 wins
+    table
+        .upsert(&id_name_batch(
+            &schema,
+            vec![5, 1, 5],
+            vec!["e2", "a2", "e3"],
+        )?)
+        .await?;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+
+    assert_eq!(
+        full_table_scan(&table).await?,
+        r#"+-------------------+----+------+
+| _indexlake_row_id | id | name |
++-------------------+----+------+
+| 3                 | 3  | c    |
+| 4                 | 4  | d    |
+| 5                 | 2  | b2   |
+| 7                 | 1  | a2   |
+| 8                 | 5  | e3   |
++-------------------+----+------+"#
+    );
+
+    let result = table.drop_column("id", false).await;
+    assert!(
+        result
+            .unwrap_err()
+            .to_string()
+            .contains("is part of the primary key")
+    );
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[tokio::test(flavor = "multi_thread")]
+async fn upsert_by_composite_key(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("name", DataType::Utf8, false),
+        Field::new("year", DataType::Int32, false),
+        Field::new("score", DataType::Int32, true),
+    ]));
+    let table = create_upsert_table(
+        &client,
+        "upsert_by_composite_key",
+        schema.clone(),
+        &["name", "year"],
+    )
+    .await?;
+
+    let batch = |names: Vec<&str>, years: Vec<i32>, scores: Vec<Option<i32>>| {
+        RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(names)),
+                Arc::new(Int32Array::from(years)),
+                Arc::new(Int32Array::from(scores)),
+            ],
+        )
+    };
+
+    table
+        .upsert(&batch(
+            vec!["Alice", "Alice", "Bob"],
+            vec![2020, 2021, 2020],
+            vec![Some(1), Some(2), Some(3)],
+        )?)
+        .await?;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+
+    // The whole row is replaced, including with nulls
+    table
+        .upsert(&batch(
+            vec!["Bob", "Alice", "Bob"],
+            vec![2021, 2021, 2021],
+            vec![Some(4), None, Some(5)],
+        )?)
+        .await?;
+
+    assert_eq!(
+        full_table_scan(&table).await?,
+        r#"+-------------------+-------+------+-------+
+| _indexlake_row_id | name  | year | score |
++-------------------+-------+------+-------+
+| 1                 | Alice | 2020 | 1     |
+| 3                 | Bob   | 2020 | 3     |
+| 4                 | Alice | 2021 |       |
+| 5                 | Bob   | 2021 | 5     |
++-------------------+-------+------+-------+"#
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn upsert_requires_primary_key() -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog_sqlite(), storage_fs());
+    let table = prepare_testing_table(&client, "upsert_requires_primary_key").await?;
+    let result = table
+        .upsert(&RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new("name", DataType::Utf8, false),
+                Field::new("age", DataType::Int32, false),
+            ])),
+            vec![
+                Arc::new(StringArray::from(vec!["Alice"])),
+                Arc::new(Int32Array::from(vec![30])),
+            ],
+        )?)
+        .await;
+    assert!(
+        result
+            .unwrap_err()
+            .to_string()
+            .contains("has no primary key")
+    );
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, true),
+        Field::new("name", DataType::Utf8, false),
+    ]));
+    for (primary_key, message) in [
+        (vec!["id"], "must not be nullable"),
+        (vec!["age"], "not found"),
+        (vec!["name", "name"], "Duplicated columns"),
+    ] {
+        let result =
+            create_upsert_table(&client, "upsert_invalid_key", schema.clone(), &primary_key).await;
+        assert!(result.unwrap_err().to_string().contains(message));
+    }
+
+    Ok(())
+}
+
+fn id_value_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("value", DataType::Int64, false),
+    ]))
+}
+
+fn id_value_batch(
+    ids: Vec<i64>,
+    values: Vec<i64>,
+) -> Result<RecordBatch, Box<dyn std::error::Error>> {
+    Ok(RecordBatch::try_new(
+        id_value_schema(),
+        vec![
+            Arc::new(Int64Array::from(ids)),
+            Arc::new(Int64Array::from(values)),
+        ],
+    )?)
+}
+
+async fn create_id_value_table(
+    client: &LakeClient,
+    table_name: &str,
+    hash_index: bool,
+) -> Result<Table, Box<dyn std::error::Error>> {
+    create_namespace_if_not_exists(client, "test_namespace").await?;
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: table_name.to_string(),
+            schema: id_value_schema(),
+            config: TableConfig {
+                inline_row_count_limit: 10000,
+                parquet_row_group_size: 1000,
+                ..Default::default()
+            },
+        })
+        .await?;
+    let mut table = client.load_table("test_namespace", table_name).await?;
+    if hash_index {
+        table
+            .create_index(IndexCreation {
+                name: "id_index".to_string(),
+                kind: HashIndex.kind().to_string(),
+                key_columns: vec!["id".to_string()],
+                include_columns: vec![],
+                params: Arc::new(HashIndexParams::default()),
+                where_predicate: None,
+            })
+            .await?;
+    }
+    Ok(table)
+}
+
+/// Values of the live rows by id, failing on duplicated ids.
+async fn values_by_id(table: &Table) -> Result<Vec<(i64, i64)>, Box<dyn std::error::Error>> {
+    let batches = table
+        .scan(TableScan::default())
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    let mut rows = Vec::new();
+    for batch in batches {
+        let ids = batch
+            .column_by_name("id")
+            .unwrap()
+            .as_primitive::<Int64Type>();
+        let values = batch
+            .column_by_name("value")
+            .unwrap()
+            .as_primitive::<Int64Type>();
+        rows.extend(
+            ids.values()
+                .iter()
+                .copied()
+                .zip(values.values().iter().copied()),
+        );
+    }
+    rows.sort();
+    let row_count = rows.len();
+    rows.dedup_by_key(|(id, _)| *id);
+    assert_eq!(rows.len(), row_count, "ids are duplicated");
+    Ok(rows)
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs(), false)]
+#[case(async { catalog_sqlite() }, storage_fs(), true)]
+#[case(async { catalog_postgres().await }, storage_s3(), true)]
+#[case(async { catalog_memory() }, storage_fs(), true)]
+#[tokio::test(flavor = "multi_thread")]
+async fn upsert_by_key_large_table(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+    #[case] hash_index: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let mut client = LakeClient::new(catalog, storage);
+    client.register_index(Arc::new(HashIndex))?;
+    let table_name = format!("upsert_by_key_large_table_{hash_index}");
+    let table = create_id_value_table(&client, &table_name, hash_index).await?;
+
+    for chunk in 0..10 {
+        let ids = (chunk * 10000..(chunk + 1) * 10000).collect::<Vec<i64>>();
+        table.insert(&id_value_batch(ids.clone(), ids)?).await?;
+    }
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+
+    // Every tenth id is updated and new ids are appended, key 5 is given twice and the last
+    // row wins
+    let mut ids = (0..10000).map(|i| i * 10).collect::<Vec<i64>>();
+    ids.extend(100000..100500);
+    ids.extend([5, 5]);
+    let mut values = ids.iter().map(|id| id + 1_000_000).collect::<Vec<_>>();
+    *values.last_mut().unwrap() = -5;
+    table
+        .upsert_by_key(&id_value_batch(ids, values)?, &["id"])
+        .await?;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+
+    let rows = values_by_id(&table).await?;
+    assert_eq!(rows.len(), 100500);
+    for (id, value) in rows {
+        let expected = if id == 5 {
+            -5
+        } else if id % 10 == 0 || id >= 100000 {
+            id + 1_000_000
+        } else {
+            id
+        };
+        assert_eq!(value, expected, "value of id {id}");
+    }
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_memory() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[tokio::test(flavor = "multi_thread")]
+async fn concurrent_upsert_disjoint_keys(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage);
+    let table_name = "concurrent_upsert_disjoint_keys";
+    let table = create_id_value_table(&client, table_name, false).await?;
+    let ids = (0..2000).collect::<Vec<i64>>();
+    table.insert(&id_value_batch(ids.clone(), ids)?).await?;
+
+    let mut handles = Vec::new();
+    for task in 0..2i64 {
+        let table = client.load_table("test_namespace", table_name).await?;
+        handles.push(tokio::spawn(async move {
+            for round in 0..10i64 {
+                let ids = (0..100)
+                    .map(|i| task * 1000 + round * 100 + i)
+                    .collect::<Vec<_>>();
+                let values = ids.iter().map(|id| -id).collect::<Vec<_>>();
+                let batch = RecordBatch::try_new(
+                    id_value_schema(),
+                    vec![
+                        Arc::new(Int64Array::from(ids)),
+                        Arc::new(Int64Array::from(values)),
+                    ],
+                )?;
+                table.upsert_by_key(&batch, &["id"]).await?;
+            }
+            Ok::<_, indexlake::ILError>(())
+        }));
+    }
+    for handle in handles {
+        handle.await??;
+    }
+
+    let rows = values_by_id(&table).await?;
+    assert_eq!(rows.len(), 2000);
+    assert!(rows.iter().all(|(id, value)| *value == -id));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn upsert_by_key_invalid_keys() -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog_sqlite(), storage_fs());
+    let table = create_id_value_table(&client, "upsert_by_key_invalid_keys", false).await?;
+    let batch = id_value_batch(vec![1], vec![1])?;
+    for (key_columns, message) in [
+        (vec![], "at least one key column"),
+        (vec!["id", "id"], "Duplicated key columns"),
+        (vec!["missing"], "not found"),
+        (vec!["_indexlake_row_id"], "not found"),
+    ] {
+        let result = table.upsert_by_key(&batch, &key_columns).await;
+        assert!(result.unwrap_err().to_string().contains(message));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,271 @@
+use arrow::array::{Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use futures::TryStreamExt;
+use indexlake::catalog::{CatalogDataType, CatalogSchema, Column};
+use indexlake::table::{CompactOptions, IngestOptions, Table};
+use indexlake::{ILResult, LakeClient, catalog::Catalog, storage::Storage};
+use indexlake_integration_tests::{
+    catalog_memory, catalog_postgres, catalog_sqlite, data::prepare_testing_table, init_env_logger,
+    storage_fs, storage_gcs, storage_s3, utils::full_table_scan,
+};
+use parquet::arrow::AsyncArrowWriter;
+use std::sync::Arc;
+use std::time::Duration;
+
+async fn write_file(storage: &Storage, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    storage
+        .create_file(path)
+        .await?
+        .write(bytes::Bytes::from("orphan"))
+        .await?;
+    Ok(())
+}
+
+async fn file_paths(
+    storage: &Storage,
+    prefix: &str,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    Ok(storage
+        .list_files(prefix)
+        .await?
+        .into_iter()
+        .map(|file| file.relative_path)
+        .collect())
+}
+
+/// Paths of the data files of the table registered in the catalog.
+async fn data_file_paths(table: &Table) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let schema = Arc::new(CatalogSchema::new(vec![Column::new(
+        "relative_path",
+        CatalogDataType::Utf8,
+        false,
+    )]));
+    let rows = table
+        .catalog
+        .query(
+            &format!(
+                "SELECT relative_path FROM indexlake_data_file WHERE table_id = {}",
+                table.table_id
+            ),
+            schema,
+        )
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    Ok(rows
+        .iter()
+        .map(|row| Ok(row.utf8(0)?.unwrap().clone()))
+        .collect::<ILResult<Vec<_>>>()?)
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_s3())]
+#[case(async { catalog_sqlite() }, storage_gcs())]
+#[tokio::test(flavor = "multi_thread")]
+async fn vacuum_unreferenced_files(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage.clone());
+    let table = prepare_testing_table(&client, "vacuum_unreferenced_files").await?;
+    table
+        .insert(&RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new("name", DataType::Utf8, false),
+                Field::new("age", DataType::Int32, false),
+            ])),
+            vec![
+                Arc::new(StringArray::from(vec!["Eve", "Frank"])),
+                Arc::new(Int32Array::from(vec![24, 25])),
+            ],
+        )?)
+        .await?;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+
+    // the two data files are replaced by a merged one
+    table.compact(CompactOptions::default()).await?;
+    let table_str = full_table_scan(&table).await?;
+
+    let table_dir = format!("{}/{}", table.namespace_id, table.table_id);
+    let orphan_path = format!("{table_dir}/orphan.parquet");
+    let orphan = bytes::Bytes::from("orphan");
+    storage
+        .create_file(&orphan_path)
+        .await?
+        .write(orphan.clone())
+        .await?;
+
+    // files within the retention window are kept
+    let report = table.vacuum(Duration::from_secs(3600)).await?;
+    assert!(report.deleted_files.is_empty());
+    assert_eq!(report.reclaimed_bytes, 0);
+
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    let files_before = storage.list_files(&table_dir).await?;
+    let report = table.vacuum(Duration::from_secs(1)).await?;
+    assert_eq!(
+        report.deleted_files,
+        vec![
+            format!("{table_dir}/1.parquet"),
+            format!("{table_dir}/2.parquet"),
+            orphan_path,
+        ]
+    );
+    assert_eq!(
+        report.reclaimed_bytes,
+        files_before
+            .iter()
+            .filter(|file| report.deleted_files.contains(&file.relative_path))
+            .map(|file| file.size_bytes)
+            .sum::<u64>()
+    );
+    // the compacted file is written under a unique path
+    let files_after = storage.list_files(&table_dir).await?;
+    assert_eq!(
+        files_after
+            .into_iter()
+            .map(|file| file.relative_path)
+            .collect::<Vec<_>>(),
+        data_file_paths(&table).await?
+    );
+    assert_eq!(full_table_scan(&table).await?, table_str);
+
+    // nothing left to delete
+    let report = table.vacuum(Duration::ZERO).await?;
+    assert!(report.deleted_files.is_empty());
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[tokio::test(flavor = "multi_thread")]
+async fn vacuum_keeps_in_flight_insert(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage.clone());
+    let table = prepare_testing_table(&client, "vacuum_keeps_in_flight_insert").await?;
+    let table_dir = table.table_dir();
+    let orphan_path = format!("{table_dir}/orphan.parquet");
+    write_file(&storage, &orphan_path).await?;
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+
+    // an insert has written its data file but not committed it yet
+    let in_flight_path = format!("{table_dir}/in_flight.parquet");
+    let batch = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("name", DataType::Utf8, false),
+            Field::new("age", DataType::Int32, false),
+        ])),
+        vec![
+            Arc::new(StringArray::from(vec!["Eve", "Frank"])),
+            Arc::new(Int32Array::from(vec![24, 25])),
+        ],
+    )?;
+    let mut writer = AsyncArrowWriter::try_new(
+        storage.create_file(&in_flight_path).await?,
+        batch.schema(),
+        None,
+    )?;
+    writer.write(&batch).await?;
+    writer.close().await?;
+
+    // a dry run reports the old orphan and deletes nothing
+    let files_before = file_paths(&storage, &table_dir).await?;
+    let report = table.vacuum_dry_run(Duration::from_secs(1)).await?;
+    assert_eq!(report.deleted_files, vec![orphan_path.clone()]);
+    assert_eq!(report.reclaimed_bytes, "orphan".len() as u64);
+    assert_eq!(file_paths(&storage, &table_dir).await?, files_before);
+
+    // the uncommitted file is within the grace period
+    let report = table.vacuum(Duration::from_secs(1)).await?;
+    assert_eq!(report.deleted_files, vec![orphan_path]);
+    assert!(
+        file_paths(&storage, &table_dir)
+            .await?
+            .contains(&in_flight_path)
+    );
+
+    // the insert commits the file it wrote, vacuum keeps it once it is old
+    table
+        .ingest_parquet(vec![in_flight_path], IngestOptions { in_place: true })
+        .await?;
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    let report = table.vacuum(Duration::from_secs(1)).await?;
+    assert!(report.deleted_files.is_empty());
+    let table_str = full_table_scan(&table).await?;
+    assert!(table_str.contains("Eve") && table_str.contains("Frank"));
+
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[tokio::test(flavor = "multi_thread")]
+async fn vacuum_namespace_dropped_tables(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage.clone());
+    let kept = prepare_testing_table(&client, "vacuum_namespace_kept").await?;
+    let dropped = prepare_testing_table(&client, "vacuum_namespace_dropped").await?;
+    let kept_dir = kept.table_dir();
+    let dropped_dir = dropped.table_dir();
+    dropped.drop().await?;
+    let orphan_path = format!("{kept_dir}/orphan.parquet");
+    write_file(&storage, &orphan_path).await?;
+    let kept_str = full_table_scan(&kept).await?;
+
+    // the dropped table left its files behind
+    let dropped_files = file_paths(&storage, &dropped_dir).await?;
+    assert!(!dropped_files.is_empty());
+    let mut expected = dropped_files.clone();
+    expected.push(orphan_path);
+    expected.sort();
+
+    // files within the retention window are kept
+    let report = client
+        .vacuum_namespace("test_namespace", Duration::from_secs(3600))
+        .await?;
+    assert!(report.deleted_files.is_empty());
+
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    let report = client
+        .vacuum_namespace_dry_run("test_namespace", Duration::from_secs(1))
+        .await?;
+    assert_eq!(report.deleted_files, expected);
+    assert_eq!(file_paths(&storage, &dropped_dir).await?, dropped_files);
+
+    let report = client
+        .vacuum_namespace("test_namespace", Duration::from_secs(1))
+        .await?;
+    assert_eq!(report.deleted_files, expected);
+    assert!(file_paths(&storage, &dropped_dir).await?.is_empty());
+    assert_eq!(full_table_scan(&kept).await?, kept_str);
+
+    assert!(
+        client
+            .vacuum_namespace("not_exists", Duration::ZERO)
+            .await
+            .is_err()
+    );
+
+    Ok(())
+}
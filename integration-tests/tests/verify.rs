@@ -0,0 +1,132 @@
+use arrow::array::{Int32Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use indexlake::table::{Table, TableConfig, TableCreation, TableScan};
+use indexlake::{ILError, LakeClient, catalog::Catalog, storage::Storage};
+use indexlake_integration_tests::data::create_namespace_if_not_exists;
+use indexlake_integration_tests::utils::table_scan;
+use indexlake_integration_tests::{
+    catalog_memory, catalog_mysql, catalog_postgres, catalog_sqlite, init_env_logger, storage_fs,
+};
+use std::sync::Arc;
+
+fn table_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("name", DataType::Utf8, false),
+        Field::new("age", DataType::Int32, false),
+    ]))
+}
+
+async fn insert_and_dump(
+    table: &Table,
+    names: Vec<&str>,
+    ages: Vec<i32>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    table
+        .insert(&RecordBatch::try_new(
+            table_schema(),
+            vec![
+                Arc::new(StringArray::from(names)),
+                Arc::new(Int32Array::from(ages)),
+            ],
+        )?)
+        .await?;
+    // wait for dump task to finish
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+    Ok(())
+}
+
+#[rstest::rstest]
+#[case(async { catalog_sqlite() }, storage_fs())]
+#[case(async { catalog_postgres().await }, storage_fs())]
+#[case(async { catalog_mysql().await }, storage_fs())]
+#[case(async { catalog_memory() }, storage_fs())]
+#[tokio::test(flavor = "multi_thread")]
+async fn verify_detects_corrupted_files(
+    #[future(awt)]
+    #[case]
+    catalog: Arc<dyn Catalog>,
+    #[case] storage: Arc<Storage>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_env_logger();
+
+    let client = LakeClient::new(catalog, storage.clone());
+    create_namespace_if_not_exists(&client, "test_namespace").await?;
+    let storage_prefix = format!("verify/{}", uuid::Uuid::new_v4());
+    client
+        .create_table(TableCreation {
+            namespace_name: "test_namespace".to_string(),
+            table_name: "verify_detects_corrupted_files".to_string(),
+            schema: table_schema(),
+            config: TableConfig {
+                inline_row_count_limit: 2,
+                storage_prefix: Some(storage_prefix.clone()),
+                ..Default::default()
+            },
+        })
+        .await?;
+    let table = client
+        .load_table("test_namespace", "verify_detects_corrupted_files")
+        .await?;
+    insert_and_dump(&table, vec!["Alice", "Bob"], vec![20, 21]).await?;
+    insert_and_dump(&table, vec!["Charlie", "David"], vec![22, 23]).await?;
+
+    let mut data_files = storage
+        .list_files(&storage_prefix)
+        .await?
+        .into_iter()
+        .map(|file| file.relative_path)
+        .filter(|path| path.ends_with(".parquet"))
+        .collect::<Vec<_>>();
+    data_files.sort();
+    assert_eq!(data_files.len(), 2);
+
+    let report = table.verify().await?;
+    assert_eq!(report.verified_file_count, 2);
+    assert_eq!(report.unchecked_file_count, 0);
+    assert!(report.mismatches.is_empty());
+    let verified_scan = TableScan::default().with_verify_checksums(true);
+    let table_str = table_scan(&table, verified_scan.clone()).await?;
+    assert_eq!(table_str.lines().count(), 4 + 4);
+
+    // Flip a byte in the middle of the first file
+    let corrupted = &data_files[0];
+    let mut bytes = storage.open_file(corrupted).await?.read().await?.to_vec();
+    let middle = bytes.len() / 2;
+    bytes[middle] ^= 0xff;
+    storage
+        .create_file(corrupted)
+        .await?
+        .write(bytes::Bytes::from(bytes))
+        .await?;
+
+    let report = table.verify().await?;
+    assert_eq!(report.verified_file_count, 2);
+    assert_eq!(report.mismatches.len(), 1);
+    let mismatch = &report.mismatches[0];
+    assert_eq!(&mismatch.relative_path, corrupted);
+    assert_ne!(mismatch.expected, mismatch.actual);
+
+    match table_scan(&table, verified_scan).await {
+        Err(ILError::ChecksumMismatch(scan_mismatch)) => assert_eq!(&scan_mismatch, mismatch),
+        other => panic!("expected checksum mismatch, got {other:?}"),
+    }
+
+    // A truncated upload is reported as well
+    let truncated = &data_files[1];
+    let bytes = storage.open_file(truncated).await?.read().await?;
+    storage
+        .create_file(truncated)
+        .await?
+        .write(bytes.slice(..bytes.len() - 8))
+        .await?;
+    let report = table.verify().await?;
+    let mut mismatched_paths = report
+        .mismatches
+        .iter()
+        .map(|mismatch| mismatch.relative_path.clone())
+        .collect::<Vec<_>>();
+    mismatched_paths.sort();
+    assert_eq!(mismatched_paths, data_files);
+
+    Ok(())
+}
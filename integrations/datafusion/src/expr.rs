@@ -0,0 +1,153 @@
+use datafusion::arrow::datatypes::{Schema, TimeUnit};
+use datafusion::common::ScalarValue;
+use datafusion::logical_expr::{Between, Like, Operator};
+use indexlake::catalog::Scalar;
+use indexlake::expr::{BinaryExpr, BinaryOp, Expr, InList};
+
+type DFExpr = datafusion::logical_expr::Expr;
+
+/// Translates a DataFusion filter into an indexlake expression over the table schema, `None` if
+/// it uses anything indexlake can not evaluate, such as casts, functions or other literal types.
+pub fn to_indexlake_expr(expr: &DFExpr, schema: &Schema) -> Option<Expr> {
+    let il_expr = match expr {
+        DFExpr::Column(column) => {
+            schema.field_with_name(&column.name).ok()?;
+            Expr::Column(column.name.clone())
+        }
+        DFExpr::Literal(value) => Expr::Literal(to_indexlake_scalar(value)?),
+        DFExpr::BinaryExpr(binary_expr) => {
+            let op = to_indexlake_op(binary_expr.op)?;
+            let left = to_indexlake_expr(&binary_expr.left, schema)?;
+            let right = to_indexlake_expr(&binary_expr.right, schema)?;
+            Expr::BinaryExpr(BinaryExpr {
+                left: Box::new(left),
+                op,
+                right: Box::new(right),
+            })
+        }
+        DFExpr::Not(expr) => Expr::Not(Box::new(to_indexlake_expr(expr, schema)?)),
+        DFExpr::IsNull(expr) => Expr::IsNull(Box::new(to_indexlake_expr(expr, schema)?)),
+        DFExpr::IsNotNull(expr) => Expr::IsNotNull(Box::new(to_indexlake_expr(expr, schema)?)),
+        DFExpr::InList(in_list) => Expr::InList(InList {
+            expr: Box::new(to_indexlake_expr(&in_list.expr, schema)?),
+            list: in_list
+                .list
+                .iter()
+                .map(|expr| to_indexlake_expr(expr, schema))
+                .collect::<Option<Vec<_>>>()?,
+            negated: in_list.negated,
+        }),
+        DFExpr::Between(Between {
+            expr,
+            negated,
+            low,
+            high,
+        }) => {
+            let expr = to_indexlake_expr(expr, schema)?;
+            let low = to_indexlake_expr(low, schema)?;
+            let high = to_indexlake_expr(high, schema)?;
+            if *negated {
+                expr.clone().lt(low).or(expr.gt(high))
+            } else {
+                expr.clone().gt_eq(low).and(expr.lt_eq(high))
+            }
+        }
+        DFExpr::Like(Like {
+            negated,
+            expr,
+            pattern,
+            escape_char: None,
+            case_insensitive,
+        }) => {
+            let expr = to_indexlake_expr(expr, schema)?;
+            let pattern = to_indexlake_expr(pattern, schema)?;
+            match (negated, case_insensitive) {
+                (false, false) => expr.like(pattern),
+                (true, false) => expr.not_like(pattern),
+                (false, true) => expr.ilike(pattern),
+                (true, true) => expr.not_ilike(pattern),
+            }
+        }
+        _ => return None,
+    };
+    // Arrow kernels compare and compute on operands of the same type only
+    il_expr.data_type(schema).ok()?;
+    if let Expr::BinaryExpr(binary_expr) = &il_expr {
+        let left_type = binary_expr.left.data_type(schema).ok()?;
+        let right_type = binary_expr.right.data_type(schema).ok()?;
+        if left_type != right_type {
+            return None;
+        }
+    }
+    Some(il_expr)
+}
+
+/// Whether filtering by the expression matches the rows DataFusion would. Inline rows are
+/// filtered by the catalog database, whose collation may compare strings case-insensitively.
+pub(crate) fn is_exact_filter(expr: &Expr) -> bool {
+    match expr {
+        Expr::Column(_) => true,
+        Expr::Literal(scalar) => !matches!(scalar, Scalar::Utf8(_)),
+        Expr::BinaryExpr(binary_expr) => {
+            is_exact_filter(&binary_expr.left) && is_exact_filter(&binary_expr.right)
+        }
+        Expr::Not(expr) | Expr::IsNull(expr) | Expr::IsNotNull(expr) => is_exact_filter(expr),
+        Expr::InList(in_list) => {
+            is_exact_filter(&in_list.expr) && in_list.list.iter().all(is_exact_filter)
+        }
+        Expr::Function(_) | Expr::LikeExpr(_) => false,
+    }
+}
+
+fn to_indexlake_scalar(value: &ScalarValue) -> Option<Scalar> {
+    Some(match value {
+        ScalarValue::Boolean(v) => Scalar::Boolean(*v),
+        ScalarValue::Int16(v) => Scalar::Int16(*v),
+        ScalarValue::Int32(v) => Scalar::Int32(*v),
+        ScalarValue::Int64(v) => Scalar::Int64(*v),
+        ScalarValue::Float32(v) => Scalar::Float32(*v),
+        ScalarValue::Float64(v) => Scalar::Float64(*v),
+        ScalarValue::Utf8(v) => Scalar::Utf8(v.clone()),
+        ScalarValue::Binary(v) => Scalar::Binary(v.clone()),
+        ScalarValue::Decimal128(v, precision, scale) => Scalar::Decimal128(*v, *precision, *scale),
+        ScalarValue::Decimal256(v, precision, scale) => Scalar::Decimal256(*v, *precision, *scale),
+        ScalarValue::TimestampSecond(v, tz) => {
+            Scalar::Timestamp(*v, TimeUnit::Second, tz.as_ref().map(|tz| tz.to_string()))
+        }
+        ScalarValue::TimestampMillisecond(v, tz) => Scalar::Timestamp(
+            *v,
+            TimeUnit::Millisecond,
+            tz.as_ref().map(|tz| tz.to_string()),
+        ),
+        ScalarValue::TimestampMicrosecond(v, tz) => Scalar::Timestamp(
+            *v,
+            TimeUnit::Microsecond,
+            tz.as_ref().map(|tz| tz.to_string()),
+        ),
+        ScalarValue::TimestampNanosecond(v, tz) => Scalar::Timestamp(
+            *v,
+            TimeUnit::Nanosecond,
+            tz.as_ref().map(|tz| tz.to_string()),
+        ),
+        _ => return None,
+    })
+}
+
+fn to_indexlake_op(op: Operator) -> Option<BinaryOp> {
+    Some(match op {
+        Operator::Eq => BinaryOp::Eq,
+        Operator::NotEq => BinaryOp::NotEq,
+        Operator::Lt => BinaryOp::Lt,
+        Operator::LtEq => BinaryOp::LtEq,
+        Operator::Gt => BinaryOp::Gt,
+        Operator::GtEq => BinaryOp::GtEq,
+        Operator::Plus => BinaryOp::Plus,
+        Operator::Minus => BinaryOp::Minus,
+        Operator::Multiply => BinaryOp::Multiply,
+        Operator::Divide => BinaryOp::Divide,
+        Operator::Modulo => BinaryOp::Modulo,
+        Operator::And => BinaryOp::And,
+        Operator::Or => BinaryOp::Or,
+        _ => return None,
+    })
+}
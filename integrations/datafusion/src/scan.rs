@@ -0,0 +1,189 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{RecordBatch, RecordBatchOptions};
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::common::{DataFusionError, Result};
+use datafusion::execution::{SendableRecordBatchStream, TaskContext};
+use datafusion::physical_expr::EquivalenceProperties;
+use datafusion::physical_plan::execution_plan::{Boundedness, EmissionType};
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::{
+    DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning, PlanProperties,
+};
+use futures::{StreamExt, TryStreamExt};
+use indexlake::catalog::INTERNAL_ROW_ID_FIELD_NAME;
+use indexlake::expr::{Expr, visited_columns};
+use indexlake::table::{Table, TableScan};
+
+/// Physical plan reading an indexlake table in one partition, with the projection, filters and
+/// limit pushed down into a [`TableScan`].
+#[derive(Debug)]
+pub struct IndexLakeScanExec {
+    table: Arc<Table>,
+    scan: TableScan,
+    /// Indices of the output columns in the scanned batches, which also hold the columns only
+    /// read by filters
+    output_columns: Option<Vec<usize>>,
+    schema: SchemaRef,
+    properties: PlanProperties,
+}
+
+impl IndexLakeScanExec {
+    pub fn try_new(
+        table: Arc<Table>,
+        projection: Option<Vec<usize>>,
+        filters: Vec<Expr>,
+        limit: Option<usize>,
+    ) -> Result<Self> {
+        let schema = match &projection {
+            Some(projection) => Arc::new(table.schema.project(projection)?),
+            None => table.schema.clone(),
+        };
+        // Table scans evaluate filters on the scanned columns, so the filter columns are
+        // scanned as well. Scans without columns, as for `COUNT(*)`, read the row id column
+        let (scan_projection, output_columns) = match projection {
+            Some(mut scan_projection) => {
+                let output_len = scan_projection.len();
+                let filter_columns = filters.iter().flat_map(visited_columns).chain(
+                    scan_projection
+                        .is_empty()
+                        .then(|| INTERNAL_ROW_ID_FIELD_NAME.to_string()),
+                );
+                for name in filter_columns {
+                    let idx = table.schema.index_of(&name)?;
+                    if !scan_projection.contains(&idx) {
+                        scan_projection.push(idx);
+                    }
+                }
+                let output_columns = (scan_projection.len() > output_len)
+                    .then(|| (0..output_len).collect::<Vec<_>>());
+                (Some(scan_projection), output_columns)
+            }
+            None => (None, None),
+        };
+        let scan = TableScan::default()
+            .with_projection(scan_projection)
+            .with_filters(filters)
+            .with_limit(limit);
+        let properties = PlanProperties::new(
+            EquivalenceProperties::new(schema.clone()),
+            Partitioning::UnknownPartitioning(1),
+            EmissionType::Incremental,
+            Boundedness::Bounded,
+        );
+        Ok(Self {
+            table,
+            scan,
+            output_columns,
+            schema,
+            properties,
+        })
+    }
+
+    pub fn table_scan(&self) -> &TableScan {
+        &self.scan
+    }
+}
+
+impl DisplayAs for IndexLakeScanExec {
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let filters = self
+            .scan
+            .filters
+            .iter()
+            .map(|filter| filter.to_string())
+            .collect::<Vec<_>>();
+        match t {
+            DisplayFormatType::Default | DisplayFormatType::Verbose => write!(
+                f,
+                "IndexLakeScanExec: table={}.{}, projection={:?}, filters=[{}], limit={:?}",
+                self.table.namespace_name,
+                self.table.table_name,
+                self.scan.projection,
+                filters.join(", "),
+                self.scan.limit
+            ),
+            DisplayFormatType::TreeRender => {
+                writeln!(
+                    f,
+                    "table={}.{}",
+                    self.table.namespace_name, self.table.table_name
+                )?;
+                if !filters.is_empty() {
+                    writeln!(f, "filters={}", filters.join(", "))?;
+                }
+                if let Some(limit) = self.scan.limit {
+                    writeln!(f, "limit={limit}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl ExecutionPlan for IndexLakeScanExec {
+    fn name(&self) -> &str {
+        "IndexLakeScanExec"
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.properties
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        _children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        Ok(self)
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        _context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Internal(format!(
+                "IndexLakeScanExec has one partition, got partition {partition}"
+            )));
+        }
+        let table = self.table.clone();
+        let scan = self.scan.clone();
+        let schema = self.schema.clone();
+        let output_columns = self.output_columns.clone();
+        let stream = futures::stream::once(async move { table.scan_stream(scan).await })
+            .try_flatten()
+            .map(move |batch| {
+                let batch = batch.map_err(|e| DataFusionError::External(Box::new(e)))?;
+                match &output_columns {
+                    Some(output_columns) => {
+                        let options =
+                            RecordBatchOptions::new().with_row_count(Some(batch.num_rows()));
+                        let columns = output_columns
+                            .iter()
+                            .map(|idx| batch.column(*idx).clone())
+                            .collect();
+                        Ok(RecordBatch::try_new_with_options(
+                            schema.clone(),
+                            columns,
+                            &options,
+                        )?)
+                    }
+                    None => Ok(batch),
+                }
+            });
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.schema.clone(),
+            stream,
+        )))
+    }
+}
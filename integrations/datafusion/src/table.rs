@@ -0,0 +1,90 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::datatypes::{DataType, SchemaRef};
+use datafusion::catalog::Session;
+use datafusion::common::Result;
+use datafusion::datasource::{TableProvider, TableType};
+use datafusion::logical_expr::TableProviderFilterPushDown;
+use datafusion::physical_plan::ExecutionPlan;
+use indexlake::expr::Expr;
+use indexlake::table::Table;
+
+use crate::{IndexLakeScanExec, is_exact_filter, to_indexlake_expr};
+
+type DFExpr = datafusion::logical_expr::Expr;
+
+/// Exposes an indexlake table to DataFusion, its schema includes the row id column.
+///
+/// Filters translated into indexlake expressions are pushed down to prune data files and use
+/// indexes. Those on strings and the untranslated ones are [`TableProviderFilterPushDown::Inexact`]
+/// and checked again by DataFusion.
+#[derive(Debug, Clone)]
+pub struct IndexLakeTable {
+    table: Arc<Table>,
+}
+
+impl IndexLakeTable {
+    pub fn new(table: Arc<Table>) -> Self {
+        Self { table }
+    }
+
+    pub fn table(&self) -> &Arc<Table> {
+        &self.table
+    }
+
+    fn pushdown_filter(&self, filter: &DFExpr) -> Option<Expr> {
+        let expr = to_indexlake_expr(filter, &self.table.schema)?;
+        match expr.data_type(&self.table.schema) {
+            Ok(DataType::Boolean) => Some(expr),
+            _ => None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TableProvider for IndexLakeTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.table.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        _state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[DFExpr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let filters = filters
+            .iter()
+            .filter_map(|filter| self.pushdown_filter(filter))
+            .collect::<Vec<_>>();
+        Ok(Arc::new(IndexLakeScanExec::try_new(
+            self.table.clone(),
+            projection.cloned(),
+            filters,
+            limit,
+        )?))
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&DFExpr],
+    ) -> Result<Vec<TableProviderFilterPushDown>> {
+        Ok(filters
+            .iter()
+            .map(|filter| match self.pushdown_filter(filter) {
+                Some(expr) if is_exact_filter(&expr) => TableProviderFilterPushDown::Exact,
+                _ => TableProviderFilterPushDown::Inexact,
+            })
+            .collect())
+    }
+}
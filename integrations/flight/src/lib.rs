@@ -0,0 +1,5 @@
+mod service;
+mod ticket;
+
+pub use service::*;
+pub use ticket::*;
@@ -0,0 +1,219 @@
+use std::pin::Pin;
+
+use arrow::datatypes::SchemaRef;
+use arrow::ipc::writer::IpcWriteOptions;
+use arrow_flight::encode::FlightDataEncoderBuilder;
+use arrow_flight::error::FlightError;
+use arrow_flight::flight_descriptor::DescriptorType;
+use arrow_flight::flight_service_server::{FlightService, FlightServiceServer};
+use arrow_flight::{
+    Action, ActionType, Criteria, Empty, FlightData, FlightDescriptor, FlightEndpoint, FlightInfo,
+    HandshakeRequest, HandshakeResponse, IpcMessage, PollInfo, PutResult, SchemaAsIpc,
+    SchemaResult, Ticket,
+};
+use futures::{Stream, StreamExt, TryStreamExt};
+use indexlake::table::Table;
+use indexlake::{ILError, ILResult, LakeClient};
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::ScanTicket;
+
+type BoxedStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send + 'static>>;
+
+/// Flight service serving scans of the tables of a [`LakeClient`]. `GetFlightInfo` and
+/// `GetSchema` take a descriptor whose command is a [`ScanTicket`], the flight info holds a
+/// single endpoint whose ticket `DoGet` streams the scanned batches of, read with
+/// [`Table::scan_stream`].
+///
+/// With a bearer token set, requests must carry an `authorization: Bearer <token>` header.
+#[derive(Debug, Clone)]
+pub struct IndexLakeFlightService {
+    client: LakeClient,
+    bearer_token: Option<String>,
+}
+
+impl IndexLakeFlightService {
+    pub fn new(client: LakeClient) -> Self {
+        Self {
+            client,
+            bearer_token: None,
+        }
+    }
+
+    pub fn with_bearer_token(mut self, token: impl Into<String>) -> Self {
+        self.bearer_token = Some(token.into());
+        self
+    }
+
+    pub fn into_server(self) -> FlightServiceServer<Self> {
+        FlightServiceServer::new(self)
+    }
+
+    /// Serves Flight requests on the connections accepted by `listener` until an error occurs.
+    pub async fn serve(self, listener: TcpListener) -> ILResult<()> {
+        tonic::transport::Server::builder()
+            .add_service(self.into_server())
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .map_err(|e| ILError::InternalError(format!("Flight server failed: {e}")))
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn check_auth<T>(&self, request: &Request<T>) -> Result<(), Status> {
+        let Some(token) = &self.bearer_token else {
+            return Ok(());
+        };
+        let authorization = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok());
+        match authorization.and_then(|value| value.strip_prefix("Bearer ")) {
+            Some(bearer) if bearer == token => Ok(()),
+            Some(_) => Err(Status::unauthenticated("Invalid bearer token")),
+            None => Err(Status::unauthenticated("Missing bearer token")),
+        }
+    }
+
+    async fn load_scan(&self, ticket: &ScanTicket) -> Result<(Table, SchemaRef), Status> {
+        let table = self
+            .client
+            .load_table(&ticket.namespace, &ticket.table)
+            .await
+            .map_err(to_status)?;
+        let schema = ticket
+            .to_scan()
+            .projected_schema(&table.schema)
+            .map_err(to_status)?;
+        Ok((table, schema))
+    }
+}
+
+fn to_status(error: ILError) -> Status {
+    match error {
+        ILError::InvalidInput(msg) => Status::invalid_argument(msg),
+        ILError::NotSupported(msg) => Status::unimplemented(msg),
+        error => Status::internal(error.to_string()),
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn descriptor_ticket(descriptor: &FlightDescriptor) -> Result<ScanTicket, Status> {
+    if descriptor.r#type() != DescriptorType::Cmd {
+        return Err(Status::invalid_argument(
+            "Flight descriptor must be a command holding a scan ticket",
+        ));
+    }
+    ScanTicket::decode(&descriptor.cmd).map_err(to_status)
+}
+
+#[tonic::async_trait]
+impl FlightService for IndexLakeFlightService {
+    type HandshakeStream = BoxedStream<HandshakeResponse>;
+    type ListFlightsStream = BoxedStream<FlightInfo>;
+    type DoGetStream = BoxedStream<FlightData>;
+    type DoPutStream = BoxedStream<PutResult>;
+    type DoActionStream = BoxedStream<arrow_flight::Result>;
+    type ListActionsStream = BoxedStream<ActionType>;
+    type DoExchangeStream = BoxedStream<FlightData>;
+
+    async fn handshake(
+        &self,
+        _request: Request<Streaming<HandshakeRequest>>,
+    ) -> Result<Response<Self::HandshakeStream>, Status> {
+        Err(Status::unimplemented("Handshake is not supported"))
+    }
+
+    async fn list_flights(
+        &self,
+        _request: Request<Criteria>,
+    ) -> Result<Response<Self::ListFlightsStream>, Status> {
+        Err(Status::unimplemented("ListFlights is not supported"))
+    }
+
+    async fn get_flight_info(
+        &self,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        self.check_auth(&request)?;
+        let descriptor = request.into_inner();
+        let ticket = descriptor_ticket(&descriptor)?;
+        let (_, schema) = self.load_scan(&ticket).await?;
+        let endpoint = FlightEndpoint::new().with_ticket(Ticket::new(descriptor.cmd.clone()));
+        let info = FlightInfo::new()
+            .try_with_schema(&schema)
+            .map_err(|e| Status::internal(e.to_string()))?
+            .with_endpoint(endpoint)
+            .with_descriptor(descriptor);
+        Ok(Response::new(info))
+    }
+
+    async fn poll_flight_info(
+        &self,
+        _request: Request<FlightDescriptor>,
+    ) -> Result<Response<PollInfo>, Status> {
+        Err(Status::unimplemented("PollFlightInfo is not supported"))
+    }
+
+    async fn get_schema(
+        &self,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<SchemaResult>, Status> {
+        self.check_auth(&request)?;
+        let ticket = descriptor_ticket(request.get_ref())?;
+        let (_, schema) = self.load_scan(&ticket).await?;
+        let options = IpcWriteOptions::default();
+        let IpcMessage(schema) = SchemaAsIpc::new(&schema, &options)
+            .try_into()
+            .map_err(|e: arrow::error::ArrowError| Status::internal(e.to_string()))?;
+        Ok(Response::new(SchemaResult { schema }))
+    }
+
+    async fn do_get(
+        &self,
+        request: Request<Ticket>,
+    ) -> Result<Response<Self::DoGetStream>, Status> {
+        self.check_auth(&request)?;
+        let ticket = ScanTicket::decode(&request.get_ref().ticket).map_err(to_status)?;
+        let (table, schema) = self.load_scan(&ticket).await?;
+        let batches = table
+            .scan_stream(ticket.to_scan())
+            .await
+            .map_err(to_status)?
+            .map_err(|e| FlightError::ExternalError(Box::new(e)));
+        let stream = FlightDataEncoderBuilder::new()
+            .with_schema(schema)
+            .build(batches)
+            .map_err(Status::from);
+        Ok(Response::new(stream.boxed()))
+    }
+
+    async fn do_put(
+        &self,
+        _request: Request<Streaming<FlightData>>,
+    ) -> Result<Response<Self::DoPutStream>, Status> {
+        Err(Status::unimplemented("DoPut is not supported"))
+    }
+
+    async fn do_action(
+        &self,
+        _request: Request<Action>,
+    ) -> Result<Response<Self::DoActionStream>, Status> {
+        Err(Status::unimplemented("DoAction is not supported"))
+    }
+
+    async fn list_actions(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<Self::ListActionsStream>, Status> {
+        Err(Status::unimplemented("ListActions is not supported"))
+    }
+
+    async fn do_exchange(
+        &self,
+        _request: Request<Streaming<FlightData>>,
+    ) -> Result<Response<Self::DoExchangeStream>, Status> {
+        Err(Status::unimplemented("DoExchange is not supported"))
+    }
+}
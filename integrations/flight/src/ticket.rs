@@ -0,0 +1,57 @@
+use arrow_flight::{FlightDescriptor, Ticket};
+use indexlake::expr::Expr;
+use indexlake::table::TableScan;
+use indexlake::{ILError, ILResult};
+use serde::{Deserialize, Serialize};
+
+/// Scan of a table requested by a Flight client, carried as JSON in tickets and in the command
+/// of flight descriptors so that clients in any language can build it, e.g.
+/// `{"namespace": "ns", "table": "t", "columns": ["id", "name"]}`.
+///
+/// `columns` are the columns to read in order, all of them with the row id column if missing.
+/// `predicate` filters the rows, in the serde JSON form of [`Expr`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, derive_with::With)]
+pub struct ScanTicket {
+    pub namespace: String,
+    pub table: String,
+    #[serde(default)]
+    pub columns: Option<Vec<String>>,
+    #[serde(default)]
+    pub predicate: Option<Expr>,
+}
+
+impl ScanTicket {
+    pub fn new(namespace: impl Into<String>, table: impl Into<String>) -> Self {
+        Self {
+            namespace: namespace.into(),
+            table: table.into(),
+            columns: None,
+            predicate: None,
+        }
+    }
+
+    pub fn encode(&self) -> ILResult<Vec<u8>> {
+        serde_json::to_vec(self)
+            .map_err(|e| ILError::InternalError(format!("Failed to encode scan ticket: {e}")))
+    }
+
+    pub fn decode(bytes: &[u8]) -> ILResult<Self> {
+        serde_json::from_slice(bytes)
+            .map_err(|e| ILError::InvalidInput(format!("Invalid scan ticket: {e}")))
+    }
+
+    pub fn to_ticket(&self) -> ILResult<Ticket> {
+        Ok(Ticket::new(self.encode()?))
+    }
+
+    /// Descriptor to pass to `GetFlightInfo` and `GetSchema`.
+    pub fn to_descriptor(&self) -> ILResult<FlightDescriptor> {
+        Ok(FlightDescriptor::new_cmd(self.encode()?))
+    }
+
+    pub fn to_scan(&self) -> TableScan {
+        TableScan::default()
+            .with_columns(self.columns.clone())
+            .with_filters(self.predicate.clone().into_iter().collect::<Vec<_>>())
+    }
+}
@@ -0,0 +1,618 @@
+use std::collections::HashSet;
+use std::io::Read;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::Sender;
+
+use arrow::array::{
+    Array, ArrayRef, BooleanArray, Int64Array, RecordBatch, RecordBatchOptions, StringArray,
+};
+use arrow::compute::{CastOptions, cast, cast_with_options, concat_batches, filter};
+use arrow::datatypes::{DataType, FieldRef, Schema, SchemaRef, TimeUnit};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use csv::StringRecord;
+use indexlake::catalog::INTERNAL_ROW_ID_FIELD_NAME;
+use indexlake::table::{ListOptions, Table, TableConfig, TableCreation};
+use indexlake::{ILError, ILResult, LakeClient};
+
+use crate::infer::infer_schema;
+
+/// Rows converted to the column types at once.
+const PARSE_BATCH_SIZE: usize = 8192;
+
+#[derive(Debug, Clone, derive_with::With)]
+pub struct CsvImportOptions {
+    pub delimiter: u8,
+    /// Whether the first line names the columns. Columns are then matched to the table columns
+    /// by name in any order, nullable table columns missing from the file are filled with their
+    /// default value or nulls. Without a header the fields are read into the table columns in
+    /// order.
+    pub has_header: bool,
+    /// Fields equal to the token are null. Empty fields are null by default.
+    pub null_token: String,
+    pub on_malformed_row: OnMalformedRow,
+    /// Rows read to infer the schema of a table created by [`import_csv_or_create`].
+    pub infer_sample_rows: usize,
+    /// Format of the fields of timestamp and date columns, in the syntax of chrono's
+    /// `strftime`, e.g. `%d/%m/%Y %H:%M:%S`. Timestamps without an offset are taken as UTC.
+    /// Fields are parsed as RFC 3339 and similar formats if `None`.
+    pub timestamp_format: Option<String>,
+    /// Reads values that do not parse as the type of their column as nulls instead of making
+    /// the row malformed. Such rows are still malformed if the column is not nullable.
+    pub lossy_casts: bool,
+    /// Rows inserted per commit, all rows are inserted in a single commit if `None`. Rows are
+    /// then parsed and inserted a chunk at a time, holding no more than a chunk in memory, and
+    /// the chunks inserted stay in the table if the import fails on a later row.
+    pub commit_rows: Option<usize>,
+    /// Receives the malformed rows skipped with [`OnMalformedRow::Skip`] instead of
+    /// [`CsvImportReport::skipped_rows`], so that loads with many of them do not hold them in
+    /// memory.
+    pub rejects: Option<Sender<CsvRowError>>,
+}
+
+impl Default for CsvImportOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            has_header: true,
+            null_token: String::new(),
+            on_malformed_row: OnMalformedRow::default(),
+            infer_sample_rows: 1000,
+            timestamp_format: None,
+            lossy_casts: false,
+            commit_rows: None,
+            rejects: None,
+        }
+    }
+}
+
+/// What to do with rows that can not be imported: rows with a wrong number of fields, values
+/// that do not parse as the type of their column and nulls in non-nullable columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnMalformedRow {
+    /// Fails the import with the first malformed row, nothing is imported.
+    #[default]
+    Fail,
+    /// Imports the other rows and lists the malformed ones in [`CsvImportReport::skipped_rows`].
+    Skip,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvRowError {
+    /// Line the row starts at, starting from 1. Rows span several lines when quoted fields
+    /// hold newlines.
+    pub line: u64,
+    /// Name of the column holding the invalid value, `None` for errors of the whole row.
+    pub column: Option<String>,
+    pub message: String,
+}
+
+impl std::fmt::Display for CsvRowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.column {
+            Some(column) => write!(f, "line {}, column {column}: {}", self.line, self.message),
+            None => write!(f, "line {}: {}", self.line, self.message),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CsvImportReport {
+    pub imported_rows: usize,
+    /// Malformed rows skipped with [`OnMalformedRow::Skip`], ordered by line. Empty when they
+    /// are sent to [`CsvImportOptions::rejects`].
+    pub skipped_rows: Vec<CsvRowError>,
+    /// Number of malformed rows skipped, wherever they were reported.
+    pub skipped_row_count: usize,
+    /// Bytes read from the reader.
+    pub bytes_read: u64,
+}
+
+/// Imports the CSV rows read from `reader` into `table`, converting fields to the types of the
+/// table columns. All rows are parsed before the valid ones are inserted in a single commit,
+/// nothing is inserted if the import fails, unless [`CsvImportOptions::commit_rows`] splits the
+/// import into several commits.
+pub async fn import_csv<R: Read>(
+    table: &Table,
+    reader: R,
+    options: &CsvImportOptions,
+) -> ILResult<CsvImportReport> {
+    let (reader, bytes_read) = CountingReader::new(reader);
+    let mut reader = csv_reader(reader, options);
+    let header = read_header(&mut reader, options)?;
+    let mut report =
+        import_records(table, header.as_deref(), reader.into_records(), options).await?;
+    report.bytes_read = bytes_read.load(Ordering::Relaxed);
+    Ok(report)
+}
+
+/// Imports the CSV rows read from `reader` like [`import_csv`] into the table `table_name` of
+/// the namespace, creating it with `config` if it does not exist. The schema of a created table
+/// is inferred from the first [`CsvImportOptions::infer_sample_rows`] rows, see
+/// [`CsvImportOptions`]. The table is created before rows are imported and stays empty if the
+/// import fails.
+pub async fn import_csv_or_create<R: Read>(
+    client: &LakeClient,
+    namespace_name: &str,
+    table_name: &str,
+    config: TableConfig,
+    reader: R,
+    options: &CsvImportOptions,
+) -> ILResult<(Table, CsvImportReport)> {
+    let (reader, bytes_read) = CountingReader::new(reader);
+    let mut reader = csv_reader(reader, options);
+    let header = read_header(&mut reader, options)?;
+    let mut records = reader.into_records();
+
+    let page = client
+        .list_tables(
+            namespace_name,
+            ListOptions {
+                prefix: Some(table_name.to_string()),
+                ..Default::default()
+            },
+        )
+        .await?;
+    if page
+        .tables
+        .iter()
+        .any(|summary| summary.table_name == table_name)
+    {
+        let table = client.load_table(namespace_name, table_name).await?;
+        let mut report = import_records(&table, header.as_deref(), records, options).await?;
+        report.bytes_read = bytes_read.load(Ordering::Relaxed);
+        return Ok((table, report));
+    }
+
+    let sample = records
+        .by_ref()
+        .take(options.infer_sample_rows)
+        .collect::<Vec<_>>();
+    let sample_records = sample
+        .iter()
+        .filter_map(|record| record.as_ref().ok().cloned())
+        .collect::<Vec<_>>();
+    let schema = infer_schema(header.as_deref(), &sample_records, &options.null_token);
+    client
+        .create_table(TableCreation {
+            namespace_name: namespace_name.to_string(),
+            table_name: table_name.to_string(),
+            schema: Arc::new(schema),
+            config,
+        })
+        .await?;
+    let table = client.load_table(namespace_name, table_name).await?;
+    let mut report = import_records(
+        &table,
+        header.as_deref(),
+        sample.into_iter().chain(records),
+        options,
+    )
+    .await?;
+    report.bytes_read = bytes_read.load(Ordering::Relaxed);
+    Ok((table, report))
+}
+
+/// Reader counting the bytes read through it, into a counter shared with the caller as the
+/// CSV reader takes ownership of it.
+struct CountingReader<R> {
+    inner: R,
+    bytes_read: Arc<AtomicU64>,
+}
+
+impl<R: Read> CountingReader<R> {
+    fn new(inner: R) -> (Self, Arc<AtomicU64>) {
+        let bytes_read = Arc::new(AtomicU64::new(0));
+        let reader = Self {
+            inner,
+            bytes_read: bytes_read.clone(),
+        };
+        (reader, bytes_read)
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.bytes_read.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+fn csv_reader<R: Read>(reader: R, options: &CsvImportOptions) -> csv::Reader<R> {
+    csv::ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .has_headers(options.has_header)
+        // Rows with a wrong number of fields are reported with the other malformed rows
+        .flexible(true)
+        .from_reader(reader)
+}
+
+fn read_header<R: Read>(
+    reader: &mut csv::Reader<R>,
+    options: &CsvImportOptions,
+) -> ILResult<Option<Vec<String>>> {
+    if !options.has_header {
+        return Ok(None);
+    }
+    let header = reader
+        .headers()
+        .map_err(|e| ILError::InvalidInput(format!("Failed to read CSV header: {e}")))?;
+    Ok(Some(header.iter().map(|name| name.to_string()).collect()))
+}
+
+async fn import_records(
+    table: &Table,
+    header: Option<&[String]>,
+    records: impl Iterator<Item = csv::Result<StringRecord>>,
+    options: &CsvImportOptions,
+) -> ILResult<CsvImportReport> {
+    if options.commit_rows == Some(0) {
+        return Err(ILError::InvalidInput(
+            "CSV import commit rows must be greater than 0".to_string(),
+        ));
+    }
+    let parse_batch_size = options.commit_rows.map_or(PARSE_BATCH_SIZE, |commit_rows| {
+        commit_rows.min(PARSE_BATCH_SIZE)
+    });
+    let (num_fields, columns) = csv_columns(table, header)?;
+    let mut parser = BatchParser::new(num_fields, &columns, &options.null_token);
+    let mut report = CsvImportReport::default();
+    let mut batches = Vec::new();
+
+    for record in records {
+        let row_error = match record {
+            Ok(record) => parser.push(&record).err(),
+            Err(e) if e.is_io_error() => {
+                return Err(ILError::InvalidInput(format!("Failed to read CSV: {e}")));
+            }
+            Err(e) => Some(CsvRowError {
+                line: e.position().map(|position| position.line()).unwrap_or(0),
+                column: None,
+                message: e.to_string(),
+            }),
+        };
+        if let Some(row_error) = row_error {
+            // Rows parsed before are checked first so that the import fails with the first
+            // malformed row
+            batches.push(parser.finish(&mut report, options)?);
+            malformed_row(&mut report, options, row_error)?;
+        }
+        if parser.len() == parse_batch_size {
+            batches.push(parser.finish(&mut report, options)?);
+        }
+        if let Some(commit_rows) = options.commit_rows
+            && batches.iter().map(|batch| batch.num_rows()).sum::<usize>() >= commit_rows
+        {
+            insert_batches(table, &parser.schema, &mut batches, &mut report).await?;
+        }
+    }
+    batches.push(parser.finish(&mut report, options)?);
+    report.skipped_rows.sort_by_key(|row_error| row_error.line);
+    insert_batches(table, &parser.schema, &mut batches, &mut report).await?;
+    Ok(report)
+}
+
+/// Inserts the parsed batches in a single commit and clears them.
+async fn insert_batches(
+    table: &Table,
+    schema: &SchemaRef,
+    batches: &mut Vec<RecordBatch>,
+    report: &mut CsvImportReport,
+) -> ILResult<()> {
+    let batch = concat_batches(schema, batches.iter())?;
+    batches.clear();
+    if batch.num_rows() > 0 {
+        table.insert(&batch).await?;
+    }
+    report.imported_rows += batch.num_rows();
+    Ok(())
+}
+
+fn malformed_row(
+    report: &mut CsvImportReport,
+    options: &CsvImportOptions,
+    row_error: CsvRowError,
+) -> ILResult<()> {
+    match options.on_malformed_row {
+        OnMalformedRow::Fail => Err(ILError::InvalidInput(format!(
+            "Malformed CSV row at {row_error}"
+        ))),
+        OnMalformedRow::Skip => {
+            report.skipped_row_count += 1;
+            match &options.rejects {
+                Some(rejects) => rejects.send(row_error).map_err(|e| {
+                    ILError::InvalidInput(format!(
+                        "Failed to send malformed CSV row to rejects: {e}"
+                    ))
+                })?,
+                None => report.skipped_rows.push(row_error),
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Number of fields of the CSV rows, and the table columns they are read into in table order
+/// along with the index of their field.
+fn csv_columns(
+    table: &Table,
+    header: Option<&[String]>,
+) -> ILResult<(usize, Vec<(usize, FieldRef)>)> {
+    let table_fields = table
+        .schema
+        .fields()
+        .iter()
+        .filter(|field| field.name() != INTERNAL_ROW_ID_FIELD_NAME)
+        .cloned()
+        .collect::<Vec<_>>();
+    let Some(header) = header else {
+        return Ok((
+            table_fields.len(),
+            table_fields.into_iter().enumerate().collect(),
+        ));
+    };
+
+    let mut seen = HashSet::new();
+    for name in header {
+        if !seen.insert(name.as_str()) {
+            return Err(ILError::InvalidInput(format!(
+                "Duplicated CSV column {name}"
+            )));
+        }
+        if !table_fields.iter().any(|field| field.name() == name) {
+            return Err(ILError::InvalidInput(format!(
+                "CSV column {name} not found in table {}",
+                table.table_name
+            )));
+        }
+    }
+    let mut columns = Vec::new();
+    for field in table_fields {
+        match header.iter().position(|name| name == field.name()) {
+            Some(index) => columns.push((index, field)),
+            None if field.is_nullable() => {}
+            None => {
+                return Err(ILError::InvalidInput(format!(
+                    "CSV has no column for non-nullable column {} of table {}",
+                    field.name(),
+                    table.table_name
+                )));
+            }
+        }
+    }
+    Ok((header.len(), columns))
+}
+
+/// Buffers the fields of parsed rows and converts them to the column types in batches.
+struct BatchParser<'a> {
+    num_fields: usize,
+    columns: &'a [(usize, FieldRef)],
+    null_token: &'a str,
+    schema: SchemaRef,
+    lines: Vec<u64>,
+    values: Vec<Vec<Option<String>>>,
+}
+
+impl<'a> BatchParser<'a> {
+    fn new(num_fields: usize, columns: &'a [(usize, FieldRef)], null_token: &'a str) -> Self {
+        let schema = Arc::new(Schema::new(
+            columns
+                .iter()
+                .map(|(_, field)| field.clone())
+                .collect::<Vec<_>>(),
+        ));
+        Self {
+            num_fields,
+            columns,
+            null_token,
+            schema,
+            lines: Vec::new(),
+            values: vec![Vec::new(); columns.len()],
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    fn push(&mut self, record: &StringRecord) -> Result<(), CsvRowError> {
+        let line = record
+            .position()
+            .map(|position| position.line())
+            .unwrap_or(0);
+        if record.len() != self.num_fields {
+            return Err(CsvRowError {
+                line,
+                column: None,
+                message: format!(
+                    "expected {} fields, found {}",
+                    self.num_fields,
+                    record.len()
+                ),
+            });
+        }
+        for ((index, _), values) in self.columns.iter().zip(self.values.iter_mut()) {
+            let value = &record[*index];
+            values.push((value != self.null_token).then(|| value.to_string()));
+        }
+        self.lines.push(line);
+        Ok(())
+    }
+
+    /// Converts the buffered rows into a batch, leaving out the malformed ones.
+    fn finish(
+        &mut self,
+        report: &mut CsvImportReport,
+        options: &CsvImportOptions,
+    ) -> ILResult<RecordBatch> {
+        let lines = std::mem::take(&mut self.lines);
+        let mut valid = vec![true; lines.len()];
+        let mut row_errors = Vec::new();
+        let cast_options = CastOptions {
+            safe: true,
+            ..Default::default()
+        };
+        let mut arrays: Vec<ArrayRef> = Vec::with_capacity(self.columns.len());
+        for ((_, field), values) in self.columns.iter().zip(self.values.iter_mut()) {
+            let strings = StringArray::from(std::mem::take(values));
+            // Values that do not parse are cast to nulls
+            let array = match &options.timestamp_format {
+                Some(format) if is_temporal(field.data_type()) => {
+                    parse_temporal(&strings, field.data_type(), format)?
+                }
+                _ => cast_with_options(&strings, field.data_type(), &cast_options)?,
+            };
+            for row in 0..lines.len() {
+                if !valid[row] {
+                    continue;
+                }
+                let unparsed = strings.is_valid(row) && array.is_null(row);
+                let message = if unparsed && !(options.lossy_casts && field.is_nullable()) {
+                    format!(
+                        "invalid {} value {:?}",
+                        field.data_type(),
+                        strings.value(row)
+                    )
+                } else if strings.is_null(row) && !field.is_nullable() {
+                    "null value in non-nullable column".to_string()
+                } else {
+                    continue;
+                };
+                valid[row] = false;
+                row_errors.push(CsvRowError {
+                    line: lines[row],
+                    column: Some(field.name().clone()),
+                    message,
+                });
+            }
+            arrays.push(array);
+        }
+
+        let mut num_rows = lines.len();
+        if !row_errors.is_empty() {
+            row_errors.sort_by_key(|row_error| row_error.line);
+            for row_error in row_errors {
+                malformed_row(report, options, row_error)?;
+            }
+            // Malformed rows are left out before building the batch, their nulls would not
+            // pass the non-nullable fields
+            let predicate = BooleanArray::from(valid);
+            arrays = arrays
+                .iter()
+                .map(|array| filter(array, &predicate))
+                .collect::<Result<Vec<_>, _>>()?;
+            num_rows = predicate.true_count();
+        }
+        Ok(RecordBatch::try_new_with_options(
+            self.schema.clone(),
+            arrays,
+            &RecordBatchOptions::new().with_row_count(Some(num_rows)),
+        )?)
+    }
+}
+
+fn is_temporal(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Timestamp(_, _) | DataType::Date32 | DataType::Date64
+    )
+}
+
+/// Parses the strings as values of the timestamp or date type in the `strftime` format, values
+/// that do not parse are nulls.
+fn parse_temporal(strings: &StringArray, data_type: &DataType, format: &str) -> ILResult<ArrayRef> {
+    let parse_datetime = |value: &str| {
+        DateTime::parse_from_str(value, format)
+            .map(|datetime| datetime.naive_utc())
+            .or_else(|_| NaiveDateTime::parse_from_str(value, format))
+            .ok()
+            .map(|datetime| datetime.and_utc())
+    };
+    let values = strings
+        .iter()
+        .map(|value| {
+            let value = value?;
+            match data_type {
+                DataType::Timestamp(unit, _) => {
+                    let datetime = parse_datetime(value)?;
+                    match unit {
+                        TimeUnit::Second => Some(datetime.timestamp()),
+                        TimeUnit::Millisecond => Some(datetime.timestamp_millis()),
+                        TimeUnit::Microsecond => Some(datetime.timestamp_micros()),
+                        TimeUnit::Nanosecond => datetime.timestamp_nanos_opt(),
+                    }
+                }
+                _ => {
+                    let date = NaiveDate::parse_from_str(value, format)
+                        .ok()
+                        .or_else(|| parse_datetime(value).map(|datetime| datetime.date_naive()))?;
+                    let datetime = date.and_hms_opt(0, 0, 0)?.and_utc();
+                    match data_type {
+                        DataType::Date32 => Some(datetime.timestamp().div_euclid(86_400)),
+                        _ => Some(datetime.timestamp_millis()),
+                    }
+                }
+            }
+        })
+        .collect::<Int64Array>();
+    // Int32 first as dates of days can not be cast from Int64
+    let values = match data_type {
+        DataType::Date32 => cast(&values, &DataType::Int32)?,
+        _ => Arc::new(values),
+    };
+    Ok(cast(&values, data_type)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{Array, StringArray};
+    use arrow::datatypes::{DataType, TimeUnit};
+    use arrow::util::display::array_value_to_string;
+
+    use crate::import::parse_temporal;
+
+    #[test]
+    fn test_parse_temporal() {
+        let strings = StringArray::from(vec![
+            Some("02/01/2024 10:30:00"),
+            Some("02/01/2024 10:30:00 +0200"),
+            Some("2024-01-02"),
+            None,
+        ]);
+        let timestamps = parse_temporal(
+            &strings,
+            &DataType::Timestamp(TimeUnit::Millisecond, None),
+            "%d/%m/%Y %H:%M:%S",
+        )
+        .unwrap();
+        assert_eq!(
+            array_value_to_string(&timestamps, 0).unwrap(),
+            "2024-01-02T10:30:00"
+        );
+        assert!(timestamps.is_null(1));
+        assert!(timestamps.is_null(2));
+        assert!(timestamps.is_null(3));
+
+        // offsets are converted to UTC
+        let timestamps = parse_temporal(
+            &strings,
+            &DataType::Timestamp(TimeUnit::Second, None),
+            "%d/%m/%Y %H:%M:%S %z",
+        )
+        .unwrap();
+        assert_eq!(
+            array_value_to_string(&timestamps, 1).unwrap(),
+            "2024-01-02T08:30:00"
+        );
+
+        let dates = parse_temporal(&strings, &DataType::Date32, "%d/%m/%Y %H:%M:%S").unwrap();
+        assert_eq!(array_value_to_string(&dates, 0).unwrap(), "2024-01-02");
+        assert!(dates.is_null(2));
+        let dates = parse_temporal(&strings, &DataType::Date64, "%Y-%m-%d").unwrap();
+        assert_eq!(
+            array_value_to_string(&dates, 2).unwrap(),
+            "2024-01-02T00:00:00"
+        );
+    }
+}
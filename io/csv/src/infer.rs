@@ -0,0 +1,65 @@
+use arrow::datatypes::{DataType, Field, Schema};
+use csv::StringRecord;
+
+/// Infers the schema of a table from sampled rows. Columns are named after the header, or
+/// `column_1`, `column_2`, ... without one. A column is `Int64` if all its values are integers,
+/// `Float64` if they are numbers, `Boolean` if they are `true` or `false` in any case and
+/// `Utf8` otherwise. All columns are nullable as rows after the sample may hold nulls.
+pub(crate) fn infer_schema(
+    header: Option<&[String]>,
+    sample: &[StringRecord],
+    null_token: &str,
+) -> Schema {
+    let num_columns = match header {
+        Some(header) => header.len(),
+        None => sample.iter().map(|record| record.len()).max().unwrap_or(0),
+    };
+    let mut types: Vec<Option<DataType>> = vec![None; num_columns];
+    for record in sample {
+        for (value, data_type) in record.iter().zip(types.iter_mut()) {
+            if value == null_token {
+                continue;
+            }
+            let value_type = value_type(value);
+            *data_type = Some(match data_type.take() {
+                None => value_type,
+                Some(data_type) => merge_types(data_type, value_type),
+            });
+        }
+    }
+
+    let fields = types
+        .into_iter()
+        .enumerate()
+        .map(|(i, data_type)| {
+            let name = match header {
+                Some(header) => header[i].clone(),
+                None => format!("column_{}", i + 1),
+            };
+            Field::new(name, data_type.unwrap_or(DataType::Utf8), true)
+        })
+        .collect::<Vec<_>>();
+    Schema::new(fields)
+}
+
+fn value_type(value: &str) -> DataType {
+    if value.parse::<i64>().is_ok() {
+        DataType::Int64
+    } else if value.parse::<f64>().is_ok() {
+        DataType::Float64
+    } else if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false") {
+        DataType::Boolean
+    } else {
+        DataType::Utf8
+    }
+}
+
+fn merge_types(left: DataType, right: DataType) -> DataType {
+    match (left, right) {
+        (left, right) if left == right => left,
+        (DataType::Int64, DataType::Float64) | (DataType::Float64, DataType::Int64) => {
+            DataType::Float64
+        }
+        _ => DataType::Utf8,
+    }
+}
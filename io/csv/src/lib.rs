@@ -0,0 +1,4 @@
+mod import;
+mod infer;
+
+pub use import::*;
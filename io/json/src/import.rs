@@ -0,0 +1,453 @@
+use std::io::BufRead;
+use std::sync::Arc;
+use std::sync::mpsc::Sender;
+
+use arrow::array::{
+    Array, ArrayRef, BooleanArray, Int64Array, RecordBatch, RecordBatchOptions, StringArray,
+    new_empty_array, new_null_array,
+};
+use arrow::compute::{CastOptions, cast, cast_with_options, concat, concat_batches, filter};
+use arrow::datatypes::{DataType, FieldRef, Schema, SchemaRef, TimeUnit};
+use arrow::json::ReaderBuilder;
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use indexlake::catalog::INTERNAL_ROW_ID_FIELD_NAME;
+use indexlake::table::Table;
+use indexlake::{ILError, ILResult};
+use serde_json::{Map, Value};
+
+/// Rows converted to the column types at once.
+const PARSE_BATCH_SIZE: usize = 8192;
+
+#[derive(Debug, Clone, Default, derive_with::With)]
+pub struct NdjsonImportOptions {
+    /// String values equal to the token are null. JSON nulls and missing keys are null in any
+    /// case.
+    pub null_token: Option<String>,
+    pub on_malformed_row: OnMalformedRow,
+    /// Format of the string values of timestamp and date columns, in the syntax of chrono's
+    /// `strftime`, e.g. `%d/%m/%Y %H:%M:%S`. Timestamps without an offset are taken as UTC.
+    /// Values are parsed as RFC 3339 and similar formats if `None`.
+    pub timestamp_format: Option<String>,
+    /// Reads values that do not convert to the type of their column as nulls instead of making
+    /// the row malformed. Such rows are still malformed if the column is not nullable.
+    pub lossy_casts: bool,
+    /// Rows inserted per commit, all rows are inserted in a single commit if `None`. Rows are
+    /// then parsed and inserted a chunk at a time, holding no more than a chunk in memory, and
+    /// the chunks inserted stay in the table if the import fails on a later row.
+    pub commit_rows: Option<usize>,
+    /// Receives the malformed rows skipped with [`OnMalformedRow::Skip`] instead of
+    /// [`NdjsonImportReport::skipped_rows`], so that loads with many of them do not hold them
+    /// in memory.
+    pub rejects: Option<Sender<JsonRowError>>,
+}
+
+/// What to do with rows that can not be imported: lines that are not JSON objects, keys that
+/// are not columns of the table, values that do not convert to the type of their column and
+/// nulls in non-nullable columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnMalformedRow {
+    /// Fails the import with the first malformed row.
+    #[default]
+    Fail,
+    /// Imports the other rows and lists the malformed ones in
+    /// [`NdjsonImportReport::skipped_rows`].
+    Skip,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonRowError {
+    /// Line of the row, starting from 1.
+    pub line: u64,
+    /// Name of the column holding the invalid value, `None` for errors of the whole row.
+    pub column: Option<String>,
+    pub message: String,
+}
+
+impl std::fmt::Display for JsonRowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.column {
+            Some(column) => write!(f, "line {}, column {column}: {}", self.line, self.message),
+            None => write!(f, "line {}: {}", self.line, self.message),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NdjsonImportReport {
+    pub imported_rows: usize,
+    /// Malformed rows skipped with [`OnMalformedRow::Skip`], ordered by line. Empty when they
+    /// are sent to [`NdjsonImportOptions::rejects`].
+    pub skipped_rows: Vec<JsonRowError>,
+    /// Number of malformed rows skipped, wherever they were reported.
+    pub skipped_row_count: usize,
+    /// Bytes read from the reader.
+    pub bytes_read: u64,
+}
+
+/// Imports the rows read from `reader` into `table`, one JSON object per line with a key per
+/// column. Blank lines are skipped. Scalar values are converted to the types of the table
+/// columns like CSV fields, numbers and booleans by their JSON text, while struct and list
+/// columns take JSON objects and arrays. All rows are parsed before the valid ones are
+/// inserted in a single commit, nothing is inserted if the import fails, unless
+/// [`NdjsonImportOptions::commit_rows`] splits the import into several commits.
+pub async fn import_ndjson<R: BufRead>(
+    table: &Table,
+    mut reader: R,
+    options: &NdjsonImportOptions,
+) -> ILResult<NdjsonImportReport> {
+    if options.commit_rows == Some(0) {
+        return Err(ILError::InvalidInput(
+            "NDJSON import commit rows must be greater than 0".to_string(),
+        ));
+    }
+    let parse_batch_size = options.commit_rows.map_or(PARSE_BATCH_SIZE, |commit_rows| {
+        commit_rows.min(PARSE_BATCH_SIZE)
+    });
+    let columns = table
+        .schema
+        .fields()
+        .iter()
+        .filter(|field| field.name() != INTERNAL_ROW_ID_FIELD_NAME)
+        .cloned()
+        .collect::<Vec<_>>();
+    let mut parser = BatchParser::new(&columns, options);
+    let mut report = NdjsonImportReport::default();
+    let mut batches = Vec::new();
+
+    let mut line = 0;
+    let mut bytes = Vec::new();
+    loop {
+        bytes.clear();
+        let n = reader
+            .read_until(b'\n', &mut bytes)
+            .map_err(|e| ILError::InvalidInput(format!("Failed to read NDJSON: {e}")))?;
+        if n == 0 {
+            break;
+        }
+        report.bytes_read += n as u64;
+        line += 1;
+        if bytes.iter().all(|byte| byte.is_ascii_whitespace()) {
+            continue;
+        }
+
+        if let Err(row_error) = parser.push(line, &bytes) {
+            // Rows parsed before are checked first so that the import fails with the first
+            // malformed row
+            batches.push(parser.finish(&mut report)?);
+            malformed_row(&mut report, options, row_error)?;
+        }
+        if parser.len() == parse_batch_size {
+            batches.push(parser.finish(&mut report)?);
+        }
+        if let Some(commit_rows) = options.commit_rows
+            && batches.iter().map(|batch| batch.num_rows()).sum::<usize>() >= commit_rows
+        {
+            insert_batches(table, &parser.schema, &mut batches, &mut report).await?;
+        }
+    }
+    batches.push(parser.finish(&mut report)?);
+    report.skipped_rows.sort_by_key(|row_error| row_error.line);
+    insert_batches(table, &parser.schema, &mut batches, &mut report).await?;
+    Ok(report)
+}
+
+/// Inserts the parsed batches in a single commit and clears them.
+async fn insert_batches(
+    table: &Table,
+    schema: &SchemaRef,
+    batches: &mut Vec<RecordBatch>,
+    report: &mut NdjsonImportReport,
+) -> ILResult<()> {
+    let batch = concat_batches(schema, batches.iter())?;
+    batches.clear();
+    if batch.num_rows() > 0 {
+        table.insert(&batch).await?;
+    }
+    report.imported_rows += batch.num_rows();
+    Ok(())
+}
+
+fn malformed_row(
+    report: &mut NdjsonImportReport,
+    options: &NdjsonImportOptions,
+    row_error: JsonRowError,
+) -> ILResult<()> {
+    match options.on_malformed_row {
+        OnMalformedRow::Fail => Err(ILError::InvalidInput(format!(
+            "Malformed NDJSON row at {row_error}"
+        ))),
+        OnMalformedRow::Skip => {
+            report.skipped_row_count += 1;
+            match &options.rejects {
+                Some(rejects) => rejects.send(row_error).map_err(|e| {
+                    ILError::InvalidInput(format!(
+                        "Failed to send malformed NDJSON row to rejects: {e}"
+                    ))
+                })?,
+                None => report.skipped_rows.push(row_error),
+            }
+            Ok(())
+        }
+    }
+}
+
+fn is_nested(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Struct(_) | DataType::List(_) | DataType::LargeList(_)
+    )
+}
+
+/// Values of a column buffered until the rows are converted. Scalar values are kept as the
+/// strings they are cast from, nested values are decoded right away as arrays of one row.
+enum ColumnValues {
+    Scalar(Vec<Option<String>>),
+    Nested(Vec<ArrayRef>),
+}
+
+/// Value of a row for [`ColumnValues`].
+enum ColumnValue {
+    Scalar(Option<String>),
+    Nested(ArrayRef),
+}
+
+/// Buffers the values of parsed rows and converts them to the column types in batches.
+struct BatchParser<'a> {
+    columns: &'a [FieldRef],
+    options: &'a NdjsonImportOptions,
+    schema: SchemaRef,
+    lines: Vec<u64>,
+    values: Vec<ColumnValues>,
+}
+
+impl<'a> BatchParser<'a> {
+    fn new(columns: &'a [FieldRef], options: &'a NdjsonImportOptions) -> Self {
+        let values = columns
+            .iter()
+            .map(|field| match is_nested(field.data_type()) {
+                true => ColumnValues::Nested(Vec::new()),
+                false => ColumnValues::Scalar(Vec::new()),
+            })
+            .collect();
+        Self {
+            columns,
+            options,
+            schema: Arc::new(Schema::new(columns.to_vec())),
+            lines: Vec::new(),
+            values,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Parses the line and buffers its values, buffering nothing if the row is malformed.
+    fn push(&mut self, line: u64, bytes: &[u8]) -> Result<(), JsonRowError> {
+        let row_error = |column: Option<&str>, message: String| JsonRowError {
+            line,
+            column: column.map(|column| column.to_string()),
+            message,
+        };
+        let mut object = serde_json::from_slice::<Map<String, Value>>(bytes)
+            .map_err(|e| row_error(None, format!("invalid JSON object: {e}")))?;
+        if let Some(key) = object
+            .keys()
+            .find(|key| !self.columns.iter().any(|field| field.name() == *key))
+        {
+            return Err(row_error(None, format!("unknown column {key}")));
+        }
+
+        let mut row = Vec::with_capacity(self.columns.len());
+        for field in self.columns {
+            let value = object.remove(field.name()).unwrap_or(Value::Null);
+            let column_error = |message: String| row_error(Some(field.name()), message);
+            if is_nested(field.data_type()) {
+                if value.is_null() {
+                    if !field.is_nullable() {
+                        return Err(column_error(
+                            "null value in non-nullable column".to_string(),
+                        ));
+                    }
+                    row.push(ColumnValue::Nested(new_null_array(field.data_type(), 1)));
+                } else {
+                    let array = decode_nested(field, value).map_err(|e| {
+                        column_error(format!("invalid {} value: {e}", field.data_type()))
+                    })?;
+                    row.push(ColumnValue::Nested(array));
+                }
+                continue;
+            }
+            let string = match value {
+                Value::Null => None,
+                Value::String(string) if self.options.null_token.as_ref() == Some(&string) => None,
+                Value::String(string) => Some(string),
+                Value::Bool(_) | Value::Number(_) => Some(value.to_string()),
+                Value::Array(_) | Value::Object(_) => {
+                    return Err(column_error(format!(
+                        "invalid {} value {value}",
+                        field.data_type()
+                    )));
+                }
+            };
+            row.push(ColumnValue::Scalar(string));
+        }
+
+        for (value, values) in row.into_iter().zip(self.values.iter_mut()) {
+            match (value, values) {
+                (ColumnValue::Scalar(string), ColumnValues::Scalar(values)) => values.push(string),
+                (ColumnValue::Nested(array), ColumnValues::Nested(values)) => values.push(array),
+                _ => unreachable!("values are parsed by the kind of their column"),
+            }
+        }
+        self.lines.push(line);
+        Ok(())
+    }
+
+    /// Converts the buffered rows into a batch, leaving out the malformed ones.
+    fn finish(&mut self, report: &mut NdjsonImportReport) -> ILResult<RecordBatch> {
+        let lines = std::mem::take(&mut self.lines);
+        let mut valid = vec![true; lines.len()];
+        let mut row_errors = Vec::new();
+        let cast_options = CastOptions {
+            safe: true,
+            ..Default::default()
+        };
+        let mut arrays: Vec<ArrayRef> = Vec::with_capacity(self.columns.len());
+        for (field, values) in self.columns.iter().zip(self.values.iter_mut()) {
+            let strings = match values {
+                ColumnValues::Nested(values) => {
+                    let values = std::mem::take(values);
+                    arrays.push(match values.is_empty() {
+                        true => new_empty_array(field.data_type()),
+                        false => concat(
+                            &values
+                                .iter()
+                                .map(|value| value.as_ref())
+                                .collect::<Vec<_>>(),
+                        )?,
+                    });
+                    continue;
+                }
+                ColumnValues::Scalar(values) => StringArray::from(std::mem::take(values)),
+            };
+            // Values that do not convert are cast to nulls
+            let array = match &self.options.timestamp_format {
+                Some(format) if is_temporal(field.data_type()) => {
+                    parse_temporal(&strings, field.data_type(), format)?
+                }
+                _ => cast_with_options(&strings, field.data_type(), &cast_options)?,
+            };
+            for row in 0..lines.len() {
+                if !valid[row] {
+                    continue;
+                }
+                let unparsed = strings.is_valid(row) && array.is_null(row);
+                let message = if unparsed && !(self.options.lossy_casts && field.is_nullable()) {
+                    format!(
+                        "invalid {} value {:?}",
+                        field.data_type(),
+                        strings.value(row)
+                    )
+                } else if array.is_null(row) && !field.is_nullable() {
+                    "null value in non-nullable column".to_string()
+                } else {
+                    continue;
+                };
+                valid[row] = false;
+                row_errors.push(JsonRowError {
+                    line: lines[row],
+                    column: Some(field.name().clone()),
+                    message,
+                });
+            }
+            arrays.push(array);
+        }
+
+        let mut num_rows = lines.len();
+        if !row_errors.is_empty() {
+            row_errors.sort_by_key(|row_error| row_error.line);
+            for row_error in row_errors {
+                malformed_row(report, self.options, row_error)?;
+            }
+            // Malformed rows are left out before building the batch, their nulls would not
+            // pass the non-nullable fields
+            let predicate = BooleanArray::from(valid);
+            arrays = arrays
+                .iter()
+                .map(|array| filter(array, &predicate))
+                .collect::<Result<Vec<_>, _>>()?;
+            num_rows = predicate.true_count();
+        }
+        Ok(RecordBatch::try_new_with_options(
+            self.schema.clone(),
+            arrays,
+            &RecordBatchOptions::new().with_row_count(Some(num_rows)),
+        )?)
+    }
+}
+
+/// Decodes a JSON object or array into an array of one row of the struct or list column.
+fn decode_nested(field: &FieldRef, value: Value) -> ILResult<ArrayRef> {
+    let schema = Arc::new(Schema::new(vec![field.clone()]));
+    let mut decoder = ReaderBuilder::new(schema).build_decoder()?;
+    let mut row = Map::new();
+    row.insert(field.name().clone(), value);
+    decoder.serialize(&[row])?;
+    let batch = decoder.flush()?.ok_or_else(|| {
+        ILError::InternalError(format!("No row decoded for column {}", field.name()))
+    })?;
+    Ok(batch.column(0).clone())
+}
+
+fn is_temporal(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Timestamp(_, _) | DataType::Date32 | DataType::Date64
+    )
+}
+
+/// Parses the strings as values of the timestamp or date type in the `strftime` format, values
+/// that do not parse are nulls.
+fn parse_temporal(strings: &StringArray, data_type: &DataType, format: &str) -> ILResult<ArrayRef> {
+    let parse_datetime = |value: &str| {
+        DateTime::parse_from_str(value, format)
+            .map(|datetime| datetime.naive_utc())
+            .or_else(|_| NaiveDateTime::parse_from_str(value, format))
+            .ok()
+            .map(|datetime| datetime.and_utc())
+    };
+    let values = strings
+        .iter()
+        .map(|value| {
+            let value = value?;
+            match data_type {
+                DataType::Timestamp(unit, _) => {
+                    let datetime = parse_datetime(value)?;
+                    match unit {
+                        TimeUnit::Second => Some(datetime.timestamp()),
+                        TimeUnit::Millisecond => Some(datetime.timestamp_millis()),
+                        TimeUnit::Microsecond => Some(datetime.timestamp_micros()),
+                        TimeUnit::Nanosecond => datetime.timestamp_nanos_opt(),
+                    }
+                }
+                _ => {
+                    let date = NaiveDate::parse_from_str(value, format)
+                        .ok()
+                        .or_else(|| parse_datetime(value).map(|datetime| datetime.date_naive()))?;
+                    let datetime = date.and_hms_opt(0, 0, 0)?.and_utc();
+                    match data_type {
+                        DataType::Date32 => Some(datetime.timestamp().div_euclid(86_400)),
+                        _ => Some(datetime.timestamp_millis()),
+                    }
+                }
+            }
+        })
+        .collect::<Int64Array>();
+    // Int32 first as dates of days can not be cast from Int64
+    let values = match data_type {
+        DataType::Date32 => cast(&values, &DataType::Int32)?,
+        _ => Arc::new(values),
+    };
+    Ok(cast(&values, data_type)?)
+}